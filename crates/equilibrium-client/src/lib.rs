@@ -0,0 +1,358 @@
+//! Pool decoding and pricing for off-chain Rust readers
+//!
+//! Geyser plugins and indexers see raw account bytes, with no `Context`, sysvars
+//! or RPC to lean on. `PoolAccount` decodes those bytes and `PoolMath` reproduces
+//! the program's quotes at a caller-supplied time, so nothing here reads the
+//! clock or makes a syscall. Neither links Anchor or the Solana runtime: quotes
+//! go through `equilibrium-math`, the same code the program prices with.
+
+use std::borrow::Cow;
+use std::io;
+
+use borsh::BorshDeserialize;
+use equilibrium_math::math::{calculate_pair_price, calculate_virtual_price, FeeCurve, FeeShape};
+use equilibrium_math::pricing::{PartnerOracle, PoolView};
+
+pub use equilibrium_math::pricing::{QuoteError, SwapQuote, WithdrawSplit};
+
+/// Account address as raw bytes; `Pubkey::new_from_array` turns it into a `Pubkey`
+pub type Address = [u8; 32];
+
+/// Anchor discriminator of `Pool` accounts, `sha256("account:Pool")[..8]`
+pub const POOL_DISCRIMINATOR: [u8; 8] = [241, 154, 109, 4, 17, 177, 109, 188];
+
+#[derive(BorshDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PoolType {
+    Seed,
+    Growth,
+}
+
+/// Depeg state of a single pool token
+#[derive(BorshDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum TokenStatus {
+    Normal,
+    StressedOver,
+    StressedUnder,
+    Frozen,
+}
+
+#[derive(BorshDeserialize, Clone, Copy)]
+enum FeeModel {
+    Linear,
+    Convex,
+}
+
+#[derive(BorshDeserialize, Clone, Copy)]
+enum FeeTier {
+    Stable,
+    Standard,
+    Volatile,
+}
+
+#[derive(BorshDeserialize, Clone, Copy)]
+struct FeeParams {
+    base_fee: u64,
+    max_fee: u64,
+    fee_slope: u64,
+    model: FeeModel,
+}
+
+/// Field-for-field mirror of the program's `Pool`, in account order
+///
+/// Borsh has no field names, so every field is kept even where nothing reads it.
+#[allow(dead_code)]
+#[derive(BorshDeserialize, Clone)]
+struct PoolData {
+    bump: u8,
+    pool_type: PoolType,
+    amm_config: Address,
+    token_mints: Vec<Address>,
+    token_accounts: Vec<Address>,
+    reserves: Vec<u64>,
+    lp_mint: Address,
+    target_weights: Vec<u64>,
+    amplification: u64,
+    total_fees: u64,
+    last_update: i64,
+    seed_pool: Option<Address>,
+    lifetime_volume_per_token: Vec<u128>,
+    lifetime_fees_per_token: Vec<u128>,
+    swap_count: u64,
+    last_d: u64,
+    d_slot: u64,
+    exit_fee_bps: u64,
+    rebalance_epoch: u64,
+    rebalance_volume_in_epoch: u64,
+    min_swap_amount: u64,
+    max_trade_bps: u64,
+    lbp_start_weights: Vec<u64>,
+    lbp_start_ts: i64,
+    lbp_end_ts: i64,
+    virtual_price: u128,
+    best_token_in: u8,
+    best_token_out: u8,
+    fee_tier: FeeTier,
+    fee_params: FeeParams,
+    token_status: Vec<TokenStatus>,
+    bootstrap_end_ts: i64,
+    max_deposit_per_wallet: u64,
+    protocol_fee_share_bps: u64,
+    protocol_fees_owed: Vec<u64>,
+    staged_withdraw_threshold_bps: u64,
+    staged_withdraw_delay: i64,
+    partner_oracle_price: u64,
+    partner_oracle_updated_at: i64,
+    oracle_band_bps: u64,
+    oracle_surcharge_bps: u64,
+    crank_tip_slot: u64,
+    crank_tips_in_slot: u64,
+    partner_oracle_updated_slot: u64,
+    paused: bool,
+    relayer: Address,
+    soulbound_lp: bool,
+    internal_lp_supply: u64,
+    last_update_slot: u64,
+    requires_credential: bool,
+    credential_mint: Address,
+    large_flow_threshold_bps: u64,
+    inherits_protocol_fee_share: bool,
+    price_band_bps: u64,
+    batch_window_slots: u64,
+    weights_ema: [u64; 3],
+    ema_lambda: u64,
+    queued_owed: [u64; 3],
+    ema_updated_slot: u64,
+}
+
+impl PoolData {
+    fn fee_curve(&self) -> FeeCurve {
+        let params = self.fee_params;
+        FeeCurve {
+            base_fee: params.base_fee,
+            max_fee: params.max_fee,
+            fee_slope: params.fee_slope,
+            shape: match params.model {
+                FeeModel::Linear => FeeShape::Linear,
+                FeeModel::Convex => FeeShape::Convex,
+            },
+        }
+    }
+}
+
+/// A decoded `Pool` account
+#[derive(Clone)]
+pub struct PoolAccount {
+    pool: PoolData,
+}
+
+impl PoolAccount {
+    /// Decode a pool from its account data, discriminator included
+    ///
+    /// Fails on data that is not a `Pool`, or is too short to be one.
+    pub fn deserialize(data: &[u8]) -> io::Result<Self> {
+        if data.get(..8) != Some(&POOL_DISCRIMINATOR[..]) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "account is not a Pool",
+            ));
+        }
+        // Pools are allocated for the most tokens, so the data can run past the fields
+        let mut data = &data[8..];
+        Ok(Self {
+            pool: PoolData::deserialize(&mut data)?,
+        })
+    }
+
+    /// Prices and quotes for this pool
+    pub fn math(&self) -> PoolMath<'_> {
+        PoolMath::new(self)
+    }
+
+    pub fn pool_type(&self) -> PoolType {
+        self.pool.pool_type
+    }
+
+    pub fn amm_config(&self) -> Address {
+        self.pool.amm_config
+    }
+
+    pub fn num_tokens(&self) -> usize {
+        self.pool.token_mints.len()
+    }
+
+    pub fn token_mints(&self) -> &[Address] {
+        &self.pool.token_mints
+    }
+
+    /// Pool vaults, indexed like `token_mints`
+    pub fn vaults(&self) -> &[Address] {
+        &self.pool.token_accounts
+    }
+
+    /// Index of `mint` in `token_mints`, if the pool trades it
+    pub fn token_index(&self, mint: &Address) -> Option<usize> {
+        self.pool.token_mints.iter().position(|m| m == mint)
+    }
+
+    /// Recorded reserves, indexed like `token_mints`
+    pub fn reserves(&self) -> &[u64] {
+        &self.pool.reserves
+    }
+
+    pub fn lp_mint(&self) -> Address {
+        self.pool.lp_mint
+    }
+
+    /// LP supply given the LP mint's `mint_supply`, which soulbound pools ignore
+    pub fn lp_supply(&self, mint_supply: u64) -> u64 {
+        if self.pool.soulbound_lp {
+            self.pool.internal_lp_supply
+        } else {
+            mint_supply
+        }
+    }
+
+    pub fn amplification(&self) -> u64 {
+        self.pool.amplification
+    }
+
+    /// The pool's dynamic fee curve
+    pub fn fee_curve(&self) -> FeeCurve {
+        self.pool.fee_curve()
+    }
+
+    /// Virtual price stored at the last state change (1e18 fixed point)
+    pub fn virtual_price(&self) -> u128 {
+        self.pool.virtual_price
+    }
+
+    pub fn swap_count(&self) -> u64 {
+        self.pool.swap_count
+    }
+
+    pub fn token_status(&self) -> &[TokenStatus] {
+        &self.pool.token_status
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.pool.paused
+    }
+
+    /// Unix time of the pool's last state change
+    pub fn last_update(&self) -> i64 {
+        self.pool.last_update
+    }
+}
+
+/// Read-only pricing against a pool, matching what its instructions would do
+///
+/// Quotes leave out what depends on other accounts: vote escrow fee discounts,
+/// the config's stale-oracle fee floor and the protocol's share of the fee.
+#[derive(Clone, Copy)]
+pub struct PoolMath<'a> {
+    pool: &'a PoolData,
+}
+
+impl<'a> PoolMath<'a> {
+    pub fn new(account: &'a PoolAccount) -> Self {
+        Self {
+            pool: &account.pool,
+        }
+    }
+
+    fn view(&self) -> PoolView<'a> {
+        let pool = self.pool;
+        PoolView {
+            reserves: &pool.reserves,
+            target_weights: &pool.target_weights,
+            lbp_start_weights: &pool.lbp_start_weights,
+            lbp_start_ts: pool.lbp_start_ts,
+            lbp_end_ts: pool.lbp_end_ts,
+            amplification: pool.amplification,
+            fee_curve: pool.fee_curve(),
+            ema_lambda: pool.ema_lambda,
+            weights_ema: &pool.weights_ema,
+            min_swap_amount: pool.min_swap_amount,
+            max_trade_bps: pool.max_trade_bps,
+            partner_oracle: (pool.pool_type == PoolType::Growth).then_some(PartnerOracle {
+                price: pool.partner_oracle_price,
+                updated_at: pool.partner_oracle_updated_at,
+                band_bps: pool.oracle_band_bps,
+                surcharge_bps: pool.oracle_surcharge_bps,
+            }),
+        }
+    }
+
+    /// Current token weights in basis points
+    pub fn weights(&self) -> Vec<u64> {
+        self.view().current_weights()
+    }
+
+    /// Target weights in effect at `now`, following any LBP schedule
+    pub fn target_weights(&self, now: i64) -> Vec<u64> {
+        self.target_weights_at(now).into_owned()
+    }
+
+    fn target_weights_at(&self, now: i64) -> Cow<'a, [u64]> {
+        self.view().target_weights_at(now)
+    }
+
+    /// Dynamic fee the next swap pays at `now`, in parts per 1000
+    pub fn dynamic_fee(&self, now: i64) -> u64 {
+        self.view().dynamic_fee(&self.target_weights_at(now))
+    }
+
+    /// Marginal price of token `token_in_idx` in units of token `token_out_idx`,
+    /// scaled by `ORACLE_PRICE_PRECISION`
+    pub fn price(&self, token_in_idx: usize, token_out_idx: usize) -> Option<u64> {
+        calculate_pair_price(
+            &self.pool.reserves,
+            token_in_idx,
+            token_out_idx,
+            self.pool.amplification,
+        )
+    }
+
+    /// Quote selling `amount_in` of token `token_in_idx` for token `token_out_idx` at `now`
+    ///
+    /// Fails where `swap` would: frozen tokens, dust, or trades past the size cap.
+    pub fn quote_swap(
+        &self,
+        token_in_idx: usize,
+        token_out_idx: usize,
+        amount_in: u64,
+        now: i64,
+    ) -> Result<SwapQuote, QuoteError> {
+        let status = &self.pool.token_status;
+        if [token_in_idx, token_out_idx]
+            .iter()
+            .any(|&idx| status.get(idx) == Some(&TokenStatus::Frozen))
+        {
+            return Err(QuoteError::TokenFrozen);
+        }
+        self.view()
+            .quote_swap(token_in_idx, token_out_idx, amount_in, 0, 0, now)
+    }
+
+    /// Tokens a proportional withdrawal of `lp_amount` pays out of `lp_supply` at
+    /// `now`, after the pool's exit fee
+    pub fn quote_withdraw(
+        &self,
+        lp_amount: u64,
+        lp_supply: u64,
+        now: i64,
+    ) -> Result<Vec<u64>, QuoteError> {
+        self.view().withdrawal_amounts(
+            &self.target_weights_at(now),
+            lp_amount,
+            lp_supply,
+            self.pool.exit_fee_bps,
+            WithdrawSplit::Proportional,
+        )
+    }
+
+    /// Virtual price of one LP token at `lp_supply` (1e18 fixed point)
+    pub fn virtual_price(&self, lp_supply: u64) -> Option<u128> {
+        calculate_virtual_price(self.pool.last_d, lp_supply)
+    }
+}
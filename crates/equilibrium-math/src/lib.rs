@@ -0,0 +1,9 @@
+//! Pool math for Equilibrium-Core, free of Anchor and the Solana runtime
+//!
+//! The program re-exports these modules as `state::math` and `state::rounding`
+//! and prices through `pricing`, so off-chain readers linking only this crate
+//! quote exactly what the program charges.
+
+pub mod math;
+pub mod pricing;
+pub mod rounding;
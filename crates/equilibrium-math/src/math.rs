@@ -0,0 +1,1309 @@
+//! Mathematical models for the Equilibrium AMM
+
+// Remove unused import
+use std::cmp;
+
+use crate::rounding;
+
+// Constants for fee calculation
+pub const BASE_FEE: u64 = 1; // 0.1% = 1/1000
+pub const MAX_FEE: u64 = 5; // 0.5% = 5/1000
+pub const FEE_MULTIPLIER: u64 = 1; // 0.1% = 1/1000 per unit of deviation
+pub const FEE_DENOMINATOR: u64 = 1000; // Fees are expressed as x/1000
+pub const MAX_SWAP_FEE: u64 = 100; // 10%, upper bound for any pool's fee curve
+
+// Weight deviation at which a convex fee curve adds exactly `fee_slope`
+pub const CONVEX_FEE_UNIT_BPS: u64 = 1000; // 10 percentage points
+
+// Amplification bounds; A = 0 divides by zero in the Newton solver and very
+// large A overflows its intermediate products
+pub const MIN_AMP: u64 = 1;
+pub const MAX_AMP: u64 = 10_000;
+
+// Bounds on either side of a Growth Pool's target weights, 20/80 to 80/20
+pub const MIN_GROWTH_WEIGHT_BPS: u64 = 2000; // 20%
+pub const MAX_GROWTH_WEIGHT_BPS: u64 = 8000; // 80%
+
+// Largest invariant drop a swap may show from Newton solver rounding alone
+pub const INVARIANT_TOLERANCE: u64 = 2;
+
+// Basis points denominator (10000 = 100%)
+pub const BPS_DENOMINATOR: u64 = 10000;
+
+// Weight deviation past which a token is flagged as stressed
+pub const STRESS_DEVIATION_BPS: u64 = 1000; // 10 percentage points
+
+// Largest share of swap fees the protocol may take from LPs
+pub const MAX_PROTOCOL_FEE_SHARE_BPS: u64 = 5000; // 50%
+
+// Fixed-point scale of partner oracle and pool spot prices, matching 8-decimal USD feeds
+pub const ORACLE_PRICE_PRECISION: u64 = 100_000_000; // 1e8
+
+// Cap on the surcharge for trades pushing a Growth pool away from its oracle price
+pub const MAX_ORACLE_SURCHARGE_BPS: u64 = 500; // 5%
+
+// Maximum withdrawal exit fee
+pub const MAX_EXIT_FEE_BPS: u64 = 100; // 1%
+
+// Cap on the front-end fee a swap may pay its integrator on top of the amount in
+pub const MAX_INTEGRATOR_FEE_BPS: u64 = 100; // 1%
+
+// Worst execution a keeper rebalance may accept against a 1:1 peg
+pub const MAX_REBALANCE_SLIPPAGE_BPS: u64 = 50; // 0.5%
+
+// Default cap on a single swap's share of the output reserve
+pub const DEFAULT_MAX_TRADE_BPS: u64 = 3000; // 30%
+
+// Constants for weight-aware deposit fees
+pub const DEPOSIT_FEE_DIVISOR: u64 = 100; // 1 bps per 1% of weight deviation
+pub const MAX_DEPOSIT_FEE_BPS: u64 = 50; // 0.5%, same cap as the swap fee
+pub const MAX_DEPOSIT_REBATE_BPS: u64 = 25; // Rebates stay below fees so LPs are never drained
+
+// Seconds in a 365-day year, used to annualize fee yield
+pub const SECONDS_PER_YEAR: u128 = 365 * 86_400;
+
+// Fixed-point scale for LP virtual price
+pub const VIRTUAL_PRICE_PRECISION: u128 = 1_000_000_000_000_000_000; // 1e18
+
+// Constants for liquidity concentration
+pub const MIN_PRICE: u64 = 995; // 0.995
+pub const MAX_PRICE: u64 = 1005; // 1.005
+pub const PRICE_DENOMINATOR: u64 = 1000; // Prices are expressed as x/1000
+
+/// Shape of a dynamic fee curve between its base and max fee
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum FeeShape {
+    /// Fee grows in proportion to weight deviation
+    Linear,
+    /// Fee grows with the square of weight deviation: cheap near target, steep off peg
+    Convex,
+}
+
+/// Dynamic swap fee curve, all values in parts per 1000
+///
+/// The program stores it on each pool as `FeeParams`.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct FeeCurve {
+    /// Fee charged at target weights
+    pub base_fee: u64,
+
+    /// Cap on the fee however far the pool drifts
+    pub max_fee: u64,
+
+    /// Fee added at 10 percentage points of total weight deviation
+    pub fee_slope: u64,
+
+    pub shape: FeeShape,
+}
+
+/// Distance of `current` weight from its `target`, in basis points
+///
+/// Unsigned on both sides of the target, so it never wraps or needs a signed cast.
+pub fn weight_deviation(current: u64, target: u64) -> u64 {
+    current.abs_diff(target)
+}
+
+/// Sum of every token's `weight_deviation`, saturating at `u64::MAX`
+pub fn total_weight_deviation(current_weights: &[u64], target_weights: &[u64]) -> u64 {
+    current_weights
+        .iter()
+        .zip(target_weights.iter())
+        .fold(0, |total: u64, (&current, &target)| {
+            total.saturating_add(weight_deviation(current, target))
+        })
+}
+
+/// Calculate dynamic swap fee based on weight deviations
+///
+/// Takes current_weights and target_weights (both in basis points where 10000 = 100%)
+/// # Returns
+/// * Fee in parts per 1000 (e.g., 1 = 0.1%)
+pub fn calculate_dynamic_fee(
+    current_weights: &[u64],
+    target_weights: &[u64],
+    fee_curve: &FeeCurve,
+) -> u64 {
+    let total_deviation = total_weight_deviation(current_weights, target_weights);
+    calculate_fee_for_deviation(total_deviation, fee_curve)
+}
+
+/// Fee on `fee_curve` for a total weight deviation in basis points
+///
+/// # Returns
+/// * Fee in parts per 1000, capped at `max_fee`
+pub fn calculate_fee_for_deviation(total_deviation: u64, fee_curve: &FeeCurve) -> u64 {
+    let variable_fee = match fee_curve.shape {
+        // base_fee + deviation * fee_slope, in whole percentage points
+        FeeShape::Linear => (total_deviation / 100).saturating_mul(fee_curve.fee_slope) / 10,
+        // base_fee + fee_slope * (deviation / 10 points)², kept in basis points so
+        // small imbalances round towards zero rather than to a full step
+        FeeShape::Convex => {
+            let scaled = (total_deviation as u128).pow(2) * fee_curve.fee_slope as u128
+                / (CONVEX_FEE_UNIT_BPS as u128).pow(2);
+            u64::try_from(scaled).unwrap_or(u64::MAX)
+        }
+    };
+    cmp::min(fee_curve.base_fee.saturating_add(variable_fee), fee_curve.max_fee)
+}
+
+/// StableSwap invariant calculator
+/// Based on the formula: An^n * sum(x_i) + D = An^n * D + D^(n+1) / (n^n * prod(x_i))
+/// Simplified for stablecoins near parity
+///
+/// # Arguments
+/// * `amounts` - Token amounts in the pool
+/// * `amplification` - Amplification coefficient (higher = closer to constant sum, lower = closer to constant product)
+///
+/// # Returns
+/// * The invariant D
+pub fn calculate_invariant(amounts: &[u64], amplification: u64) -> Option<u64> {
+    if amounts.is_empty() || amounts.contains(&0) {
+        return None;
+    }
+
+    // Work in u128: D^(n+1) terms overflow u64 long before realistic reserves do
+    let n = amounts.len() as u128;
+    let sum: u128 = amounts.iter().map(|&amount| amount as u128).sum();
+
+    // D cannot be less than the sum in the worst case (constant sum)
+    let mut d = sum;
+
+    // A * n^n
+    let ann = amplification as u128 * n.checked_pow(amounts.len() as u32)?;
+
+    // Newton's method to approximate D
+    for _ in 0..255 {
+        // D_P = D^(n+1) / (n^n * prod(x_i))
+        let mut d_p = d;
+        for &amount in amounts {
+            d_p = d_p.checked_mul(d)? / (amount as u128 * n);
+        }
+        let d_prev = d;
+
+        // Newton iteration: D = (A * n^n * sum + D_P * n) * D / ((A * n^n - 1) * D + (n + 1) * D_P)
+        let numerator = (ann * sum)
+            .checked_add(d_p.checked_mul(n)?)?
+            .checked_mul(d)?;
+        let denominator = (ann - 1)
+            .checked_mul(d)?
+            .checked_add((n + 1).checked_mul(d_p)?)?;
+        d = numerator / denominator;
+
+        // Check for convergence with precision of 1
+        if d.abs_diff(d_prev) <= 1 {
+            break;
+        }
+    }
+
+    u64::try_from(d).ok()
+}
+
+/// Whether `amplification` lies within `MIN_AMP..=MAX_AMP`
+pub fn is_valid_amplification(amplification: u64) -> bool {
+    (MIN_AMP..=MAX_AMP).contains(&amplification)
+}
+
+/// Whether `target_weights` sum to 100% with each side within
+/// `MIN_GROWTH_WEIGHT_BPS..=MAX_GROWTH_WEIGHT_BPS`
+pub fn is_valid_growth_weights(target_weights: &[u64; 2]) -> bool {
+    target_weights
+        .iter()
+        .all(|weight| (MIN_GROWTH_WEIGHT_BPS..=MAX_GROWTH_WEIGHT_BPS).contains(weight))
+        && target_weights.iter().sum::<u64>() == BPS_DENOMINATOR
+}
+
+/// D of the `after` reserves, if moving there from `before` kept the invariant within rounding
+///
+/// Fees accrue to the reserves, so a correctly priced swap never lowers D by more
+/// than `INVARIANT_TOLERANCE`. Reserves the solver cannot handle count as a violation.
+pub fn preserved_invariant(before: &[u64], after: &[u64], amplification: u64) -> Option<u64> {
+    let d_before = calculate_invariant(before, amplification)?;
+    let d_after = calculate_invariant(after, amplification)?;
+    (d_after + INVARIANT_TOLERANCE >= d_before).then_some(d_after)
+}
+
+/// Whether moving from `before` to `after` reserves kept the invariant within rounding
+pub fn is_invariant_preserved(before: &[u64], after: &[u64], amplification: u64) -> bool {
+    preserved_invariant(before, after, amplification).is_some()
+}
+
+/// Calculate output amount for a swap
+///
+/// # Arguments
+/// * `x_amount` - Input token amount
+/// * `x_reserve` - Input token reserve
+/// * `y_reserve` - Output token reserve  
+/// * `fee` - Fee in parts per 1000
+/// * `amplification` - Amplification coefficient
+///
+/// # Returns
+/// * Output amount after fees, rounded down
+pub fn calculate_output_amount(
+    x_amount: u64,
+    x_reserve: u64,
+    y_reserve: u64,
+    fee: u64,
+    amplification: u64,
+) -> Option<u64> {
+    if x_reserve == 0 || y_reserve == 0 {
+        return None;
+    }
+
+    // Calculate invariant before swap
+    let d = calculate_invariant(&[x_reserve, y_reserve], amplification)? as u128;
+
+    // Apply fee to input amount
+    let fee_amount = calculate_fee_amount(x_amount, fee);
+    let x_amount_after_fee = x_amount - fee_amount;
+
+    // New input reserve after swap
+    let new_x_reserve = x_reserve as u128 + x_amount_after_fee as u128;
+
+    // Find new_y_reserve such that invariant is preserved, for 2 tokens (n=2):
+    // y^2 + (b - D) * y = c, with b = x' + D / (A * n^n) and c = D^3 / (n^n * x' * A * n^n)
+    let ann = amplification as u128 * 4; // A * n^n for n=2
+    let c = d.checked_mul(d)? / (new_x_reserve * 2) * d / (ann * 2);
+    let b = new_x_reserve + d / ann;
+
+    // Newton's method on y, starting from D
+    let mut new_y_reserve = d;
+    for _ in 0..255 {
+        let y_prev = new_y_reserve;
+        new_y_reserve = new_y_reserve.checked_mul(new_y_reserve)?.checked_add(c)?
+            / (2 * new_y_reserve + b).checked_sub(d)?;
+        if new_y_reserve.abs_diff(y_prev) <= 1 {
+            break;
+        }
+    }
+
+    Some(rounding::swap_output(y_reserve, new_y_reserve))
+}
+
+/// Calculate the output of a swap between two tokens of a pool of any size
+///
+/// Pools of more than two tokens solve `calculate_y` for the output reserve against
+/// the full-pool D, holding the other reserves constant, so a Seed Pool prices each
+/// pair on the same curve its deposits and withdrawals use. Two-token pools fall
+/// back to the pairwise `calculate_output_amount`, which gives the same result.
+///
+/// # Arguments
+/// * `amount_in` - Input amount net of fees
+/// * `reserves` - Token reserves before the swap
+/// * `in_index` / `out_index` - Indices of the tokens sold and bought
+/// * `amplification` - Amplification coefficient
+///
+/// # Returns
+/// * Output amount, rounded down
+pub fn calculate_swap_output(
+    amount_in: u64,
+    reserves: &[u64],
+    in_index: usize,
+    out_index: usize,
+    amplification: u64,
+) -> Option<u64> {
+    if in_index == out_index || in_index.max(out_index) >= reserves.len() {
+        return None;
+    }
+    if reserves.len() == 2 {
+        return calculate_output_amount(
+            amount_in,
+            reserves[in_index],
+            reserves[out_index],
+            0,
+            amplification,
+        );
+    }
+
+    let d = calculate_invariant(reserves, amplification)?;
+    let mut new_reserves = reserves.to_vec();
+    new_reserves[in_index] = new_reserves[in_index].checked_add(amount_in)?;
+    let new_y_reserve = calculate_y(&new_reserves, out_index, d, amplification)?;
+
+    Some(rounding::swap_output(reserves[out_index], new_y_reserve))
+}
+
+/// Solve the invariant `d` for the reserve at `index`, holding every other reserve fixed
+///
+/// The n-token form of the quadratic `calculate_output_amount` solves for n=2:
+/// y^2 + (b - D) * y = c, with b = S' + D / (A * n^n) and c = D^(n+1) / (n^n * P' * A * n^n),
+/// where S' and P' are the sum and product of the other reserves.
+///
+/// # Returns
+/// * The reserve at `index`, or None if a reserve is empty or the solve overflows
+pub fn calculate_y(reserves: &[u64], index: usize, d: u64, amplification: u64) -> Option<u128> {
+    let n = reserves.len() as u128;
+    let d = d as u128;
+    let ann = amplification as u128 * n.checked_pow(reserves.len() as u32)?;
+
+    let mut c = d;
+    let mut sum = 0u128;
+    for (i, &reserve) in reserves.iter().enumerate() {
+        if i == index {
+            continue;
+        }
+        if reserve == 0 {
+            return None;
+        }
+        sum += reserve as u128;
+        c = c.checked_mul(d)? / (reserve as u128 * n);
+    }
+    c = c.checked_mul(d)? / (ann * n);
+    let b = sum + d / ann;
+
+    // Newton's method on y, starting from D
+    let mut y = d;
+    for _ in 0..255 {
+        let y_prev = y;
+        y = y.checked_mul(y)?.checked_add(c)? / (2 * y + b).checked_sub(d)?;
+        if y.abs_diff(y_prev) <= 1 {
+            break;
+        }
+    }
+
+    Some(y)
+}
+
+/// Calculate the output of selling `amounts_in` of several tokens for the one at `out_index`
+///
+/// All inputs land in the reserves before a single solve for the output, so the
+/// trade pays one curve's price impact instead of one per leg.
+///
+/// # Arguments
+/// * `amounts_in` - Input amounts net of fees, one per pool token; zero at `out_index`
+/// * `reserves` - Token reserves before the swap
+/// * `out_index` - Index of the token bought
+/// * `amplification` - Amplification coefficient
+///
+/// # Returns
+/// * Output amount, rounded down
+pub fn calculate_basket_output(
+    amounts_in: &[u64],
+    reserves: &[u64],
+    out_index: usize,
+    amplification: u64,
+) -> Option<u64> {
+    if amounts_in.len() != reserves.len() || amounts_in.get(out_index) != Some(&0) {
+        return None;
+    }
+
+    let d = calculate_invariant(reserves, amplification)?;
+    let mut new_reserves = reserves.to_vec();
+    for (reserve, &amount) in new_reserves.iter_mut().zip(amounts_in.iter()) {
+        *reserve = reserve.checked_add(amount)?;
+    }
+    let new_y_reserve = calculate_y(&new_reserves, out_index, d, amplification)?;
+
+    Some(rounding::swap_output(reserves[out_index], new_y_reserve))
+}
+
+/// Calculate the marginal StableSwap price of x in units of y for a 2-token pool
+///
+/// Differentiates the invariant F(x, y) = A·n^n·(x + y) − D³ / (n^n·x·y) = const,
+/// giving dy/dx = (A·n^n + D³/(4x²y)) / (A·n^n + D³/(4xy²)).
+///
+/// # Returns
+/// * Price scaled by `ORACLE_PRICE_PRECISION`, or None for an empty or overflowing pool
+pub fn calculate_spot_price(x_reserve: u64, y_reserve: u64, amplification: u64) -> Option<u64> {
+    let reserves = [x_reserve, y_reserve];
+    let d = calculate_invariant(&reserves, amplification)?;
+    spot_price_on_curve(&reserves, d, amplification)
+}
+
+/// Calculate the marginal StableSwap price of token `x_index` in units of token `y_index`
+///
+/// The remaining tokens of an n-token pool are held fixed.
+///
+/// # Returns
+/// * Price scaled by `ORACLE_PRICE_PRECISION`, or None for bad indices or an empty or
+///   overflowing pool
+pub fn calculate_pair_price(
+    reserves: &[u64],
+    x_index: usize,
+    y_index: usize,
+    amplification: u64,
+) -> Option<u64> {
+    if x_index == y_index || x_index.max(y_index) >= reserves.len() {
+        return None;
+    }
+
+    // D is symmetric in the reserves, so reorder them to put the pair first
+    let mut ordered = vec![reserves[x_index], reserves[y_index]];
+    ordered.extend(
+        reserves
+            .iter()
+            .enumerate()
+            .filter(|&(i, _)| i != x_index && i != y_index)
+            .map(|(_, &reserve)| reserve),
+    );
+    let d = calculate_invariant(&ordered, amplification)?;
+    spot_price_on_curve(&ordered, d, amplification)
+}
+
+/// Distance of a price scaled by `ORACLE_PRICE_PRECISION` from 1:1, in basis points
+pub fn peg_deviation_bps(price: u64) -> u64 {
+    (price.abs_diff(ORACLE_PRICE_PRECISION) as u128 * BPS_DENOMINATOR as u128
+        / ORACLE_PRICE_PRECISION as u128) as u64
+}
+
+/// Marginal price of `reserves[0]` in units of `reserves[1]` for reserves already known
+/// to lie on invariant `d`; any further reserves are held fixed
+///
+/// The n-token form of `calculate_spot_price`: dF/dx_i = A·n^n + D^(n+1)/(n^n·P·x_i).
+fn spot_price_on_curve(reserves: &[u64], d: u64, amplification: u64) -> Option<u64> {
+    let n = reserves.len() as u128;
+    let d = d as u128;
+    let precision = ORACLE_PRICE_PRECISION as u128;
+    let ann = amplification as u128 * n.checked_pow(reserves.len() as u32)?;
+
+    // D^(n+1)/(n^n·P·x_i) = (D/x_i)·Π(D/(n·x_k)), kept in fixed point to stay within u128
+    let ratio = |reserve: u64, scale: u128| -> Option<u128> {
+        Some(d.checked_mul(precision)? / scale.checked_mul(reserve as u128).filter(|&v| v > 0)?)
+    };
+    let term = |index: usize| -> Option<u128> {
+        let mut term = ratio(reserves[index], 1)?;
+        for &reserve in reserves {
+            term = term.checked_mul(ratio(reserve, n)?)? / precision;
+        }
+        Some(term)
+    };
+    let numerator = (ann * precision).checked_add(term(0)?)?;
+    let denominator = (ann * precision).checked_add(term(1)?)?;
+
+    u64::try_from(numerator.checked_mul(precision)? / denominator).ok()
+}
+
+/// Net input of token `x_index` a two-sided batch routes through the curve
+///
+/// Sellers bring `x_in` of token x and `y_in` of token y, with x the side in excess.
+/// The net `e` solves out(e)·(x_in − e) = e·y_in, where out(e) is the curve's output
+/// for `e` after a `fee` (parts per 1000). Both sides then clear at the price the pool
+/// gives the net trade: x sellers share `y_in + out(e)` and y sellers `x_in − e`.
+///
+/// # Returns
+/// * Net input of token x, or None for an empty or overflowing pool
+pub fn batch_net_input(
+    reserves: &[u64],
+    x_index: usize,
+    y_index: usize,
+    x_in: u64,
+    y_in: u64,
+    amplification: u64,
+    fee: u64,
+) -> Option<u64> {
+    if y_in == 0 {
+        return Some(x_in);
+    }
+    let d = calculate_invariant(reserves, amplification)?;
+
+    // Whether the pool still pays more per unit than the uniform price at net input `e`
+    let pool_is_dearer = |e: u64| -> Option<bool> {
+        let mut new_reserves = reserves.to_vec();
+        new_reserves[x_index] =
+            new_reserves[x_index].checked_add(e - calculate_fee_amount(e, fee))?;
+        let new_y_reserve = calculate_y(&new_reserves, y_index, d, amplification)?;
+        let out = rounding::swap_output(reserves[y_index], new_y_reserve);
+        Some(out as u128 * (x_in - e) as u128 > e as u128 * y_in as u128)
+    };
+
+    // The pool's average price falls as `e` grows, so the crossing is found by bisection
+    let mut low = 0u64;
+    let mut high = x_in;
+    while high - low > 1 {
+        let mid = low + (high - low) / 2;
+        if pool_is_dearer(mid)? {
+            low = mid;
+        } else {
+            high = mid;
+        }
+    }
+    Some(low)
+}
+
+/// Input of the first token that moves its marginal price by `impact_bps`
+///
+/// Searches for the largest fee-free trade of `reserves[0]` into `reserves[1]` after
+/// which the price of the input, in units of the output, sits at most `impact_bps`
+/// below where it started. Any further reserves are held fixed, so a Seed Pool is
+/// measured on its full invariant. Trades as large as the whole pool are the ceiling.
+///
+/// # Arguments
+/// * `reserves` - Pool reserves, input first and output second
+/// * `amplification` - Amplification coefficient
+/// * `impact_bps` - Price impact in basis points
+///
+/// # Returns
+/// * Input amount, or None for fewer than two reserves or an empty one
+pub fn depth_at_impact(reserves: &[u64], amplification: u64, impact_bps: u64) -> Option<u64> {
+    if reserves.len() < 2 {
+        return None;
+    }
+    let (x_reserve, y_reserve) = (reserves[0], reserves[1]);
+    let d = calculate_invariant(reserves, amplification)?;
+    let start_price = spot_price_on_curve(reserves, d, amplification)?;
+    let floor_price = start_price as u128 * BPS_DENOMINATOR.saturating_sub(impact_bps) as u128
+        / BPS_DENOMINATOR as u128;
+
+    // Price after selling `amount_in`; the reserves stay on the same curve without fees
+    let price_after = |amount_in: u64| -> Option<u64> {
+        let mut new_reserves = reserves.to_vec();
+        new_reserves[0] = x_reserve.checked_add(amount_in)?;
+        let new_y_reserve = calculate_y(&new_reserves, 1, d, amplification)?;
+        new_reserves[1] = u64::try_from(new_y_reserve).ok()?.max(1);
+        spot_price_on_curve(&new_reserves, d, amplification)
+    };
+
+    // Binary search for the largest input still at or above the floor price
+    let mut low = 0u64;
+    let mut high = x_reserve.checked_add(y_reserve)?;
+    if price_after(high)? as u128 >= floor_price {
+        return Some(high);
+    }
+    while high - low > 1 {
+        let mid = low + (high - low) / 2;
+        if price_after(mid)? as u128 >= floor_price {
+            low = mid;
+        } else {
+            high = mid;
+        }
+    }
+
+    Some(low)
+}
+
+/// How far (in basis points) a trade's execution price falls short of the spot price
+///
+/// # Arguments
+/// * `spot_price` - Pre-trade marginal price of the input in units of the output,
+///   scaled by `ORACLE_PRICE_PRECISION`
+/// * `amount_in` - Input the curve priced, net of fees
+/// * `amount_out` - Output the trade paid
+///
+/// # Returns
+/// * Impact rounded up, 0 for a trade at or better than spot, or None for an empty
+///   trade or price
+pub fn price_impact_bps(spot_price: u64, amount_in: u64, amount_out: u64) -> Option<u64> {
+    if spot_price == 0 || amount_in == 0 {
+        return None;
+    }
+    let execution_price = amount_out as u128 * ORACLE_PRICE_PRECISION as u128 / amount_in as u128;
+    let shortfall = (spot_price as u128).saturating_sub(execution_price);
+    rounding::mul_div_u128(
+        shortfall,
+        BPS_DENOMINATOR as u128,
+        spot_price as u128,
+        rounding::Rounding::Up,
+    )
+    .map(|impact| impact as u64)
+}
+
+/// Calculate the fee charged on a swap input
+///
+/// # Arguments
+/// * `amount` - Input token amount
+/// * `fee` - Fee in parts per 1000
+///
+/// # Returns
+/// * Fee amount in input token units, rounded up
+pub fn calculate_fee_amount(amount: u64, fee: u64) -> u64 {
+    rounding::fee(amount, fee, FEE_DENOMINATOR)
+}
+
+/// `amount` less a `discount_bps` share of it
+pub fn apply_fee_discount(amount: u64, discount_bps: u64) -> u64 {
+    let discount =
+        amount as u128 * discount_bps.min(BPS_DENOMINATOR) as u128 / BPS_DENOMINATOR as u128;
+    amount - discount as u64
+}
+
+/// Calculate the deposit fee or rebate for a single token
+///
+/// Depositing a token that is already over target weight pushes the pool further
+/// from balance and pays a fee; depositing an under-weighted token earns a rebate.
+///
+/// # Arguments
+/// * `current_weight` - Token weight before the deposit in basis points
+/// * `target_weight` - Token target weight in basis points
+///
+/// # Returns
+/// * (fee_bps, rebate_bps), at most one of which is non-zero
+pub fn calculate_deposit_fee_bps(current_weight: u64, target_weight: u64) -> (u64, u64) {
+    let adjustment = weight_deviation(current_weight, target_weight) / DEPOSIT_FEE_DIVISOR;
+
+    if current_weight > target_weight {
+        (cmp::min(adjustment, MAX_DEPOSIT_FEE_BPS), 0)
+    } else {
+        (0, cmp::min(adjustment / 2, MAX_DEPOSIT_REBATE_BPS))
+    }
+}
+
+/// Calculate the deposit amounts credited towards LP minting
+///
+/// The full amounts still go to the reserves; fees are left to existing LPs
+/// and rebates are paid out of their share.
+///
+/// # Arguments
+/// * `amounts` - Deposited token amounts
+/// * `reserves` - Reserves before the deposit
+/// * `target_weights` - Target weights in basis points
+///
+/// # Returns
+/// * Fee-adjusted amounts, rounded in favor of the pool
+pub fn calculate_deposit_adjusted_amounts(
+    amounts: &[u64],
+    reserves: &[u64],
+    target_weights: &[u64],
+) -> Vec<u64> {
+    let weights = calculate_weights(reserves);
+
+    amounts
+        .iter()
+        .zip(weights.iter().zip(target_weights.iter()))
+        .map(|(&amount, (&weight, &target))| {
+            let (fee_bps, rebate_bps) = calculate_deposit_fee_bps(weight, target);
+            (amount as u128 * (BPS_DENOMINATOR + rebate_bps - fee_bps) as u128
+                / BPS_DENOMINATOR as u128) as u64
+        })
+        .collect()
+}
+
+/// Marginal curve price of each token in units of token 0
+///
+/// # Returns
+/// * Prices scaled by `ORACLE_PRICE_PRECISION`, or None for an empty or overflowing pool
+pub fn calculate_curve_prices(reserves: &[u64], amplification: u64) -> Option<Vec<u64>> {
+    (0..reserves.len())
+        .map(|i| match i {
+            0 => Some(ORACLE_PRICE_PRECISION),
+            _ => calculate_pair_price(reserves, i, 0, amplification),
+        })
+        .collect()
+}
+
+/// Value of token `amounts` at `prices` (`ORACLE_PRICE_PRECISION` scale), rounded down
+pub fn calculate_value_at_prices(amounts: &[u64], prices: &[u64]) -> u128 {
+    amounts
+        .iter()
+        .zip(prices.iter())
+        .map(|(&amount, &price)| amount as u128 * price as u128 / ORACLE_PRICE_PRECISION as u128)
+        .sum()
+}
+
+/// Split a withdrawal worth `value` across tokens, paying out over-weighted tokens first
+///
+/// Each token's excess over its target share of the post-withdrawal pool value is
+/// paid out before anything else; whatever is left is taken pro-rata from the
+/// remainder. Tokens are valued at `prices`, so skewing the payout towards a token
+/// the curve prices below peg pays out more of it rather than more value.
+///
+/// # Arguments
+/// * `reserves` - Current token reserves
+/// * `target_weights` - Target weights in basis points
+/// * `prices` - Price of each token in a common unit (`ORACLE_PRICE_PRECISION` scale)
+/// * `value` - Total value to withdraw across all tokens, in the unit of `prices`
+///
+/// # Returns
+/// * Amount of each token to pay out, rounded in favor of the pool, or None if a
+///   token has no price
+pub fn calculate_balanced_withdrawal(
+    reserves: &[u64],
+    target_weights: &[u64],
+    prices: &[u64],
+    value: u128,
+) -> Option<Vec<u64>> {
+    if prices.contains(&0) {
+        return None;
+    }
+    let precision = ORACLE_PRICE_PRECISION as u128;
+    let values: Vec<u128> = reserves
+        .iter()
+        .zip(prices.iter())
+        .map(|(&reserve, &price)| reserve as u128 * price as u128 / precision)
+        .collect();
+    let total: u128 = values.iter().sum();
+    let value = value.min(total);
+    let remaining_total = total - value;
+
+    // Excess value of each token over its target share of what remains
+    let excess: Vec<u128> = values
+        .iter()
+        .zip(target_weights.iter())
+        .map(|(&held, &target)| {
+            held.saturating_sub(remaining_total * target as u128 / BPS_DENOMINATOR as u128)
+        })
+        .collect();
+    let total_excess: u128 = excess.iter().sum();
+
+    let payouts: Vec<u128> = if total_excess >= value || total_excess == total {
+        excess
+            .iter()
+            .map(|&amount| amount * value / total_excess.max(1))
+            .collect()
+    } else {
+        // All excess is paid, the rest comes pro-rata from the balanced remainder
+        let leftover = value - total_excess;
+        let remainder_total = total - total_excess;
+        values
+            .iter()
+            .zip(excess.iter())
+            .map(|(&held, &amount)| amount + (held - amount) * leftover / remainder_total)
+            .collect()
+    };
+
+    Some(
+        payouts
+            .iter()
+            .zip(prices.iter().zip(reserves.iter()))
+            .map(|(&payout, (&price, &reserve))| {
+                (payout * precision / price as u128).min(reserve as u128) as u64
+            })
+            .collect(),
+    )
+}
+
+/// Calculate the withdrawal amounts charged against LP when paying out exact amounts
+///
+/// Withdrawing a token that is already under target weight pushes the pool further
+/// from balance, so it is charged as if more were taken out; the mirror of
+/// `calculate_deposit_adjusted_amounts`. Withdrawals never earn a rebate.
+///
+/// # Arguments
+/// * `amounts` - Token amounts paid out
+/// * `reserves` - Reserves before the withdrawal
+/// * `target_weights` - Target weights in basis points
+///
+/// # Returns
+/// * Fee-adjusted amounts, rounded in favor of the pool
+pub fn calculate_withdrawal_charged_amounts(
+    amounts: &[u64],
+    reserves: &[u64],
+    target_weights: &[u64],
+) -> Vec<u64> {
+    let weights = calculate_weights(reserves);
+
+    amounts
+        .iter()
+        .zip(weights.iter().zip(target_weights.iter()))
+        .map(|(&amount, (&weight, &target))| {
+            // Reuse the deposit curve with the roles of over- and under-weight swapped
+            let (fee_bps, _) = calculate_deposit_fee_bps(target, weight);
+            (amount as u128 * (BPS_DENOMINATOR + fee_bps) as u128)
+                .div_ceil(BPS_DENOMINATOR as u128) as u64
+        })
+        .collect()
+}
+
+/// Calculate the LP token virtual price
+///
+/// # Arguments
+/// * `d` - StableSwap invariant
+/// * `lp_supply` - Outstanding LP tokens
+///
+/// # Returns
+/// * D / lp_supply scaled by 1e18, or None for an empty pool
+pub fn calculate_virtual_price(d: u64, lp_supply: u64) -> Option<u128> {
+    if lp_supply == 0 {
+        return None;
+    }
+
+    Some(d as u128 * VIRTUAL_PRICE_PRECISION / lp_supply as u128)
+}
+
+/// Calculate current weights of tokens in the pool
+///
+/// # Arguments
+/// * `reserves` - Current token reserves
+///
+/// # Returns
+/// * Weights in basis points (sum = 10000)
+pub fn calculate_weights(reserves: &[u64]) -> Vec<u64> {
+    let total: u64 = reserves.iter().sum();
+
+    reserves
+        .iter()
+        .map(|&reserve| calculate_weight(reserve, total))
+        .collect()
+}
+
+/// Scale weights back to a 10000 total after a token was dropped from them
+///
+/// Each weight keeps its share of the remaining total; the largest absorbs the
+/// rounding so the result sums to exactly 10000.
+///
+/// # Returns
+/// * Weights in basis points, or None if every remaining weight is zero
+pub fn rescale_weights(weights: &[u64]) -> Option<Vec<u64>> {
+    let total: u64 = weights.iter().sum();
+    if total == 0 {
+        return None;
+    }
+
+    let mut rescaled: Vec<u64> = weights
+        .iter()
+        .map(|&weight| (weight as u128 * BPS_DENOMINATOR as u128 / total as u128) as u64)
+        .collect();
+    let largest = (0..rescaled.len()).max_by_key(|&i| rescaled[i])?;
+    rescaled[largest] += BPS_DENOMINATOR - rescaled.iter().sum::<u64>();
+    Some(rescaled)
+}
+
+/// Calculate a single token's weight without allocating
+///
+/// # Arguments
+/// * `reserve` - Token reserve
+/// * `total` - Sum of all reserves
+///
+/// # Returns
+/// * Weight in basis points, 0 for an empty pool
+pub fn calculate_weight(reserve: u64, total: u64) -> u64 {
+    if total == 0 {
+        return 0;
+    }
+
+    (reserve * 10000) / total
+}
+
+/// Calculate the dynamic swap fee straight from reserves
+///
+/// Same result as `calculate_dynamic_fee` on `calculate_weights(reserves)`
+/// without building the intermediate weights.
+///
+/// # Returns
+/// * Fee in parts per 1000
+pub fn calculate_dynamic_fee_from_reserves(
+    reserves: &[u64],
+    target_weights: &[u64],
+    fee_curve: &FeeCurve,
+) -> u64 {
+    let total: u64 = reserves.iter().sum();
+    let total_deviation = reserves
+        .iter()
+        .zip(target_weights.iter())
+        .fold(0, |deviation: u64, (&reserve, &target)| {
+            deviation.saturating_add(weight_deviation(calculate_weight(reserve, total), target))
+        });
+
+    calculate_fee_for_deviation(total_deviation, fee_curve)
+}
+
+/// Share (in basis points) of a moving average left after `slots` slots when
+/// each slot replaces `lambda_bps` of it: `(1 - lambda)^slots`, rounded down
+pub fn ema_retention_bps(lambda_bps: u64, slots: u64) -> u64 {
+    const SCALE: u128 = 1_000_000_000_000; // 1e12
+    let mut base = (BPS_DENOMINATOR - lambda_bps.min(BPS_DENOMINATOR)) as u128 * SCALE
+        / BPS_DENOMINATOR as u128;
+    let mut retention = SCALE;
+    let mut exponent = slots;
+    while exponent > 0 && retention > 0 {
+        if exponent & 1 == 1 {
+            retention = retention * base / SCALE;
+        }
+        base = base * base / SCALE;
+        exponent >>= 1;
+    }
+    (retention * BPS_DENOMINATOR as u128 / SCALE) as u64
+}
+
+/// Annualize `fees` earned over `elapsed` seconds against `tvl`, in basis points
+///
+/// Returns 0 when either the window or the TVL is empty.
+pub fn calculate_annualized_yield_bps(fees: u128, tvl: u64, elapsed: i64) -> u64 {
+    if tvl == 0 || elapsed <= 0 {
+        return 0;
+    }
+    let yield_bps = fees
+        .saturating_mul(SECONDS_PER_YEAR * BPS_DENOMINATOR as u128)
+        / (tvl as u128 * elapsed as u128);
+    u64::try_from(yield_bps).unwrap_or(u64::MAX)
+}
+
+/// Calculate position bounds based on concentration factor
+///
+/// # Arguments
+/// * `center_price` - Center price in price_denominator units (typically 1000)
+/// * `concentration` - Number of 0.005 increments to use
+///
+/// # Returns
+/// * (min_price, max_price) in price_denominator units
+pub fn calculate_position_bounds(center_price: u64, concentration: u64) -> (u64, u64) {
+    let increment = 5; // 0.005 * PRICE_DENOMINATOR
+    let half_range = concentration * increment;
+
+    let min_price = center_price.saturating_sub(half_range);
+    let max_price = center_price + half_range;
+
+    (min_price, max_price)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STABLE: FeeCurve = FeeCurve {
+        base_fee: BASE_FEE,
+        max_fee: MAX_FEE,
+        fee_slope: FEE_MULTIPLIER,
+        shape: FeeShape::Linear,
+    };
+
+    #[test]
+    fn amplification_bounds_are_inclusive() {
+        assert!(!is_valid_amplification(0));
+        assert!(!is_valid_amplification(MIN_AMP - 1));
+        assert!(is_valid_amplification(MIN_AMP));
+        assert!(is_valid_amplification(MAX_AMP));
+        assert!(!is_valid_amplification(MAX_AMP + 1));
+        assert!(!is_valid_amplification(u64::MAX));
+    }
+
+    #[test]
+    fn growth_weights_sum_to_one_within_bounds() {
+        assert!(is_valid_growth_weights(&[5000, 5000]));
+        assert!(is_valid_growth_weights(&[8000, 2000]));
+        assert!(is_valid_growth_weights(&[2000, 8000]));
+        assert!(!is_valid_growth_weights(&[8001, 1999]));
+        assert!(!is_valid_growth_weights(&[5000, 4999]));
+        assert!(!is_valid_growth_weights(&[u64::MAX, 10001]));
+    }
+
+    #[test]
+    fn convex_fee_is_cheap_near_target_and_steep_off_peg() {
+        let linear = FeeCurve {
+            base_fee: 1,
+            max_fee: 50,
+            fee_slope: 4,
+            shape: FeeShape::Linear,
+        };
+        let convex = FeeCurve {
+            shape: FeeShape::Convex,
+            ..linear
+        };
+
+        // Both curves agree at 10 points of deviation
+        assert_eq!(calculate_fee_for_deviation(1000, &linear), 5);
+        assert_eq!(calculate_fee_for_deviation(1000, &convex), 5);
+
+        // Below it the convex curve is cheaper, above it steeper
+        assert_eq!(calculate_fee_for_deviation(500, &linear), 3);
+        assert_eq!(calculate_fee_for_deviation(500, &convex), 2);
+        assert_eq!(calculate_fee_for_deviation(3000, &linear), 13);
+        assert_eq!(calculate_fee_for_deviation(3000, &convex), 37);
+
+        // Both still cap at max_fee
+        assert_eq!(calculate_fee_for_deviation(20_000, &convex), 50);
+        assert_eq!(calculate_fee_for_deviation(0, &convex), 1);
+
+        // The dynamic fee follows the pool's curve
+        let current = [6000, 3000, 1000];
+        let target = [4500, 3500, 2000];
+        assert_eq!(calculate_dynamic_fee(&current, &target, &convex), 37);
+        assert_eq!(calculate_dynamic_fee(&current, &target, &linear), 13);
+    }
+
+    #[test]
+    fn weight_deviation_is_symmetric_and_never_wraps() {
+        for current in (0..=BPS_DENOMINATOR).step_by(125) {
+            for target in (0..=BPS_DENOMINATOR).step_by(125) {
+                let expected = (current as i128 - target as i128).unsigned_abs() as u64;
+                assert_eq!(weight_deviation(current, target), expected);
+                assert_eq!(weight_deviation(target, current), expected);
+            }
+        }
+
+        assert_eq!(weight_deviation(0, u64::MAX), u64::MAX);
+        assert_eq!(weight_deviation(u64::MAX, 0), u64::MAX);
+        assert_eq!(weight_deviation(u64::MAX, u64::MAX), 0);
+    }
+
+    #[test]
+    fn total_weight_deviation_saturates() {
+        assert_eq!(total_weight_deviation(&[4500, 3500, 2000], &[4500, 3500, 2000]), 0);
+        assert_eq!(total_weight_deviation(&[6000, 3000, 1000], &[4500, 3500, 2000]), 3000);
+        assert_eq!(total_weight_deviation(&[10_000, 0], &[0, 10_000]), 20_000);
+        assert_eq!(total_weight_deviation(&[u64::MAX, u64::MAX], &[0, 0]), u64::MAX);
+        assert_eq!(total_weight_deviation(&[], &[]), 0);
+
+        // The reserve-based fee agrees with the weight-based one
+        let reserves = [6_000_000, 3_000_000, 1_000_000];
+        assert_eq!(
+            calculate_dynamic_fee_from_reserves(&reserves, &[4500, 3500, 2000], &STABLE),
+            calculate_dynamic_fee(&calculate_weights(&reserves), &[4500, 3500, 2000], &STABLE)
+        );
+    }
+
+    #[test]
+    fn stableswap_math_holds_at_realistic_reserves() {
+        let reserves = [1_000_000_000_000, 1_000_000_000_000, 1_000_000_000_000];
+        assert_eq!(calculate_invariant(&reserves, 100), Some(3_000_000_000_000));
+
+        // A balanced swap returns almost all of its post-fee input
+        let out = calculate_output_amount(1_000_000_000, reserves[0], reserves[1], 1, 100).unwrap();
+        assert!(out <= 999_000_000 && out > 998_900_000);
+    }
+
+    #[test]
+    fn spot_price_matches_small_trades() {
+        let precision = ORACLE_PRICE_PRECISION as u128;
+        assert_eq!(
+            calculate_spot_price(1_000_000_000, 1_000_000_000, 100),
+            Some(ORACLE_PRICE_PRECISION)
+        );
+
+        // The scarce side is the expensive one, and a tiny trade executes at the spot price
+        let (x, y) = (500_000_000_000, 1_500_000_000_000);
+        let price = calculate_spot_price(x, y, 100).unwrap();
+        assert!(price > ORACLE_PRICE_PRECISION);
+        let out = calculate_output_amount(1_000_000, x, y, 0, 100).unwrap();
+        let executed = out as u128 * precision / 1_000_000;
+        assert!(executed.abs_diff(price as u128) < precision / 1_000);
+
+        assert_eq!(calculate_spot_price(0, y, 100), None);
+    }
+
+    #[test]
+    fn ema_retention_compounds_per_slot() {
+        assert_eq!(ema_retention_bps(1_000, 0), 10_000);
+        assert_eq!(ema_retention_bps(1_000, 1), 9_000);
+        assert_eq!(ema_retention_bps(1_000, 2), 8_100);
+        assert_eq!(ema_retention_bps(1_000, 10), 3_486);
+        assert_eq!(ema_retention_bps(1_000, 1_000), 0);
+        assert_eq!(ema_retention_bps(10_000, 1), 0);
+        assert_eq!(ema_retention_bps(0, u64::MAX), 10_000);
+    }
+
+    #[test]
+    fn invariant_converges_at_amplification_edges() {
+        let balanced = [1_000_000, 1_000_000, 1_000_000];
+        let imbalanced = [1_000_000, 500_000, 2_000_000];
+
+        for amplification in [MIN_AMP, MAX_AMP] {
+            assert_eq!(calculate_invariant(&balanced, amplification), Some(3_000_000));
+
+            let d = calculate_invariant(&imbalanced, amplification).unwrap();
+            assert!(d > 0 && d <= imbalanced.iter().sum::<u64>());
+        }
+    }
+
+    #[test]
+    fn fee_yield_annualizes_over_the_window() {
+        // 10 of fees on 100_000 TVL over a day is 0.01% per day, 3.65% per year
+        assert_eq!(calculate_annualized_yield_bps(10, 100_000, 86_400), 365);
+        assert_eq!(calculate_annualized_yield_bps(70, 100_000, 7 * 86_400), 365);
+        assert_eq!(calculate_annualized_yield_bps(10, 0, 86_400), 0);
+        assert_eq!(calculate_annualized_yield_bps(10, 100_000, 0), 0);
+    }
+
+    #[test]
+    fn fee_discount_rounds_in_the_pools_favor() {
+        assert_eq!(apply_fee_discount(1_000, 2500), 750);
+        assert_eq!(apply_fee_discount(3, 5000), 2);
+        assert_eq!(apply_fee_discount(1_000, BPS_DENOMINATOR), 0);
+    }
+
+    #[test]
+    fn withdrawal_charges_under_weighted_tokens_only() {
+        // Token 1 sits 5 points under its 35% target; token 0 is over and token 2 on target
+        let reserves = [50_000, 30_000, 20_000];
+        let target_weights = [4500, 3500, 2000];
+        let charged =
+            calculate_withdrawal_charged_amounts(&[10_000; 3], &reserves, &target_weights);
+        assert_eq!(charged, vec![10_000, 10_005, 10_000]);
+    }
+
+    #[test]
+    fn balanced_withdrawal_pays_the_proportional_value_at_curve_prices() {
+        let target_weights = [4500, 3500, 2000];
+        let reserves = [1_000_000_000, 1_000_000_000, 2_000_000_000];
+        let proportional = [10_000_000, 10_000_000, 20_000_000];
+        let prices = calculate_curve_prices(&reserves, 100).unwrap();
+        assert_eq!(prices[0], ORACLE_PRICE_PRECISION);
+        assert!(prices[2] < ORACLE_PRICE_PRECISION);
+
+        // The glut of token 2 is paid out first, in more units than its value at peg
+        let value = calculate_value_at_prices(&proportional, &prices);
+        let paid = calculate_balanced_withdrawal(&reserves, &target_weights, &prices, value)
+            .unwrap();
+        assert_eq!(&paid[..2], &[0, 0]);
+        assert!(paid[2] > proportional.iter().sum::<u64>());
+        let paid_value = calculate_value_at_prices(&paid, &prices);
+        assert!(paid_value <= value && value - paid_value <= 1);
+
+        // At peg prices this is the plain 1:1 split
+        let peg = [ORACLE_PRICE_PRECISION; 3];
+        let paid = calculate_balanced_withdrawal(&reserves, &target_weights, &peg, 40_000_000);
+        assert_eq!(paid, Some(vec![0, 0, 40_000_000]));
+        assert_eq!(calculate_balanced_withdrawal(&reserves, &target_weights, &[0; 3], 1), None);
+    }
+
+    #[test]
+    fn invariant_guard_rejects_overpaid_swaps() {
+        let before = [1_000_000_000_000, 1_000_000_000_000];
+        let amount_out =
+            calculate_output_amount(1_000_000_000, before[0], before[1], 0, 100).unwrap();
+        let after = [before[0] + 1_000_000_000, before[1] - amount_out];
+        assert!(is_invariant_preserved(&before, &after, 100));
+
+        // The guard hands back D of the new reserves, not the reserve sum
+        let d_after = preserved_invariant(&before, &after, 100).unwrap();
+        assert_eq!(Some(d_after), calculate_invariant(&after, 100));
+
+        // Paying out a little more than the curve allows must trip the guard
+        let overpaid = [after[0], after[1] - 10];
+        assert!(!is_invariant_preserved(&before, &overpaid, 100));
+    }
+
+    #[test]
+    fn depth_grows_with_amplification_and_impact() {
+        let reserves = [1_000_000_000_000, 1_000_000_000_000];
+        let shallow = depth_at_impact(&reserves, 10, 100).unwrap();
+        let deep = depth_at_impact(&reserves, 1_000, 100).unwrap();
+        assert!(shallow > 0 && deep > shallow);
+        assert!(depth_at_impact(&reserves, 10, 50).unwrap() < shallow);
+
+        // The trade found lands on the requested impact
+        let start = calculate_spot_price(reserves[0], reserves[1], 10).unwrap();
+        let out = calculate_output_amount(shallow, reserves[0], reserves[1], 0, 10).unwrap();
+        let end = calculate_spot_price(reserves[0] + shallow, reserves[1] - out, 10).unwrap();
+        let impact_bps = (start - end) as u128 * BPS_DENOMINATOR as u128 / start as u128;
+        assert!((99..=100).contains(&impact_bps));
+
+        // A Seed Pool holds its third reserve fixed, which deepens the pair
+        let seed = [reserves[0], reserves[1], reserves[0]];
+        let seed_depth = depth_at_impact(&seed, 10, 100).unwrap();
+        assert!(seed_depth > shallow);
+        let out = calculate_swap_output(seed_depth, &seed, 0, 1, 10).unwrap();
+        let mut after = seed;
+        after[0] += seed_depth;
+        after[1] -= out;
+        let d = calculate_invariant(&seed, 10).unwrap();
+        let start = spot_price_on_curve(&seed, d, 10).unwrap();
+        let end = spot_price_on_curve(&after, d, 10).unwrap();
+        let impact_bps = (start - end) as u128 * BPS_DENOMINATOR as u128 / start as u128;
+        assert!((99..=101).contains(&impact_bps));
+
+        assert_eq!(depth_at_impact(&[1_000], 10, 100), None);
+        assert_eq!(depth_at_impact(&[0, 1_000], 10, 100), None);
+    }
+
+    #[test]
+    fn price_impact_grows_with_trade_size() {
+        let reserves = [1_000_000_000_000, 1_000_000_000_000];
+        let spot = calculate_pair_price(&reserves, 0, 1, 10).unwrap();
+        let impact = |amount_in: u64| {
+            let amount_out = calculate_swap_output(amount_in, &reserves, 0, 1, 10).unwrap();
+            price_impact_bps(spot, amount_in, amount_out).unwrap()
+        };
+
+        assert!(impact(1_000_000) <= 1);
+        assert!(impact(100_000_000_000) > impact(10_000_000_000));
+        assert!(impact(500_000_000_000) > 100);
+
+        // Better than spot is no impact at all
+        assert_eq!(price_impact_bps(ORACLE_PRICE_PRECISION, 100, 101), Some(0));
+        assert_eq!(price_impact_bps(ORACLE_PRICE_PRECISION, 100, 99), Some(100));
+        assert_eq!(price_impact_bps(0, 100, 99), None);
+        assert_eq!(price_impact_bps(ORACLE_PRICE_PRECISION, 0, 0), None);
+    }
+
+    #[test]
+    fn basket_output_solves_the_full_invariant_once() {
+        // A one-leg basket in a 2-token pool is an ordinary swap
+        let pair = [1_000_000_000_000, 1_200_000_000_000];
+        assert_eq!(
+            calculate_basket_output(&[1_000_000_000, 0], &pair, 1, 100),
+            calculate_output_amount(1_000_000_000, pair[0], pair[1], 0, 100)
+        );
+
+        // Two legs into the third token keep the 3-token invariant and stay near par
+        let reserves = [1_000_000_000_000; 3];
+        let amounts_in = [5_000_000_000, 5_000_000_000, 0];
+        let out = calculate_basket_output(&amounts_in, &reserves, 2, 100).unwrap();
+        assert!(out < 10_000_000_000 && out > 9_990_000_000);
+        let after = [
+            reserves[0] + amounts_in[0],
+            reserves[1] + amounts_in[1],
+            reserves[2] - out,
+        ];
+        assert!(is_invariant_preserved(&reserves, &after, 100));
+
+        // The bought token cannot also be sold
+        assert_eq!(calculate_basket_output(&[1, 0, 1], &reserves, 2, 100), None);
+    }
+
+    #[test]
+    fn seed_pool_swaps_solve_against_the_full_invariant() {
+        // Two-token pools keep the pairwise solve, which the n-token one agrees with
+        let pair = [1_000_000_000_000, 1_200_000_000_000];
+        let out = calculate_swap_output(1_000_000_000, &pair, 0, 1, 100);
+        assert_eq!(
+            out,
+            calculate_output_amount(1_000_000_000, pair[0], pair[1], 0, 100)
+        );
+        assert_eq!(
+            out,
+            calculate_basket_output(&[1_000_000_000, 0], &pair, 1, 100)
+        );
+
+        // In a 3-token pool the third reserve flattens the curve: the full solve keeps
+        // D exactly, where the pairwise one underpays and leaves D higher
+        let reserves = [1_200_000_000_000, 800_000_000_000, 1_000_000_000_000];
+        let amount_in = 50_000_000_000;
+        let full = calculate_swap_output(amount_in, &reserves, 0, 1, 10).unwrap();
+        let pairwise = calculate_output_amount(amount_in, reserves[0], reserves[1], 0, 10).unwrap();
+        assert!(full > pairwise);
+        let after = |out: u64| [reserves[0] + amount_in, reserves[1] - out, reserves[2]];
+        let d = calculate_invariant(&reserves, 10).unwrap();
+        assert!(calculate_invariant(&after(full), 10).unwrap().abs_diff(d) <= INVARIANT_TOLERANCE);
+        assert!(calculate_invariant(&after(pairwise), 10).unwrap() > d + INVARIANT_TOLERANCE);
+
+        assert_eq!(calculate_swap_output(1, &reserves, 1, 1, 10), None);
+        assert_eq!(calculate_swap_output(1, &reserves, 0, 3, 10), None);
+    }
+
+    #[test]
+    fn rescaled_weights_keep_their_shares_and_sum_to_one() {
+        // 45/35/20 with the last token dropped
+        assert_eq!(rescale_weights(&[4500, 3500]), Some(vec![5625, 4375]));
+        let rescaled = rescale_weights(&[3333, 3333]).unwrap();
+        assert_eq!(rescaled.iter().sum::<u64>(), BPS_DENOMINATOR);
+        assert_eq!(rescale_weights(&[0, 0]), None);
+    }
+
+    #[test]
+    fn pair_price_reads_any_two_tokens_of_a_seed_pool() {
+        let balanced = [1_000_000_000u64; 3];
+        let price = calculate_pair_price(&balanced, 2, 0, 200).unwrap();
+        assert!(peg_deviation_bps(price) <= 1);
+
+        // Token 0 is abundant, so it is worth less than token 2 and the reverse more
+        let skewed = [3_000_000_000u64, 1_000_000_000, 500_000_000];
+        let cheap = calculate_pair_price(&skewed, 0, 2, 10).unwrap();
+        let dear = calculate_pair_price(&skewed, 2, 0, 10).unwrap();
+        assert!(cheap < ORACLE_PRICE_PRECISION && dear > ORACLE_PRICE_PRECISION);
+        assert!(peg_deviation_bps(cheap) > 100);
+
+        assert_eq!(calculate_pair_price(&skewed, 1, 1, 10), None);
+        assert_eq!(calculate_pair_price(&skewed, 0, 3, 10), None);
+        assert_eq!(peg_deviation_bps(95_000_000), 500);
+        assert_eq!(peg_deviation_bps(105_000_000), 500);
+    }
+
+    #[test]
+    fn batch_net_input_clears_both_sides_at_the_pools_price() {
+        let reserves = [1_000_000_000u64, 1_000_000_000];
+        let (x_in, y_in) = (30_000_000u64, 10_000_000u64);
+        let e = batch_net_input(&reserves, 0, 1, x_in, y_in, 200, 1).unwrap();
+
+        // Near the peg roughly the imbalance is routed, and at the pool's own price
+        assert!(e > 19_000_000 && e < 21_000_000);
+        let net = e - calculate_fee_amount(e, 1);
+        let out = calculate_swap_output(net, &reserves, 0, 1, 200).unwrap();
+        let pool_price = out as u128 * 1_000_000 / e as u128;
+        let uniform_price = (y_in + out) as u128 * 1_000_000 / x_in as u128;
+        assert!(pool_price.abs_diff(uniform_price) <= 1);
+
+        // A one-sided batch routes everything, a balanced one almost nothing
+        assert_eq!(batch_net_input(&reserves, 0, 1, x_in, 0, 200, 1), Some(x_in));
+        let matched = batch_net_input(&reserves, 0, 1, y_in, y_in, 200, 0).unwrap();
+        assert!(matched < 1_000);
+    }
+}
@@ -0,0 +1,393 @@
+//! Pool-level pricing: dynamic fees, swap quotes and withdrawal payouts
+//!
+//! `PoolView` borrows the fields pricing reads from a pool, so the program and
+//! off-chain readers that decode pools their own way quote through the same code.
+
+use std::borrow::Cow;
+
+use crate::math::{
+    apply_fee_discount, calculate_balanced_withdrawal, calculate_curve_prices,
+    calculate_dynamic_fee, calculate_dynamic_fee_from_reserves, calculate_fee_amount,
+    calculate_spot_price, calculate_swap_output, calculate_value_at_prices, calculate_weights,
+    ema_retention_bps, total_weight_deviation, FeeCurve, BPS_DENOMINATOR,
+};
+use crate::rounding;
+
+/// Age after which a Growth pool's partner price no longer drives its oracle band (in seconds)
+pub const MAX_PARTNER_PRICE_AGE: i64 = 600;
+
+/// Index of USD* in a Growth pool's tokens
+pub const USD_STAR_INDEX: usize = 0;
+
+/// Index of the partner token in a Growth pool's tokens
+pub const PARTNER_INDEX: usize = 1;
+
+/// Why a quote could not be priced; each maps to the program error of the same name
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum QuoteError {
+    InvalidSwap,
+    TokenFrozen,
+    MinSwapAmount,
+    MaxTradeSizeExceeded,
+    InsufficientLiquidity,
+    MathOverflow,
+}
+
+/// Result of pricing a swap against a pool
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct SwapQuote {
+    /// Output amount after fees
+    pub amount_out: u64,
+
+    /// Dynamic fee applied, in parts per 1000
+    pub fee: u64,
+
+    /// Fee charged, in input token units
+    pub fee_amount: u64,
+}
+
+/// How a withdrawal is split across the pool's tokens
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum WithdrawSplit {
+    /// Pro-rata share of every reserve
+    Proportional,
+    /// Same total value at the curve's prices, paid out of over-weighted tokens first
+    Balanced,
+}
+
+/// Partner price feed of a Growth pool
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct PartnerOracle {
+    /// Partner token price in USD* (`ORACLE_PRICE_PRECISION` scale), 0 when unset
+    pub price: u64,
+
+    /// Timestamp of the last `price` update
+    pub updated_at: i64,
+
+    /// Deviation from `price` tolerated before surcharging (in basis points); 0 disables it
+    pub band_bps: u64,
+
+    /// Surcharge on trades pushing the pool price further outside the band
+    /// (in basis points of the input)
+    pub surcharge_bps: u64,
+}
+
+/// The fields of a pool that pricing reads
+#[derive(Clone, Copy, Debug)]
+pub struct PoolView<'a> {
+    pub reserves: &'a [u64],
+    pub target_weights: &'a [u64],
+
+    /// LBP start weights; empty when no schedule is set
+    pub lbp_start_weights: &'a [u64],
+    pub lbp_start_ts: i64,
+    pub lbp_end_ts: i64,
+    pub amplification: u64,
+    pub fee_curve: FeeCurve,
+
+    /// Share of `weights_ema` each slot replaces (in basis points); 0 prices the
+    /// fee on the current weights
+    pub ema_lambda: u64,
+
+    /// Moving average of the weights, at least one entry per token
+    pub weights_ema: &'a [u64],
+    pub min_swap_amount: u64,
+    pub max_trade_bps: u64,
+
+    /// Growth pools only
+    pub partner_oracle: Option<PartnerOracle>,
+}
+
+impl<'a> PoolView<'a> {
+    /// Current token weights in basis points (sum = 10000)
+    pub fn current_weights(&self) -> Vec<u64> {
+        calculate_weights(self.reserves)
+    }
+
+    /// Weights the dynamic fee is priced on: `weights_ema` while `ema_lambda` is
+    /// set, the current weights otherwise
+    pub fn fee_weights(&self) -> Vec<u64> {
+        if self.ema_lambda == 0 {
+            return self.current_weights();
+        }
+        self.weights_ema[..self.reserves.len()].to_vec()
+    }
+
+    /// Fee weights once the pool has held `weights` for `slots` slots
+    pub fn fee_weights_after(&self, weights: &[u64], slots: u64) -> Vec<u64> {
+        if self.ema_lambda == 0 {
+            return weights.to_vec();
+        }
+        let retention = ema_retention_bps(self.ema_lambda, slots);
+        weights
+            .iter()
+            .zip(self.weights_ema.iter())
+            .map(|(&weight, &average)| {
+                ((average as u128 * retention as u128
+                    + weight as u128 * (BPS_DENOMINATOR - retention) as u128)
+                    / BPS_DENOMINATOR as u128) as u64
+            })
+            .collect()
+    }
+
+    /// Dynamic fee (in parts per 1000) of the next swap, against `target_weights`
+    ///
+    /// With `ema_lambda` set, one large trade only moves the average part of the
+    /// way, so it cannot spike the fee the next trader pays.
+    pub fn dynamic_fee(&self, target_weights: &[u64]) -> u64 {
+        if self.ema_lambda == 0 {
+            return calculate_dynamic_fee_from_reserves(
+                self.reserves,
+                target_weights,
+                &self.fee_curve,
+            );
+        }
+        calculate_dynamic_fee(&self.fee_weights(), target_weights, &self.fee_curve)
+    }
+
+    /// Dynamic fee of a trade that leaves the pool at `reserves_after`
+    ///
+    /// The average only softens the fee of trades that bring the weights back
+    /// towards `target_weights`. A trade moving them further away pays at least
+    /// the instantaneous fee, so splitting it up inside the slot saves nothing.
+    pub fn trade_fee(&self, target_weights: &[u64], reserves_after: &[u64]) -> u64 {
+        let fee = self.dynamic_fee(target_weights);
+        if self.ema_lambda == 0 {
+            return fee;
+        }
+        let deviation_before = total_weight_deviation(&self.current_weights(), target_weights);
+        let deviation_after =
+            total_weight_deviation(&calculate_weights(reserves_after), target_weights);
+        if deviation_after <= deviation_before {
+            return fee;
+        }
+        fee.max(calculate_dynamic_fee_from_reserves(
+            self.reserves,
+            target_weights,
+            &self.fee_curve,
+        ))
+    }
+
+    /// Target weights in effect at `now`, following the LBP schedule if one is set
+    pub fn target_weights_at(&self, now: i64) -> Cow<'a, [u64]> {
+        if self.lbp_start_weights.len() != self.target_weights.len() || now >= self.lbp_end_ts {
+            return Cow::Borrowed(self.target_weights);
+        }
+        if now <= self.lbp_start_ts {
+            return Cow::Borrowed(self.lbp_start_weights);
+        }
+
+        let elapsed = (now - self.lbp_start_ts) as u128;
+        let duration = (self.lbp_end_ts - self.lbp_start_ts) as u128;
+        self.lbp_start_weights
+            .iter()
+            .zip(self.target_weights.iter())
+            .map(|(&start, &end)| {
+                let shift = start.abs_diff(end) as u128 * elapsed / duration;
+                if end >= start {
+                    start + shift as u64
+                } else {
+                    start - shift as u64
+                }
+            })
+            .collect::<Vec<u64>>()
+            .into()
+    }
+
+    /// Growth pools: the partner oracle price if it was pushed within `MAX_PARTNER_PRICE_AGE`
+    fn fresh_partner_oracle_price(&self, now: i64) -> Option<u64> {
+        let oracle = self.partner_oracle?;
+        (oracle.price > 0 && now.saturating_sub(oracle.updated_at) <= MAX_PARTNER_PRICE_AGE)
+            .then_some(oracle.price)
+    }
+
+    /// Growth pools: partner token price in USD* (`ORACLE_PRICE_PRECISION` scale) from
+    /// a fresh oracle, falling back to the curve's spot price; None for an empty pool
+    pub fn partner_price(&self, now: i64) -> Option<u64> {
+        self.partner_oracle?;
+        self.fresh_partner_oracle_price(now).or_else(|| {
+            calculate_spot_price(
+                self.reserves[PARTNER_INDEX],
+                self.reserves[USD_STAR_INDEX],
+                self.amplification,
+            )
+        })
+    }
+
+    /// Deviation of the partner token's pool price from the oracle price with
+    /// `reserves` (in basis points), or None when no fresh oracle price applies
+    fn oracle_deviation_bps(&self, reserves: &[u64], now: i64) -> Option<u64> {
+        let oracle_price = self.fresh_partner_oracle_price(now)?;
+        let pool_price = calculate_spot_price(
+            reserves[PARTNER_INDEX],
+            reserves[USD_STAR_INDEX],
+            self.amplification,
+        )?;
+        let deviation = pool_price.abs_diff(oracle_price) as u128 * BPS_DENOMINATOR as u128
+            / oracle_price as u128;
+        Some(deviation as u64)
+    }
+
+    /// Surcharge (in basis points of the input) owed by a trade that would leave the pool
+    /// price outside the oracle band and further from the oracle than before it
+    pub fn oracle_surcharge_bps(
+        &self,
+        token_in_idx: usize,
+        token_out_idx: usize,
+        amount_in: u64,
+        amount_out: u64,
+        now: i64,
+    ) -> u64 {
+        let Some(oracle) = self.partner_oracle.filter(|oracle| oracle.band_bps > 0) else {
+            return 0;
+        };
+        let mut reserves_after = self.reserves.to_vec();
+        reserves_after[token_in_idx] = reserves_after[token_in_idx].saturating_add(amount_in);
+        reserves_after[token_out_idx] = reserves_after[token_out_idx].saturating_sub(amount_out);
+
+        match (
+            self.oracle_deviation_bps(self.reserves, now),
+            self.oracle_deviation_bps(&reserves_after, now),
+        ) {
+            (Some(before), Some(after)) if after > oracle.band_bps && after > before => {
+                oracle.surcharge_bps
+            }
+            _ => 0,
+        }
+    }
+
+    /// Price a swap of `amount_in` from `token_in_idx` to `token_out_idx` at unix time
+    /// `now`, waiving `fee_discount_bps` of the fee and charging at least `min_fee`
+    /// parts per 1000 before the discount
+    ///
+    /// Token status is not checked here; the caller rejects frozen tokens first.
+    pub fn quote_swap(
+        &self,
+        token_in_idx: usize,
+        token_out_idx: usize,
+        amount_in: u64,
+        fee_discount_bps: u64,
+        min_fee: u64,
+        now: i64,
+    ) -> Result<SwapQuote, QuoteError> {
+        let num_tokens = self.reserves.len();
+        if token_in_idx == token_out_idx || token_in_idx.max(token_out_idx) >= num_tokens {
+            return Err(QuoteError::InvalidSwap);
+        }
+        if amount_in == 0 || amount_in < self.min_swap_amount {
+            return Err(QuoteError::MinSwapAmount);
+        }
+
+        // Calculate dynamic fee based on weight deviation
+        let target_weights = self.target_weights_at(now);
+        let mut fee = self.dynamic_fee(&target_weights).max(min_fee);
+
+        // Charge the fee on the input up front, so a discount applies to the exact amount
+        let fee_amount_at =
+            |fee: u64| apply_fee_discount(calculate_fee_amount(amount_in, fee), fee_discount_bps);
+        let mut fee_amount = fee_amount_at(fee);
+        let output_for = |fee_amount: u64| {
+            calculate_swap_output(
+                amount_in - fee_amount,
+                self.reserves,
+                token_in_idx,
+                token_out_idx,
+                self.amplification,
+            )
+            .ok_or(QuoteError::InvalidSwap)
+        };
+
+        // Calculate output amount
+        let mut amount_out = output_for(fee_amount)?;
+
+        // Under a smoothed fee, a trade away from the targets pays at least the live fee
+        if self.ema_lambda > 0 {
+            let mut reserves_after = self.reserves.to_vec();
+            reserves_after[token_in_idx] += amount_in;
+            reserves_after[token_out_idx] =
+                reserves_after[token_out_idx].saturating_sub(amount_out);
+            let trade_fee = self
+                .trade_fee(&target_weights, &reserves_after)
+                .max(min_fee);
+            if trade_fee > fee {
+                fee = trade_fee;
+                fee_amount = fee_amount_at(fee);
+                amount_out = output_for(fee_amount)?;
+            }
+        }
+
+        // Growth pools surcharge trades that drag the price away from the partner oracle
+        let surcharge_bps =
+            self.oracle_surcharge_bps(token_in_idx, token_out_idx, amount_in, amount_out, now);
+        if surcharge_bps > 0 {
+            fee_amount += rounding::fee(amount_in, surcharge_bps, BPS_DENOMINATOR);
+            amount_out = output_for(fee_amount)?;
+        }
+
+        // Dust inputs that round to nothing would only move tokens one way
+        if amount_out == 0 {
+            return Err(QuoteError::MinSwapAmount);
+        }
+
+        // The cap is a share of the output reserve, so only the output is held to it
+        let max_trade = (self.reserves[token_out_idx] as u128 * self.max_trade_bps as u128
+            / BPS_DENOMINATOR as u128) as u64;
+        if amount_out > max_trade {
+            return Err(QuoteError::MaxTradeSizeExceeded);
+        }
+
+        Ok(SwapQuote {
+            amount_out,
+            fee,
+            fee_amount,
+        })
+    }
+
+    /// Tokens paid out for burning `lp_amount` of `total_lp_supply`, split by `split`
+    /// against `target_weights` and net of `exit_fee_bps`
+    pub fn withdrawal_amounts(
+        &self,
+        target_weights: &[u64],
+        lp_amount: u64,
+        total_lp_supply: u64,
+        exit_fee_bps: u64,
+        split: WithdrawSplit,
+    ) -> Result<Vec<u64>, QuoteError> {
+        // LP burned outside `withdraw` can leave positions claiming more than exists
+        if lp_amount > total_lp_supply || total_lp_supply == 0 {
+            return Err(QuoteError::InsufficientLiquidity);
+        }
+
+        // Calculate token amounts to withdraw based on share of pool
+        let proportional_amounts = self
+            .reserves
+            .iter()
+            .map(|&reserve| rounding::lp_payout(reserve, lp_amount, total_lp_supply))
+            .collect::<Option<Vec<u64>>>()
+            .ok_or(QuoteError::MathOverflow)?;
+
+        let gross_amounts = match split {
+            WithdrawSplit::Proportional => proportional_amounts,
+            // Skewing the payout must not change its value at the curve's prices
+            WithdrawSplit::Balanced => {
+                let prices = calculate_curve_prices(self.reserves, self.amplification)
+                    .ok_or(QuoteError::MathOverflow)?;
+                calculate_balanced_withdrawal(
+                    self.reserves,
+                    target_weights,
+                    &prices,
+                    calculate_value_at_prices(&proportional_amounts, &prices),
+                )
+                .ok_or(QuoteError::MathOverflow)?
+            }
+        };
+
+        // The exit fee is left in the pool for the remaining LPs
+        Ok(gross_amounts
+            .iter()
+            .map(|&gross_amount| {
+                gross_amount - rounding::fee(gross_amount, exit_fee_bps, BPS_DENOMINATOR)
+            })
+            .collect())
+    }
+}
@@ -0,0 +1,139 @@
+//! Rounding policy for every division that moves value between a user and the pool
+//!
+//! Whatever the pool pays out rounds down and whatever it charges rounds up, so
+//! truncation can never be farmed at the expense of LPs:
+//!
+//! | Quantity                              | Direction |
+//! |---------------------------------------|-----------|
+//! | Swap output                           | Down      |
+//! | LP minted for a deposit               | Down      |
+//! | Tokens paid out for burned LP         | Down      |
+//! | Tokens charged for an exact LP mint   | Up        |
+//! | LP burned for exact token payouts     | Up        |
+//! | Swap, surcharge and exit fees         | Up        |
+
+/// Direction a division rounds in
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Rounding {
+    Down,
+    Up,
+}
+
+/// Units kept back from a solved swap output, covering the Newton solver
+/// converging to within one unit of the true reserve
+pub const SWAP_SOLVER_MARGIN: u128 = 1;
+
+/// `value * numerator / denominator` in u128, rounded as asked
+///
+/// # Returns
+/// * None on a zero denominator or a result that does not fit in u64
+pub fn mul_div(value: u64, numerator: u64, denominator: u64, rounding: Rounding) -> Option<u64> {
+    mul_div_u128(
+        value as u128,
+        numerator as u128,
+        denominator as u128,
+        rounding,
+    )
+    .and_then(|result| u64::try_from(result).ok())
+}
+
+/// `value * numerator / denominator` on u128 operands, rounded as asked
+pub fn mul_div_u128(
+    value: u128,
+    numerator: u128,
+    denominator: u128,
+    rounding: Rounding,
+) -> Option<u128> {
+    if denominator == 0 {
+        return None;
+    }
+    let product = value.checked_mul(numerator)?;
+    Some(match rounding {
+        Rounding::Down => product / denominator,
+        Rounding::Up => product.div_ceil(denominator),
+    })
+}
+
+/// Fee of `rate / denominator` on `amount`, rounded up and never above `amount`
+pub fn fee(amount: u64, rate: u64, denominator: u64) -> u64 {
+    mul_div(amount, rate, denominator, Rounding::Up).map_or(amount, |fee| fee.min(amount))
+}
+
+/// Swap output leaving `new_y_reserve` of a `y_reserve`, rounded down past solver error
+pub fn swap_output(y_reserve: u64, new_y_reserve: u128) -> u64 {
+    (y_reserve as u128)
+        .saturating_sub(new_y_reserve)
+        .saturating_sub(SWAP_SOLVER_MARGIN) as u64
+}
+
+/// LP minted for growing the invariant from `old_d` by `d_growth`, rounded down
+pub fn lp_minted(lp_supply: u64, d_growth: u64, old_d: u64) -> Option<u64> {
+    mul_div(lp_supply, d_growth, old_d, Rounding::Down)
+}
+
+/// Share of `reserve` paid out for burning `lp_amount` of `lp_supply`, rounded down
+pub fn lp_payout(reserve: u64, lp_amount: u64, lp_supply: u64) -> Option<u64> {
+    mul_div(reserve, lp_amount, lp_supply, Rounding::Down)
+}
+
+/// Share of `reserve` charged for minting exactly `lp_amount` of `lp_supply`, rounded up
+pub fn lp_cost(reserve: u64, lp_amount: u64, lp_supply: u64) -> Option<u64> {
+    mul_div(reserve, lp_amount, lp_supply, Rounding::Up)
+}
+
+/// LP burned for shrinking the invariant from `old_d` by `d_drop`, rounded up
+pub fn lp_burned(lp_supply: u64, d_drop: u64, old_d: u64) -> Option<u64> {
+    mul_div(lp_supply, d_drop, old_d, Rounding::Up)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mul_div_rounds_in_the_requested_direction() {
+        assert_eq!(mul_div(10, 1, 3, Rounding::Down), Some(3));
+        assert_eq!(mul_div(10, 1, 3, Rounding::Up), Some(4));
+        assert_eq!(mul_div(9, 1, 3, Rounding::Up), Some(3));
+        assert_eq!(
+            mul_div(u64::MAX, u64::MAX, u64::MAX, Rounding::Down),
+            Some(u64::MAX)
+        );
+        assert_eq!(mul_div(u64::MAX, 2, 1, Rounding::Down), None);
+        assert_eq!(mul_div(1, 1, 0, Rounding::Up), None);
+    }
+
+    #[test]
+    fn charges_round_up_and_payouts_round_down() {
+        // Any nonzero fee costs at least one unit, but never more than the amount
+        assert_eq!(fee(999, 1, 1000), 1);
+        assert_eq!(fee(1000, 1, 1000), 1);
+        assert_eq!(fee(1001, 1, 1000), 2);
+        assert_eq!(fee(5, 0, 1000), 0);
+        assert_eq!(fee(5, 2000, 1000), 5);
+
+        // A third of the supply is worth a third of the reserve, less the odd unit
+        assert_eq!(lp_payout(100, 1, 3), Some(33));
+        assert_eq!(lp_cost(100, 1, 3), Some(34));
+        assert_eq!(lp_minted(3, 1, 100), Some(0));
+        assert_eq!(lp_burned(3, 1, 100), Some(1));
+    }
+
+    #[test]
+    fn round_trips_never_profit_the_user() {
+        let (reserve, lp_supply) = (1_000_003, 999_983);
+        for lp_amount in [1, 7, 1_000, 333_331] {
+            let cost = lp_cost(reserve, lp_amount, lp_supply).unwrap();
+            let new_reserve = reserve + cost;
+            let new_supply = lp_supply + lp_amount;
+            assert!(lp_payout(new_reserve, lp_amount, new_supply).unwrap() <= cost);
+        }
+    }
+
+    #[test]
+    fn swap_output_keeps_a_solver_margin() {
+        assert_eq!(swap_output(1_000, 900), 99);
+        assert_eq!(swap_output(1_000, 1_000), 0);
+        assert_eq!(swap_output(1_000, 1_200), 0);
+    }
+}
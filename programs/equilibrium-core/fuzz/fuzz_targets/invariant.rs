@@ -0,0 +1,30 @@
+//! `calculate_invariant` against the BigUint reference for 2- and 3-token pools
+
+#![no_main]
+
+use equilibrium_core::state::math::{calculate_invariant, MAX_AMP, MIN_AMP};
+use libfuzzer_sys::fuzz_target;
+use stableswap_reference::{within_imbalance, MAX_RESERVE};
+
+fuzz_target!(|input: ([u64; 3], bool, u64)| {
+    let (raw_reserves, three_tokens, raw_amp) = input;
+    let count = if three_tokens { 3 } else { 2 };
+    let reserves: Vec<u64> = raw_reserves[..count]
+        .iter()
+        .map(|&reserve| reserve % MAX_RESERVE + 1)
+        .collect();
+    if !within_imbalance(&reserves) {
+        return;
+    }
+    let amplification = MIN_AMP + raw_amp % (MAX_AMP - MIN_AMP + 1);
+
+    let expected = stableswap_reference::invariant(&reserves, amplification).unwrap();
+    let actual = calculate_invariant(&reserves, amplification)
+        .unwrap_or_else(|| panic!("no invariant for {reserves:?} at A={amplification}"));
+
+    // Newton stops within one unit and integer division truncates each step
+    assert!(
+        actual.abs_diff(expected) <= 2,
+        "D={actual}, reference {expected} for {reserves:?} at A={amplification}"
+    );
+});
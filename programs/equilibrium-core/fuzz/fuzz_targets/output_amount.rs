@@ -0,0 +1,36 @@
+//! `calculate_output_amount` against the BigUint reference for 2-token swaps
+
+#![no_main]
+
+use equilibrium_core::state::math::{calculate_output_amount, MAX_AMP, MAX_SWAP_FEE, MIN_AMP};
+use libfuzzer_sys::fuzz_target;
+use stableswap_reference::{within_imbalance, MAX_RESERVE};
+
+fuzz_target!(|input: (u64, u64, u64, u64, u64)| {
+    let (raw_amount, raw_x, raw_y, raw_fee, raw_amp) = input;
+    let x_reserve = raw_x % MAX_RESERVE + 1;
+    let y_reserve = raw_y % MAX_RESERVE + 1;
+    if !within_imbalance(&[x_reserve, y_reserve]) {
+        return;
+    }
+    // Swaps are capped well below the reserve on-chain; stay within one reserve
+    let amount = raw_amount % x_reserve + 1;
+    let fee = raw_fee % (MAX_SWAP_FEE + 1);
+    let amplification = MIN_AMP + raw_amp % (MAX_AMP - MIN_AMP + 1);
+
+    let expected =
+        stableswap_reference::output_amount(amount, x_reserve, y_reserve, fee, amplification)
+            .unwrap();
+    let actual = calculate_output_amount(amount, x_reserve, y_reserve, fee, amplification)
+        .unwrap_or_else(|| panic!("no output for {amount} into {x_reserve}/{y_reserve}"));
+
+    assert!(
+        actual <= y_reserve,
+        "output {actual} exceeds reserve {y_reserve}"
+    );
+    assert!(
+        actual.abs_diff(expected) <= 2,
+        "output {actual}, reference {expected} for {amount} into {x_reserve}/{y_reserve} \
+         at fee={fee} A={amplification}"
+    );
+});
@@ -40,4 +40,240 @@ pub enum ErrorCode {
     
     #[msg("Unauthorized")]
     Unauthorized,
-}
\ No newline at end of file
+    
+    #[msg("Limit price not reached")]
+    LimitPriceNotReached,
+    
+    #[msg("DCA execution not due yet")]
+    DcaNotDue,
+    
+    #[msg("DCA budget exhausted")]
+    DcaBudgetExhausted,
+    
+    #[msg("Invalid fee parameter")]
+    InvalidFee,
+    
+    #[msg("Rebalance exceeds the per-epoch limit")]
+    RebalanceLimitExceeded,
+    
+    #[msg("Rebalance must move an over-weighted token into an under-weighted one")]
+    InvalidRebalance,
+    
+    #[msg("Program is not whitelisted")]
+    ProgramNotWhitelisted,
+    
+    #[msg("Growth pool is missing its partner token mint")]
+    MissingPartnerMint,
+    
+    #[msg("Swap amount is below the pool minimum")]
+    MinSwapAmount,
+    
+    #[msg("Swap exceeds the maximum trade size for this pool")]
+    MaxTradeSizeExceeded,
+    
+    #[msg("Invalid liquidity bootstrapping schedule")]
+    InvalidLbpSchedule,
+    
+    #[msg("Partner token mint is not approved")]
+    PartnerNotApproved,
+    
+    #[msg("Fee recipient account is missing or invalid")]
+    InvalidFeeRecipient,
+    
+    #[msg("Invalid oracle price")]
+    InvalidOraclePrice,
+    
+    #[msg("Token is frozen")]
+    TokenFrozen,
+    
+    #[msg("Transaction deadline has passed")]
+    DeadlineExceeded,
+    
+    #[msg("No rewards to compound")]
+    NothingToCompound,
+    
+    #[msg("Amount too small to mint or redeem any shares")]
+    ZeroShares,
+    
+    #[msg("Swap hook program is not approved")]
+    InvalidSwapHook,
+    
+    #[msg("Pool stats were sampled less than an hour ago")]
+    PoolStatsTooRecent,
+    
+    #[msg("A bootstrap phase needs a non-zero per-wallet deposit limit")]
+    InvalidBootstrapConfig,
+    
+    #[msg("Deposit exceeds the per-wallet limit for the bootstrap phase")]
+    BootstrapDepositLimitExceeded,
+    
+    #[msg("No protocol fees accrued for this token")]
+    NothingToCollect,
+    
+    #[msg("Withdrawal is below the pool's staging threshold or staging is disabled")]
+    StagedWithdrawNotAllowed,
+    
+    #[msg("Staged withdrawal delay has not elapsed")]
+    StagedWithdrawNotReady,
+    
+    #[msg("Program version was not bumped since the last recorded upgrade")]
+    VersionNotIncreased,
+    
+    #[msg("Feature is not enabled")]
+    FeatureDisabled,
+    
+    #[msg("Vote escrow locking is disabled")]
+    VeLockDisabled,
+    
+    #[msg("Unlock time must be in the future, within the maximum lock and not earlier than the current one")]
+    InvalidLockDuration,
+    
+    #[msg("Lock has not expired yet")]
+    LockNotExpired,
+    
+    #[msg("Swap would decrease the pool invariant")]
+    InvariantViolated,
+    
+    #[msg("Crank tip accounts do not match the pool")]
+    InvalidCrankTip,
+    
+    #[msg("Pool oracle price is stale")]
+    StalePool,
+    
+    #[msg("Position transfer must move a positive amount it holds to another wallet")]
+    InvalidPositionTransfer,
+    
+    #[msg("Pool token account is not the pool's vault for that token")]
+    VaultMismatch,
+    
+    #[msg("Token mint does not match the pool's mint at that position")]
+    MintOrderMismatch,
+    
+    #[msg("Pool is paused")]
+    PoolPaused,
+    
+    #[msg("Guardian list is full, already holds the key, or does not hold it")]
+    InvalidGuardian,
+    
+    #[msg("Relayed swap is not preceded by an ed25519 verification of the order by its user")]
+    InvalidOrderSignature,
+    
+    #[msg("Relayed order does not match the swap accounts or its nonce was already used")]
+    InvalidRelayedOrder,
+    
+    #[msg("USD* mint must be the Seed Pool's LP mint")]
+    InvalidUsdStarMint,
+    
+    #[msg("Lock duration must be positive and within the maximum, with the cliff no longer than it")]
+    InvalidLockSchedule,
+    
+    #[msg("Pool LP is soulbound and cannot leave its position")]
+    LpNotTransferable,
+    
+    #[msg("A soulbound pool needs the creator's position to hold its initial LP")]
+    CreatorPositionRequired,
+    
+    #[msg("Parameter batch is empty, too long or sets a parameter twice")]
+    InvalidPoolParams,
+    
+    #[msg("Program whitelist is full, already holds the entry, or does not hold it")]
+    InvalidWhitelistedProgram,
+    
+    #[msg("Voting snapshot slot has not been reached yet")]
+    SnapshotSlotNotReached,
+    
+    #[msg("Position changed at or after the voting snapshot slot")]
+    PositionChangedSinceSnapshot,
+    
+    #[msg("Pool is permissioned: the user must hold its credential token")]
+    CredentialRequired,
+    
+    #[msg("Config is already at the current layout version")]
+    ConfigAlreadyMigrated,
+    
+    #[msg("Only a frozen token can be removed from a pool")]
+    TokenNotFrozen,
+    
+    #[msg("Pool vault still holds tokens, reserves or protocol fees")]
+    VaultNotEmpty,
+    
+    #[msg("Swap would push the pair's price outside the pool's price band")]
+    PriceOutOfBounds,
+    
+    #[msg("Pool runs batch auctions; submit a swap intent instead")]
+    BatchModeActive,
+    
+    #[msg("Pool does not run batch auctions")]
+    BatchModeInactive,
+    
+    #[msg("Batch window has closed; settle the batch first")]
+    BatchWindowClosed,
+    
+    #[msg("Batch window is still open")]
+    BatchWindowOpen,
+    
+    #[msg("Batch holds the maximum number of intents")]
+    BatchFull,
+    
+    #[msg("Swap intent has not been settled yet")]
+    IntentNotSettled,
+    
+    #[msg("Portfolio indexes the maximum number of positions")]
+    PortfolioFull,
+    
+    #[msg("Pool vault holds less than its recorded reserve; queue the withdrawal")]
+    VaultShortfall,
+    
+    #[msg("Pool vaults hold their recorded reserves; withdraw directly")]
+    VaultsBacked,
+    
+    #[msg("Withdraw request is not next in the queue")]
+    NotNextInQueue,
+    
+    #[msg("Integrator fee exceeds the maximum")]
+    IntegratorFeeTooHigh,
+    
+    #[msg("Integrator fee set without an integrator fee or referral account")]
+    MissingIntegratorFeeAccount,
+    
+    #[msg("Swap's price impact exceeds the caller's maximum")]
+    PriceImpactExceeded,
+    
+    #[msg("Pool still has LP outstanding, so its reserves are not orphaned")]
+    LpSupplyOutstanding,
+    
+    #[msg("Swap takes an integrator fee account or a referral account, not both")]
+    ConflictingIntegratorFeeAccounts,
+    
+    #[msg("No referral fees accrued for this token")]
+    NoReferralFeesOwed,
+    
+    #[msg("Vote escrow was not locked under this config's governance token")]
+    VoteEscrowMismatch,
+    
+    #[msg("Vote escrow changed at or after the voting snapshot slot")]
+    VoteEscrowChangedSinceSnapshot,
+    
+    #[msg("Pool holds reserves with no LP outstanding; recover them before depositing")]
+    OrphanedReservesPending,
+    
+    #[msg("Swap intent is below the batch minimum share of the pool reserve")]
+    IntentTooSmall,
+
+    #[msg("Voting checkpoints need the owner's LP token account unless the pool is soulbound")]
+    LpTokenAccountRequired,
+}
+
+impl From<equilibrium_math::pricing::QuoteError> for ErrorCode {
+    fn from(error: equilibrium_math::pricing::QuoteError) -> Self {
+        use equilibrium_math::pricing::QuoteError;
+        match error {
+            QuoteError::InvalidSwap => ErrorCode::InvalidSwap,
+            QuoteError::TokenFrozen => ErrorCode::TokenFrozen,
+            QuoteError::MinSwapAmount => ErrorCode::MinSwapAmount,
+            QuoteError::MaxTradeSizeExceeded => ErrorCode::MaxTradeSizeExceeded,
+            QuoteError::InsufficientLiquidity => ErrorCode::InsufficientLiquidity,
+            QuoteError::MathOverflow => ErrorCode::MathOverflow,
+        }
+    }
+}
@@ -0,0 +1,521 @@
+use crate::state::pool::TokenStatus;
+use crate::state::user::UserPosition;
+use anchor_lang::prelude::*;
+
+/// Emitted when a pool token moves between depeg states
+#[event]
+pub struct TokenStatusChanged {
+    /// Pool holding the token
+    pub pool: Pubkey,
+
+    /// Token whose status changed
+    pub token_mint: Pubkey,
+
+    /// Status before the change
+    pub previous_status: TokenStatus,
+
+    /// Status after the change
+    pub status: TokenStatus,
+
+    /// Token weight at the time of the change (in basis points)
+    pub weight_bps: u64,
+}
+
+/// Reason a user position was modified
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PositionChangeReason {
+    Deposit,
+    Withdraw,
+    Claim,
+    Transfer,
+    Autocompounder,
+    LiquidityLock,
+}
+
+/// Emitted whenever a `UserPosition` changes, so off-chain trackers can rebuild position history
+#[event]
+pub struct PositionChanged {
+    /// Position account
+    pub position: Pubkey,
+
+    /// Position owner
+    pub owner: Pubkey,
+
+    /// Pool the position belongs to
+    pub pool: Pubkey,
+
+    /// What caused the change
+    pub reason: PositionChangeReason,
+
+    /// LP amount before the change
+    pub lp_amount_before: u64,
+
+    /// LP amount after the change
+    pub lp_amount_after: u64,
+
+    /// Price bounds before the change
+    pub min_price_before: u64,
+    pub max_price_before: u64,
+
+    /// Price bounds after the change
+    pub min_price_after: u64,
+    pub max_price_after: u64,
+
+    /// Timestamp of the change
+    pub timestamp: i64,
+
+    /// Pool LP virtual price after the change (1e18 fixed point)
+    pub virtual_price: u128,
+}
+
+impl PositionChanged {
+    /// Change to the LP of `position` (at `key`) alone, its price bounds untouched
+    pub fn lp_only(
+        key: Pubkey,
+        position: &UserPosition,
+        reason: PositionChangeReason,
+        lp_amount_before: u64,
+        virtual_price: u128,
+    ) -> Self {
+        Self {
+            position: key,
+            owner: position.owner,
+            pool: position.pool,
+            reason,
+            lp_amount_before,
+            lp_amount_after: position.lp_amount,
+            min_price_before: position.min_price,
+            max_price_before: position.max_price,
+            min_price_after: position.min_price,
+            max_price_after: position.max_price,
+            timestamp: position.last_update,
+            virtual_price,
+        }
+    }
+}
+
+/// Emitted when the keeper rebalances a pool through the external venue
+#[event]
+pub struct Rebalanced {
+    /// Pool that was rebalanced
+    pub pool: Pubkey,
+
+    /// Keeper that ran the rebalance
+    pub keeper: Pubkey,
+
+    /// Over-weighted token sold
+    pub token_mint_from: Pubkey,
+
+    /// Under-weighted token bought
+    pub token_mint_to: Pubkey,
+
+    /// Amount that left the pool
+    pub amount_in: u64,
+
+    /// Amount that arrived in the pool
+    pub amount_out: u64,
+
+    /// Epoch the rebalance was counted against
+    pub epoch: u64,
+
+    /// Pool LP virtual price after the rebalance (1e18 fixed point)
+    pub virtual_price: u128,
+}
+
+/// Emitted for every swap against a pool
+#[event]
+pub struct SwapExecuted {
+    /// Pool swapped against
+    pub pool: Pubkey,
+
+    /// Trader
+    pub user: Pubkey,
+
+    /// Token account the output was delivered to
+    pub recipient: Pubkey,
+
+    /// Token sold to the pool
+    pub token_mint_in: Pubkey,
+
+    /// Token bought from the pool
+    pub token_mint_out: Pubkey,
+
+    /// Amount sold, as received by the pool after any transfer fee
+    pub amount_in: u64,
+
+    /// Amount bought
+    pub amount_out: u64,
+
+    /// Fee charged, in input token units
+    pub fee_amount: u64,
+
+    /// Pool LP virtual price after the swap (1e18 fixed point)
+    pub virtual_price: u128,
+}
+
+/// Emitted alongside `SwapExecuted` when the swap paid its front end a fee
+#[event]
+pub struct IntegratorFeePaid {
+    /// Pool swapped against
+    pub pool: Pubkey,
+
+    /// Trader
+    pub user: Pubkey,
+
+    /// Token account the fee was paid to, or the referral account it accrued to
+    pub integrator_fee_account: Pubkey,
+
+    /// Token the fee was paid in, the swap's input
+    pub token_mint: Pubkey,
+
+    /// Fee rate the trader signed for
+    pub integrator_fee_bps: u64,
+
+    /// Fee sent or accrued, on top of the swap's amount in
+    pub fee_amount: u64,
+}
+
+/// Emitted when a referrer claims the fees accrued to one of their referral accounts
+#[event]
+pub struct ReferralFeesClaimed {
+    /// Pool whose swaps paid the fees
+    pub pool: Pubkey,
+
+    /// Referrer claiming
+    pub referrer: Pubkey,
+
+    /// Token the fees were paid in
+    pub token_mint: Pubkey,
+
+    /// Token account the fees were sent to
+    pub destination: Pubkey,
+
+    /// Amount claimed
+    pub amount: u64,
+}
+
+/// Emitted when the keeper redeposits an autocompounder's rewards as liquidity
+#[event]
+pub struct Compounded {
+    /// Pool the rewards were deposited into
+    pub pool: Pubkey,
+
+    /// Autocompounder that held the rewards
+    pub autocompounder: Pubkey,
+
+    /// Reward amounts deposited, one per pool token
+    pub amounts: Vec<u64>,
+
+    /// LP minted into the vault
+    pub lp_minted: u64,
+
+    /// LP held for shareholders after compounding
+    pub vault_lp: u64,
+
+    /// Outstanding shares against the vault
+    pub share_supply: u64,
+}
+
+/// Emitted when a pool's accrued protocol fees move to the treasury
+#[event]
+pub struct ProtocolFeesCollected {
+    /// Pool the fees were charged in
+    pub pool: Pubkey,
+
+    /// Token the fees were paid in
+    pub token_mint: Pubkey,
+
+    /// Amount moved to the treasury vault
+    pub amount: u64,
+}
+
+/// Emitted when reserves left in a pool with no LP supply move to the treasury
+#[event]
+pub struct OrphanedReservesRecovered {
+    /// Pool the reserves were left in
+    pub pool: Pubkey,
+
+    /// Token recovered
+    pub token_mint: Pubkey,
+
+    /// Amount moved to the treasury vault
+    pub amount: u64,
+}
+
+/// Emitted when the config authority withdraws from a treasury vault
+#[event]
+pub struct TreasurySwept {
+    /// Token withdrawn
+    pub token_mint: Pubkey,
+
+    /// Token account that received the funds
+    pub destination: Pubkey,
+
+    /// Amount withdrawn
+    pub amount: u64,
+}
+
+/// Emitted when a large exit is staged, warning keepers ahead of the withdrawal
+#[event]
+pub struct WithdrawRequested {
+    /// Pool being exited
+    pub pool: Pubkey,
+
+    /// Position the LP is withdrawn from
+    pub position: Pubkey,
+
+    /// LP amount to be burned
+    pub lp_amount: u64,
+
+    /// Share of the LP supply being withdrawn (in basis points)
+    pub share_bps: u64,
+
+    /// Earliest timestamp the withdrawal can execute
+    pub executable_at: i64,
+}
+
+/// Emitted when a withdrawal is booked while a vault is short and its tokens queued
+#[event]
+pub struct WithdrawQueued {
+    /// Pool owing the tokens
+    pub pool: Pubkey,
+
+    /// Position the LP was withdrawn from
+    pub position: Pubkey,
+
+    /// Position owner, paid on fulfillment
+    pub owner: Pubkey,
+
+    /// Place in the pool's withdraw queue
+    pub seq: u64,
+
+    /// LP amount burned
+    pub lp_amount: u64,
+
+    /// Tokens owed, indexed by pool slot
+    pub amounts: Vec<u64>,
+}
+
+/// Emitted when the request at the head of a pool's withdraw queue is paid
+#[event]
+pub struct WithdrawRequestFulfilled {
+    /// Pool that paid
+    pub pool: Pubkey,
+
+    /// Owner paid
+    pub owner: Pubkey,
+
+    /// Place the request held in the queue
+    pub seq: u64,
+
+    /// Tokens paid, indexed by pool slot
+    pub amounts: Vec<u64>,
+}
+
+/// Emitted when a pool is paused or unpaused
+#[event]
+pub struct PoolPauseChanged {
+    /// Pool whose pause state changed
+    pub pool: Pubkey,
+
+    /// Whether the pool is now paused
+    pub paused: bool,
+
+    /// Guardian or authority that signed the change
+    pub signer: Pubkey,
+}
+
+/// Emitted when a permissionless crank is tipped out of protocol fees
+#[event]
+pub struct CrankTipPaid {
+    /// Pool whose protocol fees funded the tip
+    pub pool: Pubkey,
+
+    /// Token the tip was paid in
+    pub token_mint: Pubkey,
+
+    /// Token account that received the tip
+    pub recipient: Pubkey,
+
+    /// Amount paid
+    pub amount: u64,
+}
+
+/// Kind of flow a `LargeFlowEvent` reports
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum FlowDirection {
+    Swap,
+    Withdraw,
+}
+
+/// Emitted when a single swap or withdrawal drains at least the pool's
+/// `large_flow_threshold_bps` of a token's reserve, so monitors can filter on it alone
+#[event]
+pub struct LargeFlowEvent {
+    /// Pool the tokens left
+    pub pool: Pubkey,
+
+    /// Trader or position owner behind the flow
+    pub user: Pubkey,
+
+    /// Whether the flow was a swap or a withdrawal
+    pub direction: FlowDirection,
+
+    /// Token whose reserve fell by the largest share
+    pub token_mint: Pubkey,
+
+    /// Amount of that token that left the reserves
+    pub amount: u64,
+
+    /// `amount` as a share of the token's reserve before the flow (in basis points)
+    pub reserve_share_bps: u64,
+
+    /// Pool weights before and after the flow (in basis points)
+    pub weights_before: Vec<u64>,
+    pub weights_after: Vec<u64>,
+}
+
+/// Emitted when a config is upgraded to a newer layout version
+#[event]
+pub struct ConfigMigrated {
+    /// Config that was migrated
+    pub amm_config: Pubkey,
+
+    /// Layout version before the migration
+    pub from_version: u8,
+
+    /// Layout version after the migration
+    pub to_version: u8,
+}
+
+/// Emitted when a batch auction settles
+#[event]
+pub struct BatchSettled {
+    /// Pool the batch traded against
+    pub pool: Pubkey,
+
+    /// Id of the settled batch
+    pub batch_id: u64,
+
+    /// Input cleared from each token's sellers; refunded intents are left out
+    pub amounts_in: [u64; 2],
+
+    /// Output shared among each token's sellers, so each side's price is
+    /// `amounts_out[i] / amounts_in[i]`
+    pub amounts_out: [u64; 2],
+
+    /// Token the batch's net imbalance was sold into the pool in
+    pub net_token_in_index: u8,
+
+    /// Net input routed through the curve; the rest was matched peer to peer
+    pub net_amount_in: u64,
+
+    /// Intents filled and refunded
+    pub filled: u32,
+    pub refunded: u32,
+}
+
+/// Emitted when a token is added to an existing pool
+#[event]
+pub struct PoolTokenAdded {
+    /// Pool the token was added to
+    pub pool: Pubkey,
+
+    /// Token that was added
+    pub token_mint: Pubkey,
+
+    /// Vault created for the token
+    pub vault: Pubkey,
+
+    /// Index of the new token, always the last
+    pub token_index: u8,
+
+    /// Opening reserve seeded by the authority
+    pub initial_amount: u64,
+
+    /// LP minted to the authority for the invariant growth the seed added
+    pub lp_amount: u64,
+
+    /// Target weights after the existing ones were scaled down
+    pub target_weights: Vec<u64>,
+}
+
+/// Emitted when an empty vault is closed and its token removed from a pool
+#[event]
+pub struct PoolVaultClosed {
+    /// Pool the token was removed from
+    pub pool: Pubkey,
+
+    /// Token that was removed
+    pub token_mint: Pubkey,
+
+    /// Vault that was closed
+    pub vault: Pubkey,
+
+    /// Index the token held before removal; later tokens shift down by one
+    pub token_index: u8,
+
+    /// Target weights of the remaining tokens after rescaling (in basis points)
+    pub target_weights: Vec<u64>,
+}
+
+/// Emitted when the config authority changes the config-wide protocol fee share
+#[event]
+pub struct ConfigUpdated {
+    /// Config that was updated
+    pub amm_config: Pubkey,
+
+    /// Authority that signed the change
+    pub authority: Pubkey,
+
+    /// Protocol fee share before and after the change (in basis points)
+    pub old_protocol_fee_share_bps: u64,
+    pub new_protocol_fee_share_bps: u64,
+}
+
+/// Emitted when LP is locked on a vesting schedule
+#[event]
+pub struct LiquidityLocked {
+    /// Pool whose LP was locked
+    pub pool: Pubkey,
+
+    /// Wallet that locked it
+    pub owner: Pubkey,
+
+    /// Lock account holding the schedule
+    pub locked_position: Pubkey,
+
+    /// LP amount locked
+    pub lp_amount: u64,
+
+    /// Timestamp before which nothing can be released
+    pub cliff_ts: i64,
+
+    /// Timestamp at which everything is released
+    pub end_ts: i64,
+}
+
+/// Emitted for a basket swap selling several tokens for one
+#[event]
+pub struct BasketSwapExecuted {
+    /// Pool swapped against
+    pub pool: Pubkey,
+
+    /// Trader
+    pub user: Pubkey,
+
+    /// Token bought from the pool
+    pub token_mint_out: Pubkey,
+
+    /// Amount of each pool token sold, as received by the pool after any transfer fee
+    pub amounts_in: Vec<u64>,
+
+    /// Amount bought
+    pub amount_out: u64,
+
+    /// Fee charged on each input, in that token's units
+    pub fee_amounts: Vec<u64>,
+
+    /// Pool LP virtual price after the swap (1e18 fixed point)
+    pub virtual_price: u128,
+}
@@ -0,0 +1,136 @@
+use crate::errors::ErrorCode;
+use crate::events::PoolTokenAdded;
+use crate::state::*;
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Mint, MintTo, Token, TokenAccount, Transfer};
+
+#[derive(Accounts)]
+pub struct AddPoolToken<'info> {
+    /// Config authority, paying for the new vault and the larger pool account
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        has_one = authority @ ErrorCode::Unauthorized,
+    )]
+    pub amm_config: Account<'info, AmmConfig>,
+
+    #[account(
+        mut,
+        has_one = amm_config,
+        has_one = lp_mint @ ErrorCode::InvalidTokenMint,
+        constraint = pool.pool_type == PoolType::Seed @ ErrorCode::InvalidPoolType,
+        realloc = Pool::space(pool.token_mints.len() + 1),
+        realloc::payer = authority,
+        realloc::zero = false,
+    )]
+    pub pool: Account<'info, Pool>,
+
+    #[account(mut)]
+    pub lp_mint: Account<'info, Mint>,
+
+    /// Token being added
+    pub token_mint: Account<'info, Mint>,
+
+    /// Vault for the new token, at the same address `create_seed_pool` would derive
+    #[account(
+        init,
+        payer = authority,
+        token::mint = token_mint,
+        token::authority = pool,
+        seeds = [&b"pool-token"[..], pool.key().as_ref(), token_mint.key().as_ref()],
+        bump
+    )]
+    pub vault: Account<'info, TokenAccount>,
+
+    /// Authority's account of the new token, funding its opening reserve
+    #[account(
+        mut,
+        token::authority = authority,
+        token::mint = token_mint,
+    )]
+    pub authority_token: Account<'info, TokenAccount>,
+
+    /// Authority's LP account, credited for the opening reserve
+    #[account(
+        mut,
+        token::authority = authority,
+        token::mint = lp_mint,
+    )]
+    pub authority_lp_token: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Add `token_mint` to a Seed Pool at `target_weight`, seeded with `initial_amount`
+///
+/// The existing target weights are scaled down to share the rest of the 10000.
+/// The authority is minted LP for the invariant growth the opening reserve adds,
+/// so existing LPs keep their share of the pool without migrating.
+pub fn add_pool_token(
+    ctx: Context<AddPoolToken>,
+    target_weight: u64,
+    initial_amount: u64,
+) -> Result<()> {
+    require!(initial_amount > 0, ErrorCode::InsufficientLiquidity);
+    let pool = &ctx.accounts.pool;
+    let lp_supply = pool.lp_supply(ctx.accounts.lp_mint.supply);
+    require!(lp_supply > 0, ErrorCode::InsufficientLiquidity);
+    let old_d = crate::state::math::calculate_invariant(&pool.reserves, pool.amplification)
+        .ok_or(ErrorCode::InsufficientLiquidity)?;
+
+    // CPI phase: seed the new vault
+    let cpi_accounts = Transfer {
+        from: ctx.accounts.authority_token.to_account_info(),
+        to: ctx.accounts.vault.to_account_info(),
+        authority: ctx.accounts.authority.to_account_info(),
+    };
+    let cpi_program = ctx.accounts.token_program.to_account_info();
+    token::transfer(CpiContext::new(cpi_program, cpi_accounts), initial_amount)?;
+
+    let token_mint = ctx.accounts.token_mint.key();
+    let vault = ctx.accounts.vault.key();
+    let pool = &mut ctx.accounts.pool;
+    pool.add_token(token_mint, vault, target_weight, initial_amount)?;
+
+    // Price the opening reserve like a one-sided deposit: LP for the growth in D
+    let new_d = crate::state::math::calculate_invariant(&pool.reserves, pool.amplification)
+        .ok_or(ErrorCode::MathOverflow)?;
+    let d_growth = new_d
+        .checked_sub(old_d)
+        .ok_or(ErrorCode::InvariantViolated)?;
+    let lp_amount = crate::state::rounding::lp_minted(lp_supply, d_growth, old_d)
+        .ok_or(ErrorCode::MathOverflow)?;
+
+    let clock = Clock::get()?;
+    pool.last_d = new_d;
+    pool.d_slot = clock.slot;
+    pool.refresh_virtual_price(lp_supply + lp_amount);
+    pool.refresh_routing_hint(clock.unix_timestamp);
+    pool.refresh_token_status(clock.unix_timestamp);
+
+    let pool = &ctx.accounts.pool;
+    let seeds = pool.signer_seeds()?;
+    let signer = &[&seeds[..]];
+    let cpi_accounts = MintTo {
+        mint: ctx.accounts.lp_mint.to_account_info(),
+        to: ctx.accounts.authority_lp_token.to_account_info(),
+        authority: pool.to_account_info(),
+    };
+    let cpi_program = ctx.accounts.token_program.to_account_info();
+    let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
+    token::mint_to(cpi_ctx, lp_amount)?;
+
+    emit!(PoolTokenAdded {
+        pool: pool.key(),
+        token_mint,
+        vault,
+        token_index: (pool.token_mints.len() - 1) as u8,
+        initial_amount,
+        lp_amount,
+        target_weights: pool.target_weights.clone(),
+    });
+
+    Ok(())
+}
@@ -0,0 +1,422 @@
+use crate::errors::ErrorCode;
+use crate::events::{Compounded, PositionChangeReason, PositionChanged};
+use crate::instructions::deposit::{apply_deposit, quote_deposit};
+use crate::state::*;
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Burn, Mint, MintTo, Token, TokenAccount, Transfer};
+
+#[derive(Accounts)]
+pub struct InitAutocompounder<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        has_one = authority @ ErrorCode::Unauthorized,
+    )]
+    pub amm_config: Account<'info, AmmConfig>,
+
+    #[account(
+        has_one = amm_config,
+        has_one = lp_mint,
+    )]
+    pub pool: Account<'info, Pool>,
+
+    pub lp_mint: Account<'info, Mint>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = Autocompounder::space(),
+        seeds = [&b"autocompounder"[..], pool.key().as_ref()],
+        bump
+    )]
+    pub autocompounder: Account<'info, Autocompounder>,
+
+    #[account(
+        init,
+        payer = authority,
+        seeds = [&b"autocompounder-shares"[..], autocompounder.key().as_ref()],
+        bump,
+        mint::decimals = lp_mint.decimals,
+        mint::authority = autocompounder,
+    )]
+    pub share_mint: Account<'info, Mint>,
+
+    #[account(
+        init,
+        payer = authority,
+        seeds = [&b"autocompounder-lp"[..], autocompounder.key().as_ref()],
+        bump,
+        token::mint = lp_mint,
+        token::authority = autocompounder,
+    )]
+    pub lp_vault: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+pub fn init_autocompounder(ctx: Context<InitAutocompounder>) -> Result<()> {
+    // The vault holds LP tokens, which a soulbound pool never mints
+    ctx.accounts.pool.require_transferable_lp()?;
+
+    let autocompounder = &mut ctx.accounts.autocompounder;
+    autocompounder.bump = ctx.bumps.autocompounder;
+    autocompounder.pool = ctx.accounts.pool.key();
+    autocompounder.share_mint = ctx.accounts.share_mint.key();
+    autocompounder.lp_vault = ctx.accounts.lp_vault.key();
+    autocompounder.total_lp = 0;
+    autocompounder.total_compounded_lp = 0;
+    autocompounder.compound_count = 0;
+    autocompounder.last_compound_ts = Clock::get()?.unix_timestamp;
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct AutocompounderShares<'info> {
+    /// Shareholder; pays for their position if a withdrawal opens it
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    pub pool: Account<'info, Pool>,
+
+    #[account(
+        mut,
+        has_one = pool,
+        has_one = share_mint,
+        has_one = lp_vault,
+    )]
+    pub autocompounder: Account<'info, Autocompounder>,
+
+    /// User's position, debited for LP moved into the vault and credited for LP
+    /// taken back out, so positions only ever count LP their owner holds
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = UserPosition::space(),
+        seeds = [&b"user-position"[..], user.key().as_ref(), pool.key().as_ref()],
+        bump
+    )]
+    pub user_position: Account<'info, UserPosition>,
+
+    #[account(mut)]
+    pub share_mint: Account<'info, Mint>,
+
+    #[account(mut)]
+    pub lp_vault: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        token::authority = user,
+        token::mint = lp_vault.mint,
+    )]
+    pub user_lp_token: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        token::authority = user,
+        token::mint = share_mint,
+    )]
+    pub user_share_token: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+impl AutocompounderShares<'_> {
+    fn emit_position_changed(&self, lp_amount_before: u64) {
+        emit!(PositionChanged::lp_only(
+            self.user_position.key(),
+            &self.user_position,
+            PositionChangeReason::Autocompounder,
+            lp_amount_before,
+            self.pool.virtual_price,
+        ));
+    }
+}
+
+/// Move LP into the vault in exchange for at least `min_shares_out` shares
+///
+/// The LP leaves the user's position along with their wallet, so it must be
+/// LP the position holds.
+pub fn deposit_to_autocompounder(
+    ctx: Context<AutocompounderShares>,
+    lp_amount: u64,
+    min_shares_out: u64,
+) -> Result<()> {
+    let shares = Autocompounder::shares_for_lp(
+        lp_amount,
+        ctx.accounts.autocompounder.total_lp,
+        ctx.accounts.share_mint.supply,
+    )
+    .ok_or(ErrorCode::MathOverflow)?;
+    require!(shares > 0, ErrorCode::ZeroShares);
+    require!(shares >= min_shares_out, ErrorCode::SlippageExceeded);
+
+    let cpi_accounts = Transfer {
+        from: ctx.accounts.user_lp_token.to_account_info(),
+        to: ctx.accounts.lp_vault.to_account_info(),
+        authority: ctx.accounts.user.to_account_info(),
+    };
+    let cpi_program = ctx.accounts.token_program.to_account_info();
+    token::transfer(CpiContext::new(cpi_program, cpi_accounts), lp_amount)?;
+
+    let autocompounder = &ctx.accounts.autocompounder;
+    let seeds = &[
+        &b"autocompounder"[..],
+        autocompounder.pool.as_ref(),
+        &[autocompounder.bump],
+    ];
+    let signer = &[&seeds[..]];
+    let cpi_accounts = MintTo {
+        mint: ctx.accounts.share_mint.to_account_info(),
+        to: ctx.accounts.user_share_token.to_account_info(),
+        authority: autocompounder.to_account_info(),
+    };
+    let cpi_program = ctx.accounts.token_program.to_account_info();
+    token::mint_to(
+        CpiContext::new_with_signer(cpi_program, cpi_accounts, signer),
+        shares,
+    )?;
+
+    let autocompounder = &mut ctx.accounts.autocompounder;
+    autocompounder.total_lp = autocompounder
+        .total_lp
+        .checked_add(lp_amount)
+        .ok_or(ErrorCode::MathOverflow)?;
+
+    let clock = Clock::get()?;
+    let lp_amount_before = ctx.accounts.user_position.lp_amount;
+    ctx.accounts
+        .user_position
+        .debit_lp(lp_amount, clock.unix_timestamp, clock.slot)?;
+    ctx.accounts.emit_position_changed(lp_amount_before);
+
+    Ok(())
+}
+
+/// Burn shares and take back their current LP value, credited to the user's position
+pub fn withdraw_from_autocompounder(ctx: Context<AutocompounderShares>, shares: u64) -> Result<()> {
+    let lp_amount = Autocompounder::lp_for_shares(
+        shares,
+        ctx.accounts.autocompounder.total_lp,
+        ctx.accounts.share_mint.supply,
+    )
+    .ok_or(ErrorCode::InsufficientLiquidity)?;
+    require!(lp_amount > 0, ErrorCode::ZeroShares);
+
+    let cpi_accounts = Burn {
+        mint: ctx.accounts.share_mint.to_account_info(),
+        from: ctx.accounts.user_share_token.to_account_info(),
+        authority: ctx.accounts.user.to_account_info(),
+    };
+    let cpi_program = ctx.accounts.token_program.to_account_info();
+    token::burn(CpiContext::new(cpi_program, cpi_accounts), shares)?;
+
+    let autocompounder = &ctx.accounts.autocompounder;
+    let seeds = &[
+        &b"autocompounder"[..],
+        autocompounder.pool.as_ref(),
+        &[autocompounder.bump],
+    ];
+    let signer = &[&seeds[..]];
+    let cpi_accounts = Transfer {
+        from: ctx.accounts.lp_vault.to_account_info(),
+        to: ctx.accounts.user_lp_token.to_account_info(),
+        authority: autocompounder.to_account_info(),
+    };
+    let cpi_program = ctx.accounts.token_program.to_account_info();
+    token::transfer(
+        CpiContext::new_with_signer(cpi_program, cpi_accounts, signer),
+        lp_amount,
+    )?;
+
+    let autocompounder = &mut ctx.accounts.autocompounder;
+    autocompounder.total_lp = autocompounder.total_lp.saturating_sub(lp_amount);
+
+    // Compounded LP was never in any position, so the shareholder's takes it on here
+    let clock = Clock::get()?;
+    let lp_amount_before = ctx.accounts.user_position.lp_amount;
+    ctx.accounts.user_position.credit_lp(
+        ctx.accounts.user.key(),
+        ctx.accounts.pool.key(),
+        ctx.bumps.user_position,
+        lp_amount,
+        clock.unix_timestamp,
+        clock.slot,
+    )?;
+    ctx.accounts.emit_position_changed(lp_amount_before);
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct Compound<'info> {
+    /// Crank run by the configured keeper
+    pub keeper: Signer<'info>,
+
+    #[account(
+        has_one = keeper @ ErrorCode::Unauthorized,
+    )]
+    pub amm_config: Account<'info, AmmConfig>,
+
+    #[account(
+        mut,
+        has_one = amm_config,
+        has_one = lp_mint,
+    )]
+    pub pool: Account<'info, Pool>,
+
+    #[account(mut)]
+    pub lp_mint: Account<'info, Mint>,
+
+    #[account(
+        mut,
+        has_one = pool,
+        has_one = lp_vault,
+        has_one = share_mint,
+    )]
+    pub autocompounder: Account<'info, Autocompounder>,
+
+    #[account(mut)]
+    pub lp_vault: Account<'info, TokenAccount>,
+
+    pub share_mint: Account<'info, Mint>,
+
+    /// Reward balances held by the autocompounder, one per pool token
+    #[account(
+        mut,
+        token::authority = autocompounder,
+        constraint = reward_token_a.mint == pool.token_mints[0] @ ErrorCode::InvalidTokenMint,
+    )]
+    pub reward_token_a: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        token::authority = autocompounder,
+        constraint = reward_token_b.mint == pool.token_mints[1] @ ErrorCode::InvalidTokenMint,
+    )]
+    pub reward_token_b: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        token::authority = autocompounder,
+        constraint = pool.token_mints.get(2) == Some(&reward_token_c.mint) @ ErrorCode::InvalidTokenMint,
+    )]
+    pub reward_token_c: Option<Account<'info, TokenAccount>>,
+
+    /// Pool vaults receiving the rewards
+    #[account(
+        mut,
+        constraint = pool_token_a.key() == pool.token_accounts[0] @ ErrorCode::InvalidTokenMint,
+    )]
+    pub pool_token_a: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = pool_token_b.key() == pool.token_accounts[1] @ ErrorCode::InvalidTokenMint,
+    )]
+    pub pool_token_b: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = pool.token_accounts.get(2) == Some(&pool_token_c.key()) @ ErrorCode::InvalidTokenMint,
+    )]
+    pub pool_token_c: Option<Account<'info, TokenAccount>>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+/// Redeposit every reward balance held by the autocompounder as pool liquidity
+pub fn compound(ctx: Context<Compound>) -> Result<()> {
+    // Read phase: price the rewards as a deposit
+    let reward_tokens = [
+        Some(&ctx.accounts.reward_token_a),
+        Some(&ctx.accounts.reward_token_b),
+        ctx.accounts.reward_token_c.as_ref(),
+    ];
+    let pool_tokens = [
+        Some(&ctx.accounts.pool_token_a),
+        Some(&ctx.accounts.pool_token_b),
+        ctx.accounts.pool_token_c.as_ref(),
+    ];
+    let mut rewards = [0u64; MAX_POOL_TOKENS];
+    for (reward, reward_token) in rewards.iter_mut().zip(reward_tokens) {
+        *reward = reward_token.map_or(0, |account| account.amount);
+    }
+
+    let pool = &ctx.accounts.pool;
+    pool.require_not_paused()?;
+    let amounts = pool.active_amounts(&rewards)?;
+    require!(
+        amounts.iter().any(|&amount| amount > 0),
+        ErrorCode::NothingToCompound
+    );
+    let quote = quote_deposit(pool, amounts, ctx.accounts.lp_mint.supply)?;
+    require!(quote.lp_amount > 0, ErrorCode::NothingToCompound);
+
+    // CPI phase: move rewards into the pool and mint LP to the vault
+    let autocompounder = &ctx.accounts.autocompounder;
+    let seeds = &[
+        &b"autocompounder"[..],
+        autocompounder.pool.as_ref(),
+        &[autocompounder.bump],
+    ];
+    let signer = &[&seeds[..]];
+    for (i, &amount) in amounts.iter().enumerate() {
+        if amount > 0 {
+            let (Some(from), Some(to)) = (reward_tokens[i], pool_tokens[i]) else {
+                return err!(ErrorCode::InvalidTokenMint);
+            };
+            let cpi_accounts = Transfer {
+                from: from.to_account_info(),
+                to: to.to_account_info(),
+                authority: autocompounder.to_account_info(),
+            };
+            let cpi_program = ctx.accounts.token_program.to_account_info();
+            token::transfer(
+                CpiContext::new_with_signer(cpi_program, cpi_accounts, signer),
+                amount,
+            )?;
+        }
+    }
+
+    let pool_seeds = pool.signer_seeds()?;
+    let pool_signer = &[&pool_seeds[..]];
+    let cpi_accounts = MintTo {
+        mint: ctx.accounts.lp_mint.to_account_info(),
+        to: ctx.accounts.lp_vault.to_account_info(),
+        authority: pool.to_account_info(),
+    };
+    let cpi_program = ctx.accounts.token_program.to_account_info();
+    token::mint_to(
+        CpiContext::new_with_signer(cpi_program, cpi_accounts, pool_signer),
+        quote.lp_amount,
+    )?;
+
+    // Write phase
+    let amounts = amounts.to_vec();
+    let lp_supply_after = ctx.accounts.lp_mint.supply.saturating_add(quote.lp_amount);
+    apply_deposit(&mut ctx.accounts.pool, &quote, lp_supply_after)?;
+
+    let autocompounder = &mut ctx.accounts.autocompounder;
+    autocompounder.total_lp = autocompounder.total_lp.saturating_add(quote.lp_amount);
+    autocompounder.total_compounded_lp = autocompounder
+        .total_compounded_lp
+        .saturating_add(quote.lp_amount);
+    autocompounder.compound_count = autocompounder.compound_count.saturating_add(1);
+    autocompounder.last_compound_ts = Clock::get()?.unix_timestamp;
+
+    emit!(Compounded {
+        pool: ctx.accounts.pool.key(),
+        autocompounder: autocompounder.key(),
+        amounts,
+        lp_minted: quote.lp_amount,
+        vault_lp: autocompounder.total_lp,
+        share_supply: ctx.accounts.share_mint.supply,
+    });
+
+    Ok(())
+}
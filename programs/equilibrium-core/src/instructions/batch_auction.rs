@@ -0,0 +1,461 @@
+use crate::errors::ErrorCode;
+use crate::events::BatchSettled;
+use crate::instructions::swap::{
+    book_swap, quote_swap_with_min_fee, stale_pool_min_fee, SwapQuote,
+};
+use crate::state::*;
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+use anchor_spl::token_interface;
+
+#[derive(Accounts)]
+pub struct InitSwapBatch<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(
+        constraint = pool.pool_type == PoolType::Growth @ ErrorCode::InvalidPoolType,
+    )]
+    pub pool: Account<'info, Pool>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = SwapBatch::space(),
+        seeds = [&b"swap-batch"[..], pool.key().as_ref()],
+        bump
+    )]
+    pub swap_batch: Account<'info, SwapBatch>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn init_swap_batch(ctx: Context<InitSwapBatch>) -> Result<()> {
+    let swap_batch = &mut ctx.accounts.swap_batch;
+    swap_batch.bump = ctx.bumps.swap_batch;
+    swap_batch.pool = ctx.accounts.pool.key();
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct SubmitSwapIntent<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(mut)]
+    pub pool: Account<'info, Pool>,
+
+    #[account(
+        mut,
+        has_one = pool,
+        seeds = [&b"swap-batch"[..], pool.key().as_ref()],
+        bump = swap_batch.bump,
+    )]
+    pub swap_batch: Account<'info, SwapBatch>,
+
+    /// One intent per owner per batch
+    #[account(
+        init,
+        payer = owner,
+        space = SwapIntent::space(),
+        seeds = [
+            &b"swap-intent"[..],
+            pool.key().as_ref(),
+            owner.key().as_ref(),
+            &swap_batch.batch_id.to_le_bytes(),
+        ],
+        bump
+    )]
+    pub swap_intent: Account<'info, SwapIntent>,
+
+    /// Owner's account of the token sold
+    #[account(
+        mut,
+        token::authority = owner,
+    )]
+    pub owner_token_in: Account<'info, TokenAccount>,
+
+    /// Pool vault of the token sold, holding the input until the intent is claimed
+    #[account(mut)]
+    pub pool_token_in: Account<'info, TokenAccount>,
+
+    /// Owner's token account of the pool's credential mint; required by permissioned pools
+    pub credential: Option<InterfaceAccount<'info, token_interface::TokenAccount>>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Sell `amount_in` of token `token_in_index` for the pool's other token in the open batch
+///
+/// The input moves into the pool's vault now. The intent clears with the rest of
+/// the batch in `settle_batch`, or is refunded if the clearing price pays less
+/// than `min_amount_out`.
+pub fn submit_swap_intent(
+    ctx: Context<SubmitSwapIntent>,
+    token_in_index: u8,
+    amount_in: u64,
+    min_amount_out: u64,
+) -> Result<()> {
+    let pool = &mut ctx.accounts.pool;
+    let owner = ctx.accounts.owner.key();
+    let token_in_idx = token_in_index as usize;
+    pool.require_not_paused()?;
+    require!(pool.batch_window_slots > 0, ErrorCode::BatchModeInactive);
+    crate::utils::check_credential(pool, &owner, ctx.accounts.credential.as_deref())?;
+    require!(
+        token_in_idx < pool.token_mints.len(),
+        ErrorCode::InvalidSwap
+    );
+    require!(
+        amount_in > 0 && min_amount_out > 0,
+        ErrorCode::InvalidInstructionData
+    );
+    crate::utils::check_pool_mint(pool, token_in_idx, &ctx.accounts.owner_token_in.mint)?;
+    crate::utils::check_pool_vault(pool, token_in_idx, &ctx.accounts.pool_token_in.key())?;
+    require!(
+        amount_in as u128 * crate::state::math::BPS_DENOMINATOR as u128
+            >= pool.reserves[token_in_idx] as u128 * MIN_BATCH_INTENT_BPS as u128,
+        ErrorCode::IntentTooSmall
+    );
+
+    let slot = Clock::get()?.slot;
+    let swap_batch = &mut ctx.accounts.swap_batch;
+    require!(
+        !swap_batch.is_closed(slot, pool.batch_window_slots),
+        ErrorCode::BatchWindowClosed
+    );
+    require!(
+        swap_batch.intent_count < MAX_BATCH_INTENTS,
+        ErrorCode::BatchFull
+    );
+    if swap_batch.intent_count == 0 {
+        swap_batch.opened_slot = slot;
+    }
+    swap_batch.intent_count += 1;
+    swap_batch.amounts_in[token_in_idx] = swap_batch.amounts_in[token_in_idx]
+        .checked_add(amount_in)
+        .ok_or(ErrorCode::MathOverflow)?;
+    // The input is owed back to the intent until settlement books it
+    pool.queued_owed[token_in_idx] = pool.queued_owed[token_in_idx]
+        .checked_add(amount_in)
+        .ok_or(ErrorCode::MathOverflow)?;
+
+    // CPI phase: the vault holds the input until the intent is claimed
+    let cpi_accounts = Transfer {
+        from: ctx.accounts.owner_token_in.to_account_info(),
+        to: ctx.accounts.pool_token_in.to_account_info(),
+        authority: ctx.accounts.owner.to_account_info(),
+    };
+    let cpi_program = ctx.accounts.token_program.to_account_info();
+    token::transfer(CpiContext::new(cpi_program, cpi_accounts), amount_in)?;
+
+    let swap_intent = &mut ctx.accounts.swap_intent;
+    swap_intent.bump = ctx.bumps.swap_intent;
+    swap_intent.owner = owner;
+    swap_intent.pool = pool.key();
+    swap_intent.batch_id = swap_batch.batch_id;
+    swap_intent.token_in_index = token_in_index;
+    swap_intent.amount_in = amount_in;
+    swap_intent.min_amount_out = min_amount_out;
+    swap_intent.settled = false;
+    swap_intent.amount_out = 0;
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct SettleBatch<'info> {
+    /// Anyone can crank a batch once its window has closed
+    pub keeper: Signer<'info>,
+
+    /// Config holding the protocol fee share and the stale-pool policy
+    pub amm_config: Account<'info, AmmConfig>,
+
+    #[account(
+        mut,
+        has_one = amm_config,
+    )]
+    pub pool: Account<'info, Pool>,
+
+    #[account(
+        mut,
+        has_one = pool,
+        seeds = [&b"swap-batch"[..], pool.key().as_ref()],
+        bump = swap_batch.bump,
+    )]
+    pub swap_batch: Account<'info, SwapBatch>,
+}
+
+/// Totals a batch clears at
+#[derive(Default)]
+struct BatchClearing {
+    /// Input cleared from each token's sellers
+    amounts_in: [u64; 2],
+
+    /// Output shared among each token's sellers
+    amounts_out: [u64; 2],
+
+    /// Net imbalance sold into the pool: input token, amount and its quote
+    net: Option<(usize, u64, SwapQuote)>,
+}
+
+impl BatchClearing {
+    /// Output `intent` is owed at its side's clearing price, rounded down
+    fn amount_out(&self, intent: &SwapIntent) -> u64 {
+        let side = intent.token_in_index as usize;
+        crate::state::rounding::mul_div(
+            intent.amount_in,
+            self.amounts_out[side],
+            self.amounts_in[side],
+            crate::state::rounding::Rounding::Down,
+        )
+        .unwrap_or(0)
+    }
+}
+
+/// Clear `amounts_in` against `pool` at one price for both sides
+///
+/// The sides are matched against each other and only their imbalance is sold
+/// into the pool, sized by `batch_net_input` so that the matched flow trades at
+/// the same price the pool gives the imbalance. Only the imbalance pays the swap
+/// fee, at least `min_fee` parts per 1000.
+fn clear_batch(pool: &Pool, amounts_in: [u64; 2], min_fee: u64) -> Result<BatchClearing> {
+    let mut clearing = BatchClearing {
+        amounts_in,
+        ..Default::default()
+    };
+    if amounts_in == [0, 0] {
+        return Ok(clearing);
+    }
+
+    // Value token 0's sellers at the pool price to find the side in excess
+    let price = crate::state::math::calculate_pair_price(&pool.reserves, 0, 1, pool.amplification)
+        .ok_or(ErrorCode::InsufficientLiquidity)?;
+    let value_in_token_1 =
+        amounts_in[0] as u128 * price as u128 / crate::state::math::ORACLE_PRICE_PRECISION as u128;
+    let (x, y) = if value_in_token_1 >= amounts_in[1] as u128 {
+        (0, 1)
+    } else {
+        (1, 0)
+    };
+
+    let target_weights = pool.target_weights_at(Clock::get()?.unix_timestamp);
+    let net_input_at = |fee: u64| {
+        crate::state::math::batch_net_input(
+            &pool.reserves,
+            x,
+            y,
+            amounts_in[x],
+            amounts_in[y],
+            pool.amplification,
+            fee,
+        )
+        .ok_or(ErrorCode::MathOverflow)
+    };
+    let fee = pool.dynamic_fee(&target_weights).max(min_fee);
+    let mut net = net_input_at(fee)?;
+    let mut quote = quote_swap_with_min_fee(pool, x, y, net, 0, min_fee);
+
+    // A net that moves the pool off target pays the live fee, so size it at that fee
+    let charged_fee = quote.as_ref().map_or(fee, |quote| quote.fee);
+    if charged_fee > fee {
+        net = net_input_at(charged_fee)?;
+        quote = quote_swap_with_min_fee(pool, x, y, net, 0, min_fee);
+    }
+
+    // A dust imbalance the pool will not quote is matched peer to peer instead
+    let mut pool_out = 0;
+    match quote {
+        Ok(quote) => {
+            pool_out = quote.amount_out;
+            clearing.net = Some((x, net, quote));
+        }
+        Err(error) if error == ErrorCode::MinSwapAmount.into() => net = 0,
+        Err(error) => return Err(error),
+    }
+    clearing.amounts_out[x] = amounts_in[y] + pool_out;
+    clearing.amounts_out[y] = amounts_in[x] - net;
+
+    Ok(clearing)
+}
+
+/// Clear the closed batch at one price per side and open the next one
+///
+/// `remaining_accounts` holds every intent of the batch, writable and in ascending
+/// address order. Intents the clearing price would pay less than their minimum
+/// are refunded and the batch is cleared again without them; if the imbalance
+/// cannot be sold at all, the whole batch is refunded. Owners then collect with
+/// `claim_swap_intent`.
+pub fn settle_batch<'info>(ctx: Context<'_, '_, 'info, 'info, SettleBatch<'info>>) -> Result<()> {
+    let slot = Clock::get()?.slot;
+    let pool_key = ctx.accounts.pool.key();
+    let swap_batch = &ctx.accounts.swap_batch;
+    require!(
+        swap_batch.is_closed(slot, ctx.accounts.pool.batch_window_slots),
+        ErrorCode::BatchWindowOpen
+    );
+    require!(
+        ctx.remaining_accounts.len() == swap_batch.intent_count as usize,
+        ErrorCode::InvalidInputLength
+    );
+
+    // Read phase: every intent exactly once, so none can be left out of the price
+    let mut intents = Vec::with_capacity(ctx.remaining_accounts.len());
+    let mut last_key = None;
+    for info in ctx.remaining_accounts {
+        require!(last_key < Some(info.key()), ErrorCode::InvalidInputLength);
+        last_key = Some(info.key());
+        let intent = Account::<SwapIntent>::try_from(info)?;
+        require!(
+            intent.pool == pool_key && intent.batch_id == swap_batch.batch_id && !intent.settled,
+            ErrorCode::InvalidInputLength
+        );
+        intents.push(intent);
+    }
+
+    // Clear, dropping intents whose limit the price misses until every one left is met.
+    // A stale pool charges its floor fee on the net, or refunds the batch if it rejects swaps
+    ctx.accounts.pool.update_weights_ema(slot);
+    let pool = &ctx.accounts.pool;
+    let min_fee = stale_pool_min_fee(pool, &ctx.accounts.amm_config).ok();
+    let mut filled = vec![true; intents.len()];
+    let clearing = loop {
+        let mut amounts_in = [0u64; 2];
+        for (intent, _) in intents.iter().zip(filled.iter()).filter(|(_, &f)| f) {
+            amounts_in[intent.token_in_index as usize] += intent.amount_in;
+        }
+        let Some(clearing) =
+            min_fee.and_then(|min_fee| clear_batch(pool, amounts_in, min_fee).ok())
+        else {
+            filled.fill(false);
+            break BatchClearing::default();
+        };
+
+        let mut refunded_any = false;
+        for (intent, filled) in intents.iter().zip(filled.iter_mut()) {
+            if *filled && clearing.amount_out(intent) < intent.min_amount_out {
+                *filled = false;
+                refunded_any = true;
+            }
+        }
+        if !refunded_any {
+            break clearing;
+        }
+    };
+
+    // Write phase: book the net trade, then what each intent is owed
+    let pool = &mut ctx.accounts.pool;
+    for (queued, &amount) in pool.queued_owed.iter_mut().zip(&swap_batch.amounts_in) {
+        *queued = queued.checked_sub(amount).ok_or(ErrorCode::MathOverflow)?;
+    }
+    let (net_token_in_idx, net_amount_in) = match &clearing.net {
+        Some((token_in_idx, amount_in, quote)) => {
+            book_swap(
+                pool,
+                &ctx.accounts.amm_config,
+                ctx.accounts.keeper.key(),
+                *token_in_idx,
+                1 - token_in_idx,
+                *amount_in,
+                quote,
+            )?;
+            crate::utils::touch_pool(pool)?;
+            (*token_in_idx, *amount_in)
+        }
+        None => (0, 0),
+    };
+
+    let filled_count = filled.iter().filter(|&&f| f).count() as u32;
+    for (intent, filled) in intents.iter_mut().zip(filled) {
+        intent.settled = true;
+        intent.amount_out = if filled {
+            clearing.amount_out(intent)
+        } else {
+            0
+        };
+        let (token_idx, amount) = intent.claim();
+        pool.queued_owed[token_idx] = pool.queued_owed[token_idx]
+            .checked_add(amount)
+            .ok_or(ErrorCode::MathOverflow)?;
+        // Accounts loaded by hand must be persisted by hand
+        intent.exit(&crate::ID)?;
+    }
+
+    let swap_batch = &mut ctx.accounts.swap_batch;
+    emit!(BatchSettled {
+        pool: pool_key,
+        batch_id: swap_batch.batch_id,
+        amounts_in: clearing.amounts_in,
+        amounts_out: clearing.amounts_out,
+        net_token_in_index: net_token_in_idx as u8,
+        net_amount_in,
+        filled: filled_count,
+        refunded: swap_batch.intent_count - filled_count,
+    });
+
+    swap_batch.batch_id += 1;
+    swap_batch.opened_slot = 0;
+    swap_batch.intent_count = 0;
+    swap_batch.amounts_in = [0; 2];
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct ClaimSwapIntent<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(mut)]
+    pub pool: Account<'info, Pool>,
+
+    #[account(
+        mut,
+        has_one = owner @ ErrorCode::Unauthorized,
+        has_one = pool,
+        close = owner,
+    )]
+    pub swap_intent: Account<'info, SwapIntent>,
+
+    /// Pool vault of the token paid: the output, or the input for a refund
+    #[account(mut)]
+    pub pool_token: Account<'info, TokenAccount>,
+
+    /// Owner's account of the same token
+    #[account(
+        mut,
+        token::authority = owner,
+    )]
+    pub owner_token: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+/// Collect a settled intent's output, or its input if it was refunded
+pub fn claim_swap_intent(ctx: Context<ClaimSwapIntent>) -> Result<()> {
+    let swap_intent = &ctx.accounts.swap_intent;
+    require!(swap_intent.settled, ErrorCode::IntentNotSettled);
+    let (token_idx, amount) = swap_intent.claim();
+
+    let pool = &mut ctx.accounts.pool;
+    crate::utils::check_pool_vault(pool, token_idx, &ctx.accounts.pool_token.key())?;
+    crate::utils::check_pool_mint(pool, token_idx, &ctx.accounts.owner_token.mint)?;
+    pool.queued_owed[token_idx] = pool.queued_owed[token_idx]
+        .checked_sub(amount)
+        .ok_or(ErrorCode::MathOverflow)?;
+
+    let seeds = pool.signer_seeds()?;
+    let signer = &[&seeds[..]];
+    let cpi_accounts = Transfer {
+        from: ctx.accounts.pool_token.to_account_info(),
+        to: ctx.accounts.owner_token.to_account_info(),
+        authority: pool.to_account_info(),
+    };
+    let cpi_program = ctx.accounts.token_program.to_account_info();
+    let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
+    token::transfer(cpi_ctx, amount)?;
+
+    Ok(())
+}
@@ -0,0 +1,99 @@
+use crate::errors::ErrorCode;
+use crate::events::PoolVaultClosed;
+use crate::state::*;
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, CloseAccount, Mint, Token, TokenAccount};
+
+#[derive(Accounts)]
+pub struct ClosePoolVault<'info> {
+    /// Config authority, paid the vault's rent and the rent the pool frees
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        has_one = authority @ ErrorCode::Unauthorized,
+    )]
+    pub amm_config: Account<'info, AmmConfig>,
+
+    #[account(
+        mut,
+        has_one = amm_config,
+        has_one = lp_mint @ ErrorCode::InvalidTokenMint,
+        constraint = pool.pool_type == PoolType::Seed @ ErrorCode::InvalidPoolType,
+        realloc = Pool::space(pool.token_mints.len().saturating_sub(1)),
+        realloc::payer = authority,
+        realloc::zero = false,
+    )]
+    pub pool: Account<'info, Pool>,
+
+    pub lp_mint: Account<'info, Mint>,
+
+    /// Vault of the token being removed
+    #[account(mut)]
+    pub vault: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Close the empty vault of permanently frozen token `token_index` and drop the token
+/// from the pool, so a deprecated pool stops carrying it through every instruction
+///
+/// The token must be frozen with nothing left in its vault, reserve, protocol fees
+/// or queued withdrawals.
+/// The remaining target weights are rescaled to keep their shares, and both the
+/// vault's rent and the rent the smaller pool account frees go to the authority.
+pub fn close_pool_vault(ctx: Context<ClosePoolVault>, token_index: u8) -> Result<()> {
+    let pool = &ctx.accounts.pool;
+    let token_idx = token_index as usize;
+    require!(
+        token_idx < pool.token_mints.len() && pool.token_mints.len() > 2,
+        ErrorCode::InvalidInputLength
+    );
+    crate::utils::check_pool_vault(pool, token_idx, &ctx.accounts.vault.key())?;
+    require!(
+        pool.token_status[token_idx] == TokenStatus::Frozen,
+        ErrorCode::TokenNotFrozen
+    );
+    require!(
+        ctx.accounts.vault.amount == 0
+            && pool.reserves[token_idx] == 0
+            && pool.protocol_fees_owed[token_idx] == 0
+            && pool.queued_owed[token_idx] == 0,
+        ErrorCode::VaultNotEmpty
+    );
+
+    let seeds = pool.signer_seeds()?;
+    let signer = &[&seeds[..]];
+    let cpi_accounts = CloseAccount {
+        account: ctx.accounts.vault.to_account_info(),
+        destination: ctx.accounts.authority.to_account_info(),
+        authority: pool.to_account_info(),
+    };
+    let cpi_program = ctx.accounts.token_program.to_account_info();
+    let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
+    token::close_account(cpi_ctx)?;
+
+    let token_mint = pool.token_mints[token_idx];
+    let lp_supply = pool.lp_supply(ctx.accounts.lp_mint.supply);
+    let pool = &mut ctx.accounts.pool;
+    pool.remove_token(token_idx)?;
+
+    // D over fewer tokens is a different curve: re-solve it and reprice LP against it
+    let clock = Clock::get()?;
+    pool.last_d =
+        crate::state::math::calculate_invariant(&pool.reserves, pool.amplification).unwrap_or(0);
+    pool.d_slot = clock.slot;
+    pool.refresh_virtual_price(lp_supply);
+    pool.refresh_routing_hint(clock.unix_timestamp);
+
+    emit!(PoolVaultClosed {
+        pool: pool.key(),
+        token_mint,
+        vault: ctx.accounts.vault.key(),
+        token_index,
+        target_weights: pool.target_weights.clone(),
+    });
+
+    Ok(())
+}
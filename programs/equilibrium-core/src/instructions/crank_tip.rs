@@ -0,0 +1,69 @@
+use crate::errors::ErrorCode;
+use crate::events::CrankTipPaid;
+use crate::state::*;
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, TokenAccount, Transfer};
+
+/// Pay the caller of a permissionless crank its tip out of `pool`'s protocol fees
+///
+/// Tips are opt-in for the caller: without the config, the pool vault to draw
+/// from, a token account to pay into and the token program, the crank simply
+/// runs untipped. The tip
+/// is paid in the vault's token, within the pool's per-slot cap.
+pub fn pay_crank_tip<'info>(
+    pool: &mut Account<'info, Pool>,
+    amm_config: Option<&Account<'info, AmmConfig>>,
+    pool_token_tip: Option<&Account<'info, TokenAccount>>,
+    tip_recipient: Option<&Account<'info, TokenAccount>>,
+    token_program: Option<AccountInfo<'info>>,
+) -> Result<()> {
+    let (Some(amm_config), Some(pool_token_tip), Some(tip_recipient), Some(token_program)) =
+        (amm_config, pool_token_tip, tip_recipient, token_program)
+    else {
+        return Ok(());
+    };
+    require_keys_eq!(
+        amm_config.key(),
+        pool.amm_config,
+        ErrorCode::InvalidCrankTip
+    );
+    require_keys_eq!(pool_token_tip.owner, pool.key(), ErrorCode::InvalidCrankTip);
+    require_keys_eq!(
+        tip_recipient.mint,
+        pool_token_tip.mint,
+        ErrorCode::InvalidCrankTip
+    );
+    let token_idx = pool
+        .token_index(&pool_token_tip.mint)
+        .ok_or(ErrorCode::InvalidCrankTip)?;
+    crate::utils::check_pool_vault(pool, token_idx, &pool_token_tip.key())?;
+
+    let amount = pool.take_crank_tip(
+        token_idx,
+        amm_config.crank_tip_amount,
+        amm_config.max_crank_tips_per_slot,
+        Clock::get()?.slot,
+    );
+    if amount == 0 {
+        return Ok(());
+    }
+
+    let seeds = pool.signer_seeds()?;
+    let signer = &[&seeds[..]];
+    let cpi_accounts = Transfer {
+        from: pool_token_tip.to_account_info(),
+        to: tip_recipient.to_account_info(),
+        authority: pool.to_account_info(),
+    };
+    let cpi_ctx = CpiContext::new_with_signer(token_program, cpi_accounts, signer);
+    token::transfer(cpi_ctx, amount)?;
+
+    emit!(CrankTipPaid {
+        pool: pool.key(),
+        token_mint: pool_token_tip.mint,
+        recipient: tip_recipient.key(),
+        amount,
+    });
+
+    Ok(())
+}
@@ -1,6 +1,7 @@
 use crate::errors::ErrorCode;
 use crate::state::*;
 use anchor_lang::prelude::*;
+use anchor_lang::system_program;
 use anchor_spl::associated_token::AssociatedToken;
 use anchor_spl::token::{self, Mint, Token, TokenAccount, Transfer};
 
@@ -19,17 +20,17 @@ pub struct CreateSeedPool<'info> {
         init,
         payer = payer,
         space = Pool::space(3), // Fixed 3 tokens for Seed Pool
-        seeds = [&b"pool"[..], &b"seed"[..]],
+        seeds = [&b"pool"[..], amm_config.key().as_ref(), &b"seed"[..]],
         bump
     )]
     pub pool: Account<'info, Pool>,
 
-    // We'll need 3 token mints for USDC, USDT, PYUSD
+    /// Seed Pool token mints (USDC, USDT, PYUSD)
     pub token_mint_a: Account<'info, Mint>,
     pub token_mint_b: Account<'info, Mint>,
     pub token_mint_c: Account<'info, Mint>,
 
-    // Token accounts owned by the user
+    /// Token accounts owned by the user
     #[account(
         mut,
         token::authority = payer,
@@ -51,7 +52,7 @@ pub struct CreateSeedPool<'info> {
     )]
     pub user_token_c: Account<'info, TokenAccount>,
 
-    // Pool token accounts
+    /// Pool token accounts
     #[account(
         init,
         payer = payer,
@@ -82,7 +83,7 @@ pub struct CreateSeedPool<'info> {
     )]
     pub pool_token_c: Account<'info, TokenAccount>,
 
-    // LP token mint
+    /// LP token mint
     #[account(
         init,
         payer = payer,
@@ -93,7 +94,7 @@ pub struct CreateSeedPool<'info> {
     )]
     pub lp_mint: Account<'info, Mint>,
 
-    // User's LP token account
+    /// User's LP token account
     #[account(
         init_if_needed,
         payer = payer,
@@ -120,6 +121,10 @@ pub fn create_seed_pool(
     // Validate inputs
     require!(target_weights.len() == 3, ErrorCode::InvalidInputLength);
     require!(initial_amounts.len() == 3, ErrorCode::InvalidInputLength);
+    require!(
+        crate::state::math::is_valid_amplification(amplification),
+        ErrorCode::InvalidAmplification
+    );
 
     // Validate target weights sum to 10000 (100%)
     let sum: u64 = target_weights.iter().sum();
@@ -161,6 +166,48 @@ pub fn create_seed_pool(
     pool.total_fees = 0;
     pool.last_update = Clock::get()?.unix_timestamp;
     pool.seed_pool = None; // This is a Seed Pool
+    pool.lifetime_volume_per_token = vec![0; 3];
+    pool.lifetime_fees_per_token = vec![0; 3];
+    pool.protocol_fees_owed = vec![0; 3];
+    pool.swap_count = 0;
+    pool.exit_fee_bps = 0;
+    pool.rebalance_epoch = 0;
+    pool.rebalance_volume_in_epoch = 0;
+    pool.min_swap_amount = 0;
+    pool.max_trade_bps = crate::state::math::DEFAULT_MAX_TRADE_BPS;
+    pool.lbp_start_weights = vec![];
+    pool.lbp_start_ts = 0;
+    pool.lbp_end_ts = 0;
+    pool.bootstrap_end_ts = 0;
+    pool.max_deposit_per_wallet = 0;
+    pool.protocol_fee_share_bps = 0;
+    pool.staged_withdraw_threshold_bps = 0;
+    pool.staged_withdraw_delay = 0;
+    pool.partner_oracle_price = 0;
+    pool.partner_oracle_updated_at = 0;
+    pool.oracle_band_bps = 0;
+    pool.oracle_surcharge_bps = 0;
+    pool.crank_tip_slot = 0;
+    pool.crank_tips_in_slot = 0;
+    pool.partner_oracle_updated_slot = 0;
+    pool.paused = false;
+    pool.relayer = Pubkey::default();
+    pool.soulbound_lp = false; // USD* must stay transferable
+    pool.internal_lp_supply = 0;
+    pool.last_update_slot = Clock::get()?.slot;
+    pool.requires_credential = false;
+    pool.credential_mint = Pubkey::default();
+    pool.large_flow_threshold_bps = 0;
+    pool.inherits_protocol_fee_share = true;
+    pool.price_band_bps = 0;
+    pool.batch_window_slots = 0;
+    pool.weights_ema = [0; MAX_POOL_TOKENS];
+    pool.ema_lambda = 0;
+    pool.queued_owed = [0; MAX_POOL_TOKENS];
+    pool.ema_updated_slot = 0;
+    pool.reserved = [0; POOL_RESERVED_BYTES];
+    pool.fee_tier = FeeTier::Stable;
+    pool.fee_params = FeeTier::Stable.fee_params();
 
     // Transfer tokens from user to pool
     let token_accounts = [
@@ -187,12 +234,27 @@ pub fn create_seed_pool(
         }
     }
 
+    // Cache the initial invariant
+    pool.last_d =
+        crate::state::math::calculate_invariant(&pool.reserves, pool.amplification).unwrap_or(0);
+    pool.d_slot = Clock::get()?.slot;
+
     // Mint initial LP tokens to user
     // For simplicity, use the sum of token amounts as the initial LP amount
     let initial_lp_amount: u64 = initial_amounts.iter().sum();
+    pool.refresh_virtual_price(initial_lp_amount);
+    pool.refresh_routing_hint(Clock::get()?.unix_timestamp);
+    pool.token_status = vec![TokenStatus::Normal; pool.token_mints.len()];
+    pool.refresh_token_status(Clock::get()?.unix_timestamp);
 
     // CPI to mint LP tokens - fixed seed array
-    let seeds = &[&b"pool"[..], &b"seed"[..], &[pool.bump]];
+    let amm_config_key = ctx.accounts.amm_config.key();
+    let seeds = &[
+        &b"pool"[..],
+        amm_config_key.as_ref(),
+        &b"seed"[..],
+        &[pool.bump],
+    ];
     let signer = &[&seeds[..]];
 
     let cpi_accounts = token::MintTo {
@@ -211,7 +273,6 @@ pub fn create_seed_pool(
 #[instruction(amplification: u64, initial_usdc_amount: u64, initial_partner_amount: u64)]
 pub struct CreateGrowthPool<'info> {
     // Similar to CreateSeedPool but with only 2 tokens
-    // Will need reference to the Seed Pool
     #[account(mut)]
     pub payer: Signer<'info>,
 
@@ -220,22 +281,45 @@ pub struct CreateGrowthPool<'info> {
     )]
     pub amm_config: Account<'info, AmmConfig>,
 
+    /// Seed Pool whose LP token is this pool's USD*
+    #[account(
+        has_one = amm_config,
+    )]
     pub seed_pool: Account<'info, Pool>,
 
     #[account(
         init,
         payer = payer,
         space = Pool::space(2), // Fixed 2 tokens for Growth Pool
-        seeds = [&b"pool"[..], &b"growth"[..], partner_token_mint.key().as_ref()],
+        seeds = [
+            &b"pool"[..],
+            amm_config.key().as_ref(),
+            &b"growth"[..],
+            partner_token_mint.key().as_ref(),
+        ],
         bump
     )]
     pub pool: Account<'info, Pool>,
 
-    // USD* from Seed Pool + Partner Token
+    /// USD* (the Seed Pool LP mint) and the partner token
+    #[account(
+        constraint = usdc_star_mint.key() == seed_pool.lp_mint @ ErrorCode::InvalidUsdStarMint,
+    )]
     pub usdc_star_mint: Account<'info, Mint>,
     pub partner_token_mint: Account<'info, Mint>,
 
-    // Token accounts owned by the user
+    /// Required when the config restricts Growth pools to approved partners
+    #[account(
+        seeds = [
+            &b"partner-approval"[..],
+            amm_config.key().as_ref(),
+            partner_token_mint.key().as_ref(),
+        ],
+        bump = partner_approval.bump,
+    )]
+    pub partner_approval: Option<Account<'info, PartnerApproval>>,
+
+    /// Token accounts owned by the user
     #[account(
         mut,
         token::authority = payer,
@@ -250,7 +334,7 @@ pub struct CreateGrowthPool<'info> {
     )]
     pub user_partner_token: Account<'info, TokenAccount>,
 
-    // Pool token accounts
+    /// Pool token accounts
     #[account(
         init,
         payer = payer,
@@ -271,7 +355,7 @@ pub struct CreateGrowthPool<'info> {
     )]
     pub pool_partner_token: Account<'info, TokenAccount>,
 
-    // LP token mint
+    /// LP token mint
     #[account(
         init,
         payer = payer,
@@ -282,7 +366,7 @@ pub struct CreateGrowthPool<'info> {
     )]
     pub lp_mint: Account<'info, Mint>,
 
-    // User's LP token account
+    /// User's LP token account
     #[account(
         init_if_needed,
         payer = payer,
@@ -298,6 +382,31 @@ pub struct CreateGrowthPool<'info> {
 
     /// CHECK: This is the authority on the AMM config
     pub authority: AccountInfo<'info>,
+
+    /// CHECK: Receives the SOL creation fee in permissionless mode
+    #[account(
+        mut,
+        address = amm_config.fee_recipient,
+    )]
+    pub fee_recipient: Option<UncheckedAccount<'info>>,
+
+    /// Receives the USD* creation fee in permissionless mode
+    #[account(
+        mut,
+        token::mint = usdc_star_mint,
+        token::authority = amm_config.fee_recipient,
+    )]
+    pub fee_recipient_usd_star: Option<Box<Account<'info, TokenAccount>>>,
+
+    /// Creator's position, holding the initial LP of a soulbound pool
+    #[account(
+        init,
+        payer = payer,
+        space = UserPosition::space(),
+        seeds = [&b"user-position"[..], payer.key().as_ref(), pool.key().as_ref()],
+        bump
+    )]
+    pub creator_position: Option<Box<Account<'info, UserPosition>>>,
 }
 
 pub fn create_growth_pool(
@@ -305,12 +414,41 @@ pub fn create_growth_pool(
     amplification: u64,
     initial_usdc_star_amount: u64,
     initial_partner_amount: u64,
+    fee_tier: FeeTier,
+    soulbound_lp: bool,
+    target_weights: [u64; 2],
 ) -> Result<()> {
     // Validate inputs
     require!(
         ctx.accounts.seed_pool.pool_type == PoolType::Seed,
         ErrorCode::InvalidPoolType
     );
+    require!(
+        crate::state::math::is_valid_growth_weights(&target_weights),
+        ErrorCode::InvalidWeights
+    );
+    require!(
+        crate::state::math::is_valid_amplification(amplification),
+        ErrorCode::InvalidAmplification
+    );
+    if ctx.accounts.amm_config.require_partner_approval {
+        require!(
+            ctx.accounts.partner_approval.is_some(),
+            ErrorCode::PartnerNotApproved
+        );
+    }
+
+    // Anyone but the authority needs permissionless mode and pays the creation fee.
+    // The authority may co-sign instead of paying, so a PDA authority works too.
+    let authority_signed = ctx.accounts.authority.is_signer
+        || ctx.accounts.payer.key() == ctx.accounts.amm_config.authority;
+    if !authority_signed {
+        require!(
+            ctx.accounts.amm_config.permissionless_pool_creation,
+            ErrorCode::Unauthorized
+        );
+        charge_pool_creation_fee(&ctx)?;
+    }
 
     // Set up pool state
     let pool = &mut ctx.accounts.pool;
@@ -336,8 +474,9 @@ pub fn create_growth_pool(
     // Set LP mint
     pool.lp_mint = ctx.accounts.lp_mint.key();
 
-    // Set target weights - for Growth Pool it's always 50/50
-    pool.target_weights = vec![5000, 5000];
+    // Set target weights, USD* first - 50/50 for a plain pair, skewed for
+    // correlated or bootstrapping pairs
+    pool.target_weights = target_weights.to_vec();
 
     // Set amplification coefficient
     pool.amplification = amplification;
@@ -346,6 +485,48 @@ pub fn create_growth_pool(
     pool.total_fees = 0;
     pool.last_update = Clock::get()?.unix_timestamp;
     pool.seed_pool = Some(ctx.accounts.seed_pool.key());
+    pool.lifetime_volume_per_token = vec![0; 2];
+    pool.lifetime_fees_per_token = vec![0; 2];
+    pool.protocol_fees_owed = vec![0; 2];
+    pool.swap_count = 0;
+    pool.exit_fee_bps = 0;
+    pool.rebalance_epoch = 0;
+    pool.rebalance_volume_in_epoch = 0;
+    pool.min_swap_amount = 0;
+    pool.max_trade_bps = crate::state::math::DEFAULT_MAX_TRADE_BPS;
+    pool.lbp_start_weights = vec![];
+    pool.lbp_start_ts = 0;
+    pool.lbp_end_ts = 0;
+    pool.bootstrap_end_ts = 0;
+    pool.max_deposit_per_wallet = 0;
+    pool.protocol_fee_share_bps = 0;
+    pool.staged_withdraw_threshold_bps = 0;
+    pool.staged_withdraw_delay = 0;
+    pool.partner_oracle_price = 0;
+    pool.partner_oracle_updated_at = 0;
+    pool.oracle_band_bps = 0;
+    pool.oracle_surcharge_bps = 0;
+    pool.crank_tip_slot = 0;
+    pool.crank_tips_in_slot = 0;
+    pool.partner_oracle_updated_slot = 0;
+    pool.paused = false;
+    pool.relayer = Pubkey::default();
+    pool.soulbound_lp = soulbound_lp;
+    pool.internal_lp_supply = 0;
+    pool.last_update_slot = Clock::get()?.slot;
+    pool.requires_credential = false;
+    pool.credential_mint = Pubkey::default();
+    pool.large_flow_threshold_bps = 0;
+    pool.inherits_protocol_fee_share = true;
+    pool.price_band_bps = 0;
+    pool.batch_window_slots = 0;
+    pool.weights_ema = [0; MAX_POOL_TOKENS];
+    pool.ema_lambda = 0;
+    pool.queued_owed = [0; MAX_POOL_TOKENS];
+    pool.ema_updated_slot = 0;
+    pool.reserved = [0; POOL_RESERVED_BYTES];
+    pool.fee_tier = fee_tier;
+    pool.fee_params = fee_tier.fee_params();
 
     // Transfer tokens from user to pool
     // Transfer USD*
@@ -378,14 +559,48 @@ pub fn create_growth_pool(
         pool.reserves[1] = initial_partner_amount;
     }
 
-    // Mint initial LP tokens to user
-    let initial_lp_amount = std::cmp::min(initial_usdc_star_amount, initial_partner_amount) * 2;
+    // Cache the initial invariant; both sides must be funded for D to exist
+    pool.last_d = crate::state::math::calculate_invariant(&pool.reserves, pool.amplification)
+        .ok_or(ErrorCode::InsufficientLiquidity)?;
+    pool.d_slot = Clock::get()?.slot;
+
+    // Mint initial LP tokens to user, one per unit of invariant so the first
+    // LP's share reflects the deposited value rather than the smaller side
+    let initial_lp_amount = pool.last_d;
+    pool.refresh_virtual_price(initial_lp_amount);
+    pool.refresh_routing_hint(Clock::get()?.unix_timestamp);
+    pool.token_status = vec![TokenStatus::Normal; pool.token_mints.len()];
+    pool.refresh_token_status(Clock::get()?.unix_timestamp);
+
+    // A soulbound pool books the initial LP on the creator's position instead
+    if soulbound_lp {
+        pool.internal_lp_supply = initial_lp_amount;
+        let pool_key = pool.key();
+        let now = pool.last_update;
+        let creator_position = ctx
+            .accounts
+            .creator_position
+            .as_mut()
+            .ok_or(ErrorCode::CreatorPositionRequired)?;
+        creator_position.bump = ctx.bumps.creator_position.unwrap_or_default();
+        creator_position.owner = ctx.accounts.payer.key();
+        creator_position.pool = pool_key;
+        creator_position.lp_amount = initial_lp_amount;
+        creator_position.is_active = true;
+        creator_position.created_at = now;
+        creator_position.last_update = now;
+        creator_position.last_update_slot = Clock::get()?.slot;
+        creator_position.delegate = None;
+        return Ok(());
+    }
 
     // CPI to mint LP tokens - fixed seed array
     let partner_token_key = ctx.accounts.partner_token_mint.key();
     let partner_token_ref = partner_token_key.as_ref();
+    let amm_config_key = ctx.accounts.amm_config.key();
     let seeds = &[
         &b"pool"[..],
+        amm_config_key.as_ref(),
         &b"growth"[..],
         partner_token_ref,
         &[pool.bump],
@@ -403,3 +618,39 @@ pub fn create_growth_pool(
 
     Ok(())
 }
+
+// Collect the configured SOL and USD* creation fees from the payer
+fn charge_pool_creation_fee(ctx: &Context<CreateGrowthPool>) -> Result<()> {
+    let amm_config = &ctx.accounts.amm_config;
+
+    if amm_config.pool_creation_fee_lamports > 0 {
+        let fee_recipient = ctx
+            .accounts
+            .fee_recipient
+            .as_ref()
+            .ok_or(ErrorCode::InvalidFeeRecipient)?;
+        let cpi_accounts = system_program::Transfer {
+            from: ctx.accounts.payer.to_account_info(),
+            to: fee_recipient.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new(ctx.accounts.system_program.to_account_info(), cpi_accounts);
+        system_program::transfer(cpi_ctx, amm_config.pool_creation_fee_lamports)?;
+    }
+
+    if amm_config.pool_creation_fee_usd_star > 0 {
+        let fee_recipient_usd_star = ctx
+            .accounts
+            .fee_recipient_usd_star
+            .as_ref()
+            .ok_or(ErrorCode::InvalidFeeRecipient)?;
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.user_usdc_star.to_account_info(),
+            to: fee_recipient_usd_star.to_account_info(),
+            authority: ctx.accounts.payer.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new(ctx.accounts.token_program.to_account_info(), cpi_accounts);
+        token::transfer(cpi_ctx, amm_config.pool_creation_fee_usd_star)?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,351 @@
+use crate::errors::ErrorCode;
+use crate::instructions::swap::{apply_swap, quote_swap_with_min_fee, stale_pool_min_fee};
+use crate::state::*;
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, CloseAccount, Mint, Token, TokenAccount, Transfer};
+
+#[derive(Accounts)]
+#[instruction(schedule_id: u64)]
+pub struct CreateDcaSchedule<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    pub pool: Account<'info, Pool>,
+
+    /// Token being spent and the token being bought
+    pub token_mint_in: Account<'info, Mint>,
+    pub token_mint_out: Account<'info, Mint>,
+
+    /// Owner's token account funding the budget
+    #[account(
+        mut,
+        token::authority = owner,
+        token::mint = token_mint_in,
+    )]
+    pub owner_token_in: Account<'info, TokenAccount>,
+
+    #[account(
+        init,
+        payer = owner,
+        space = DcaSchedule::space(),
+        seeds = [&b"dca"[..], owner.key().as_ref(), pool.key().as_ref(), &schedule_id.to_le_bytes()],
+        bump
+    )]
+    pub dca_schedule: Account<'info, DcaSchedule>,
+
+    /// Escrow owned by the schedule PDA
+    #[account(
+        init,
+        payer = owner,
+        token::mint = token_mint_in,
+        token::authority = dca_schedule,
+        seeds = [&b"dca-escrow"[..], dca_schedule.key().as_ref()],
+        bump
+    )]
+    pub escrow: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+pub fn create_dca_schedule(
+    ctx: Context<CreateDcaSchedule>,
+    schedule_id: u64,
+    amount_per_interval: u64,
+    min_amount_out_per_interval: u64,
+    interval: i64,
+    total_budget: u64,
+) -> Result<()> {
+    let pool = &ctx.accounts.pool;
+
+    // Both tokens must be tradable in the pool
+    let token_in_idx = pool
+        .token_index(&ctx.accounts.token_mint_in.key())
+        .ok_or(ErrorCode::InvalidTokenMint)?;
+    let token_out_idx = pool
+        .token_index(&ctx.accounts.token_mint_out.key())
+        .ok_or(ErrorCode::InvalidTokenMint)?;
+    require!(token_in_idx != token_out_idx, ErrorCode::InvalidSwap);
+    require!(
+        amount_per_interval > 0 && interval > 0 && total_budget >= amount_per_interval,
+        ErrorCode::InvalidInstructionData
+    );
+
+    // Escrow the full budget up front
+    let cpi_accounts = Transfer {
+        from: ctx.accounts.owner_token_in.to_account_info(),
+        to: ctx.accounts.escrow.to_account_info(),
+        authority: ctx.accounts.owner.to_account_info(),
+    };
+    let cpi_program = ctx.accounts.token_program.to_account_info();
+    let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
+    token::transfer(cpi_ctx, total_budget)?;
+
+    // Record the schedule, first execution is due immediately
+    let now = Clock::get()?.unix_timestamp;
+    let dca_schedule = &mut ctx.accounts.dca_schedule;
+    dca_schedule.bump = ctx.bumps.dca_schedule;
+    dca_schedule.escrow_bump = ctx.bumps.escrow;
+    dca_schedule.owner = ctx.accounts.owner.key();
+    dca_schedule.pool = ctx.accounts.pool.key();
+    dca_schedule.schedule_id = schedule_id;
+    dca_schedule.token_mint_in = ctx.accounts.token_mint_in.key();
+    dca_schedule.token_mint_out = ctx.accounts.token_mint_out.key();
+    dca_schedule.escrow = ctx.accounts.escrow.key();
+    dca_schedule.amount_per_interval = amount_per_interval;
+    dca_schedule.min_amount_out_per_interval = min_amount_out_per_interval;
+    dca_schedule.interval = interval;
+    dca_schedule.remaining_budget = total_budget;
+    dca_schedule.next_execution_ts = now;
+    dca_schedule.executions = 0;
+    dca_schedule.created_at = now;
+    dca_schedule.last_execution_slot = 0;
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct ExecuteDca<'info> {
+    /// Permissionless crank, paid a tip in token_out
+    pub keeper: Signer<'info>,
+
+    #[account(
+        mut,
+        token::mint = dca_schedule.token_mint_out,
+    )]
+    pub keeper_token_out: Account<'info, TokenAccount>,
+
+    /// Config holding the protocol fee share and crank tip policy
+    pub amm_config: Account<'info, AmmConfig>,
+
+    #[account(
+        mut,
+        has_one = amm_config,
+    )]
+    pub pool: Account<'info, Pool>,
+
+    #[account(
+        mut,
+        has_one = pool,
+        has_one = escrow,
+    )]
+    pub dca_schedule: Account<'info, DcaSchedule>,
+
+    #[account(mut)]
+    pub escrow: Account<'info, TokenAccount>,
+
+    /// Pool's token accounts
+    #[account(
+        mut,
+        token::authority = pool,
+        token::mint = dca_schedule.token_mint_in,
+    )]
+    pub pool_token_in: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        token::authority = pool,
+        token::mint = dca_schedule.token_mint_out,
+    )]
+    pub pool_token_out: Account<'info, TokenAccount>,
+
+    /// Owner's account receiving the output
+    #[account(
+        mut,
+        token::authority = dca_schedule.owner,
+        token::mint = dca_schedule.token_mint_out,
+    )]
+    pub owner_token_out: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+
+    /// Optional keeper tip out of protocol fees: the vault the tip is paid from and
+    /// the caller's account receiving it
+    #[account(mut)]
+    pub pool_token_tip: Option<Account<'info, TokenAccount>>,
+
+    #[account(mut)]
+    pub tip_recipient: Option<Account<'info, TokenAccount>>,
+}
+
+pub fn execute_dca(ctx: Context<ExecuteDca>) -> Result<()> {
+    let clock = Clock::get()?;
+    let now = clock.unix_timestamp;
+    let dca_schedule = &ctx.accounts.dca_schedule;
+
+    require!(
+        dca_schedule.remaining_budget > 0,
+        ErrorCode::DcaBudgetExhausted
+    );
+    // A timestamp stepping back across a fork cannot re-run an interval: the
+    // slot must also have advanced since the last execution
+    require!(
+        now >= dca_schedule.next_execution_ts && clock.slot > dca_schedule.last_execution_slot,
+        ErrorCode::DcaNotDue
+    );
+
+    // The final execution may spend less than a full interval amount
+    let amount_in = std::cmp::min(
+        dca_schedule.amount_per_interval,
+        dca_schedule.remaining_budget,
+    );
+    let min_amount_out = (dca_schedule.min_amount_out_per_interval as u128 * amount_in as u128
+        / dca_schedule.amount_per_interval as u128) as u64;
+
+    // Price the swap against the pool
+    let pool = &mut ctx.accounts.pool;
+    let token_in_idx = pool
+        .token_index(&dca_schedule.token_mint_in)
+        .ok_or(ErrorCode::InvalidTokenMint)?;
+    let token_out_idx = pool
+        .token_index(&dca_schedule.token_mint_out)
+        .ok_or(ErrorCode::InvalidTokenMint)?;
+    crate::utils::check_pool_vault(pool, token_in_idx, &ctx.accounts.pool_token_in.key())?;
+    crate::utils::check_pool_vault(pool, token_out_idx, &ctx.accounts.pool_token_out.key())?;
+
+    pool.update_weights_ema(clock.slot);
+    let min_fee = stale_pool_min_fee(pool, &ctx.accounts.amm_config)?;
+    let quote = quote_swap_with_min_fee(pool, token_in_idx, token_out_idx, amount_in, 0, min_fee)?;
+    crate::utils::check_min_amount("DCA output", quote.amount_out, min_amount_out)?;
+
+    pool.require_permissionless()?;
+    apply_swap(
+        pool,
+        &ctx.accounts.amm_config,
+        dca_schedule.owner,
+        token_in_idx,
+        token_out_idx,
+        amount_in,
+        &quote,
+    )?;
+    crate::utils::touch_pool(pool)?;
+
+    // Split the output between the owner and the cranker
+    let tip = quote.amount_out * DCA_KEEPER_TIP_BPS / crate::state::math::BPS_DENOMINATOR;
+    let owner_amount = quote.amount_out - tip;
+
+    // Move the interval amount from escrow into the pool
+    let schedule_id = dca_schedule.schedule_id.to_le_bytes();
+    let schedule_seeds = &[
+        &b"dca"[..],
+        dca_schedule.owner.as_ref(),
+        dca_schedule.pool.as_ref(),
+        &schedule_id,
+        &[dca_schedule.bump],
+    ];
+    let schedule_signer = &[&schedule_seeds[..]];
+
+    let cpi_accounts = Transfer {
+        from: ctx.accounts.escrow.to_account_info(),
+        to: ctx.accounts.pool_token_in.to_account_info(),
+        authority: dca_schedule.to_account_info(),
+    };
+    let cpi_program = ctx.accounts.token_program.to_account_info();
+    let cpi_ctx = CpiContext::new_with_signer(cpi_program.clone(), cpi_accounts, schedule_signer);
+    token::transfer(cpi_ctx, amount_in)?;
+
+    // Pay out from the pool
+    let pool = &ctx.accounts.pool;
+    let pool_seeds = pool.signer_seeds()?;
+    let pool_signer = &[&pool_seeds[..]];
+
+    let cpi_accounts = Transfer {
+        from: ctx.accounts.pool_token_out.to_account_info(),
+        to: ctx.accounts.owner_token_out.to_account_info(),
+        authority: pool.to_account_info(),
+    };
+    let cpi_ctx = CpiContext::new_with_signer(cpi_program.clone(), cpi_accounts, pool_signer);
+    token::transfer(cpi_ctx, owner_amount)?;
+
+    if tip > 0 {
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.pool_token_out.to_account_info(),
+            to: ctx.accounts.keeper_token_out.to_account_info(),
+            authority: pool.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, pool_signer);
+        token::transfer(cpi_ctx, tip)?;
+    }
+
+    // The protocol tip comes on top of the owner-funded one
+    crate::instructions::crank_tip::pay_crank_tip(
+        &mut ctx.accounts.pool,
+        Some(&ctx.accounts.amm_config),
+        ctx.accounts.pool_token_tip.as_ref(),
+        ctx.accounts.tip_recipient.as_ref(),
+        Some(ctx.accounts.token_program.to_account_info()),
+    )?;
+
+    // Advance the schedule
+    let dca_schedule = &mut ctx.accounts.dca_schedule;
+    dca_schedule.remaining_budget -= amount_in;
+    dca_schedule.next_execution_ts = now + dca_schedule.interval;
+    dca_schedule.executions += 1;
+    dca_schedule.last_execution_slot = clock.slot;
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct CancelDcaSchedule<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        mut,
+        has_one = owner @ ErrorCode::Unauthorized,
+        has_one = escrow,
+        close = owner,
+    )]
+    pub dca_schedule: Account<'info, DcaSchedule>,
+
+    #[account(mut)]
+    pub escrow: Account<'info, TokenAccount>,
+
+    /// Owner's account receiving the unspent budget
+    #[account(
+        mut,
+        token::authority = owner,
+        token::mint = dca_schedule.token_mint_in,
+    )]
+    pub owner_token_in: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+pub fn cancel_dca_schedule(ctx: Context<CancelDcaSchedule>) -> Result<()> {
+    let dca_schedule = &ctx.accounts.dca_schedule;
+    let schedule_id = dca_schedule.schedule_id.to_le_bytes();
+    let seeds = &[
+        &b"dca"[..],
+        dca_schedule.owner.as_ref(),
+        dca_schedule.pool.as_ref(),
+        &schedule_id,
+        &[dca_schedule.bump],
+    ];
+    let signer = &[&seeds[..]];
+
+    // Refund the unspent budget
+    let cpi_program = ctx.accounts.token_program.to_account_info();
+    if ctx.accounts.escrow.amount > 0 {
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.escrow.to_account_info(),
+            to: ctx.accounts.owner_token_in.to_account_info(),
+            authority: dca_schedule.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program.clone(), cpi_accounts, signer);
+        token::transfer(cpi_ctx, ctx.accounts.escrow.amount)?;
+    }
+
+    // Close the escrow, returning rent to the owner
+    let cpi_accounts = CloseAccount {
+        account: ctx.accounts.escrow.to_account_info(),
+        destination: ctx.accounts.owner.to_account_info(),
+        authority: dca_schedule.to_account_info(),
+    };
+    let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
+    token::close_account(cpi_ctx)?;
+
+    Ok(())
+}
@@ -1,25 +1,27 @@
 use crate::errors::ErrorCode;
+use crate::events::{PositionChangeReason, PositionChanged};
 use crate::state::*;
 use anchor_lang::prelude::*;
 use anchor_spl::token::{self, Mint, Token, TokenAccount, Transfer};
+use anchor_spl::token_interface;
 
 #[derive(Accounts)]
-#[instruction(amounts: Vec<u64>, min_lp_amount: u64, concentration: u64)]
 pub struct Deposit<'info> {
+    /// CHECK: Position owner; must sign unless the position's delegate does
     #[account(mut)]
-    pub user: Signer<'info>,
+    pub user: UncheckedAccount<'info>,
 
     #[account(mut)]
     pub pool: Account<'info, Pool>,
 
-    // LP token mint
+    /// LP token mint
     #[account(
         mut,
         constraint = lp_mint.key() == pool.lp_mint
     )]
     pub lp_mint: Account<'info, Mint>,
 
-    // User's LP token account
+    /// User's LP token account
     #[account(
         mut,
         token::authority = user,
@@ -27,7 +29,7 @@ pub struct Deposit<'info> {
     )]
     pub user_lp_token: Account<'info, TokenAccount>,
 
-    // Token accounts owned by the user - we'll handle different pool types
+    /// Depositor's token accounts; `user_token_c` is only used by the Seed Pool
     #[account(
         mut,
         token::authority = user,
@@ -50,12 +52,12 @@ pub struct Deposit<'info> {
     )]
     pub user_token_c: Option<Account<'info, TokenAccount>>,
 
-    // Token mints - must match the order in pool.token_mints
+    /// Token mints - must match the order in pool.token_mints
     pub token_mint_a: Account<'info, Mint>,
     pub token_mint_b: Account<'info, Mint>,
     pub token_mint_c: Option<Account<'info, Mint>>,
 
-    // Pool token accounts
+    /// Pool token accounts
     #[account(
         mut,
         token::authority = pool,
@@ -77,7 +79,7 @@ pub struct Deposit<'info> {
     )]
     pub pool_token_c: Option<Account<'info, TokenAccount>>,
 
-    // User position for concentrated liquidity
+    /// User position for concentrated liquidity
     #[account(
         init_if_needed,
         payer = user,
@@ -90,204 +92,215 @@ pub struct Deposit<'info> {
     pub token_program: Program<'info, Token>,
     pub system_program: Program<'info, System>,
     pub rent: Sysvar<'info, Rent>,
+
+    /// Position delegate depositing the owner's tokens under their SPL approval
+    pub delegate: Option<Signer<'info>>,
+
+    /// Pool's front-end snapshot, refreshed once the deposit settles
+    #[account(
+        mut,
+        has_one = pool,
+    )]
+    pub pool_snapshot: Option<Account<'info, PoolSnapshot>>,
+
+    /// Caller's token account of the pool's credential mint; required by permissioned pools
+    pub credential: Option<InterfaceAccount<'info, token_interface::TokenAccount>>,
+
+    /// Owner's portfolio, indexing the position once the deposit settles
+    #[account(
+        mut,
+        seeds = [&b"user-portfolio"[..], user.key().as_ref()],
+        bump = user_portfolio.bump,
+    )]
+    pub user_portfolio: Option<Account<'info, UserPortfolio>>,
 }
 
 pub fn handler(
     ctx: Context<Deposit>,
-    amounts: Vec<u64>,
+    amounts: [u64; MAX_POOL_TOKENS],
     min_lp_amount: u64,
     concentration: u64,
+    deadline: i64,
 ) -> Result<()> {
-    // Get the pool key first to avoid borrow conflicts later
-    let pool_key = ctx.accounts.pool.key();
+    crate::utils::check_deadline(deadline)?;
+    validate_deposit_accounts(ctx.accounts)?;
+    ctx.accounts.pool.update_weights_ema(Clock::get()?.slot);
 
-    // Now mutably borrow pool
-    let pool = &mut ctx.accounts.pool;
+    // Read phase: price the deposit against the current reserves
+    let pool = &ctx.accounts.pool;
+    let amounts = pool.active_amounts(&amounts)?;
 
-    // Extract all the data we need from the pool to avoid borrow conflicts later
-    let pool_type = pool.pool_type;
-    let token_mints = pool.token_mints.clone();
-    let old_reserves = pool.reserves.clone();
-    let amplification = pool.amplification;
-    let pool_bump = pool.bump;
-
-    match pool_type {
-        PoolType::Seed => {
-            require!(amounts.len() == 3, ErrorCode::InvalidInputLength);
-            require!(
-                ctx.accounts.token_mint_c.is_some(),
-                ErrorCode::InvalidTokenMint
-            );
-            require!(
-                ctx.accounts.user_token_c.is_some(),
-                ErrorCode::InvalidTokenMint
-            );
-            require!(
-                ctx.accounts.pool_token_c.is_some(),
-                ErrorCode::InvalidTokenMint
-            );
-        }
-        PoolType::Growth => {
-            require!(amounts.len() == 2, ErrorCode::InvalidInputLength);
-        }
-    }
+    // Calculate LP tokens to mint based on the invariant increase
+    let quote = quote_deposit(pool, amounts, pool.lp_supply(ctx.accounts.lp_mint.supply))?;
 
-    // Verify token mints match pool configuration
-    require!(
-        ctx.accounts.token_mint_a.key() == token_mints[0],
-        ErrorCode::InvalidTokenMint
-    );
+    // Check minimum LP amount
+    crate::utils::check_min_amount("LP minted", quote.lp_amount, min_lp_amount)?;
+
+    settle_deposit(ctx, amounts, &quote, concentration)
+}
 
+/// Deposit `amounts`, guarded by the imbalance fee instead of a minimum LP out
+///
+/// Reverts if the weight-aware deposit fee exceeds `max_imbalance_fee_bps` of
+/// the invariant growth the deposit adds. Rebated deposits always pass.
+pub fn deposit_with_max_imbalance_fee(
+    ctx: Context<Deposit>,
+    amounts: [u64; MAX_POOL_TOKENS],
+    max_imbalance_fee_bps: u64,
+    concentration: u64,
+    deadline: i64,
+) -> Result<()> {
+    crate::utils::check_deadline(deadline)?;
     require!(
-        ctx.accounts.token_mint_b.key() == token_mints[1],
-        ErrorCode::InvalidTokenMint
+        max_imbalance_fee_bps <= BPS_DENOMINATOR,
+        ErrorCode::InvalidInstructionData
     );
+    validate_deposit_accounts(ctx.accounts)?;
+    ctx.accounts.pool.update_weights_ema(Clock::get()?.slot);
+
+    let pool = &ctx.accounts.pool;
+    let amounts = pool.active_amounts(&amounts)?;
+    let quote = quote_deposit(pool, amounts, pool.lp_supply(ctx.accounts.lp_mint.supply))?;
+    crate::utils::check_max_amount(
+        "Imbalance fee (bps)",
+        quote.imbalance_fee_bps,
+        max_imbalance_fee_bps,
+    )?;
+
+    settle_deposit(ctx, amounts, &quote, concentration)
+}
 
-    if pool_type == PoolType::Seed {
-        require!(
-            ctx.accounts.token_mint_c.as_ref().unwrap().key() == token_mints[2],
-            ErrorCode::InvalidTokenMint
-        );
-    }
-
-    // Transfer tokens from user to pool
-    let total_old_reserves = old_reserves.iter().sum::<u64>();
-
-    // Handle different pool types
-    if pool_type == PoolType::Seed {
-        // For Seed Pool, handle 3 tokens
-        let token_accounts = [
-            (
-                &ctx.accounts.user_token_a,
-                &ctx.accounts.pool_token_a,
-                amounts[0],
-            ),
-            (
-                &ctx.accounts.user_token_b,
-                &ctx.accounts.pool_token_b,
-                amounts[1],
-            ),
-            (
-                ctx.accounts.user_token_c.as_ref().unwrap(),
-                ctx.accounts.pool_token_c.as_ref().unwrap(),
-                amounts[2],
-            ),
-        ];
-
-        // Process each token transfer
-        for (i, (from, to, amount)) in token_accounts.iter().enumerate() {
-            if *amount > 0 {
-                let cpi_accounts = Transfer {
-                    from: from.to_account_info(),
-                    to: to.to_account_info(),
-                    authority: ctx.accounts.user.to_account_info(),
-                };
-                let cpi_program = ctx.accounts.token_program.to_account_info();
-                let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
-                token::transfer(cpi_ctx, *amount)?;
-
-                // Update reserves
-                pool.reserves[i] += amount;
-            }
-        }
-    } else {
-        // For Growth Pool, handle 2 tokens
-        let token_accounts = [
-            (
-                &ctx.accounts.user_token_a,
-                &ctx.accounts.pool_token_a,
-                amounts[0],
-            ),
-            (
-                &ctx.accounts.user_token_b,
-                &ctx.accounts.pool_token_b,
-                amounts[1],
-            ),
-        ];
-
-        // Process each token transfer
-        for (i, (from, to, amount)) in token_accounts.iter().enumerate() {
-            if *amount > 0 {
-                let cpi_accounts = Transfer {
-                    from: from.to_account_info(),
-                    to: to.to_account_info(),
-                    authority: ctx.accounts.user.to_account_info(),
-                };
-                let cpi_program = ctx.accounts.token_program.to_account_info();
-                let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
-                token::transfer(cpi_ctx, *amount)?;
-
-                // Update reserves
-                pool.reserves[i] += amount;
-            }
-        }
+/// Deposit the balanced amounts that mint exactly `lp_amount`, each capped by `max_amounts`
+pub fn deposit_exact_lp(
+    ctx: Context<Deposit>,
+    lp_amount: u64,
+    max_amounts: [u64; MAX_POOL_TOKENS],
+    concentration: u64,
+    deadline: i64,
+) -> Result<()> {
+    crate::utils::check_deadline(deadline)?;
+    validate_deposit_accounts(ctx.accounts)?;
+    ctx.accounts.pool.update_weights_ema(Clock::get()?.slot);
+
+    let pool = &ctx.accounts.pool;
+    let max_amounts = pool.active_amounts(&max_amounts)?;
+    let lp_supply = pool.lp_supply(ctx.accounts.lp_mint.supply);
+    let (amounts, quote) = quote_exact_lp_deposit(pool, lp_amount, lp_supply)?;
+    for (i, (&amount, &max_amount)) in amounts.iter().zip(max_amounts.iter()).enumerate() {
+        crate::utils::check_max_amount(format_args!("Token {} deposit", i), amount, max_amount)?;
     }
 
-    // Calculate LP tokens to mint based on the invariant increase
-    let lp_amount: u64;
+    settle_deposit(ctx, &amounts, &quote, concentration)
+}
 
-    if total_old_reserves == 0 {
-        // Initial deposit - for simplicity, use the sum
-        lp_amount = amounts.iter().sum();
-    } else {
-        // Calculate based on invariant
-        let old_d = crate::state::math::calculate_invariant(&old_reserves, amplification)
-            .ok_or(ErrorCode::MathOverflow)?;
+/// Check the optional third token and every mint against the pool's configuration
+fn validate_deposit_accounts(accounts: &Deposit) -> Result<()> {
+    let pool = &accounts.pool;
+    pool.require_not_paused()?;
+    crate::utils::check_credential(pool, &accounts.user.key(), accounts.credential.as_deref())?;
+    // A third token needs its accounts; a Seed Pool can be down to two tokens
+    if pool.token_mints.len() > 2 {
+        require!(accounts.token_mint_c.is_some(), ErrorCode::InvalidTokenMint);
+        require!(accounts.user_token_c.is_some(), ErrorCode::InvalidTokenMint);
+        require!(accounts.pool_token_c.is_some(), ErrorCode::InvalidTokenMint);
+    }
 
-        let new_d = crate::state::math::calculate_invariant(&pool.reserves, amplification)
-            .ok_or(ErrorCode::MathOverflow)?;
+    // Verify token mints and vaults match pool configuration
+    crate::utils::check_pool_mint(pool, 0, &accounts.token_mint_a.key())?;
+    crate::utils::check_pool_mint(pool, 1, &accounts.token_mint_b.key())?;
+    if let Some(token_mint_c) = &accounts.token_mint_c {
+        crate::utils::check_pool_mint(pool, 2, &token_mint_c.key())?;
+    }
 
-        // LP tokens minted proportional to invariant growth
-        let lp_supply = ctx.accounts.lp_mint.supply;
-        lp_amount = (lp_supply as u128 * (new_d - old_d) as u128 / old_d as u128) as u64;
+    crate::utils::check_pool_vault(pool, 0, &accounts.pool_token_a.key())?;
+    crate::utils::check_pool_vault(pool, 1, &accounts.pool_token_b.key())?;
+    if let Some(pool_token_c) = &accounts.pool_token_c {
+        crate::utils::check_pool_vault(pool, 2, &pool_token_c.key())?;
     }
 
-    // Check minimum LP amount
-    require!(lp_amount >= min_lp_amount, ErrorCode::SlippageExceeded);
+    Ok(())
+}
 
-    // Now prepare the seeds for the CPI call
-    let partner_token_mint_ref = if pool_type == PoolType::Growth {
-        Some(token_mints[1].as_ref())
+/// Move priced `amounts` into the pool, mint `quote.lp_amount` and credit the position
+fn settle_deposit(
+    ctx: Context<Deposit>,
+    amounts: &[u64],
+    quote: &DepositQuote,
+    concentration: u64,
+) -> Result<()> {
+    let authority = ctx
+        .accounts
+        .user_position
+        .acting_authority(&ctx.accounts.user, ctx.accounts.delegate.as_ref())?;
+    let pool = &ctx.accounts.pool;
+    let pool_key = pool.key();
+    let lp_amount = quote.lp_amount;
+
+    // Spread early LP share by capping each position while the pool bootstraps
+    let now = Clock::get()?.unix_timestamp;
+    let bootstrap_deposited = if now < pool.bootstrap_end_ts {
+        let deposited = ctx
+            .accounts
+            .user_position
+            .bootstrap_deposited
+            .saturating_add(amounts.iter().sum());
+        require!(
+            deposited <= pool.max_deposit_per_wallet,
+            ErrorCode::BootstrapDepositLimitExceeded
+        );
+        Some(deposited)
     } else {
         None
     };
 
-    let seed_type = if pool_type == PoolType::Seed {
-        &b"seed"[..]
-    } else {
-        &b"growth"[..]
-    };
-
-    // Store the seeds in longer-lived variables
-    let seed_pool = &b"pool"[..];
-
-    // Clone the pool account info to avoid borrow conflicts
-    let pool_account_info = ctx.accounts.pool.to_account_info();
-
-    let seeds_with_partner = [
-        seed_pool,
-        seed_type,
-        partner_token_mint_ref.unwrap_or(&[]),
-        &[pool_bump],
+    // CPI phase: transfer tokens from user to pool
+    let user_tokens = [
+        Some(&ctx.accounts.user_token_a),
+        Some(&ctx.accounts.user_token_b),
+        ctx.accounts.user_token_c.as_ref(),
+    ];
+    let pool_tokens = [
+        Some(&ctx.accounts.pool_token_a),
+        Some(&ctx.accounts.pool_token_b),
+        ctx.accounts.pool_token_c.as_ref(),
     ];
-    let seeds_without_partner = [seed_pool, seed_type, &[pool_bump]];
 
-    let seeds = match partner_token_mint_ref {
-        Some(_) => &seeds_with_partner[..],
-        None => &seeds_without_partner[..],
-    };
+    for (i, &amount) in amounts.iter().enumerate() {
+        if amount > 0 {
+            let (Some(from), Some(to)) = (user_tokens[i], pool_tokens[i]) else {
+                return err!(ErrorCode::InvalidTokenMint);
+            };
+            let cpi_accounts = Transfer {
+                from: from.to_account_info(),
+                to: to.to_account_info(),
+                authority: authority.clone(),
+            };
+            let cpi_program = ctx.accounts.token_program.to_account_info();
+            let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
+            token::transfer(cpi_ctx, amount)?;
+        }
+    }
 
-    let signer = &[seeds];
+    // Mint LP tokens to user; a soulbound pool only credits the position
+    if !pool.soulbound_lp {
+        let seeds = pool.signer_seeds()?;
+        let signer = &[&seeds[..]];
+
+        let cpi_accounts = token::MintTo {
+            mint: ctx.accounts.lp_mint.to_account_info(),
+            to: ctx.accounts.user_lp_token.to_account_info(),
+            authority: pool.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
+        token::mint_to(cpi_ctx, lp_amount)?;
+    }
 
-    // Mint LP tokens to user
-    let cpi_accounts = token::MintTo {
-        mint: ctx.accounts.lp_mint.to_account_info(),
-        to: ctx.accounts.user_lp_token.to_account_info(),
-        authority: pool_account_info,
-    };
-    let cpi_program = ctx.accounts.token_program.to_account_info();
-    let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
-    token::mint_to(cpi_ctx, lp_amount)?;
+    // Write phase: update reserves and cache the post-deposit invariant
+    let lp_supply_after = pool
+        .lp_supply(ctx.accounts.lp_mint.supply)
+        .saturating_add(lp_amount);
+    apply_deposit(&mut ctx.accounts.pool, quote, lp_supply_after)?;
 
     // Initialize user position if it's new
     if ctx.accounts.user_position.owner == Pubkey::default() {
@@ -295,16 +308,210 @@ pub fn handler(
         user_position.bump = ctx.bumps.user_position;
         user_position.owner = ctx.accounts.user.key();
         user_position.pool = pool_key;
-        user_position.created_at = Clock::get()?.unix_timestamp;
+        user_position.created_at = now;
+        user_position.delegate = None;
     }
 
     // Update position
     let user_position = &mut ctx.accounts.user_position;
+    let lp_amount_before = user_position.lp_amount;
+    let min_price_before = user_position.min_price;
+    let max_price_before = user_position.max_price;
+
     user_position.lp_amount += lp_amount;
     user_position.min_price = concentration.saturating_sub(1000); // Lower bound = concentration - 10%
     user_position.max_price = concentration.saturating_add(1000); // Upper bound = concentration + 10%
     user_position.is_active = true;
-    user_position.last_update = Clock::get()?.unix_timestamp;
+    user_position.last_update = now;
+    user_position.last_update_slot = Clock::get()?.slot;
+    if let Some(deposited) = bootstrap_deposited {
+        user_position.bootstrap_deposited = deposited;
+    }
+
+    emit!(PositionChanged {
+        position: user_position.key(),
+        owner: user_position.owner,
+        pool: pool_key,
+        reason: PositionChangeReason::Deposit,
+        lp_amount_before,
+        lp_amount_after: user_position.lp_amount,
+        min_price_before,
+        max_price_before,
+        min_price_after: user_position.min_price,
+        max_price_after: user_position.max_price,
+        timestamp: user_position.last_update,
+        virtual_price: ctx.accounts.pool.virtual_price,
+    });
+
+    if let Some(user_portfolio) = ctx.accounts.user_portfolio.as_mut() {
+        let user_position = &ctx.accounts.user_position;
+        user_portfolio.record_deposit(user_position.key(), user_position, amounts, now)?;
+    }
+
+    crate::instructions::pool_snapshot::refresh_if_present(
+        ctx.accounts.pool_snapshot.as_mut(),
+        &ctx.accounts.pool,
+    )
+}
+
+/// Result of pricing a deposit against the current pool state
+pub struct DepositQuote {
+    /// LP tokens to mint, net of the weight-aware deposit fee or rebate
+    pub lp_amount: u64,
+    /// Invariant over the post-deposit reserves
+    pub new_d: u64,
+    /// Post-deposit reserves, only the pool's own token slots are meaningful
+    pub new_reserves: TokenAmounts,
+    /// Deposit fee as a share of the invariant growth, in bps, rounded up; zero when rebated
+    pub imbalance_fee_bps: u64,
+}
+
+/// Price a deposit of `amounts` (one per pool token) without touching state
+pub fn quote_deposit(pool: &Pool, amounts: &[u64], lp_supply: u64) -> Result<DepositQuote> {
+    let num_tokens = pool.reserves.len();
+
+    // Post-deposit reserves, kept on the stack
+    let mut new_reserves = [0u64; MAX_POOL_TOKENS];
+    for (new_reserve, (reserve, amount)) in new_reserves
+        .iter_mut()
+        .zip(pool.reserves.iter().zip(amounts.iter()))
+    {
+        *new_reserve = reserve
+            .checked_add(*amount)
+            .ok_or(ErrorCode::MathOverflow)?;
+    }
+
+    let amplification = pool.amplification;
+    let total_old_reserves = pool.reserves.iter().sum::<u64>();
+    // A pool whose LP was all burned outside `withdraw` holds reserves no share
+    // can claim; a deposit priced as the first would collect them all
+    require!(
+        lp_supply > 0 || total_old_reserves == 0,
+        ErrorCode::OrphanedReservesPending
+    );
+    let (lp_amount, new_d, imbalance_fee_bps) = if total_old_reserves == 0 || lp_supply == 0 {
+        // Initial deposit - for simplicity, use the sum
+        let new_d =
+            crate::state::math::calculate_invariant(&new_reserves[..num_tokens], amplification)
+                .unwrap_or(0);
+        (amounts.iter().sum(), new_d, 0)
+    } else {
+        // Calculate based on invariant
+        let old_d = crate::state::math::calculate_invariant(&pool.reserves, amplification)
+            .ok_or(ErrorCode::MathOverflow)?;
+
+        let new_d =
+            crate::state::math::calculate_invariant(&new_reserves[..num_tokens], amplification)
+                .ok_or(ErrorCode::MathOverflow)?;
+
+        // Charge deposits of over-weighted tokens and rebate under-weighted ones
+        let target_weights = pool.target_weights_at(Clock::get()?.unix_timestamp);
+        let adjusted_amounts = crate::state::math::calculate_deposit_adjusted_amounts(
+            amounts,
+            &pool.reserves,
+            &target_weights,
+        );
+        let mut adjusted_reserves = [0u64; MAX_POOL_TOKENS];
+        for (adjusted_reserve, (reserve, amount)) in adjusted_reserves
+            .iter_mut()
+            .zip(pool.reserves.iter().zip(adjusted_amounts.iter()))
+        {
+            *adjusted_reserve = reserve + amount;
+        }
+        let adjusted_d = crate::state::math::calculate_invariant(
+            &adjusted_reserves[..num_tokens],
+            amplification,
+        )
+        .ok_or(ErrorCode::MathOverflow)?;
+
+        // LP tokens minted proportional to fee-adjusted invariant growth
+        let lp_amount =
+            crate::state::rounding::lp_minted(lp_supply, adjusted_d.saturating_sub(old_d), old_d)
+                .ok_or(ErrorCode::MathOverflow)?;
+
+        // Invariant the fee withholds from the deposit's own growth
+        let growth = new_d.saturating_sub(old_d);
+        let imbalance_fee_bps = if growth == 0 {
+            0
+        } else {
+            crate::state::rounding::mul_div(
+                new_d.saturating_sub(adjusted_d),
+                BPS_DENOMINATOR,
+                growth,
+                crate::state::rounding::Rounding::Up,
+            )
+            .ok_or(ErrorCode::MathOverflow)?
+        };
+        (lp_amount, new_d, imbalance_fee_bps)
+    };
+
+    Ok(DepositQuote {
+        lp_amount,
+        new_d,
+        new_reserves,
+        imbalance_fee_bps,
+    })
+}
+
+/// Price a balanced deposit minting exactly `lp_amount`, returning the inputs it needs
+///
+/// Each input is the pool's reserve share for `lp_amount`, rounded up so the pool
+/// never mints LP it is not paid for. Balanced deposits leave weights unchanged,
+/// so no deposit fee or rebate applies.
+pub fn quote_exact_lp_deposit(
+    pool: &Pool,
+    lp_amount: u64,
+    lp_supply: u64,
+) -> Result<(Vec<u64>, DepositQuote)> {
+    require!(lp_amount > 0, ErrorCode::ZeroShares);
+    require!(
+        lp_supply > 0 && pool.reserves.iter().all(|&reserve| reserve > 0),
+        ErrorCode::InsufficientLiquidity
+    );
+
+    let num_tokens = pool.reserves.len();
+    let mut amounts = Vec::with_capacity(num_tokens);
+    let mut new_reserves = [0u64; MAX_POOL_TOKENS];
+    for (new_reserve, &reserve) in new_reserves.iter_mut().zip(pool.reserves.iter()) {
+        let amount = crate::state::rounding::lp_cost(reserve, lp_amount, lp_supply)
+            .ok_or(ErrorCode::MathOverflow)?;
+        *new_reserve = reserve.checked_add(amount).ok_or(ErrorCode::MathOverflow)?;
+        amounts.push(amount);
+    }
+
+    let new_d =
+        crate::state::math::calculate_invariant(&new_reserves[..num_tokens], pool.amplification)
+            .ok_or(ErrorCode::MathOverflow)?;
+
+    Ok((
+        amounts,
+        DepositQuote {
+            lp_amount,
+            new_d,
+            new_reserves,
+            imbalance_fee_bps: 0,
+        },
+    ))
+}
+
+/// Apply a priced deposit to the pool reserves, invariant and virtual price
+pub fn apply_deposit(
+    pool: &mut Account<Pool>,
+    quote: &DepositQuote,
+    lp_supply_after: u64,
+) -> Result<()> {
+    let num_tokens = pool.reserves.len();
+    pool.update_weights_ema(Clock::get()?.slot);
+    pool.reserves
+        .copy_from_slice(&quote.new_reserves[..num_tokens]);
+    pool.last_d = quote.new_d;
+    pool.d_slot = Clock::get()?.slot;
+    pool.refresh_virtual_price(lp_supply_after);
+    let (now, _) = crate::utils::touch_pool(pool)?;
+    pool.refresh_routing_hint(now);
+    if pool.soulbound_lp {
+        pool.internal_lp_supply = lp_supply_after;
+    }
 
     Ok(())
 }
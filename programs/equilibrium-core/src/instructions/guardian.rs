@@ -0,0 +1,36 @@
+use crate::errors::ErrorCode;
+use crate::events::PoolPauseChanged;
+use crate::state::*;
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct PausePool<'info> {
+    /// A guardian listed on the config, or its authority
+    pub guardian: Signer<'info>,
+
+    #[account(
+        constraint = amm_config.can_pause(&guardian.key()) @ ErrorCode::Unauthorized,
+    )]
+    pub amm_config: Account<'info, AmmConfig>,
+
+    #[account(
+        mut,
+        has_one = amm_config,
+    )]
+    pub pool: Account<'info, Pool>,
+}
+
+/// Halt swaps and deposits on the pool until the authority unpauses it
+pub fn pause_pool(ctx: Context<PausePool>) -> Result<()> {
+    let pool = &mut ctx.accounts.pool;
+    if !pool.paused {
+        pool.paused = true;
+        emit!(PoolPauseChanged {
+            pool: pool.key(),
+            paused: true,
+            signer: ctx.accounts.guardian.key(),
+        });
+    }
+
+    Ok(())
+}
@@ -3,6 +3,7 @@ use crate::state::*;
 use anchor_lang::prelude::*;
 
 #[derive(Accounts)]
+#[instruction(index: u16)]
 pub struct Initialize<'info> {
     #[account(mut)]
     pub authority: Signer<'info>,
@@ -10,8 +11,8 @@ pub struct Initialize<'info> {
     #[account(
         init,
         payer = authority,
-        space = 8 + 1 + 32 + 32 + 8 + (3 * 8), // anchor discriminator + bump + authority + fee_recipient + amplification + target_weights
-        seeds = [&b"amm-config"[..]],
+        space = AmmConfig::space(),
+        seeds = [&b"amm-config"[..], &index.to_le_bytes()],
         bump
     )]
     pub amm_config: Account<'info, AmmConfig>,
@@ -21,6 +22,7 @@ pub struct Initialize<'info> {
 
 pub fn handler(
     ctx: Context<Initialize>,
+    index: u16,
     default_amplification: u64,
     default_target_weights: [u64; 3],
 ) -> Result<()> {
@@ -30,12 +32,55 @@ pub fn handler(
     let sum: u64 = default_target_weights.iter().sum();
     require!(sum == 10000, ErrorCode::InvalidWeights);
 
+    require!(
+        crate::state::math::is_valid_amplification(default_amplification),
+        ErrorCode::InvalidAmplification
+    );
+
     // Set config values - using the new direct bump access
     amm_config.bump = ctx.bumps.amm_config;
+    amm_config.index = index;
     amm_config.authority = ctx.accounts.authority.key();
     amm_config.fee_recipient = ctx.accounts.authority.key(); // Initially set to authority
     amm_config.default_amplification = default_amplification;
     amm_config.default_target_weights = default_target_weights;
 
+    // Rebalancing stays disabled until a keeper and venue are configured
+    amm_config.keeper = Pubkey::default();
+    amm_config.rebalance_program = Pubkey::default();
+    amm_config.max_rebalance_bps = 0;
+
+    // Growth pool creation is open until the allowlist is switched on
+    amm_config.require_partner_approval = false;
+
+    // Only the authority creates Growth pools until permissionless mode is enabled
+    amm_config.permissionless_pool_creation = false;
+    amm_config.pool_creation_fee_lamports = 0;
+    amm_config.pool_creation_fee_usd_star = 0;
+
+    // No authority handoff in progress
+    amm_config.pending_authority = Pubkey::default();
+
+    // Vote escrow locking stays off until a governance token is chosen
+    amm_config.ve_mint = Pubkey::default();
+
+    // Cranks go untipped until the authority funds them out of protocol fees
+    amm_config.crank_tip_amount = 0;
+    amm_config.max_crank_tips_per_slot = 0;
+
+    // Swaps ignore oracle staleness until the authority sets a threshold
+    amm_config.max_oracle_age_slots = 0;
+    amm_config.reject_stale_swaps = false;
+
+    // Only the authority can pause pools until guardians are added
+    amm_config.guardians = Vec::new();
+
+    // No external program may be called until the authority whitelists it
+    amm_config.whitelisted_programs = Vec::new();
+
+    amm_config.version = CONFIG_VERSION;
+    amm_config.protocol_fee_share_bps = 0;
+    amm_config.reserved = [0; CONFIG_RESERVED_BYTES];
+
     Ok(())
 }
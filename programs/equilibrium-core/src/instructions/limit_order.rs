@@ -0,0 +1,303 @@
+use crate::errors::ErrorCode;
+use crate::instructions::swap::{apply_swap, quote_swap_with_min_fee, stale_pool_min_fee};
+use crate::state::*;
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, CloseAccount, Mint, Token, TokenAccount, Transfer};
+
+#[derive(Accounts)]
+#[instruction(order_id: u64)]
+pub struct PlaceLimitOrder<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    /// Limit orders ship behind `FEATURE_LIMIT_ORDERS`
+    #[account(
+        seeds = [&b"program-state"[..]],
+        bump = program_state.bump,
+        constraint = program_state.is_enabled(FEATURE_LIMIT_ORDERS) @ ErrorCode::FeatureDisabled,
+    )]
+    pub program_state: Account<'info, ProgramState>,
+
+    pub pool: Account<'info, Pool>,
+
+    /// Token being escrowed and the token wanted in return
+    pub token_mint_in: Account<'info, Mint>,
+    pub token_mint_out: Account<'info, Mint>,
+
+    /// Owner's token account funding the order
+    #[account(
+        mut,
+        token::authority = owner,
+        token::mint = token_mint_in,
+    )]
+    pub owner_token_in: Account<'info, TokenAccount>,
+
+    #[account(
+        init,
+        payer = owner,
+        space = LimitOrder::space(),
+        seeds = [&b"limit-order"[..], owner.key().as_ref(), pool.key().as_ref(), &order_id.to_le_bytes()],
+        bump
+    )]
+    pub limit_order: Account<'info, LimitOrder>,
+
+    /// Escrow owned by the order PDA
+    #[account(
+        init,
+        payer = owner,
+        token::mint = token_mint_in,
+        token::authority = limit_order,
+        seeds = [&b"limit-order-escrow"[..], limit_order.key().as_ref()],
+        bump
+    )]
+    pub escrow: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+pub fn place_limit_order(
+    ctx: Context<PlaceLimitOrder>,
+    order_id: u64,
+    amount_in: u64,
+    min_amount_out: u64,
+) -> Result<()> {
+    let pool = &ctx.accounts.pool;
+
+    // Both tokens must be tradable in the pool
+    let token_in_idx = pool
+        .token_index(&ctx.accounts.token_mint_in.key())
+        .ok_or(ErrorCode::InvalidTokenMint)?;
+    let token_out_idx = pool
+        .token_index(&ctx.accounts.token_mint_out.key())
+        .ok_or(ErrorCode::InvalidTokenMint)?;
+    require!(token_in_idx != token_out_idx, ErrorCode::InvalidSwap);
+    require!(
+        amount_in > 0 && min_amount_out > 0,
+        ErrorCode::InvalidInstructionData
+    );
+
+    // Escrow the input tokens
+    let cpi_accounts = Transfer {
+        from: ctx.accounts.owner_token_in.to_account_info(),
+        to: ctx.accounts.escrow.to_account_info(),
+        authority: ctx.accounts.owner.to_account_info(),
+    };
+    let cpi_program = ctx.accounts.token_program.to_account_info();
+    let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
+    token::transfer(cpi_ctx, amount_in)?;
+
+    // Record the order
+    let limit_order = &mut ctx.accounts.limit_order;
+    limit_order.bump = ctx.bumps.limit_order;
+    limit_order.escrow_bump = ctx.bumps.escrow;
+    limit_order.owner = ctx.accounts.owner.key();
+    limit_order.pool = ctx.accounts.pool.key();
+    limit_order.order_id = order_id;
+    limit_order.token_mint_in = ctx.accounts.token_mint_in.key();
+    limit_order.token_mint_out = ctx.accounts.token_mint_out.key();
+    limit_order.escrow = ctx.accounts.escrow.key();
+    limit_order.amount_in = amount_in;
+    limit_order.min_amount_out = min_amount_out;
+    limit_order.created_at = Clock::get()?.unix_timestamp;
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct CancelLimitOrder<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        mut,
+        has_one = owner @ ErrorCode::Unauthorized,
+        has_one = escrow,
+        close = owner,
+    )]
+    pub limit_order: Account<'info, LimitOrder>,
+
+    #[account(mut)]
+    pub escrow: Account<'info, TokenAccount>,
+
+    /// Owner's account receiving the refund
+    #[account(
+        mut,
+        token::authority = owner,
+        token::mint = limit_order.token_mint_in,
+    )]
+    pub owner_token_in: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+pub fn cancel_limit_order(ctx: Context<CancelLimitOrder>) -> Result<()> {
+    let limit_order = &ctx.accounts.limit_order;
+    let order_id = limit_order.order_id.to_le_bytes();
+    let seeds = &[
+        &b"limit-order"[..],
+        limit_order.owner.as_ref(),
+        limit_order.pool.as_ref(),
+        &order_id,
+        &[limit_order.bump],
+    ];
+    let signer = &[&seeds[..]];
+
+    // Refund escrowed tokens
+    let cpi_accounts = Transfer {
+        from: ctx.accounts.escrow.to_account_info(),
+        to: ctx.accounts.owner_token_in.to_account_info(),
+        authority: limit_order.to_account_info(),
+    };
+    let cpi_program = ctx.accounts.token_program.to_account_info();
+    let cpi_ctx = CpiContext::new_with_signer(cpi_program.clone(), cpi_accounts, signer);
+    token::transfer(cpi_ctx, ctx.accounts.escrow.amount)?;
+
+    // Close the escrow, returning rent to the owner
+    let cpi_accounts = CloseAccount {
+        account: ctx.accounts.escrow.to_account_info(),
+        destination: ctx.accounts.owner.to_account_info(),
+        authority: limit_order.to_account_info(),
+    };
+    let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
+    token::close_account(cpi_ctx)?;
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct FillLimitOrder<'info> {
+    /// Anyone can crank a fill once the limit is crossed
+    pub keeper: Signer<'info>,
+
+    /// CHECK: Order owner, receives the closed accounts' rent
+    #[account(mut)]
+    pub owner: AccountInfo<'info>,
+
+    /// Config holding the protocol fee share
+    pub amm_config: Account<'info, AmmConfig>,
+
+    #[account(
+        mut,
+        has_one = amm_config,
+    )]
+    pub pool: Account<'info, Pool>,
+
+    #[account(
+        mut,
+        has_one = owner,
+        has_one = pool,
+        has_one = escrow,
+        close = owner,
+    )]
+    pub limit_order: Account<'info, LimitOrder>,
+
+    #[account(mut)]
+    pub escrow: Account<'info, TokenAccount>,
+
+    /// Pool's token accounts
+    #[account(
+        mut,
+        token::authority = pool,
+        token::mint = limit_order.token_mint_in,
+    )]
+    pub pool_token_in: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        token::authority = pool,
+        token::mint = limit_order.token_mint_out,
+    )]
+    pub pool_token_out: Account<'info, TokenAccount>,
+
+    /// Owner's account receiving the output
+    #[account(
+        mut,
+        token::authority = owner,
+        token::mint = limit_order.token_mint_out,
+    )]
+    pub owner_token_out: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+pub fn fill_limit_order(ctx: Context<FillLimitOrder>) -> Result<()> {
+    let limit_order = &ctx.accounts.limit_order;
+    let amount_in = limit_order.amount_in;
+
+    // Price the order against the pool
+    let pool = &mut ctx.accounts.pool;
+    let token_in_idx = pool
+        .token_index(&limit_order.token_mint_in)
+        .ok_or(ErrorCode::InvalidTokenMint)?;
+    let token_out_idx = pool
+        .token_index(&limit_order.token_mint_out)
+        .ok_or(ErrorCode::InvalidTokenMint)?;
+    crate::utils::check_pool_vault(pool, token_in_idx, &ctx.accounts.pool_token_in.key())?;
+    crate::utils::check_pool_vault(pool, token_out_idx, &ctx.accounts.pool_token_out.key())?;
+
+    pool.update_weights_ema(Clock::get()?.slot);
+    let min_fee = stale_pool_min_fee(pool, &ctx.accounts.amm_config)?;
+    let quote = quote_swap_with_min_fee(pool, token_in_idx, token_out_idx, amount_in, 0, min_fee)?;
+    require!(
+        quote.amount_out >= limit_order.min_amount_out,
+        ErrorCode::LimitPriceNotReached
+    );
+
+    pool.require_permissionless()?;
+    apply_swap(
+        pool,
+        &ctx.accounts.amm_config,
+        limit_order.owner,
+        token_in_idx,
+        token_out_idx,
+        amount_in,
+        &quote,
+    )?;
+    crate::utils::touch_pool(pool)?;
+
+    // Move escrowed tokens into the pool
+    let order_id = limit_order.order_id.to_le_bytes();
+    let order_seeds = &[
+        &b"limit-order"[..],
+        limit_order.owner.as_ref(),
+        limit_order.pool.as_ref(),
+        &order_id,
+        &[limit_order.bump],
+    ];
+    let order_signer = &[&order_seeds[..]];
+
+    let cpi_accounts = Transfer {
+        from: ctx.accounts.escrow.to_account_info(),
+        to: ctx.accounts.pool_token_in.to_account_info(),
+        authority: limit_order.to_account_info(),
+    };
+    let cpi_program = ctx.accounts.token_program.to_account_info();
+    let cpi_ctx = CpiContext::new_with_signer(cpi_program.clone(), cpi_accounts, order_signer);
+    token::transfer(cpi_ctx, amount_in)?;
+
+    // Pay the owner from the pool
+    let pool = &ctx.accounts.pool;
+    let pool_seeds = pool.signer_seeds()?;
+    let pool_signer = &[&pool_seeds[..]];
+
+    let cpi_accounts = Transfer {
+        from: ctx.accounts.pool_token_out.to_account_info(),
+        to: ctx.accounts.owner_token_out.to_account_info(),
+        authority: pool.to_account_info(),
+    };
+    let cpi_ctx = CpiContext::new_with_signer(cpi_program.clone(), cpi_accounts, pool_signer);
+    token::transfer(cpi_ctx, quote.amount_out)?;
+
+    // Close the now-empty escrow
+    let cpi_accounts = CloseAccount {
+        account: ctx.accounts.escrow.to_account_info(),
+        destination: ctx.accounts.owner.to_account_info(),
+        authority: limit_order.to_account_info(),
+    };
+    let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, order_signer);
+    token::close_account(cpi_ctx)?;
+
+    Ok(())
+}
@@ -0,0 +1,233 @@
+use crate::errors::ErrorCode;
+use crate::events::{LiquidityLocked, PositionChangeReason, PositionChanged};
+use crate::state::*;
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, CloseAccount, Mint, Token, TokenAccount, Transfer};
+
+#[derive(Accounts)]
+#[instruction(lock_id: u64)]
+pub struct LockLiquidity<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        constraint = pool.pool_type == PoolType::Growth @ ErrorCode::InvalidPoolType,
+    )]
+    pub pool: Account<'info, Pool>,
+
+    #[account(
+        constraint = lp_mint.key() == pool.lp_mint @ ErrorCode::InvalidTokenMint,
+    )]
+    pub lp_mint: Account<'info, Mint>,
+
+    #[account(
+        init,
+        payer = owner,
+        space = LockedPosition::space(),
+        seeds = [&b"locked-position"[..], owner.key().as_ref(), pool.key().as_ref(), &lock_id.to_le_bytes()],
+        bump
+    )]
+    pub locked_position: Account<'info, LockedPosition>,
+
+    /// Vault holding the locked LP, owned by the lock PDA
+    #[account(
+        init,
+        payer = owner,
+        seeds = [&b"locked-position-vault"[..], locked_position.key().as_ref()],
+        bump,
+        token::mint = lp_mint,
+        token::authority = locked_position,
+    )]
+    pub vault: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        token::authority = owner,
+        token::mint = lp_mint,
+    )]
+    pub owner_lp_token: Account<'info, TokenAccount>,
+
+    /// Owner's position, debited for the LP it no longer holds while locked
+    #[account(
+        mut,
+        seeds = [&b"user-position"[..], owner.key().as_ref(), pool.key().as_ref()],
+        bump = user_position.bump,
+    )]
+    pub user_position: Account<'info, UserPosition>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+/// Lock `lp_amount` of a Growth pool's LP for `duration` seconds, releasing
+/// nothing for the first `cliff_duration` seconds and vesting linearly after
+///
+/// The LP leaves the owner's position while locked, so it must be LP the
+/// position holds; releases credit it back as it vests.
+pub fn lock_liquidity(
+    ctx: Context<LockLiquidity>,
+    lock_id: u64,
+    lp_amount: u64,
+    duration: i64,
+    cliff_duration: i64,
+) -> Result<()> {
+    ctx.accounts.pool.require_transferable_lp()?;
+    require!(lp_amount > 0, ErrorCode::InvalidInstructionData);
+    require!(
+        duration > 0
+            && duration <= MAX_LIQUIDITY_LOCK_DURATION
+            && (0..=duration).contains(&cliff_duration),
+        ErrorCode::InvalidLockSchedule
+    );
+
+    let cpi_accounts = Transfer {
+        from: ctx.accounts.owner_lp_token.to_account_info(),
+        to: ctx.accounts.vault.to_account_info(),
+        authority: ctx.accounts.owner.to_account_info(),
+    };
+    let cpi_program = ctx.accounts.token_program.to_account_info();
+    token::transfer(CpiContext::new(cpi_program, cpi_accounts), lp_amount)?;
+
+    let clock = Clock::get()?;
+    let now = clock.unix_timestamp;
+    let user_position = &mut ctx.accounts.user_position;
+    let lp_amount_before = user_position.lp_amount;
+    user_position.debit_lp(lp_amount, now, clock.slot)?;
+    emit!(PositionChanged::lp_only(
+        user_position.key(),
+        user_position,
+        PositionChangeReason::LiquidityLock,
+        lp_amount_before,
+        ctx.accounts.pool.virtual_price,
+    ));
+
+    let locked_position = &mut ctx.accounts.locked_position;
+    locked_position.bump = ctx.bumps.locked_position;
+    locked_position.owner = ctx.accounts.owner.key();
+    locked_position.pool = ctx.accounts.pool.key();
+    locked_position.lock_id = lock_id;
+    locked_position.vault = ctx.accounts.vault.key();
+    locked_position.amount = lp_amount;
+    locked_position.released = 0;
+    locked_position.start_ts = now;
+    locked_position.cliff_ts = now.saturating_add(cliff_duration);
+    locked_position.end_ts = now.saturating_add(duration);
+
+    emit!(LiquidityLocked {
+        pool: locked_position.pool,
+        owner: locked_position.owner,
+        locked_position: locked_position.key(),
+        lp_amount,
+        cliff_ts: locked_position.cliff_ts,
+        end_ts: locked_position.end_ts,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct ReleaseLiquidity<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    pub pool: Account<'info, Pool>,
+
+    #[account(
+        mut,
+        seeds = [
+            &b"locked-position"[..],
+            owner.key().as_ref(),
+            locked_position.pool.as_ref(),
+            &locked_position.lock_id.to_le_bytes(),
+        ],
+        bump = locked_position.bump,
+        has_one = owner @ ErrorCode::Unauthorized,
+        has_one = pool,
+        has_one = vault,
+    )]
+    pub locked_position: Account<'info, LockedPosition>,
+
+    #[account(mut)]
+    pub vault: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        token::authority = owner,
+        token::mint = vault.mint,
+    )]
+    pub owner_lp_token: Account<'info, TokenAccount>,
+
+    /// Owner's position, credited with the LP released to them
+    #[account(
+        mut,
+        seeds = [&b"user-position"[..], owner.key().as_ref(), pool.key().as_ref()],
+        bump = user_position.bump,
+    )]
+    pub user_position: Account<'info, UserPosition>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+/// Pay out the LP vested so far, back into the owner's position; the lock and
+/// its vault are closed once everything has been released
+pub fn release_liquidity(ctx: Context<ReleaseLiquidity>) -> Result<()> {
+    let clock = Clock::get()?;
+    let now = clock.unix_timestamp;
+    let locked_position = &ctx.accounts.locked_position;
+    let amount = locked_position.releasable_amount(now);
+    require!(amount > 0, ErrorCode::LockNotExpired);
+
+    let lock_id = locked_position.lock_id.to_le_bytes();
+    let seeds = &[
+        &b"locked-position"[..],
+        locked_position.owner.as_ref(),
+        locked_position.pool.as_ref(),
+        &lock_id,
+        &[locked_position.bump],
+    ];
+    let signer = &[&seeds[..]];
+    let cpi_accounts = Transfer {
+        from: ctx.accounts.vault.to_account_info(),
+        to: ctx.accounts.owner_lp_token.to_account_info(),
+        authority: locked_position.to_account_info(),
+    };
+    let cpi_program = ctx.accounts.token_program.to_account_info();
+    let cpi_ctx = CpiContext::new_with_signer(cpi_program.clone(), cpi_accounts, signer);
+    token::transfer(cpi_ctx, amount)?;
+
+    let user_position = &mut ctx.accounts.user_position;
+    let lp_amount_before = user_position.lp_amount;
+    let (owner, pool, bump) = (user_position.owner, user_position.pool, user_position.bump);
+    user_position.credit_lp(owner, pool, bump, amount, now, clock.slot)?;
+    emit!(PositionChanged::lp_only(
+        user_position.key(),
+        user_position,
+        PositionChangeReason::LiquidityLock,
+        lp_amount_before,
+        ctx.accounts.pool.virtual_price,
+    ));
+
+    let locked_position = &ctx.accounts.locked_position;
+    let released = locked_position
+        .released
+        .checked_add(amount)
+        .ok_or(ErrorCode::MathOverflow)?;
+    if released < locked_position.amount {
+        ctx.accounts.locked_position.released = released;
+        return Ok(());
+    }
+
+    // Fully released: close the vault and the lock, returning rent to the owner
+    let cpi_accounts = CloseAccount {
+        account: ctx.accounts.vault.to_account_info(),
+        destination: ctx.accounts.owner.to_account_info(),
+        authority: locked_position.to_account_info(),
+    };
+    let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
+    token::close_account(cpi_ctx)?;
+
+    ctx.accounts
+        .locked_position
+        .close(ctx.accounts.owner.to_account_info())
+}
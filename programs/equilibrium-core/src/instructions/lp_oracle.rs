@@ -0,0 +1,91 @@
+use crate::errors::ErrorCode;
+use crate::state::math::VIRTUAL_PRICE_PRECISION;
+use crate::state::*;
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct InitLpOracle<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub pool: Account<'info, Pool>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = LpOracleAdapter::space(),
+        seeds = [&b"lp-oracle"[..], pool.key().as_ref()],
+        bump
+    )]
+    pub lp_oracle: Account<'info, LpOracleAdapter>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn init_lp_oracle(ctx: Context<InitLpOracle>) -> Result<()> {
+    let lp_oracle = &mut ctx.accounts.lp_oracle;
+    lp_oracle.bump = ctx.bumps.lp_oracle;
+    lp_oracle.pool = ctx.accounts.pool.key();
+    lp_oracle.expo = LP_ORACLE_EXPO;
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct UpdateLpOracle<'info> {
+    /// Crank run by the configured keeper, which sources the token prices
+    pub keeper: Signer<'info>,
+
+    #[account(
+        has_one = keeper @ ErrorCode::Unauthorized,
+    )]
+    pub amm_config: Account<'info, AmmConfig>,
+
+    #[account(
+        has_one = amm_config,
+    )]
+    pub pool: Account<'info, Pool>,
+
+    #[account(
+        mut,
+        has_one = pool,
+        seeds = [&b"lp-oracle"[..], pool.key().as_ref()],
+        bump = lp_oracle.bump,
+    )]
+    pub lp_oracle: Account<'info, LpOracleAdapter>,
+}
+
+/// Publish a new LP price from per-token USD prices given with `LP_ORACLE_EXPO`
+pub fn update_lp_oracle(ctx: Context<UpdateLpOracle>, token_prices: Vec<u64>) -> Result<()> {
+    let pool = &ctx.accounts.pool;
+    require!(
+        token_prices.len() == pool.token_mints.len(),
+        ErrorCode::InvalidInputLength
+    );
+
+    let min_token_price = *token_prices.iter().min().unwrap_or(&0);
+    let max_token_price = *token_prices.iter().max().unwrap_or(&0);
+    require!(min_token_price > 0, ErrorCode::InvalidOraclePrice);
+
+    // Value every LP token as if the whole pool were the cheapest token, at D
+    // solved from the reserves rather than the estimate swaps keep between recomputes
+    let virtual_price = pool
+        .exact_virtual_price()
+        .ok_or(ErrorCode::InsufficientLiquidity)?;
+    let to_lp_units =
+        |token_price: u64| token_price as u128 * virtual_price / VIRTUAL_PRICE_PRECISION;
+    let price = i64::try_from(to_lp_units(min_token_price)).map_err(|_| ErrorCode::MathOverflow)?;
+    let conf = u64::try_from(to_lp_units(max_token_price - min_token_price))
+        .map_err(|_| ErrorCode::MathOverflow)?;
+
+    let clock = Clock::get()?;
+    let lp_oracle = &mut ctx.accounts.lp_oracle;
+    lp_oracle.price = price;
+    lp_oracle.conf = conf;
+    lp_oracle.min_token_price = min_token_price;
+    lp_oracle.virtual_price = virtual_price;
+    lp_oracle.publish_slot = clock.slot;
+    lp_oracle.publish_time = clock.unix_timestamp;
+
+    Ok(())
+}
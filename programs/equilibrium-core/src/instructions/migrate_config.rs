@@ -0,0 +1,80 @@
+use crate::errors::ErrorCode;
+use crate::events::ConfigMigrated;
+use crate::state::*;
+use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+
+#[derive(Accounts)]
+#[instruction(index: u16)]
+pub struct MigrateConfig<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// CHECK: Deserialized by the handler once grown to the current layout; older
+    /// configs may be too short to load as `AmmConfig` before that
+    #[account(
+        mut,
+        owner = crate::ID,
+        seeds = [&b"amm-config"[..], &index.to_le_bytes()],
+        bump,
+    )]
+    pub amm_config: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Upgrade a config to the `CONFIG_VERSION` layout, in place and under the same address
+///
+/// The account is grown (the authority paying the extra rent) before it is loaded,
+/// then each step from its stored version onwards sets the fields that version added.
+pub fn migrate_config(ctx: Context<MigrateConfig>, _index: u16) -> Result<()> {
+    let info = ctx.accounts.amm_config.to_account_info();
+    let space = AmmConfig::space();
+    if info.data_len() < space {
+        let shortfall = Rent::get()?
+            .minimum_balance(space)
+            .saturating_sub(info.lamports());
+        if shortfall > 0 {
+            let cpi_accounts = system_program::Transfer {
+                from: ctx.accounts.authority.to_account_info(),
+                to: info.clone(),
+            };
+            let cpi_ctx =
+                CpiContext::new(ctx.accounts.system_program.to_account_info(), cpi_accounts);
+            system_program::transfer(cpi_ctx, shortfall)?;
+        }
+        // Zero-filled, so fields past the old layout read as unset
+        info.realloc(space, true)?;
+    }
+
+    let mut amm_config = AmmConfig::try_deserialize(&mut &info.try_borrow_data()?[..])?;
+    require_keys_eq!(
+        amm_config.authority,
+        ctx.accounts.authority.key(),
+        ErrorCode::Unauthorized
+    );
+    let from_version = amm_config.version;
+    require!(
+        from_version < CONFIG_VERSION,
+        ErrorCode::ConfigAlreadyMigrated
+    );
+
+    if from_version < 1 {
+        // Version 1 introduced the version itself and the reserved headroom
+        amm_config.reserved = [0; CONFIG_RESERVED_BYTES];
+    }
+    if from_version < 2 {
+        // Version 2 added the config-wide protocol fee share, off until the authority sets it
+        amm_config.protocol_fee_share_bps = 0;
+    }
+    amm_config.version = CONFIG_VERSION;
+    amm_config.try_serialize(&mut &mut info.try_borrow_mut_data()?[..])?;
+
+    emit!(ConfigMigrated {
+        amm_config: info.key(),
+        from_version,
+        to_version: CONFIG_VERSION,
+    });
+
+    Ok(())
+}
@@ -1,19 +1,143 @@
+pub mod add_pool_token;
+pub mod autocompounder;
+pub mod batch_auction;
+pub mod close_pool_vault;
+pub mod crank_tip;
 pub mod create_pool;
+pub mod dca;
 pub mod deposit;
+pub mod guardian;
 pub mod initialize;
+pub mod limit_order;
+pub mod liquidity_lock;
+pub mod lp_oracle;
+pub mod migrate_config;
+pub mod orphaned_reserves;
+pub mod partner_approval;
+pub mod partner_oracle;
+pub mod partner_stats;
+pub mod pool_snapshot;
+pub mod pool_stats;
+pub mod portfolio;
+pub mod position_delegate;
+pub mod program_state;
+pub mod rebalance;
+pub mod recompute_invariant;
+pub mod referral;
+pub mod relayed_swap;
+pub mod staged_withdraw;
 pub mod swap;
+pub mod swap_basket;
+pub mod swap_hook;
+pub mod swap_partner_to_partner;
+pub mod transfer_position;
+pub mod treasury;
+pub mod update_config;
+pub mod update_pool;
+pub mod views;
+pub mod vote_escrow;
+pub mod voting_snapshot;
 pub mod withdraw;
+pub mod withdraw_all_positions;
+pub mod withdraw_queue;
 
 // Re-export everything from each module including hidden generated types
+pub use add_pool_token::*;
+pub use autocompounder::*;
+pub use batch_auction::*;
+pub use close_pool_vault::*;
 pub use create_pool::*;
+pub use dca::*;
 pub use deposit::*;
+pub use guardian::*;
 pub use initialize::*;
+pub use limit_order::*;
+pub use liquidity_lock::*;
+pub use lp_oracle::*;
+pub use migrate_config::*;
+pub use orphaned_reserves::*;
+pub use partner_approval::*;
+pub use partner_oracle::*;
+pub use partner_stats::*;
+pub use pool_snapshot::*;
+pub use pool_stats::*;
+pub use portfolio::*;
+pub use position_delegate::*;
+pub use program_state::*;
+pub use rebalance::*;
+pub use recompute_invariant::*;
+pub use referral::*;
+pub use relayed_swap::*;
+pub use staged_withdraw::*;
 pub use swap::*;
+pub use swap_basket::*;
+pub use swap_hook::*;
+pub use swap_partner_to_partner::*;
+pub use transfer_position::*;
+pub use treasury::*;
+pub use update_config::*;
+pub use update_pool::*;
+pub use views::*;
+pub use vote_escrow::*;
+pub use voting_snapshot::*;
 pub use withdraw::*;
+pub use withdraw_all_positions::*;
+pub use withdraw_queue::*;
 
 // Handler functions with specific names to avoid conflicts
+pub use add_pool_token::add_pool_token;
+pub use autocompounder::{
+    compound, deposit_to_autocompounder, init_autocompounder, withdraw_from_autocompounder,
+};
+pub use batch_auction::{claim_swap_intent, init_swap_batch, settle_batch, submit_swap_intent};
+pub use close_pool_vault::close_pool_vault;
 pub use create_pool::{create_growth_pool, create_seed_pool};
+pub use dca::{cancel_dca_schedule, create_dca_schedule, execute_dca};
+pub use deposit::{deposit_exact_lp, deposit_with_max_imbalance_fee};
 pub use deposit::handler as deposit_handler;
+pub use guardian::pause_pool;
 pub use initialize::handler as initialize_handler;
-pub use swap::handler as swap_handler;
+pub use limit_order::{cancel_limit_order, fill_limit_order, place_limit_order};
+pub use liquidity_lock::{lock_liquidity, release_liquidity};
+pub use lp_oracle::{init_lp_oracle, update_lp_oracle};
+pub use migrate_config::migrate_config;
+pub use orphaned_reserves::recover_orphaned_reserves;
+pub use partner_approval::{approve_partner_mint, revoke_partner_mint};
+pub use partner_oracle::update_partner_price;
+pub use partner_stats::{init_partner_stats, refresh_partner_stats};
+pub use pool_snapshot::{init_pool_snapshot, refresh_pool_snapshot};
+pub use pool_stats::{init_pool_stats, record_pool_stats};
+pub use portfolio::{init_user_portfolio, sync_user_portfolio};
+pub use position_delegate::handler as position_delegate_handler;
+pub use program_state::{init_program_state, record_upgrade, set_feature_flags};
+pub use rebalance::handler as rebalance_handler;
+pub use recompute_invariant::handler as recompute_invariant_handler;
+pub use referral::{claim_referral_fees, register_referral};
+pub use relayed_swap::relayed_swap;
+pub use staged_withdraw::{cancel_withdraw, execute_withdraw, request_withdraw};
+pub use swap::{handler as swap_handler, swap_indexed};
+pub use swap_basket::swap_basket;
+pub use swap_hook::{approve_swap_hook, revoke_swap_hook};
+pub use swap_partner_to_partner::handler as swap_partner_to_partner_handler;
+pub use transfer_position::handler as transfer_position_handler;
+pub use treasury::{collect_protocol_fees, init_treasury, init_treasury_vault, sweep_treasury};
+pub use update_config::{
+    accept_authority, add_guardian, add_whitelisted_program, propose_authority, remove_guardian,
+    remove_whitelisted_program, set_config_protocol_fee_share, set_crank_tip,
+    set_partner_approval_required, set_pool_creation_config, set_rebalance_config, set_ve_mint,
+};
+pub use update_pool::{
+    set_bootstrap_limits, set_exit_fee, set_fee_params, set_lbp_schedule, set_max_trade_bps,
+    set_min_swap_amount, set_oracle_band, set_protocol_fee_share, set_staged_withdraw_config,
+    set_pool_paused, set_relayer, set_token_frozen, update_pool_params,
+};
+pub use views::{
+    get_amounts_out, get_depth, get_lock_expiry, get_pool_apy, get_pool_state, get_virtual_price,
+    quote_fee,
+};
+pub use vote_escrow::{add_fee_exemption, lock_ve_tokens, remove_fee_exemption, unlock_ve_tokens};
+pub use voting_snapshot::checkpoint_voting_power;
 pub use withdraw::handler as withdraw_handler;
+pub use withdraw::{withdraw_imbalanced, withdraw_percent};
+pub use withdraw_all_positions::withdraw_all_positions;
+pub use withdraw_queue::{fulfill_withdraw_request, queue_withdraw};
@@ -0,0 +1,100 @@
+use crate::errors::ErrorCode;
+use crate::events::OrphanedReservesRecovered;
+use crate::state::*;
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Mint, Token, TokenAccount, Transfer};
+
+#[derive(Accounts)]
+pub struct RecoverOrphanedReserves<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        has_one = authority @ ErrorCode::Unauthorized,
+    )]
+    pub amm_config: Account<'info, AmmConfig>,
+
+    #[account(
+        mut,
+        has_one = amm_config,
+        has_one = lp_mint @ ErrorCode::InvalidTokenMint,
+    )]
+    pub pool: Account<'info, Pool>,
+
+    pub lp_mint: Account<'info, Mint>,
+
+    pub token_mint: Account<'info, Mint>,
+
+    #[account(
+        mut,
+        token::authority = pool,
+        token::mint = token_mint,
+    )]
+    pub pool_token: Account<'info, TokenAccount>,
+
+    /// Treasury of the pool's config
+    #[account(
+        seeds = [&b"treasury"[..], amm_config.key().as_ref()],
+        bump = treasury.bump,
+    )]
+    pub treasury: Account<'info, Treasury>,
+
+    #[account(
+        mut,
+        seeds = [&b"treasury-vault"[..], treasury.key().as_ref(), token_mint.key().as_ref()],
+        bump,
+    )]
+    pub treasury_vault: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+/// Move the reserve of `token_mint` left in a pool with no LP supply to the treasury
+/// (config authority)
+///
+/// LP burned directly rather than through `withdraw` leaves reserves that no share
+/// can claim, and deposits are refused until they are gone. Recovering every
+/// reserve returns the pool to empty, so the next deposit is priced as its first.
+/// Accrued protocol fees stay put.
+pub fn recover_orphaned_reserves(ctx: Context<RecoverOrphanedReserves>) -> Result<()> {
+    let pool = &ctx.accounts.pool;
+    require!(
+        pool.lp_supply(ctx.accounts.lp_mint.supply) == 0,
+        ErrorCode::LpSupplyOutstanding
+    );
+    let token_idx = pool
+        .token_index(&ctx.accounts.token_mint.key())
+        .ok_or(ErrorCode::InvalidTokenMint)?;
+    crate::utils::check_pool_vault(pool, token_idx, &ctx.accounts.pool_token.key())?;
+    let amount = pool.reserves[token_idx];
+    require!(amount > 0, ErrorCode::NothingToCollect);
+
+    let seeds = pool.signer_seeds()?;
+    let signer = &[&seeds[..]];
+    let cpi_accounts = Transfer {
+        from: ctx.accounts.pool_token.to_account_info(),
+        to: ctx.accounts.treasury_vault.to_account_info(),
+        authority: pool.to_account_info(),
+    };
+    let cpi_program = ctx.accounts.token_program.to_account_info();
+    token::transfer(
+        CpiContext::new_with_signer(cpi_program, cpi_accounts, signer),
+        amount,
+    )?;
+
+    // Without LP there is no share price; D follows whatever reserves remain
+    let pool = &mut ctx.accounts.pool;
+    pool.reserves[token_idx] = 0;
+    pool.last_d =
+        crate::state::math::calculate_invariant(&pool.reserves, pool.amplification).unwrap_or(0);
+    pool.d_slot = Clock::get()?.slot;
+    pool.refresh_virtual_price(0);
+    crate::utils::touch_pool(pool)?;
+
+    emit!(OrphanedReservesRecovered {
+        pool: pool.key(),
+        token_mint: ctx.accounts.token_mint.key(),
+        amount,
+    });
+
+    Ok(())
+}
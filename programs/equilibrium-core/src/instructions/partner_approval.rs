@@ -0,0 +1,66 @@
+use crate::errors::ErrorCode;
+use crate::state::*;
+use anchor_lang::prelude::*;
+use anchor_spl::token::Mint;
+
+#[derive(Accounts)]
+pub struct ApprovePartnerMint<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        has_one = authority @ ErrorCode::Unauthorized,
+    )]
+    pub amm_config: Account<'info, AmmConfig>,
+
+    pub partner_mint: Account<'info, Mint>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = PartnerApproval::space(),
+        seeds = [&b"partner-approval"[..], amm_config.key().as_ref(), partner_mint.key().as_ref()],
+        bump
+    )]
+    pub partner_approval: Account<'info, PartnerApproval>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn approve_partner_mint(ctx: Context<ApprovePartnerMint>) -> Result<()> {
+    let partner_approval = &mut ctx.accounts.partner_approval;
+    partner_approval.bump = ctx.bumps.partner_approval;
+    partner_approval.partner_mint = ctx.accounts.partner_mint.key();
+    partner_approval.approved_by = ctx.accounts.authority.key();
+    partner_approval.approved_at = Clock::get()?.unix_timestamp;
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct RevokePartnerMint<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        has_one = authority @ ErrorCode::Unauthorized,
+    )]
+    pub amm_config: Account<'info, AmmConfig>,
+
+    /// Existing pools keep trading, only new pool creation is blocked
+    #[account(
+        mut,
+        seeds = [
+            &b"partner-approval"[..],
+            amm_config.key().as_ref(),
+            partner_approval.partner_mint.as_ref(),
+        ],
+        bump = partner_approval.bump,
+        close = authority,
+    )]
+    pub partner_approval: Account<'info, PartnerApproval>,
+}
+
+pub fn revoke_partner_mint(_ctx: Context<RevokePartnerMint>) -> Result<()> {
+    Ok(())
+}
@@ -0,0 +1,33 @@
+use crate::errors::ErrorCode;
+use crate::state::*;
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct UpdatePartnerPrice<'info> {
+    /// Crank run by the configured keeper, which sources the partner price
+    pub keeper: Signer<'info>,
+
+    #[account(
+        has_one = keeper @ ErrorCode::Unauthorized,
+    )]
+    pub amm_config: Account<'info, AmmConfig>,
+
+    #[account(
+        mut,
+        has_one = amm_config,
+        constraint = pool.pool_type == PoolType::Growth @ ErrorCode::InvalidPoolType,
+    )]
+    pub pool: Account<'info, Pool>,
+}
+
+/// Publish the partner token's price in USD*, scaled by `ORACLE_PRICE_PRECISION`
+pub fn update_partner_price(ctx: Context<UpdatePartnerPrice>, price: u64) -> Result<()> {
+    require!(price > 0, ErrorCode::InvalidOraclePrice);
+
+    let pool = &mut ctx.accounts.pool;
+    pool.partner_oracle_price = price;
+    pool.partner_oracle_updated_at = Clock::get()?.unix_timestamp;
+    pool.partner_oracle_updated_slot = Clock::get()?.slot;
+
+    Ok(())
+}
@@ -0,0 +1,116 @@
+use crate::errors::ErrorCode;
+use crate::state::*;
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct InitPartnerStats<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(
+        constraint = pool.pool_type == PoolType::Growth @ ErrorCode::InvalidPoolType,
+    )]
+    pub pool: Account<'info, Pool>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = PartnerStats::space(),
+        seeds = [&b"partner-stats"[..], pool.key().as_ref()],
+        bump
+    )]
+    pub partner_stats: Account<'info, PartnerStats>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn init_partner_stats(ctx: Context<InitPartnerStats>) -> Result<()> {
+    let partner_stats = &mut ctx.accounts.partner_stats;
+    partner_stats.bump = ctx.bumps.partner_stats;
+    partner_stats.pool = ctx.accounts.pool.key();
+    publish(
+        partner_stats,
+        &ctx.accounts.pool,
+        Clock::get()?.unix_timestamp,
+    );
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct RefreshPartnerStats<'info> {
+    pub pool: Account<'info, Pool>,
+
+    /// Permissionless crank: figures are read from the pool, never supplied by the caller
+    #[account(
+        mut,
+        has_one = pool,
+        seeds = [&b"partner-stats"[..], pool.key().as_ref()],
+        bump = partner_stats.bump,
+    )]
+    pub partner_stats: Account<'info, PartnerStats>,
+}
+
+/// Sample the pool and republish its 24h figures, at most once per `POOL_STATS_INTERVAL`
+pub fn refresh_partner_stats(ctx: Context<RefreshPartnerStats>) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let partner_stats = &mut ctx.accounts.partner_stats;
+    if let Some(latest) = partner_stats.latest() {
+        require!(
+            now >= latest.timestamp + POOL_STATS_INTERVAL,
+            ErrorCode::PoolStatsTooRecent
+        );
+    }
+
+    publish(partner_stats, &ctx.accounts.pool, now);
+
+    Ok(())
+}
+
+/// Record a sample of `pool` at `now` and recompute the published figures from it,
+/// valuing the partner token at `Pool::partner_price`
+fn publish(partner_stats: &mut PartnerStats, pool: &Pool, now: i64) {
+    let per_token = |counters: &[u128]| [counters[USD_STAR_INDEX], counters[PARTNER_INDEX]];
+    let sample = VolumeSample {
+        timestamp: now,
+        cumulative_volume: per_token(&pool.lifetime_volume_per_token),
+        cumulative_fees: per_token(&pool.lifetime_fees_per_token),
+    };
+    partner_stats.record(sample);
+    let start = *partner_stats.window_start(now).unwrap_or(&sample);
+
+    let partner_price = pool.partner_price(now).unwrap_or(0);
+    let window_value = |end: [u128; 2], start: [u128; 2]| {
+        usd_star_value(
+            [
+                end[USD_STAR_INDEX].saturating_sub(start[USD_STAR_INDEX]),
+                end[PARTNER_INDEX].saturating_sub(start[PARTNER_INDEX]),
+            ],
+            partner_price,
+        )
+    };
+    let reserves = [
+        pool.reserves[USD_STAR_INDEX] as u128,
+        pool.reserves[PARTNER_INDEX] as u128,
+    ];
+    let tvl = u64::try_from(usd_star_value(reserves, partner_price)).unwrap_or(u64::MAX);
+    let fees_24h = window_value(sample.cumulative_fees, start.cumulative_fees);
+    let depth = |token_in: usize, token_out: usize| {
+        crate::state::math::depth_at_impact(
+            &[pool.reserves[token_in], pool.reserves[token_out]],
+            pool.amplification,
+            PARTNER_STATS_DEPTH_IMPACT_BPS,
+        )
+        .unwrap_or(0)
+    };
+
+    partner_stats.partner_price = partner_price;
+    partner_stats.volume_24h = window_value(sample.cumulative_volume, start.cumulative_volume);
+    partner_stats.fees_24h = fees_24h;
+    partner_stats.fee_apr_bps =
+        crate::state::math::calculate_annualized_yield_bps(fees_24h, tvl, now - start.timestamp);
+    partner_stats.tvl = tvl;
+    partner_stats.depth_usd_star_in = depth(USD_STAR_INDEX, PARTNER_INDEX);
+    partner_stats.depth_partner_in = depth(PARTNER_INDEX, USD_STAR_INDEX);
+    partner_stats.updated_at = now;
+}
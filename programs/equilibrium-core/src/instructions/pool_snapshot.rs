@@ -0,0 +1,62 @@
+use crate::state::*;
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct InitPoolSnapshot<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub pool: Account<'info, Pool>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = PoolSnapshot::space(),
+        seeds = [&b"pool-snapshot"[..], pool.key().as_ref()],
+        bump
+    )]
+    pub pool_snapshot: Account<'info, PoolSnapshot>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn init_pool_snapshot(ctx: Context<InitPoolSnapshot>) -> Result<()> {
+    let pool_snapshot = &mut ctx.accounts.pool_snapshot;
+    pool_snapshot.bump = ctx.bumps.pool_snapshot;
+    pool_snapshot.pool = ctx.accounts.pool.key();
+    pool_snapshot.reset(&ctx.accounts.pool, Clock::get()?.unix_timestamp);
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct RefreshPoolSnapshot<'info> {
+    pub pool: Account<'info, Pool>,
+
+    /// Permissionless crank: everything is copied from the pool
+    #[account(
+        mut,
+        has_one = pool,
+        seeds = [&b"pool-snapshot"[..], pool.key().as_ref()],
+        bump = pool_snapshot.bump,
+    )]
+    pub pool_snapshot: Account<'info, PoolSnapshot>,
+}
+
+/// Catch the snapshot up with the pool, its fee priced as of the current slot
+pub fn refresh_pool_snapshot(ctx: Context<RefreshPoolSnapshot>) -> Result<()> {
+    let pool = ctx.accounts.pool.with_weights_ema_at(Clock::get()?.slot);
+    refresh_if_present(Some(&mut ctx.accounts.pool_snapshot), &pool)
+}
+
+/// Refresh the snapshot a handler was passed, if any, from the pool it just settled
+pub fn refresh_if_present(
+    pool_snapshot: Option<&mut Account<PoolSnapshot>>,
+    pool: &Pool,
+) -> Result<()> {
+    if let Some(pool_snapshot) = pool_snapshot {
+        pool_snapshot.refresh(pool, Clock::get()?.unix_timestamp);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,92 @@
+use crate::errors::ErrorCode;
+use crate::state::*;
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Token, TokenAccount};
+
+#[derive(Accounts)]
+pub struct InitPoolStats<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub pool: Account<'info, Pool>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = PoolStats::space(),
+        seeds = [&b"pool-stats"[..], pool.key().as_ref()],
+        bump
+    )]
+    pub pool_stats: Account<'info, PoolStats>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn init_pool_stats(ctx: Context<InitPoolStats>) -> Result<()> {
+    let pool_stats = &mut ctx.accounts.pool_stats;
+    pool_stats.bump = ctx.bumps.pool_stats;
+    pool_stats.pool = ctx.accounts.pool.key();
+    pool_stats.record(FeeSample {
+        timestamp: Clock::get()?.unix_timestamp,
+        cumulative_fees: ctx.accounts.pool.lifetime_fees(),
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct RecordPoolStats<'info> {
+    #[account(mut)]
+    pub pool: Account<'info, Pool>,
+
+    /// Permissionless crank: samples are read from the pool, never supplied by the caller
+    #[account(
+        mut,
+        has_one = pool,
+        seeds = [&b"pool-stats"[..], pool.key().as_ref()],
+        bump = pool_stats.bump,
+    )]
+    pub pool_stats: Account<'info, PoolStats>,
+
+    /// Optional keeper tip out of protocol fees: the pool's config, the vault the tip
+    /// is paid from and the caller's account receiving it
+    pub amm_config: Option<Account<'info, AmmConfig>>,
+
+    #[account(mut)]
+    pub pool_token_tip: Option<Account<'info, TokenAccount>>,
+
+    #[account(mut)]
+    pub tip_recipient: Option<Account<'info, TokenAccount>>,
+
+    pub token_program: Option<Program<'info, Token>>,
+}
+
+/// Sample the pool's cumulative fees, at most once per `POOL_STATS_INTERVAL`
+pub fn record_pool_stats(ctx: Context<RecordPoolStats>) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let pool_stats = &mut ctx.accounts.pool_stats;
+    if let Some(latest) = pool_stats.latest() {
+        require!(
+            now >= latest.timestamp + POOL_STATS_INTERVAL,
+            ErrorCode::PoolStatsTooRecent
+        );
+    }
+
+    pool_stats.record(FeeSample {
+        timestamp: now,
+        cumulative_fees: ctx.accounts.pool.lifetime_fees(),
+    });
+
+    crate::instructions::crank_tip::pay_crank_tip(
+        &mut ctx.accounts.pool,
+        ctx.accounts.amm_config.as_ref(),
+        ctx.accounts.pool_token_tip.as_ref(),
+        ctx.accounts.tip_recipient.as_ref(),
+        ctx.accounts
+            .token_program
+            .as_ref()
+            .map(|program| program.to_account_info()),
+    )?;
+
+    Ok(())
+}
@@ -0,0 +1,77 @@
+use crate::state::*;
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct InitUserPortfolio<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        init,
+        payer = owner,
+        space = UserPortfolio::space(),
+        seeds = [&b"user-portfolio"[..], owner.key().as_ref()],
+        bump
+    )]
+    pub user_portfolio: Account<'info, UserPortfolio>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Create the owner's portfolio, indexing the positions in `remaining_accounts`
+///
+/// Positions opened before the portfolio existed are only indexed when listed
+/// here or passed to `sync_user_portfolio` later.
+pub fn init_user_portfolio<'info>(
+    ctx: Context<'_, '_, 'info, 'info, InitUserPortfolio<'info>>,
+) -> Result<()> {
+    let user_portfolio = &mut ctx.accounts.user_portfolio;
+    user_portfolio.bump = ctx.bumps.user_portfolio;
+    user_portfolio.owner = ctx.accounts.owner.key();
+    user_portfolio.positions = Vec::new();
+
+    sync_positions(user_portfolio, ctx.remaining_accounts)
+}
+
+#[derive(Accounts)]
+pub struct SyncUserPortfolio<'info> {
+    /// Anyone may sync a portfolio; it only mirrors the positions' own state
+    pub payer: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [&b"user-portfolio"[..], user_portfolio.owner.as_ref()],
+        bump = user_portfolio.bump,
+    )]
+    pub user_portfolio: Account<'info, UserPortfolio>,
+}
+
+/// Refresh the entries of the positions in `remaining_accounts`
+///
+/// Picks up positions changed outside deposits and withdrawals, such as by
+/// transfers, autocompounding or a deposit made without the portfolio.
+pub fn sync_user_portfolio<'info>(
+    ctx: Context<'_, '_, 'info, 'info, SyncUserPortfolio<'info>>,
+) -> Result<()> {
+    sync_positions(&mut ctx.accounts.user_portfolio, ctx.remaining_accounts)
+}
+
+fn sync_positions<'info>(
+    user_portfolio: &mut UserPortfolio,
+    positions: &'info [AccountInfo<'info>],
+) -> Result<()> {
+    for info in positions {
+        // A closed position leaves the index
+        if info.data_is_empty() {
+            user_portfolio
+                .positions
+                .retain(|entry| entry.position != info.key());
+            continue;
+        }
+        let position = Account::<UserPosition>::try_from(info)?;
+        user_portfolio.sync(info.key(), &position)?;
+    }
+    user_portfolio.last_update = Clock::get()?.unix_timestamp;
+
+    Ok(())
+}
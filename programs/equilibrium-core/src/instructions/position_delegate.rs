@@ -0,0 +1,22 @@
+use crate::errors::ErrorCode;
+use crate::state::*;
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct SetPositionDelegate<'info> {
+    pub owner: Signer<'info>,
+
+    #[account(
+        mut,
+        has_one = owner @ ErrorCode::Unauthorized,
+    )]
+    pub user_position: Account<'info, UserPosition>,
+}
+
+/// Let `delegate` deposit and withdraw on the owner's behalf, or revoke with `None`.
+/// Token movements are still capped by the SPL approvals the owner grants it.
+pub fn handler(ctx: Context<SetPositionDelegate>, delegate: Option<Pubkey>) -> Result<()> {
+    ctx.accounts.user_position.delegate = delegate;
+
+    Ok(())
+}
@@ -0,0 +1,89 @@
+use crate::errors::ErrorCode;
+use crate::program::EquilibriumCore;
+use crate::state::*;
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct InitProgramState<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// This program, locating its program data account
+    #[account(
+        constraint = program.programdata_address()? == Some(program_data.key()),
+    )]
+    pub program: Program<'info, EquilibriumCore>,
+
+    /// Program-wide state is the upgrade authority's to manage, not any config's
+    #[account(
+        constraint = program_data.upgrade_authority_address == Some(authority.key())
+            @ ErrorCode::Unauthorized,
+    )]
+    pub program_data: Account<'info, ProgramData>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = ProgramState::space(),
+        seeds = [&b"program-state"[..]],
+        bump
+    )]
+    pub program_state: Account<'info, ProgramState>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn init_program_state(ctx: Context<InitProgramState>) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let program_state = &mut ctx.accounts.program_state;
+    program_state.bump = ctx.bumps.program_state;
+    program_state.feature_flags = 0;
+    program_state.initialized_at = now;
+    program_state.record_upgrade(PROGRAM_VERSION, now);
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct UpdateProgramState<'info> {
+    pub authority: Signer<'info>,
+
+    /// This program, locating its program data account
+    #[account(
+        constraint = program.programdata_address()? == Some(program_data.key()),
+    )]
+    pub program: Program<'info, EquilibriumCore>,
+
+    /// Program-wide state is the upgrade authority's to manage, not any config's
+    #[account(
+        constraint = program_data.upgrade_authority_address == Some(authority.key())
+            @ ErrorCode::Unauthorized,
+    )]
+    pub program_data: Account<'info, ProgramData>,
+
+    #[account(
+        mut,
+        seeds = [&b"program-state"[..]],
+        bump = program_state.bump,
+    )]
+    pub program_state: Account<'info, ProgramState>,
+}
+
+/// Replace the enabled feature bits
+pub fn set_feature_flags(ctx: Context<UpdateProgramState>, feature_flags: u64) -> Result<()> {
+    ctx.accounts.program_state.feature_flags = feature_flags;
+
+    Ok(())
+}
+
+/// Record the version of the binary now deployed, once per version bump
+pub fn record_upgrade(ctx: Context<UpdateProgramState>) -> Result<()> {
+    let program_state = &mut ctx.accounts.program_state;
+    require!(
+        PROGRAM_VERSION > program_state.version,
+        ErrorCode::VersionNotIncreased
+    );
+    program_state.record_upgrade(PROGRAM_VERSION, Clock::get()?.unix_timestamp);
+
+    Ok(())
+}
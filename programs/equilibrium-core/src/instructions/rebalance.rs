@@ -0,0 +1,203 @@
+use crate::errors::ErrorCode;
+use crate::events::Rebalanced;
+use crate::state::math::{BPS_DENOMINATOR, MAX_REBALANCE_SLIPPAGE_BPS};
+use crate::state::*;
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::solana_program::program::invoke_signed;
+use anchor_spl::token::TokenAccount;
+
+#[derive(Accounts)]
+pub struct Rebalance<'info> {
+    pub keeper: Signer<'info>,
+
+    #[account(
+        has_one = keeper @ ErrorCode::Unauthorized,
+    )]
+    pub amm_config: Account<'info, AmmConfig>,
+
+    #[account(
+        mut,
+        has_one = amm_config,
+    )]
+    pub pool: Account<'info, Pool>,
+
+    /// Vault of the over-weighted token being sold
+    #[account(
+        mut,
+        token::authority = pool,
+    )]
+    pub pool_token_from: Account<'info, TokenAccount>,
+
+    /// Vault of the under-weighted token being bought
+    #[account(
+        mut,
+        token::authority = pool,
+    )]
+    pub pool_token_to: Account<'info, TokenAccount>,
+
+    /// CHECK: External AMM, must be the config's venue and whitelisted as one
+    #[account(
+        executable,
+        constraint = venue_program.key() == amm_config.rebalance_program @ ErrorCode::ProgramNotWhitelisted,
+        constraint = amm_config.is_whitelisted(
+            &venue_program.key(),
+            CpiTargetKind::RebalanceVenue,
+        ) @ ErrorCode::ProgramNotWhitelisted,
+    )]
+    pub venue_program: UncheckedAccount<'info>,
+    // Accounts for the venue's swap instruction are passed as remaining accounts
+}
+
+pub fn handler<'info>(
+    ctx: Context<'_, '_, '_, 'info, Rebalance<'info>>,
+    amount_in: u64,
+    min_amount_out: u64,
+    venue_data: Vec<u8>,
+) -> Result<()> {
+    ctx.accounts.pool.update_weights_ema(Clock::get()?.slot);
+    let pool = &ctx.accounts.pool;
+
+    let from_idx = pool
+        .token_accounts
+        .iter()
+        .position(|account| account == &ctx.accounts.pool_token_from.key())
+        .ok_or(ErrorCode::InvalidTokenMint)?;
+    let to_idx = pool
+        .token_accounts
+        .iter()
+        .position(|account| account == &ctx.accounts.pool_token_to.key())
+        .ok_or(ErrorCode::InvalidTokenMint)?;
+    require!(from_idx != to_idx, ErrorCode::InvalidRebalance);
+    require!(amount_in > 0, ErrorCode::InvalidInstructionData);
+
+    // Only restore weights, never push them further from target
+    let weights = pool.current_weights();
+    let target_weights = pool.target_weights_at(Clock::get()?.unix_timestamp);
+    require!(
+        weights[from_idx] > target_weights[from_idx] && weights[to_idx] < target_weights[to_idx],
+        ErrorCode::InvalidRebalance
+    );
+
+    // Per-epoch cap as a share of the reserve being sold
+    let epoch = Clock::get()?.epoch;
+    let volume_in_epoch = if pool.rebalance_epoch == epoch {
+        pool.rebalance_volume_in_epoch
+    } else {
+        0
+    };
+    let epoch_limit = (pool.reserves[from_idx] as u128
+        * ctx.accounts.amm_config.max_rebalance_bps as u128
+        / BPS_DENOMINATOR as u128) as u64;
+    let volume_after = volume_in_epoch
+        .checked_add(amount_in)
+        .ok_or(ErrorCode::MathOverflow)?;
+    require!(
+        volume_after <= epoch_limit,
+        ErrorCode::RebalanceLimitExceeded
+    );
+
+    let from_before = ctx.accounts.pool_token_from.amount;
+    let to_before = ctx.accounts.pool_token_to.amount;
+
+    // The pool signs the CPI, so any other vault handed to the venue could be
+    // drained by it; snapshot those to check they come back untouched
+    let mut other_vaults = Vec::new();
+    for account in ctx.remaining_accounts {
+        let is_other_vault = pool
+            .token_accounts
+            .iter()
+            .enumerate()
+            .any(|(i, vault)| i != from_idx && i != to_idx && vault == account.key);
+        if is_other_vault {
+            other_vaults.push((account, vault_amount(account)?));
+        }
+    }
+
+    // Swap through the venue with the pool signing for its vault
+    let pool_key = pool.key();
+    let accounts = ctx
+        .remaining_accounts
+        .iter()
+        .map(|account| AccountMeta {
+            pubkey: account.key(),
+            is_signer: account.is_signer || account.key() == pool_key,
+            is_writable: account.is_writable,
+        })
+        .collect();
+    let instruction = Instruction {
+        program_id: ctx.accounts.venue_program.key(),
+        accounts,
+        data: venue_data,
+    };
+
+    let mut account_infos = ctx.remaining_accounts.to_vec();
+    account_infos.push(ctx.accounts.pool.to_account_info());
+    account_infos.push(ctx.accounts.pool_token_from.to_account_info());
+    account_infos.push(ctx.accounts.pool_token_to.to_account_info());
+    account_infos.push(ctx.accounts.venue_program.to_account_info());
+
+    let pool_seeds = pool.signer_seeds()?;
+    invoke_signed(&instruction, &account_infos, &[&pool_seeds[..]])?;
+
+    for (account, amount_before) in other_vaults {
+        if vault_amount(account)? != amount_before {
+            msg!("Venue moved pool vault {}", account.key);
+            return err!(ErrorCode::InvalidRebalance);
+        }
+    }
+
+    // Measure what actually moved rather than trusting the venue
+    ctx.accounts.pool_token_from.reload()?;
+    ctx.accounts.pool_token_to.reload()?;
+    let amount_sold = from_before
+        .checked_sub(ctx.accounts.pool_token_from.amount)
+        .ok_or(ErrorCode::InvalidRebalance)?;
+    let amount_bought = ctx
+        .accounts
+        .pool_token_to
+        .amount
+        .checked_sub(to_before)
+        .ok_or(ErrorCode::InvalidRebalance)?;
+
+    require!(amount_sold > 0, ErrorCode::InvalidRebalance);
+    require!(amount_sold <= amount_in, ErrorCode::RebalanceLimitExceeded);
+    crate::utils::check_min_amount("Rebalance output", amount_bought, min_amount_out)?;
+    require!(
+        amount_bought as u128 * BPS_DENOMINATOR as u128
+            >= amount_sold as u128 * (BPS_DENOMINATOR - MAX_REBALANCE_SLIPPAGE_BPS) as u128,
+        ErrorCode::SlippageExceeded
+    );
+
+    // Book the trade against the pool
+    let pool = &mut ctx.accounts.pool;
+    pool.reserves[from_idx] = pool.reserves[from_idx].saturating_sub(amount_sold);
+    pool.reserves[to_idx] += amount_bought;
+    let old_d = pool.last_d;
+    pool.last_d = crate::state::math::calculate_invariant(&pool.reserves, pool.amplification)
+        .ok_or(ErrorCode::InsufficientLiquidity)?;
+    pool.d_slot = Clock::get()?.slot;
+    pool.scale_virtual_price(old_d);
+    pool.rebalance_epoch = epoch;
+    pool.rebalance_volume_in_epoch = volume_in_epoch + amount_sold;
+    let (now, _) = crate::utils::touch_pool(pool)?;
+    pool.refresh_routing_hint(now);
+
+    emit!(Rebalanced {
+        pool: pool.key(),
+        keeper: ctx.accounts.keeper.key(),
+        token_mint_from: pool.token_mints[from_idx],
+        token_mint_to: pool.token_mints[to_idx],
+        amount_in: amount_sold,
+        amount_out: amount_bought,
+        epoch,
+        virtual_price: pool.virtual_price,
+    });
+
+    Ok(())
+}
+
+/// Token balance of a pool vault passed through as a remaining account
+fn vault_amount(account: &AccountInfo) -> Result<u64> {
+    Ok(TokenAccount::try_deserialize(&mut &account.try_borrow_data()?[..])?.amount)
+}
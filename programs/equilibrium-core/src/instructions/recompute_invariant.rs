@@ -0,0 +1,50 @@
+use crate::errors::ErrorCode;
+use crate::state::*;
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Token, TokenAccount};
+
+#[derive(Accounts)]
+pub struct RecomputeInvariant<'info> {
+    /// Permissionless crank, no signer required beyond the fee payer
+    #[account(mut)]
+    pub pool: Account<'info, Pool>,
+
+    /// Optional keeper tip out of protocol fees: the pool's config, the vault the tip
+    /// is paid from and the caller's account receiving it
+    pub amm_config: Option<Account<'info, AmmConfig>>,
+
+    #[account(mut)]
+    pub pool_token_tip: Option<Account<'info, TokenAccount>>,
+
+    #[account(mut)]
+    pub tip_recipient: Option<Account<'info, TokenAccount>>,
+
+    pub token_program: Option<Program<'info, Token>>,
+}
+
+pub fn handler(ctx: Context<RecomputeInvariant>) -> Result<()> {
+    let pool = &mut ctx.accounts.pool;
+
+    // Full Newton solve over the current reserves
+    let old_d = pool.last_d;
+    pool.last_d = crate::state::math::calculate_invariant(&pool.reserves, pool.amplification)
+        .ok_or(ErrorCode::InsufficientLiquidity)?;
+    pool.d_slot = Clock::get()?.slot;
+    pool.scale_virtual_price(old_d);
+
+    // Only a recompute that corrected the cached D has earned a tip
+    if pool.last_d != old_d {
+        crate::instructions::crank_tip::pay_crank_tip(
+            pool,
+            ctx.accounts.amm_config.as_ref(),
+            ctx.accounts.pool_token_tip.as_ref(),
+            ctx.accounts.tip_recipient.as_ref(),
+            ctx.accounts
+                .token_program
+                .as_ref()
+                .map(|program| program.to_account_info()),
+        )?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,149 @@
+use crate::errors::ErrorCode;
+use crate::events::ReferralFeesClaimed;
+use crate::state::*;
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{self, Mint, TokenAccount, TokenInterface, TransferChecked};
+
+#[derive(Accounts)]
+pub struct RegisterReferral<'info> {
+    #[account(mut)]
+    pub referrer: Signer<'info>,
+
+    pub pool: Account<'info, Pool>,
+
+    /// Pool token the earnings accrue in
+    pub token_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        init,
+        payer = referrer,
+        space = ReferralAccount::space(),
+        seeds = [
+            &b"referral"[..],
+            pool.key().as_ref(),
+            token_mint.key().as_ref(),
+            referrer.key().as_ref(),
+        ],
+        bump
+    )]
+    pub referral_account: Account<'info, ReferralAccount>,
+
+    /// Vault holding the earnings, owned by the referral account
+    #[account(
+        init,
+        payer = referrer,
+        seeds = [&b"referral-vault"[..], referral_account.key().as_ref()],
+        bump,
+        token::mint = token_mint,
+        token::authority = referral_account,
+        token::token_program = token_program,
+    )]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+
+    /// SPL Token or Token-2022, matching `token_mint`
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Open the caller's referral account and its vault for one token of a pool
+pub fn register_referral(ctx: Context<RegisterReferral>) -> Result<()> {
+    require!(
+        ctx.accounts
+            .pool
+            .token_index(&ctx.accounts.token_mint.key())
+            .is_some(),
+        ErrorCode::InvalidTokenMint
+    );
+
+    let referral_account = &mut ctx.accounts.referral_account;
+    referral_account.bump = ctx.bumps.referral_account;
+    referral_account.referrer = ctx.accounts.referrer.key();
+    referral_account.pool = ctx.accounts.pool.key();
+    referral_account.token_mint = ctx.accounts.token_mint.key();
+    referral_account.vault = ctx.accounts.vault.key();
+    referral_account.fees_owed = 0;
+    referral_account.lifetime_fees = 0;
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct ClaimReferralFees<'info> {
+    pub referrer: Signer<'info>,
+
+    pub pool: Account<'info, Pool>,
+
+    pub token_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        has_one = referrer,
+        has_one = pool,
+        has_one = token_mint,
+        has_one = vault,
+        seeds = [
+            &b"referral"[..],
+            pool.key().as_ref(),
+            token_mint.key().as_ref(),
+            referrer.key().as_ref(),
+        ],
+        bump = referral_account.bump,
+    )]
+    pub referral_account: Account<'info, ReferralAccount>,
+
+    /// Referral vault holding the accrued fees
+    #[account(mut)]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        token::mint = token_mint,
+    )]
+    pub destination: InterfaceAccount<'info, TokenAccount>,
+
+    /// SPL Token or Token-2022, matching `token_mint`
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+/// Pay out everything a referral account has accrued, in one transfer
+pub fn claim_referral_fees(ctx: Context<ClaimReferralFees>) -> Result<()> {
+    let referral_account = &ctx.accounts.referral_account;
+    let amount = referral_account.fees_owed;
+    require!(amount > 0, ErrorCode::NoReferralFeesOwed);
+
+    let pool_key = ctx.accounts.pool.key();
+    let token_mint_key = ctx.accounts.token_mint.key();
+    let referrer_key = ctx.accounts.referrer.key();
+    let seeds = &[
+        &b"referral"[..],
+        pool_key.as_ref(),
+        token_mint_key.as_ref(),
+        referrer_key.as_ref(),
+        &[referral_account.bump],
+    ];
+    let signer = &[&seeds[..]];
+    let cpi_accounts = TransferChecked {
+        from: ctx.accounts.vault.to_account_info(),
+        mint: ctx.accounts.token_mint.to_account_info(),
+        to: ctx.accounts.destination.to_account_info(),
+        authority: referral_account.to_account_info(),
+    };
+    let cpi_program = ctx.accounts.token_program.to_account_info();
+    token_interface::transfer_checked(
+        CpiContext::new_with_signer(cpi_program, cpi_accounts, signer),
+        amount,
+        ctx.accounts.token_mint.decimals,
+    )?;
+
+    ctx.accounts.referral_account.fees_owed = 0;
+
+    emit!(ReferralFeesClaimed {
+        pool: pool_key,
+        referrer: referrer_key,
+        token_mint: token_mint_key,
+        destination: ctx.accounts.destination.key(),
+        amount,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,248 @@
+use crate::errors::ErrorCode;
+use crate::events::SwapExecuted;
+use crate::instructions::swap::{apply_swap, quote_swap_with_min_fee, stale_pool_min_fee};
+use crate::state::*;
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{ed25519_program, sysvar::instructions as sysvar_instructions};
+use anchor_spl::token_interface::{self, Mint, TokenAccount, TokenInterface, TransferChecked};
+
+/// Size of the ed25519 instruction header: signature count, padding and one offsets entry
+const ED25519_HEADER_LEN: usize = 16;
+
+#[derive(Accounts)]
+pub struct RelayedSwap<'info> {
+    /// The pool's trusted relayer, paying the transaction and nonce rent
+    #[account(mut)]
+    pub relayer: Signer<'info>,
+
+    /// CHECK: Order signer; authorizes the swap through the ed25519 instruction
+    pub user: UncheckedAccount<'info>,
+
+    /// Config holding the stale-pool policy and protocol fee share
+    pub amm_config: Account<'info, AmmConfig>,
+
+    #[account(
+        mut,
+        has_one = amm_config,
+        constraint = pool.relayer == relayer.key() @ ErrorCode::Unauthorized,
+    )]
+    pub pool: Account<'info, Pool>,
+
+    /// CHECK: PDA the user approves as SPL delegate of `user_token_in`; signs only
+    /// for signed orders
+    #[account(
+        seeds = [&b"relay-authority"[..], pool.key().as_ref()],
+        bump,
+    )]
+    pub relay_authority: UncheckedAccount<'info>,
+
+    pub token_mint_in: InterfaceAccount<'info, Mint>,
+    pub token_mint_out: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        token::authority = user,
+        token::mint = token_mint_in,
+    )]
+    pub user_token_in: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        token::authority = user,
+        token::mint = token_mint_out,
+    )]
+    pub user_token_out: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        token::authority = pool,
+        token::mint = token_mint_in,
+    )]
+    pub pool_token_in: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        token::authority = pool,
+        token::mint = token_mint_out,
+    )]
+    pub pool_token_out: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        init_if_needed,
+        payer = relayer,
+        space = RelayNonce::space(),
+        seeds = [&b"relay-nonce"[..], pool.key().as_ref(), user.key().as_ref()],
+        bump
+    )]
+    pub relay_nonce: Account<'info, RelayNonce>,
+
+    /// CHECK: Instructions sysvar, read for the ed25519 verification
+    #[account(address = sysvar_instructions::ID)]
+    pub instructions: UncheckedAccount<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Execute a user-signed order submitted by the pool's relayer
+pub fn relayed_swap(ctx: Context<RelayedSwap>, order: RelayedSwapOrder) -> Result<()> {
+    verify_order_signature(
+        &ctx.accounts.instructions,
+        &ctx.accounts.user.key(),
+        &order.try_to_vec()?,
+    )?;
+    require!(
+        order.pool == ctx.accounts.pool.key()
+            && order.user == ctx.accounts.user.key()
+            && order.token_mint_in == ctx.accounts.token_mint_in.key()
+            && order.token_mint_out == ctx.accounts.token_mint_out.key(),
+        ErrorCode::InvalidRelayedOrder
+    );
+    crate::utils::check_deadline(order.deadline)?;
+
+    // Each order executes once, in nonce order
+    let relay_nonce = &mut ctx.accounts.relay_nonce;
+    relay_nonce.bump = ctx.bumps.relay_nonce;
+    if order.nonce != relay_nonce.next_nonce {
+        msg!("Nonce {}, expected {}", order.nonce, relay_nonce.next_nonce);
+        return err!(ErrorCode::InvalidRelayedOrder);
+    }
+    relay_nonce.next_nonce += 1;
+
+    let pool_account_info = ctx.accounts.pool.to_account_info();
+    let pool = &mut ctx.accounts.pool;
+    let token_in_idx = pool
+        .token_index(&order.token_mint_in)
+        .ok_or(ErrorCode::InvalidTokenMint)?;
+    let token_out_idx = pool
+        .token_index(&order.token_mint_out)
+        .ok_or(ErrorCode::InvalidTokenMint)?;
+    crate::utils::check_pool_vault(pool, token_in_idx, &ctx.accounts.pool_token_in.key())?;
+    crate::utils::check_pool_vault(pool, token_out_idx, &ctx.accounts.pool_token_out.key())?;
+    pool.update_weights_ema(Clock::get()?.slot);
+    let min_fee = stale_pool_min_fee(pool, &ctx.accounts.amm_config)?;
+
+    // Pull the input under the user's delegation and price what the vault received
+    let pool_key = pool.key();
+    let relay_seeds = &[
+        &b"relay-authority"[..],
+        pool_key.as_ref(),
+        &[ctx.bumps.relay_authority],
+    ];
+    let vault_before = ctx.accounts.pool_token_in.amount;
+    let cpi_program = ctx.accounts.token_program.to_account_info();
+    token_interface::transfer_checked(
+        CpiContext::new_with_signer(
+            cpi_program.clone(),
+            TransferChecked {
+                from: ctx.accounts.user_token_in.to_account_info(),
+                mint: ctx.accounts.token_mint_in.to_account_info(),
+                to: ctx.accounts.pool_token_in.to_account_info(),
+                authority: ctx.accounts.relay_authority.to_account_info(),
+            },
+            &[&relay_seeds[..]],
+        ),
+        order.amount_in,
+        ctx.accounts.token_mint_in.decimals,
+    )?;
+    ctx.accounts.pool_token_in.reload()?;
+    let amount_received = ctx
+        .accounts
+        .pool_token_in
+        .amount
+        .checked_sub(vault_before)
+        .ok_or(ErrorCode::MathOverflow)?;
+
+    let quote = quote_swap_with_min_fee(
+        pool,
+        token_in_idx,
+        token_out_idx,
+        amount_received,
+        0,
+        min_fee,
+    )?;
+    crate::utils::check_min_amount("Swap output", quote.amount_out, order.min_amount_out)?;
+
+    let seeds = pool.signer_seeds()?;
+    token_interface::transfer_checked(
+        CpiContext::new_with_signer(
+            cpi_program,
+            TransferChecked {
+                from: ctx.accounts.pool_token_out.to_account_info(),
+                mint: ctx.accounts.token_mint_out.to_account_info(),
+                to: ctx.accounts.user_token_out.to_account_info(),
+                authority: pool_account_info,
+            },
+            &[&seeds[..]],
+        ),
+        quote.amount_out,
+        ctx.accounts.token_mint_out.decimals,
+    )?;
+
+    pool.require_permissionless()?;
+    apply_swap(
+        pool,
+        &ctx.accounts.amm_config,
+        order.user,
+        token_in_idx,
+        token_out_idx,
+        amount_received,
+        &quote,
+    )?;
+    crate::utils::touch_pool(pool)?;
+
+    emit!(SwapExecuted {
+        pool: pool_key,
+        user: order.user,
+        recipient: ctx.accounts.user_token_out.key(),
+        token_mint_in: order.token_mint_in,
+        token_mint_out: order.token_mint_out,
+        amount_in: amount_received,
+        amount_out: quote.amount_out,
+        fee_amount: quote.fee_amount,
+        virtual_price: pool.virtual_price,
+    });
+
+    Ok(())
+}
+
+/// Require the instruction right before this one to be an ed25519 verification of
+/// `message` signed by `signer`
+///
+/// The ed25519 program has already checked the signature when this runs; what is
+/// left is making sure it covered this signer and this exact message.
+fn verify_order_signature(
+    instructions: &AccountInfo,
+    signer: &Pubkey,
+    message: &[u8],
+) -> Result<()> {
+    let current = sysvar_instructions::load_current_index_checked(instructions)?;
+    require!(current > 0, ErrorCode::InvalidOrderSignature);
+    let verify =
+        sysvar_instructions::load_instruction_at_checked(current as usize - 1, instructions)?;
+    require_keys_eq!(
+        verify.program_id,
+        ed25519_program::ID,
+        ErrorCode::InvalidOrderSignature
+    );
+
+    // One signature, with its key and message inside the ed25519 instruction itself
+    let data = &verify.data;
+    require!(
+        data.len() >= ED25519_HEADER_LEN && data[0] == 1,
+        ErrorCode::InvalidOrderSignature
+    );
+    let read_u16 = |at: usize| u16::from_le_bytes([data[at], data[at + 1]]);
+    let in_this_instruction = [4, 8, 14].iter().all(|&at| read_u16(at) == u16::MAX);
+    let public_key_offset = read_u16(6) as usize;
+    let message_offset = read_u16(10) as usize;
+    let message_len = read_u16(12) as usize;
+    require!(in_this_instruction, ErrorCode::InvalidOrderSignature);
+    require!(
+        data.get(public_key_offset..public_key_offset + 32) == Some(signer.as_ref())
+            && data.get(message_offset..message_offset + message_len) == Some(message),
+        ErrorCode::InvalidOrderSignature
+    );
+
+    Ok(())
+}
@@ -0,0 +1,134 @@
+use crate::errors::ErrorCode;
+use crate::events::WithdrawRequested;
+use crate::instructions::withdraw::*;
+use crate::state::*;
+use anchor_lang::prelude::*;
+use anchor_spl::token::Mint;
+
+#[derive(Accounts)]
+pub struct RequestWithdraw<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    pub pool: Account<'info, Pool>,
+
+    #[account(
+        constraint = lp_mint.key() == pool.lp_mint @ ErrorCode::InvalidTokenMint,
+    )]
+    pub lp_mint: Account<'info, Mint>,
+
+    #[account(
+        seeds = [&b"user-position"[..], user.key().as_ref(), pool.key().as_ref()],
+        bump = user_position.bump,
+        constraint = user_position.owner == user.key() @ ErrorCode::Unauthorized,
+        constraint = user_position.is_active @ ErrorCode::PositionNotActive,
+    )]
+    pub user_position: Account<'info, UserPosition>,
+
+    /// One staged withdrawal per position at a time
+    #[account(
+        init,
+        payer = user,
+        space = StagedWithdrawal::space(),
+        seeds = [&b"staged-withdraw"[..], user_position.key().as_ref()],
+        bump
+    )]
+    pub staged_withdrawal: Account<'info, StagedWithdrawal>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Announce a withdrawal of at least the pool's staging threshold of LP supply
+pub fn request_withdraw(ctx: Context<RequestWithdraw>, lp_amount: u64) -> Result<()> {
+    let pool = &ctx.accounts.pool;
+    require!(
+        lp_amount > 0 && lp_amount <= ctx.accounts.user_position.lp_amount,
+        ErrorCode::InsufficientLiquidity
+    );
+
+    let share_bps = (lp_amount as u128 * BPS_DENOMINATOR as u128
+        / pool.lp_supply(ctx.accounts.lp_mint.supply).max(1) as u128) as u64;
+    require!(
+        pool.staged_withdraw_threshold_bps > 0 && share_bps >= pool.staged_withdraw_threshold_bps,
+        ErrorCode::StagedWithdrawNotAllowed
+    );
+
+    let now = Clock::get()?.unix_timestamp;
+    let staged_withdrawal = &mut ctx.accounts.staged_withdrawal;
+    staged_withdrawal.bump = ctx.bumps.staged_withdrawal;
+    staged_withdrawal.user_position = ctx.accounts.user_position.key();
+    staged_withdrawal.pool = pool.key();
+    staged_withdrawal.lp_amount = lp_amount;
+    staged_withdrawal.requested_at = now;
+    staged_withdrawal.executable_at = now + pool.staged_withdraw_delay;
+
+    emit!(WithdrawRequested {
+        pool: pool.key(),
+        position: staged_withdrawal.user_position,
+        lp_amount,
+        share_bps,
+        executable_at: staged_withdrawal.executable_at,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct ExecuteWithdraw<'info> {
+    pub withdraw: Withdraw<'info>,
+
+    #[account(
+        mut,
+        seeds = [&b"staged-withdraw"[..], withdraw.user_position.key().as_ref()],
+        bump = staged_withdrawal.bump,
+    )]
+    pub staged_withdrawal: Account<'info, StagedWithdrawal>,
+}
+
+/// Carry out a staged withdrawal proportionally and without the exit fee
+pub fn execute_withdraw(
+    ctx: Context<ExecuteWithdraw>,
+    min_amounts: [u64; MAX_POOL_TOKENS],
+    deadline: i64,
+) -> Result<()> {
+    crate::utils::check_deadline(deadline)?;
+    let staged_withdrawal = &ctx.accounts.staged_withdrawal;
+    require!(
+        Clock::get()?.unix_timestamp >= staged_withdrawal.executable_at,
+        ErrorCode::StagedWithdrawNotReady
+    );
+
+    process_withdraw(
+        &mut ctx.accounts.withdraw,
+        staged_withdrawal.lp_amount,
+        &min_amounts,
+        WithdrawMode::Proportional,
+        0,
+    )?;
+
+    let user = ctx.accounts.withdraw.user.to_account_info();
+    ctx.accounts.staged_withdrawal.close(user)
+}
+
+#[derive(Accounts)]
+pub struct CancelWithdraw<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(
+        constraint = user_position.owner == user.key() @ ErrorCode::Unauthorized,
+    )]
+    pub user_position: Account<'info, UserPosition>,
+
+    #[account(
+        mut,
+        seeds = [&b"staged-withdraw"[..], user_position.key().as_ref()],
+        bump = staged_withdrawal.bump,
+        close = user,
+    )]
+    pub staged_withdrawal: Account<'info, StagedWithdrawal>,
+}
+
+pub fn cancel_withdraw(_ctx: Context<CancelWithdraw>) -> Result<()> {
+    Ok(())
+}
@@ -1,164 +1,757 @@
 use crate::errors::ErrorCode;
+use crate::events::{
+    FlowDirection, IntegratorFeePaid, LargeFlowEvent, SwapExecuted, TokenStatusChanged,
+};
 use crate::state::*;
 use anchor_lang::prelude::*;
-use anchor_spl::token::{self, Mint, Token, TokenAccount, Transfer};
+use anchor_spl::token_interface::{self, Mint, TokenAccount, TokenInterface, TransferChecked};
 
+/// Accounts of `swap` and `swap_indexed`, whose arguments differ
 #[derive(Accounts)]
-#[instruction(amount_in: u64, min_amount_out: u64)]
 pub struct Swap<'info> {
     #[account(mut)]
     pub user: Signer<'info>,
 
-    #[account(mut)]
+    /// Config holding the stale-pool policy and protocol fee share
+    pub amm_config: Account<'info, AmmConfig>,
+
+    #[account(
+        mut,
+        has_one = amm_config,
+    )]
     pub pool: Account<'info, Pool>,
 
-    // Token being sent to the pool
-    pub token_mint_in: Account<'info, Mint>,
+    /// Token being sent to the pool
+    pub token_mint_in: InterfaceAccount<'info, Mint>,
 
-    // Token being received from the pool
-    pub token_mint_out: Account<'info, Mint>,
+    /// Token being received from the pool
+    pub token_mint_out: InterfaceAccount<'info, Mint>,
 
-    // User's token accounts
+    /// User's token accounts
     #[account(
         mut,
         token::authority = user,
         token::mint = token_mint_in,
     )]
-    pub user_token_in: Account<'info, TokenAccount>,
+    pub user_token_in: InterfaceAccount<'info, TokenAccount>,
 
     #[account(
         mut,
         token::authority = user,
         token::mint = token_mint_out,
     )]
-    pub user_token_out: Account<'info, TokenAccount>,
+    pub user_token_out: InterfaceAccount<'info, TokenAccount>,
 
-    // Pool's token accounts
+    /// Pool's token accounts
     #[account(
         mut,
         token::authority = pool,
         token::mint = token_mint_in,
     )]
-    pub pool_token_in: Account<'info, TokenAccount>,
+    pub pool_token_in: InterfaceAccount<'info, TokenAccount>,
 
     #[account(
         mut,
         token::authority = pool,
         token::mint = token_mint_out,
     )]
-    pub pool_token_out: Account<'info, TokenAccount>,
+    pub pool_token_out: InterfaceAccount<'info, TokenAccount>,
 
-    pub token_program: Program<'info, Token>,
-}
+    /// SPL Token or Token-2022, matching the traded mints
+    pub token_program: Interface<'info, TokenInterface>,
 
-pub fn handler(ctx: Context<Swap>, amount_in: u64, min_amount_out: u64) -> Result<()> {
-    // Extract pool information first to avoid borrow conflicts
-    let pool_key = ctx.accounts.pool.key();
-    let pool_account_info = ctx.accounts.pool.to_account_info();
+    /// Optional third-party account to deliver the output to instead of `user_token_out`
+    #[account(
+        mut,
+        token::mint = token_mint_out,
+    )]
+    pub recipient_token_out: Option<InterfaceAccount<'info, TokenAccount>>,
 
-    // Now use mutable borrow
-    let pool = &mut ctx.accounts.pool;
+    /// Caller's vote escrow under the pool's config; its voting power discounts the swap fee
+    #[account(
+        seeds = [&b"vote-escrow"[..], amm_config.key().as_ref(), user.key().as_ref()],
+        bump = vote_escrow.bump,
+    )]
+    pub vote_escrow: Option<Account<'info, VoteEscrow>>,
+
+    /// Pool's front-end snapshot, refreshed once the swap settles
+    #[account(
+        mut,
+        has_one = pool,
+    )]
+    pub pool_snapshot: Option<Account<'info, PoolSnapshot>>,
+
+    /// Caller's token account of the pool's credential mint; required by permissioned pools
+    pub credential: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Front end's account of the input token, paid `integrator_fee_bps` on top of the swap
+    #[account(
+        mut,
+        token::mint = token_mint_in,
+    )]
+    pub integrator_fee_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Referrer's account for the input token, credited `integrator_fee_bps` in place of
+    /// `integrator_fee_account`; the fee waits in its `referral_vault` until claimed
+    #[account(
+        mut,
+        has_one = pool,
+        constraint = referral_account.token_mint == token_mint_in.key() @ ErrorCode::InvalidTokenMint,
+    )]
+    pub referral_account: Option<Account<'info, ReferralAccount>>,
+
+    /// The referral account's vault; required with it
+    #[account(mut)]
+    pub referral_vault: Option<InterfaceAccount<'info, TokenAccount>>,
+}
+
+pub fn handler(
+    ctx: Context<Swap>,
+    amount_in: u64,
+    min_amount_out: u64,
+    deadline: i64,
+    integrator_fee_bps: u64,
+    max_price_impact_bps: Option<u64>,
+) -> Result<()> {
+    crate::utils::check_deadline(deadline)?;
 
     // Find the token indices
+    let pool = &ctx.accounts.pool;
     let token_in_idx = pool
-        .token_mints
-        .iter()
-        .position(|mint| mint == &ctx.accounts.token_mint_in.key())
+        .token_index(&ctx.accounts.token_mint_in.key())
         .ok_or(ErrorCode::InvalidTokenMint)?;
 
     let token_out_idx = pool
-        .token_mints
-        .iter()
-        .position(|mint| mint == &ctx.accounts.token_mint_out.key())
+        .token_index(&ctx.accounts.token_mint_out.key())
         .ok_or(ErrorCode::InvalidTokenMint)?;
 
-    // Capture values we'll need later
-    let pool_type = pool.pool_type;
-    let pool_reserves = pool.reserves.clone();
-    let pool_amplification = pool.amplification;
-    let pool_bump = pool.bump;
-    let token_mints = pool.token_mints.clone();
-
-    // Get current reserves
-    let in_reserve = pool_reserves[token_in_idx];
-    let out_reserve = pool_reserves[token_out_idx];
+    execute_swap(
+        ctx,
+        token_in_idx,
+        token_out_idx,
+        amount_in,
+        min_amount_out,
+        integrator_fee_bps,
+        max_price_impact_bps,
+    )
+}
 
-    // Calculate current weights
-    let current_weights = crate::state::math::calculate_weights(&pool_reserves);
+/// Swap with token indices the caller derived from `pool.token_mints` off-chain
+///
+/// The mints are checked against the pool's mints at those indices rather than
+/// searched for, which saves compute and keeps router-built transactions fixed.
+#[allow(clippy::too_many_arguments)]
+pub fn swap_indexed(
+    ctx: Context<Swap>,
+    token_in_index: u8,
+    token_out_index: u8,
+    amount_in: u64,
+    min_amount_out: u64,
+    deadline: i64,
+    integrator_fee_bps: u64,
+    max_price_impact_bps: Option<u64>,
+) -> Result<()> {
+    crate::utils::check_deadline(deadline)?;
 
-    // Calculate dynamic fee based on weight deviation
-    let fee = crate::state::math::calculate_dynamic_fee(&current_weights, &pool.target_weights);
+    let token_in_idx = token_in_index as usize;
+    let token_out_idx = token_out_index as usize;
+    let pool = &ctx.accounts.pool;
+    crate::utils::check_pool_mint(pool, token_in_idx, &ctx.accounts.token_mint_in.key())?;
+    crate::utils::check_pool_mint(pool, token_out_idx, &ctx.accounts.token_mint_out.key())?;
 
-    // Calculate output amount
-    let amount_out = crate::state::math::calculate_output_amount(
+    execute_swap(
+        ctx,
+        token_in_idx,
+        token_out_idx,
         amount_in,
-        in_reserve,
-        out_reserve,
-        fee,
-        pool_amplification,
+        min_amount_out,
+        integrator_fee_bps,
+        max_price_impact_bps,
     )
-    .ok_or(ErrorCode::InvalidSwap)?;
+}
 
-    // Check minimum output amount
-    require!(amount_out >= min_amount_out, ErrorCode::SlippageExceeded);
+/// Price and settle a swap between the pool tokens at `token_in_idx` and `token_out_idx`
+///
+/// A nonzero `integrator_fee_bps` of `amount_in` is sent from the user to
+/// `integrator_fee_account` on top of the swap, so the pool still prices the full
+/// `amount_in`. With a `referral_account` instead, the fee goes to its vault and
+/// is booked to the referrer, who claims many swaps' fees in one transfer. A
+/// `max_price_impact_bps` caps how far the execution price may fall below the
+/// pool's spot price, independently of `min_amount_out`.
+fn execute_swap(
+    ctx: Context<Swap>,
+    token_in_idx: usize,
+    token_out_idx: usize,
+    amount_in: u64,
+    min_amount_out: u64,
+    integrator_fee_bps: u64,
+    max_price_impact_bps: Option<u64>,
+) -> Result<()> {
+    let integrator_fee_amount = integrator_fee(amount_in, integrator_fee_bps)?;
+    let refers = match &ctx.accounts.referral_account {
+        Some(referral_account) => {
+            let vault = ctx
+                .accounts
+                .referral_vault
+                .as_ref()
+                .map(|vault| vault.key());
+            require!(
+                vault == Some(referral_account.vault),
+                ErrorCode::VaultMismatch
+            );
+            true
+        }
+        None => false,
+    };
+    require!(
+        !(refers && ctx.accounts.integrator_fee_account.is_some()),
+        ErrorCode::ConflictingIntegratorFeeAccounts
+    );
+    require!(
+        integrator_fee_bps == 0 || refers || ctx.accounts.integrator_fee_account.is_some(),
+        ErrorCode::MissingIntegratorFeeAccount
+    );
 
-    // Transfer tokens from user to pool
-    let cpi_accounts_in = Transfer {
+    // Extract pool information first to avoid borrow conflicts
+    let pool_account_info = ctx.accounts.pool.to_account_info();
+
+    // Now use mutable borrow
+    let pool = &mut ctx.accounts.pool;
+    crate::utils::check_credential(
+        pool,
+        &ctx.accounts.user.key(),
+        ctx.accounts.credential.as_deref(),
+    )?;
+    crate::utils::check_pool_vault(pool, token_in_idx, &ctx.accounts.pool_token_in.key())?;
+    crate::utils::check_pool_vault(pool, token_out_idx, &ctx.accounts.pool_token_out.key())?;
+    pool.update_weights_ema(Clock::get()?.slot);
+
+    // Price the swap against current reserves, less any vote escrow discount
+    let fee_discount_bps = crate::utils::vote_escrow_discount_bps(
+        ctx.accounts.vote_escrow.as_deref(),
+        &ctx.accounts.amm_config,
+        Clock::get()?.unix_timestamp,
+    )?;
+
+    let min_fee = stale_pool_min_fee(pool, &ctx.accounts.amm_config)?;
+
+    // Pull the input first and price what the vault actually received, so a
+    // Token-2022 transfer fee can never credit the reserves more than they hold
+    let vault_before = ctx.accounts.pool_token_in.amount;
+    let cpi_accounts_in = TransferChecked {
         from: ctx.accounts.user_token_in.to_account_info(),
+        mint: ctx.accounts.token_mint_in.to_account_info(),
         to: ctx.accounts.pool_token_in.to_account_info(),
         authority: ctx.accounts.user.to_account_info(),
     };
     let cpi_program = ctx.accounts.token_program.to_account_info();
     let cpi_ctx_in = CpiContext::new(cpi_program.clone(), cpi_accounts_in);
-    token::transfer(cpi_ctx_in, amount_in)?;
-
-    // Transfer tokens from pool to user - fixed seed array handling
-    let partner_token_mint_ref = if pool_type == PoolType::Growth {
-        // For Growth pools, we need the partner token mint as part of the seeds
-        let partner_idx = if token_in_idx == 0 { 1 } else { 0 };
-        Some(token_mints[partner_idx].as_ref())
-    } else {
-        None
-    };
+    token_interface::transfer_checked(cpi_ctx_in, amount_in, ctx.accounts.token_mint_in.decimals)?;
+    ctx.accounts.pool_token_in.reload()?;
+    let amount_received = ctx
+        .accounts
+        .pool_token_in
+        .amount
+        .checked_sub(vault_before)
+        .ok_or(ErrorCode::MathOverflow)?;
 
-    let seed_type = if pool_type == PoolType::Seed {
-        &b"seed"[..]
-    } else {
-        &b"growth"[..]
-    };
+    // Pay the front end after the vault is measured, so its fee never reaches the reserves
+    if let Some(integrator_fee_account) = &ctx.accounts.integrator_fee_account {
+        if integrator_fee_amount > 0 {
+            let cpi_accounts_fee = TransferChecked {
+                from: ctx.accounts.user_token_in.to_account_info(),
+                mint: ctx.accounts.token_mint_in.to_account_info(),
+                to: integrator_fee_account.to_account_info(),
+                authority: ctx.accounts.user.to_account_info(),
+            };
+            let cpi_ctx_fee = CpiContext::new(cpi_program.clone(), cpi_accounts_fee);
+            token_interface::transfer_checked(
+                cpi_ctx_fee,
+                integrator_fee_amount,
+                ctx.accounts.token_mint_in.decimals,
+            )?;
+        }
+    }
 
-    // Store the seeds in longer-lived variables
-    let seed_pool = &b"pool"[..];
+    // A referral fee is booked at what its vault received, net of any transfer fee
+    let mut referral_fee = 0;
+    if let (Some(referral_account), Some(referral_vault)) = (
+        ctx.accounts.referral_account.as_mut(),
+        ctx.accounts.referral_vault.as_mut(),
+    ) {
+        if integrator_fee_amount > 0 {
+            let referral_vault_before = referral_vault.amount;
+            let cpi_accounts_fee = TransferChecked {
+                from: ctx.accounts.user_token_in.to_account_info(),
+                mint: ctx.accounts.token_mint_in.to_account_info(),
+                to: referral_vault.to_account_info(),
+                authority: ctx.accounts.user.to_account_info(),
+            };
+            let cpi_ctx_fee = CpiContext::new(cpi_program.clone(), cpi_accounts_fee);
+            token_interface::transfer_checked(
+                cpi_ctx_fee,
+                integrator_fee_amount,
+                ctx.accounts.token_mint_in.decimals,
+            )?;
+            referral_vault.reload()?;
+            referral_fee = referral_vault
+                .amount
+                .checked_sub(referral_vault_before)
+                .ok_or(ErrorCode::MathOverflow)?;
+            referral_account.fees_owed = referral_account
+                .fees_owed
+                .checked_add(referral_fee)
+                .ok_or(ErrorCode::MathOverflow)?;
+            referral_account.lifetime_fees += referral_fee as u128;
+        }
+    }
 
-    let seeds_with_partner = [
-        seed_pool,
-        seed_type,
-        partner_token_mint_ref.unwrap_or(&[]),
-        &[pool_bump],
-    ];
-    let seeds_without_partner = [seed_pool, seed_type, &[pool_bump]];
+    let quote = quote_swap_with_min_fee(
+        pool,
+        token_in_idx,
+        token_out_idx,
+        amount_received,
+        fee_discount_bps,
+        min_fee,
+    )?;
+    let amount_out = quote.amount_out;
 
-    let seeds = match partner_token_mint_ref {
-        Some(_) => &seeds_with_partner[..],
-        None => &seeds_without_partner[..],
-    };
+    // Check minimum output amount
+    crate::utils::check_min_amount("Swap output", amount_out, min_amount_out)?;
+    if let Some(max_price_impact_bps) = max_price_impact_bps {
+        check_price_impact(
+            pool,
+            token_in_idx,
+            token_out_idx,
+            amount_received,
+            &quote,
+            max_price_impact_bps,
+        )?;
+    }
 
-    let signer = &[seeds];
+    // Transfer tokens from pool to user
+    let seeds = pool.signer_seeds()?;
+    let signer = &[&seeds[..]];
 
-    let cpi_accounts_out = Transfer {
+    let recipient_token_out = ctx
+        .accounts
+        .recipient_token_out
+        .as_ref()
+        .unwrap_or(&ctx.accounts.user_token_out);
+    let recipient = recipient_token_out.key();
+    let cpi_accounts_out = TransferChecked {
         from: ctx.accounts.pool_token_out.to_account_info(),
-        to: ctx.accounts.user_token_out.to_account_info(),
+        mint: ctx.accounts.token_mint_out.to_account_info(),
+        to: recipient_token_out.to_account_info(),
         authority: pool_account_info,
     };
     let cpi_ctx_out = CpiContext::new_with_signer(cpi_program, cpi_accounts_out, signer);
-    token::transfer(cpi_ctx_out, amount_out)?;
+    token_interface::transfer_checked(
+        cpi_ctx_out,
+        amount_out,
+        ctx.accounts.token_mint_out.decimals,
+    )?;
 
-    // Update pool reserves
-    pool.reserves[token_in_idx] += amount_in;
-    pool.reserves[token_out_idx] = pool.reserves[token_out_idx].saturating_sub(amount_out);
+    // Update pool reserves and statistics
+    apply_swap(
+        pool,
+        &ctx.accounts.amm_config,
+        ctx.accounts.user.key(),
+        token_in_idx,
+        token_out_idx,
+        amount_received,
+        &quote,
+    )?;
 
     // Update pool last update timestamp
-    pool.last_update = Clock::get()?.unix_timestamp;
+    crate::utils::touch_pool(pool)?;
+
+    emit!(SwapExecuted {
+        pool: pool.key(),
+        user: ctx.accounts.user.key(),
+        recipient,
+        token_mint_in: ctx.accounts.token_mint_in.key(),
+        token_mint_out: ctx.accounts.token_mint_out.key(),
+        amount_in: amount_received,
+        amount_out,
+        fee_amount: quote.fee_amount,
+        virtual_price: pool.virtual_price,
+    });
+    if let Some(integrator_fee_account) = &ctx.accounts.integrator_fee_account {
+        if integrator_fee_amount > 0 {
+            emit!(IntegratorFeePaid {
+                pool: pool.key(),
+                user: ctx.accounts.user.key(),
+                integrator_fee_account: integrator_fee_account.key(),
+                token_mint: ctx.accounts.token_mint_in.key(),
+                integrator_fee_bps,
+                fee_amount: integrator_fee_amount,
+            });
+        }
+    }
+    if let Some(referral_account) = &ctx.accounts.referral_account {
+        if referral_fee > 0 {
+            emit!(IntegratorFeePaid {
+                pool: pool.key(),
+                user: ctx.accounts.user.key(),
+                integrator_fee_account: referral_account.key(),
+                token_mint: ctx.accounts.token_mint_in.key(),
+                integrator_fee_bps,
+                fee_amount: referral_fee,
+            });
+        }
+    }
+    crate::instructions::pool_snapshot::refresh_if_present(
+        ctx.accounts.pool_snapshot.as_mut(),
+        pool,
+    )?;
+
+    if !ctx.remaining_accounts.is_empty() {
+        // Persist the settled pool so the hook reads post-swap reserves
+        pool.exit(&crate::ID)?;
+        let payload = crate::instructions::swap_hook::SwapHookPayload {
+            pool: pool.key(),
+            user: ctx.accounts.user.key(),
+            recipient,
+            token_mint_in: ctx.accounts.token_mint_in.key(),
+            token_mint_out: ctx.accounts.token_mint_out.key(),
+            amount_in: amount_received,
+            amount_out,
+            fee_amount: quote.fee_amount,
+        };
+        crate::instructions::swap_hook::invoke_swap_hook(
+            ctx.remaining_accounts,
+            &ctx.accounts.amm_config,
+            &payload,
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Reject a quoted swap whose execution price, net of fees, falls more than
+/// `max_price_impact_bps` below the pool's pre-trade spot price
+///
+/// Slippage guards against the pool moving before the swap lands; this guards
+/// against the swap itself being too large for the pool's depth.
+pub fn check_price_impact(
+    pool: &Pool,
+    token_in_idx: usize,
+    token_out_idx: usize,
+    amount_in: u64,
+    quote: &SwapQuote,
+    max_price_impact_bps: u64,
+) -> Result<()> {
+    let spot_price = crate::state::math::calculate_pair_price(
+        &pool.reserves,
+        token_in_idx,
+        token_out_idx,
+        pool.amplification,
+    )
+    .ok_or(ErrorCode::MathOverflow)?;
+    let impact_bps = crate::state::math::price_impact_bps(
+        spot_price,
+        amount_in.saturating_sub(quote.fee_amount),
+        quote.amount_out,
+    )
+    .ok_or(ErrorCode::MathOverflow)?;
+    if impact_bps > max_price_impact_bps {
+        msg!(
+            "Price impact: got {} bps, maximum {}",
+            impact_bps,
+            max_price_impact_bps
+        );
+        return err!(ErrorCode::PriceImpactExceeded);
+    }
+    Ok(())
+}
+
+/// Front-end fee of `integrator_fee_bps` on `amount_in`, capped at `MAX_INTEGRATOR_FEE_BPS`
+///
+/// Rounds down, so the trader never pays above the rate they signed for.
+pub fn integrator_fee(amount_in: u64, integrator_fee_bps: u64) -> Result<u64> {
+    require!(
+        integrator_fee_bps <= crate::state::math::MAX_INTEGRATOR_FEE_BPS,
+        ErrorCode::IntegratorFeeTooHigh
+    );
+    crate::state::rounding::mul_div(
+        amount_in,
+        integrator_fee_bps,
+        crate::state::math::BPS_DENOMINATOR,
+        crate::state::rounding::Rounding::Down,
+    )
+    .ok_or(error!(ErrorCode::MathOverflow))
+}
+
+/// Fee floor for swapping against `pool` under its config's stale-pool policy
+///
+/// Without a fresh oracle price the band cannot protect LPs, so a stale pool charges
+/// its max fee, or rejects the swap when the config says so.
+pub fn stale_pool_min_fee(pool: &Pool, amm_config: &AmmConfig) -> Result<u64> {
+    if pool.is_oracle_stale(Clock::get()?.slot, amm_config.max_oracle_age_slots) {
+        require!(!amm_config.reject_stale_swaps, ErrorCode::StalePool);
+        return Ok(pool.fee_params.max_fee);
+    }
+    Ok(0)
+}
+
+pub use equilibrium_math::pricing::SwapQuote;
+
+/// Price a swap of `amount_in` from `token_in_idx` to `token_out_idx` using the pool's dynamic fee
+pub fn quote_swap(
+    pool: &Pool,
+    token_in_idx: usize,
+    token_out_idx: usize,
+    amount_in: u64,
+) -> Result<SwapQuote> {
+    quote_discounted_swap(pool, token_in_idx, token_out_idx, amount_in, 0)
+}
+
+/// Price a swap like `quote_swap`, waiving `fee_discount_bps` of the fee
+pub fn quote_discounted_swap(
+    pool: &Pool,
+    token_in_idx: usize,
+    token_out_idx: usize,
+    amount_in: u64,
+    fee_discount_bps: u64,
+) -> Result<SwapQuote> {
+    quote_swap_with_min_fee(
+        pool,
+        token_in_idx,
+        token_out_idx,
+        amount_in,
+        fee_discount_bps,
+        0,
+    )
+}
+
+/// Price a swap like `quote_discounted_swap`, charging at least `min_fee` parts per 1000
+/// before the discount
+pub fn quote_swap_with_min_fee(
+    pool: &Pool,
+    token_in_idx: usize,
+    token_out_idx: usize,
+    amount_in: u64,
+    fee_discount_bps: u64,
+    min_fee: u64,
+) -> Result<SwapQuote> {
+    quote_swap_at(
+        pool,
+        token_in_idx,
+        token_out_idx,
+        amount_in,
+        fee_discount_bps,
+        min_fee,
+        Clock::get()?.unix_timestamp,
+    )
+}
+
+/// Price a swap like `quote_swap_with_min_fee` at unix time `now`, without reading the clock
+pub fn quote_swap_at(
+    pool: &Pool,
+    token_in_idx: usize,
+    token_out_idx: usize,
+    amount_in: u64,
+    fee_discount_bps: u64,
+    min_fee: u64,
+    now: i64,
+) -> Result<SwapQuote> {
+    require!(token_in_idx != token_out_idx, ErrorCode::InvalidSwap);
+    require!(
+        pool.token_status[token_in_idx] != TokenStatus::Frozen
+            && pool.token_status[token_out_idx] != TokenStatus::Frozen,
+        ErrorCode::TokenFrozen
+    );
+
+    pool.view()
+        .quote_swap(
+            token_in_idx,
+            token_out_idx,
+            amount_in,
+            fee_discount_bps,
+            min_fee,
+            now,
+        )
+        .map_err(|error| error!(ErrorCode::from(error)))
+}
+
+/// Apply `trader`'s priced swap to the pool reserves and lifetime statistics,
+/// splitting the fee with the protocol at the share `amm_config` resolves to
+pub fn apply_swap(
+    pool: &mut Account<Pool>,
+    amm_config: &AmmConfig,
+    trader: Pubkey,
+    token_in_idx: usize,
+    token_out_idx: usize,
+    amount_in: u64,
+    quote: &SwapQuote,
+) -> Result<()> {
+    pool.require_continuous_trading()?;
+    book_swap(
+        pool,
+        amm_config,
+        trader,
+        token_in_idx,
+        token_out_idx,
+        amount_in,
+        quote,
+    )
+}
+
+/// Book a priced swap like `apply_swap` without the batch auction check; the net
+/// trade `settle_batch` routes through the curve is the only swap batch-mode pools take
+pub fn book_swap(
+    pool: &mut Account<Pool>,
+    amm_config: &AmmConfig,
+    trader: Pubkey,
+    token_in_idx: usize,
+    token_out_idx: usize,
+    amount_in: u64,
+    quote: &SwapQuote,
+) -> Result<()> {
+    pool.require_not_paused()?;
+    let reserves_before = pool.reserves.clone();
+    let protocol_fee = pool.protocol_fee(amm_config, quote.fee_amount);
+    let clock = Clock::get()?;
+    book_swap_at(
+        pool,
+        protocol_fee,
+        token_in_idx,
+        token_out_idx,
+        amount_in,
+        quote,
+        clock.unix_timestamp,
+        clock.slot,
+    )?;
+    refresh_and_emit_token_status(pool, clock.unix_timestamp);
+    emit_large_flow(pool, trader, FlowDirection::Swap, &reserves_before);
+
+    Ok(())
+}
+
+/// Book a priced swap on `pool` at unix time `now` and slot `slot`, without reading
+/// the clock: move the reserves, set aside `protocol_fee` of the fee for the
+/// protocol and update D, the virtual price and the lifetime statistics.
+/// Token status is left to the caller.
+#[allow(clippy::too_many_arguments)]
+pub fn book_swap_at(
+    pool: &mut Pool,
+    protocol_fee: u64,
+    token_in_idx: usize,
+    token_out_idx: usize,
+    amount_in: u64,
+    quote: &SwapQuote,
+    now: i64,
+    slot: u64,
+) -> Result<()> {
+    pool.update_weights_ema(slot);
+
+    // The protocol's cut of the fee stays in the vault but leaves the reserves
+    let amount_in_to_reserves = amount_in - protocol_fee;
+    pool.protocol_fees_owed[token_in_idx] += protocol_fee;
+
+    let new_d = move_swap_reserves(
+        pool,
+        token_in_idx,
+        token_out_idx,
+        amount_in_to_reserves,
+        quote.amount_out,
+    )?;
+
+    // The invariant guard already solved D for the new reserves
+    let old_d = pool.last_d;
+    pool.last_d = new_d;
+    pool.d_slot = slot;
+    pool.scale_virtual_price(old_d);
+
+    pool.lifetime_volume_per_token[token_in_idx] =
+        pool.lifetime_volume_per_token[token_in_idx].saturating_add(amount_in as u128);
+    pool.lifetime_fees_per_token[token_in_idx] = pool.lifetime_fees_per_token[token_in_idx]
+        .saturating_add((quote.fee_amount - protocol_fee) as u128);
+    pool.swap_count = pool.swap_count.saturating_add(1);
+    pool.refresh_routing_hint(now);
 
     Ok(())
 }
+
+/// Move a priced swap through `pool`'s reserves, guarded by the invariant and price band,
+/// returning D of the new reserves
+pub fn move_swap_reserves(
+    pool: &mut Pool,
+    token_in_idx: usize,
+    token_out_idx: usize,
+    amount_in_to_reserves: u64,
+    amount_out: u64,
+) -> Result<u64> {
+    let reserves_before = pool.reserves.clone();
+    pool.reserves[token_in_idx] += amount_in_to_reserves;
+    pool.reserves[token_out_idx] = pool.reserves[token_out_idx].saturating_sub(amount_out);
+
+    // Swaps are priced on the pool's full invariant, so a drop there means the math is wrong
+    let new_d = crate::state::math::preserved_invariant(
+        &reserves_before,
+        &pool.reserves,
+        pool.amplification,
+    )
+    .ok_or(ErrorCode::InvariantViolated)?;
+    pool.check_price_band(&reserves_before, token_in_idx, token_out_idx)?;
+
+    Ok(new_d)
+}
+
+/// Emit `LargeFlowEvent` if the reserve change since `reserves_before` drained at
+/// least the pool's `large_flow_threshold_bps` of any token
+pub fn emit_large_flow(
+    pool: &Account<Pool>,
+    user: Pubkey,
+    direction: FlowDirection,
+    reserves_before: &[u64],
+) {
+    if pool.large_flow_threshold_bps == 0 {
+        return;
+    }
+    let drained = reserves_before
+        .iter()
+        .zip(pool.reserves.iter())
+        .enumerate()
+        .filter(|(_, (&before, &after))| before > after)
+        .map(|(i, (&before, &after))| {
+            let amount = before - after;
+            let share_bps = (amount as u128 * crate::state::math::BPS_DENOMINATOR as u128
+                / before as u128) as u64;
+            (i, amount, share_bps)
+        })
+        .max_by_key(|&(_, _, share_bps)| share_bps);
+    let Some((i, amount, reserve_share_bps)) = drained else {
+        return;
+    };
+    if reserve_share_bps < pool.large_flow_threshold_bps {
+        return;
+    }
+
+    emit!(LargeFlowEvent {
+        pool: pool.key(),
+        user,
+        direction,
+        token_mint: pool.token_mints[i],
+        amount,
+        reserve_share_bps,
+        weights_before: crate::state::math::calculate_weights(reserves_before),
+        weights_after: pool.current_weights(),
+    });
+}
+
+/// Refresh `pool`'s token statuses at `now` and emit an event per transition
+pub fn refresh_and_emit_token_status(pool: &mut Account<Pool>, now: i64) {
+    let pool_key = pool.key();
+    let total: u64 = pool.reserves.iter().sum();
+    for (i, previous_status) in pool.refresh_token_status(now) {
+        emit!(TokenStatusChanged {
+            pool: pool_key,
+            token_mint: pool.token_mints[i],
+            previous_status,
+            status: pool.token_status[i],
+            weight_bps: crate::state::math::calculate_weight(pool.reserves[i], total),
+        });
+    }
+}
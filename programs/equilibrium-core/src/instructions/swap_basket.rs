@@ -0,0 +1,414 @@
+use crate::errors::ErrorCode;
+use crate::events::{BasketSwapExecuted, FlowDirection};
+use crate::instructions::swap::{
+    emit_large_flow, refresh_and_emit_token_status, stale_pool_min_fee,
+};
+use crate::state::*;
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{self, Mint, TokenAccount, TokenInterface, TransferChecked};
+
+#[derive(Accounts)]
+pub struct SwapBasket<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    /// Config holding the stale-pool policy and protocol fee share
+    pub amm_config: Account<'info, AmmConfig>,
+
+    #[account(
+        mut,
+        has_one = amm_config,
+        constraint = pool.pool_type == PoolType::Seed @ ErrorCode::InvalidPoolType,
+    )]
+    pub pool: Account<'info, Pool>,
+
+    /// Token mints - must match the order in pool.token_mints; the third only for 3-token pools
+    pub token_mint_a: InterfaceAccount<'info, Mint>,
+    pub token_mint_b: InterfaceAccount<'info, Mint>,
+    pub token_mint_c: Option<InterfaceAccount<'info, Mint>>,
+
+    /// User's token accounts, sending the basket and receiving the output
+    #[account(
+        mut,
+        token::authority = user,
+        token::mint = token_mint_a,
+    )]
+    pub user_token_a: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        token::authority = user,
+        token::mint = token_mint_b,
+    )]
+    pub user_token_b: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        token::authority = user,
+        token::mint = token_mint_c,
+    )]
+    pub user_token_c: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Pool's token accounts
+    #[account(
+        mut,
+        token::authority = pool,
+        token::mint = token_mint_a,
+    )]
+    pub pool_token_a: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        token::authority = pool,
+        token::mint = token_mint_b,
+    )]
+    pub pool_token_b: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        token::authority = pool,
+        token::mint = token_mint_c,
+    )]
+    pub pool_token_c: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// SPL Token or Token-2022, matching the traded mints
+    pub token_program: Interface<'info, TokenInterface>,
+
+    /// Caller's vote escrow under the pool's config; its voting power discounts the swap fee
+    #[account(
+        seeds = [&b"vote-escrow"[..], amm_config.key().as_ref(), user.key().as_ref()],
+        bump = vote_escrow.bump,
+    )]
+    pub vote_escrow: Option<Account<'info, VoteEscrow>>,
+
+    /// Pool's front-end snapshot, refreshed once the swap settles
+    #[account(
+        mut,
+        has_one = pool,
+    )]
+    pub pool_snapshot: Option<Account<'info, PoolSnapshot>>,
+
+    /// Caller's token account of the pool's credential mint; required by permissioned pools
+    pub credential: Option<InterfaceAccount<'info, TokenAccount>>,
+}
+
+impl<'info> SwapBasket<'info> {
+    /// User account, mint and pool vault of the token at `index`, if all three were passed
+    fn token_accounts(
+        &self,
+        index: usize,
+    ) -> Option<(
+        &InterfaceAccount<'info, TokenAccount>,
+        &InterfaceAccount<'info, Mint>,
+        &InterfaceAccount<'info, TokenAccount>,
+    )> {
+        match index {
+            0 => Some((&self.user_token_a, &self.token_mint_a, &self.pool_token_a)),
+            1 => Some((&self.user_token_b, &self.token_mint_b, &self.pool_token_b)),
+            _ => Some((
+                self.user_token_c.as_ref()?,
+                self.token_mint_c.as_ref()?,
+                self.pool_token_c.as_ref()?,
+            )),
+        }
+    }
+
+    /// Pool vault balances, reloaded after the inputs were pulled
+    fn reload_vault_amounts(&mut self) -> Result<TokenAmounts> {
+        self.pool_token_a.reload()?;
+        self.pool_token_b.reload()?;
+        let mut amounts = [self.pool_token_a.amount, self.pool_token_b.amount, 0];
+        if let Some(pool_token_c) = self.pool_token_c.as_mut() {
+            pool_token_c.reload()?;
+            amounts[2] = pool_token_c.amount;
+        }
+        Ok(amounts)
+    }
+}
+
+/// Sell `amounts_in` of several Seed Pool tokens for the one at `token_out_index`
+///
+/// The inputs are priced together in a single invariant solve, so a mixed bag pays
+/// the curve's price impact once rather than once per sequential swap.
+pub fn swap_basket(
+    ctx: Context<SwapBasket>,
+    amounts_in: Vec<u64>,
+    token_out_index: u8,
+    min_amount_out: u64,
+    deadline: i64,
+) -> Result<()> {
+    crate::utils::check_deadline(deadline)?;
+
+    let pool = &ctx.accounts.pool;
+    let num_tokens = pool.reserves.len();
+    let token_out_idx = token_out_index as usize;
+    require!(
+        amounts_in.len() == num_tokens && token_out_idx < num_tokens,
+        ErrorCode::InvalidInstructionData
+    );
+    require!(amounts_in[token_out_idx] == 0, ErrorCode::InvalidSwap);
+    crate::utils::check_credential(
+        pool,
+        &ctx.accounts.user.key(),
+        ctx.accounts.credential.as_deref(),
+    )?;
+
+    // Verify token mints and vaults match pool configuration, one per pool token
+    for i in 0..num_tokens {
+        let Some((_, mint, vault)) = ctx.accounts.token_accounts(i) else {
+            msg!("Token {}: accounts are missing", i);
+            return err!(ErrorCode::InvalidTokenMint);
+        };
+        crate::utils::check_pool_mint(pool, i, &mint.key())?;
+        crate::utils::check_pool_vault(pool, i, &vault.key())?;
+    }
+
+    let fee_discount_bps = crate::utils::vote_escrow_discount_bps(
+        ctx.accounts.vote_escrow.as_deref(),
+        &ctx.accounts.amm_config,
+        Clock::get()?.unix_timestamp,
+    )?;
+    let min_fee = stale_pool_min_fee(pool, &ctx.accounts.amm_config)?;
+
+    // Pull each input and price what the vaults actually received, as `swap` does
+    let user = ctx.accounts.user.to_account_info();
+    let cpi_program = ctx.accounts.token_program.to_account_info();
+    let mut vaults_before = [0u64; MAX_POOL_TOKENS];
+    for (i, &amount) in amounts_in.iter().enumerate() {
+        let (user_token, mint, vault) = ctx
+            .accounts
+            .token_accounts(i)
+            .ok_or(ErrorCode::InvalidTokenMint)?;
+        vaults_before[i] = vault.amount;
+        if amount == 0 {
+            continue;
+        }
+        let cpi_accounts = TransferChecked {
+            from: user_token.to_account_info(),
+            mint: mint.to_account_info(),
+            to: vault.to_account_info(),
+            authority: user.clone(),
+        };
+        let cpi_ctx = CpiContext::new(cpi_program.clone(), cpi_accounts);
+        token_interface::transfer_checked(cpi_ctx, amount, mint.decimals)?;
+    }
+    let vaults_after = ctx.accounts.reload_vault_amounts()?;
+    let mut amounts_received = [0u64; MAX_POOL_TOKENS];
+    for (received, (after, before)) in amounts_received
+        .iter_mut()
+        .zip(vaults_after.iter().zip(vaults_before.iter()))
+    {
+        *received = after.checked_sub(*before).ok_or(ErrorCode::MathOverflow)?;
+    }
+
+    ctx.accounts.pool.update_weights_ema(Clock::get()?.slot);
+    let quote = quote_basket_swap(
+        &ctx.accounts.pool,
+        &amounts_received[..num_tokens],
+        token_out_idx,
+        fee_discount_bps,
+        min_fee,
+    )?;
+    let amount_out = quote.amount_out;
+    crate::utils::check_min_amount("Swap output", amount_out, min_amount_out)?;
+
+    // Transfer the output from the pool to the user
+    let pool_account_info = ctx.accounts.pool.to_account_info();
+    let seeds = ctx.accounts.pool.signer_seeds()?;
+    let signer = &[&seeds[..]];
+    let (user_token_out, mint_out, vault_out) = ctx
+        .accounts
+        .token_accounts(token_out_idx)
+        .ok_or(ErrorCode::InvalidTokenMint)?;
+    let token_mint_out = mint_out.key();
+    let cpi_accounts_out = TransferChecked {
+        from: vault_out.to_account_info(),
+        mint: mint_out.to_account_info(),
+        to: user_token_out.to_account_info(),
+        authority: pool_account_info,
+    };
+    let cpi_ctx_out = CpiContext::new_with_signer(cpi_program, cpi_accounts_out, signer);
+    token_interface::transfer_checked(cpi_ctx_out, amount_out, mint_out.decimals)?;
+
+    let pool = &mut ctx.accounts.pool;
+    apply_basket_swap(
+        pool,
+        &ctx.accounts.amm_config,
+        ctx.accounts.user.key(),
+        &amounts_received[..num_tokens],
+        token_out_idx,
+        &quote,
+    )?;
+    crate::utils::touch_pool(pool)?;
+
+    emit!(BasketSwapExecuted {
+        pool: pool.key(),
+        user: ctx.accounts.user.key(),
+        token_mint_out,
+        amounts_in: amounts_received[..num_tokens].to_vec(),
+        amount_out,
+        fee_amounts: quote.fee_amounts[..num_tokens].to_vec(),
+        virtual_price: pool.virtual_price,
+    });
+    crate::instructions::pool_snapshot::refresh_if_present(
+        ctx.accounts.pool_snapshot.as_mut(),
+        pool,
+    )
+}
+
+/// Result of pricing a basket swap against a pool
+pub struct BasketSwapQuote {
+    /// Output amount after fees
+    pub amount_out: u64,
+
+    /// Dynamic fee applied to every input, in parts per 1000
+    pub fee: u64,
+
+    /// Fee charged on each input, in that token's units
+    pub fee_amounts: TokenAmounts,
+}
+
+/// Price selling `amounts_in` (one per pool token) for the token at `token_out_idx`
+///
+/// Each input pays the pool's dynamic fee, at least `min_fee` parts per 1000 before
+/// `fee_discount_bps` is waived; the size limits apply to the basket as a whole.
+pub fn quote_basket_swap(
+    pool: &Pool,
+    amounts_in: &[u64],
+    token_out_idx: usize,
+    fee_discount_bps: u64,
+    min_fee: u64,
+) -> Result<BasketSwapQuote> {
+    require!(
+        amounts_in.get(token_out_idx) == Some(&0),
+        ErrorCode::InvalidSwap
+    );
+    let trades_frozen_token = amounts_in.iter().enumerate().any(|(i, &amount)| {
+        (amount > 0 || i == token_out_idx) && pool.token_status[i] == TokenStatus::Frozen
+    });
+    require!(!trades_frozen_token, ErrorCode::TokenFrozen);
+
+    let total_in = amounts_in
+        .iter()
+        .try_fold(0u64, |total, &amount| total.checked_add(amount))
+        .ok_or(ErrorCode::MathOverflow)?;
+    require!(
+        total_in > 0 && total_in >= pool.min_swap_amount,
+        ErrorCode::MinSwapAmount
+    );
+
+    let target_weights = pool.target_weights_at(Clock::get()?.unix_timestamp);
+    let mut fee = pool.dynamic_fee(&target_weights).max(min_fee);
+
+    // Charge the fee on every input up front, then solve once for the output
+    let price_at = |fee: u64| {
+        let mut fee_amounts = [0u64; MAX_POOL_TOKENS];
+        let mut net_amounts = [0u64; MAX_POOL_TOKENS];
+        for (i, &amount) in amounts_in.iter().enumerate() {
+            fee_amounts[i] = apply_fee_discount(
+                crate::state::math::calculate_fee_amount(amount, fee),
+                fee_discount_bps,
+            );
+            net_amounts[i] = amount - fee_amounts[i];
+        }
+        let amount_out = crate::state::math::calculate_basket_output(
+            &net_amounts[..amounts_in.len()],
+            &pool.reserves,
+            token_out_idx,
+            pool.amplification,
+        )
+        .ok_or(ErrorCode::InvalidSwap)?;
+        Ok::<_, ErrorCode>((fee_amounts, amount_out))
+    };
+    let (mut fee_amounts, mut amount_out) = price_at(fee)?;
+
+    // Under a smoothed fee, a basket away from the targets pays at least the live fee
+    if pool.ema_lambda > 0 {
+        let mut reserves_after = pool.reserves.clone();
+        for (reserve, &amount) in reserves_after.iter_mut().zip(amounts_in) {
+            *reserve += amount;
+        }
+        reserves_after[token_out_idx] = reserves_after[token_out_idx].saturating_sub(amount_out);
+        let trade_fee = pool
+            .trade_fee(&target_weights, &reserves_after)
+            .max(min_fee);
+        if trade_fee > fee {
+            fee = trade_fee;
+            (fee_amounts, amount_out) = price_at(fee)?;
+        }
+    }
+
+    // Dust inputs that round to nothing would only move tokens one way
+    require!(amount_out > 0, ErrorCode::MinSwapAmount);
+
+    // The cap is a share of the output reserve, so only the output is held to it
+    let max_trade = (pool.reserves[token_out_idx] as u128 * pool.max_trade_bps as u128
+        / crate::state::math::BPS_DENOMINATOR as u128) as u64;
+    require!(amount_out <= max_trade, ErrorCode::MaxTradeSizeExceeded);
+
+    Ok(BasketSwapQuote {
+        amount_out,
+        fee,
+        fee_amounts,
+    })
+}
+
+/// Apply a priced basket swap to the pool reserves and lifetime statistics
+pub fn apply_basket_swap(
+    pool: &mut Account<Pool>,
+    amm_config: &AmmConfig,
+    trader: Pubkey,
+    amounts_in: &[u64],
+    token_out_idx: usize,
+    quote: &BasketSwapQuote,
+) -> Result<()> {
+    pool.require_not_paused()?;
+
+    let reserves_before = pool.reserves.clone();
+    pool.update_weights_ema(Clock::get()?.slot);
+    for (i, &amount_in) in amounts_in.iter().enumerate() {
+        if amount_in == 0 {
+            continue;
+        }
+
+        // The protocol's cut of each fee stays in the vault but leaves the reserves
+        let fee_amount = quote.fee_amounts[i];
+        let protocol_fee = pool.protocol_fee(amm_config, fee_amount);
+        let amount_in_to_reserves = amount_in - protocol_fee;
+        pool.protocol_fees_owed[i] += protocol_fee;
+        pool.reserves[i] += amount_in_to_reserves;
+
+        pool.lifetime_volume_per_token[i] =
+            pool.lifetime_volume_per_token[i].saturating_add(amount_in as u128);
+        pool.lifetime_fees_per_token[i] =
+            pool.lifetime_fees_per_token[i].saturating_add((fee_amount - protocol_fee) as u128);
+    }
+    pool.reserves[token_out_idx] = pool.reserves[token_out_idx].saturating_sub(quote.amount_out);
+
+    // The basket is priced on the full invariant, so check it there
+    let new_d = crate::state::math::preserved_invariant(
+        &reserves_before,
+        &pool.reserves,
+        pool.amplification,
+    )
+    .ok_or(ErrorCode::InvariantViolated)?;
+    for (i, &amount_in) in amounts_in.iter().enumerate() {
+        if amount_in > 0 {
+            pool.check_price_band(&reserves_before, i, token_out_idx)?;
+        }
+    }
+
+    // The invariant guard already solved D for the new reserves
+    let old_d = pool.last_d;
+    pool.last_d = new_d;
+    pool.d_slot = Clock::get()?.slot;
+    pool.scale_virtual_price(old_d);
+
+    pool.swap_count = pool.swap_count.saturating_add(1);
+    let now = Clock::get()?.unix_timestamp;
+    pool.refresh_routing_hint(now);
+    refresh_and_emit_token_status(pool, now);
+    emit_large_flow(pool, trader, FlowDirection::Swap, &reserves_before);
+
+    Ok(())
+}
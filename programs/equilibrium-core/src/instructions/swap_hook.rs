@@ -0,0 +1,157 @@
+use crate::errors::ErrorCode;
+use crate::state::*;
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{
+    instruction::{AccountMeta, Instruction},
+    program::invoke,
+};
+
+/// Instruction discriminator of the hook callback, Anchor's sighash of `global:on_swap`
+pub const SWAP_HOOK_DISCRIMINATOR: [u8; 8] = [201, 131, 123, 189, 167, 226, 124, 81];
+
+/// Swap result passed to the hook program after the swap settles
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct SwapHookPayload {
+    pub pool: Pubkey,
+    pub user: Pubkey,
+    /// Token account that received the output
+    pub recipient: Pubkey,
+    pub token_mint_in: Pubkey,
+    pub token_mint_out: Pubkey,
+    pub amount_in: u64,
+    pub amount_out: u64,
+    pub fee_amount: u64,
+}
+
+#[derive(Accounts)]
+pub struct ApproveSwapHook<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        has_one = authority @ ErrorCode::Unauthorized,
+    )]
+    pub amm_config: Account<'info, AmmConfig>,
+
+    /// CHECK: Only its address is recorded; must be a deployed program
+    #[account(executable)]
+    pub hook_program: UncheckedAccount<'info>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = SwapHookApproval::space(),
+        seeds = [&b"swap-hook"[..], amm_config.key().as_ref(), hook_program.key().as_ref()],
+        bump
+    )]
+    pub swap_hook_approval: Account<'info, SwapHookApproval>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn approve_swap_hook(ctx: Context<ApproveSwapHook>) -> Result<()> {
+    let approval = &mut ctx.accounts.swap_hook_approval;
+    approval.bump = ctx.bumps.swap_hook_approval;
+    approval.hook_program = ctx.accounts.hook_program.key();
+    approval.amm_config = ctx.accounts.amm_config.key();
+    approval.approved_by = ctx.accounts.authority.key();
+    approval.approved_at = Clock::get()?.unix_timestamp;
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct RevokeSwapHook<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        has_one = authority @ ErrorCode::Unauthorized,
+    )]
+    pub amm_config: Account<'info, AmmConfig>,
+
+    /// Swaps naming the hook fail once the approval is closed
+    #[account(
+        mut,
+        seeds = [
+            &b"swap-hook"[..],
+            amm_config.key().as_ref(),
+            swap_hook_approval.hook_program.as_ref(),
+        ],
+        bump = swap_hook_approval.bump,
+        close = authority,
+    )]
+    pub swap_hook_approval: Account<'info, SwapHookApproval>,
+}
+
+pub fn revoke_swap_hook(_ctx: Context<RevokeSwapHook>) -> Result<()> {
+    Ok(())
+}
+
+/// Call the hook described by a swap's `remaining_accounts`
+///
+/// The accounts are `[swap_hook_approval, hook_program, ..hook_accounts]`; the
+/// hook accounts are forwarded with the signer and writable flags the caller
+/// gave them. The approval must come from the swapped pool's config, which must
+/// also still whitelist the hook. The pool never signs the callback.
+pub fn invoke_swap_hook<'info>(
+    remaining_accounts: &[AccountInfo<'info>],
+    amm_config: &Account<AmmConfig>,
+    payload: &SwapHookPayload,
+) -> Result<()> {
+    let [approval_info, hook_program, hook_accounts @ ..] = remaining_accounts else {
+        return err!(ErrorCode::InvalidSwapHook);
+    };
+    require_keys_eq!(*approval_info.owner, crate::ID, ErrorCode::InvalidSwapHook);
+    let approval = SwapHookApproval::try_deserialize(&mut &approval_info.data.borrow()[..])
+        .map_err(|_| error!(ErrorCode::InvalidSwapHook))?;
+    require_keys_eq!(
+        approval.hook_program,
+        hook_program.key(),
+        ErrorCode::InvalidSwapHook
+    );
+    require_keys_eq!(
+        approval.amm_config,
+        amm_config.key(),
+        ErrorCode::InvalidSwapHook
+    );
+    require!(hook_program.executable, ErrorCode::InvalidSwapHook);
+    require!(
+        amm_config.is_whitelisted(&hook_program.key(), CpiTargetKind::SwapHook),
+        ErrorCode::ProgramNotWhitelisted
+    );
+
+    let mut data = SWAP_HOOK_DISCRIMINATOR.to_vec();
+    payload.serialize(&mut data)?;
+    let instruction = Instruction {
+        program_id: hook_program.key(),
+        accounts: hook_accounts
+            .iter()
+            .map(|account| AccountMeta {
+                pubkey: account.key(),
+                is_signer: account.is_signer,
+                is_writable: account.is_writable,
+            })
+            .collect(),
+        data,
+    };
+    let mut account_infos = hook_accounts.to_vec();
+    account_infos.push(hook_program.clone());
+    invoke(&instruction, &account_infos)?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::solana_program::hash::hash;
+
+    #[test]
+    fn discriminator_matches_anchor_sighash() {
+        assert_eq!(
+            SWAP_HOOK_DISCRIMINATOR,
+            hash(b"global:on_swap").to_bytes()[..8]
+        );
+    }
+}
@@ -0,0 +1,216 @@
+use crate::errors::ErrorCode;
+use crate::instructions::swap::{apply_swap, quote_swap_with_min_fee, stale_pool_min_fee};
+use crate::state::*;
+use anchor_lang::prelude::*;
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::token::{self, Mint, Token, TokenAccount, Transfer};
+
+#[derive(Accounts)]
+#[instruction(amount_in: u64, min_amount_out: u64)]
+pub struct SwapPartnerToPartner<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    /// Config both pools belong to, holding the protocol fee share
+    pub amm_config: Account<'info, AmmConfig>,
+
+    /// Growth Pool the input partner token is sold into
+    #[account(
+        mut,
+        has_one = amm_config,
+        constraint = pool_in.pool_type == PoolType::Growth @ ErrorCode::InvalidPoolType,
+        constraint = pool_in.token_mints[PARTNER_INDEX] == partner_mint_in.key() @ ErrorCode::InvalidTokenMint,
+        constraint = pool_in.token_mints[USD_STAR_INDEX] == usd_star_mint.key() @ ErrorCode::InvalidTokenMint,
+    )]
+    pub pool_in: Box<Account<'info, Pool>>,
+
+    /// Growth Pool the output partner token is bought from
+    #[account(
+        mut,
+        has_one = amm_config,
+        constraint = pool_out.key() != pool_in.key() @ ErrorCode::InvalidSwap,
+        constraint = pool_out.pool_type == PoolType::Growth @ ErrorCode::InvalidPoolType,
+        constraint = pool_out.token_mints[PARTNER_INDEX] == partner_mint_out.key() @ ErrorCode::InvalidTokenMint,
+        constraint = pool_out.token_mints[USD_STAR_INDEX] == usd_star_mint.key() @ ErrorCode::InvalidTokenMint,
+    )]
+    pub pool_out: Box<Account<'info, Pool>>,
+
+    pub partner_mint_in: Account<'info, Mint>,
+    pub usd_star_mint: Account<'info, Mint>,
+    pub partner_mint_out: Account<'info, Mint>,
+
+    /// User's token accounts
+    #[account(
+        mut,
+        token::authority = user,
+        token::mint = partner_mint_in,
+    )]
+    pub user_token_in: Account<'info, TokenAccount>,
+
+    /// User's associated account of the output token, created on the first route into
+    /// it. The USD* leg moves vault to vault, so the route leaves no temporary account
+    #[account(
+        init_if_needed,
+        payer = user,
+        associated_token::mint = partner_mint_out,
+        associated_token::authority = user,
+    )]
+    pub user_token_out: Account<'info, TokenAccount>,
+
+    /// Input pool's token accounts
+    #[account(
+        mut,
+        token::authority = pool_in,
+        token::mint = partner_mint_in,
+    )]
+    pub pool_in_partner_token: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        token::authority = pool_in,
+        token::mint = usd_star_mint,
+    )]
+    pub pool_in_usd_star: Account<'info, TokenAccount>,
+
+    /// Output pool's token accounts
+    #[account(
+        mut,
+        token::authority = pool_out,
+        token::mint = usd_star_mint,
+    )]
+    pub pool_out_usd_star: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        token::authority = pool_out,
+        token::mint = partner_mint_out,
+    )]
+    pub pool_out_partner_token: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(
+    ctx: Context<SwapPartnerToPartner>,
+    amount_in: u64,
+    min_amount_out: u64,
+    deadline: i64,
+) -> Result<()> {
+    crate::utils::check_deadline(deadline)?;
+    let accounts = &ctx.accounts;
+    crate::utils::check_pool_vault(
+        &accounts.pool_in,
+        PARTNER_INDEX,
+        &accounts.pool_in_partner_token.key(),
+    )?;
+    crate::utils::check_pool_vault(
+        &accounts.pool_in,
+        USD_STAR_INDEX,
+        &accounts.pool_in_usd_star.key(),
+    )?;
+    crate::utils::check_pool_vault(
+        &accounts.pool_out,
+        USD_STAR_INDEX,
+        &accounts.pool_out_usd_star.key(),
+    )?;
+    crate::utils::check_pool_vault(
+        &accounts.pool_out,
+        PARTNER_INDEX,
+        &accounts.pool_out_partner_token.key(),
+    )?;
+
+    // First hop: partner token in -> USD*
+    let slot = Clock::get()?.slot;
+    let pool_in = &mut ctx.accounts.pool_in;
+    pool_in.update_weights_ema(slot);
+    let min_fee = stale_pool_min_fee(pool_in, &ctx.accounts.amm_config)?;
+    let quote_in = quote_swap_with_min_fee(
+        pool_in,
+        PARTNER_INDEX,
+        USD_STAR_INDEX,
+        amount_in,
+        0,
+        min_fee,
+    )?;
+    pool_in.require_permissionless()?;
+    let user = ctx.accounts.user.key();
+    apply_swap(
+        pool_in,
+        &ctx.accounts.amm_config,
+        user,
+        PARTNER_INDEX,
+        USD_STAR_INDEX,
+        amount_in,
+        &quote_in,
+    )?;
+    crate::utils::touch_pool(pool_in)?;
+
+    // Second hop: USD* -> partner token out
+    let usd_star_amount = quote_in.amount_out;
+    let pool_out = &mut ctx.accounts.pool_out;
+    pool_out.update_weights_ema(slot);
+    let min_fee = stale_pool_min_fee(pool_out, &ctx.accounts.amm_config)?;
+    let quote_out = quote_swap_with_min_fee(
+        pool_out,
+        USD_STAR_INDEX,
+        PARTNER_INDEX,
+        usd_star_amount,
+        0,
+        min_fee,
+    )?;
+    pool_out.require_permissionless()?;
+    apply_swap(
+        pool_out,
+        &ctx.accounts.amm_config,
+        user,
+        USD_STAR_INDEX,
+        PARTNER_INDEX,
+        usd_star_amount,
+        &quote_out,
+    )?;
+    crate::utils::touch_pool(pool_out)?;
+
+    // Single slippage bound over the whole route
+    let amount_out = quote_out.amount_out;
+    crate::utils::check_min_amount("Route output", amount_out, min_amount_out)?;
+
+    // Transfer partner token from user to the input pool
+    let cpi_accounts = Transfer {
+        from: ctx.accounts.user_token_in.to_account_info(),
+        to: ctx.accounts.pool_in_partner_token.to_account_info(),
+        authority: ctx.accounts.user.to_account_info(),
+    };
+    let cpi_program = ctx.accounts.token_program.to_account_info();
+    let cpi_ctx = CpiContext::new(cpi_program.clone(), cpi_accounts);
+    token::transfer(cpi_ctx, amount_in)?;
+
+    // Move USD* directly between the pool vaults
+    let pool_in = &ctx.accounts.pool_in;
+    let pool_in_seeds = pool_in.signer_seeds()?;
+    let pool_in_signer = &[&pool_in_seeds[..]];
+
+    let cpi_accounts = Transfer {
+        from: ctx.accounts.pool_in_usd_star.to_account_info(),
+        to: ctx.accounts.pool_out_usd_star.to_account_info(),
+        authority: pool_in.to_account_info(),
+    };
+    let cpi_ctx = CpiContext::new_with_signer(cpi_program.clone(), cpi_accounts, pool_in_signer);
+    token::transfer(cpi_ctx, usd_star_amount)?;
+
+    // Pay the output partner token to the user
+    let pool_out = &ctx.accounts.pool_out;
+    let pool_out_seeds = pool_out.signer_seeds()?;
+    let pool_out_signer = &[&pool_out_seeds[..]];
+
+    let cpi_accounts = Transfer {
+        from: ctx.accounts.pool_out_partner_token.to_account_info(),
+        to: ctx.accounts.user_token_out.to_account_info(),
+        authority: pool_out.to_account_info(),
+    };
+    let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, pool_out_signer);
+    token::transfer(cpi_ctx, amount_out)?;
+
+    Ok(())
+}
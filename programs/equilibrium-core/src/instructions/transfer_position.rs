@@ -0,0 +1,160 @@
+use crate::errors::ErrorCode;
+use crate::events::{PositionChangeReason, PositionChanged};
+use crate::state::*;
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Mint, Token, TokenAccount, Transfer};
+use anchor_spl::token_interface;
+
+#[derive(Accounts)]
+pub struct TransferPosition<'info> {
+    /// Position owner; pays for the recipient's position if it does not exist yet
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    /// CHECK: Any wallet may receive LP shares
+    #[account(
+        constraint = recipient.key() != owner.key() @ ErrorCode::InvalidPositionTransfer,
+    )]
+    pub recipient: UncheckedAccount<'info>,
+
+    pub pool: Account<'info, Pool>,
+
+    /// LP token mint
+    #[account(constraint = lp_mint.key() == pool.lp_mint)]
+    pub lp_mint: Account<'info, Mint>,
+
+    /// Owner's LP token account
+    #[account(
+        mut,
+        token::authority = owner,
+        token::mint = lp_mint,
+    )]
+    pub owner_lp_token: Account<'info, TokenAccount>,
+
+    /// Recipient's LP token account
+    #[account(
+        mut,
+        token::authority = recipient,
+        token::mint = lp_mint,
+    )]
+    pub recipient_lp_token: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [&b"user-position"[..], owner.key().as_ref(), pool.key().as_ref()],
+        bump = owner_position.bump,
+        constraint = owner_position.is_active @ ErrorCode::PositionNotActive,
+    )]
+    pub owner_position: Account<'info, UserPosition>,
+
+    #[account(
+        init_if_needed,
+        payer = owner,
+        space = UserPosition::space(),
+        seeds = [&b"user-position"[..], recipient.key().as_ref(), pool.key().as_ref()],
+        bump
+    )]
+    pub recipient_position: Account<'info, UserPosition>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+
+    /// Recipient's token account of the pool's credential mint; required by permissioned pools
+    pub credential: Option<InterfaceAccount<'info, token_interface::TokenAccount>>,
+}
+
+/// Move `amount` of the owner's position, and the LP tokens backing it, to the
+/// recipient's position in the same pool
+///
+/// A new or emptied recipient position takes the owner's price bounds; an active one
+/// keeps its own. Fees accrue through the pool's virtual price, so the shares carry
+/// their earnings with them. Delegates and bootstrap allowances stay with each position.
+/// A permissioned pool only lets shares move to another credential holder.
+pub fn handler(ctx: Context<TransferPosition>, amount: u64) -> Result<()> {
+    ctx.accounts.pool.require_transferable_lp()?;
+    crate::utils::check_credential(
+        &ctx.accounts.pool,
+        &ctx.accounts.recipient.key(),
+        ctx.accounts.credential.as_deref(),
+    )?;
+    require!(
+        amount > 0 && amount <= ctx.accounts.owner_position.lp_amount,
+        ErrorCode::InvalidPositionTransfer
+    );
+
+    let cpi_accounts = Transfer {
+        from: ctx.accounts.owner_lp_token.to_account_info(),
+        to: ctx.accounts.recipient_lp_token.to_account_info(),
+        authority: ctx.accounts.owner.to_account_info(),
+    };
+    let cpi_program = ctx.accounts.token_program.to_account_info();
+    token::transfer(CpiContext::new(cpi_program, cpi_accounts), amount)?;
+
+    let now = Clock::get()?.unix_timestamp;
+    let pool_key = ctx.accounts.pool.key();
+    let virtual_price = ctx.accounts.pool.virtual_price;
+
+    let owner_position = &mut ctx.accounts.owner_position;
+    let owner_lp_before = owner_position.lp_amount;
+    owner_position.lp_amount -= amount;
+    owner_position.last_update = now;
+    owner_position.last_update_slot = Clock::get()?.slot;
+    if owner_position.lp_amount == 0 {
+        owner_position.is_active = false;
+    }
+    let (min_price, max_price) = (owner_position.min_price, owner_position.max_price);
+
+    emit!(PositionChanged {
+        position: owner_position.key(),
+        owner: owner_position.owner,
+        pool: pool_key,
+        reason: PositionChangeReason::Transfer,
+        lp_amount_before: owner_lp_before,
+        lp_amount_after: owner_position.lp_amount,
+        min_price_before: min_price,
+        max_price_before: max_price,
+        min_price_after: min_price,
+        max_price_after: max_price,
+        timestamp: now,
+        virtual_price,
+    });
+
+    // Initialize the recipient's position if it's new
+    let recipient_position = &mut ctx.accounts.recipient_position;
+    if recipient_position.owner == Pubkey::default() {
+        recipient_position.bump = ctx.bumps.recipient_position;
+        recipient_position.owner = ctx.accounts.recipient.key();
+        recipient_position.pool = pool_key;
+        recipient_position.created_at = now;
+        recipient_position.delegate = None;
+    }
+
+    let recipient_lp_before = recipient_position.lp_amount;
+    let min_price_before = recipient_position.min_price;
+    let max_price_before = recipient_position.max_price;
+    if !recipient_position.is_active {
+        recipient_position.min_price = min_price;
+        recipient_position.max_price = max_price;
+    }
+    recipient_position.lp_amount += amount;
+    recipient_position.is_active = true;
+    recipient_position.last_update = now;
+    recipient_position.last_update_slot = Clock::get()?.slot;
+
+    emit!(PositionChanged {
+        position: recipient_position.key(),
+        owner: recipient_position.owner,
+        pool: pool_key,
+        reason: PositionChangeReason::Transfer,
+        lp_amount_before: recipient_lp_before,
+        lp_amount_after: recipient_position.lp_amount,
+        min_price_before,
+        max_price_before,
+        min_price_after: recipient_position.min_price,
+        max_price_after: recipient_position.max_price,
+        timestamp: now,
+        virtual_price,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,203 @@
+use crate::errors::ErrorCode;
+use crate::events::{ProtocolFeesCollected, TreasurySwept};
+use crate::state::*;
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Mint, Token, TokenAccount, Transfer};
+
+#[derive(Accounts)]
+pub struct InitTreasury<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        has_one = authority @ ErrorCode::Unauthorized,
+    )]
+    pub amm_config: Account<'info, AmmConfig>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = Treasury::space(),
+        seeds = [&b"treasury"[..], amm_config.key().as_ref()],
+        bump
+    )]
+    pub treasury: Account<'info, Treasury>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn init_treasury(ctx: Context<InitTreasury>) -> Result<()> {
+    let treasury = &mut ctx.accounts.treasury;
+    treasury.bump = ctx.bumps.treasury;
+    treasury.amm_config = ctx.accounts.amm_config.key();
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct InitTreasuryVault<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(
+        seeds = [&b"treasury"[..], treasury.amm_config.as_ref()],
+        bump = treasury.bump,
+    )]
+    pub treasury: Account<'info, Treasury>,
+
+    pub token_mint: Account<'info, Mint>,
+
+    /// Treasury's vault for `token_mint`
+    #[account(
+        init,
+        payer = payer,
+        seeds = [&b"treasury-vault"[..], treasury.key().as_ref(), token_mint.key().as_ref()],
+        bump,
+        token::mint = token_mint,
+        token::authority = treasury,
+    )]
+    pub treasury_vault: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+pub fn init_treasury_vault(_ctx: Context<InitTreasuryVault>) -> Result<()> {
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct CollectProtocolFees<'info> {
+    /// Permissionless crank: the amount is read from the pool's accrued fees
+    #[account(mut)]
+    pub pool: Account<'info, Pool>,
+
+    pub token_mint: Account<'info, Mint>,
+
+    #[account(
+        mut,
+        token::authority = pool,
+        token::mint = token_mint,
+    )]
+    pub pool_token: Account<'info, TokenAccount>,
+
+    /// Treasury of the pool's config
+    #[account(
+        seeds = [&b"treasury"[..], pool.amm_config.as_ref()],
+        bump = treasury.bump,
+    )]
+    pub treasury: Account<'info, Treasury>,
+
+    #[account(
+        mut,
+        seeds = [&b"treasury-vault"[..], treasury.key().as_ref(), token_mint.key().as_ref()],
+        bump,
+    )]
+    pub treasury_vault: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+/// Move the protocol fees a pool accrued in `token_mint` to the treasury vault
+pub fn collect_protocol_fees(ctx: Context<CollectProtocolFees>) -> Result<()> {
+    let pool = &ctx.accounts.pool;
+    let token_idx = pool
+        .token_index(&ctx.accounts.token_mint.key())
+        .ok_or(ErrorCode::InvalidTokenMint)?;
+    crate::utils::check_pool_vault(pool, token_idx, &ctx.accounts.pool_token.key())?;
+    let amount = pool.protocol_fees_owed[token_idx];
+    require!(amount > 0, ErrorCode::NothingToCollect);
+
+    let seeds = pool.signer_seeds()?;
+    let signer = &[&seeds[..]];
+    let cpi_accounts = Transfer {
+        from: ctx.accounts.pool_token.to_account_info(),
+        to: ctx.accounts.treasury_vault.to_account_info(),
+        authority: pool.to_account_info(),
+    };
+    let cpi_program = ctx.accounts.token_program.to_account_info();
+    token::transfer(
+        CpiContext::new_with_signer(cpi_program, cpi_accounts, signer),
+        amount,
+    )?;
+
+    let pool = &mut ctx.accounts.pool;
+    pool.protocol_fees_owed[token_idx] = 0;
+
+    emit!(ProtocolFeesCollected {
+        pool: pool.key(),
+        token_mint: ctx.accounts.token_mint.key(),
+        amount,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct SweepTreasury<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        has_one = authority @ ErrorCode::Unauthorized,
+    )]
+    pub amm_config: Account<'info, AmmConfig>,
+
+    #[account(
+        has_one = amm_config,
+        seeds = [&b"treasury"[..], amm_config.key().as_ref()],
+        bump = treasury.bump,
+    )]
+    pub treasury: Account<'info, Treasury>,
+
+    pub token_mint: Account<'info, Mint>,
+
+    #[account(
+        mut,
+        seeds = [&b"treasury-vault"[..], treasury.key().as_ref(), token_mint.key().as_ref()],
+        bump,
+    )]
+    pub treasury_vault: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        token::mint = token_mint,
+    )]
+    pub destination: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+/// Withdraw `amount` of protocol cash from the treasury vault of `token_mint`
+pub fn sweep_treasury(ctx: Context<SweepTreasury>, amount: u64) -> Result<()> {
+    require!(
+        amount > 0 && amount <= ctx.accounts.treasury_vault.amount,
+        ErrorCode::InsufficientLiquidity
+    );
+
+    let amm_config_key = ctx.accounts.amm_config.key();
+    let seeds = &[
+        &b"treasury"[..],
+        amm_config_key.as_ref(),
+        &[ctx.accounts.treasury.bump],
+    ];
+    let signer = &[&seeds[..]];
+    let cpi_accounts = Transfer {
+        from: ctx.accounts.treasury_vault.to_account_info(),
+        to: ctx.accounts.destination.to_account_info(),
+        authority: ctx.accounts.treasury.to_account_info(),
+    };
+    let cpi_program = ctx.accounts.token_program.to_account_info();
+    token::transfer(
+        CpiContext::new_with_signer(cpi_program, cpi_accounts, signer),
+        amount,
+    )?;
+
+    emit!(TreasurySwept {
+        token_mint: ctx.accounts.token_mint.key(),
+        destination: ctx.accounts.destination.key(),
+        amount,
+    });
+
+    Ok(())
+}
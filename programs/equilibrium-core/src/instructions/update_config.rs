@@ -0,0 +1,207 @@
+use crate::errors::ErrorCode;
+use crate::events::ConfigUpdated;
+use crate::state::*;
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct UpdateConfig<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        has_one = authority @ ErrorCode::Unauthorized,
+    )]
+    pub amm_config: Account<'info, AmmConfig>,
+}
+
+#[derive(Accounts)]
+pub struct AcceptAuthority<'info> {
+    pub pending_authority: Signer<'info>,
+
+    #[account(
+        mut,
+        constraint = amm_config.pending_authority == pending_authority.key() @ ErrorCode::Unauthorized,
+    )]
+    pub amm_config: Account<'info, AmmConfig>,
+}
+
+pub fn set_rebalance_config(
+    ctx: Context<UpdateConfig>,
+    keeper: Pubkey,
+    rebalance_program: Pubkey,
+    max_rebalance_bps: u64,
+) -> Result<()> {
+    require!(
+        max_rebalance_bps <= crate::state::math::BPS_DENOMINATOR,
+        ErrorCode::InvalidInstructionData
+    );
+
+    let amm_config = &mut ctx.accounts.amm_config;
+    amm_config.keeper = keeper;
+    amm_config.rebalance_program = rebalance_program;
+    amm_config.max_rebalance_bps = max_rebalance_bps;
+
+    Ok(())
+}
+
+pub fn set_partner_approval_required(
+    ctx: Context<UpdateConfig>,
+    require_partner_approval: bool,
+) -> Result<()> {
+    ctx.accounts.amm_config.require_partner_approval = require_partner_approval;
+
+    Ok(())
+}
+
+pub fn set_pool_creation_config(
+    ctx: Context<UpdateConfig>,
+    permissionless_pool_creation: bool,
+    pool_creation_fee_lamports: u64,
+    pool_creation_fee_usd_star: u64,
+) -> Result<()> {
+    let amm_config = &mut ctx.accounts.amm_config;
+    amm_config.permissionless_pool_creation = permissionless_pool_creation;
+    amm_config.pool_creation_fee_lamports = pool_creation_fee_lamports;
+    amm_config.pool_creation_fee_usd_star = pool_creation_fee_usd_star;
+
+    Ok(())
+}
+
+/// Choose the governance token locked in vote escrows; existing locks keep
+/// their vault and stay redeemable
+pub fn set_ve_mint(ctx: Context<UpdateConfig>, ve_mint: Pubkey) -> Result<()> {
+    ctx.accounts.amm_config.ve_mint = ve_mint;
+
+    Ok(())
+}
+
+/// Tip permissionless cranks `crank_tip_amount` out of protocol fees, at most
+/// `max_crank_tips_per_slot` times per pool and slot
+pub fn set_crank_tip(
+    ctx: Context<UpdateConfig>,
+    crank_tip_amount: u64,
+    max_crank_tips_per_slot: u64,
+) -> Result<()> {
+    let amm_config = &mut ctx.accounts.amm_config;
+    amm_config.crank_tip_amount = crank_tip_amount;
+    amm_config.max_crank_tips_per_slot = max_crank_tips_per_slot;
+
+    Ok(())
+}
+
+/// Treat a banded Growth pool's oracle price as stale once it is more than
+/// `max_oracle_age_slots` old; swaps then pay the pool's max fee, or are
+/// rejected outright with `reject_stale_swaps`
+pub fn set_stale_pool_protection(
+    ctx: Context<UpdateConfig>,
+    max_oracle_age_slots: u64,
+    reject_stale_swaps: bool,
+) -> Result<()> {
+    let amm_config = &mut ctx.accounts.amm_config;
+    amm_config.max_oracle_age_slots = max_oracle_age_slots;
+    amm_config.reject_stale_swaps = reject_stale_swaps;
+
+    Ok(())
+}
+
+/// Route `protocol_fee_share_bps` of every swap fee to the treasury in each of the
+/// config's pools that does not set its own share
+pub fn set_config_protocol_fee_share(
+    ctx: Context<UpdateConfig>,
+    protocol_fee_share_bps: u64,
+) -> Result<()> {
+    require!(
+        protocol_fee_share_bps <= crate::state::math::MAX_PROTOCOL_FEE_SHARE_BPS,
+        ErrorCode::InvalidFee
+    );
+
+    let amm_config = &mut ctx.accounts.amm_config;
+    let old_protocol_fee_share_bps = amm_config.protocol_fee_share_bps;
+    amm_config.protocol_fee_share_bps = protocol_fee_share_bps;
+
+    emit!(ConfigUpdated {
+        amm_config: amm_config.key(),
+        authority: ctx.accounts.authority.key(),
+        old_protocol_fee_share_bps,
+        new_protocol_fee_share_bps: protocol_fee_share_bps,
+    });
+
+    Ok(())
+}
+
+/// Let `guardian` pause this config's pools
+pub fn add_guardian(ctx: Context<UpdateConfig>, guardian: Pubkey) -> Result<()> {
+    let guardians = &mut ctx.accounts.amm_config.guardians;
+    require!(
+        guardians.len() < MAX_GUARDIANS && !guardians.contains(&guardian),
+        ErrorCode::InvalidGuardian
+    );
+    guardians.push(guardian);
+
+    Ok(())
+}
+
+pub fn remove_guardian(ctx: Context<UpdateConfig>, guardian: Pubkey) -> Result<()> {
+    let guardians = &mut ctx.accounts.amm_config.guardians;
+    let index = guardians
+        .iter()
+        .position(|key| *key == guardian)
+        .ok_or(ErrorCode::InvalidGuardian)?;
+    guardians.remove(index);
+
+    Ok(())
+}
+
+/// Register `program_id` as a CPI target of `kind` for this config's pools
+pub fn add_whitelisted_program(
+    ctx: Context<UpdateConfig>,
+    program_id: Pubkey,
+    kind: CpiTargetKind,
+) -> Result<()> {
+    let amm_config = &mut ctx.accounts.amm_config;
+    require!(
+        amm_config.whitelisted_programs.len() < MAX_WHITELISTED_PROGRAMS
+            && !amm_config.is_whitelisted(&program_id, kind),
+        ErrorCode::InvalidWhitelistedProgram
+    );
+    amm_config
+        .whitelisted_programs
+        .push(WhitelistedProgram { program_id, kind });
+
+    Ok(())
+}
+
+/// Stop the config's pools from calling `program_id` as `kind`, whatever
+/// approval or venue setting still names it
+pub fn remove_whitelisted_program(
+    ctx: Context<UpdateConfig>,
+    program_id: Pubkey,
+    kind: CpiTargetKind,
+) -> Result<()> {
+    let whitelisted_programs = &mut ctx.accounts.amm_config.whitelisted_programs;
+    let index = whitelisted_programs
+        .iter()
+        .position(|entry| *entry == WhitelistedProgram { program_id, kind })
+        .ok_or(ErrorCode::InvalidWhitelistedProgram)?;
+    whitelisted_programs.remove(index);
+
+    Ok(())
+}
+
+/// Start handing the config over to `new_authority`, which may be a PDA
+/// (e.g. a multisig vault) that signs through CPI
+pub fn propose_authority(ctx: Context<UpdateConfig>, new_authority: Pubkey) -> Result<()> {
+    ctx.accounts.amm_config.pending_authority = new_authority;
+
+    Ok(())
+}
+
+/// Complete the handoff; requiring the new authority's signature proves it can
+/// actually execute admin instructions before the old one is dropped
+pub fn accept_authority(ctx: Context<AcceptAuthority>) -> Result<()> {
+    let amm_config = &mut ctx.accounts.amm_config;
+    amm_config.authority = ctx.accounts.pending_authority.key();
+    amm_config.pending_authority = Pubkey::default();
+
+    Ok(())
+}
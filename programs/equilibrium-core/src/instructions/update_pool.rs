@@ -0,0 +1,462 @@
+use crate::errors::ErrorCode;
+use crate::events::{PoolPauseChanged, TokenStatusChanged};
+use crate::state::*;
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct UpdatePool<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        has_one = authority @ ErrorCode::Unauthorized,
+    )]
+    pub amm_config: Account<'info, AmmConfig>,
+
+    #[account(
+        mut,
+        has_one = amm_config,
+    )]
+    pub pool: Account<'info, Pool>,
+}
+
+/// Most changes a single `update_pool_params` batch may carry
+pub const MAX_POOL_PARAM_CHANGES: usize = 16;
+
+/// One pool parameter change, as applied by `update_pool_params` and the
+/// individual setters
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PoolParamChange {
+    ExitFee {
+        exit_fee_bps: u64,
+    },
+    /// Custom fee curve replacing the fee tier preset
+    FeeParams {
+        fee_params: FeeParams,
+    },
+    /// Share of every swap fee routed to the treasury, overriding the config's
+    ProtocolFeeShare {
+        protocol_fee_share_bps: u64,
+    },
+    /// Drop the pool's own protocol fee share and follow the config's again
+    InheritProtocolFeeShare,
+    /// Per-position deposit cap during the bootstrap phase
+    BootstrapLimits {
+        bootstrap_end_ts: i64,
+        max_deposit_per_wallet: u64,
+    },
+    StagedWithdrawConfig {
+        threshold_bps: u64,
+        delay: i64,
+    },
+    /// Growth pools only
+    OracleBand {
+        band_bps: u64,
+        surcharge_bps: u64,
+    },
+    MinSwapAmount {
+        min_swap_amount: u64,
+    },
+    MaxTradeBps {
+        max_trade_bps: u64,
+    },
+    Relayer {
+        relayer: Pubkey,
+    },
+    Paused {
+        paused: bool,
+    },
+    TokenFrozen {
+        token_mint: Pubkey,
+        frozen: bool,
+    },
+    /// Share of a reserve one swap or withdrawal must drain to emit `LargeFlowEvent`
+    LargeFlowThreshold {
+        threshold_bps: u64,
+    },
+    /// Seed Pools only: hard band around the 1:1 peg swaps may not push a pair past
+    PriceBand {
+        band_bps: u64,
+    },
+    /// Growth pools only: clear swaps in batch auctions over `window_slots`; 0 turns it off
+    BatchWindow {
+        window_slots: u64,
+    },
+    /// Limit deposits and swaps to holders of `credential_mint`
+    Credential {
+        requires_credential: bool,
+        credential_mint: Pubkey,
+    },
+    /// Price the dynamic fee on an EMA of the weights, each swap moving it by
+    /// `ema_lambda` basis points of the way; 0 prices it on the live weights
+    WeightsEma {
+        ema_lambda: u64,
+    },
+}
+
+impl PoolParamChange {
+    /// Check the change against `pool` without applying it
+    pub fn validate(&self, pool: &Pool, now: i64) -> Result<()> {
+        match *self {
+            Self::ExitFee { exit_fee_bps } => require!(
+                exit_fee_bps <= crate::state::math::MAX_EXIT_FEE_BPS,
+                ErrorCode::InvalidFee
+            ),
+            Self::FeeParams { fee_params } => require!(
+                fee_params.base_fee <= fee_params.max_fee
+                    && fee_params.max_fee <= crate::state::math::MAX_SWAP_FEE,
+                ErrorCode::InvalidFee
+            ),
+            Self::ProtocolFeeShare {
+                protocol_fee_share_bps,
+            } => require!(
+                protocol_fee_share_bps <= crate::state::math::MAX_PROTOCOL_FEE_SHARE_BPS,
+                ErrorCode::InvalidFee
+            ),
+            Self::BootstrapLimits {
+                bootstrap_end_ts,
+                max_deposit_per_wallet,
+            } => require!(
+                max_deposit_per_wallet > 0 || bootstrap_end_ts <= now,
+                ErrorCode::InvalidBootstrapConfig
+            ),
+            Self::StagedWithdrawConfig {
+                threshold_bps,
+                delay,
+            } => require!(
+                threshold_bps <= BPS_DENOMINATOR && delay >= 0,
+                ErrorCode::InvalidInputLength
+            ),
+            Self::OracleBand {
+                band_bps,
+                surcharge_bps,
+            } => {
+                require!(
+                    pool.pool_type == PoolType::Growth,
+                    ErrorCode::InvalidPoolType
+                );
+                require!(
+                    band_bps <= BPS_DENOMINATOR && surcharge_bps <= MAX_ORACLE_SURCHARGE_BPS,
+                    ErrorCode::InvalidFee
+                );
+            }
+            Self::MaxTradeBps { max_trade_bps } => require!(
+                max_trade_bps > 0 && max_trade_bps <= crate::state::math::BPS_DENOMINATOR,
+                ErrorCode::InvalidInstructionData
+            ),
+            Self::TokenFrozen { token_mint, .. } => {
+                pool.token_index(&token_mint)
+                    .ok_or(ErrorCode::InvalidTokenMint)?;
+            }
+            Self::LargeFlowThreshold { threshold_bps } => require!(
+                threshold_bps <= BPS_DENOMINATOR,
+                ErrorCode::InvalidInstructionData
+            ),
+            Self::PriceBand { band_bps } => {
+                require!(pool.pool_type == PoolType::Seed, ErrorCode::InvalidPoolType);
+                require!(
+                    band_bps <= BPS_DENOMINATOR,
+                    ErrorCode::InvalidInstructionData
+                );
+            }
+            Self::BatchWindow { window_slots } => {
+                require!(
+                    pool.pool_type == PoolType::Growth,
+                    ErrorCode::InvalidPoolType
+                );
+                require!(
+                    window_slots <= MAX_BATCH_WINDOW_SLOTS,
+                    ErrorCode::InvalidInstructionData
+                );
+            }
+            Self::Credential {
+                requires_credential,
+                credential_mint,
+            } => require!(
+                !requires_credential || credential_mint != Pubkey::default(),
+                ErrorCode::InvalidTokenMint
+            ),
+            Self::WeightsEma { ema_lambda } => require!(
+                ema_lambda <= BPS_DENOMINATOR,
+                ErrorCode::InvalidInstructionData
+            ),
+            Self::MinSwapAmount { .. }
+            | Self::Relayer { .. }
+            | Self::Paused { .. }
+            | Self::InheritProtocolFeeShare => {}
+        }
+
+        Ok(())
+    }
+
+    /// Whether both changes set the same parameter
+    fn conflicts_with(&self, other: &Self) -> bool {
+        match (self, other) {
+            (
+                Self::TokenFrozen { token_mint, .. },
+                Self::TokenFrozen {
+                    token_mint: other, ..
+                },
+            ) => token_mint == other,
+            (
+                Self::ProtocolFeeShare { .. } | Self::InheritProtocolFeeShare,
+                Self::ProtocolFeeShare { .. } | Self::InheritProtocolFeeShare,
+            ) => true,
+            _ => std::mem::discriminant(self) == std::mem::discriminant(other),
+        }
+    }
+
+    /// Write a validated change to `pool`, emitting its events
+    fn apply(&self, pool: &mut Account<Pool>, signer: Pubkey, now: i64) {
+        match *self {
+            Self::ExitFee { exit_fee_bps } => pool.exit_fee_bps = exit_fee_bps,
+            Self::FeeParams { fee_params } => pool.fee_params = fee_params,
+            Self::ProtocolFeeShare {
+                protocol_fee_share_bps,
+            } => {
+                pool.protocol_fee_share_bps = protocol_fee_share_bps;
+                pool.inherits_protocol_fee_share = false;
+            }
+            Self::InheritProtocolFeeShare => pool.inherits_protocol_fee_share = true,
+            Self::BootstrapLimits {
+                bootstrap_end_ts,
+                max_deposit_per_wallet,
+            } => {
+                pool.bootstrap_end_ts = bootstrap_end_ts;
+                pool.max_deposit_per_wallet = max_deposit_per_wallet;
+            }
+            Self::StagedWithdrawConfig {
+                threshold_bps,
+                delay,
+            } => {
+                pool.staged_withdraw_threshold_bps = threshold_bps;
+                pool.staged_withdraw_delay = delay;
+            }
+            Self::OracleBand {
+                band_bps,
+                surcharge_bps,
+            } => {
+                pool.oracle_band_bps = band_bps;
+                pool.oracle_surcharge_bps = surcharge_bps;
+            }
+            Self::MinSwapAmount { min_swap_amount } => pool.min_swap_amount = min_swap_amount,
+            Self::MaxTradeBps { max_trade_bps } => pool.max_trade_bps = max_trade_bps,
+            Self::Relayer { relayer } => pool.relayer = relayer,
+            Self::Paused { paused } => {
+                if pool.paused != paused {
+                    pool.paused = paused;
+                    emit!(PoolPauseChanged {
+                        pool: pool.key(),
+                        paused,
+                        signer,
+                    });
+                }
+            }
+            Self::TokenFrozen { token_mint, frozen } => {
+                set_token_status(pool, token_mint, frozen, now)
+            }
+            Self::LargeFlowThreshold { threshold_bps } => {
+                pool.large_flow_threshold_bps = threshold_bps
+            }
+            Self::PriceBand { band_bps } => pool.price_band_bps = band_bps,
+            Self::BatchWindow { window_slots } => pool.batch_window_slots = window_slots,
+            Self::Credential {
+                requires_credential,
+                credential_mint,
+            } => {
+                pool.requires_credential = requires_credential;
+                pool.credential_mint = credential_mint;
+            }
+            Self::WeightsEma { ema_lambda } => {
+                pool.ema_lambda = ema_lambda;
+                pool.reset_weights_ema();
+            }
+        }
+    }
+}
+
+/// Apply a batch of parameter changes atomically
+///
+/// Every change is validated against the current pool before any is written, and
+/// a batch may set each parameter (each token's freeze) only once, so a
+/// governance proposal either lands in full or not at all.
+pub fn update_pool_params(ctx: Context<UpdatePool>, changes: Vec<PoolParamChange>) -> Result<()> {
+    apply_pool_params(ctx.accounts, &changes)
+}
+
+fn apply_pool_params(accounts: &mut UpdatePool, changes: &[PoolParamChange]) -> Result<()> {
+    require!(
+        !changes.is_empty() && changes.len() <= MAX_POOL_PARAM_CHANGES,
+        ErrorCode::InvalidPoolParams
+    );
+    let now = Clock::get()?.unix_timestamp;
+    for (i, change) in changes.iter().enumerate() {
+        if changes[..i]
+            .iter()
+            .any(|earlier| earlier.conflicts_with(change))
+        {
+            msg!("Change {}: sets a parameter already set in this batch", i);
+            return err!(ErrorCode::InvalidPoolParams);
+        }
+        change.validate(&accounts.pool, now).inspect_err(|_| {
+            msg!("Change {}: {:?} is invalid", i, change);
+        })?;
+    }
+
+    let signer = accounts.authority.key();
+    for change in changes {
+        change.apply(&mut accounts.pool, signer, now);
+    }
+
+    Ok(())
+}
+
+pub fn set_exit_fee(ctx: Context<UpdatePool>, exit_fee_bps: u64) -> Result<()> {
+    apply_pool_params(ctx.accounts, &[PoolParamChange::ExitFee { exit_fee_bps }])
+}
+
+/// Override the pool's fee tier preset with a custom curve
+pub fn set_fee_params(ctx: Context<UpdatePool>, fee_params: FeeParams) -> Result<()> {
+    apply_pool_params(ctx.accounts, &[PoolParamChange::FeeParams { fee_params }])
+}
+
+/// Cap what each position may deposit until `bootstrap_end_ts`; a past timestamp lifts the cap
+pub fn set_bootstrap_limits(
+    ctx: Context<UpdatePool>,
+    bootstrap_end_ts: i64,
+    max_deposit_per_wallet: u64,
+) -> Result<()> {
+    apply_pool_params(
+        ctx.accounts,
+        &[PoolParamChange::BootstrapLimits {
+            bootstrap_end_ts,
+            max_deposit_per_wallet,
+        }],
+    )
+}
+
+/// Route `protocol_fee_share_bps` of every swap fee to the treasury instead of LPs,
+/// overriding the config-wide share for this pool
+pub fn set_protocol_fee_share(ctx: Context<UpdatePool>, protocol_fee_share_bps: u64) -> Result<()> {
+    apply_pool_params(
+        ctx.accounts,
+        &[PoolParamChange::ProtocolFeeShare {
+            protocol_fee_share_bps,
+        }],
+    )
+}
+
+/// Let withdrawals of at least `threshold_bps` of LP supply be staged `delay` seconds ahead
+pub fn set_staged_withdraw_config(
+    ctx: Context<UpdatePool>,
+    threshold_bps: u64,
+    delay: i64,
+) -> Result<()> {
+    apply_pool_params(
+        ctx.accounts,
+        &[PoolParamChange::StagedWithdrawConfig {
+            threshold_bps,
+            delay,
+        }],
+    )
+}
+
+/// Configure a Growth pool's oracle band; 0 `band_bps` turns it off
+pub fn set_oracle_band(ctx: Context<UpdatePool>, band_bps: u64, surcharge_bps: u64) -> Result<()> {
+    apply_pool_params(
+        ctx.accounts,
+        &[PoolParamChange::OracleBand {
+            band_bps,
+            surcharge_bps,
+        }],
+    )
+}
+
+/// Halt (or resume) swaps into and out of `token_mint`
+pub fn set_token_frozen(ctx: Context<UpdatePool>, token_mint: Pubkey, frozen: bool) -> Result<()> {
+    apply_pool_params(
+        ctx.accounts,
+        &[PoolParamChange::TokenFrozen { token_mint, frozen }],
+    )
+}
+
+fn set_token_status(pool: &mut Account<Pool>, token_mint: Pubkey, frozen: bool, now: i64) {
+    let Some(token_idx) = pool.token_index(&token_mint) else {
+        return;
+    };
+
+    let previous_status = pool.token_status[token_idx];
+    pool.token_status[token_idx] = if frozen {
+        TokenStatus::Frozen
+    } else {
+        TokenStatus::Normal
+    };
+    if previous_status != pool.token_status[token_idx] {
+        let total: u64 = pool.reserves.iter().sum();
+        emit!(TokenStatusChanged {
+            pool: pool.key(),
+            token_mint,
+            previous_status,
+            status: pool.token_status[token_idx],
+            weight_bps: crate::state::math::calculate_weight(pool.reserves[token_idx], total),
+        });
+    }
+
+    // An unfrozen token picks up whatever stress its weight implies right away
+    if !frozen {
+        crate::instructions::swap::refresh_and_emit_token_status(pool, now);
+    }
+}
+
+/// Pause or unpause the pool; guardians can only pause, with `pause_pool`
+pub fn set_pool_paused(ctx: Context<UpdatePool>, paused: bool) -> Result<()> {
+    apply_pool_params(ctx.accounts, &[PoolParamChange::Paused { paused }])
+}
+
+/// Trust `relayer` to submit user-signed swaps; `Pubkey::default()` turns relaying off
+pub fn set_relayer(ctx: Context<UpdatePool>, relayer: Pubkey) -> Result<()> {
+    apply_pool_params(ctx.accounts, &[PoolParamChange::Relayer { relayer }])
+}
+
+pub fn set_min_swap_amount(ctx: Context<UpdatePool>, min_swap_amount: u64) -> Result<()> {
+    apply_pool_params(
+        ctx.accounts,
+        &[PoolParamChange::MinSwapAmount { min_swap_amount }],
+    )
+}
+
+pub fn set_max_trade_bps(ctx: Context<UpdatePool>, max_trade_bps: u64) -> Result<()> {
+    apply_pool_params(
+        ctx.accounts,
+        &[PoolParamChange::MaxTradeBps { max_trade_bps }],
+    )
+}
+
+pub fn set_lbp_schedule(
+    ctx: Context<UpdatePool>,
+    start_weights: [u64; 2],
+    end_weights: [u64; 2],
+    start_ts: i64,
+    end_ts: i64,
+) -> Result<()> {
+    let pool = &mut ctx.accounts.pool;
+    require!(
+        pool.pool_type == PoolType::Growth,
+        ErrorCode::InvalidPoolType
+    );
+    require!(
+        start_weights.iter().sum::<u64>() == 10000 && end_weights.iter().sum::<u64>() == 10000,
+        ErrorCode::InvalidWeights
+    );
+    require!(
+        start_ts >= Clock::get()?.unix_timestamp && end_ts > start_ts,
+        ErrorCode::InvalidLbpSchedule
+    );
+
+    // The schedule ends on the pool's regular target weights
+    pool.lbp_start_weights = start_weights.to_vec();
+    pool.lbp_start_ts = start_ts;
+    pool.lbp_end_ts = end_ts;
+    pool.target_weights = end_weights.to_vec();
+
+    Ok(())
+}
@@ -0,0 +1,305 @@
+use crate::errors::ErrorCode;
+use crate::instructions::swap::quote_swap;
+use crate::state::*;
+use anchor_lang::prelude::*;
+use anchor_spl::token::Mint;
+
+// Read-only instructions meant to be simulated; results are returned as
+// Borsh-encoded return data and no account is written
+
+#[derive(Accounts)]
+pub struct ViewPool<'info> {
+    /// Config holding the protocol fee share
+    pub amm_config: Account<'info, AmmConfig>,
+
+    #[account(
+        has_one = amm_config,
+    )]
+    pub pool: Account<'info, Pool>,
+
+    #[account(
+        constraint = lp_mint.key() == pool.lp_mint @ ErrorCode::InvalidTokenMint,
+    )]
+    pub lp_mint: Account<'info, Mint>,
+}
+
+/// Snapshot of a pool returned by `get_pool_state`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct PoolStateView {
+    pub pool_type: PoolType,
+    pub token_mints: Vec<Pubkey>,
+    pub reserves: Vec<u64>,
+    pub current_weights: Vec<u64>,
+    pub target_weights: Vec<u64>,
+    pub amplification: u64,
+    pub lp_supply: u64,
+    pub invariant: u64,
+    /// Dynamic swap fee right now, in parts per 1000
+    pub swap_fee: u64,
+    pub swap_count: u64,
+    /// Virtual price stored at the last state change (1e18 fixed point)
+    pub virtual_price: u128,
+    /// Depeg status of each token as of the last swap
+    pub token_status: Vec<TokenStatus>,
+}
+
+pub fn get_pool_state(ctx: Context<ViewPool>) -> Result<PoolStateView> {
+    let pool = &ctx.accounts.pool.with_weights_ema_at(Clock::get()?.slot);
+    let current_weights = pool.current_weights();
+    let target_weights = pool.target_weights_at(Clock::get()?.unix_timestamp);
+    let swap_fee = pool.dynamic_fee(&target_weights);
+
+    Ok(PoolStateView {
+        pool_type: pool.pool_type,
+        token_mints: pool.token_mints.clone(),
+        reserves: pool.reserves.clone(),
+        current_weights,
+        target_weights: target_weights.into_owned(),
+        amplification: pool.amplification,
+        lp_supply: pool.lp_supply(ctx.accounts.lp_mint.supply),
+        invariant: pool.last_d,
+        swap_fee,
+        swap_count: pool.swap_count,
+        virtual_price: pool.virtual_price,
+        token_status: pool.token_status.clone(),
+    })
+}
+
+pub fn get_virtual_price(ctx: Context<ViewPool>) -> Result<u128> {
+    let pool = &ctx.accounts.pool;
+    let d = crate::state::math::calculate_invariant(&pool.reserves, pool.amplification)
+        .ok_or(ErrorCode::InsufficientLiquidity)?;
+
+    Ok(
+        crate::state::math::calculate_virtual_price(d, pool.lp_supply(ctx.accounts.lp_mint.supply))
+            .ok_or(ErrorCode::InsufficientLiquidity)?,
+    )
+}
+
+/// Quote `amount_in` of `token_mint_in` against every other pool token.
+/// The input token's own slot and unquotable pairs are zero.
+pub fn get_amounts_out(
+    ctx: Context<ViewPool>,
+    token_mint_in: Pubkey,
+    amount_in: u64,
+) -> Result<Vec<u64>> {
+    let pool = &ctx.accounts.pool.with_weights_ema_at(Clock::get()?.slot);
+    let token_in_idx = pool
+        .token_index(&token_mint_in)
+        .ok_or(ErrorCode::InvalidTokenMint)?;
+
+    Ok((0..pool.token_mints.len())
+        .map(|token_out_idx| {
+            if token_out_idx == token_in_idx {
+                return 0;
+            }
+            quote_swap(pool, token_in_idx, token_out_idx, amount_in)
+                .map(|quote| quote.amount_out)
+                .unwrap_or(0)
+        })
+        .collect())
+}
+
+/// Fee and resulting balance of a hypothetical swap, returned by `quote_fee`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct FeeQuoteView {
+    /// Fee the swap would pay, including any oracle surcharge, in basis points of `amount_in`
+    pub fee_bps: u64,
+    pub fee_amount: u64,
+    pub amount_out: u64,
+    /// Pool weights before and after the swap, and the targets they are measured against
+    pub weights_before: Vec<u64>,
+    pub weights_after: Vec<u64>,
+    pub target_weights: Vec<u64>,
+    /// Whether the swap leaves the pool closer to its target weights
+    pub improves_balance: bool,
+    /// Dynamic fee the next swap would pay a slot after this one settles, in basis points
+    pub fee_after_bps: u64,
+}
+
+/// Simulate the dynamic fee schedule for selling `amount_in` of token `token_in_index`
+/// for token `token_out_index`, so a UI can show the fee and whether the trade
+/// restores or worsens balance before the user signs
+pub fn quote_fee(
+    ctx: Context<ViewPool>,
+    token_in_index: u8,
+    token_out_index: u8,
+    amount_in: u64,
+) -> Result<FeeQuoteView> {
+    let pool = &ctx.accounts.pool.with_weights_ema_at(Clock::get()?.slot);
+    let (token_in_idx, token_out_idx) = (token_in_index as usize, token_out_index as usize);
+    require!(
+        token_in_idx < pool.reserves.len() && token_out_idx < pool.reserves.len(),
+        ErrorCode::InvalidInstructionData
+    );
+    let quote = quote_swap(pool, token_in_idx, token_out_idx, amount_in)?;
+
+    // Reserves as `apply_swap` would book them, less the protocol's cut of the fee
+    let protocol_fee = pool.protocol_fee(&ctx.accounts.amm_config, quote.fee_amount);
+    let mut reserves = pool.reserves.clone();
+    reserves[token_in_idx] += amount_in - protocol_fee;
+    reserves[token_out_idx] = reserves[token_out_idx].saturating_sub(quote.amount_out);
+
+    let target_weights = pool.target_weights_at(Clock::get()?.unix_timestamp);
+    let weights_before = pool.current_weights();
+    let weights_after = crate::state::math::calculate_weights(&reserves);
+    let improves_balance =
+        crate::state::math::total_weight_deviation(&weights_after, &target_weights)
+            < crate::state::math::total_weight_deviation(&weights_before, &target_weights);
+    let fee_after = crate::state::math::calculate_dynamic_fee(
+        &pool.fee_weights_after(&weights_after, 1),
+        &target_weights,
+        &pool.fee_params.into(),
+    );
+
+    Ok(FeeQuoteView {
+        fee_bps: crate::state::rounding::mul_div(
+            quote.fee_amount,
+            BPS_DENOMINATOR,
+            amount_in,
+            crate::state::rounding::Rounding::Up,
+        )
+        .ok_or(ErrorCode::MathOverflow)?,
+        fee_amount: quote.fee_amount,
+        amount_out: quote.amount_out,
+        weights_before,
+        weights_after,
+        target_weights: target_weights.into_owned(),
+        improves_balance,
+        fee_after_bps: fee_after * (BPS_DENOMINATOR / crate::state::math::FEE_DENOMINATOR),
+    })
+}
+
+/// Input a pair absorbs at set price impacts, returned by `get_depth`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct DepthView {
+    /// Fee-free input of `token_in_index` that moves its price by 0.1%, 0.5% and 1%
+    pub depth_10_bps: u64,
+    pub depth_50_bps: u64,
+    pub depth_100_bps: u64,
+}
+
+/// Measure how much of token `token_in_index` the pool absorbs, sold for token
+/// `token_out_index`, before the marginal price moves 10, 50 and 100 bps
+pub fn get_depth(
+    ctx: Context<ViewPool>,
+    token_in_index: u8,
+    token_out_index: u8,
+) -> Result<DepthView> {
+    let pool = &ctx.accounts.pool;
+    let (token_in_idx, token_out_idx) = (token_in_index as usize, token_out_index as usize);
+    require!(
+        token_in_idx != token_out_idx
+            && token_in_idx < pool.reserves.len()
+            && token_out_idx < pool.reserves.len(),
+        ErrorCode::InvalidInstructionData
+    );
+
+    // Input first and output second; a Seed Pool's third reserve rides along unchanged
+    let mut reserves = vec![pool.reserves[token_in_idx], pool.reserves[token_out_idx]];
+    reserves.extend(
+        pool.reserves
+            .iter()
+            .enumerate()
+            .filter(|&(index, _)| index != token_in_idx && index != token_out_idx)
+            .map(|(_, &reserve)| reserve),
+    );
+    let depth = |impact_bps: u64| {
+        crate::state::math::depth_at_impact(&reserves, pool.amplification, impact_bps)
+            .ok_or(ErrorCode::InsufficientLiquidity)
+    };
+
+    Ok(DepthView {
+        depth_10_bps: depth(10)?,
+        depth_50_bps: depth(50)?,
+        depth_100_bps: depth(100)?,
+    })
+}
+
+#[derive(Accounts)]
+pub struct ViewPoolStats<'info> {
+    pub pool: Account<'info, Pool>,
+
+    #[account(
+        has_one = pool,
+        seeds = [&b"pool-stats"[..], pool.key().as_ref()],
+        bump = pool_stats.bump,
+    )]
+    pub pool_stats: Account<'info, PoolStats>,
+}
+
+/// Rolling fee totals and realized APY returned by `get_pool_apy`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct PoolApyView {
+    /// Reserves summed at peg
+    pub tvl: u64,
+    pub fees_24h: u128,
+    pub fees_7d: u128,
+    /// Seconds actually covered by each window, shorter while history builds up
+    pub window_24h: i64,
+    pub window_7d: i64,
+    /// Fee yield annualized from each window, in basis points
+    pub apy_24h_bps: u64,
+    pub apy_7d_bps: u64,
+}
+
+pub fn get_pool_apy(ctx: Context<ViewPoolStats>) -> Result<PoolApyView> {
+    const DAY: i64 = 86_400;
+    let pool = &ctx.accounts.pool;
+    let now = Clock::get()?.unix_timestamp;
+    let tvl = pool.total_reserves();
+    let cumulative_fees = pool.lifetime_fees();
+    let (fees_24h, window_24h) = ctx.accounts.pool_stats.fees_over(DAY, now, cumulative_fees);
+    let (fees_7d, window_7d) = ctx
+        .accounts
+        .pool_stats
+        .fees_over(7 * DAY, now, cumulative_fees);
+
+    Ok(PoolApyView {
+        tvl,
+        fees_24h,
+        fees_7d,
+        window_24h,
+        window_7d,
+        apy_24h_bps: crate::state::math::calculate_annualized_yield_bps(fees_24h, tvl, window_24h),
+        apy_7d_bps: crate::state::math::calculate_annualized_yield_bps(fees_7d, tvl, window_7d),
+    })
+}
+
+#[derive(Accounts)]
+pub struct ViewLockedPosition<'info> {
+    pub locked_position: Account<'info, LockedPosition>,
+}
+
+/// Schedule and progress of a liquidity lock returned by `get_lock_expiry`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct LockExpiryView {
+    pub owner: Pubkey,
+    pub pool: Pubkey,
+    /// LP locked in total
+    pub amount: u64,
+    /// LP vested so far, including what was already released
+    pub vested: u64,
+    pub released: u64,
+    pub cliff_ts: i64,
+    /// Timestamp at which the whole lock is released
+    pub end_ts: i64,
+    /// Seconds until `end_ts`, zero once the lock has expired
+    pub seconds_remaining: i64,
+}
+
+pub fn get_lock_expiry(ctx: Context<ViewLockedPosition>) -> Result<LockExpiryView> {
+    let locked_position = &ctx.accounts.locked_position;
+    let now = Clock::get()?.unix_timestamp;
+
+    Ok(LockExpiryView {
+        owner: locked_position.owner,
+        pool: locked_position.pool,
+        amount: locked_position.amount,
+        vested: locked_position.vested_amount(now),
+        released: locked_position.released,
+        cliff_ts: locked_position.cliff_ts,
+        end_ts: locked_position.end_ts,
+        seconds_remaining: locked_position.end_ts.saturating_sub(now).max(0),
+    })
+}
@@ -0,0 +1,221 @@
+use crate::errors::ErrorCode;
+use crate::state::*;
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, CloseAccount, Mint, Token, TokenAccount, Transfer};
+
+#[derive(Accounts)]
+pub struct LockVeTokens<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        constraint = amm_config.ve_mint != Pubkey::default() @ ErrorCode::VeLockDisabled,
+        constraint = amm_config.ve_mint == ve_mint.key() @ ErrorCode::InvalidTokenMint,
+    )]
+    pub amm_config: Account<'info, AmmConfig>,
+
+    /// Governance token configured on the AMM config
+    pub ve_mint: Account<'info, Mint>,
+
+    #[account(
+        init_if_needed,
+        payer = owner,
+        space = VoteEscrow::space(),
+        seeds = [&b"vote-escrow"[..], amm_config.key().as_ref(), owner.key().as_ref()],
+        bump
+    )]
+    pub vote_escrow: Account<'info, VoteEscrow>,
+
+    /// Vault holding the locked tokens, owned by the escrow PDA
+    #[account(
+        init_if_needed,
+        payer = owner,
+        seeds = [&b"vote-escrow-vault"[..], vote_escrow.key().as_ref()],
+        bump,
+        token::mint = ve_mint,
+        token::authority = vote_escrow,
+    )]
+    pub vault: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        token::authority = owner,
+        token::mint = ve_mint,
+    )]
+    pub owner_token: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+/// Lock `amount` more tokens and set the unlock time; a lock can only be
+/// topped up or extended, never shortened
+pub fn lock_ve_tokens(ctx: Context<LockVeTokens>, amount: u64, unlock_ts: i64) -> Result<()> {
+    let clock = Clock::get()?;
+    let now = clock.unix_timestamp;
+    let vote_escrow = &mut ctx.accounts.vote_escrow;
+    require!(
+        unlock_ts > now
+            && unlock_ts <= now.saturating_add(MAX_VE_LOCK_DURATION)
+            && unlock_ts >= vote_escrow.unlock_ts,
+        ErrorCode::InvalidLockDuration
+    );
+
+    if amount > 0 {
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.owner_token.to_account_info(),
+            to: ctx.accounts.vault.to_account_info(),
+            authority: ctx.accounts.owner.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        token::transfer(CpiContext::new(cpi_program, cpi_accounts), amount)?;
+    }
+
+    vote_escrow.bump = ctx.bumps.vote_escrow;
+    vote_escrow.owner = ctx.accounts.owner.key();
+    vote_escrow.amm_config = ctx.accounts.amm_config.key();
+    vote_escrow.mint = ctx.accounts.ve_mint.key();
+    vote_escrow.vault = ctx.accounts.vault.key();
+    vote_escrow.amount = vote_escrow
+        .amount
+        .checked_add(amount)
+        .ok_or(ErrorCode::MathOverflow)?;
+    vote_escrow.unlock_ts = unlock_ts;
+    vote_escrow.last_update_slot = clock.slot;
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct UnlockVeTokens<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [
+            &b"vote-escrow"[..],
+            vote_escrow.amm_config.as_ref(),
+            owner.key().as_ref(),
+        ],
+        bump = vote_escrow.bump,
+        has_one = owner @ ErrorCode::Unauthorized,
+        has_one = vault,
+        close = owner,
+    )]
+    pub vote_escrow: Account<'info, VoteEscrow>,
+
+    #[account(mut)]
+    pub vault: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        token::authority = owner,
+        token::mint = vault.mint,
+    )]
+    pub owner_token: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+/// Return the locked tokens once the lock has expired and close the escrow
+pub fn unlock_ve_tokens(ctx: Context<UnlockVeTokens>) -> Result<()> {
+    let vote_escrow = &ctx.accounts.vote_escrow;
+    require!(
+        Clock::get()?.unix_timestamp >= vote_escrow.unlock_ts,
+        ErrorCode::LockNotExpired
+    );
+
+    let seeds = &[
+        &b"vote-escrow"[..],
+        vote_escrow.amm_config.as_ref(),
+        vote_escrow.owner.as_ref(),
+        &[vote_escrow.bump],
+    ];
+    let signer = &[&seeds[..]];
+    let cpi_accounts = Transfer {
+        from: ctx.accounts.vault.to_account_info(),
+        to: ctx.accounts.owner_token.to_account_info(),
+        authority: vote_escrow.to_account_info(),
+    };
+    let cpi_program = ctx.accounts.token_program.to_account_info();
+    let cpi_ctx = CpiContext::new_with_signer(cpi_program.clone(), cpi_accounts, signer);
+    token::transfer(cpi_ctx, ctx.accounts.vault.amount)?;
+
+    // Close the vault, returning rent to the owner
+    let cpi_accounts = CloseAccount {
+        account: ctx.accounts.vault.to_account_info(),
+        destination: ctx.accounts.owner.to_account_info(),
+        authority: vote_escrow.to_account_info(),
+    };
+    let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
+    token::close_account(cpi_ctx)?;
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct AddFeeExemption<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        has_one = authority @ ErrorCode::Unauthorized,
+    )]
+    pub amm_config: Account<'info, AmmConfig>,
+
+    /// CHECK: Only its address is recorded
+    pub user: UncheckedAccount<'info>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = FeeExemption::space(),
+        seeds = [&b"fee-exemption"[..], amm_config.key().as_ref(), user.key().as_ref()],
+        bump
+    )]
+    pub fee_exemption: Account<'info, FeeExemption>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Exempt `user`'s withdrawals from the config's pools from exit fees, e.g. for
+/// protocol-owned vaults
+pub fn add_fee_exemption(ctx: Context<AddFeeExemption>) -> Result<()> {
+    let fee_exemption = &mut ctx.accounts.fee_exemption;
+    fee_exemption.bump = ctx.bumps.fee_exemption;
+    fee_exemption.user = ctx.accounts.user.key();
+    fee_exemption.amm_config = ctx.accounts.amm_config.key();
+    fee_exemption.added_by = ctx.accounts.authority.key();
+    fee_exemption.added_at = Clock::get()?.unix_timestamp;
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct RemoveFeeExemption<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        has_one = authority @ ErrorCode::Unauthorized,
+    )]
+    pub amm_config: Account<'info, AmmConfig>,
+
+    #[account(
+        mut,
+        seeds = [
+            &b"fee-exemption"[..],
+            amm_config.key().as_ref(),
+            fee_exemption.user.as_ref(),
+        ],
+        bump = fee_exemption.bump,
+        close = authority,
+    )]
+    pub fee_exemption: Account<'info, FeeExemption>,
+}
+
+pub fn remove_fee_exemption(_ctx: Context<RemoveFeeExemption>) -> Result<()> {
+    Ok(())
+}
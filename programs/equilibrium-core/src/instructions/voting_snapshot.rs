@@ -0,0 +1,111 @@
+use crate::errors::ErrorCode;
+use crate::state::*;
+use anchor_lang::prelude::*;
+use anchor_spl::token::TokenAccount;
+
+#[derive(Accounts)]
+#[instruction(snapshot_slot: u64)]
+pub struct CheckpointVotingPower<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    pub pool: Account<'info, Pool>,
+
+    #[account(address = pool.amm_config)]
+    pub amm_config: Account<'info, AmmConfig>,
+
+    #[account(
+        seeds = [&b"user-position"[..], owner.key().as_ref(), pool.key().as_ref()],
+        bump = user_position.bump,
+    )]
+    pub user_position: Account<'info, UserPosition>,
+
+    /// Owner's LP token account, capping the recorded LP at what the wallet
+    /// still holds; required unless the pool is soulbound
+    #[account(
+        token::mint = pool.lp_mint,
+        token::authority = owner,
+    )]
+    pub user_lp_token: Option<Account<'info, TokenAccount>>,
+
+    /// Owner's vote escrow, adding its lock weight when present
+    #[account(
+        seeds = [&b"vote-escrow"[..], amm_config.key().as_ref(), owner.key().as_ref()],
+        bump = vote_escrow.bump,
+    )]
+    pub vote_escrow: Option<Account<'info, VoteEscrow>>,
+
+    #[account(
+        init,
+        payer = owner,
+        space = VotingSnapshot::space(),
+        seeds = [
+            &b"voting-snapshot"[..],
+            owner.key().as_ref(),
+            pool.key().as_ref(),
+            &snapshot_slot.to_le_bytes(),
+        ],
+        bump
+    )]
+    pub voting_snapshot: Account<'info, VotingSnapshot>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Record the owner's LP and lock weight for a governance snapshot at `snapshot_slot`
+///
+/// Only callable once the snapshot slot has passed, and only while the position
+/// and vote escrow are unchanged since before it. The recorded LP was therefore
+/// held at the snapshot slot, and LP borrowed for a single transaction can never
+/// count. LP tokens can leave the wallet without touching the position, so the
+/// record is capped at the wallet's LP balance. The lock weight is read now, so
+/// it can only have decayed since.
+pub fn checkpoint_voting_power(
+    ctx: Context<CheckpointVotingPower>,
+    snapshot_slot: u64,
+) -> Result<()> {
+    let clock = Clock::get()?;
+    require!(
+        clock.slot >= snapshot_slot,
+        ErrorCode::SnapshotSlotNotReached
+    );
+    let user_position = &ctx.accounts.user_position;
+    require!(
+        user_position.last_update_slot < snapshot_slot,
+        ErrorCode::PositionChangedSinceSnapshot
+    );
+
+    let lp_amount = if ctx.accounts.pool.soulbound_lp {
+        user_position.lp_amount
+    } else {
+        let user_lp_token = ctx
+            .accounts
+            .user_lp_token
+            .as_ref()
+            .ok_or(ErrorCode::LpTokenAccountRequired)?;
+        user_position.lp_amount.min(user_lp_token.amount)
+    };
+
+    let lock_weight = match &ctx.accounts.vote_escrow {
+        Some(vote_escrow) => {
+            crate::utils::check_vote_escrow(vote_escrow, &ctx.accounts.amm_config)?;
+            require!(
+                vote_escrow.last_update_slot < snapshot_slot,
+                ErrorCode::VoteEscrowChangedSinceSnapshot
+            );
+            vote_escrow.voting_power(clock.unix_timestamp)
+        }
+        None => 0,
+    };
+
+    let voting_snapshot = &mut ctx.accounts.voting_snapshot;
+    voting_snapshot.bump = ctx.bumps.voting_snapshot;
+    voting_snapshot.owner = ctx.accounts.owner.key();
+    voting_snapshot.pool = ctx.accounts.pool.key();
+    voting_snapshot.snapshot_slot = snapshot_slot;
+    voting_snapshot.lp_amount = lp_amount;
+    voting_snapshot.lock_weight = lock_weight;
+    voting_snapshot.recorded_slot = clock.slot;
+
+    Ok(())
+}
@@ -1,25 +1,51 @@
 use crate::errors::ErrorCode;
+use crate::events::{FlowDirection, PositionChangeReason, PositionChanged};
 use crate::state::*;
 use anchor_lang::prelude::*;
 use anchor_spl::token::{self, Burn, Mint, Token, TokenAccount, Transfer};
+use equilibrium_math::pricing::WithdrawSplit;
+
+/// How a withdrawal is split across the pool's tokens
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum WithdrawMode {
+    /// Pro-rata share of every reserve
+    Proportional,
+    /// Same total value at the curve's prices, paid out of over-weighted tokens first
+    Balanced,
+}
+
+impl From<WithdrawMode> for WithdrawSplit {
+    fn from(mode: WithdrawMode) -> Self {
+        match mode {
+            WithdrawMode::Proportional => WithdrawSplit::Proportional,
+            WithdrawMode::Balanced => WithdrawSplit::Balanced,
+        }
+    }
+}
 
 #[derive(Accounts)]
-#[instruction(lp_amount: u64, min_amounts: Vec<u64>)]
 pub struct Withdraw<'info> {
+    /// CHECK: Position owner; must sign unless the position's delegate does
     #[account(mut)]
-    pub user: Signer<'info>,
+    pub user: UncheckedAccount<'info>,
+
+    /// Config of the pool, whose governance token vote escrows must be locked in
+    #[account(
+        address = pool.amm_config,
+    )]
+    pub amm_config: Account<'info, AmmConfig>,
 
     #[account(mut)]
     pub pool: Account<'info, Pool>,
 
-    // LP token mint
+    /// LP token mint
     #[account(
         mut,
         constraint = lp_mint.key() == pool.lp_mint
     )]
     pub lp_mint: Account<'info, Mint>,
 
-    // User's LP token account
+    /// User's LP token account
     #[account(
         mut,
         token::authority = user,
@@ -27,7 +53,7 @@ pub struct Withdraw<'info> {
     )]
     pub user_lp_token: Account<'info, TokenAccount>,
 
-    // Token accounts for receiving withdrawn assets
+    /// Token accounts for receiving withdrawn assets
     #[account(
         mut,
         token::authority = user,
@@ -49,12 +75,12 @@ pub struct Withdraw<'info> {
     )]
     pub user_token_c: Option<Account<'info, TokenAccount>>,
 
-    // Token mints - must match the order in pool.token_mints
+    /// Token mints - must match the order in pool.token_mints
     pub token_mint_a: Account<'info, Mint>,
     pub token_mint_b: Account<'info, Mint>,
     pub token_mint_c: Option<Account<'info, Mint>>,
 
-    // Pool token accounts
+    /// Pool token accounts
     #[account(
         mut,
         token::authority = pool,
@@ -76,7 +102,7 @@ pub struct Withdraw<'info> {
     )]
     pub pool_token_c: Option<Account<'info, TokenAccount>>,
 
-    // User position
+    /// User position
     #[account(
         mut,
         seeds = [&b"user-position"[..], user.key().as_ref(), pool.key().as_ref()],
@@ -88,197 +114,508 @@ pub struct Withdraw<'info> {
     pub user_position: Account<'info, UserPosition>,
 
     pub token_program: Program<'info, Token>,
-}
 
-pub fn handler(ctx: Context<Withdraw>, lp_amount: u64, min_amounts: Vec<u64>) -> Result<()> {
-    // Extract pool information first to avoid borrow conflicts
-    let pool_key = ctx.accounts.pool.key();
-    let pool_account_info = ctx.accounts.pool.to_account_info();
+    /// Optional third-party accounts to deliver proceeds to instead of `user_token_*`
+    #[account(
+        mut,
+        token::mint = token_mint_a,
+    )]
+    pub recipient_token_a: Option<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        token::mint = token_mint_b,
+    )]
+    pub recipient_token_b: Option<Account<'info, TokenAccount>>,
 
-    // Now use mutable borrow
-    let pool = &mut ctx.accounts.pool;
+    #[account(
+        mut,
+        token::mint = token_mint_c,
+    )]
+    pub recipient_token_c: Option<Account<'info, TokenAccount>>,
 
-    // Validate inputs based on pool type
-    match pool.pool_type {
-        PoolType::Seed => {
-            require!(min_amounts.len() == 3, ErrorCode::InvalidInputLength);
-        }
-        PoolType::Growth => {
-            require!(min_amounts.len() == 2, ErrorCode::InvalidInputLength);
-        }
+    /// Position delegate burning the owner's LP under their SPL approval
+    pub delegate: Option<Signer<'info>>,
+
+    /// Owner's vote escrow under the pool's config; its voting power discounts the exit fee
+    #[account(
+        seeds = [&b"vote-escrow"[..], pool.amm_config.as_ref(), user.key().as_ref()],
+        bump = vote_escrow.bump,
+    )]
+    pub vote_escrow: Option<Account<'info, VoteEscrow>>,
+
+    /// Owner's entry on the exit fee exemption list of the pool's config
+    #[account(
+        seeds = [&b"fee-exemption"[..], pool.amm_config.as_ref(), user.key().as_ref()],
+        bump = fee_exemption.bump,
+    )]
+    pub fee_exemption: Option<Account<'info, FeeExemption>>,
+
+    /// Pool's front-end snapshot, refreshed once the withdrawal settles
+    #[account(
+        mut,
+        has_one = pool,
+    )]
+    pub pool_snapshot: Option<Account<'info, PoolSnapshot>>,
+
+    /// Owner's portfolio, updated once the withdrawal settles
+    #[account(
+        mut,
+        seeds = [&b"user-portfolio"[..], user.key().as_ref()],
+        bump = user_portfolio.bump,
+    )]
+    pub user_portfolio: Option<Account<'info, UserPortfolio>>,
+}
+
+impl Withdraw<'_> {
+    /// The pool's exit fee for this owner, after any exemption or vote escrow discount
+    pub fn exit_fee_bps(&self) -> Result<u64> {
+        let fee_discount_bps = crate::utils::vote_escrow_discount_bps(
+            self.vote_escrow.as_deref(),
+            &self.amm_config,
+            Clock::get()?.unix_timestamp,
+        )?;
+        Ok(owner_exit_fee_bps(
+            &self.pool,
+            fee_discount_bps,
+            self.fee_exemption.is_some(),
+        ))
+    }
+}
+
+/// `pool`'s exit fee for an owner with the given vote escrow discount and exemption status
+pub fn owner_exit_fee_bps(pool: &Pool, fee_discount_bps: u64, fee_exempt: bool) -> u64 {
+    if fee_exempt {
+        return 0;
     }
+    apply_fee_discount(pool.exit_fee_bps, fee_discount_bps)
+}
+
+pub fn handler(
+    ctx: Context<Withdraw>,
+    lp_amount: u64,
+    min_amounts: [u64; MAX_POOL_TOKENS],
+    mode: WithdrawMode,
+    deadline: i64,
+) -> Result<()> {
+    crate::utils::check_deadline(deadline)?;
+    let exit_fee_bps = ctx.accounts.exit_fee_bps()?;
+    process_withdraw(ctx.accounts, lp_amount, &min_amounts, mode, exit_fee_bps)
+}
+
+/// Withdraw `bps` of the position, sized from its LP balance when the instruction
+/// executes rather than when it was quoted, so an autocompound or deposit landing
+/// first cannot leave a dust remainder or make the withdrawal fail
+pub fn withdraw_percent(
+    ctx: Context<Withdraw>,
+    bps: u64,
+    min_amounts: [u64; MAX_POOL_TOKENS],
+    mode: WithdrawMode,
+    deadline: i64,
+) -> Result<()> {
+    crate::utils::check_deadline(deadline)?;
+    require!(
+        bps > 0 && bps <= BPS_DENOMINATOR,
+        ErrorCode::InvalidInstructionData
+    );
+    let lp_amount = rounding::mul_div(
+        ctx.accounts.user_position.lp_amount,
+        bps,
+        BPS_DENOMINATOR,
+        rounding::Rounding::Down,
+    )
+    .ok_or(ErrorCode::MathOverflow)?;
+    require!(lp_amount > 0, ErrorCode::InsufficientLiquidity);
+
+    let exit_fee_bps = ctx.accounts.exit_fee_bps()?;
+    process_withdraw(ctx.accounts, lp_amount, &min_amounts, mode, exit_fee_bps)
+}
 
-    // Get data needed for calculations
-    let pool_type = pool.pool_type;
-    let pool_reserves = pool.reserves.clone();
-    let pool_amplification = pool.amplification;
-    let pool_bump = pool.bump;
-    let token_mints = pool.token_mints.clone();
-    let total_lp_supply = ctx.accounts.lp_mint.supply;
+/// Withdraw exactly `amounts`, burning the LP they cost including the imbalance fee
+pub fn withdraw_imbalanced(
+    ctx: Context<Withdraw>,
+    amounts: [u64; MAX_POOL_TOKENS],
+    max_lp_burn: u64,
+    deadline: i64,
+) -> Result<()> {
+    crate::utils::check_deadline(deadline)?;
+    let exit_fee_bps = ctx.accounts.exit_fee_bps()?;
+    let accounts = ctx.accounts;
+
+    // Read phase: price the withdrawal in LP
+    accounts.pool.update_weights_ema(Clock::get()?.slot);
+    let pool = &accounts.pool;
+    let amounts = pool.active_amounts(&amounts)?;
+    let total_lp_supply = pool.lp_supply(accounts.lp_mint.supply);
+    let (lp_amount, new_d) =
+        quote_imbalanced_withdrawal(pool, amounts, total_lp_supply, exit_fee_bps)?;
+    crate::utils::check_max_amount("LP burned", lp_amount, max_lp_burn)?;
+    require!(
+        accounts.user_position.lp_amount >= lp_amount,
+        ErrorCode::InsufficientLiquidity
+    );
+
+    settle_withdraw(accounts, lp_amount, amounts, total_lp_supply, new_d)
+}
+
+/// Burn `lp_amount` from the position and pay out the reserves, charging `exit_fee_bps`
+pub fn process_withdraw(
+    accounts: &mut Withdraw,
+    lp_amount: u64,
+    min_amounts: &TokenAmounts,
+    mode: WithdrawMode,
+    exit_fee_bps: u64,
+) -> Result<()> {
+    // Read phase: validate and size the withdrawal
+    accounts.pool.update_weights_ema(Clock::get()?.slot);
+    let pool = &accounts.pool;
+
+    // Only the pool's own token slots may carry a minimum
+    let min_amounts = pool.active_amounts(min_amounts)?;
+
+    let total_lp_supply = pool.lp_supply(accounts.lp_mint.supply);
 
     // Validate user has enough LP tokens
     require!(
-        ctx.accounts.user_position.lp_amount >= lp_amount,
+        accounts.user_position.lp_amount >= lp_amount,
         ErrorCode::InsufficientLiquidity
     );
 
     // Calculate withdrawal amounts
-    let withdraw_amounts =
-        calculate_withdrawal_amounts(&pool_reserves, lp_amount, total_lp_supply, &min_amounts)?;
-
-    // Burn LP tokens
-    let cpi_accounts = Burn {
-        mint: ctx.accounts.lp_mint.to_account_info(),
-        from: ctx.accounts.user_lp_token.to_account_info(),
-        authority: ctx.accounts.user.to_account_info(),
-    };
-    let cpi_program = ctx.accounts.token_program.to_account_info();
-    let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
-    token::burn(cpi_ctx, lp_amount)?;
-
-    // Transfer tokens from pool to user - fixed seed array handling
-    // Prepare the seeds for token transfers
-    let partner_token_mint_ref = if pool_type == PoolType::Growth {
-        // For Growth pools, we need the partner token mint as part of the seeds
-        Some(token_mints[1].as_ref())
-    } else {
-        None
+    let target_weights = pool.target_weights_at(Clock::get()?.unix_timestamp);
+    let withdraw_amounts = calculate_withdrawal_amounts(
+        pool,
+        &target_weights,
+        lp_amount,
+        total_lp_supply,
+        exit_fee_bps,
+        min_amounts,
+        mode,
+    )?;
+
+    let new_d = match mode {
+        WithdrawMode::Proportional => {
+            proportional_withdrawal_d(pool, lp_amount, total_lp_supply, exit_fee_bps)
+        }
+        // A skewed withdrawal changes the reserve mix, so solve for D again
+        WithdrawMode::Balanced => {
+            let new_reserves = remaining_reserves(&pool.reserves, &withdraw_amounts)?;
+            crate::state::math::calculate_invariant(&new_reserves, pool.amplification).unwrap_or(0)
+        }
     };
 
-    let seed_type = if pool_type == PoolType::Seed {
-        &b"seed"[..]
-    } else {
-        &b"growth"[..]
-    };
+    settle_withdraw(
+        accounts,
+        lp_amount,
+        &withdraw_amounts,
+        total_lp_supply,
+        new_d,
+    )
+}
 
-    // Store the seeds in longer-lived variables
-    let seed_pool = &b"pool"[..];
+/// Burn priced `lp_amount`, pay out `withdraw_amounts`, debit the position and
+/// refresh the pool snapshot when one was passed
+fn settle_withdraw(
+    accounts: &mut Withdraw,
+    lp_amount: u64,
+    withdraw_amounts: &[u64],
+    total_lp_supply: u64,
+    new_d: u64,
+) -> Result<()> {
+    let authority = accounts
+        .user_position
+        .acting_authority(&accounts.user, accounts.delegate.as_ref())?;
+
+    // Delegates manage liquidity but can never redirect the proceeds
+    if accounts.delegate.is_some() {
+        require!(
+            accounts.recipient_token_a.is_none()
+                && accounts.recipient_token_b.is_none()
+                && accounts.recipient_token_c.is_none(),
+            ErrorCode::Unauthorized
+        );
+    }
 
-    let seeds_with_partner = [
-        seed_pool,
-        seed_type,
-        partner_token_mint_ref.unwrap_or(&[]),
-        &[pool_bump],
+    // Amounts are indexed by pool slot, so the mints must line up with it
+    let pool = &accounts.pool;
+    let token_mints = [
+        Some(accounts.token_mint_a.key()),
+        Some(accounts.token_mint_b.key()),
+        accounts.token_mint_c.as_ref().map(|mint| mint.key()),
     ];
-    let seeds_without_partner = [seed_pool, seed_type, &[pool_bump]];
+    let vaults = [
+        Some(accounts.pool_token_a.key()),
+        Some(accounts.pool_token_b.key()),
+        accounts.pool_token_c.as_ref().map(|vault| vault.key()),
+    ];
+    for (i, &amount) in withdraw_amounts.iter().enumerate() {
+        if amount == 0 {
+            continue;
+        }
+        let (Some(mint), Some(vault)) = (token_mints[i], vaults[i]) else {
+            msg!("Token {}: pays {} but its accounts are missing", i, amount);
+            return err!(ErrorCode::InvalidTokenMint);
+        };
+        crate::utils::check_pool_mint(pool, i, &mint)?;
+        crate::utils::check_pool_vault(pool, i, &vault)?;
+    }
 
-    let seeds = match partner_token_mint_ref {
-        Some(_) => &seeds_with_partner[..],
-        None => &seeds_without_partner[..],
-    };
+    // CPI phase: burn LP tokens; a soulbound pool's LP only lives in the position
+    if !pool.soulbound_lp {
+        let cpi_accounts = Burn {
+            mint: accounts.lp_mint.to_account_info(),
+            from: accounts.user_lp_token.to_account_info(),
+            authority,
+        };
+        let cpi_program = accounts.token_program.to_account_info();
+        let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
+        token::burn(cpi_ctx, lp_amount)?;
+    }
 
-    let signer = &[seeds];
-
-    // Track reserve updates
-    let mut updated_reserves = pool_reserves.clone();
-
-    if pool_type == PoolType::Seed {
-        // For Seed Pool, handle 3 tokens
-        let token_accounts = [
-            (
-                &ctx.accounts.pool_token_a,
-                &ctx.accounts.user_token_a,
-                withdraw_amounts[0],
-            ),
-            (
-                &ctx.accounts.pool_token_b,
-                &ctx.accounts.user_token_b,
-                withdraw_amounts[1],
-            ),
-            (
-                ctx.accounts.pool_token_c.as_ref().unwrap(),
-                ctx.accounts.user_token_c.as_ref().unwrap(),
-                withdraw_amounts[2],
-            ),
-        ];
-
-        // Process each token transfer and update reserves
-        for (i, (from, to, amount)) in token_accounts.iter().enumerate() {
-            if *amount > 0 {
-                let cpi_accounts = Transfer {
-                    from: from.to_account_info(),
-                    to: to.to_account_info(),
-                    authority: pool_account_info.clone(),
-                };
-                let cpi_program = ctx.accounts.token_program.to_account_info();
-                let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
-                token::transfer(cpi_ctx, *amount)?;
-
-                // Update the tracking array
-                updated_reserves[i] = updated_reserves[i].saturating_sub(*amount);
-            }
+    // Transfer tokens from pool to user
+    let seeds = pool.signer_seeds()?;
+    let signer = &[&seeds[..]];
+    let pool_tokens = [
+        Some(&accounts.pool_token_a),
+        Some(&accounts.pool_token_b),
+        accounts.pool_token_c.as_ref(),
+    ];
+    for (i, vault) in pool_tokens.iter().enumerate() {
+        if let Some(vault) = vault {
+            crate::utils::check_vault_backing(pool, i, vault.amount)?;
         }
-    } else {
-        // For Growth Pool, handle 2 tokens
-        let token_accounts = [
-            (
-                &ctx.accounts.pool_token_a,
-                &ctx.accounts.user_token_a,
-                withdraw_amounts[0],
-            ),
-            (
-                &ctx.accounts.pool_token_b,
-                &ctx.accounts.user_token_b,
-                withdraw_amounts[1],
-            ),
-        ];
-
-        // Process each token transfer and update reserves
-        for (i, (from, to, amount)) in token_accounts.iter().enumerate() {
-            if *amount > 0 {
-                let cpi_accounts = Transfer {
-                    from: from.to_account_info(),
-                    to: to.to_account_info(),
-                    authority: pool_account_info.clone(),
-                };
-                let cpi_program = ctx.accounts.token_program.to_account_info();
-                let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
-                token::transfer(cpi_ctx, *amount)?;
-
-                // Update the tracking array
-                updated_reserves[i] = updated_reserves[i].saturating_sub(*amount);
-            }
+    }
+    let recipient_tokens = [
+        accounts
+            .recipient_token_a
+            .as_ref()
+            .or(Some(&accounts.user_token_a)),
+        accounts
+            .recipient_token_b
+            .as_ref()
+            .or(Some(&accounts.user_token_b)),
+        accounts
+            .recipient_token_c
+            .as_ref()
+            .or(accounts.user_token_c.as_ref()),
+    ];
+
+    for (i, &amount) in withdraw_amounts.iter().enumerate() {
+        if amount > 0 {
+            let (Some(from), Some(to)) = (pool_tokens[i], recipient_tokens[i]) else {
+                return err!(ErrorCode::InvalidTokenMint);
+            };
+            let cpi_accounts = Transfer {
+                from: from.to_account_info(),
+                to: to.to_account_info(),
+                authority: pool.to_account_info(),
+            };
+            let cpi_program = accounts.token_program.to_account_info();
+            let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
+            token::transfer(cpi_ctx, amount)?;
         }
     }
 
-    // Now update the pool reserves
-    pool.reserves = updated_reserves;
+    record_withdrawal(
+        &mut accounts.pool,
+        &mut accounts.user_position,
+        lp_amount,
+        withdraw_amounts,
+        total_lp_supply,
+        new_d,
+    )?;
+
+    if let Some(user_portfolio) = accounts.user_portfolio.as_mut() {
+        user_portfolio.record_withdrawal(
+            accounts.user_position.key(),
+            &accounts.user_position,
+            withdraw_amounts,
+            Clock::get()?.unix_timestamp,
+        )?;
+    }
+
+    crate::instructions::pool_snapshot::refresh_if_present(
+        accounts.pool_snapshot.as_mut(),
+        &accounts.pool,
+    )
+}
+
+/// Invariant after a proportional withdrawal of `lp_amount`
+///
+/// D is linear in the reserves, so a proportional withdrawal scales it by the
+/// paid-out share (the exit fee stays in the pool).
+pub fn proportional_withdrawal_d(
+    pool: &Pool,
+    lp_amount: u64,
+    total_lp_supply: u64,
+    exit_fee_bps: u64,
+) -> u64 {
+    let paid_out_lp =
+        lp_amount as u128 * (BPS_DENOMINATOR - exit_fee_bps) as u128 / BPS_DENOMINATOR as u128;
+    (pool.last_d as u128 * (total_lp_supply as u128 - paid_out_lp) / total_lp_supply as u128) as u64
+}
+
+/// Write a settled withdrawal to the pool and position and emit `PositionChanged`
+pub fn record_withdrawal(
+    pool: &mut Account<Pool>,
+    user_position: &mut Account<UserPosition>,
+    lp_amount: u64,
+    withdraw_amounts: &[u64],
+    total_lp_supply: u64,
+    new_d: u64,
+) -> Result<()> {
+    // Write phase: update the pool reserves
+    let reserves_before = pool.reserves.clone();
+    pool.update_weights_ema(Clock::get()?.slot);
+    for (reserve, amount) in pool.reserves.iter_mut().zip(withdraw_amounts.iter()) {
+        *reserve = reserve.saturating_sub(*amount);
+    }
+
+    pool.last_d = new_d;
+    pool.d_slot = Clock::get()?.slot;
+    pool.refresh_virtual_price(total_lp_supply - lp_amount);
+    pool.refresh_routing_hint(Clock::get()?.unix_timestamp);
+    if pool.soulbound_lp {
+        pool.internal_lp_supply = total_lp_supply - lp_amount;
+    }
 
     // Update user position
-    let user_position = &mut ctx.accounts.user_position;
+    let lp_amount_before = user_position.lp_amount;
     user_position.lp_amount = user_position.lp_amount.saturating_sub(lp_amount);
     user_position.last_update = Clock::get()?.unix_timestamp;
+    user_position.last_update_slot = Clock::get()?.slot;
 
     // If lp_amount is 0, mark position as inactive
     if user_position.lp_amount == 0 {
         user_position.is_active = false;
     }
 
+    emit!(PositionChanged {
+        position: user_position.key(),
+        owner: user_position.owner,
+        pool: user_position.pool,
+        reason: PositionChangeReason::Withdraw,
+        lp_amount_before,
+        lp_amount_after: user_position.lp_amount,
+        min_price_before: user_position.min_price,
+        max_price_before: user_position.max_price,
+        min_price_after: user_position.min_price,
+        max_price_after: user_position.max_price,
+        timestamp: user_position.last_update,
+        virtual_price: pool.virtual_price,
+    });
+
+    crate::instructions::swap::emit_large_flow(
+        pool,
+        user_position.owner,
+        FlowDirection::Withdraw,
+        &reserves_before,
+    );
+
     // Update pool last update timestamp
-    pool.last_update = Clock::get()?.unix_timestamp;
+    crate::utils::touch_pool(pool)?;
 
     Ok(())
 }
 
-// Helper function to calculate withdrawal amounts
-fn calculate_withdrawal_amounts(
-    reserves: &[u64],
+/// Reserves left after paying out `amounts`
+fn remaining_reserves(reserves: &[u64], amounts: &[u64]) -> Result<Vec<u64>> {
+    reserves
+        .iter()
+        .zip(amounts.iter())
+        .map(|(&reserve, &amount)| {
+            reserve
+                .checked_sub(amount)
+                .ok_or(error!(ErrorCode::InsufficientLiquidity))
+        })
+        .collect()
+}
+
+/// Price a withdrawal of exactly `amounts`, returning the LP to burn and the new invariant
+///
+/// The LP burned is the invariant drop after charging the imbalance fee on
+/// under-weighted tokens, grossed up by the exit fee and rounded up so the
+/// remaining LPs never subsidize the exit.
+pub fn quote_imbalanced_withdrawal(
+    pool: &Pool,
+    amounts: &[u64],
+    lp_supply: u64,
+    exit_fee_bps: u64,
+) -> Result<(u64, u64)> {
+    require!(
+        amounts.iter().any(|&amount| amount > 0),
+        ErrorCode::ZeroShares
+    );
+    require!(lp_supply > 0, ErrorCode::InsufficientLiquidity);
+
+    let amplification = pool.amplification;
+    let old_d = crate::state::math::calculate_invariant(&pool.reserves, amplification)
+        .ok_or(ErrorCode::InsufficientLiquidity)?;
+
+    // Draining a token entirely leaves no invariant to price against
+    let new_reserves = remaining_reserves(&pool.reserves, amounts)?;
+    let new_d = crate::state::math::calculate_invariant(&new_reserves, amplification)
+        .ok_or(ErrorCode::InsufficientLiquidity)?;
+
+    let target_weights = pool.target_weights_at(Clock::get()?.unix_timestamp);
+    let charged_amounts = crate::state::math::calculate_withdrawal_charged_amounts(
+        amounts,
+        &pool.reserves,
+        &target_weights,
+    );
+    let charged_reserves = remaining_reserves(&pool.reserves, &charged_amounts)?;
+    let charged_d = crate::state::math::calculate_invariant(&charged_reserves, amplification)
+        .ok_or(ErrorCode::InsufficientLiquidity)?;
+
+    let lp_amount = rounding::lp_burned(lp_supply, old_d.saturating_sub(charged_d), old_d)
+        .and_then(|lp_amount| {
+            rounding::mul_div(
+                lp_amount,
+                BPS_DENOMINATOR,
+                BPS_DENOMINATOR - exit_fee_bps,
+                rounding::Rounding::Up,
+            )
+        })
+        .ok_or(ErrorCode::MathOverflow)?;
+    require!(lp_amount < lp_supply, ErrorCode::InsufficientLiquidity);
+
+    Ok((lp_amount, new_d))
+}
+
+// Helper function to calculate withdrawal amounts from `pool`, net of its exit fee
+pub fn calculate_withdrawal_amounts(
+    pool: &Pool,
+    target_weights: &[u64],
     lp_amount: u64,
     total_lp_supply: u64,
+    exit_fee_bps: u64,
     min_amounts: &[u64],
+    mode: WithdrawMode,
 ) -> Result<Vec<u64>> {
-    // Calculate token amounts to withdraw based on share of pool
-    let withdraw_ratio = (lp_amount as u128 * 10000) as u128 / total_lp_supply as u128;
-
-    let mut withdraw_amounts = Vec::new();
-    for (i, &reserve) in reserves.iter().enumerate() {
-        let amount = (reserve as u128 * withdraw_ratio / 10000) as u64;
-        withdraw_amounts.push(amount);
+    // LP burned outside `withdraw` can leave positions claiming more than exists
+    if lp_amount > total_lp_supply || total_lp_supply == 0 {
+        msg!(
+            "LP withdrawn: got {}, supply {}",
+            lp_amount,
+            total_lp_supply
+        );
+        return err!(ErrorCode::InsufficientLiquidity);
+    }
 
-        // Check minimum amounts
-        if i < min_amounts.len() {
-            require!(amount >= min_amounts[i], ErrorCode::SlippageExceeded);
-        }
+    let withdraw_amounts = pool
+        .view()
+        .withdrawal_amounts(
+            target_weights,
+            lp_amount,
+            total_lp_supply,
+            exit_fee_bps,
+            mode.into(),
+        )
+        .map_err(|error| error!(ErrorCode::from(error)))?;
+
+    // Check minimum amounts
+    for (i, (&amount, &min_amount)) in withdraw_amounts.iter().zip(min_amounts).enumerate() {
+        crate::utils::check_min_amount(format_args!("Token {} payout", i), amount, min_amount)?;
     }
 
     Ok(withdraw_amounts)
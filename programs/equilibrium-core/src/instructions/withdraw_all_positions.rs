@@ -0,0 +1,194 @@
+use crate::errors::ErrorCode;
+use crate::instructions::withdraw::*;
+use crate::state::*;
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Burn, Mint, Token, TokenAccount, Transfer};
+
+#[derive(Accounts)]
+pub struct WithdrawAllPositions<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+
+    /// Owner's vote escrow under one config; its voting power discounts the exit
+    /// fee of that config's pools
+    #[account(
+        seeds = [
+            &b"vote-escrow"[..],
+            vote_escrow.amm_config.as_ref(),
+            user.key().as_ref(),
+        ],
+        bump = vote_escrow.bump,
+    )]
+    pub vote_escrow: Option<Account<'info, VoteEscrow>>,
+
+    /// Config the vote escrow was locked under; required with it
+    pub amm_config: Option<Account<'info, AmmConfig>>,
+
+    /// Owner's entry on one config's exit fee exemption list; only that config's
+    /// pools skip the exit fee
+    #[account(
+        seeds = [
+            &b"fee-exemption"[..],
+            fee_exemption.amm_config.as_ref(),
+            user.key().as_ref(),
+        ],
+        bump = fee_exemption.bump,
+    )]
+    pub fee_exemption: Option<Account<'info, FeeExemption>>,
+
+    /// Owner's portfolio, updated for every position withdrawn
+    #[account(
+        mut,
+        seeds = [&b"user-portfolio"[..], user.key().as_ref()],
+        bump = user_portfolio.bump,
+    )]
+    pub user_portfolio: Option<Account<'info, UserPortfolio>>,
+}
+
+/// Withdraw every listed position in full, proportionally
+///
+/// `remaining_accounts` holds one set per position: `[pool, lp_mint,
+/// user_lp_token, user_position]` followed by a `(pool_token, user_token)`
+/// pair for each of the pool's tokens, in pool order. `min_amounts` has one
+/// entry per position.
+pub fn withdraw_all_positions<'info>(
+    ctx: Context<'_, '_, 'info, 'info, WithdrawAllPositions<'info>>,
+    min_amounts: Vec<TokenAmounts>,
+    deadline: i64,
+) -> Result<()> {
+    crate::utils::check_deadline(deadline)?;
+    let user = ctx.accounts.user.key();
+
+    let mut remaining = ctx.remaining_accounts;
+    for min_amounts in min_amounts.iter() {
+        let [pool_info, lp_mint_info, user_lp_token_info, user_position_info, rest @ ..] =
+            remaining
+        else {
+            return err!(ErrorCode::InvalidInputLength);
+        };
+        let mut pool = Account::<Pool>::try_from(pool_info)?;
+        let num_tokens = pool.token_mints.len();
+        require!(rest.len() >= num_tokens * 2, ErrorCode::InvalidInputLength);
+        let (token_accounts, next) = rest.split_at(num_tokens * 2);
+        remaining = next;
+
+        // Read phase: validate the set against its pool
+        let lp_mint = Account::<Mint>::try_from(lp_mint_info)?;
+        require_keys_eq!(lp_mint.key(), pool.lp_mint, ErrorCode::InvalidTokenMint);
+        let user_lp_token = Account::<TokenAccount>::try_from(user_lp_token_info)?;
+        require!(
+            user_lp_token.owner == user && user_lp_token.mint == pool.lp_mint,
+            ErrorCode::InvalidTokenMint
+        );
+        let mut user_position = Account::<UserPosition>::try_from(user_position_info)?;
+        require_keys_eq!(user_position.owner, user, ErrorCode::Unauthorized);
+        require_keys_eq!(user_position.pool, pool.key(), ErrorCode::InvalidPoolType);
+        require!(user_position.is_active, ErrorCode::PositionNotActive);
+
+        let mut pool_tokens = Vec::with_capacity(num_tokens);
+        let mut user_tokens = Vec::with_capacity(num_tokens);
+        for (i, pair) in token_accounts.chunks_exact(2).enumerate() {
+            let pool_token = Account::<TokenAccount>::try_from(&pair[0])?;
+            let user_token = Account::<TokenAccount>::try_from(&pair[1])?;
+            crate::utils::check_pool_vault(&pool, i, &pool_token.key())?;
+            crate::utils::check_vault_backing(&pool, i, pool_token.amount)?;
+            crate::utils::check_pool_mint(&pool, i, &user_token.mint)?;
+            require_keys_eq!(user_token.owner, user, ErrorCode::Unauthorized);
+            pool_tokens.push(pool_token);
+            user_tokens.push(user_token);
+        }
+
+        let lp_amount = user_position.lp_amount;
+        let total_lp_supply = pool.lp_supply(lp_mint.supply);
+        let fee_exempt = ctx
+            .accounts
+            .fee_exemption
+            .as_ref()
+            .is_some_and(|exemption| exemption.amm_config == pool.amm_config);
+        // The escrow only discounts pools of the config it was locked under
+        let fee_discount_bps = match ctx.accounts.vote_escrow.as_deref() {
+            Some(vote_escrow) if vote_escrow.amm_config == pool.amm_config => {
+                let amm_config = ctx
+                    .accounts
+                    .amm_config
+                    .as_ref()
+                    .ok_or(ErrorCode::VoteEscrowMismatch)?;
+                crate::utils::vote_escrow_discount_bps(
+                    Some(vote_escrow),
+                    amm_config,
+                    Clock::get()?.unix_timestamp,
+                )?
+            }
+            _ => 0,
+        };
+        let exit_fee_bps = owner_exit_fee_bps(&pool, fee_discount_bps, fee_exempt);
+        let target_weights = pool.target_weights_at(Clock::get()?.unix_timestamp);
+        let withdraw_amounts = calculate_withdrawal_amounts(
+            &pool,
+            &target_weights,
+            lp_amount,
+            total_lp_supply,
+            exit_fee_bps,
+            pool.active_amounts(min_amounts)?,
+            WithdrawMode::Proportional,
+        )?;
+        let new_d = proportional_withdrawal_d(&pool, lp_amount, total_lp_supply, exit_fee_bps);
+
+        // CPI phase: burn the position's LP and pay out the pool's share
+        if !pool.soulbound_lp {
+            let cpi_accounts = Burn {
+                mint: lp_mint.to_account_info(),
+                from: user_lp_token.to_account_info(),
+                authority: ctx.accounts.user.to_account_info(),
+            };
+            let cpi_program = ctx.accounts.token_program.to_account_info();
+            token::burn(CpiContext::new(cpi_program, cpi_accounts), lp_amount)?;
+        }
+
+        let seeds = pool.signer_seeds()?;
+        let signer = &[&seeds[..]];
+        for ((from, to), &amount) in pool_tokens
+            .iter()
+            .zip(user_tokens.iter())
+            .zip(withdraw_amounts.iter())
+        {
+            if amount > 0 {
+                let cpi_accounts = Transfer {
+                    from: from.to_account_info(),
+                    to: to.to_account_info(),
+                    authority: pool.to_account_info(),
+                };
+                let cpi_program = ctx.accounts.token_program.to_account_info();
+                let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
+                token::transfer(cpi_ctx, amount)?;
+            }
+        }
+
+        // Write phase: accounts loaded by hand must be persisted by hand
+        record_withdrawal(
+            &mut pool,
+            &mut user_position,
+            lp_amount,
+            &withdraw_amounts,
+            total_lp_supply,
+            new_d,
+        )?;
+        if let Some(user_portfolio) = ctx.accounts.user_portfolio.as_mut() {
+            user_portfolio.record_withdrawal(
+                user_position.key(),
+                &user_position,
+                &withdraw_amounts,
+                Clock::get()?.unix_timestamp,
+            )?;
+        }
+        pool.exit(&crate::ID)?;
+        user_position.exit(&crate::ID)?;
+    }
+
+    // Every account passed must belong to a listed position
+    require!(remaining.is_empty(), ErrorCode::InvalidInputLength);
+
+    Ok(())
+}
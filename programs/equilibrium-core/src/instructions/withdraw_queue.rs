@@ -0,0 +1,317 @@
+use crate::errors::ErrorCode;
+use crate::events::{WithdrawQueued, WithdrawRequestFulfilled};
+use crate::instructions::withdraw::*;
+use crate::state::*;
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Burn, Mint, Token, TokenAccount, Transfer};
+
+#[derive(Accounts)]
+pub struct QueueWithdraw<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(mut)]
+    pub pool: Account<'info, Pool>,
+
+    #[account(
+        mut,
+        constraint = lp_mint.key() == pool.lp_mint @ ErrorCode::InvalidTokenMint,
+    )]
+    pub lp_mint: Account<'info, Mint>,
+
+    #[account(
+        mut,
+        token::authority = user,
+        token::mint = lp_mint,
+    )]
+    pub user_lp_token: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [&b"user-position"[..], user.key().as_ref(), pool.key().as_ref()],
+        bump = user_position.bump,
+        constraint = user_position.owner == user.key() @ ErrorCode::Unauthorized,
+        constraint = user_position.is_active @ ErrorCode::PositionNotActive,
+    )]
+    pub user_position: Account<'info, UserPosition>,
+
+    /// Pool vaults, read to confirm one is short; `pool_token_c` only for a third token
+    pub pool_token_a: Account<'info, TokenAccount>,
+    pub pool_token_b: Account<'info, TokenAccount>,
+    pub pool_token_c: Option<Account<'info, TokenAccount>>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = WithdrawQueue::space(),
+        seeds = [&b"withdraw-queue"[..], pool.key().as_ref()],
+        bump
+    )]
+    pub withdraw_queue: Account<'info, WithdrawQueue>,
+
+    #[account(
+        init,
+        payer = user,
+        space = WithdrawRequest::space(),
+        seeds = [
+            &b"withdraw-request"[..],
+            pool.key().as_ref(),
+            &withdraw_queue.tail.to_le_bytes(),
+        ],
+        bump
+    )]
+    pub withdraw_request: Account<'info, WithdrawRequest>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Book a proportional withdrawal of `lp_amount` while a pool vault is short
+///
+/// The withdrawal is priced and settled against the pool's recorded reserves
+/// now: the LP is burned, the position debited and the reserves reduced. The
+/// tokens owed wait in a `WithdrawRequest` at the back of the pool's queue until
+/// the vaults hold them again, instead of the withdrawal failing on transfer.
+pub fn queue_withdraw(
+    ctx: Context<QueueWithdraw>,
+    lp_amount: u64,
+    min_amounts: [u64; MAX_POOL_TOKENS],
+    deadline: i64,
+) -> Result<()> {
+    crate::utils::check_deadline(deadline)?;
+
+    // Read phase: only a short pool queues, and every vault must be the pool's
+    let pool = &ctx.accounts.pool;
+    pool.require_not_paused()?;
+    let vaults = [
+        Some(&ctx.accounts.pool_token_a),
+        Some(&ctx.accounts.pool_token_b),
+        ctx.accounts.pool_token_c.as_ref(),
+    ];
+    let mut short = false;
+    for (i, vault) in vaults.into_iter().enumerate().take(pool.reserves.len()) {
+        let Some(vault) = vault else {
+            return err!(ErrorCode::InvalidTokenMint);
+        };
+        crate::utils::check_pool_vault(pool, i, &vault.key())?;
+        short |= vault.amount < pool.vault_liability(i);
+    }
+    require!(short, ErrorCode::VaultsBacked);
+
+    require!(
+        lp_amount > 0 && ctx.accounts.user_position.lp_amount >= lp_amount,
+        ErrorCode::InsufficientLiquidity
+    );
+    let min_amounts = pool.active_amounts(&min_amounts)?;
+    let total_lp_supply = pool.lp_supply(ctx.accounts.lp_mint.supply);
+    let exit_fee_bps = owner_exit_fee_bps(pool, 0, false);
+    let target_weights = pool.target_weights_at(Clock::get()?.unix_timestamp);
+    let withdraw_amounts = calculate_withdrawal_amounts(
+        pool,
+        &target_weights,
+        lp_amount,
+        total_lp_supply,
+        exit_fee_bps,
+        min_amounts,
+        WithdrawMode::Proportional,
+    )?;
+    let new_d = proportional_withdrawal_d(pool, lp_amount, total_lp_supply, exit_fee_bps);
+
+    // CPI phase: burn the LP now, so the request is fixed at today's price
+    if !pool.soulbound_lp {
+        let cpi_accounts = Burn {
+            mint: ctx.accounts.lp_mint.to_account_info(),
+            from: ctx.accounts.user_lp_token.to_account_info(),
+            authority: ctx.accounts.user.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        token::burn(CpiContext::new(cpi_program, cpi_accounts), lp_amount)?;
+    }
+
+    // Write phase: the owed tokens leave the reserves and join the queue
+    record_withdrawal(
+        &mut ctx.accounts.pool,
+        &mut ctx.accounts.user_position,
+        lp_amount,
+        &withdraw_amounts,
+        total_lp_supply,
+        new_d,
+    )?;
+    let pool = &mut ctx.accounts.pool;
+    for (queued, &amount) in pool.queued_owed.iter_mut().zip(&withdraw_amounts) {
+        *queued = queued.checked_add(amount).ok_or(ErrorCode::MathOverflow)?;
+    }
+
+    let pool_key = ctx.accounts.pool.key();
+    let withdraw_queue = &mut ctx.accounts.withdraw_queue;
+    if withdraw_queue.pool == Pubkey::default() {
+        withdraw_queue.bump = ctx.bumps.withdraw_queue;
+        withdraw_queue.pool = pool_key;
+    }
+    let seq = withdraw_queue.tail;
+    withdraw_queue.tail += 1;
+
+    let mut amounts = [0; MAX_POOL_TOKENS];
+    amounts[..withdraw_amounts.len()].copy_from_slice(&withdraw_amounts);
+    let withdraw_request = &mut ctx.accounts.withdraw_request;
+    withdraw_request.bump = ctx.bumps.withdraw_request;
+    withdraw_request.pool = pool_key;
+    withdraw_request.owner = ctx.accounts.user.key();
+    withdraw_request.seq = seq;
+    withdraw_request.lp_amount = lp_amount;
+    withdraw_request.amounts = amounts;
+    withdraw_request.queued_at = Clock::get()?.unix_timestamp;
+
+    emit!(WithdrawQueued {
+        pool: pool_key,
+        position: ctx.accounts.user_position.key(),
+        owner: withdraw_request.owner,
+        seq,
+        lp_amount,
+        amounts: withdraw_amounts,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct FulfillWithdrawRequest<'info> {
+    /// Anyone can crank the queue once the vaults hold the head request's tokens
+    pub keeper: Signer<'info>,
+
+    #[account(mut)]
+    pub pool: Account<'info, Pool>,
+
+    #[account(
+        mut,
+        has_one = pool,
+        seeds = [&b"withdraw-queue"[..], pool.key().as_ref()],
+        bump = withdraw_queue.bump,
+    )]
+    pub withdraw_queue: Account<'info, WithdrawQueue>,
+
+    #[account(
+        mut,
+        has_one = pool,
+        has_one = owner @ ErrorCode::Unauthorized,
+        close = owner,
+    )]
+    pub withdraw_request: Account<'info, WithdrawRequest>,
+
+    /// CHECK: Request owner, receiving the request's rent back
+    #[account(mut)]
+    pub owner: UncheckedAccount<'info>,
+
+    /// Pool vaults paying the request; `pool_token_c` only for a third token
+    #[account(mut)]
+    pub pool_token_a: Account<'info, TokenAccount>,
+    #[account(mut)]
+    pub pool_token_b: Account<'info, TokenAccount>,
+    #[account(mut)]
+    pub pool_token_c: Option<Account<'info, TokenAccount>>,
+
+    /// Owner's accounts receiving the tokens owed
+    #[account(
+        mut,
+        token::authority = owner,
+    )]
+    pub owner_token_a: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        token::authority = owner,
+    )]
+    pub owner_token_b: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        token::authority = owner,
+    )]
+    pub owner_token_c: Option<Account<'info, TokenAccount>>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+/// Pay the request at the head of the pool's queue (permissionless)
+///
+/// Each vault must hold the pool's recorded reserve and protocol fees plus what
+/// the request is owed, so paying it never dips into the liquidity still backing
+/// the pool.
+pub fn fulfill_withdraw_request(ctx: Context<FulfillWithdrawRequest>) -> Result<()> {
+    let withdraw_request = &ctx.accounts.withdraw_request;
+    require!(
+        withdraw_request.seq == ctx.accounts.withdraw_queue.head,
+        ErrorCode::NotNextInQueue
+    );
+
+    // Read phase: every owed token needs a funded vault and the owner's account
+    let pool = &ctx.accounts.pool;
+    let vaults = [
+        Some(&ctx.accounts.pool_token_a),
+        Some(&ctx.accounts.pool_token_b),
+        ctx.accounts.pool_token_c.as_ref(),
+    ];
+    let owner_tokens = [
+        Some(&ctx.accounts.owner_token_a),
+        Some(&ctx.accounts.owner_token_b),
+        ctx.accounts.owner_token_c.as_ref(),
+    ];
+    for (i, &amount) in withdraw_request.amounts.iter().enumerate() {
+        if amount == 0 {
+            continue;
+        }
+        let (Some(vault), Some(owner_token)) = (vaults[i], owner_tokens[i]) else {
+            return err!(ErrorCode::InvalidTokenMint);
+        };
+        crate::utils::check_pool_vault(pool, i, &vault.key())?;
+        crate::utils::check_pool_mint(pool, i, &owner_token.mint)?;
+        let owed = pool.reserves[i]
+            .checked_add(pool.protocol_fees_owed[i])
+            .and_then(|owed| owed.checked_add(amount))
+            .ok_or(ErrorCode::MathOverflow)?;
+        if vault.amount < owed {
+            msg!(
+                "Token {}: vault holds {} of the {} needed",
+                i,
+                vault.amount,
+                owed
+            );
+            return err!(ErrorCode::VaultShortfall);
+        }
+    }
+
+    // CPI phase: pay the owner
+    let seeds = pool.signer_seeds()?;
+    let signer = &[&seeds[..]];
+    for (i, &amount) in withdraw_request.amounts.iter().enumerate() {
+        if amount == 0 {
+            continue;
+        }
+        let (Some(from), Some(to)) = (vaults[i], owner_tokens[i]) else {
+            return err!(ErrorCode::InvalidTokenMint);
+        };
+        let cpi_accounts = Transfer {
+            from: from.to_account_info(),
+            to: to.to_account_info(),
+            authority: pool.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
+        token::transfer(cpi_ctx, amount)?;
+    }
+
+    emit!(WithdrawRequestFulfilled {
+        pool: pool.key(),
+        owner: withdraw_request.owner,
+        seq: withdraw_request.seq,
+        amounts: withdraw_request.amounts[..pool.token_mints.len()].to_vec(),
+    });
+
+    // Write phase: the request is no longer owed and the next moves to the head
+    let amounts = ctx.accounts.withdraw_request.amounts;
+    let pool = &mut ctx.accounts.pool;
+    for (queued, amount) in pool.queued_owed.iter_mut().zip(amounts) {
+        *queued = queued.saturating_sub(amount);
+    }
+    ctx.accounts.withdraw_queue.head += 1;
+
+    Ok(())
+}
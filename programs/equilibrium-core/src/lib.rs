@@ -1,38 +1,134 @@
+// Anchor requires glob re-exports of every instruction module, each of which
+// defines its own `handler`
+#![allow(ambiguous_glob_reexports)]
+
 use anchor_lang::prelude::*;
 
 declare_id!("1uu1R8otFuC235hhTstPWVUwsuZ1z5cLoKYd1biVv8Y");
 
 // Load modules
 pub mod errors;
+pub mod events;
 pub mod instructions;
 pub mod state;
 pub mod utils;
+#[cfg(any(test, feature = "test-utils"))]
+pub mod test_utils;
 
 // Re-export state accounts
-pub use state::config::AmmConfig;
-pub use state::pool::{Pool, PoolType};
+pub use state::autocompounder::Autocompounder;
+pub use state::config::{
+    AmmConfig, CpiTargetKind, WhitelistedProgram, CONFIG_RESERVED_BYTES, CONFIG_VERSION,
+    MAX_GUARDIANS, MAX_WHITELISTED_PROGRAMS,
+};
+pub use state::dca::DcaSchedule;
+pub use state::limit_order::LimitOrder;
+pub use state::locked_position::{LockedPosition, MAX_LIQUIDITY_LOCK_DURATION};
+pub use state::oracle::{LpOracleAdapter, MAX_PARTNER_PRICE_AGE};
+pub use state::partner::PartnerApproval;
+pub use state::partner_stats::{
+    PartnerStats, VolumeSample, PARTNER_STATS_DEPTH_IMPACT_BPS, PARTNER_STATS_WINDOW,
+};
+pub use state::pool::{
+    FeeModel, FeeParams, FeeTier, Pool, PoolType, TokenAmounts, TokenStatus, MAX_POOL_TOKENS,
+    PARTNER_INDEX, USD_STAR_INDEX,
+};
+pub use state::pool_snapshot::{PoolSnapshot, SNAPSHOT_BUCKETS, SNAPSHOT_BUCKET_SECONDS};
+pub use state::pool_stats::{FeeSample, PoolStats, POOL_STATS_INTERVAL};
+pub use state::portfolio::{PortfolioEntry, UserPortfolio, MAX_PORTFOLIO_POSITIONS};
+pub use state::program_state::{
+    ProgramState, FEATURE_FLASH_LOANS, FEATURE_LIMIT_ORDERS, FEATURE_TOKEN_2022, PROGRAM_VERSION,
+};
+pub use state::referral::ReferralAccount;
+pub use state::relay::{RelayNonce, RelayedSwapOrder};
+pub use state::staged_withdrawal::StagedWithdrawal;
+pub use state::swap_batch::{
+    SwapBatch, SwapIntent, MAX_BATCH_INTENTS, MAX_BATCH_WINDOW_SLOTS,
+};
+pub use state::swap_hook::SwapHookApproval;
+pub use state::treasury::Treasury;
 pub use state::user::UserPosition;
+pub use state::vote_escrow::{
+    FeeExemption, VoteEscrow, MAX_VE_LOCK_DURATION, VE_FEE_DISCOUNT_TIERS,
+};
+pub use state::voting_snapshot::VotingSnapshot;
+pub use state::withdraw_queue::{WithdrawQueue, WithdrawRequest};
 
 // Re-export all instruction accounts including hidden types generated by Anchor
+pub use instructions::add_pool_token::*;
+pub use instructions::autocompounder::*;
+pub use instructions::batch_auction::*;
+pub use instructions::close_pool_vault::*;
 pub use instructions::create_pool::*;
+pub use instructions::dca::*;
 pub use instructions::deposit::*;
+pub use instructions::guardian::*;
 pub use instructions::initialize::*;
+pub use instructions::limit_order::*;
+pub use instructions::liquidity_lock::*;
+pub use instructions::lp_oracle::*;
+pub use instructions::migrate_config::*;
+pub use instructions::orphaned_reserves::*;
+pub use instructions::partner_approval::*;
+pub use instructions::partner_oracle::*;
+pub use instructions::partner_stats::*;
+pub use instructions::pool_snapshot::*;
+pub use instructions::pool_stats::*;
+pub use instructions::portfolio::*;
+pub use instructions::position_delegate::*;
+pub use instructions::program_state::*;
+pub use instructions::rebalance::*;
+pub use instructions::recompute_invariant::*;
+pub use instructions::referral::*;
+pub use instructions::relayed_swap::*;
+pub use instructions::staged_withdraw::*;
 pub use instructions::swap::*;
+pub use instructions::swap_basket::*;
+pub use instructions::swap_hook::*;
+pub use instructions::swap_partner_to_partner::*;
+pub use instructions::transfer_position::*;
+pub use instructions::treasury::*;
+pub use instructions::update_config::*;
+pub use instructions::update_pool::*;
+pub use instructions::views::*;
+pub use instructions::vote_escrow::*;
+pub use instructions::voting_snapshot::*;
 pub use instructions::withdraw::*;
+pub use instructions::withdraw_all_positions::*;
+pub use instructions::withdraw_queue::*;
 
 // Program
 #[program]
 pub mod equilibrium_core {
     use super::*;
 
+    /// Create the AMM config at `index` with default amplification and Seed Pool weights
+    ///
+    /// Each index is an independent namespace with its own authority, pools,
+    /// treasury and approvals
     pub fn initialize(
         context: Context<Initialize>,
+        index: u16,
         default_amplification: u64,
         default_target_weights: [u64; 3],
     ) -> Result<()> {
-        instructions::initialize::handler(context, default_amplification, default_target_weights)
+        instructions::initialize::handler(
+            context,
+            index,
+            default_amplification,
+            default_target_weights,
+        )
+    }
+
+    /// Upgrade config `index` to the current layout version (authority only)
+    ///
+    /// `initialize` runs once per index; later layouts reach existing configs
+    /// through this instead, under the same address and program ID
+    pub fn migrate_config(context: Context<MigrateConfig>, index: u16) -> Result<()> {
+        instructions::migrate_config::migrate_config(context, index)
     }
 
+    /// Create the three-token Seed Pool and mint the initial LP to the payer
     pub fn create_seed_pool(
         context: Context<CreateSeedPool>,
         amplification: u64,
@@ -47,38 +143,859 @@ pub mod equilibrium_core {
         )
     }
 
+    /// Create a USD*/partner Growth Pool with the given fee tier and USD*/partner
+    /// target weights (20/80 to 80/20); soulbound pools track LP in positions only
+    /// and mint no LP tokens
+    #[allow(clippy::too_many_arguments)]
     pub fn create_growth_pool(
         context: Context<CreateGrowthPool>,
         amplification: u64,
         initial_usdc_amount: u64,
         initial_partner_amount: u64,
+        fee_tier: FeeTier,
+        soulbound_lp: bool,
+        target_weights: [u64; 2],
     ) -> Result<()> {
         instructions::create_pool::create_growth_pool(
             context,
             amplification,
             initial_usdc_amount,
             initial_partner_amount,
+            fee_tier,
+            soulbound_lp,
+            target_weights,
         )
     }
 
+    /// Add liquidity, one amount per pool token (unused slots must be zero)
     pub fn deposit(
         context: Context<Deposit>,
-        amounts: Vec<u64>,
+        amounts: [u64; MAX_POOL_TOKENS],
         min_lp_amount: u64,
         concentration: u64,
+        deadline: i64,
     ) -> Result<()> {
-        instructions::deposit::handler(context, amounts, min_lp_amount, concentration)
+        instructions::deposit::handler(context, amounts, min_lp_amount, concentration, deadline)
     }
 
+    /// Add liquidity, reverting if the imbalance fee exceeds `max_imbalance_fee_bps`
+    pub fn deposit_with_max_imbalance_fee(
+        context: Context<Deposit>,
+        amounts: [u64; MAX_POOL_TOKENS],
+        max_imbalance_fee_bps: u64,
+        concentration: u64,
+        deadline: i64,
+    ) -> Result<()> {
+        instructions::deposit::deposit_with_max_imbalance_fee(
+            context,
+            amounts,
+            max_imbalance_fee_bps,
+            concentration,
+            deadline,
+        )
+    }
+
+    /// Add balanced liquidity minting exactly `lp_amount`, capped per token by `max_amounts`
+    pub fn deposit_exact_lp(
+        context: Context<Deposit>,
+        lp_amount: u64,
+        max_amounts: [u64; MAX_POOL_TOKENS],
+        concentration: u64,
+        deadline: i64,
+    ) -> Result<()> {
+        instructions::deposit::deposit_exact_lp(
+            context,
+            lp_amount,
+            max_amounts,
+            concentration,
+            deadline,
+        )
+    }
+
+    /// Burn LP for pool tokens, proportionally or paying out over-weighted tokens first
     pub fn withdraw(
         context: Context<Withdraw>,
         lp_amount: u64,
-        min_amounts: Vec<u64>,
+        min_amounts: [u64; MAX_POOL_TOKENS],
+        mode: WithdrawMode,
+        deadline: i64,
+    ) -> Result<()> {
+        instructions::withdraw::handler(context, lp_amount, min_amounts, mode, deadline)
+    }
+
+    /// Withdraw `bps` of the position's LP, as held when the instruction executes
+    pub fn withdraw_percent(
+        context: Context<Withdraw>,
+        bps: u64,
+        min_amounts: [u64; MAX_POOL_TOKENS],
+        mode: WithdrawMode,
+        deadline: i64,
+    ) -> Result<()> {
+        instructions::withdraw::withdraw_percent(context, bps, min_amounts, mode, deadline)
+    }
+
+    /// Withdraw exactly `amounts`, burning at most `max_lp_burn` LP including the imbalance fee
+    pub fn withdraw_imbalanced(
+        context: Context<Withdraw>,
+        amounts: [u64; MAX_POOL_TOKENS],
+        max_lp_burn: u64,
+        deadline: i64,
+    ) -> Result<()> {
+        instructions::withdraw::withdraw_imbalanced(context, amounts, max_lp_burn, deadline)
+    }
+
+    /// Withdraw several positions in full in one transaction
+    ///
+    /// `remaining_accounts` holds `[pool, lp_mint, user_lp_token, user_position]` per
+    /// position, each followed by `(pool_token, user_token)` for every pool token
+    pub fn withdraw_all_positions<'info>(
+        context: Context<'_, '_, 'info, 'info, WithdrawAllPositions<'info>>,
+        min_amounts: Vec<[u64; MAX_POOL_TOKENS]>,
+        deadline: i64,
+    ) -> Result<()> {
+        instructions::withdraw_all_positions::withdraw_all_positions(
+            context,
+            min_amounts,
+            deadline,
+        )
+    }
+
+    /// Swap one pool token for another at the dynamic fee
+    ///
+    /// Optional `remaining_accounts` of `[swap_hook_approval, hook_program, ..hook_accounts]`
+    /// make the swap call the approved hook program with the result once it settles.
+    /// A nonzero `integrator_fee_bps` pays the optional `integrator_fee_account` that
+    /// share of `amount_in` on top, up to `MAX_INTEGRATOR_FEE_BPS`, or accrues it to
+    /// the optional `referral_account`'s vault for a later `claim_referral_fees`. A
+    /// `max_price_impact_bps` reverts the swap if its execution price falls further
+    /// than that below the pre-trade spot price
+    pub fn swap(
+        context: Context<Swap>,
+        amount_in: u64,
+        min_amount_out: u64,
+        deadline: i64,
+        integrator_fee_bps: u64,
+        max_price_impact_bps: Option<u64>,
+    ) -> Result<()> {
+        instructions::swap::handler(
+            context,
+            amount_in,
+            min_amount_out,
+            deadline,
+            integrator_fee_bps,
+            max_price_impact_bps,
+        )
+    }
+
+    /// Swap like `swap`, naming the tokens by their index in `pool.token_mints`
+    ///
+    /// Routers that already know the indices skip the on-chain mint lookup
+    #[allow(clippy::too_many_arguments)]
+    pub fn swap_indexed(
+        context: Context<Swap>,
+        token_in_index: u8,
+        token_out_index: u8,
+        amount_in: u64,
+        min_amount_out: u64,
+        deadline: i64,
+        integrator_fee_bps: u64,
+        max_price_impact_bps: Option<u64>,
+    ) -> Result<()> {
+        instructions::swap::swap_indexed(
+            context,
+            token_in_index,
+            token_out_index,
+            amount_in,
+            min_amount_out,
+            deadline,
+            integrator_fee_bps,
+            max_price_impact_bps,
+        )
+    }
+
+    /// Sell a basket of Seed Pool tokens for the one at `token_out_index` in one invariant solve
+    pub fn swap_basket(
+        context: Context<SwapBasket>,
+        amounts_in: Vec<u64>,
+        token_out_index: u8,
+        min_amount_out: u64,
+        deadline: i64,
+    ) -> Result<()> {
+        instructions::swap_basket::swap_basket(
+            context,
+            amounts_in,
+            token_out_index,
+            min_amount_out,
+            deadline,
+        )
+    }
+
+    /// Execute a user-signed swap order on behalf of its user (pool relayer)
+    ///
+    /// Must follow an ed25519 verification of the order's Borsh encoding by the user
+    pub fn relayed_swap(context: Context<RelayedSwap>, order: RelayedSwapOrder) -> Result<()> {
+        instructions::relayed_swap::relayed_swap(context, order)
+    }
+
+    /// Escrow tokens for a swap that fills once the pool quotes `min_amount_out`
+    pub fn place_limit_order(
+        context: Context<PlaceLimitOrder>,
+        order_id: u64,
+        amount_in: u64,
+        min_amount_out: u64,
+    ) -> Result<()> {
+        instructions::limit_order::place_limit_order(context, order_id, amount_in, min_amount_out)
+    }
+
+    /// Cancel an open limit order and return its escrow
+    pub fn cancel_limit_order(context: Context<CancelLimitOrder>) -> Result<()> {
+        instructions::limit_order::cancel_limit_order(context)
+    }
+
+    /// Fill a limit order whose price has been reached (permissionless)
+    pub fn fill_limit_order(context: Context<FillLimitOrder>) -> Result<()> {
+        instructions::limit_order::fill_limit_order(context)
+    }
+
+    /// Create the swap batch of a Growth pool running batch auctions
+    pub fn init_swap_batch(context: Context<InitSwapBatch>) -> Result<()> {
+        instructions::batch_auction::init_swap_batch(context)
+    }
+
+    /// Sell tokens in the pool's open batch; the input is held until the batch settles
+    pub fn submit_swap_intent(
+        context: Context<SubmitSwapIntent>,
+        token_in_index: u8,
+        amount_in: u64,
+        min_amount_out: u64,
+    ) -> Result<()> {
+        instructions::batch_auction::submit_swap_intent(
+            context,
+            token_in_index,
+            amount_in,
+            min_amount_out,
+        )
+    }
+
+    /// Clear a closed batch at a uniform price per side (permissionless)
+    pub fn settle_batch<'info>(
+        context: Context<'_, '_, 'info, 'info, SettleBatch<'info>>,
+    ) -> Result<()> {
+        instructions::batch_auction::settle_batch(context)
+    }
+
+    /// Collect a settled swap intent's output or refund
+    pub fn claim_swap_intent(context: Context<ClaimSwapIntent>) -> Result<()> {
+        instructions::batch_auction::claim_swap_intent(context)
+    }
+
+    /// Escrow tokens to be swapped in equal slices over time
+    pub fn create_dca_schedule(
+        context: Context<CreateDcaSchedule>,
+        schedule_id: u64,
+        amount_per_interval: u64,
+        min_amount_out_per_interval: u64,
+        interval: i64,
+        total_budget: u64,
+    ) -> Result<()> {
+        instructions::dca::create_dca_schedule(
+            context,
+            schedule_id,
+            amount_per_interval,
+            min_amount_out_per_interval,
+            interval,
+            total_budget,
+        )
+    }
+
+    /// Execute the next due slice of a DCA schedule (permissionless)
+    pub fn execute_dca(context: Context<ExecuteDca>) -> Result<()> {
+        instructions::dca::execute_dca(context)
+    }
+
+    /// Cancel a DCA schedule and return the remaining escrow
+    pub fn cancel_dca_schedule(context: Context<CancelDcaSchedule>) -> Result<()> {
+        instructions::dca::cancel_dca_schedule(context)
+    }
+
+    /// Re-solve and cache the pool invariant (permissionless crank)
+    pub fn recompute_invariant(context: Context<RecomputeInvariant>) -> Result<()> {
+        instructions::recompute_invariant::handler(context)
+    }
+
+    /// Set the pool's withdrawal exit fee (config authority)
+    pub fn set_exit_fee(context: Context<UpdatePool>, exit_fee_bps: u64) -> Result<()> {
+        instructions::update_pool::set_exit_fee(context, exit_fee_bps)
+    }
+
+    /// Cap per-wallet deposits until `bootstrap_end_ts` (config authority)
+    pub fn set_bootstrap_limits(
+        context: Context<UpdatePool>,
+        bootstrap_end_ts: i64,
+        max_deposit_per_wallet: u64,
+    ) -> Result<()> {
+        instructions::update_pool::set_bootstrap_limits(
+            context,
+            bootstrap_end_ts,
+            max_deposit_per_wallet,
+        )
+    }
+
+    /// Set the share of swap fees routed to the protocol treasury (config authority)
+    pub fn set_protocol_fee_share(
+        context: Context<UpdatePool>,
+        protocol_fee_share_bps: u64,
+    ) -> Result<()> {
+        instructions::update_pool::set_protocol_fee_share(context, protocol_fee_share_bps)
+    }
+
+    /// Configure staged withdrawals for large exits (config authority)
+    pub fn set_staged_withdraw_config(
+        context: Context<UpdatePool>,
+        threshold_bps: u64,
+        delay: i64,
+    ) -> Result<()> {
+        instructions::update_pool::set_staged_withdraw_config(context, threshold_bps, delay)
+    }
+
+    /// Configure a Growth pool's oracle price band and surcharge (config authority)
+    pub fn set_oracle_band(
+        context: Context<UpdatePool>,
+        band_bps: u64,
+        surcharge_bps: u64,
+    ) -> Result<()> {
+        instructions::update_pool::set_oracle_band(context, band_bps, surcharge_bps)
+    }
+
+    /// Freeze or unfreeze swaps of a pool token (config authority)
+    pub fn set_token_frozen(
+        context: Context<UpdatePool>,
+        token_mint: Pubkey,
+        frozen: bool,
+    ) -> Result<()> {
+        instructions::update_pool::set_token_frozen(context, token_mint, frozen)
+    }
+
+    /// Pause or unpause swaps and deposits on a pool (config authority)
+    pub fn set_pool_paused(context: Context<UpdatePool>, paused: bool) -> Result<()> {
+        instructions::update_pool::set_pool_paused(context, paused)
+    }
+
+    /// Close a frozen token's empty vault and remove the token from a Seed Pool (config authority)
+    pub fn close_pool_vault(context: Context<ClosePoolVault>, token_index: u8) -> Result<()> {
+        instructions::close_pool_vault::close_pool_vault(context, token_index)
+    }
+
+    /// Add a token to a Seed Pool at `target_weight`, seeded with `initial_amount`
+    /// (config authority)
+    pub fn add_pool_token(
+        context: Context<AddPoolToken>,
+        target_weight: u64,
+        initial_amount: u64,
+    ) -> Result<()> {
+        instructions::add_pool_token::add_pool_token(context, target_weight, initial_amount)
+    }
+
+    /// Apply several pool parameter changes atomically, e.g. for a governance
+    /// proposal (config authority)
+    pub fn update_pool_params(
+        context: Context<UpdatePool>,
+        changes: Vec<PoolParamChange>,
+    ) -> Result<()> {
+        instructions::update_pool::update_pool_params(context, changes)
+    }
+
+    /// Trust a relayer to submit user-signed swaps on a pool (config authority)
+    pub fn set_relayer(context: Context<UpdatePool>, relayer: Pubkey) -> Result<()> {
+        instructions::update_pool::set_relayer(context, relayer)
+    }
+
+    /// Pause swaps and deposits on a pool (guardian or config authority)
+    pub fn pause_pool(context: Context<PausePool>) -> Result<()> {
+        instructions::guardian::pause_pool(context)
+    }
+
+    /// Override the pool's dynamic fee curve (config authority)
+    pub fn set_fee_params(context: Context<UpdatePool>, fee_params: FeeParams) -> Result<()> {
+        instructions::update_pool::set_fee_params(context, fee_params)
+    }
+
+    /// Set the smallest swap input the pool accepts (config authority)
+    pub fn set_min_swap_amount(context: Context<UpdatePool>, min_swap_amount: u64) -> Result<()> {
+        instructions::update_pool::set_min_swap_amount(context, min_swap_amount)
+    }
+
+    /// Cap a single swap as a share of the output reserve (config authority)
+    pub fn set_max_trade_bps(context: Context<UpdatePool>, max_trade_bps: u64) -> Result<()> {
+        instructions::update_pool::set_max_trade_bps(context, max_trade_bps)
+    }
+
+    /// Schedule a linear target-weight shift for a Growth Pool (config authority)
+    pub fn set_lbp_schedule(
+        context: Context<UpdatePool>,
+        start_weights: [u64; 2],
+        end_weights: [u64; 2],
+        start_ts: i64,
+        end_ts: i64,
+    ) -> Result<()> {
+        instructions::update_pool::set_lbp_schedule(
+            context,
+            start_weights,
+            end_weights,
+            start_ts,
+            end_ts,
+        )
+    }
+
+    /// Swap between two Growth Pool partner tokens through USD*
+    pub fn swap_partner_to_partner(
+        context: Context<SwapPartnerToPartner>,
+        amount_in: u64,
+        min_amount_out: u64,
+        deadline: i64,
+    ) -> Result<()> {
+        instructions::swap_partner_to_partner::handler(
+            context,
+            amount_in,
+            min_amount_out,
+            deadline,
+        )
+    }
+
+    /// Configure the rebalance keeper, venue and per-epoch cap (config authority)
+    pub fn set_rebalance_config(
+        context: Context<UpdateConfig>,
+        keeper: Pubkey,
+        rebalance_program: Pubkey,
+        max_rebalance_bps: u64,
+    ) -> Result<()> {
+        instructions::update_config::set_rebalance_config(
+            context,
+            keeper,
+            rebalance_program,
+            max_rebalance_bps,
+        )
+    }
+
+    /// Rebalance pool reserves through the whitelisted venue (keeper only)
+    pub fn rebalance<'info>(
+        context: Context<'_, '_, '_, 'info, Rebalance<'info>>,
+        amount_in: u64,
+        min_amount_out: u64,
+        venue_data: Vec<u8>,
+    ) -> Result<()> {
+        instructions::rebalance::handler(context, amount_in, min_amount_out, venue_data)
+    }
+
+    /// Require partner mint approval for Growth Pool creation (config authority)
+    pub fn set_partner_approval_required(
+        context: Context<UpdateConfig>,
+        require_partner_approval: bool,
+    ) -> Result<()> {
+        instructions::update_config::set_partner_approval_required(
+            context,
+            require_partner_approval,
+        )
+    }
+
+    /// Configure permissionless Growth Pool creation and its fees (config authority)
+    pub fn set_pool_creation_config(
+        context: Context<UpdateConfig>,
+        permissionless_pool_creation: bool,
+        pool_creation_fee_lamports: u64,
+        pool_creation_fee_usd_star: u64,
     ) -> Result<()> {
-        instructions::withdraw::handler(context, lp_amount, min_amounts)
+        instructions::update_config::set_pool_creation_config(
+            context,
+            permissionless_pool_creation,
+            pool_creation_fee_lamports,
+            pool_creation_fee_usd_star,
+        )
+    }
+
+    /// Set the governance token locked in vote escrows for fee discounts (config authority)
+    pub fn set_ve_mint(context: Context<UpdateConfig>, ve_mint: Pubkey) -> Result<()> {
+        instructions::update_config::set_ve_mint(context, ve_mint)
+    }
+
+    /// Set the protocol-fee tip paid to permissionless crank callers (config authority)
+    pub fn set_crank_tip(
+        context: Context<UpdateConfig>,
+        crank_tip_amount: u64,
+        max_crank_tips_per_slot: u64,
+    ) -> Result<()> {
+        instructions::update_config::set_crank_tip(
+            context,
+            crank_tip_amount,
+            max_crank_tips_per_slot,
+        )
+    }
+
+    /// Set the oracle age past which swaps degrade to the max fee or revert (config authority)
+    pub fn set_stale_pool_protection(
+        context: Context<UpdateConfig>,
+        max_oracle_age_slots: u64,
+        reject_stale_swaps: bool,
+    ) -> Result<()> {
+        instructions::update_config::set_stale_pool_protection(
+            context,
+            max_oracle_age_slots,
+            reject_stale_swaps,
+        )
+    }
+
+    /// Set the protocol share of swap fees for every pool without its own (config authority)
+    pub fn set_config_protocol_fee_share(
+        context: Context<UpdateConfig>,
+        protocol_fee_share_bps: u64,
+    ) -> Result<()> {
+        instructions::update_config::set_config_protocol_fee_share(context, protocol_fee_share_bps)
+    }
+
+    /// Propose a new config authority, which may be a PDA (config authority)
+    pub fn propose_authority(context: Context<UpdateConfig>, new_authority: Pubkey) -> Result<()> {
+        instructions::update_config::propose_authority(context, new_authority)
+    }
+
+    /// Allow a key to pause this config's pools (config authority)
+    pub fn add_guardian(context: Context<UpdateConfig>, guardian: Pubkey) -> Result<()> {
+        instructions::update_config::add_guardian(context, guardian)
+    }
+
+    /// Revoke a pause guardian (config authority)
+    pub fn remove_guardian(context: Context<UpdateConfig>, guardian: Pubkey) -> Result<()> {
+        instructions::update_config::remove_guardian(context, guardian)
+    }
+
+    /// Whitelist an external program as a rebalance venue or swap hook (config authority)
+    pub fn add_whitelisted_program(
+        context: Context<UpdateConfig>,
+        program_id: Pubkey,
+        kind: CpiTargetKind,
+    ) -> Result<()> {
+        instructions::update_config::add_whitelisted_program(context, program_id, kind)
+    }
+
+    /// Remove an external program from the CPI whitelist (config authority)
+    pub fn remove_whitelisted_program(
+        context: Context<UpdateConfig>,
+        program_id: Pubkey,
+        kind: CpiTargetKind,
+    ) -> Result<()> {
+        instructions::update_config::remove_whitelisted_program(context, program_id, kind)
+    }
+
+    /// Accept a proposed config authority handoff (pending authority)
+    pub fn accept_authority(context: Context<AcceptAuthority>) -> Result<()> {
+        instructions::update_config::accept_authority(context)
+    }
+
+    /// Approve a partner mint for Growth Pool creation (config authority)
+    pub fn approve_partner_mint(context: Context<ApprovePartnerMint>) -> Result<()> {
+        instructions::partner_approval::approve_partner_mint(context)
+    }
+
+    /// Revoke a partner mint approval (config authority)
+    pub fn revoke_partner_mint(context: Context<RevokePartnerMint>) -> Result<()> {
+        instructions::partner_approval::revoke_partner_mint(context)
+    }
+
+    /// Return a snapshot of the pool state (view, simulate only)
+    pub fn get_pool_state(context: Context<ViewPool>) -> Result<PoolStateView> {
+        instructions::views::get_pool_state(context)
+    }
+
+    /// Return the pool LP virtual price, 1e18 fixed point (view, simulate only)
+    pub fn get_virtual_price(context: Context<ViewPool>) -> Result<u128> {
+        instructions::views::get_virtual_price(context)
+    }
+
+    /// Quote `amount_in` against every other pool token (view, simulate only)
+    pub fn get_amounts_out(
+        context: Context<ViewPool>,
+        token_mint_in: Pubkey,
+        amount_in: u64,
+    ) -> Result<Vec<u64>> {
+        instructions::views::get_amounts_out(context, token_mint_in, amount_in)
+    }
+
+    /// Return the fee a swap would pay and the pool weights it leaves (view, simulate only)
+    pub fn quote_fee(
+        context: Context<ViewPool>,
+        token_in_index: u8,
+        token_out_index: u8,
+        amount_in: u64,
+    ) -> Result<FeeQuoteView> {
+        instructions::views::quote_fee(context, token_in_index, token_out_index, amount_in)
+    }
+
+    /// Return the input a pair absorbs at 10, 50 and 100 bps price impact (view, simulate only)
+    pub fn get_depth(
+        context: Context<ViewPool>,
+        token_in_index: u8,
+        token_out_index: u8,
+    ) -> Result<DepthView> {
+        instructions::views::get_depth(context, token_in_index, token_out_index)
+    }
+
+    /// Create the LP price oracle adapter for a pool
+    pub fn init_lp_oracle(context: Context<InitLpOracle>) -> Result<()> {
+        instructions::lp_oracle::init_lp_oracle(context)
+    }
+
+    /// Publish a lower-bound LP price from token prices (keeper only)
+    pub fn update_lp_oracle(context: Context<UpdateLpOracle>, token_prices: Vec<u64>) -> Result<()> {
+        instructions::lp_oracle::update_lp_oracle(context, token_prices)
+    }
+
+    /// Set or clear the delegate allowed to manage a position
+    pub fn set_position_delegate(
+        context: Context<SetPositionDelegate>,
+        delegate: Option<Pubkey>,
+    ) -> Result<()> {
+        instructions::position_delegate::handler(context, delegate)
+    }
+
+    /// Move part of a position and its LP tokens to another wallet's position in the pool
+    pub fn transfer_position(context: Context<TransferPosition>, amount: u64) -> Result<()> {
+        instructions::transfer_position::handler(context, amount)
+    }
+
+    /// Create the owner's portfolio, indexing the positions passed as remaining accounts
+    pub fn init_user_portfolio<'info>(
+        context: Context<'_, '_, 'info, 'info, InitUserPortfolio<'info>>,
+    ) -> Result<()> {
+        instructions::portfolio::init_user_portfolio(context)
+    }
+
+    /// Refresh a portfolio's entries for the positions passed as remaining accounts
+    pub fn sync_user_portfolio<'info>(
+        context: Context<'_, '_, 'info, 'info, SyncUserPortfolio<'info>>,
+    ) -> Result<()> {
+        instructions::portfolio::sync_user_portfolio(context)
+    }
+
+    /// Create the pool's autocompounder vault and share mint (config authority)
+    pub fn init_autocompounder(context: Context<InitAutocompounder>) -> Result<()> {
+        instructions::autocompounder::init_autocompounder(context)
+    }
+
+    /// Deposit LP into the autocompounder for shares
+    pub fn deposit_to_autocompounder(
+        context: Context<AutocompounderShares>,
+        lp_amount: u64,
+        min_shares_out: u64,
+    ) -> Result<()> {
+        instructions::autocompounder::deposit_to_autocompounder(context, lp_amount, min_shares_out)
+    }
+
+    /// Burn autocompounder shares for their LP
+    pub fn withdraw_from_autocompounder(
+        context: Context<AutocompounderShares>,
+        shares: u64,
+    ) -> Result<()> {
+        instructions::autocompounder::withdraw_from_autocompounder(context, shares)
+    }
+
+    /// Redeposit the autocompounder's rewards as liquidity (keeper only)
+    pub fn compound(context: Context<Compound>) -> Result<()> {
+        instructions::autocompounder::compound(context)
+    }
+
+    /// Whitelist a program to receive post-swap callbacks (config authority)
+    pub fn approve_swap_hook(context: Context<ApproveSwapHook>) -> Result<()> {
+        instructions::swap_hook::approve_swap_hook(context)
+    }
+
+    /// Remove a program from the swap hook whitelist (config authority)
+    pub fn revoke_swap_hook(context: Context<RevokeSwapHook>) -> Result<()> {
+        instructions::swap_hook::revoke_swap_hook(context)
+    }
+
+    /// Create the fee sample buffer used for a pool's realized APY
+    pub fn init_pool_stats(context: Context<InitPoolStats>) -> Result<()> {
+        instructions::pool_stats::init_pool_stats(context)
+    }
+
+    /// Sample a pool's cumulative fees, at most hourly (permissionless crank)
+    pub fn record_pool_stats(context: Context<RecordPoolStats>) -> Result<()> {
+        instructions::pool_stats::record_pool_stats(context)
+    }
+
+    /// Create the live statistics account partner projects embed for a Growth pool
+    pub fn init_partner_stats(context: Context<InitPartnerStats>) -> Result<()> {
+        instructions::partner_stats::init_partner_stats(context)
+    }
+
+    /// Republish a Growth pool's 24h volume, fees, APR and depth, at most hourly (permissionless crank)
+    pub fn refresh_partner_stats(context: Context<RefreshPartnerStats>) -> Result<()> {
+        instructions::partner_stats::refresh_partner_stats(context)
+    }
+
+    /// Create the compact summary account front ends read for a pool
+    pub fn init_pool_snapshot(context: Context<InitPoolSnapshot>) -> Result<()> {
+        instructions::pool_snapshot::init_pool_snapshot(context)
+    }
+
+    /// Catch a pool's snapshot up after handlers that do not refresh it (permissionless crank)
+    pub fn refresh_pool_snapshot(context: Context<RefreshPoolSnapshot>) -> Result<()> {
+        instructions::pool_snapshot::refresh_pool_snapshot(context)
+    }
+
+    /// Return rolling 24h/7d fees and realized APY (view, simulate only)
+    pub fn get_pool_apy(context: Context<ViewPoolStats>) -> Result<PoolApyView> {
+        instructions::views::get_pool_apy(context)
+    }
+
+    /// Create the protocol treasury (config authority)
+    pub fn init_treasury(context: Context<InitTreasury>) -> Result<()> {
+        instructions::treasury::init_treasury(context)
+    }
+
+    /// Create the treasury's token vault for a mint
+    pub fn init_treasury_vault(context: Context<InitTreasuryVault>) -> Result<()> {
+        instructions::treasury::init_treasury_vault(context)
+    }
+
+    /// Move a pool's accrued protocol fees to the treasury (permissionless crank)
+    pub fn collect_protocol_fees(context: Context<CollectProtocolFees>) -> Result<()> {
+        instructions::treasury::collect_protocol_fees(context)
+    }
+
+    /// Withdraw protocol fees from a treasury vault (config authority)
+    pub fn sweep_treasury(context: Context<SweepTreasury>, amount: u64) -> Result<()> {
+        instructions::treasury::sweep_treasury(context, amount)
+    }
+
+    /// Move reserves left in a pool with no LP supply to the treasury (config authority)
+    pub fn recover_orphaned_reserves(context: Context<RecoverOrphanedReserves>) -> Result<()> {
+        instructions::orphaned_reserves::recover_orphaned_reserves(context)
+    }
+
+    /// Open a referral account that swaps can accrue front-end fees in one pool token to
+    pub fn register_referral(context: Context<RegisterReferral>) -> Result<()> {
+        instructions::referral::register_referral(context)
+    }
+
+    /// Pay out a referral account's accrued fees (referrer)
+    pub fn claim_referral_fees(context: Context<ClaimReferralFees>) -> Result<()> {
+        instructions::referral::claim_referral_fees(context)
+    }
+
+    /// Stage a withdrawal of a large share of the pool, executable after the pool's delay
+    pub fn request_withdraw(context: Context<RequestWithdraw>, lp_amount: u64) -> Result<()> {
+        instructions::staged_withdraw::request_withdraw(context, lp_amount)
+    }
+
+    /// Execute a staged withdrawal proportionally, exempt from the exit fee
+    pub fn execute_withdraw(
+        context: Context<ExecuteWithdraw>,
+        min_amounts: [u64; MAX_POOL_TOKENS],
+        deadline: i64,
+    ) -> Result<()> {
+        instructions::staged_withdraw::execute_withdraw(context, min_amounts, deadline)
+    }
+
+    /// Drop a staged withdrawal
+    pub fn cancel_withdraw(context: Context<CancelWithdraw>) -> Result<()> {
+        instructions::staged_withdraw::cancel_withdraw(context)
+    }
+
+    /// Book a withdrawal while a pool vault is short, queueing the tokens it is owed
+    pub fn queue_withdraw(
+        context: Context<QueueWithdraw>,
+        lp_amount: u64,
+        min_amounts: [u64; MAX_POOL_TOKENS],
+        deadline: i64,
+    ) -> Result<()> {
+        instructions::withdraw_queue::queue_withdraw(context, lp_amount, min_amounts, deadline)
+    }
+
+    /// Pay the withdraw request at the head of a pool's queue (permissionless)
+    pub fn fulfill_withdraw_request(context: Context<FulfillWithdrawRequest>) -> Result<()> {
+        instructions::withdraw_queue::fulfill_withdraw_request(context)
+    }
+
+    /// Create the program version and feature flag account (upgrade authority)
+    pub fn init_program_state(context: Context<InitProgramState>) -> Result<()> {
+        instructions::program_state::init_program_state(context)
+    }
+
+    /// Enable or disable experimental features (upgrade authority)
+    pub fn set_feature_flags(
+        context: Context<UpdateProgramState>,
+        feature_flags: u64,
+    ) -> Result<()> {
+        instructions::program_state::set_feature_flags(context, feature_flags)
+    }
+
+    /// Record the newly deployed program version (upgrade authority)
+    pub fn record_upgrade(context: Context<UpdateProgramState>) -> Result<()> {
+        instructions::program_state::record_upgrade(context)
+    }
+
+    /// Lock governance tokens (or extend a lock) for swap and exit fee discounts
+    pub fn lock_ve_tokens(
+        context: Context<LockVeTokens>,
+        amount: u64,
+        unlock_ts: i64,
+    ) -> Result<()> {
+        instructions::vote_escrow::lock_ve_tokens(context, amount, unlock_ts)
+    }
+
+    /// Withdraw governance tokens from an expired lock
+    pub fn unlock_ve_tokens(context: Context<UnlockVeTokens>) -> Result<()> {
+        instructions::vote_escrow::unlock_ve_tokens(context)
+    }
+
+    /// Record the caller's LP and lock weight in a pool for a governance snapshot slot
+    pub fn checkpoint_voting_power(
+        context: Context<CheckpointVotingPower>,
+        snapshot_slot: u64,
+    ) -> Result<()> {
+        instructions::voting_snapshot::checkpoint_voting_power(context, snapshot_slot)
+    }
+
+    /// Lock Growth pool LP on a cliff and linear vesting schedule, e.g. for a
+    /// partner team committing liquidity
+    pub fn lock_liquidity(
+        context: Context<LockLiquidity>,
+        lock_id: u64,
+        lp_amount: u64,
+        duration: i64,
+        cliff_duration: i64,
+    ) -> Result<()> {
+        instructions::liquidity_lock::lock_liquidity(
+            context,
+            lock_id,
+            lp_amount,
+            duration,
+            cliff_duration,
+        )
+    }
+
+    /// Withdraw the vested part of a liquidity lock
+    pub fn release_liquidity(context: Context<ReleaseLiquidity>) -> Result<()> {
+        instructions::liquidity_lock::release_liquidity(context)
+    }
+
+    /// Return a liquidity lock's schedule and vested amount (view, simulate only)
+    pub fn get_lock_expiry(context: Context<ViewLockedPosition>) -> Result<LockExpiryView> {
+        instructions::views::get_lock_expiry(context)
+    }
+
+    /// Add a wallet to the exit fee exemption list (config authority)
+    pub fn add_fee_exemption(context: Context<AddFeeExemption>) -> Result<()> {
+        instructions::vote_escrow::add_fee_exemption(context)
+    }
+
+    /// Remove a wallet from the exit fee exemption list (config authority)
+    pub fn remove_fee_exemption(context: Context<RemoveFeeExemption>) -> Result<()> {
+        instructions::vote_escrow::remove_fee_exemption(context)
     }
 
-    pub fn swap(context: Context<Swap>, amount_in: u64, min_amount_out: u64) -> Result<()> {
-        instructions::swap::handler(context, amount_in, min_amount_out)
+    /// Publish a Growth pool's partner token price for its oracle band (keeper)
+    pub fn update_partner_price(context: Context<UpdatePartnerPrice>, price: u64) -> Result<()> {
+        instructions::partner_oracle::update_partner_price(context, price)
     }
 }
@@ -0,0 +1,105 @@
+use anchor_lang::prelude::*;
+
+/// Virtual shares and LP added to both sides of the share price
+///
+/// They make the first depositor's shares worth their LP at 1:1, and make
+/// inflating the price against later depositors cost the attacker about
+/// `AUTOCOMPOUNDER_VIRTUAL_SHARES` times what it takes from them.
+pub const AUTOCOMPOUNDER_VIRTUAL_SHARES: u64 = 1_000;
+
+/// Auto-compounding LP vault for a single pool
+///
+/// Holds pool LP tokens in `lp_vault` and issues `share_mint` tokens against
+/// them. Rewards sent to the autocompounder's token accounts are redeposited as
+/// liquidity by the keeper, so each share redeems for a growing amount of LP.
+/// Vault LP belongs to no position: depositors' positions are debited on the way
+/// in and credited with the redeemed LP, compounding included, on the way out.
+#[account]
+pub struct Autocompounder {
+    /// Bump seed for PDA
+    pub bump: u8,
+
+    /// Pool whose LP token this vault compounds
+    pub pool: Pubkey,
+
+    /// Share token issued to depositors
+    pub share_mint: Pubkey,
+
+    /// LP token account owned by this autocompounder
+    pub lp_vault: Pubkey,
+
+    /// LP held for shareholders; tracked here so LP sent straight to `lp_vault`
+    /// cannot move the share price
+    pub total_lp: u64,
+
+    /// LP minted into the vault by compounding over its lifetime
+    pub total_compounded_lp: u64,
+
+    /// Number of successful compound cranks
+    pub compound_count: u64,
+
+    /// Timestamp of the last compound
+    pub last_compound_ts: i64,
+}
+
+impl Autocompounder {
+    pub fn space() -> usize {
+        8 + // discriminator
+        1 + // bump
+        32 + // pool
+        32 + // share_mint
+        32 + // lp_vault
+        8 + // total_lp
+        8 + // total_compounded_lp
+        8 + // compound_count
+        8 // last_compound_ts
+    }
+
+    /// Shares minted for `lp_amount` added to a vault holding `vault_lp` against `share_supply`
+    pub fn shares_for_lp(lp_amount: u64, vault_lp: u64, share_supply: u64) -> Option<u64> {
+        let virtual_supply = share_supply as u128 + AUTOCOMPOUNDER_VIRTUAL_SHARES as u128;
+        let virtual_lp = vault_lp as u128 + AUTOCOMPOUNDER_VIRTUAL_SHARES as u128;
+        u64::try_from(lp_amount as u128 * virtual_supply / virtual_lp).ok()
+    }
+
+    /// LP redeemed for burning `shares` out of `share_supply` against a vault holding `vault_lp`
+    pub fn lp_for_shares(shares: u64, vault_lp: u64, share_supply: u64) -> Option<u64> {
+        if shares > share_supply {
+            return None;
+        }
+        let virtual_supply = share_supply as u128 + AUTOCOMPOUNDER_VIRTUAL_SHARES as u128;
+        let virtual_lp = vault_lp as u128 + AUTOCOMPOUNDER_VIRTUAL_SHARES as u128;
+        u64::try_from(shares as u128 * virtual_lp / virtual_supply).ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn first_deposit_mints_shares_one_for_one() {
+        assert_eq!(Autocompounder::shares_for_lp(1_000_000, 0, 0), Some(1_000_000));
+        assert_eq!(Autocompounder::shares_for_lp(1, 0, 0), Some(1));
+    }
+
+    #[test]
+    fn inflating_the_share_price_costs_the_attacker_the_donation() {
+        // One share, then a donation compounded into the vault
+        let donation = 1_000_000_000;
+        let vault_lp = 1 + donation;
+        let victim_shares = Autocompounder::shares_for_lp(donation, vault_lp, 1).unwrap();
+        assert!(victim_shares > 0);
+
+        // The attacker's share redeems for a sliver of what they gave up
+        let attacker_lp =
+            Autocompounder::lp_for_shares(1, vault_lp + donation, 1 + victim_shares).unwrap();
+        assert!(attacker_lp < donation / 100);
+    }
+
+    #[test]
+    fn redemption_is_capped_by_the_share_supply() {
+        assert_eq!(Autocompounder::lp_for_shares(1, 100, 0), None);
+        assert!(Autocompounder::lp_for_shares(100, 150, 100).unwrap() <= 150);
+    }
+}
@@ -1,11 +1,47 @@
 use anchor_lang::prelude::*;
 
+/// Layout version written by `initialize` and reached by `migrate_config`
+///
+/// 0 marks configs created before versioning; bump this and add a step to
+/// `migrate_config` whenever a field is added
+pub const CONFIG_VERSION: u8 = 2;
+
+/// Zeroed bytes at the end of every config. New fixed-size fields take their
+/// bytes from here, so later versions migrate without a realloc
+pub const CONFIG_RESERVED_BYTES: usize = 24;
+
+/// Most pause guardians a config can list
+pub const MAX_GUARDIANS: usize = 5;
+
+/// Most external programs a config may whitelist as CPI targets
+pub const MAX_WHITELISTED_PROGRAMS: usize = 8;
+
+/// Role an external program is whitelisted for
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum CpiTargetKind {
+    /// AMM the keeper may `rebalance` through
+    RebalanceVenue,
+    /// Program swaps may call back after settling
+    SwapHook,
+}
+
+/// External program the config's pools may CPI into, and for what
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct WhitelistedProgram {
+    pub program_id: Pubkey,
+    pub kind: CpiTargetKind,
+}
+
 #[account]
 #[derive(Default)]
 pub struct AmmConfig {
     /// Bump seed for PDA
     pub bump: u8,
     
+    /// Namespace of this config, part of its PDA seeds
+    /// Lets independent deployments (e.g. a partner-managed instance) share one program
+    pub index: u16,
+    
     /// Authority that can update the config
     pub authority: Pubkey,
     
@@ -18,4 +54,108 @@ pub struct AmmConfig {
     /// Default target weights for the Seed Pool (in basis points, sum must be 10000)
     /// [USDC weight, USDT weight, PYUSD weight]
     pub default_target_weights: [u64; 3],
+    
+    /// Keeper allowed to run `rebalance`
+    pub keeper: Pubkey,
+    
+    /// External AMM program whitelisted as the rebalance venue
+    pub rebalance_program: Pubkey,
+    
+    /// Maximum share of a reserve the keeper may rebalance per epoch (in basis points)
+    pub max_rebalance_bps: u64,
+    
+    /// Whether Growth pools can only be created for approved partner mints
+    pub require_partner_approval: bool,
+    
+    /// Whether anyone can create a Growth pool by paying the creation fee
+    pub permissionless_pool_creation: bool,
+    
+    /// SOL fee paid to `fee_recipient` for permissionless pool creation (in lamports)
+    pub pool_creation_fee_lamports: u64,
+    
+    /// USD* fee paid to `fee_recipient` for permissionless pool creation
+    pub pool_creation_fee_usd_star: u64,
+    
+    /// Authority proposed by `propose_authority`, set once it signs `accept_authority`
+    /// May be a PDA such as a multisig vault; `Pubkey::default()` when none is pending
+    pub pending_authority: Pubkey,
+    
+    /// Governance token locked in vote escrows for fee discounts
+    /// `Pubkey::default()` disables locking
+    pub ve_mint: Pubkey,
+    
+    /// Protocol-fee tip paid to whoever runs a permissionless crank, in pool token units
+    /// 0 disables tips
+    pub crank_tip_amount: u64,
+    
+    /// Most cranks tipped per pool in a single slot
+    pub max_crank_tips_per_slot: u64,
+    
+    /// Slots a Growth pool's partner oracle price may go unrefreshed before swaps treat it as stale
+    /// 0 disables the check
+    pub max_oracle_age_slots: u64,
+    
+    /// Whether swaps against a stale pool are rejected rather than charged the pool's max fee
+    pub reject_stale_swaps: bool,
+    
+    /// Security council keys that can pause pools, but not unpause them or change
+    /// any parameter; at most `MAX_GUARDIANS`
+    pub guardians: Vec<Pubkey>,
+    
+    /// Every external program the config's pools may CPI into; at most
+    /// `MAX_WHITELISTED_PROGRAMS`
+    pub whitelisted_programs: Vec<WhitelistedProgram>,
+    
+    /// Layout version, `CONFIG_VERSION` once initialized or migrated
+    pub version: u8,
+    
+    /// Share of each swap fee owed to the protocol treasury in every pool that does not
+    /// set its own (in basis points, at most `MAX_PROTOCOL_FEE_SHARE_BPS`)
+    pub protocol_fee_share_bps: u64,
+    
+    /// Headroom for future fields, always zero
+    pub reserved: [u8; CONFIG_RESERVED_BYTES],
+}
+
+impl AmmConfig {
+    pub fn space() -> usize {
+        8 + // discriminator
+        1 + // bump
+        2 + // index
+        32 + // authority
+        32 + // fee_recipient
+        8 + // default_amplification
+        (3 * 8) + // default_target_weights
+        32 + // keeper
+        32 + // rebalance_program
+        8 + // max_rebalance_bps
+        1 + // require_partner_approval
+        1 + // permissionless_pool_creation
+        8 + // pool_creation_fee_lamports
+        8 + // pool_creation_fee_usd_star
+        32 + // pending_authority
+        32 + // ve_mint
+        8 + // crank_tip_amount
+        8 + // max_crank_tips_per_slot
+        8 + // max_oracle_age_slots
+        1 + // reject_stale_swaps
+        4 + (32 * MAX_GUARDIANS) + // guardians
+        4 + (33 * MAX_WHITELISTED_PROGRAMS) + // whitelisted_programs
+        1 + // version
+        8 + // protocol_fee_share_bps
+        CONFIG_RESERVED_BYTES // reserved
+    }
+    
+    /// Whether `key` may pause this config's pools
+    pub fn can_pause(&self, key: &Pubkey) -> bool {
+        *key == self.authority || self.guardians.contains(key)
+    }
+    
+    /// Whether `program_id` is registered as a CPI target of `kind`
+    pub fn is_whitelisted(&self, program_id: &Pubkey, kind: CpiTargetKind) -> bool {
+        self.whitelisted_programs.contains(&WhitelistedProgram {
+            program_id: *program_id,
+            kind,
+        })
+    }
 }
\ No newline at end of file
@@ -0,0 +1,77 @@
+use anchor_lang::prelude::*;
+
+/// Share of each DCA swap output paid to the cranker, in basis points
+pub const DCA_KEEPER_TIP_BPS: u64 = 10; // 0.1%
+
+#[account]
+pub struct DcaSchedule {
+    /// Bump seed for PDA
+    pub bump: u8,
+
+    /// Bump seed for the escrow token account
+    pub escrow_bump: u8,
+
+    /// Schedule owner
+    pub owner: Pubkey,
+
+    /// Pool the swaps execute against
+    pub pool: Pubkey,
+
+    /// Client-chosen schedule identifier, unique per owner and pool
+    pub schedule_id: u64,
+
+    /// Token spent by the schedule
+    pub token_mint_in: Pubkey,
+
+    /// Token bought by the schedule
+    pub token_mint_out: Pubkey,
+
+    /// Escrow token account holding the remaining budget
+    pub escrow: Pubkey,
+
+    /// Amount of token_in swapped per execution
+    pub amount_per_interval: u64,
+
+    /// Minimum token_out accepted per full interval amount
+    pub min_amount_out_per_interval: u64,
+
+    /// Seconds between executions
+    pub interval: i64,
+
+    /// Remaining token_in budget
+    pub remaining_budget: u64,
+
+    /// Earliest timestamp of the next execution
+    pub next_execution_ts: i64,
+
+    /// Number of executions performed
+    pub executions: u64,
+
+    /// Creation timestamp
+    pub created_at: i64,
+
+    /// Slot of the latest execution; at most one execution lands per slot
+    pub last_execution_slot: u64,
+}
+
+impl DcaSchedule {
+    pub fn space() -> usize {
+        8 + // discriminator
+        1 + // bump
+        1 + // escrow_bump
+        32 + // owner
+        32 + // pool
+        8 + // schedule_id
+        32 + // token_mint_in
+        32 + // token_mint_out
+        32 + // escrow
+        8 + // amount_per_interval
+        8 + // min_amount_out_per_interval
+        8 + // interval
+        8 + // remaining_budget
+        8 + // next_execution_ts
+        8 + // executions
+        8 + // created_at
+        8 // last_execution_slot
+    }
+}
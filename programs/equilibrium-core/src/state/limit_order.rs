@@ -0,0 +1,54 @@
+use anchor_lang::prelude::*;
+
+#[account]
+pub struct LimitOrder {
+    /// Bump seed for PDA
+    pub bump: u8,
+
+    /// Bump seed for the escrow token account
+    pub escrow_bump: u8,
+
+    /// Order owner
+    pub owner: Pubkey,
+
+    /// Pool the order executes against
+    pub pool: Pubkey,
+
+    /// Client-chosen order identifier, unique per owner and pool
+    pub order_id: u64,
+
+    /// Token escrowed by the order
+    pub token_mint_in: Pubkey,
+
+    /// Token the owner wants to receive
+    pub token_mint_out: Pubkey,
+
+    /// Escrow token account holding `amount_in`
+    pub escrow: Pubkey,
+
+    /// Amount of token_in escrowed
+    pub amount_in: u64,
+
+    /// Limit: minimum amount of token_out the order accepts
+    pub min_amount_out: u64,
+
+    /// Creation timestamp
+    pub created_at: i64,
+}
+
+impl LimitOrder {
+    pub fn space() -> usize {
+        8 + // discriminator
+        1 + // bump
+        1 + // escrow_bump
+        32 + // owner
+        32 + // pool
+        8 + // order_id
+        32 + // token_mint_in
+        32 + // token_mint_out
+        32 + // escrow
+        8 + // amount_in
+        8 + // min_amount_out
+        8 // created_at
+    }
+}
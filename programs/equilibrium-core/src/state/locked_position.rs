@@ -0,0 +1,124 @@
+use anchor_lang::prelude::*;
+
+/// Longest schedule a liquidity lock accepts
+pub const MAX_LIQUIDITY_LOCK_DURATION: i64 = 10 * 365 * 86_400;
+
+/// LP tokens escrowed on a cliff and vesting schedule
+///
+/// Nothing vests before `cliff_ts`; after it the lock releases linearly from
+/// `start_ts` until everything is free at `end_ts`. A cliff equal to the
+/// duration is a plain time lock. The locked LP is taken out of the owner's
+/// `UserPosition` and credited back as it is released.
+#[account]
+pub struct LockedPosition {
+    /// Bump seed for PDA
+    pub bump: u8,
+
+    /// Wallet that locked the LP and receives it as it vests
+    pub owner: Pubkey,
+
+    /// Pool whose LP is locked
+    pub pool: Pubkey,
+
+    /// Client-chosen lock identifier, unique per owner and pool
+    pub lock_id: u64,
+
+    /// Token account holding the locked LP, owned by this PDA
+    pub vault: Pubkey,
+
+    /// LP locked in total
+    pub amount: u64,
+
+    /// LP already released to the owner
+    pub released: u64,
+
+    /// Timestamp the lock was created
+    pub start_ts: i64,
+
+    /// Timestamp before which nothing can be released
+    pub cliff_ts: i64,
+
+    /// Timestamp at which the whole amount is released
+    pub end_ts: i64,
+}
+
+impl LockedPosition {
+    pub fn space() -> usize {
+        8 + // discriminator
+        1 + // bump
+        32 + // owner
+        32 + // pool
+        8 + // lock_id
+        32 + // vault
+        8 + // amount
+        8 + // released
+        8 + // start_ts
+        8 + // cliff_ts
+        8 // end_ts
+    }
+
+    /// LP vested at `now`, released or not
+    pub fn vested_amount(&self, now: i64) -> u64 {
+        if now < self.cliff_ts {
+            return 0;
+        }
+        if now >= self.end_ts {
+            return self.amount;
+        }
+        let elapsed = now.saturating_sub(self.start_ts) as u128;
+        let duration = self.end_ts.saturating_sub(self.start_ts) as u128;
+        (self.amount as u128 * elapsed / duration) as u64
+    }
+
+    /// LP the owner can release at `now`
+    pub fn releasable_amount(&self, now: i64) -> u64 {
+        self.vested_amount(now).saturating_sub(self.released)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn locked(amount: u64, cliff_ts: i64, end_ts: i64) -> LockedPosition {
+        LockedPosition {
+            bump: 0,
+            owner: Pubkey::default(),
+            pool: Pubkey::default(),
+            lock_id: 0,
+            vault: Pubkey::default(),
+            amount,
+            released: 0,
+            start_ts: 0,
+            cliff_ts,
+            end_ts,
+        }
+    }
+
+    #[test]
+    fn vests_linearly_from_start_once_past_the_cliff() {
+        let lock = locked(1_200, 300, 1_200);
+        assert_eq!(lock.vested_amount(0), 0);
+        assert_eq!(lock.vested_amount(299), 0);
+        // Crossing the cliff releases everything accrued since the start
+        assert_eq!(lock.vested_amount(300), 300);
+        assert_eq!(lock.vested_amount(900), 900);
+        assert_eq!(lock.vested_amount(1_200), 1_200);
+        assert_eq!(lock.vested_amount(i64::MAX), 1_200);
+    }
+
+    #[test]
+    fn releasable_excludes_what_was_already_released() {
+        let mut lock = locked(1_000, 1_000, 1_000);
+        assert_eq!(lock.releasable_amount(999), 0);
+        assert_eq!(lock.releasable_amount(1_000), 1_000);
+
+        let mut vesting = locked(u64::MAX, 0, 2);
+        vesting.released = u64::MAX / 2;
+        assert_eq!(vesting.releasable_amount(1), 0);
+        assert_eq!(vesting.releasable_amount(2), u64::MAX - u64::MAX / 2);
+
+        lock.released = 1_000;
+        assert_eq!(lock.releasable_amount(5_000), 0);
+    }
+}
@@ -1,9 +1,49 @@
+pub mod autocompounder;
 pub mod config;
+pub mod dca;
+pub mod limit_order;
+pub mod locked_position;
+pub mod oracle;
+pub mod partner;
+pub mod partner_stats;
 pub mod pool;
+pub mod pool_snapshot;
+pub mod pool_stats;
+pub mod portfolio;
+pub mod program_state;
+pub mod referral;
+pub mod relay;
+pub mod staged_withdrawal;
+pub mod swap_batch;
+pub mod swap_hook;
+pub mod treasury;
 pub mod user;
-pub mod math;
+pub mod vote_escrow;
+pub mod voting_snapshot;
+pub mod withdraw_queue;
+pub use equilibrium_math::{math, rounding};
 
+pub use autocompounder::*;
 pub use config::*;
+pub use dca::*;
+pub use limit_order::*;
+pub use locked_position::*;
+pub use oracle::*;
+pub use partner::*;
+pub use partner_stats::*;
 pub use pool::*;
+pub use pool_snapshot::*;
+pub use pool_stats::*;
+pub use portfolio::*;
+pub use program_state::*;
+pub use referral::*;
+pub use relay::*;
+pub use staged_withdrawal::*;
+pub use swap_batch::*;
+pub use swap_hook::*;
+pub use treasury::*;
 pub use user::*;
+pub use vote_escrow::*;
+pub use voting_snapshot::*;
+pub use withdraw_queue::*;
 pub use math::*;
\ No newline at end of file
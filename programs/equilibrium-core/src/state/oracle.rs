@@ -0,0 +1,55 @@
+use anchor_lang::prelude::*;
+
+/// Exponent of `LpOracleAdapter::price`, matching the usual 8-decimal USD feeds
+pub const LP_ORACLE_EXPO: i32 = -8;
+
+pub use equilibrium_math::pricing::MAX_PARTNER_PRICE_AGE;
+
+/// Lower-bound LP token price published for lending markets
+///
+/// Laid out like a Pyth price (`price * 10^expo`, `conf`, `publish_time`) so
+/// existing oracle readers can consume it with minimal glue.
+#[account]
+pub struct LpOracleAdapter {
+    /// Bump seed for PDA
+    pub bump: u8,
+
+    /// Pool this adapter prices
+    pub pool: Pubkey,
+
+    /// Lower-bound LP price: cheapest pool token price × virtual price
+    pub price: i64,
+
+    /// Spread between the cheapest and richest pool token, scaled to LP units
+    pub conf: u64,
+
+    /// Price exponent
+    pub expo: i32,
+
+    /// Cheapest pool token price used for `price`
+    pub min_token_price: u64,
+
+    /// Pool virtual price used for `price`, at D solved from the reserves (1e18 fixed point)
+    pub virtual_price: u128,
+
+    /// Slot of the last update
+    pub publish_slot: u64,
+
+    /// Timestamp of the last update
+    pub publish_time: i64,
+}
+
+impl LpOracleAdapter {
+    pub fn space() -> usize {
+        8 + // discriminator
+        1 + // bump
+        32 + // pool
+        8 + // price
+        8 + // conf
+        4 + // expo
+        8 + // min_token_price
+        16 + // virtual_price
+        8 + // publish_slot
+        8 // publish_time
+    }
+}
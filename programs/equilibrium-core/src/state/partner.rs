@@ -0,0 +1,26 @@
+use anchor_lang::prelude::*;
+
+#[account]
+pub struct PartnerApproval {
+    /// Bump seed for PDA
+    pub bump: u8,
+
+    /// Partner token approved for pairing against USD*
+    pub partner_mint: Pubkey,
+
+    /// Config authority that approved the mint
+    pub approved_by: Pubkey,
+
+    /// Timestamp of the approval
+    pub approved_at: i64,
+}
+
+impl PartnerApproval {
+    pub fn space() -> usize {
+        8 + // discriminator
+        1 + // bump
+        32 + // partner_mint
+        32 + // approved_by
+        8 // approved_at
+    }
+}
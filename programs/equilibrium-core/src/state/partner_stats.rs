@@ -0,0 +1,183 @@
+use crate::state::math::ORACLE_PRICE_PRECISION;
+use crate::state::pool::{PARTNER_INDEX, USD_STAR_INDEX};
+use anchor_lang::prelude::*;
+
+/// Window the published volume, fees and APR cover
+pub const PARTNER_STATS_WINDOW: i64 = 86_400;
+
+/// Hourly samples kept: a full window plus the sample that opens it
+pub const PARTNER_STATS_SAMPLES: usize = 24 + 1;
+
+/// Price impact the published depth is measured at
+pub const PARTNER_STATS_DEPTH_IMPACT_BPS: u64 = 100; // 1%
+
+/// Cumulative volume and fee counters of a pool at a point in time
+///
+/// Counters stay in token units, indexed like the pool's tokens, so a window's
+/// figures can be valued at the price current when they are published.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct VolumeSample {
+    pub timestamp: i64,
+
+    /// `Pool::lifetime_volume_per_token` when sampled
+    pub cumulative_volume: [u128; 2],
+
+    /// `Pool::lifetime_fees_per_token` when sampled
+    pub cumulative_fees: [u128; 2],
+}
+
+/// Value of USD* and partner token `amounts` in USD*, the partner leg converted at
+/// `partner_price` (`ORACLE_PRICE_PRECISION` scale)
+pub fn usd_star_value(amounts: [u128; 2], partner_price: u64) -> u128 {
+    amounts[USD_STAR_INDEX].saturating_add(
+        amounts[PARTNER_INDEX].saturating_mul(partner_price as u128)
+            / ORACLE_PRICE_PRECISION as u128,
+    )
+}
+
+/// Live Growth pool statistics that partner projects can embed from chain data
+///
+/// The `refresh_partner_stats` crank samples the pool hourly and republishes the
+/// rolling 24h figures and current depth, so readers only deserialize this account.
+#[account]
+pub struct PartnerStats {
+    /// Bump seed for PDA
+    pub bump: u8,
+
+    /// Growth pool these statistics track
+    pub pool: Pubkey,
+
+    /// Slot the next sample is written to once the buffer is full
+    pub next_index: u16,
+
+    /// Up to `PARTNER_STATS_SAMPLES` samples, oldest overwritten first
+    pub samples: Vec<VolumeSample>,
+
+    /// Partner token price in USD* the figures below are valued at: the partner
+    /// oracle price while fresh, the pool's spot price otherwise
+    /// (`ORACLE_PRICE_PRECISION` scale)
+    pub partner_price: u64,
+
+    /// Swap volume over the last 24h (in USD*)
+    pub volume_24h: u128,
+
+    /// Swap fees kept by LPs over the last 24h (in USD*)
+    pub fees_24h: u128,
+
+    /// LP fee APR on the pool's reserves, annualized from the last 24h (in basis points)
+    pub fee_apr_bps: u64,
+
+    /// Pool reserves (in USD*)
+    pub tvl: u64,
+
+    /// USD* a fee-free swap can sell before moving the price 1%
+    pub depth_usd_star_in: u64,
+
+    /// Partner tokens a fee-free swap can sell before moving the price 1%
+    pub depth_partner_in: u64,
+
+    /// Timestamp of the last refresh
+    pub updated_at: i64,
+}
+
+impl PartnerStats {
+    pub fn space() -> usize {
+        8 + // discriminator
+        1 + // bump
+        32 + // pool
+        2 + // next_index
+        4 + (PARTNER_STATS_SAMPLES * (8 + 2 * 16 + 2 * 16)) + // samples
+        8 + // partner_price
+        16 + // volume_24h
+        16 + // fees_24h
+        8 + // fee_apr_bps
+        8 + // tvl
+        8 + // depth_usd_star_in
+        8 + // depth_partner_in
+        8 // updated_at
+    }
+
+    /// Most recent sample, if any
+    pub fn latest(&self) -> Option<&VolumeSample> {
+        let len = self.samples.len();
+        if len == 0 {
+            return None;
+        }
+        self.samples.get((self.next_index as usize + len - 1) % len)
+    }
+
+    /// Append a sample, overwriting the oldest one once the buffer is full
+    pub fn record(&mut self, sample: VolumeSample) {
+        if self.samples.len() < PARTNER_STATS_SAMPLES {
+            self.samples.push(sample);
+            self.next_index = (self.samples.len() % PARTNER_STATS_SAMPLES) as u16;
+        } else {
+            self.samples[self.next_index as usize] = sample;
+            self.next_index = ((self.next_index as usize + 1) % PARTNER_STATS_SAMPLES) as u16;
+        }
+    }
+
+    /// Sample opening the 24h window ending at `now`: the newest one at least a
+    /// window old, or the oldest one while the buffer does not reach that far back
+    pub fn window_start(&self, now: i64) -> Option<&VolumeSample> {
+        let cutoff = now.saturating_sub(PARTNER_STATS_WINDOW);
+        self.samples
+            .iter()
+            .filter(|sample| sample.timestamp <= cutoff)
+            .max_by_key(|sample| sample.timestamp)
+            .or_else(|| self.samples.iter().min_by_key(|sample| sample.timestamp))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(hour: i64) -> VolumeSample {
+        VolumeSample {
+            timestamp: hour * 3600,
+            cumulative_volume: [hour as u128 * 1_000, hour as u128 * 500],
+            cumulative_fees: [hour as u128 * 3, hour as u128],
+        }
+    }
+
+    #[test]
+    fn window_starts_a_day_back_once_the_buffer_covers_it() {
+        let mut stats = PartnerStats {
+            bump: 0,
+            pool: Pubkey::default(),
+            next_index: 0,
+            samples: Vec::new(),
+            partner_price: 0,
+            volume_24h: 0,
+            fees_24h: 0,
+            fee_apr_bps: 0,
+            tvl: 0,
+            depth_usd_star_in: 0,
+            depth_partner_in: 0,
+            updated_at: 0,
+        };
+        assert_eq!(stats.window_start(0), None);
+
+        for hour in 0..=10 {
+            stats.record(sample(hour));
+        }
+        assert_eq!(stats.window_start(10 * 3600), Some(&sample(0)));
+
+        for hour in 11..=40 {
+            stats.record(sample(hour));
+        }
+        assert_eq!(stats.samples.len(), PARTNER_STATS_SAMPLES);
+        assert_eq!(stats.latest(), Some(&sample(40)));
+        assert_eq!(stats.window_start(40 * 3600 + 600), Some(&sample(16)));
+    }
+
+    #[test]
+    fn partner_leg_is_valued_at_the_partner_price() {
+        let amounts = [1_000, 500];
+        assert_eq!(usd_star_value(amounts, ORACLE_PRICE_PRECISION), 1_500);
+        assert_eq!(usd_star_value(amounts, 2 * ORACLE_PRICE_PRECISION), 2_000);
+        assert_eq!(usd_star_value(amounts, ORACLE_PRICE_PRECISION / 4), 1_125);
+        assert_eq!(usd_star_value(amounts, 0), 1_000);
+    }
+}
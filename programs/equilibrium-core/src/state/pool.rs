@@ -1,4 +1,33 @@
 use anchor_lang::prelude::*;
+use std::borrow::Cow;
+use crate::state::math::{
+    calculate_invariant, calculate_pair_price, calculate_virtual_price, calculate_weight, calculate_weights, peg_deviation_bps, rescale_weights,
+    weight_deviation, FeeCurve, FeeShape, BASE_FEE, BPS_DENOMINATOR, FEE_MULTIPLIER, MAX_FEE, STRESS_DEVIATION_BPS,
+};
+use equilibrium_math::pricing::{PartnerOracle, PoolView};
+use crate::state::config::AmmConfig;
+use crate::errors::ErrorCode;
+use crate::utils::get_pool_signer_seeds;
+
+/// Maximum number of tokens a pool can hold (Seed pools trade three)
+pub const MAX_POOL_TOKENS: usize = 3;
+
+/// Token layout of every Growth Pool
+pub use equilibrium_math::pricing::{PARTNER_INDEX, USD_STAR_INDEX};
+
+/// Zeroed bytes at the end of every pool account. New fixed-size fields take
+/// their bytes from here, so existing pools never need a realloc to grow
+pub const POOL_RESERVED_BYTES: usize = 6;
+
+// Pools are created by `init`, whose system program CPI caps the account size
+const _: () = assert!(
+    Pool::space(MAX_POOL_TOKENS)
+        <= anchor_lang::solana_program::entrypoint::MAX_PERMITTED_DATA_INCREASE
+);
+
+/// Per-token amounts passed to deposit/withdraw, indexed like `token_mints`.
+/// Slots past the pool's token count must be zero.
+pub type TokenAmounts = [u64; MAX_POOL_TOKENS];
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
 pub enum PoolType {
@@ -6,6 +35,96 @@ pub enum PoolType {
     Growth,
 }
 
+/// Depeg state of a single pool token
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum TokenStatus {
+    /// Weight within `STRESS_DEVIATION_BPS` of target
+    Normal,
+    /// Weight above target by more than the stress threshold; the pool is absorbing it
+    StressedOver,
+    /// Weight below target by more than the stress threshold; the pool is running out of it
+    StressedUnder,
+    /// Swaps into and out of the token are halted by the admin
+    Frozen,
+}
+
+/// Shape of the dynamic fee between `base_fee` and `max_fee`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum FeeModel {
+    /// Fee grows in proportion to weight deviation
+    Linear,
+    /// Fee grows with the square of weight deviation: cheap near target, steep off peg
+    Convex,
+}
+
+/// Dynamic swap fee curve, all values in parts per 1000
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct FeeParams {
+    /// Fee charged at target weights
+    pub base_fee: u64,
+    
+    /// Cap on the fee however far the pool drifts
+    pub max_fee: u64,
+    
+    /// Fee added at 10 percentage points of total weight deviation; linear
+    /// curves add it again per further 10 points, convex ones scale it by the
+    /// squared deviation
+    pub fee_slope: u64,
+    
+    /// Curve shape
+    pub model: FeeModel,
+}
+
+impl From<FeeParams> for FeeCurve {
+    fn from(params: FeeParams) -> Self {
+        FeeCurve {
+            base_fee: params.base_fee,
+            max_fee: params.max_fee,
+            fee_slope: params.fee_slope,
+            shape: match params.model {
+                FeeModel::Linear => FeeShape::Linear,
+                FeeModel::Convex => FeeShape::Convex,
+            },
+        }
+    }
+}
+
+/// Curated fee presets selectable at pool creation
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum FeeTier {
+    /// Tightly pegged pairs: 0.1% to 0.5%
+    Stable,
+    /// Loosely pegged pairs: 0.3% to 1%
+    Standard,
+    /// Pairs that can trade well off peg: 1% to 3%
+    Volatile,
+}
+
+impl FeeTier {
+    pub fn fee_params(self) -> FeeParams {
+        match self {
+            FeeTier::Stable => FeeParams {
+                base_fee: BASE_FEE,
+                max_fee: MAX_FEE,
+                fee_slope: FEE_MULTIPLIER,
+                model: FeeModel::Linear,
+            },
+            FeeTier::Standard => FeeParams {
+                base_fee: 3,
+                max_fee: 10,
+                fee_slope: 2,
+                model: FeeModel::Linear,
+            },
+            FeeTier::Volatile => FeeParams {
+                base_fee: 10,
+                max_fee: 30,
+                fee_slope: 5,
+                model: FeeModel::Linear,
+            },
+        }
+    }
+}
+
 #[account]
 pub struct Pool {
     /// Bump seed for PDA
@@ -43,10 +162,176 @@ pub struct Pool {
     
     /// If this is a Growth Pool, the Seed Pool it's connected to
     pub seed_pool: Option<Pubkey>,
+    
+    /// Cumulative swap input volume per token (in token units)
+    pub lifetime_volume_per_token: Vec<u128>,
+    
+    /// Cumulative swap fees kept by LPs per token, net of the protocol share (in token units)
+    pub lifetime_fees_per_token: Vec<u128>,
+    
+    /// Number of swaps executed against this pool
+    pub swap_count: u64,
+    
+    /// Cached StableSwap invariant D
+    pub last_d: u64,
+    
+    /// Slot at which `last_d` was last computed exactly
+    pub d_slot: u64,
+    
+    /// Fee charged on withdrawals and left to remaining LPs (in basis points)
+    pub exit_fee_bps: u64,
+    
+    /// Epoch the rebalance counter refers to
+    pub rebalance_epoch: u64,
+    
+    /// Amount sold by keeper rebalances during `rebalance_epoch`
+    pub rebalance_volume_in_epoch: u64,
+    
+    /// Smallest swap input the pool accepts (in token units)
+    pub min_swap_amount: u64,
+    
+    /// Largest share of the output reserve a single swap may take (in basis points)
+    pub max_trade_bps: u64,
+    
+    /// Target weights at `lbp_start_ts` in liquidity bootstrapping mode, empty otherwise.
+    /// They shift linearly to `target_weights` by `lbp_end_ts`
+    pub lbp_start_weights: Vec<u64>,
+    
+    /// Start of the liquidity bootstrapping weight shift
+    pub lbp_start_ts: i64,
+    
+    /// End of the liquidity bootstrapping weight shift
+    pub lbp_end_ts: i64,
+    
+    /// LP virtual price, D / lp_supply scaled by 1e18
+    pub virtual_price: u128,
+    
+    /// Routing hint: most under-weighted token, the cheapest one to sell into the pool
+    pub best_token_in: u8,
+    
+    /// Routing hint: most over-weighted token, the cheapest one to buy from the pool
+    pub best_token_out: u8,
+    
+    /// Fee tier preset chosen at creation
+    pub fee_tier: FeeTier,
+    
+    /// Dynamic swap fee curve, seeded from `fee_tier` and adjustable by the admin
+    pub fee_params: FeeParams,
+    
+    /// Per-token depeg status derived from weight deviation, refreshed on every swap
+    pub token_status: Vec<TokenStatus>,
+    
+    /// Deposits before this timestamp are capped per wallet by `max_deposit_per_wallet`
+    pub bootstrap_end_ts: i64,
+    
+    /// Total a single position may deposit during the bootstrap phase (in token units)
+    pub max_deposit_per_wallet: u64,
+    
+    /// Share of each swap fee owed to the protocol treasury (in basis points), unless
+    /// `inherits_protocol_fee_share` defers to the config's
+    pub protocol_fee_share_bps: u64,
+    
+    /// Protocol fees per token held in the pool vaults but excluded from `reserves`,
+    /// until `collect_protocol_fees` moves them to the treasury
+    pub protocol_fees_owed: Vec<u64>,
+    
+    /// Share of LP supply (in basis points) from which a withdrawal may be staged
+    /// with `request_withdraw` and skip the exit fee; 0 disables staging
+    pub staged_withdraw_threshold_bps: u64,
+    
+    /// Delay between `request_withdraw` and `execute_withdraw` (in seconds)
+    pub staged_withdraw_delay: i64,
+    
+    /// Growth pools: partner token price in USD* pushed by the keeper
+    /// (`ORACLE_PRICE_PRECISION` scale), 0 when unset
+    pub partner_oracle_price: u64,
+    
+    /// Timestamp of the last `partner_oracle_price` update
+    pub partner_oracle_updated_at: i64,
+    
+    /// Deviation of the pool price from `partner_oracle_price` tolerated before
+    /// surcharging (in basis points); 0 disables the band
+    pub oracle_band_bps: u64,
+    
+    /// Extra fee on trades that push the pool price further outside the band
+    /// (in basis points of the input)
+    pub oracle_surcharge_bps: u64,
+    
+    /// Slot of the latest keeper crank tip
+    pub crank_tip_slot: u64,
+    
+    /// Crank tips paid in `crank_tip_slot`
+    pub crank_tips_in_slot: u64,
+    
+    /// Slot of the last `partner_oracle_price` update
+    pub partner_oracle_updated_slot: u64,
+    
+    /// Swaps and deposits are halted by a guardian or the admin; withdrawals stay
+    /// open so LPs can always exit. Only the admin unpauses
+    pub paused: bool,
+    
+    /// Trusted relayer allowed to submit user-signed orders with `relayed_swap`;
+    /// `Pubkey::default()` disables relayed swaps
+    pub relayer: Pubkey,
+    
+    /// LP shares exist only in `UserPosition`s and `internal_lp_supply`; no SPL LP
+    /// is minted, so positions cannot be transferred (set at creation)
+    pub soulbound_lp: bool,
+    
+    /// LP supply of a soulbound pool; zero otherwise, where the LP mint tracks it
+    pub internal_lp_supply: u64,
+    
+    /// Slot of `last_update`; both only move forward (see `utils::touch_pool`)
+    pub last_update_slot: u64,
+    
+    /// Deposits and swaps are limited to holders of `credential_mint`, such as a
+    /// soulbound KYC token or attestation; paths that cannot present it are closed
+    pub requires_credential: bool,
+    
+    /// Mint whose tokens gate a permissioned pool
+    pub credential_mint: Pubkey,
+    
+    /// Share of a token's reserve (in basis points) a single swap or withdrawal must
+    /// drain to emit `LargeFlowEvent`; 0 disables the alert
+    pub large_flow_threshold_bps: u64,
+    
+    /// Swap fees are split at the config's `protocol_fee_share_bps` rather than the
+    /// pool's own; set for new pools and cleared by a per-pool share
+    pub inherits_protocol_fee_share: bool,
+    
+    /// Seed Pools only: largest deviation (in basis points) of a swap pair's price
+    /// from 1:1 that a swap may push it to; 0 disables the band
+    pub price_band_bps: u64,
+    
+    /// Growth pools only: slots a batch auction collects swap intents for; while
+    /// non-zero, swaps only clear through `settle_batch`. 0 trades continuously
+    pub batch_window_slots: u64,
+    
+    /// Exponential moving average of the token weights in basis points, indexed
+    /// like `token_mints`; every swap, deposit and withdrawal first folds in the
+    /// weights the pool held since `ema_updated_slot`
+    pub weights_ema: [u64; MAX_POOL_TOKENS],
+    
+    /// Share (in basis points) of `weights_ema` the pool's weights replace for
+    /// every slot they hold; while non-zero the dynamic fee is priced on the
+    /// average rather than the instantaneous weights. 0 turns the average off
+    pub ema_lambda: u64,
+    
+    /// Tokens per pool slot held in the vaults but owed to queued withdrawals and
+    /// batch swap intents; outside `reserves`, and paid out by
+    /// `fulfill_withdraw_request` and `claim_swap_intent`
+    pub queued_owed: [u64; MAX_POOL_TOKENS],
+    
+    /// Slot `weights_ema` was last brought up to date
+    pub ema_updated_slot: u64,
+    
+    /// Headroom for future fields, always zero
+    pub reserved: [u8; POOL_RESERVED_BYTES],
 }
 
 impl Pool {
-    pub fn space(num_tokens: usize) -> usize {
+    /// Account size for `num_tokens` tokens; tied to the Borsh layout by the tests below
+    pub const fn space(num_tokens: usize) -> usize {
         8 + // discriminator
         1 + // bump
         1 + // pool_type
@@ -59,6 +344,724 @@ impl Pool {
         8 + // amplification
         8 + // total_fees
         8 + // last_update
-        1 + 32 // optional seed_pool
+        1 + 32 + // optional seed_pool
+        4 + (16 * num_tokens) + // lifetime_volume_per_token
+        4 + (16 * num_tokens) + // lifetime_fees_per_token
+        8 + // swap_count
+        8 + // last_d
+        8 + // d_slot
+        8 + // exit_fee_bps
+        8 + // rebalance_epoch
+        8 + // rebalance_volume_in_epoch
+        8 + // min_swap_amount
+        8 + // max_trade_bps
+        4 + (8 * num_tokens) + // lbp_start_weights
+        8 + // lbp_start_ts
+        8 + // lbp_end_ts
+        16 + // virtual_price
+        1 + // best_token_in
+        1 + // best_token_out
+        1 + // fee_tier
+        25 + // fee_params
+        4 + num_tokens + // token_status
+        8 + // bootstrap_end_ts
+        8 + // max_deposit_per_wallet
+        8 + // protocol_fee_share_bps
+        4 + (8 * num_tokens) + // protocol_fees_owed
+        8 + // staged_withdraw_threshold_bps
+        8 + // staged_withdraw_delay
+        8 + // partner_oracle_price
+        8 + // partner_oracle_updated_at
+        8 + // oracle_band_bps
+        8 + // oracle_surcharge_bps
+        8 + // crank_tip_slot
+        8 + // crank_tips_in_slot
+        8 + // partner_oracle_updated_slot
+        1 + // paused
+        32 + // relayer
+        1 + // soulbound_lp
+        8 + // internal_lp_supply
+        8 + // last_update_slot
+        1 + // requires_credential
+        32 + // credential_mint
+        8 + // large_flow_threshold_bps
+        1 + // inherits_protocol_fee_share
+        8 + // price_band_bps
+        8 + // batch_window_slots
+        8 * MAX_POOL_TOKENS + // weights_ema
+        8 + // ema_lambda
+        8 * MAX_POOL_TOKENS + // queued_owed
+        8 + // ema_updated_slot
+        POOL_RESERVED_BYTES // reserved
+    }
+    
+    /// LP supply given the LP mint's `mint_supply`: the internal ledger's for
+    /// soulbound pools, the mint's otherwise
+    pub fn lp_supply(&self, mint_supply: u64) -> u64 {
+        if self.soulbound_lp {
+            self.internal_lp_supply
+        } else {
+            mint_supply
+        }
+    }
+    
+    /// Reject operations that move LP tokens out of a soulbound pool's positions
+    pub fn require_transferable_lp(&self) -> Result<()> {
+        require!(!self.soulbound_lp, ErrorCode::LpNotTransferable);
+        Ok(())
+    }
+    
+    /// Reject swaps and deposits that cannot present a credential to a permissioned pool
+    pub fn require_permissionless(&self) -> Result<()> {
+        require!(!self.requires_credential, ErrorCode::CredentialRequired);
+        Ok(())
+    }
+    
+    /// Share of each swap fee owed to the protocol (in basis points): the config's,
+    /// unless the pool sets its own
+    pub fn protocol_fee_share(&self, amm_config: &AmmConfig) -> u64 {
+        if self.inherits_protocol_fee_share {
+            amm_config.protocol_fee_share_bps
+        } else {
+            self.protocol_fee_share_bps
+        }
+    }
+    
+    /// Protocol's cut of a swap fee of `fee_amount`, kept in the vault but out of the reserves
+    pub fn protocol_fee(&self, amm_config: &AmmConfig, fee_amount: u64) -> u64 {
+        (fee_amount as u128 * self.protocol_fee_share(amm_config) as u128
+            / BPS_DENOMINATOR as u128) as u64
+    }
+    
+    /// Reject swaps outside `settle_batch` while the pool runs batch auctions
+    pub fn require_continuous_trading(&self) -> Result<()> {
+        require!(self.batch_window_slots == 0, ErrorCode::BatchModeActive);
+        Ok(())
+    }
+    
+    /// Reject swaps and deposits while the pool is paused
+    pub fn require_not_paused(&self) -> Result<()> {
+        require!(!self.paused, ErrorCode::PoolPaused);
+        Ok(())
+    }
+    
+    /// Index of `mint` in `token_mints`, if the pool trades it
+    pub fn token_index(&self, mint: &Pubkey) -> Option<usize> {
+        self.token_mints.iter().position(|token_mint| token_mint == mint)
+    }
+    
+    /// Drop token `index` from every per-token field, rescaling the remaining target
+    /// weights (and LBP start weights, if scheduled) back to a full 10000
+    pub fn remove_token(&mut self, index: usize) -> Result<()> {
+        require!(
+            index < self.token_mints.len(),
+            ErrorCode::InvalidInputLength
+        );
+        let without = |weights: &[u64]| -> Result<Vec<u64>> {
+            let mut remaining = weights.to_vec();
+            remaining.remove(index);
+            rescale_weights(&remaining).ok_or_else(|| error!(ErrorCode::InvalidWeights))
+        };
+        self.target_weights = without(&self.target_weights)?;
+        if self.lbp_start_weights.len() == self.token_mints.len() {
+            self.lbp_start_weights = without(&self.lbp_start_weights)?;
+        }
+        
+        self.token_mints.remove(index);
+        self.token_accounts.remove(index);
+        self.reserves.remove(index);
+        self.lifetime_volume_per_token.remove(index);
+        self.lifetime_fees_per_token.remove(index);
+        self.token_status.remove(index);
+        self.protocol_fees_owed.remove(index);
+        self.queued_owed.copy_within(index + 1.., index);
+        self.queued_owed[MAX_POOL_TOKENS - 1] = 0;
+        self.reset_weights_ema();
+        Ok(())
     }
-}
\ No newline at end of file
+    
+    /// Append `mint` with `vault` and an opening `reserve`, giving it `target_weight`
+    /// and scaling the existing target weights (and LBP start weights, if scheduled)
+    /// down to share the rest of the 10000
+    pub fn add_token(
+        &mut self,
+        mint: Pubkey,
+        vault: Pubkey,
+        target_weight: u64,
+        reserve: u64,
+    ) -> Result<()> {
+        require!(
+            self.token_mints.len() < MAX_POOL_TOKENS,
+            ErrorCode::InvalidInputLength
+        );
+        require!(self.token_index(&mint).is_none(), ErrorCode::InvalidTokenMint);
+        require!(
+            target_weight > 0 && target_weight < BPS_DENOMINATOR,
+            ErrorCode::InvalidWeights
+        );
+        let with = |weights: &[u64]| -> Result<Vec<u64>> {
+            let mut scaled: Vec<u64> = weights
+                .iter()
+                .map(|&weight| weight * (BPS_DENOMINATOR - target_weight) / BPS_DENOMINATOR)
+                .collect();
+            scaled.push(target_weight);
+            rescale_weights(&scaled).ok_or_else(|| error!(ErrorCode::InvalidWeights))
+        };
+        self.target_weights = with(&self.target_weights)?;
+        if self.lbp_start_weights.len() == self.token_mints.len() {
+            self.lbp_start_weights = with(&self.lbp_start_weights)?;
+        }
+        
+        self.token_mints.push(mint);
+        self.token_accounts.push(vault);
+        self.reserves.push(reserve);
+        self.lifetime_volume_per_token.push(0);
+        self.lifetime_fees_per_token.push(0);
+        self.token_status.push(TokenStatus::Normal);
+        self.protocol_fees_owed.push(0);
+        self.reset_weights_ema();
+        Ok(())
+    }
+    
+    /// The slots of `amounts` this pool trades, rejecting non-zero trailing slots
+    pub fn active_amounts<'a>(&self, amounts: &'a TokenAmounts) -> Result<&'a [u64]> {
+        let num_tokens = self.token_mints.len();
+        require!(num_tokens <= MAX_POOL_TOKENS, ErrorCode::InvalidInputLength);
+        require!(
+            amounts[num_tokens..].iter().all(|&amount| amount == 0),
+            ErrorCode::InvalidInputLength
+        );
+        Ok(&amounts[..num_tokens])
+    }
+    
+    /// Re-derive each token's status from its weight deviation at `now`, returning
+    /// `(index, previous_status)` for every token whose status changed.
+    /// Frozen tokens keep their status until the admin unfreezes them.
+    pub fn refresh_token_status(&mut self, now: i64) -> Vec<(usize, TokenStatus)> {
+        let total: u64 = self.reserves.iter().sum();
+        let statuses: Vec<TokenStatus> = self
+            .target_weights_at(now)
+            .iter()
+            .zip(self.reserves.iter())
+            .zip(self.token_status.iter())
+            .map(|((&target, &reserve), &status)| {
+                let weight = calculate_weight(reserve, total);
+                if status == TokenStatus::Frozen {
+                    TokenStatus::Frozen
+                } else if weight_deviation(weight, target) <= STRESS_DEVIATION_BPS {
+                    TokenStatus::Normal
+                } else if weight > target {
+                    TokenStatus::StressedOver
+                } else {
+                    TokenStatus::StressedUnder
+                }
+            })
+            .collect();
+
+        let mut changes = Vec::new();
+        for (i, status) in statuses.into_iter().enumerate() {
+            if self.token_status[i] != status {
+                changes.push((i, self.token_status[i]));
+                self.token_status[i] = status;
+            }
+        }
+        changes
+    }
+    
+    /// The fields swap and withdrawal pricing read, for `equilibrium_math::pricing`
+    pub fn view(&self) -> PoolView<'_> {
+        PoolView {
+            reserves: &self.reserves,
+            target_weights: &self.target_weights,
+            lbp_start_weights: &self.lbp_start_weights,
+            lbp_start_ts: self.lbp_start_ts,
+            lbp_end_ts: self.lbp_end_ts,
+            amplification: self.amplification,
+            fee_curve: self.fee_params.into(),
+            ema_lambda: self.ema_lambda,
+            weights_ema: &self.weights_ema,
+            min_swap_amount: self.min_swap_amount,
+            max_trade_bps: self.max_trade_bps,
+            partner_oracle: (self.pool_type == PoolType::Growth).then_some(PartnerOracle {
+                price: self.partner_oracle_price,
+                updated_at: self.partner_oracle_updated_at,
+                band_bps: self.oracle_band_bps,
+                surcharge_bps: self.oracle_surcharge_bps,
+            }),
+        }
+    }
+    
+    /// Current token weights in basis points (sum = 10000)
+    pub fn current_weights(&self) -> Vec<u64> {
+        calculate_weights(&self.reserves)
+    }
+    
+    /// Weights the dynamic fee is priced on: `weights_ema` while `ema_lambda` is
+    /// set, the current weights otherwise
+    pub fn fee_weights(&self) -> Vec<u64> {
+        self.view().fee_weights()
+    }
+    
+    /// Fee weights once the pool has held `weights` for `slots` slots
+    pub fn fee_weights_after(&self, weights: &[u64], slots: u64) -> Vec<u64> {
+        self.view().fee_weights_after(weights, slots)
+    }
+    
+    /// Dynamic fee (in parts per 1000) of the next swap, against `target_weights`
+    ///
+    /// With `ema_lambda` set, one large trade only moves the average part of the
+    /// way, so it cannot spike the fee the next trader pays.
+    pub fn dynamic_fee(&self, target_weights: &[u64]) -> u64 {
+        self.view().dynamic_fee(target_weights)
+    }
+    
+    /// Dynamic fee of a trade that leaves the pool at `reserves_after`; see
+    /// `PoolView::trade_fee`
+    pub fn trade_fee(&self, target_weights: &[u64], reserves_after: &[u64]) -> u64 {
+        self.view().trade_fee(target_weights, reserves_after)
+    }
+    
+    /// Fold the weights held since `ema_updated_slot` into `weights_ema`,
+    /// decayed by the slots elapsed; call before pricing against the pool and
+    /// before every change to the reserves
+    ///
+    /// Trades within one slot leave the average where it was, so only weights
+    /// that last move the fee.
+    pub fn update_weights_ema(&mut self, slot: u64) {
+        let elapsed = slot.saturating_sub(self.ema_updated_slot);
+        self.ema_updated_slot = self.ema_updated_slot.max(slot);
+        if self.ema_lambda == 0 || elapsed == 0 {
+            return;
+        }
+        let updated = self.fee_weights_after(&self.current_weights(), elapsed);
+        self.weights_ema[..updated.len()].copy_from_slice(&updated);
+    }
+    
+    /// Copy of the pool with `weights_ema` folded up to `slot`, for read-only
+    /// quotes that must match what a trade at `slot` would pay
+    pub fn with_weights_ema_at(&self, slot: u64) -> Pool {
+        let mut pool = self.clone();
+        pool.update_weights_ema(slot);
+        pool
+    }
+    
+    /// Restart `weights_ema` from the current weights, as when the average is
+    /// turned on or the pool's tokens change
+    pub fn reset_weights_ema(&mut self) {
+        let weights = self.current_weights();
+        self.weights_ema = [0; MAX_POOL_TOKENS];
+        self.weights_ema[..weights.len()].copy_from_slice(&weights);
+    }
+    
+    /// Pool TVL, summing reserves as if every token were at peg
+    pub fn total_reserves(&self) -> u64 {
+        self.reserves.iter().sum()
+    }
+    
+    /// Cumulative swap fees across all tokens, valued at peg
+    pub fn lifetime_fees(&self) -> u128 {
+        self.lifetime_fees_per_token.iter().sum()
+    }
+    
+    /// Target weights in effect at `now`, following the LBP schedule if one is set
+    pub fn target_weights_at(&self, now: i64) -> Cow<'_, [u64]> {
+        self.view().target_weights_at(now)
+    }
+    
+    /// Recompute `virtual_price` from `last_d` and the current LP supply
+    pub fn refresh_virtual_price(&mut self, lp_supply: u64) {
+        self.virtual_price = calculate_virtual_price(self.last_d, lp_supply).unwrap_or(0);
+    }
+    
+    /// `virtual_price` at D solved afresh from the reserves, for readers that
+    /// must not trust the running `last_d` estimate between recomputes
+    pub fn exact_virtual_price(&self) -> Option<u128> {
+        if self.last_d == 0 {
+            return None;
+        }
+        let d = calculate_invariant(&self.reserves, self.amplification)?;
+        Some(self.virtual_price * d as u128 / self.last_d as u128)
+    }
+    
+    /// Rescale `virtual_price` after D moved from `old_d` with the LP supply unchanged
+    pub fn scale_virtual_price(&mut self, old_d: u64) {
+        if old_d > 0 {
+            self.virtual_price = self.virtual_price * self.last_d as u128 / old_d as u128;
+        }
+    }
+    
+    /// Recompute the cached best rebalancing pair for off-chain routers
+    pub fn refresh_routing_hint(&mut self, now: i64) {
+        let target_weights = self.target_weights_at(now);
+        let deviations: Vec<i64> = self
+            .current_weights()
+            .iter()
+            .zip(target_weights.iter())
+            .map(|(&current, &target)| current as i64 - target as i64)
+            .collect();
+        
+        let best_in = (0..deviations.len()).min_by_key(|&i| deviations[i]);
+        let best_out = (0..deviations.len()).max_by_key(|&i| deviations[i]);
+        if let (Some(best_in), Some(best_out)) = (best_in, best_out) {
+            self.best_token_in = best_in as u8;
+            self.best_token_out = best_out as u8;
+        }
+    }
+    
+    /// Whether every token is within `threshold_bps` of its target weight
+    pub fn is_balanced(&self, threshold_bps: u64) -> bool {
+        self.current_weights()
+            .iter()
+            .zip(self.target_weights.iter())
+            .all(|(&current, &target)| weight_deviation(current, target) <= threshold_bps)
+    }
+    
+    /// Growth pools: partner token price in USD* (`ORACLE_PRICE_PRECISION` scale) from
+    /// a fresh oracle, falling back to the curve's spot price; None for an empty pool
+    pub fn partner_price(&self, now: i64) -> Option<u64> {
+        self.view().partner_price(now)
+    }
+    
+    /// Reject a swap from `token_in_idx` to `token_out_idx` that left the pair's price
+    /// outside the price band and further from the peg than at `reserves_before`
+    pub fn check_price_band(
+        &self,
+        reserves_before: &[u64],
+        token_in_idx: usize,
+        token_out_idx: usize,
+    ) -> Result<()> {
+        if self.price_band_bps == 0 {
+            return Ok(());
+        }
+        let deviation = |reserves: &[u64]| {
+            calculate_pair_price(reserves, token_in_idx, token_out_idx, self.amplification)
+                .map(peg_deviation_bps)
+        };
+        
+        match (deviation(reserves_before), deviation(&self.reserves)) {
+            (Some(before), Some(after)) if after <= self.price_band_bps || after <= before => Ok(()),
+            (_, after) => {
+                msg!(
+                    "Token {} in token {}: {:?} bps from peg, band is {} bps",
+                    token_in_idx,
+                    token_out_idx,
+                    after,
+                    self.price_band_bps
+                );
+                err!(ErrorCode::PriceOutOfBounds)
+            }
+        }
+    }
+    
+    /// Surcharge (in basis points of the input) owed by a trade that would leave the pool
+    /// price outside the oracle band and further from the oracle than before it
+    pub fn oracle_surcharge_bps(
+        &self,
+        token_in_idx: usize,
+        token_out_idx: usize,
+        amount_in: u64,
+        amount_out: u64,
+        now: i64,
+    ) -> u64 {
+        self.view().oracle_surcharge_bps(token_in_idx, token_out_idx, amount_in, amount_out, now)
+    }
+    
+    /// Whether swaps rely on the partner oracle price and it is more than
+    /// `max_age_slots` slots old at `slot`. Only banded Growth pools price
+    /// against the oracle; `max_age_slots == 0` disables the check.
+    pub fn is_oracle_stale(&self, slot: u64, max_age_slots: u64) -> bool {
+        max_age_slots > 0
+            && self.pool_type == PoolType::Growth
+            && self.oracle_band_bps > 0
+            && slot.saturating_sub(self.partner_oracle_updated_slot) > max_age_slots
+    }
+    
+    /// Book a keeper tip of up to `tip_amount` out of the protocol fees owed in token
+    /// `token_idx`, returning the amount to pay. At most `max_tips_per_slot` tips are
+    /// paid per slot, and never more than the protocol has accrued.
+    pub fn take_crank_tip(
+        &mut self,
+        token_idx: usize,
+        tip_amount: u64,
+        max_tips_per_slot: u64,
+        slot: u64,
+    ) -> u64 {
+        if slot != self.crank_tip_slot {
+            self.crank_tip_slot = slot;
+            self.crank_tips_in_slot = 0;
+        }
+        if self.crank_tips_in_slot >= max_tips_per_slot {
+            return 0;
+        }
+        let tip = tip_amount.min(self.protocol_fees_owed[token_idx]);
+        if tip > 0 {
+            self.protocol_fees_owed[token_idx] -= tip;
+            self.crank_tips_in_slot += 1;
+        }
+        tip
+    }
+    
+    /// Tokens the vault of slot `index` must hold: the reserve plus what is owed
+    /// out of it to the treasury, to queued withdrawals and to swap intents
+    pub fn vault_liability(&self, index: usize) -> u64 {
+        let reserve = self.reserves.get(index).copied().unwrap_or(0);
+        let protocol_fees = self.protocol_fees_owed.get(index).copied().unwrap_or(0);
+        let queued = self.queued_owed.get(index).copied().unwrap_or(0);
+        reserve.saturating_add(protocol_fees).saturating_add(queued)
+    }
+    
+    /// Seeds the pool PDA signs with
+    pub fn signer_seeds(&self) -> Result<Vec<&[u8]>> {
+        get_pool_signer_seeds(self, std::slice::from_ref(&self.bump))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::math::{
+        calculate_dynamic_fee, calculate_spot_price, MAX_SWAP_FEE, ORACLE_PRICE_PRECISION,
+    };
+    use crate::state::oracle::MAX_PARTNER_PRICE_AGE;
+
+    #[test]
+    fn fee_tiers_scale_with_volatility() {
+        let current = [6000, 2000, 2000];
+        let target = [4500, 3500, 2000];
+        let fees = [FeeTier::Stable, FeeTier::Standard, FeeTier::Volatile]
+            .map(|tier| calculate_dynamic_fee(&current, &target, &tier.fee_params().into()));
+
+        assert!(fees[0] < fees[1] && fees[1] < fees[2]);
+        for tier in [FeeTier::Stable, FeeTier::Standard, FeeTier::Volatile] {
+            let params = tier.fee_params();
+            assert!(params.base_fee <= params.max_fee && params.max_fee <= MAX_SWAP_FEE);
+            assert_eq!(
+                calculate_dynamic_fee(&target, &target, &params.into()),
+                params.base_fee
+            );
+        }
+    }
+
+    /// A pool with every variable-length field at its `num_tokens` length and
+    /// every option set, so it serializes to the largest layout `space` covers
+    fn full_pool(pool_type: PoolType, num_tokens: usize) -> Pool {
+        Pool {
+            bump: 0,
+            pool_type,
+            amm_config: Pubkey::default(),
+            token_mints: vec![Pubkey::default(); num_tokens],
+            token_accounts: vec![Pubkey::default(); num_tokens],
+            reserves: vec![0; num_tokens],
+            lp_mint: Pubkey::default(),
+            target_weights: vec![0; num_tokens],
+            amplification: 0,
+            total_fees: 0,
+            last_update: 0,
+            seed_pool: Some(Pubkey::default()),
+            lifetime_volume_per_token: vec![0; num_tokens],
+            lifetime_fees_per_token: vec![0; num_tokens],
+            swap_count: 0,
+            last_d: 0,
+            d_slot: 0,
+            exit_fee_bps: 0,
+            rebalance_epoch: 0,
+            rebalance_volume_in_epoch: 0,
+            min_swap_amount: 0,
+            max_trade_bps: 0,
+            lbp_start_weights: vec![0; num_tokens],
+            lbp_start_ts: 0,
+            lbp_end_ts: 0,
+            virtual_price: 0,
+            best_token_in: 0,
+            best_token_out: 0,
+            fee_tier: FeeTier::Volatile,
+            fee_params: FeeTier::Volatile.fee_params(),
+            token_status: vec![TokenStatus::Frozen; num_tokens],
+            bootstrap_end_ts: 0,
+            max_deposit_per_wallet: 0,
+            protocol_fee_share_bps: 0,
+            protocol_fees_owed: vec![0; num_tokens],
+            staged_withdraw_threshold_bps: 0,
+            staged_withdraw_delay: 0,
+            partner_oracle_price: 0,
+            partner_oracle_updated_at: 0,
+            oracle_band_bps: 0,
+            oracle_surcharge_bps: 0,
+            crank_tip_slot: 0,
+            crank_tips_in_slot: 0,
+            partner_oracle_updated_slot: 0,
+            paused: false,
+            relayer: Pubkey::default(),
+            soulbound_lp: false,
+            internal_lp_supply: 0,
+            last_update_slot: 0,
+            requires_credential: false,
+            credential_mint: Pubkey::default(),
+            large_flow_threshold_bps: 0,
+            inherits_protocol_fee_share: false,
+            price_band_bps: 0,
+            batch_window_slots: 0,
+            weights_ema: [0; MAX_POOL_TOKENS],
+            ema_lambda: 0,
+            queued_owed: [0; MAX_POOL_TOKENS],
+            ema_updated_slot: 0,
+            reserved: [0; POOL_RESERVED_BYTES],
+        }
+    }
+
+    #[test]
+    fn exact_virtual_price_follows_the_reserves_not_the_cached_d() {
+        let mut pool = full_pool(PoolType::Seed, 3);
+        pool.amplification = 100;
+        pool.reserves = vec![1_000_000_000; 3];
+        pool.last_d = calculate_invariant(&pool.reserves, pool.amplification).unwrap();
+        pool.refresh_virtual_price(pool.last_d);
+        assert_eq!(pool.exact_virtual_price(), Some(pool.virtual_price));
+
+        // A running estimate that drifted above the curve is not published
+        let exact = pool.virtual_price;
+        let old_d = pool.last_d;
+        pool.last_d += 30_000_000;
+        pool.scale_virtual_price(old_d);
+        assert!(pool.virtual_price > exact);
+        assert!(pool.exact_virtual_price().unwrap().abs_diff(exact) <= 1);
+
+        pool.last_d = 0;
+        assert_eq!(pool.exact_virtual_price(), None);
+    }
+
+    #[test]
+    fn space_matches_the_borsh_layout() {
+        for (pool_type, num_tokens) in [(PoolType::Seed, 3), (PoolType::Growth, 2)] {
+            let pool = full_pool(pool_type, num_tokens);
+            let serialized = pool.try_to_vec().unwrap();
+            assert_eq!(8 + serialized.len(), Pool::space(num_tokens));
+        }
+    }
+
+    #[test]
+    fn removing_a_token_shrinks_the_pool_to_the_smaller_layout() {
+        let mut pool = full_pool(PoolType::Seed, 3);
+        pool.token_mints = vec![Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique()];
+        pool.target_weights = vec![4500, 3500, 2000];
+        pool.lbp_start_weights = vec![2000, 3000, 5000];
+        pool.reserves = vec![1, 2, 3];
+        let kept = [pool.token_mints[0], pool.token_mints[2]];
+
+        pool.remove_token(1).unwrap();
+        assert_eq!(pool.token_mints, kept);
+        assert_eq!(pool.reserves, vec![1, 3]);
+        assert_eq!(pool.target_weights, vec![6924, 3076]);
+        assert_eq!(pool.lbp_start_weights, vec![2857, 7143]);
+        assert_eq!(8 + pool.try_to_vec().unwrap().len(), Pool::space(2));
+        assert!(pool.remove_token(2).is_err());
+    }
+
+    #[test]
+    fn price_band_blocks_swaps_that_push_a_pair_past_it() {
+        let mut pool = full_pool(PoolType::Seed, 3);
+        pool.amplification = 10;
+        pool.price_band_bps = 500;
+        let balanced = vec![1_000_000_000; 3];
+        let drained = vec![3_000_000_000, 1_000_000_000, 200_000_000];
+
+        // Dumping token 0 for token 2 pushes 0's price past the band...
+        pool.reserves = drained.clone();
+        assert!(pool.check_price_band(&balanced, 0, 2).is_err());
+        // ...but trading back towards the peg from outside it is allowed
+        pool.reserves = balanced.clone();
+        assert!(pool.check_price_band(&drained, 2, 0).is_ok());
+
+        pool.price_band_bps = 0;
+        pool.reserves = drained;
+        assert!(pool.check_price_band(&balanced, 0, 2).is_ok());
+    }
+
+    #[test]
+    fn adding_a_token_grows_the_pool_back_to_the_larger_layout() {
+        let mut pool = full_pool(PoolType::Seed, 2);
+        pool.token_mints = vec![Pubkey::new_unique(), Pubkey::new_unique()];
+        pool.target_weights = vec![6000, 4000];
+        pool.lbp_start_weights = vec![5000, 5000];
+        pool.reserves = vec![1, 2];
+        let mint = Pubkey::new_unique();
+
+        assert!(pool.add_token(pool.token_mints[0], Pubkey::new_unique(), 2000, 3).is_err());
+        assert!(pool.add_token(mint, Pubkey::new_unique(), 0, 3).is_err());
+        pool.add_token(mint, Pubkey::new_unique(), 2000, 3).unwrap();
+        assert_eq!(pool.token_mints[2], mint);
+        assert_eq!(pool.reserves, vec![1, 2, 3]);
+        assert_eq!(pool.target_weights, vec![4800, 3200, 2000]);
+        assert_eq!(pool.lbp_start_weights, vec![4000, 4000, 2000]);
+        assert_eq!(pool.token_status[2], TokenStatus::Normal);
+        assert_eq!(8 + pool.try_to_vec().unwrap().len(), Pool::space(3));
+        assert!(pool.add_token(Pubkey::new_unique(), Pubkey::new_unique(), 2000, 4).is_err());
+    }
+
+    #[test]
+    fn weights_ema_damps_the_fee_after_a_large_swap() {
+        let mut pool = full_pool(PoolType::Seed, 3);
+        pool.target_weights = vec![3334, 3333, 3333];
+        pool.reserves = vec![1_000_000_000; 3];
+        let target_weights = pool.target_weights.clone();
+        let calm_fee = pool.dynamic_fee(&target_weights);
+
+        // One trade dumps token 0 for token 2; the instantaneous fee jumps with it
+        pool.reserves = vec![1_600_000_000, 1_000_000_000, 400_000_000];
+        let spiked_fee = pool.dynamic_fee(&target_weights);
+        assert!(spiked_fee > calm_fee);
+
+        // Averaged at 10% per slot, the trade's own slot leaves the fee alone
+        pool.reserves = vec![1_000_000_000; 3];
+        pool.ema_lambda = 1000;
+        pool.reset_weights_ema();
+        pool.update_weights_ema(100);
+        pool.reserves = vec![1_600_000_000, 1_000_000_000, 400_000_000];
+        pool.update_weights_ema(100);
+        assert_eq!(pool.dynamic_fee(&target_weights), calm_fee);
+
+        // One slot later the next trader pays little more than before
+        pool.update_weights_ema(101);
+        assert_eq!(pool.fee_weights(), vec![3533, 3333, 3133]);
+        assert!(pool.dynamic_fee(&target_weights) < spiked_fee);
+
+        // ...unless they push further off target, which pays the instantaneous fee
+        let further = [1_700_000_000, 1_000_000_000, 300_000_000];
+        let back = [1_500_000_000, 1_000_000_000, 500_000_000];
+        assert_eq!(pool.trade_fee(&target_weights, &further), spiked_fee);
+        assert!(pool.trade_fee(&target_weights, &back) < spiked_fee);
+
+        // Sustained imbalance still reaches the fee as the slots go by
+        pool.update_weights_ema(301);
+        assert_eq!(pool.dynamic_fee(&target_weights), spiked_fee);
+    }
+
+    #[test]
+    fn partner_price_falls_back_to_the_curve_once_the_oracle_is_stale() {
+        let mut pool = full_pool(PoolType::Growth, 2);
+        pool.amplification = 100;
+        pool.reserves = vec![2_000_000_000, 500_000_000];
+        let spot = calculate_spot_price(500_000_000, 2_000_000_000, 100).unwrap();
+        assert!(spot > ORACLE_PRICE_PRECISION);
+        assert_eq!(pool.partner_price(1_000), Some(spot));
+
+        let oracle_price = 3 * ORACLE_PRICE_PRECISION;
+        pool.partner_oracle_price = oracle_price;
+        pool.partner_oracle_updated_at = 1_000;
+        assert_eq!(pool.partner_price(1_000 + MAX_PARTNER_PRICE_AGE), Some(oracle_price));
+        assert_eq!(pool.partner_price(1_001 + MAX_PARTNER_PRICE_AGE), Some(spot));
+
+        pool.reserves = vec![0, 0];
+        assert_eq!(pool.partner_price(1_001 + MAX_PARTNER_PRICE_AGE), None);
+        assert_eq!(full_pool(PoolType::Seed, 3).partner_price(0), None);
+    }
+    
+    #[test]
+    fn enums_serialize_to_a_single_tag_byte() {
+        assert_eq!(PoolType::Growth.try_to_vec().unwrap().len(), 1);
+        assert_eq!(TokenStatus::Frozen.try_to_vec().unwrap().len(), 1);
+        assert_eq!(FeeTier::Volatile.try_to_vec().unwrap().len(), 1);
+        assert_eq!(FeeTier::Volatile.fee_params().try_to_vec().unwrap().len(), 25);
+        assert_eq!(Some(Pubkey::default()).try_to_vec().unwrap().len(), 33);
+    }
+}
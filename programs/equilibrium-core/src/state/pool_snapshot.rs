@@ -0,0 +1,187 @@
+use crate::state::math::calculate_weights;
+use crate::state::pool::{Pool, MAX_POOL_TOKENS};
+use anchor_lang::prelude::*;
+
+/// Width of one volume bucket
+pub const SNAPSHOT_BUCKET_SECONDS: i64 = 3600;
+
+/// Hourly volume buckets kept, covering the last 24 hours
+pub const SNAPSHOT_BUCKETS: usize = 24;
+
+/// Compact, fixed-size summary of a pool for front ends
+///
+/// One account fetch gives a UI what it would otherwise decode from the pool and
+/// its vaults. Swaps, deposits and withdrawals that pass the snapshot keep it
+/// current; `refresh_pool_snapshot` catches it up after any other handler.
+#[account]
+pub struct PoolSnapshot {
+    /// Bump seed for PDA
+    pub bump: u8,
+
+    /// Pool summarized here
+    pub pool: Pubkey,
+
+    /// Tokens in the pool; trailing slots of the arrays below are zero
+    pub num_tokens: u8,
+
+    /// Pool reserves, in pool token order
+    pub reserves: [u64; MAX_POOL_TOKENS],
+
+    /// Current weights in basis points
+    pub weights: [u64; MAX_POOL_TOKENS],
+
+    /// Dynamic swap fee at the current reserves, in parts per 1000
+    pub fee: u64,
+
+    /// `Pool::virtual_price` at the last refresh
+    pub virtual_price: u128,
+
+    /// Swap input volume over the last 24 hours, summed across tokens
+    pub volume_24h: u64,
+
+    /// Swap input volume per hour, indexed by hour since the epoch modulo `SNAPSHOT_BUCKETS`
+    pub hourly_volume: [u64; SNAPSHOT_BUCKETS],
+
+    /// Hour since the epoch of the newest bucket
+    pub current_hour: i64,
+
+    /// Pool's summed `lifetime_volume_per_token` at the last refresh
+    pub last_cumulative_volume: u128,
+
+    /// Timestamp of the last refresh
+    pub updated_at: i64,
+}
+
+impl PoolSnapshot {
+    pub fn space() -> usize {
+        8 + // discriminator
+        1 + // bump
+        32 + // pool
+        1 + // num_tokens
+        (MAX_POOL_TOKENS * 8) + // reserves
+        (MAX_POOL_TOKENS * 8) + // weights
+        8 + // fee
+        16 + // virtual_price
+        8 + // volume_24h
+        (SNAPSHOT_BUCKETS * 8) + // hourly_volume
+        8 + // current_hour
+        16 + // last_cumulative_volume
+        8 // updated_at
+    }
+
+    /// Start tracking `pool` at `now`, with no volume history
+    pub fn reset(&mut self, pool: &Pool, now: i64) {
+        self.hourly_volume = [0; SNAPSHOT_BUCKETS];
+        self.current_hour = now / SNAPSHOT_BUCKET_SECONDS;
+        self.last_cumulative_volume = cumulative_volume(pool);
+        self.refresh(pool, now);
+    }
+
+    /// Copy `pool`'s current state in and roll the 24h volume forward to `now`
+    ///
+    /// Volume is read from the pool's lifetime counters, so swaps made without the
+    /// snapshot are still counted, in the hour of the next refresh.
+    pub fn refresh(&mut self, pool: &Pool, now: i64) {
+        self.record_volume(cumulative_volume(pool), now);
+
+        let num_tokens = pool.reserves.len().min(MAX_POOL_TOKENS);
+        self.num_tokens = num_tokens as u8;
+        self.reserves = [0; MAX_POOL_TOKENS];
+        self.reserves[..num_tokens].copy_from_slice(&pool.reserves[..num_tokens]);
+        self.weights = [0; MAX_POOL_TOKENS];
+        self.weights[..num_tokens]
+            .copy_from_slice(&calculate_weights(&pool.reserves)[..num_tokens]);
+        self.fee = pool.dynamic_fee(&pool.target_weights_at(now));
+        self.virtual_price = pool.virtual_price;
+        self.updated_at = now;
+    }
+
+    /// Book the volume traded since the last refresh into the hour of `now`, clearing
+    /// buckets that fell out of the 24h window, and re-total `volume_24h`
+    pub fn record_volume(&mut self, cumulative_volume: u128, now: i64) {
+        let hour = now / SNAPSHOT_BUCKET_SECONDS;
+        let elapsed_hours = hour.saturating_sub(self.current_hour);
+        for offset in 1..=elapsed_hours.min(SNAPSHOT_BUCKETS as i64) {
+            self.hourly_volume[bucket(self.current_hour + offset)] = 0;
+        }
+        self.current_hour = self.current_hour.max(hour);
+
+        let traded = cumulative_volume.saturating_sub(self.last_cumulative_volume);
+        let slot = &mut self.hourly_volume[bucket(self.current_hour)];
+        *slot = slot.saturating_add(u64::try_from(traded).unwrap_or(u64::MAX));
+        self.last_cumulative_volume = cumulative_volume;
+        self.volume_24h = self
+            .hourly_volume
+            .iter()
+            .fold(0, |total: u64, &volume| total.saturating_add(volume));
+    }
+}
+
+/// Sum of a pool's per-token swap input volume
+fn cumulative_volume(pool: &Pool) -> u128 {
+    pool.lifetime_volume_per_token
+        .iter()
+        .fold(0, |total: u128, &volume| total.saturating_add(volume))
+}
+
+fn bucket(hour: i64) -> usize {
+    hour.rem_euclid(SNAPSHOT_BUCKETS as i64) as usize
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HOUR: i64 = SNAPSHOT_BUCKET_SECONDS;
+
+    fn snapshot(now: i64) -> PoolSnapshot {
+        PoolSnapshot {
+            bump: 0,
+            pool: Pubkey::default(),
+            num_tokens: 0,
+            reserves: [0; MAX_POOL_TOKENS],
+            weights: [0; MAX_POOL_TOKENS],
+            fee: 0,
+            virtual_price: 0,
+            volume_24h: 0,
+            hourly_volume: [0; SNAPSHOT_BUCKETS],
+            current_hour: now / HOUR,
+            last_cumulative_volume: 0,
+            updated_at: now,
+        }
+    }
+
+    #[test]
+    fn volume_rolls_off_after_24_hours() {
+        let start = 1_000 * HOUR;
+        let mut snapshot = snapshot(start);
+        snapshot.record_volume(100, start);
+        snapshot.record_volume(150, start + HOUR / 2);
+        snapshot.record_volume(400, start + 5 * HOUR);
+        assert_eq!(snapshot.volume_24h, 400);
+
+        // The first hour's 150 drops out once it is a full day old
+        snapshot.record_volume(400, start + 24 * HOUR);
+        assert_eq!(snapshot.volume_24h, 250);
+        snapshot.record_volume(410, start + 29 * HOUR);
+        assert_eq!(snapshot.volume_24h, 10);
+
+        // After a quiet day everything has rolled off
+        snapshot.record_volume(410, start + 60 * HOUR);
+        assert_eq!(snapshot.volume_24h, 0);
+        assert_eq!(snapshot.hourly_volume, [0; SNAPSHOT_BUCKETS]);
+    }
+
+    #[test]
+    fn volume_never_runs_backwards() {
+        let mut snapshot = snapshot(10 * HOUR);
+        snapshot.record_volume(500, 10 * HOUR);
+
+        // A clock or counter behind the snapshot books nothing new
+        snapshot.record_volume(400, 9 * HOUR);
+        assert_eq!(snapshot.volume_24h, 500);
+        assert_eq!(snapshot.current_hour, 10);
+        snapshot.record_volume(u128::MAX, 10 * HOUR);
+        assert_eq!(snapshot.volume_24h, u64::MAX);
+    }
+}
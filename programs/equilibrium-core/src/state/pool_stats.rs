@@ -0,0 +1,144 @@
+use anchor_lang::prelude::*;
+
+/// Minimum spacing between two fee samples
+pub const POOL_STATS_INTERVAL: i64 = 3600;
+
+/// Hourly samples kept: a full week plus the sample that opens it
+pub const POOL_STATS_SAMPLES: usize = 7 * 24 + 1;
+
+/// Cumulative fee counter of a pool at a point in time
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct FeeSample {
+    pub timestamp: i64,
+
+    /// `Pool::lifetime_fees` when sampled
+    pub cumulative_fees: u128,
+}
+
+/// Ring buffer of hourly fee samples used to derive rolling fee totals and APY
+#[account]
+pub struct PoolStats {
+    /// Bump seed for PDA
+    pub bump: u8,
+
+    /// Pool these statistics track
+    pub pool: Pubkey,
+
+    /// Slot the next sample is written to once the buffer is full
+    pub next_index: u16,
+
+    /// Up to `POOL_STATS_SAMPLES` samples, oldest overwritten first
+    pub samples: Vec<FeeSample>,
+}
+
+impl PoolStats {
+    pub fn space() -> usize {
+        8 + // discriminator
+        1 + // bump
+        32 + // pool
+        2 + // next_index
+        4 + (POOL_STATS_SAMPLES * (8 + 16)) // samples
+    }
+
+    /// Most recent sample, if any
+    pub fn latest(&self) -> Option<&FeeSample> {
+        let len = self.samples.len();
+        if len == 0 {
+            return None;
+        }
+        self.samples.get((self.next_index as usize + len - 1) % len)
+    }
+
+    /// Append a sample, overwriting the oldest one once the buffer is full
+    pub fn record(&mut self, sample: FeeSample) {
+        if self.samples.len() < POOL_STATS_SAMPLES {
+            self.samples.push(sample);
+            self.next_index = (self.samples.len() % POOL_STATS_SAMPLES) as u16;
+        } else {
+            self.samples[self.next_index as usize] = sample;
+            self.next_index = ((self.next_index as usize + 1) % POOL_STATS_SAMPLES) as u16;
+        }
+    }
+
+    /// Fees earned over roughly the last `window` seconds and the seconds actually covered
+    ///
+    /// Measures from the newest sample at least `window` old, or from the
+    /// oldest sample while the buffer does not reach that far back yet.
+    pub fn fees_over(&self, window: i64, now: i64, cumulative_fees: u128) -> (u128, i64) {
+        let cutoff = now.saturating_sub(window);
+        let baseline = self
+            .samples
+            .iter()
+            .filter(|sample| sample.timestamp <= cutoff)
+            .max_by_key(|sample| sample.timestamp)
+            .or_else(|| self.samples.iter().min_by_key(|sample| sample.timestamp));
+
+        match baseline {
+            Some(sample) => (
+                cumulative_fees.saturating_sub(sample.cumulative_fees),
+                now - sample.timestamp,
+            ),
+            None => (0, 0),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stats() -> PoolStats {
+        PoolStats {
+            bump: 0,
+            pool: Pubkey::default(),
+            next_index: 0,
+            samples: Vec::new(),
+        }
+    }
+
+    fn sample(hour: i64) -> FeeSample {
+        FeeSample {
+            timestamp: hour * POOL_STATS_INTERVAL,
+            cumulative_fees: hour as u128 * 10,
+        }
+    }
+
+    #[test]
+    fn ring_buffer_overwrites_the_oldest_sample() {
+        let mut stats = stats();
+        for hour in 0..POOL_STATS_SAMPLES as i64 + 5 {
+            stats.record(sample(hour));
+            assert_eq!(stats.latest(), Some(&sample(hour)));
+        }
+
+        assert_eq!(stats.samples.len(), POOL_STATS_SAMPLES);
+        assert_eq!(stats.next_index, 5);
+        assert_eq!(
+            stats.samples.iter().map(|s| s.timestamp).min(),
+            Some(5 * 3600)
+        );
+    }
+
+    #[test]
+    fn fees_over_uses_the_window_or_the_oldest_sample() {
+        let mut stats = stats();
+        assert_eq!(stats.fees_over(86_400, 0, 0), (0, 0));
+
+        for hour in 0..=48 {
+            stats.record(sample(hour));
+        }
+        let now = 48 * POOL_STATS_INTERVAL + 600;
+        let cumulative = 48 * 10 + 3;
+
+        // The last day is measured from hour 24
+        assert_eq!(
+            stats.fees_over(86_400, now, cumulative),
+            (24 * 10 + 3, 86_400 + 600)
+        );
+        // A week is not covered yet, so it falls back to hour 0
+        assert_eq!(
+            stats.fees_over(7 * 86_400, now, cumulative),
+            (cumulative, now)
+        );
+    }
+}
@@ -0,0 +1,232 @@
+use crate::errors::ErrorCode;
+use crate::state::{UserPosition, MAX_POOL_TOKENS};
+use anchor_lang::prelude::*;
+
+/// Most live positions one portfolio indexes
+pub const MAX_PORTFOLIO_POSITIONS: usize = 16;
+
+/// One live position in a wallet's portfolio
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PortfolioEntry {
+    /// `UserPosition` PDA
+    pub position: Pubkey,
+
+    /// Pool the position belongs to
+    pub pool: Pubkey,
+
+    /// Position's LP balance as of its last deposit, withdrawal or sync
+    pub lp_amount: u64,
+
+    /// Tokens deposited into the position while indexed, indexed like the pool's tokens
+    pub deposited: [u64; MAX_POOL_TOKENS],
+
+    /// Tokens withdrawn from the position while indexed, indexed like the pool's tokens
+    pub withdrawn: [u64; MAX_POOL_TOKENS],
+}
+
+/// Index of a wallet's positions across every pool, so front ends can list
+/// them with a single account fetch
+///
+/// Kept current by deposits and withdrawals that are passed the portfolio;
+/// `sync_user_portfolio` picks up changes made any other way. Positions leave
+/// the index once they are empty or change owner.
+#[account]
+pub struct UserPortfolio {
+    /// Bump seed for PDA
+    pub bump: u8,
+
+    /// Wallet whose positions are indexed
+    pub owner: Pubkey,
+
+    /// Up to `MAX_PORTFOLIO_POSITIONS` live positions
+    pub positions: Vec<PortfolioEntry>,
+
+    /// Deposits recorded across all positions
+    pub deposit_count: u64,
+
+    /// Withdrawals recorded across all positions
+    pub withdraw_count: u64,
+
+    /// Timestamp of the last recorded change
+    pub last_update: i64,
+}
+
+impl UserPortfolio {
+    pub fn space() -> usize {
+        8 + // discriminator
+        1 + // bump
+        32 + // owner
+        4 + (MAX_PORTFOLIO_POSITIONS * (32 + 32 + 8 + 16 * MAX_POOL_TOKENS)) + // positions
+        8 + // deposit_count
+        8 + // withdraw_count
+        8 // last_update
+    }
+
+    /// Bring the entry for `position` in line with its account, returning its index
+    /// while it is still indexed
+    pub fn sync(&mut self, key: Pubkey, position: &UserPosition) -> Result<Option<usize>> {
+        let live = position.owner == self.owner && position.lp_amount > 0;
+        match self
+            .positions
+            .iter()
+            .position(|entry| entry.position == key)
+        {
+            Some(index) if live => {
+                self.positions[index].lp_amount = position.lp_amount;
+                Ok(Some(index))
+            }
+            Some(index) => {
+                self.positions.remove(index);
+                Ok(None)
+            }
+            None if live => {
+                require!(
+                    self.positions.len() < MAX_PORTFOLIO_POSITIONS,
+                    ErrorCode::PortfolioFull
+                );
+                self.positions.push(PortfolioEntry {
+                    position: key,
+                    pool: position.pool,
+                    lp_amount: position.lp_amount,
+                    ..Default::default()
+                });
+                Ok(Some(self.positions.len() - 1))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Record a deposit of `amounts` of each pool token that left `position` at its
+    /// current balance
+    pub fn record_deposit(
+        &mut self,
+        key: Pubkey,
+        position: &UserPosition,
+        amounts: &[u64],
+        now: i64,
+    ) -> Result<()> {
+        if let Some(index) = self.sync(key, position)? {
+            add_amounts(&mut self.positions[index].deposited, amounts);
+        }
+        self.deposit_count += 1;
+        self.last_update = now;
+
+        Ok(())
+    }
+
+    /// Record a withdrawal of `amounts` of each pool token that left `position` at its
+    /// current balance
+    pub fn record_withdrawal(
+        &mut self,
+        key: Pubkey,
+        position: &UserPosition,
+        amounts: &[u64],
+        now: i64,
+    ) -> Result<()> {
+        if let Some(index) = self.sync(key, position)? {
+            add_amounts(&mut self.positions[index].withdrawn, amounts);
+        }
+        self.withdraw_count += 1;
+        self.last_update = now;
+
+        Ok(())
+    }
+}
+
+/// Add `amounts` to `totals` slot by slot; a pool's tokens differ, so they are never summed
+fn add_amounts(totals: &mut [u64; MAX_POOL_TOKENS], amounts: &[u64]) {
+    for (total, &amount) in totals.iter_mut().zip(amounts) {
+        *total = total.saturating_add(amount);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn position(owner: Pubkey, lp_amount: u64) -> UserPosition {
+        UserPosition {
+            bump: 0,
+            owner,
+            pool: Pubkey::new_unique(),
+            lp_amount,
+            min_price: 0,
+            max_price: 0,
+            is_active: lp_amount > 0,
+            created_at: 0,
+            last_update: 0,
+            delegate: None,
+            bootstrap_deposited: 0,
+            last_update_slot: 0,
+        }
+    }
+
+    fn portfolio(owner: Pubkey) -> UserPortfolio {
+        UserPortfolio {
+            bump: 0,
+            owner,
+            positions: Vec::new(),
+            deposit_count: 0,
+            withdraw_count: 0,
+            last_update: 0,
+        }
+    }
+
+    #[test]
+    fn space_fits_a_full_portfolio() {
+        let mut portfolio = portfolio(Pubkey::default());
+        portfolio.positions = vec![PortfolioEntry::default(); MAX_PORTFOLIO_POSITIONS];
+        assert_eq!(
+            8 + portfolio.try_to_vec().unwrap().len(),
+            UserPortfolio::space()
+        );
+    }
+
+    #[test]
+    fn positions_enter_on_deposit_and_leave_once_empty() {
+        let owner = Pubkey::new_unique();
+        let mut portfolio = portfolio(owner);
+        let key = Pubkey::new_unique();
+        let mut held = position(owner, 100);
+
+        portfolio.record_deposit(key, &held, &[1_000, 0, 0], 1).unwrap();
+        held.lp_amount = 250;
+        portfolio.record_deposit(key, &held, &[500, 1_000], 2).unwrap();
+        held.lp_amount = 50;
+        portfolio.record_withdrawal(key, &held, &[700, 600, 700], 3).unwrap();
+        assert_eq!(
+            portfolio.positions,
+            vec![PortfolioEntry {
+                position: key,
+                pool: held.pool,
+                lp_amount: 50,
+                deposited: [1_500, 1_000, 0],
+                withdrawn: [700, 600, 700],
+            }]
+        );
+
+        held.lp_amount = 0;
+        portfolio.record_withdrawal(key, &held, &[500; 3], 4).unwrap();
+        assert!(portfolio.positions.is_empty());
+        assert_eq!((portfolio.deposit_count, portfolio.withdraw_count), (2, 2));
+        assert_eq!(portfolio.last_update, 4);
+
+        // Positions of another wallet are never indexed
+        let foreign = position(Pubkey::new_unique(), 100);
+        assert_eq!(portfolio.sync(key, &foreign).unwrap(), None);
+    }
+
+    #[test]
+    fn a_full_portfolio_rejects_new_positions() {
+        let owner = Pubkey::new_unique();
+        let mut portfolio = portfolio(owner);
+        for _ in 0..MAX_PORTFOLIO_POSITIONS {
+            portfolio
+                .sync(Pubkey::new_unique(), &position(owner, 1))
+                .unwrap();
+        }
+        assert!(portfolio
+            .sync(Pubkey::new_unique(), &position(owner, 1))
+            .is_err());
+    }
+}
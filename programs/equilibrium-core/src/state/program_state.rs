@@ -0,0 +1,69 @@
+use anchor_lang::prelude::*;
+
+/// Semantic version of this program binary, recorded by `record_upgrade`
+pub const PROGRAM_VERSION: [u16; 3] = [0, 1, 0];
+
+/// Upgrade timestamps kept in `ProgramState`, oldest dropped first
+pub const MAX_UPGRADE_HISTORY: usize = 16;
+
+/// Feature bits in `ProgramState::feature_flags`; features ship dark until enabled
+pub const FEATURE_FLASH_LOANS: u64 = 1 << 0;
+pub const FEATURE_LIMIT_ORDERS: u64 = 1 << 1;
+pub const FEATURE_TOKEN_2022: u64 = 1 << 2;
+
+/// Program-wide version and feature switches
+#[account]
+pub struct ProgramState {
+    /// Bump seed for PDA
+    pub bump: u8,
+
+    /// Version of the binary that last recorded an upgrade (major, minor, patch)
+    pub version: [u16; 3],
+
+    /// Enabled `FEATURE_*` bits
+    pub feature_flags: u64,
+
+    /// Timestamp the state was created
+    pub initialized_at: i64,
+
+    /// Timestamps of recorded upgrades, most recent last
+    pub upgrade_history: Vec<i64>,
+}
+
+impl ProgramState {
+    pub fn space() -> usize {
+        8 + // discriminator
+        1 + // bump
+        (3 * 2) + // version
+        8 + // feature_flags
+        8 + // initialized_at
+        4 + (MAX_UPGRADE_HISTORY * 8) // upgrade_history
+    }
+
+    pub fn is_enabled(&self, feature: u64) -> bool {
+        self.feature_flags & feature == feature
+    }
+
+    /// Record that `version` went live at `now`
+    pub fn record_upgrade(&mut self, version: [u16; 3], now: i64) {
+        if self.upgrade_history.len() == MAX_UPGRADE_HISTORY {
+            self.upgrade_history.remove(0);
+        }
+        self.upgrade_history.push(now);
+        self.version = version;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn program_version_matches_the_crate() {
+        let crate_version: Vec<u16> = env!("CARGO_PKG_VERSION")
+            .split('.')
+            .map(|part| part.parse().unwrap())
+            .collect();
+        assert_eq!(PROGRAM_VERSION.to_vec(), crate_version);
+    }
+}
@@ -0,0 +1,43 @@
+use anchor_lang::prelude::*;
+
+/// Referral earnings of one referrer in one token of one pool
+///
+/// Swaps that name this account send their front-end fee to `vault`, a token
+/// account it owns, and add it to `fees_owed`. The fees never pass through the
+/// pool's vaults; the referrer claims them in one transfer with `claim_referral_fees`.
+#[account]
+pub struct ReferralAccount {
+    /// Bump seed for PDA
+    pub bump: u8,
+
+    /// Wallet that may claim the earnings
+    pub referrer: Pubkey,
+
+    /// Pool whose swaps pay the earnings
+    pub pool: Pubkey,
+
+    /// Token the earnings accrue in, a swap's input
+    pub token_mint: Pubkey,
+
+    /// Token account holding the earnings until claimed, owned by this account
+    pub vault: Pubkey,
+
+    /// Fees accrued and not yet claimed
+    pub fees_owed: u64,
+
+    /// Fees ever accrued, claimed or not
+    pub lifetime_fees: u128,
+}
+
+impl ReferralAccount {
+    pub fn space() -> usize {
+        8 + // discriminator
+        1 + // bump
+        32 + // referrer
+        32 + // pool
+        32 + // token_mint
+        32 + // vault
+        8 + // fees_owed
+        16 // lifetime_fees
+    }
+}
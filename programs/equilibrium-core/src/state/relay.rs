@@ -0,0 +1,51 @@
+use anchor_lang::prelude::*;
+
+/// Swap a user signs off-chain for the pool's relayer to submit
+///
+/// The user signs the Borsh encoding of the order with their wallet key, and the
+/// relayer places an ed25519 verification of that signature right before
+/// `relayed_swap` in the same transaction.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct RelayedSwapOrder {
+    /// Pool the order may execute against
+    pub pool: Pubkey,
+
+    /// Wallet that signed the order and owns the token accounts
+    pub user: Pubkey,
+
+    /// Token sold
+    pub token_mint_in: Pubkey,
+
+    /// Token bought
+    pub token_mint_out: Pubkey,
+
+    /// Amount sold
+    pub amount_in: u64,
+
+    /// Least the user accepts in return
+    pub min_amount_out: u64,
+
+    /// Unix timestamp after which the order is void
+    pub deadline: i64,
+
+    /// Must equal the user's `RelayNonce::next_nonce` for this pool
+    pub nonce: u64,
+}
+
+/// Per-user, per-pool replay counter for relayed orders
+#[account]
+pub struct RelayNonce {
+    /// Bump seed for PDA
+    pub bump: u8,
+
+    /// Nonce the user's next relayed order must carry
+    pub next_nonce: u64,
+}
+
+impl RelayNonce {
+    pub fn space() -> usize {
+        8 + // discriminator
+        1 + // bump
+        8 // next_nonce
+    }
+}
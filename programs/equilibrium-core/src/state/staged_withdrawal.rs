@@ -0,0 +1,35 @@
+use anchor_lang::prelude::*;
+
+/// A large exit announced ahead of time with `request_withdraw`
+#[account]
+pub struct StagedWithdrawal {
+    /// Bump seed for PDA
+    pub bump: u8,
+
+    /// Position the LP is withdrawn from
+    pub user_position: Pubkey,
+
+    /// Pool being exited
+    pub pool: Pubkey,
+
+    /// LP amount to burn on execution
+    pub lp_amount: u64,
+
+    /// Timestamp of the request
+    pub requested_at: i64,
+
+    /// Earliest timestamp `execute_withdraw` may run
+    pub executable_at: i64,
+}
+
+impl StagedWithdrawal {
+    pub fn space() -> usize {
+        8 + // discriminator
+        1 + // bump
+        32 + // user_position
+        32 + // pool
+        8 + // lp_amount
+        8 + // requested_at
+        8 // executable_at
+    }
+}
@@ -0,0 +1,113 @@
+use anchor_lang::prelude::*;
+
+/// Most intents one batch collects; `settle_batch` loads every one of them
+pub const MAX_BATCH_INTENTS: u32 = 16;
+
+/// Smallest intent, in basis points of the input token's reserve, so a batch's
+/// few slots cannot be filled with dust to lock other traders out
+pub const MIN_BATCH_INTENT_BPS: u64 = 10;
+
+/// Longest window a pool may collect intents for (about a minute)
+pub const MAX_BATCH_WINDOW_SLOTS: u64 = 150;
+
+/// Open batch of swap intents for a pool in batch auction mode
+///
+/// Intents arrive over `batch_window_slots` from the first one, then clear
+/// together at one price per side in `settle_batch`, which opens the next batch.
+#[account]
+pub struct SwapBatch {
+    /// Bump seed for PDA
+    pub bump: u8,
+
+    /// Pool the batch trades against
+    pub pool: Pubkey,
+
+    /// Id of the open batch; each settlement moves to the next
+    pub batch_id: u64,
+
+    /// Slot the open batch's first intent arrived in
+    pub opened_slot: u64,
+
+    /// Intents submitted to the open batch
+    pub intent_count: u32,
+
+    /// Input submitted to the open batch, per pool token
+    pub amounts_in: [u64; 2],
+}
+
+impl SwapBatch {
+    pub fn space() -> usize {
+        8 + // discriminator
+        1 + // bump
+        32 + // pool
+        8 + // batch_id
+        8 + // opened_slot
+        4 + // intent_count
+        16 // amounts_in
+    }
+
+    /// Whether the open batch stopped taking intents at `slot`
+    pub fn is_closed(&self, slot: u64, window_slots: u64) -> bool {
+        self.intent_count > 0 && slot >= self.opened_slot.saturating_add(window_slots)
+    }
+}
+
+/// One owner's swap in one batch; its input already sits in the pool's vault
+///
+/// Until the intent is claimed, what it is owed is counted in the pool's
+/// `queued_owed`: its input before settlement, its output or refund after.
+#[account]
+pub struct SwapIntent {
+    /// Bump seed for PDA
+    pub bump: u8,
+
+    /// Intent owner, paid on claim
+    pub owner: Pubkey,
+
+    /// Pool the intent trades against
+    pub pool: Pubkey,
+
+    /// Batch the intent belongs to
+    pub batch_id: u64,
+
+    /// Index of the token sold; the other pool token is bought
+    pub token_in_index: u8,
+
+    /// Amount of the input token sold
+    pub amount_in: u64,
+
+    /// Least output the owner accepts; below it the intent is refunded
+    pub min_amount_out: u64,
+
+    /// Set by `settle_batch`; the intent can be claimed from then on
+    pub settled: bool,
+
+    /// Output owed at the clearing price, 0 when refunded
+    pub amount_out: u64,
+}
+
+impl SwapIntent {
+    pub fn space() -> usize {
+        8 + // discriminator
+        1 + // bump
+        32 + // owner
+        32 + // pool
+        8 + // batch_id
+        1 + // token_in_index
+        8 + // amount_in
+        8 + // min_amount_out
+        1 + // settled
+        8 // amount_out
+    }
+
+    /// Pool token index and amount a settled intent pays out: the output, or the
+    /// input when it was refunded
+    pub fn claim(&self) -> (usize, u64) {
+        let token_in_idx = self.token_in_index as usize;
+        if self.amount_out > 0 {
+            (1 - token_in_idx, self.amount_out)
+        } else {
+            (token_in_idx, self.amount_in)
+        }
+    }
+}
@@ -0,0 +1,30 @@
+use anchor_lang::prelude::*;
+
+#[account]
+pub struct SwapHookApproval {
+    /// Bump seed for PDA
+    pub bump: u8,
+
+    /// Program allowed to receive post-swap callbacks
+    pub hook_program: Pubkey,
+
+    /// Config whose pools may call the program
+    pub amm_config: Pubkey,
+
+    /// Config authority that approved the program
+    pub approved_by: Pubkey,
+
+    /// Timestamp of the approval
+    pub approved_at: i64,
+}
+
+impl SwapHookApproval {
+    pub fn space() -> usize {
+        8 + // discriminator
+        1 + // bump
+        32 + // hook_program
+        32 + // amm_config
+        32 + // approved_by
+        8 // approved_at
+    }
+}
@@ -0,0 +1,22 @@
+use anchor_lang::prelude::*;
+
+/// Protocol treasury of one config, authority over one token vault per mint
+///
+/// Protocol fees are kept here, apart from the LP reserves in pool vaults,
+/// and only the config authority can sweep them out.
+#[account]
+pub struct Treasury {
+    /// Bump seed for PDA
+    pub bump: u8,
+
+    /// Config whose authority may sweep the vaults
+    pub amm_config: Pubkey,
+}
+
+impl Treasury {
+    pub fn space() -> usize {
+        8 + // discriminator
+        1 + // bump
+        32 // amm_config
+    }
+}
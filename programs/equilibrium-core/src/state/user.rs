@@ -1,4 +1,5 @@
 use anchor_lang::prelude::*;
+use crate::errors::ErrorCode;
 
 #[account]
 pub struct UserPosition {
@@ -12,6 +13,8 @@ pub struct UserPosition {
     pub pool: Pubkey,
     
     /// LP token amount
+    /// Swap fees stay in the pool reserves, so they compound into what each LP token
+    /// redeems for rather than accruing here
     pub lp_amount: u64,
     
     /// Min price boundary (in price_denominator units)
@@ -28,6 +31,17 @@ pub struct UserPosition {
     
     /// Last update timestamp
     pub last_update: i64,
+    
+    /// Optional manager (bot or vault program) allowed to deposit and withdraw
+    /// for the owner; proceeds always go to the owner
+    pub delegate: Option<Pubkey>,
+    
+    /// Tokens deposited through this position during the pool's bootstrap phase
+    pub bootstrap_deposited: u64,
+    
+    /// Slot of the last change to `lp_amount`, so voting snapshots can prove the
+    /// balance they record was held at the snapshot slot
+    pub last_update_slot: u64,
 }
 
 impl UserPosition {
@@ -41,6 +55,119 @@ impl UserPosition {
         8 + // max_price
         1 + // is_active
         8 + // created_at
-        8 // last_update
+        8 + // last_update
+        1 + 32 + // delegate
+        8 + // bootstrap_deposited
+        8 // last_update_slot
+    }
+    
+    /// Account authorizing an operation on this position: the owner when it signed,
+    /// otherwise `delegate`, which must be the one registered on the position
+    pub fn acting_authority<'info>(
+        &self,
+        owner: &AccountInfo<'info>,
+        delegate: Option<&Signer<'info>>,
+    ) -> Result<AccountInfo<'info>> {
+        match delegate {
+            Some(delegate) => {
+                require!(
+                    self.delegate == Some(delegate.key()),
+                    ErrorCode::Unauthorized
+                );
+                Ok(delegate.to_account_info())
+            }
+            None => {
+                require!(owner.is_signer, ErrorCode::Unauthorized);
+                Ok(owner.clone())
+            }
+        }
+    }
+    
+    /// Take `amount` of LP out of the position as its tokens leave for somewhere the
+    /// position no longer counts them, such as the autocompounder's vault
+    pub fn debit_lp(&mut self, amount: u64, now: i64, slot: u64) -> Result<()> {
+        require!(amount <= self.lp_amount, ErrorCode::InsufficientLiquidity);
+        self.lp_amount -= amount;
+        self.is_active = self.lp_amount > 0;
+        self.last_update = now;
+        self.last_update_slot = slot;
+        Ok(())
+    }
+    
+    /// Add `amount` of LP to the position as its tokens come back to the owner,
+    /// opening it for `owner` in `pool` if it is new
+    pub fn credit_lp(
+        &mut self,
+        owner: Pubkey,
+        pool: Pubkey,
+        bump: u8,
+        amount: u64,
+        now: i64,
+        slot: u64,
+    ) -> Result<()> {
+        if self.owner == Pubkey::default() {
+            self.bump = bump;
+            self.owner = owner;
+            self.pool = pool;
+            self.created_at = now;
+        }
+        self.lp_amount = self
+            .lp_amount
+            .checked_add(amount)
+            .ok_or(ErrorCode::MathOverflow)?;
+        self.is_active = self.lp_amount > 0;
+        self.last_update = now;
+        self.last_update_slot = slot;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn position() -> UserPosition {
+        UserPosition {
+            bump: 0,
+            owner: Pubkey::default(),
+            pool: Pubkey::default(),
+            lp_amount: 0,
+            min_price: 0,
+            max_price: 0,
+            is_active: false,
+            created_at: 0,
+            last_update: 0,
+            delegate: Some(Pubkey::default()),
+            bootstrap_deposited: 0,
+            last_update_slot: 0,
+        }
+    }
+
+    #[test]
+    fn space_matches_the_borsh_layout() {
+        let position = position();
+        assert_eq!(
+            8 + position.try_to_vec().unwrap().len(),
+            UserPosition::space()
+        );
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn lp_moves_in_and_out_of_a_position() {
+        let (owner, pool) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let mut held = position();
+        held.credit_lp(owner, pool, 7, 500, 10, 20).unwrap();
+        assert_eq!((held.owner, held.pool, held.bump), (owner, pool, 7));
+        assert_eq!((held.lp_amount, held.created_at, held.last_update_slot), (500, 10, 20));
+        assert!(held.is_active);
+
+        // A credit to an open position leaves who owns it alone
+        held.credit_lp(Pubkey::new_unique(), pool, 0, 100, 30, 40).unwrap();
+        assert_eq!((held.owner, held.bump, held.lp_amount), (owner, 7, 600));
+
+        assert!(held.debit_lp(601, 50, 60).is_err());
+        held.debit_lp(600, 50, 60).unwrap();
+        assert_eq!((held.lp_amount, held.last_update), (0, 50));
+        assert!(!held.is_active);
+    }
+}
@@ -0,0 +1,154 @@
+use anchor_lang::prelude::*;
+
+/// Longest lock accepted; a lock this long carries full voting power
+pub const MAX_VE_LOCK_DURATION: i64 = 4 * 365 * 86_400;
+
+/// Fee discount tiers as (minimum voting power, discount in basis points of the fee),
+/// in ascending order. Thresholds are in governance token base units
+pub const VE_FEE_DISCOUNT_TIERS: [(u64, u64); 3] = [
+    (1_000_000_000, 1000),   // 10%
+    (10_000_000_000, 2500),  // 25%
+    (100_000_000_000, 5000), // 50%
+];
+
+/// Governance tokens locked under one config, discounting fees in that config's pools
+#[account]
+pub struct VoteEscrow {
+    /// Bump seed for PDA
+    pub bump: u8,
+
+    /// Wallet that locked the tokens
+    pub owner: Pubkey,
+
+    /// Config the tokens were locked under; only its pools honor the lock
+    pub amm_config: Pubkey,
+
+    /// Governance token locked, the config's `ve_mint` at lock time
+    pub mint: Pubkey,
+
+    /// Token account holding the locked governance tokens
+    pub vault: Pubkey,
+
+    /// Governance tokens locked
+    pub amount: u64,
+
+    /// Timestamp after which the tokens can be unlocked
+    pub unlock_ts: i64,
+
+    /// Slot of the last lock, top-up or extension
+    pub last_update_slot: u64,
+}
+
+impl VoteEscrow {
+    pub fn space() -> usize {
+        8 + // discriminator
+        1 + // bump
+        32 + // owner
+        32 + // amm_config
+        32 + // mint
+        32 + // vault
+        8 + // amount
+        8 + // unlock_ts
+        8 // last_update_slot
+    }
+
+    /// Voting power at `now`, decaying linearly to zero at `unlock_ts`
+    pub fn voting_power(&self, now: i64) -> u64 {
+        let remaining = self
+            .unlock_ts
+            .saturating_sub(now)
+            .clamp(0, MAX_VE_LOCK_DURATION);
+        (self.amount as u128 * remaining as u128 / MAX_VE_LOCK_DURATION as u128) as u64
+    }
+
+    /// Share of swap and exit fees waived for the owner at `now`, in basis points
+    pub fn fee_discount_bps(&self, now: i64) -> u64 {
+        let voting_power = self.voting_power(now);
+        VE_FEE_DISCOUNT_TIERS
+            .iter()
+            .rev()
+            .find(|(threshold, _)| voting_power >= *threshold)
+            .map_or(0, |&(_, discount_bps)| discount_bps)
+    }
+}
+
+/// Entry on the exit fee exemption list
+#[account]
+pub struct FeeExemption {
+    /// Bump seed for PDA
+    pub bump: u8,
+
+    /// Wallet whose withdrawals skip the exit fee
+    pub user: Pubkey,
+
+    /// Config whose pools the exemption applies to
+    pub amm_config: Pubkey,
+
+    /// Config authority that added the entry
+    pub added_by: Pubkey,
+
+    /// Timestamp of the entry
+    pub added_at: i64,
+}
+
+impl FeeExemption {
+    pub fn space() -> usize {
+        8 + // discriminator
+        1 + // bump
+        32 + // user
+        32 + // amm_config
+        32 + // added_by
+        8 // added_at
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn escrow(amount: u64, unlock_ts: i64) -> VoteEscrow {
+        VoteEscrow {
+            bump: 0,
+            owner: Pubkey::default(),
+            amm_config: Pubkey::default(),
+            mint: Pubkey::default(),
+            vault: Pubkey::default(),
+            amount,
+            unlock_ts,
+            last_update_slot: 0,
+        }
+    }
+
+    #[test]
+    fn voting_power_decays_to_zero_at_unlock() {
+        let lock = escrow(1_000_000, MAX_VE_LOCK_DURATION);
+        assert_eq!(lock.voting_power(0), 1_000_000);
+        assert_eq!(lock.voting_power(MAX_VE_LOCK_DURATION / 2), 500_000);
+        assert_eq!(lock.voting_power(MAX_VE_LOCK_DURATION), 0);
+        assert_eq!(lock.voting_power(MAX_VE_LOCK_DURATION + 1), 0);
+    }
+
+    #[test]
+    fn discount_follows_the_highest_tier_reached() {
+        assert_eq!(
+            escrow(999_999_999, MAX_VE_LOCK_DURATION).fee_discount_bps(0),
+            0
+        );
+        assert_eq!(
+            escrow(1_000_000_000, MAX_VE_LOCK_DURATION).fee_discount_bps(0),
+            1000
+        );
+        assert_eq!(
+            escrow(50_000_000_000, MAX_VE_LOCK_DURATION).fee_discount_bps(0),
+            2500
+        );
+        assert_eq!(
+            escrow(u64::MAX, MAX_VE_LOCK_DURATION).fee_discount_bps(0),
+            5000
+        );
+
+        // The same balance drops tiers as the lock runs down
+        let lock = escrow(200_000_000_000, MAX_VE_LOCK_DURATION);
+        assert_eq!(lock.fee_discount_bps(MAX_VE_LOCK_DURATION * 3 / 4), 2500);
+    }
+}
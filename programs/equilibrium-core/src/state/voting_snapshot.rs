@@ -0,0 +1,48 @@
+use anchor_lang::prelude::*;
+
+/// A wallet's LP voting weight in a pool as of a governance snapshot slot
+///
+/// One account per owner, pool and snapshot slot, so an external governance
+/// program can read a proposal's weights by deriving
+/// `["voting-snapshot", owner, pool, snapshot_slot]`.
+#[account]
+pub struct VotingSnapshot {
+    /// Bump seed for PDA
+    pub bump: u8,
+
+    /// Wallet whose weight was recorded
+    pub owner: Pubkey,
+
+    /// Pool whose LP is counted
+    pub pool: Pubkey,
+
+    /// Governance snapshot slot the weight applies to
+    pub snapshot_slot: u64,
+
+    /// LP held in the owner's position, unchanged since before `snapshot_slot`
+    pub lp_amount: u64,
+
+    /// Vote escrow lock weight at `recorded_slot`
+    pub lock_weight: u64,
+
+    /// Slot the checkpoint was taken
+    pub recorded_slot: u64,
+}
+
+impl VotingSnapshot {
+    pub fn space() -> usize {
+        8 + // discriminator
+        1 + // bump
+        32 + // owner
+        32 + // pool
+        8 + // snapshot_slot
+        8 + // lp_amount
+        8 + // lock_weight
+        8 // recorded_slot
+    }
+
+    /// Total voting weight: position LP plus lock weight
+    pub fn voting_power(&self) -> u64 {
+        self.lp_amount.saturating_add(self.lock_weight)
+    }
+}
@@ -0,0 +1,70 @@
+use crate::state::MAX_POOL_TOKENS;
+use anchor_lang::prelude::*;
+
+/// FIFO queue of withdrawals booked while one of a pool's vaults was short
+///
+/// Requests are numbered from `tail` as they are queued and paid from `head`,
+/// one at a time and in order, by `fulfill_withdraw_request`.
+#[account]
+pub struct WithdrawQueue {
+    /// Bump seed for PDA
+    pub bump: u8,
+
+    /// Pool the queue pays out of
+    pub pool: Pubkey,
+
+    /// Sequence number of the next request to pay
+    pub head: u64,
+
+    /// Sequence number the next queued request gets
+    pub tail: u64,
+}
+
+impl WithdrawQueue {
+    pub fn space() -> usize {
+        8 + // discriminator
+        1 + // bump
+        32 + // pool
+        8 + // head
+        8 // tail
+    }
+}
+
+/// A withdrawal priced and booked against the pool, waiting for its vaults to
+/// hold the tokens it is owed
+#[account]
+pub struct WithdrawRequest {
+    /// Bump seed for PDA
+    pub bump: u8,
+
+    /// Pool owing the tokens
+    pub pool: Pubkey,
+
+    /// Position owner, paid on fulfillment
+    pub owner: Pubkey,
+
+    /// Place in the pool's queue
+    pub seq: u64,
+
+    /// LP burned when the request was queued
+    pub lp_amount: u64,
+
+    /// Tokens owed, indexed by pool slot
+    pub amounts: [u64; MAX_POOL_TOKENS],
+
+    /// Timestamp the request was queued
+    pub queued_at: i64,
+}
+
+impl WithdrawRequest {
+    pub fn space() -> usize {
+        8 + // discriminator
+        1 + // bump
+        32 + // pool
+        32 + // owner
+        8 + // seq
+        8 + // lp_amount
+        8 * MAX_POOL_TOKENS + // amounts
+        8 // queued_at
+    }
+}
@@ -0,0 +1,302 @@
+//! Pool fixtures for testing code built on Equilibrium (`test-utils` feature)
+//!
+//! Pools here are plain `Pool` values priced with the program's own swap math,
+//! so integrators can unit test their logic against real curves without a
+//! validator. Nothing reads the clock or touches accounts: callers pass `now`.
+
+use crate::instructions::swap::{book_swap_at, quote_swap_at, SwapQuote};
+use crate::state::math::{calculate_invariant, is_invariant_preserved, BPS_DENOMINATOR};
+use crate::state::*;
+use anchor_lang::prelude::*;
+
+/// A live pool of `pool_type` holding `reserves`, as `create_seed_pool` or
+/// `create_growth_pool` would leave it
+///
+/// Mints and vaults are fresh unique keys, the fee tier is `Stable` and the LP
+/// supply is taken to be the sum of the reserves. Every field is public, so
+/// tests can adjust anything else on the returned pool.
+pub fn build_pool(
+    pool_type: PoolType,
+    reserves: &[u64],
+    target_weights: &[u64],
+    amplification: u64,
+) -> Pool {
+    let num_tokens = reserves.len();
+    assert!(
+        (2..=MAX_POOL_TOKENS).contains(&num_tokens) && target_weights.len() == num_tokens,
+        "a pool holds 2 to {} tokens, each with a target weight",
+        MAX_POOL_TOKENS
+    );
+    let mut pool = Pool {
+        bump: 0,
+        pool_type,
+        amm_config: Pubkey::new_unique(),
+        token_mints: (0..num_tokens).map(|_| Pubkey::new_unique()).collect(),
+        token_accounts: (0..num_tokens).map(|_| Pubkey::new_unique()).collect(),
+        reserves: reserves.to_vec(),
+        lp_mint: Pubkey::new_unique(),
+        target_weights: target_weights.to_vec(),
+        amplification,
+        total_fees: 0,
+        last_update: 0,
+        seed_pool: match pool_type {
+            PoolType::Seed => None,
+            PoolType::Growth => Some(Pubkey::new_unique()),
+        },
+        lifetime_volume_per_token: vec![0; num_tokens],
+        lifetime_fees_per_token: vec![0; num_tokens],
+        swap_count: 0,
+        last_d: calculate_invariant(reserves, amplification).unwrap_or(0),
+        d_slot: 0,
+        exit_fee_bps: 0,
+        rebalance_epoch: 0,
+        rebalance_volume_in_epoch: 0,
+        min_swap_amount: 0,
+        max_trade_bps: crate::state::math::DEFAULT_MAX_TRADE_BPS,
+        lbp_start_weights: vec![],
+        lbp_start_ts: 0,
+        lbp_end_ts: 0,
+        virtual_price: 0,
+        best_token_in: 0,
+        best_token_out: 0,
+        fee_tier: FeeTier::Stable,
+        fee_params: FeeTier::Stable.fee_params(),
+        token_status: vec![TokenStatus::Normal; num_tokens],
+        bootstrap_end_ts: 0,
+        max_deposit_per_wallet: 0,
+        protocol_fee_share_bps: 0,
+        protocol_fees_owed: vec![0; num_tokens],
+        staged_withdraw_threshold_bps: 0,
+        staged_withdraw_delay: 0,
+        partner_oracle_price: 0,
+        partner_oracle_updated_at: 0,
+        oracle_band_bps: 0,
+        oracle_surcharge_bps: 0,
+        crank_tip_slot: 0,
+        crank_tips_in_slot: 0,
+        partner_oracle_updated_slot: 0,
+        paused: false,
+        relayer: Pubkey::default(),
+        soulbound_lp: false,
+        internal_lp_supply: 0,
+        last_update_slot: 0,
+        requires_credential: false,
+        credential_mint: Pubkey::default(),
+        large_flow_threshold_bps: 0,
+        inherits_protocol_fee_share: false,
+        price_band_bps: 0,
+        batch_window_slots: 0,
+        weights_ema: [0; MAX_POOL_TOKENS],
+        ema_lambda: 0,
+        queued_owed: [0; MAX_POOL_TOKENS],
+        ema_updated_slot: 0,
+        reserved: [0; POOL_RESERVED_BYTES],
+    };
+    pool.refresh_virtual_price(reserves.iter().sum());
+    pool.refresh_routing_hint(0);
+    pool.refresh_token_status(0);
+    pool
+}
+
+/// A balanced-weight Seed Pool holding `reserves`
+pub fn build_seed_pool(reserves: &[u64], amplification: u64) -> Pool {
+    let weight = BPS_DENOMINATOR / reserves.len() as u64;
+    let mut target_weights = vec![weight; reserves.len()];
+    target_weights[0] += BPS_DENOMINATOR - weight * reserves.len() as u64;
+    build_pool(PoolType::Seed, reserves, &target_weights, amplification)
+}
+
+/// Swap `amount_in` of token `token_in_idx` for token `token_out_idx` at unix time
+/// `now` and slot `slot`
+///
+/// Prices, guards and books the trade as `swap` does, with the dynamic fee, the
+/// invariant check and the price band. There is no `AmmConfig`, so the whole fee
+/// stays in the reserves, as in a config without a protocol fee share, and the
+/// stale-pool fee floor is never charged. The oracle surcharge only applies to a
+/// pool given a partner oracle price and band fresh at `now`, which built pools
+/// are not.
+pub fn swap(
+    pool: &mut Pool,
+    token_in_idx: usize,
+    token_out_idx: usize,
+    amount_in: u64,
+    now: i64,
+    slot: u64,
+) -> Result<SwapQuote> {
+    pool.require_not_paused()?;
+    pool.require_continuous_trading()?;
+    pool.update_weights_ema(slot);
+    let quote = quote_swap_at(pool, token_in_idx, token_out_idx, amount_in, 0, 0, now)?;
+    book_swap_at(
+        pool,
+        0,
+        token_in_idx,
+        token_out_idx,
+        amount_in,
+        &quote,
+        now,
+        slot,
+    )?;
+    pool.refresh_token_status(now);
+
+    Ok(quote)
+}
+
+/// Assert `pool` is internally consistent: one entry per token in every per-token
+/// list, target weights summing to 100% and a solvable invariant
+pub fn assert_pool_invariants(pool: &Pool) {
+    let num_tokens = pool.token_mints.len();
+    for (name, len) in [
+        ("token_accounts", pool.token_accounts.len()),
+        ("reserves", pool.reserves.len()),
+        ("target_weights", pool.target_weights.len()),
+        ("token_status", pool.token_status.len()),
+        ("protocol_fees_owed", pool.protocol_fees_owed.len()),
+        (
+            "lifetime_volume_per_token",
+            pool.lifetime_volume_per_token.len(),
+        ),
+        (
+            "lifetime_fees_per_token",
+            pool.lifetime_fees_per_token.len(),
+        ),
+    ] {
+        assert_eq!(
+            len, num_tokens,
+            "{} has {} entries for {} tokens",
+            name, len, num_tokens
+        );
+    }
+    assert_eq!(
+        pool.target_weights.iter().sum::<u64>(),
+        BPS_DENOMINATOR,
+        "target weights {:?} do not sum to 100%",
+        pool.target_weights
+    );
+
+    let d = calculate_invariant(&pool.reserves, pool.amplification)
+        .unwrap_or_else(|| panic!("no invariant for reserves {:?}", pool.reserves));
+    let total: u64 = pool.reserves.iter().sum();
+    assert!(
+        d <= total,
+        "invariant {} exceeds the reserve total {}",
+        d,
+        total
+    );
+}
+
+/// Assert moving from `reserves_before` to `pool`'s reserves did not lower the invariant
+pub fn assert_invariant_preserved(reserves_before: &[u64], pool: &Pool) {
+    assert!(
+        is_invariant_preserved(reserves_before, &pool.reserves, pool.amplification),
+        "invariant dropped from reserves {:?} to {:?}",
+        reserves_before,
+        pool.reserves
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::errors::ErrorCode;
+
+    #[test]
+    fn built_pools_swap_on_the_real_curve() {
+        let mut pool = build_seed_pool(&[1_000_000_000; 3], 100);
+        assert_pool_invariants(&pool);
+        assert_eq!(pool.target_weights, vec![3334, 3333, 3333]);
+
+        let before = pool.reserves.clone();
+        let quote = swap(&mut pool, 0, 2, 10_000_000, 0, 0).unwrap();
+        assert!(quote.amount_out > 9_900_000 && quote.amount_out < 10_000_000);
+        assert_eq!(pool.reserves[0], before[0] + 10_000_000);
+        assert_eq!(pool.reserves[2], before[2] - quote.amount_out);
+        assert_eq!(pool.swap_count, 1);
+        assert_pool_invariants(&pool);
+        assert_invariant_preserved(&before, &pool);
+
+        // The program's guards apply as they would on chain
+        pool.paused = true;
+        assert!(swap(&mut pool, 0, 2, 10_000_000, 0, 0).is_err());
+    }
+
+    #[test]
+    fn decoded_pools_price_like_the_program() {
+        use equilibrium_client::{PoolAccount, QuoteError, POOL_DISCRIMINATOR};
+
+        assert_eq!(
+            POOL_DISCRIMINATOR,
+            <Pool as anchor_lang::Discriminator>::DISCRIMINATOR
+        );
+        let mut pool = build_seed_pool(&[1_000_000_000; 3], 100);
+        let mut data = Vec::new();
+        pool.try_serialize(&mut data).unwrap();
+
+        let account = PoolAccount::deserialize(&data).unwrap();
+        let mints = pool
+            .token_mints
+            .iter()
+            .map(|mint| mint.to_bytes())
+            .collect::<Vec<_>>();
+        assert_eq!(account.token_mints(), &mints[..]);
+        assert_eq!(account.reserves(), &pool.reserves[..]);
+        assert_eq!(account.fee_curve(), pool.fee_params.into());
+        assert_eq!(account.lp_supply(0), 0);
+        assert!(PoolAccount::deserialize(&data[8..]).is_err());
+        assert!(PoolAccount::deserialize(&data[..40]).is_err());
+
+        let quote = account.math().quote_swap(0, 2, 10_000_000, 0).unwrap();
+        assert_eq!(
+            account.math().dynamic_fee(0),
+            pool.dynamic_fee(&pool.target_weights)
+        );
+        let swapped = swap(&mut pool, 0, 2, 10_000_000, 0, 0).unwrap();
+        assert_eq!(quote, swapped);
+        assert!(account.math().quote_swap(0, 3, 10_000_000, 0).is_err());
+
+        let paid = account
+            .math()
+            .quote_withdraw(300_000_000, 3_000_000_000, 0)
+            .unwrap();
+        assert_eq!(paid, vec![100_000_000; 3]);
+
+        pool.token_status[2] = TokenStatus::Frozen;
+        data.clear();
+        pool.try_serialize(&mut data).unwrap();
+        let account = PoolAccount::deserialize(&data).unwrap();
+        assert_eq!(
+            account.math().quote_swap(0, 2, 10_000_000, 0),
+            Err(QuoteError::TokenFrozen)
+        );
+    }
+
+    #[test]
+    fn max_trade_size_caps_the_output() {
+        // Selling the glut token pays out less than it takes in, so the input may
+        // exceed the cap while the output stays under it
+        let mut pool = build_seed_pool(&[3_000_000_000, 1_000_000_000, 1_000_000_000], 1);
+        assert_eq!(pool.max_trade_bps, 3_000);
+        let quote = swap(&mut pool.clone(), 0, 1, 310_000_000, 0, 0).unwrap();
+        assert!(quote.amount_out <= 300_000_000);
+
+        let error = swap(&mut pool, 0, 1, 1_500_000_000, 0, 0).err();
+        assert_eq!(error, Some(ErrorCode::MaxTradeSizeExceeded.into()));
+    }
+
+    #[test]
+    fn idle_slots_of_imbalance_reach_the_next_quote() {
+        let mut pool = build_seed_pool(&[1_000_000_000; 3], 100);
+        pool.ema_lambda = 1000;
+        pool.reset_weights_ema();
+
+        // A large trade leaves the pool off target; in its own slot the average holds
+        swap(&mut pool, 0, 2, 250_000_000, 0, 100).unwrap();
+        let same_slot = swap(&mut pool.clone(), 2, 0, 1_000_000, 0, 100).unwrap();
+
+        // Slots later, with nothing traded in between, the imbalance has reached the fee
+        let idle = swap(&mut pool.clone(), 2, 0, 1_000_000, 0, 300).unwrap();
+        assert!(idle.fee > same_slot.fee);
+        pool.update_weights_ema(300);
+        assert_eq!(idle.fee, pool.dynamic_fee(&pool.target_weights));
+    }
+}
@@ -3,6 +3,152 @@ use anchor_spl::token::TokenAccount;
 use crate::state::*;
 use crate::errors::ErrorCode;
 
+/// Reject execution once the caller's `deadline` (unix timestamp) has passed
+pub fn check_deadline(deadline: i64) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    if now > deadline {
+        msg!("Deadline {} passed at {}", deadline, now);
+        return err!(ErrorCode::DeadlineExceeded);
+    }
+    Ok(())
+}
+
+/// Current unix timestamp and slot, held at or after `last_timestamp` and `last_slot`
+///
+/// Validator timestamps can step backwards, notably across forks, so state stamped
+/// from the clock reads it through here and never appears to move back in time.
+pub fn monotonic_clock(last_timestamp: i64, last_slot: u64) -> Result<(i64, u64)> {
+    let clock = Clock::get()?;
+    Ok((
+        clock.unix_timestamp.max(last_timestamp),
+        clock.slot.max(last_slot),
+    ))
+}
+
+/// Stamp `pool` as updated now, returning the monotonic timestamp and slot it recorded
+///
+/// With the `debug-logs` feature this also logs the pool's stats after every handler
+/// that stamps it; release builds leave the handler's events as the only output.
+pub fn touch_pool(pool: &mut Pool) -> Result<(i64, u64)> {
+    let (now, slot) = monotonic_clock(pool.last_update, pool.last_update_slot)?;
+    pool.last_update = now;
+    pool.last_update_slot = slot;
+    log_pool_stats(pool);
+    Ok((now, slot))
+}
+
+/// Require `actual` to reach the caller's `minimum`, logging both on failure
+pub fn check_min_amount(what: impl std::fmt::Display, actual: u64, minimum: u64) -> Result<()> {
+    if actual < minimum {
+        msg!("{}: got {}, minimum {}", what, actual, minimum);
+        return err!(ErrorCode::SlippageExceeded);
+    }
+    Ok(())
+}
+
+/// Require `actual` to stay within the caller's `maximum`, logging both on failure
+pub fn check_max_amount(what: impl std::fmt::Display, actual: u64, maximum: u64) -> Result<()> {
+    if actual > maximum {
+        msg!("{}: got {}, maximum {}", what, actual, maximum);
+        return err!(ErrorCode::SlippageExceeded);
+    }
+    Ok(())
+}
+
+/// Require `mint` to be the pool's mint at token `index`, logging both on failure
+pub fn check_pool_mint(pool: &Pool, index: usize, mint: &Pubkey) -> Result<()> {
+    let expected = pool.token_mints.get(index);
+    if expected != Some(mint) {
+        msg!("Token {}: expected mint {:?}, got {}", index, expected, mint);
+        return err!(ErrorCode::MintOrderMismatch);
+    }
+    Ok(())
+}
+
+/// Require `vault` to be the pool's token account at token `index`, logging both on failure
+pub fn check_pool_vault(pool: &Pool, index: usize, vault: &Pubkey) -> Result<()> {
+    let expected = pool.token_accounts.get(index);
+    if expected != Some(vault) {
+        msg!("Token {}: expected vault {:?}, got {}", index, expected, vault);
+        return err!(ErrorCode::VaultMismatch);
+    }
+    Ok(())
+}
+
+/// Require the vault at token `index` to hold everything the pool owes out of it
+/// (`Pool::vault_liability`), logging both on failure; while a vault is short,
+/// withdrawals go through `queue_withdraw`
+pub fn check_vault_backing(pool: &Pool, index: usize, vault_balance: u64) -> Result<()> {
+    let liability = pool.vault_liability(index);
+    if vault_balance < liability {
+        msg!("Token {}: vault holds {} of the {} it owes", index, vault_balance, liability);
+        return err!(ErrorCode::VaultShortfall);
+    }
+    Ok(())
+}
+
+/// Require `holder` to present a funded token account of a permissioned pool's
+/// credential mint; pools without `requires_credential` accept anyone
+pub fn check_credential(
+    pool: &Pool,
+    holder: &Pubkey,
+    credential: Option<&anchor_spl::token_interface::TokenAccount>,
+) -> Result<()> {
+    if !pool.requires_credential {
+        return Ok(());
+    }
+    match credential {
+        Some(credential)
+            if credential.mint == pool.credential_mint
+                && credential.owner == *holder
+                && credential.amount > 0 =>
+        {
+            Ok(())
+        }
+        _ => {
+            msg!("{} holds no credential {}", holder, pool.credential_mint);
+            err!(ErrorCode::CredentialRequired)
+        }
+    }
+}
+
+/// Fee discount `vote_escrow` earns at `now` in a pool of `amm_config`, in basis points
+pub fn vote_escrow_discount_bps(
+    vote_escrow: Option<&VoteEscrow>,
+    amm_config: &Account<AmmConfig>,
+    now: i64,
+) -> Result<u64> {
+    let Some(vote_escrow) = vote_escrow else {
+        return Ok(0);
+    };
+    check_vote_escrow(vote_escrow, amm_config)?;
+    Ok(vote_escrow.fee_discount_bps(now))
+}
+
+/// Check `vote_escrow` was locked under `amm_config` in its current governance token
+///
+/// Escrows are locked per config, so one from another config, or in a governance
+/// token the config has since replaced, is rejected rather than honored.
+pub fn check_vote_escrow(
+    vote_escrow: &VoteEscrow,
+    amm_config: &Account<AmmConfig>,
+) -> Result<()> {
+    if vote_escrow.amm_config != amm_config.key()
+        || vote_escrow.mint != amm_config.ve_mint
+        || amm_config.ve_mint == Pubkey::default()
+    {
+        msg!(
+            "Vote escrow locked {} under {}, config {} uses {}",
+            vote_escrow.mint,
+            vote_escrow.amm_config,
+            amm_config.key(),
+            amm_config.ve_mint
+        );
+        return err!(ErrorCode::VoteEscrowMismatch);
+    }
+    Ok(())
+}
+
 /// Verify token account belongs to the expected owner and has the expected mint
 pub fn verify_token_account(
     token_account: &AccountInfo,
@@ -22,23 +168,29 @@ pub fn verify_token_account(
 }
 
 /// Get seeds for pool signing
-pub fn get_pool_signer_seeds<'a>(
-    pool: &'a Pool,
-    partner_token_mint: Option<&'a [u8]>,
-    bump: &'a [u8],
-) -> Vec<&'a [u8]> {
-    let mut seeds = vec![
-        &b"pool"[..],
-        if pool.pool_type == PoolType::Seed { &b"seed"[..] } else { &b"growth"[..] },
-    ];
-    
-    if pool.pool_type == PoolType::Growth && partner_token_mint.is_some() {
-        seeds.push(partner_token_mint.unwrap());
-    }
+///
+/// Seed pools sign with ["pool", amm_config, "seed", bump] and Growth pools with
+/// ["pool", amm_config, "growth", partner_mint, bump], where the partner mint is
+/// the pool's second token. Scoping by config lets every config have its own pools.
+pub fn get_pool_signer_seeds<'a>(pool: &'a Pool, bump: &'a [u8]) -> Result<Vec<&'a [u8]>> {
+    let seeds = match pool.pool_type {
+        PoolType::Seed => vec![&b"pool"[..], pool.amm_config.as_ref(), &b"seed"[..], bump],
+        PoolType::Growth => {
+            let partner_token_mint = pool
+                .token_mints
+                .get(1)
+                .ok_or(ErrorCode::MissingPartnerMint)?;
+            vec![
+                &b"pool"[..],
+                pool.amm_config.as_ref(),
+                &b"growth"[..],
+                partner_token_mint.as_ref(),
+                bump,
+            ]
+        }
+    };
     
-    seeds.push(bump);
-    
-    seeds
+    Ok(seeds)
 }
 
 /// Format basis points (10000 = 100%) as a percentage string
@@ -53,10 +205,11 @@ pub fn calculate_fee_percentage(fee: u64) -> String {
     format!("0.{}%", fee / 10)
 }
 
-/// Log pool statistics
+/// Log pool statistics (`debug-logs` builds only)
+#[cfg(feature = "debug-logs")]
 pub fn log_pool_stats(pool: &Pool) {
     let weights = crate::state::math::calculate_weights(&pool.reserves);
-    let fee = crate::state::math::calculate_dynamic_fee(&weights, &pool.target_weights);
+    let fee = pool.dynamic_fee(&pool.target_weights);
     
     msg!("Pool type: {:?}", pool.pool_type);
     msg!("Current reserves: {:?}", pool.reserves);
@@ -66,6 +219,11 @@ pub fn log_pool_stats(pool: &Pool) {
     msg!("Amplification coefficient: {}", pool.amplification);
 }
 
+/// Without `debug-logs` pool statistics are not logged
+#[cfg(not(feature = "debug-logs"))]
+#[inline(always)]
+pub fn log_pool_stats(_pool: &Pool) {}
+
 /// Calculate dollar value of a token amount using a price oracle
 /// Note: In a real implementation, you would integrate with a price oracle
 pub fn calculate_dollar_value(amount: u64, decimals: u8) -> f64 {
@@ -0,0 +1,155 @@
+//! Evolving a Seed Pool's basket: removing a drained token and adding a new one
+
+mod common;
+
+use anchor_lang::{InstructionData, ToAccountMetas};
+use common::{assert_program_error, create_ata, create_mint, mint_to, SeedPoolEnv};
+use equilibrium_core::errors::ErrorCode;
+use equilibrium_core::events::PoolTokenAdded;
+use equilibrium_core::instruction::{ClosePoolVault, SetTokenFrozen};
+use equilibrium_core::Pool;
+use solana_sdk::{instruction::Instruction, pubkey::Pubkey, system_program};
+
+const RESERVE: u64 = 1_000_000_000;
+const AMOUNT: u64 = 1_000_000;
+
+fn vault_address(env: &SeedPoolEnv, mint: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[b"pool-token", env.pool.as_ref(), mint.as_ref()],
+        &equilibrium_core::id(),
+    )
+    .0
+}
+
+fn add_ix(
+    env: &SeedPoolEnv,
+    mint: Pubkey,
+    authority_token: Pubkey,
+    target_weight: u64,
+    initial_amount: u64,
+) -> Instruction {
+    Instruction {
+        program_id: equilibrium_core::id(),
+        accounts: equilibrium_core::accounts::AddPoolToken {
+            authority: env.user(),
+            amm_config: env.amm_config,
+            pool: env.pool,
+            lp_mint: env.lp_mint,
+            token_mint: mint,
+            vault: vault_address(env, &mint),
+            authority_token,
+            authority_lp_token: env.user_lp_token,
+            token_program: spl_token::ID,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: equilibrium_core::instruction::AddPoolToken {
+            target_weight,
+            initial_amount,
+        }
+        .data(),
+    }
+}
+
+fn remove_ix(env: &SeedPoolEnv, token_index: u8) -> Instruction {
+    Instruction {
+        program_id: equilibrium_core::id(),
+        accounts: equilibrium_core::accounts::ClosePoolVault {
+            authority: env.user(),
+            amm_config: env.amm_config,
+            pool: env.pool,
+            lp_mint: env.lp_mint,
+            vault: env.pool_tokens[token_index as usize],
+            token_program: spl_token::ID,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: ClosePoolVault { token_index }.data(),
+    }
+}
+
+#[tokio::test]
+#[ignore = "needs the SBF program from `anchor build`"]
+async fn basket_swaps_a_drained_token_for_a_new_one_in_place() {
+    common::require_program();
+
+    let mut env = SeedPoolEnv::new([RESERVE; 3]).await;
+    let user = env.user();
+    let new_mint = create_mint(&mut env.context).await;
+    let new_user_token = create_ata(&mut env.context, &new_mint, &user).await;
+    mint_to(&mut env.context, &new_mint, &new_user_token, RESERVE).await;
+
+    // A full pool has no room for a fourth token
+    assert_program_error(
+        env.process(&[add_ix(&env, new_mint, new_user_token, 2_000, RESERVE)])
+            .await,
+        ErrorCode::InvalidInputLength,
+    );
+
+    // Retire token 2
+    env.drain_token(2).await;
+    let freeze = env.update_pool_ix(SetTokenFrozen {
+        token_mint: env.mints[2],
+        frozen: true,
+    });
+    env.process(&[freeze]).await.unwrap();
+    env.process(&[remove_ix(&env, 2)]).await.unwrap();
+    assert_eq!(env.pool_state().await.token_mints.len(), 2);
+
+    // The two-token pool takes deposits without third-token accounts
+    let mut deposit = env.deposit_ix([AMOUNT, AMOUNT, 0], 0);
+    let retired = [env.user_tokens[2], env.mints[2], env.pool_tokens[2]];
+    for meta in deposit.accounts.iter_mut() {
+        if retired.contains(&meta.pubkey) {
+            meta.pubkey = equilibrium_core::id();
+            meta.is_writable = false;
+        }
+    }
+    env.process(&[deposit]).await.unwrap();
+
+    // Only the authority's seed funds the new token, and it must carry a weight
+    assert_program_error(
+        env.process(&[add_ix(&env, new_mint, new_user_token, 0, RESERVE)])
+            .await,
+        ErrorCode::InvalidWeights,
+    );
+
+    let before = env.pool_state().await;
+    let lp_before = env.token_balance(env.user_lp_token).await;
+    let events = env
+        .process_events::<PoolTokenAdded>(&[add_ix(&env, new_mint, new_user_token, 2_000, RESERVE)])
+        .await
+        .unwrap();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].token_mint, new_mint);
+    assert_eq!(events[0].token_index, 2);
+    assert_eq!(events[0].initial_amount, RESERVE);
+
+    // The pool grew back to three tokens, seeded and reweighted
+    let vault = vault_address(&env, &new_mint);
+    assert_eq!(env.account(env.pool).await.data.len(), Pool::space(3));
+    assert_eq!(env.token_balance(vault).await, RESERVE);
+    let after = env.pool_state().await;
+    assert_eq!(after.token_mints[..2], before.token_mints[..]);
+    assert_eq!(after.token_mints[2], new_mint);
+    assert_eq!(after.token_accounts[2], vault);
+    assert_eq!(after.reserves[2], RESERVE);
+    assert_eq!(after.target_weights[2], 2_000);
+    assert_eq!(after.target_weights.iter().sum::<u64>(), 10_000);
+    assert_eq!(events[0].target_weights, after.target_weights);
+
+    // LP is minted for the invariant growth alone, so existing LPs are not diluted
+    let lp_minted = env.token_balance(env.user_lp_token).await - lp_before;
+    assert_eq!(lp_minted, events[0].lp_amount);
+    assert!(lp_minted > 0 && lp_minted <= RESERVE);
+    assert!(after.virtual_price >= before.virtual_price);
+
+    // The new token trades like any other
+    env.mints[2] = new_mint;
+    env.user_tokens[2] = new_user_token;
+    env.pool_tokens[2] = vault;
+    env.process(&[env.swap_ix(0, 2, AMOUNT, 0)]).await.unwrap();
+    env.process(&[env.deposit_ix([AMOUNT; 3], 0)])
+        .await
+        .unwrap();
+}
@@ -0,0 +1,205 @@
+//! Auto-compounding LP vault
+
+mod common;
+
+use anchor_lang::{InstructionData, ToAccountMetas};
+use common::{assert_program_error, create_ata, mint_to, SeedPoolEnv};
+use equilibrium_core::errors::ErrorCode;
+use equilibrium_core::instruction::{
+    Compound, DepositToAutocompounder, InitAutocompounder, SetRebalanceConfig,
+    WithdrawFromAutocompounder,
+};
+use equilibrium_core::{Autocompounder, WithdrawMode};
+use solana_sdk::{instruction::Instruction, pubkey::Pubkey, system_program, sysvar};
+
+struct AutocompounderAccounts {
+    autocompounder: Pubkey,
+    share_mint: Pubkey,
+    lp_vault: Pubkey,
+    user_share_token: Pubkey,
+}
+
+async fn init_autocompounder(env: &mut SeedPoolEnv) -> AutocompounderAccounts {
+    let program_id = equilibrium_core::id();
+    let (autocompounder, _) =
+        Pubkey::find_program_address(&[b"autocompounder", env.pool.as_ref()], &program_id);
+    let (share_mint, _) = Pubkey::find_program_address(
+        &[b"autocompounder-shares", autocompounder.as_ref()],
+        &program_id,
+    );
+    let (lp_vault, _) = Pubkey::find_program_address(
+        &[b"autocompounder-lp", autocompounder.as_ref()],
+        &program_id,
+    );
+
+    let init = Instruction {
+        program_id,
+        accounts: equilibrium_core::accounts::InitAutocompounder {
+            authority: env.user(),
+            amm_config: env.amm_config,
+            pool: env.pool,
+            lp_mint: env.lp_mint,
+            autocompounder,
+            share_mint,
+            lp_vault,
+            token_program: spl_token::ID,
+            system_program: system_program::ID,
+            rent: sysvar::rent::ID,
+        }
+        .to_account_metas(None),
+        data: InitAutocompounder {}.data(),
+    };
+    env.process(&[init]).await.unwrap();
+
+    let user = env.user();
+    let user_share_token = create_ata(&mut env.context, &share_mint, &user).await;
+    AutocompounderAccounts {
+        autocompounder,
+        share_mint,
+        lp_vault,
+        user_share_token,
+    }
+}
+
+fn shares_ix(
+    env: &SeedPoolEnv,
+    accounts: &AutocompounderAccounts,
+    data: impl InstructionData,
+) -> Instruction {
+    Instruction {
+        program_id: equilibrium_core::id(),
+        accounts: equilibrium_core::accounts::AutocompounderShares {
+            user: env.user(),
+            pool: env.pool,
+            autocompounder: accounts.autocompounder,
+            user_position: env.user_position,
+            share_mint: accounts.share_mint,
+            lp_vault: accounts.lp_vault,
+            user_lp_token: env.user_lp_token,
+            user_share_token: accounts.user_share_token,
+            token_program: spl_token::ID,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: data.data(),
+    }
+}
+
+#[tokio::test]
+#[ignore = "needs the SBF program from `anchor build`"]
+async fn compounding_rewards_grows_lp_per_share() {
+    common::require_program();
+
+    let mut env = SeedPoolEnv::new([1_000_000_000, 1_000_000_000, 1_000_000_000]).await;
+    let set_keeper = env.update_config_ix(SetRebalanceConfig {
+        keeper: env.user(),
+        rebalance_program: Pubkey::default(),
+        max_rebalance_bps: 0,
+    });
+    env.process(&[set_keeper]).await.unwrap();
+    let accounts = init_autocompounder(&mut env).await;
+
+    let lp_deposited = 100_000_000;
+    let deposit = |env: &SeedPoolEnv, min_shares_out| {
+        shares_ix(
+            env,
+            &accounts,
+            DepositToAutocompounder {
+                lp_amount: lp_deposited,
+                min_shares_out,
+            },
+        )
+    };
+    assert_program_error(
+        env.process(&[deposit(&env, lp_deposited + 1)]).await,
+        ErrorCode::SlippageExceeded,
+    );
+    let position_lp = env.position_state().await.lp_amount;
+    env.process(&[deposit(&env, lp_deposited)]).await.unwrap();
+    assert_eq!(
+        env.token_balance(accounts.user_share_token).await,
+        lp_deposited
+    );
+    // LP in the vault is no longer the position's
+    assert_eq!(
+        env.position_state().await.lp_amount,
+        position_lp - lp_deposited
+    );
+
+    // LP sent straight to the vault is not counted towards the share price
+    let donate = spl_token::instruction::transfer(
+        &spl_token::ID,
+        &env.user_lp_token,
+        &accounts.lp_vault,
+        &env.user(),
+        &[],
+        1_000_000,
+    )
+    .unwrap();
+    env.process(&[donate]).await.unwrap();
+    let state: Autocompounder = env.account_state(accounts.autocompounder).await;
+    assert_eq!(state.total_lp, lp_deposited);
+
+    // Rewards land in the autocompounder's own token accounts
+    let mut reward_tokens = [Pubkey::default(); 3];
+    for (reward_token, mint) in reward_tokens.iter_mut().zip(env.mints) {
+        *reward_token = create_ata(&mut env.context, &mint, &accounts.autocompounder).await;
+        mint_to(&mut env.context, &mint, reward_token, 1_000_000).await;
+    }
+
+    let compound = Instruction {
+        program_id: equilibrium_core::id(),
+        accounts: equilibrium_core::accounts::Compound {
+            keeper: env.user(),
+            amm_config: env.amm_config,
+            pool: env.pool,
+            lp_mint: env.lp_mint,
+            autocompounder: accounts.autocompounder,
+            lp_vault: accounts.lp_vault,
+            share_mint: accounts.share_mint,
+            reward_token_a: reward_tokens[0],
+            reward_token_b: reward_tokens[1],
+            reward_token_c: Some(reward_tokens[2]),
+            pool_token_a: env.pool_tokens[0],
+            pool_token_b: env.pool_tokens[1],
+            pool_token_c: Some(env.pool_tokens[2]),
+            token_program: spl_token::ID,
+        }
+        .to_account_metas(None),
+        data: Compound {}.data(),
+    };
+    env.process(&[compound]).await.unwrap();
+    let vault_lp = env
+        .account_state::<Autocompounder>(accounts.autocompounder)
+        .await
+        .total_lp;
+    assert!(vault_lp > lp_deposited);
+    for reward_token in reward_tokens {
+        assert_eq!(env.token_balance(reward_token).await, 0);
+    }
+
+    // Redeeming every share returns the deposit plus the compounded LP, less
+    // the sliver the virtual shares hold
+    let lp_before = env.token_balance(env.user_lp_token).await;
+    let withdraw = shares_ix(
+        &env,
+        &accounts,
+        WithdrawFromAutocompounder {
+            shares: lp_deposited,
+        },
+    );
+    env.process(&[withdraw]).await.unwrap();
+    let redeemed = env.token_balance(env.user_lp_token).await - lp_before;
+    assert!(redeemed > lp_deposited);
+    assert!(vault_lp - redeemed <= (vault_lp - lp_deposited) / 10_000);
+    assert_eq!(
+        env.position_state().await.lp_amount,
+        position_lp - lp_deposited + redeemed
+    );
+
+    // The compounded LP comes back into the position, so it redeems for reserves
+    let wallet_lp = env.token_balance(env.user_lp_token).await;
+    let withdraw = env.withdraw_ix(wallet_lp, [0; 3], WithdrawMode::Proportional);
+    env.process(&[withdraw]).await.unwrap();
+    assert_eq!(env.token_balance(env.user_lp_token).await, 0);
+}
@@ -0,0 +1,402 @@
+//! Batch auction mode on Growth pools: intents clear together at one price per side
+
+mod common;
+
+use anchor_lang::{InstructionData, ToAccountMetas};
+use common::{
+    assert_program_error, create_ata, create_growth_pool, mint_to, GrowthPool, SeedPoolEnv,
+};
+use equilibrium_core::errors::ErrorCode;
+use equilibrium_core::events::BatchSettled;
+use equilibrium_core::instruction::UpdatePoolParams;
+use equilibrium_core::{PoolParamChange, SwapBatch};
+use solana_sdk::{
+    instruction::{AccountMeta, Instruction},
+    native_token::LAMPORTS_PER_SOL,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    system_instruction, system_program,
+};
+
+const RESERVE: u64 = 1_000_000_000;
+const AMOUNT: u64 = 10_000_000;
+const WINDOW: u64 = 10;
+
+fn pda(seeds: &[&[u8]]) -> Pubkey {
+    Pubkey::find_program_address(seeds, &equilibrium_core::id()).0
+}
+
+fn swap_batch_address(growth: &GrowthPool) -> Pubkey {
+    pda(&[b"swap-batch", growth.pool.as_ref()])
+}
+
+fn intent_address(growth: &GrowthPool, owner: &Pubkey, batch_id: u64) -> Pubkey {
+    pda(&[
+        b"swap-intent",
+        growth.pool.as_ref(),
+        owner.as_ref(),
+        &batch_id.to_le_bytes(),
+    ])
+}
+
+fn growth_swap_ix(env: &SeedPoolEnv, growth: &GrowthPool, amount_in: u64) -> Instruction {
+    Instruction {
+        program_id: equilibrium_core::id(),
+        accounts: equilibrium_core::accounts::Swap {
+            user: env.user(),
+            amm_config: env.amm_config,
+            pool: growth.pool,
+            token_mint_in: growth.mints[0],
+            token_mint_out: growth.mints[1],
+            user_token_in: growth.user_tokens[0],
+            user_token_out: growth.user_tokens[1],
+            pool_token_in: growth.pool_tokens[0],
+            pool_token_out: growth.pool_tokens[1],
+            token_program: spl_token::ID,
+            recipient_token_out: None,
+            vote_escrow: None,
+            pool_snapshot: None,
+            credential: None,
+            integrator_fee_account: None,
+            referral_account: None,
+            referral_vault: None,
+        }
+        .to_account_metas(None),
+        data: equilibrium_core::instruction::Swap {
+            amount_in,
+            min_amount_out: 0,
+            deadline: i64::MAX,
+            integrator_fee_bps: 0,
+            max_price_impact_bps: None,
+        }
+        .data(),
+    }
+}
+
+fn set_window_ix(env: &SeedPoolEnv, pool: Pubkey, window_slots: u64) -> Instruction {
+    Instruction {
+        program_id: equilibrium_core::id(),
+        accounts: equilibrium_core::accounts::UpdatePool {
+            authority: env.user(),
+            amm_config: env.amm_config,
+            pool,
+        }
+        .to_account_metas(None),
+        data: UpdatePoolParams {
+            changes: vec![PoolParamChange::BatchWindow { window_slots }],
+        }
+        .data(),
+    }
+}
+
+fn init_batch_ix(env: &SeedPoolEnv, growth: &GrowthPool) -> Instruction {
+    Instruction {
+        program_id: equilibrium_core::id(),
+        accounts: equilibrium_core::accounts::InitSwapBatch {
+            payer: env.user(),
+            pool: growth.pool,
+            swap_batch: swap_batch_address(growth),
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: equilibrium_core::instruction::InitSwapBatch {}.data(),
+    }
+}
+
+fn submit_ix(
+    growth: &GrowthPool,
+    owner: &Pubkey,
+    owner_token_in: Pubkey,
+    batch_id: u64,
+    token_in_index: u8,
+    amount_in: u64,
+    min_amount_out: u64,
+) -> Instruction {
+    Instruction {
+        program_id: equilibrium_core::id(),
+        accounts: equilibrium_core::accounts::SubmitSwapIntent {
+            owner: *owner,
+            pool: growth.pool,
+            swap_batch: swap_batch_address(growth),
+            swap_intent: intent_address(growth, owner, batch_id),
+            owner_token_in,
+            pool_token_in: growth.pool_tokens[token_in_index as usize],
+            credential: None,
+            token_program: spl_token::ID,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: equilibrium_core::instruction::SubmitSwapIntent {
+            token_in_index,
+            amount_in,
+            min_amount_out,
+        }
+        .data(),
+    }
+}
+
+fn settle_ix(env: &SeedPoolEnv, growth: &GrowthPool, mut intents: Vec<Pubkey>) -> Instruction {
+    let mut accounts = equilibrium_core::accounts::SettleBatch {
+        keeper: env.user(),
+        amm_config: env.amm_config,
+        pool: growth.pool,
+        swap_batch: swap_batch_address(growth),
+    }
+    .to_account_metas(None);
+    intents.sort();
+    accounts.extend(
+        intents
+            .into_iter()
+            .map(|intent| AccountMeta::new(intent, false)),
+    );
+    Instruction {
+        program_id: equilibrium_core::id(),
+        accounts,
+        data: equilibrium_core::instruction::SettleBatch {}.data(),
+    }
+}
+
+fn claim_ix(
+    growth: &GrowthPool,
+    owner: &Pubkey,
+    batch_id: u64,
+    token_index: usize,
+    owner_token: Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id: equilibrium_core::id(),
+        accounts: equilibrium_core::accounts::ClaimSwapIntent {
+            owner: *owner,
+            pool: growth.pool,
+            swap_intent: intent_address(growth, owner, batch_id),
+            pool_token: growth.pool_tokens[token_index],
+            owner_token,
+            token_program: spl_token::ID,
+        }
+        .to_account_metas(None),
+        data: equilibrium_core::instruction::ClaimSwapIntent {}.data(),
+    }
+}
+
+#[tokio::test]
+#[ignore = "needs the SBF program from `anchor build`"]
+async fn opposing_intents_clear_at_one_price_and_only_the_net_hits_the_pool() {
+    common::require_program();
+
+    let mut env = SeedPoolEnv::new([RESERVE; 3]).await;
+    let growth = create_growth_pool(&mut env, RESERVE).await;
+    let user = env.user();
+
+    // A taker selling the partner token against the user's USD*
+    let taker = Keypair::new();
+    let fund = system_instruction::transfer(&user, &taker.pubkey(), LAMPORTS_PER_SOL);
+    env.process(&[fund]).await.unwrap();
+    let taker_tokens = [
+        create_ata(&mut env.context, &growth.mints[0], &taker.pubkey()).await,
+        create_ata(&mut env.context, &growth.mints[1], &taker.pubkey()).await,
+    ];
+    mint_to(&mut env.context, &growth.mints[1], &taker_tokens[1], AMOUNT).await;
+
+    // Batch mode is for Growth pools, and the window is capped
+    assert_program_error(
+        env.process(&[set_window_ix(&env, env.pool, WINDOW)]).await,
+        ErrorCode::InvalidPoolType,
+    );
+    assert_program_error(
+        env.process(&[set_window_ix(&env, growth.pool, 151)]).await,
+        ErrorCode::InvalidInstructionData,
+    );
+    env.process(&[
+        set_window_ix(&env, growth.pool, WINDOW),
+        init_batch_ix(&env, &growth),
+    ])
+    .await
+    .unwrap();
+
+    // Continuous swaps are off while the pool runs batches
+    assert_program_error(
+        env.process(&[growth_swap_ix(&env, &growth, AMOUNT)]).await,
+        ErrorCode::BatchModeActive,
+    );
+
+    // Dust intents cannot take up the batch's slots
+    assert_program_error(
+        env.process(&[submit_ix(
+            &growth,
+            &user,
+            growth.user_tokens[0],
+            0,
+            0,
+            RESERVE / 10_000,
+            1,
+        )])
+        .await,
+        ErrorCode::IntentTooSmall,
+    );
+
+    env.process(&[submit_ix(
+        &growth,
+        &user,
+        growth.user_tokens[0],
+        0,
+        0,
+        AMOUNT,
+        1,
+    )])
+    .await
+    .unwrap();
+    let taker_submit = submit_ix(
+        &growth,
+        &taker.pubkey(),
+        taker_tokens[1],
+        0,
+        1,
+        AMOUNT / 2,
+        1,
+    );
+    env.process_signed(&[taker_submit], &[&taker])
+        .await
+        .unwrap();
+    // The vaults owe the submitted input back until settlement
+    let pool = env
+        .account_state::<equilibrium_core::Pool>(growth.pool)
+        .await;
+    assert_eq!(pool.queued_owed[..2], [AMOUNT, AMOUNT / 2]);
+
+    let intents = vec![
+        intent_address(&growth, &user, 0),
+        intent_address(&growth, &taker.pubkey(), 0),
+    ];
+
+    // Nothing settles or pays out before the window closes
+    assert_program_error(
+        env.process(&[settle_ix(&env, &growth, intents.clone())])
+            .await,
+        ErrorCode::BatchWindowOpen,
+    );
+    assert_program_error(
+        env.process(&[claim_ix(&growth, &user, 0, 1, growth.user_tokens[1])])
+            .await,
+        ErrorCode::IntentNotSettled,
+    );
+
+    // Leaving an intent out of the settlement is refused
+    env.advance_slots(WINDOW).await;
+    assert_program_error(
+        env.process(&[settle_ix(&env, &growth, intents[..1].to_vec())])
+            .await,
+        ErrorCode::InvalidInputLength,
+    );
+
+    let reserves_before = env
+        .account_state::<equilibrium_core::Pool>(growth.pool)
+        .await
+        .reserves;
+    let events = env
+        .process_events::<BatchSettled>(&[settle_ix(&env, &growth, intents)])
+        .await
+        .unwrap();
+    assert_eq!(events.len(), 1);
+    let settled = &events[0];
+    assert_eq!(settled.filled, 2);
+    assert_eq!(settled.refunded, 0);
+    assert_eq!(settled.amounts_in, [AMOUNT, AMOUNT / 2]);
+
+    // Only the USD* left over after matching the taker was sold into the pool
+    assert_eq!(settled.net_token_in_index, 0);
+    assert!(settled.net_amount_in > 0 && settled.net_amount_in < AMOUNT / 2 + AMOUNT / 100);
+    let reserves_after = env
+        .account_state::<equilibrium_core::Pool>(growth.pool)
+        .await
+        .reserves;
+    assert_eq!(
+        reserves_after[0],
+        reserves_before[0] + settled.net_amount_in
+    );
+    assert!(reserves_after[1] < reserves_before[1]);
+
+    // Each side collects its share of the other side's flow
+    let before = env.token_balance(growth.user_tokens[1]).await;
+    env.process(&[claim_ix(&growth, &user, 0, 1, growth.user_tokens[1])])
+        .await
+        .unwrap();
+    let user_out = env.token_balance(growth.user_tokens[1]).await - before;
+    assert_eq!(user_out, settled.amounts_out[0]);
+    assert!(user_out > AMOUNT * 98 / 100 && user_out < AMOUNT);
+
+    let taker_claim = claim_ix(&growth, &taker.pubkey(), 0, 0, taker_tokens[0]);
+    env.process_signed(&[taker_claim], &[&taker]).await.unwrap();
+    assert_eq!(
+        env.token_balance(taker_tokens[0]).await,
+        settled.amounts_out[1]
+    );
+    assert!(settled.amounts_out[1] <= AMOUNT / 2 + AMOUNT / 100);
+    let pool = env
+        .account_state::<equilibrium_core::Pool>(growth.pool)
+        .await;
+    assert_eq!(pool.queued_owed[..2], [0, 0]);
+
+    let batch: SwapBatch = env.account_state(swap_batch_address(&growth)).await;
+    assert_eq!(batch.batch_id, 1);
+    assert_eq!(batch.intent_count, 0);
+}
+
+#[tokio::test]
+#[ignore = "needs the SBF program from `anchor build`"]
+async fn intents_the_clearing_price_misses_are_refunded() {
+    common::require_program();
+
+    let mut env = SeedPoolEnv::new([RESERVE; 3]).await;
+    let growth = create_growth_pool(&mut env, RESERVE).await;
+    let user = env.user();
+    env.process(&[
+        set_window_ix(&env, growth.pool, WINDOW),
+        init_batch_ix(&env, &growth),
+    ])
+    .await
+    .unwrap();
+
+    // A one-sided batch asking for more than the pool pays
+    let balance = env.token_balance(growth.user_tokens[0]).await;
+    env.process(&[submit_ix(
+        &growth,
+        &user,
+        growth.user_tokens[0],
+        0,
+        0,
+        AMOUNT,
+        AMOUNT,
+    )])
+    .await
+    .unwrap();
+    env.advance_slots(WINDOW).await;
+    let events = env
+        .process_events::<BatchSettled>(&[settle_ix(
+            &env,
+            &growth,
+            vec![intent_address(&growth, &user, 0)],
+        )])
+        .await
+        .unwrap();
+    assert_eq!(events[0].filled, 0);
+    assert_eq!(events[0].refunded, 1);
+    assert_eq!(events[0].net_amount_in, 0);
+
+    // The owner takes the input back, and the pool's reserves never moved
+    env.process(&[claim_ix(&growth, &user, 0, 0, growth.user_tokens[0])])
+        .await
+        .unwrap();
+    assert_eq!(env.token_balance(growth.user_tokens[0]).await, balance);
+    let pool = env
+        .account_state::<equilibrium_core::Pool>(growth.pool)
+        .await;
+    assert_eq!(pool.reserves, vec![RESERVE, RESERVE]);
+
+    // Turning batch mode off restores continuous swaps
+    env.process(&[set_window_ix(&env, growth.pool, 0)])
+        .await
+        .unwrap();
+    env.process(&[growth_swap_ix(&env, &growth, AMOUNT)])
+        .await
+        .unwrap();
+}
@@ -0,0 +1,52 @@
+//! Per-wallet deposit limits during a pool's bootstrap phase
+
+mod common;
+
+use common::{assert_program_error, SeedPoolEnv};
+use equilibrium_core::errors::ErrorCode;
+use equilibrium_core::instruction::SetBootstrapLimits;
+use solana_sdk::clock::Clock;
+
+#[tokio::test]
+#[ignore = "needs the SBF program from `anchor build`"]
+async fn deposits_are_capped_per_wallet_until_bootstrap_ends() {
+    common::require_program();
+
+    let mut env = SeedPoolEnv::new([1_000_000_000, 1_000_000_000, 1_000_000_000]).await;
+    let clock: Clock = env.context.banks_client.get_sysvar().await.unwrap();
+    let bootstrap_end_ts = clock.unix_timestamp + 3_600;
+
+    // A bootstrap phase without a limit would block every deposit
+    let set_limits = env.update_pool_ix(SetBootstrapLimits {
+        bootstrap_end_ts,
+        max_deposit_per_wallet: 0,
+    });
+    assert_program_error(
+        env.process(&[set_limits]).await,
+        ErrorCode::InvalidBootstrapConfig,
+    );
+
+    let set_limits = env.update_pool_ix(SetBootstrapLimits {
+        bootstrap_end_ts,
+        max_deposit_per_wallet: 3_000_000,
+    });
+    env.process(&[set_limits]).await.unwrap();
+
+    let deposit = env.deposit_ix([1_000_000, 1_000_000, 0], 0);
+    env.process(&[deposit]).await.unwrap();
+    assert_eq!(env.position_state().await.bootstrap_deposited, 2_000_000);
+
+    // The cap counts everything the position deposited so far
+    let deposit = env.deposit_ix([600_000, 600_000, 0], 0);
+    assert_program_error(
+        env.process(&[deposit]).await,
+        ErrorCode::BootstrapDepositLimitExceeded,
+    );
+    let deposit = env.deposit_ix([500_000, 500_000, 0], 0);
+    env.process(&[deposit]).await.unwrap();
+
+    env.advance_clock(3_600).await;
+    let deposit = env.deposit_ix([5_000_000, 5_000_000, 5_000_000], 0);
+    env.process(&[deposit]).await.unwrap();
+    assert_eq!(env.position_state().await.bootstrap_deposited, 3_000_000);
+}
@@ -0,0 +1,38 @@
+//! Pool timestamps never run backwards when the validator clock does
+
+mod common;
+
+use common::SeedPoolEnv;
+
+const RESERVE: u64 = 1_000_000_000;
+
+#[tokio::test]
+#[ignore = "needs the SBF program from `anchor build`"]
+async fn regressed_clock_does_not_rewind_pool_updates() {
+    common::require_program();
+
+    let mut env = SeedPoolEnv::new([RESERVE; 3]).await;
+    env.advance_slots(1).await;
+    let swap = env.swap_ix(0, 1, 1_000_000, 0);
+    env.process(&[swap]).await.unwrap();
+    let first = env.pool_state().await;
+    assert!(first.last_update_slot > 0);
+
+    // The clock steps an hour back, as it can across a fork
+    env.advance_clock(-3_600).await;
+    env.advance_slots(1).await;
+    let swap = env.swap_ix(1, 0, 1_000_000, 0);
+    env.process(&[swap]).await.unwrap();
+    let second = env.pool_state().await;
+    assert_eq!(second.last_update, first.last_update);
+    assert!(second.last_update_slot > first.last_update_slot);
+
+    // Once the clock catches up the pool follows it again
+    env.advance_clock(7_200).await;
+    let deposit = env.deposit_ix([1_000_000, 0, 0], 0);
+    env.process(&[deposit]).await.unwrap();
+    assert_eq!(
+        env.pool_state().await.last_update,
+        first.last_update + 3_600
+    );
+}
@@ -0,0 +1,102 @@
+//! Closing a deprecated token's empty vault and removing the token from the pool
+
+mod common;
+
+use anchor_lang::{InstructionData, ToAccountMetas};
+use common::{assert_program_error, SeedPoolEnv};
+use equilibrium_core::errors::ErrorCode;
+use equilibrium_core::events::PoolVaultClosed;
+use equilibrium_core::instruction::SetTokenFrozen;
+use equilibrium_core::Pool;
+use solana_sdk::{instruction::Instruction, system_program};
+
+const RESERVE: u64 = 1_000_000_000;
+
+fn close_ix(env: &SeedPoolEnv, token_index: u8) -> Instruction {
+    Instruction {
+        program_id: equilibrium_core::id(),
+        accounts: equilibrium_core::accounts::ClosePoolVault {
+            authority: env.user(),
+            amm_config: env.amm_config,
+            pool: env.pool,
+            lp_mint: env.lp_mint,
+            vault: env.pool_tokens[token_index as usize],
+            token_program: spl_token::ID,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: equilibrium_core::instruction::ClosePoolVault { token_index }.data(),
+    }
+}
+
+#[tokio::test]
+#[ignore = "needs the SBF program from `anchor build`"]
+async fn empty_frozen_vault_is_closed_and_its_token_removed() {
+    common::require_program();
+
+    let mut env = SeedPoolEnv::new([RESERVE; 3]).await;
+    let before = env.pool_state().await;
+    env.drain_token(2).await;
+
+    // The token must be frozen first, and only an empty vault can go
+    assert_program_error(
+        env.process(&[close_ix(&env, 2)]).await,
+        ErrorCode::TokenNotFrozen,
+    );
+    for token_mint in [env.mints[0], env.mints[2]] {
+        let freeze = env.update_pool_ix(SetTokenFrozen {
+            token_mint,
+            frozen: true,
+        });
+        env.process(&[freeze]).await.unwrap();
+    }
+    assert_program_error(
+        env.process(&[close_ix(&env, 0)]).await,
+        ErrorCode::VaultNotEmpty,
+    );
+
+    let pool_rent = env.account(env.pool).await.lamports;
+    let events = env
+        .process_events::<PoolVaultClosed>(&[close_ix(&env, 2)])
+        .await
+        .unwrap();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].token_mint, env.mints[2]);
+    assert_eq!(events[0].token_index, 2);
+
+    // The pool shrank to the two-token layout and returned the freed rent
+    let pool_account = env.account(env.pool).await;
+    assert_eq!(pool_account.data.len(), Pool::space(2));
+    assert!(pool_account.lamports < pool_rent);
+    assert!(env
+        .context
+        .banks_client
+        .get_account(env.pool_tokens[2])
+        .await
+        .unwrap()
+        .is_none());
+
+    let after = env.pool_state().await;
+    assert_eq!(after.token_mints, before.token_mints[..2]);
+    assert_eq!(after.token_accounts, before.token_accounts[..2]);
+    assert_eq!(after.reserves, before.reserves[..2]);
+    assert_eq!(after.target_weights.iter().sum::<u64>(), 10_000);
+    assert_eq!(events[0].target_weights, after.target_weights);
+    assert!(after.last_d > 0 && after.virtual_price > 0);
+
+    // The remaining pair keeps trading once unfrozen
+    let unfreeze = env.update_pool_ix(SetTokenFrozen {
+        token_mint: env.mints[0],
+        frozen: false,
+    });
+    env.process(&[unfreeze]).await.unwrap();
+    env.process(&[env.swap_ix(0, 1, 1_000_000, 0)])
+        .await
+        .unwrap();
+
+    // A pool keeps at least two tokens
+    assert_program_error(
+        env.process(&[close_ix(&env, 1)]).await,
+        ErrorCode::InvalidInputLength,
+    );
+}
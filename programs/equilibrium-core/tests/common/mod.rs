@@ -0,0 +1,880 @@
+//! Shared solana-program-test harness for the integration suites
+//!
+//! The suites execute the compiled SBF program, so they are `#[ignore]`d by
+//! default: build it with `anchor build` (or `cargo build-sbf`), then run them
+//! with `cargo test -p equilibrium-core -- --ignored`. Without the binary they
+//! fail rather than pass having exercised nothing.
+
+#![allow(dead_code)]
+
+use anchor_lang::{AccountDeserialize, AccountSerialize, InstructionData, ToAccountMetas};
+use base64::Engine;
+use equilibrium_core::errors::ErrorCode;
+use equilibrium_core::state::math::ORACLE_PRICE_PRECISION;
+use solana_program_test::{BanksClientError, ProgramTest, ProgramTestContext};
+use solana_sdk::{
+    account::{Account, AccountSharedData},
+    clock::Clock,
+    compute_budget::ComputeBudgetInstruction,
+    instruction::{Instruction, InstructionError},
+    program_pack::Pack,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    system_instruction, system_program, sysvar,
+    transaction::{Transaction, TransactionError},
+};
+use std::path::PathBuf;
+
+pub const DECIMALS: u8 = 6;
+pub const INITIAL_MINT_AMOUNT: u64 = 10_000_000_000_000; // 10M tokens with 6 decimals
+pub const DEFAULT_AMPLIFICATION: u64 = 200;
+pub const DEFAULT_WEIGHTS: [u64; 3] = [4500, 3500, 2000];
+/// Namespace of the config every suite runs against
+pub const CONFIG_INDEX: u16 = 0;
+
+/// Locate the compiled program, pointing `SBF_OUT_DIR` at `target/deploy` when needed
+///
+/// Panics when it is missing, so a suite never passes without running.
+pub fn require_program() {
+    let file_name = "equilibrium_core.so";
+    for var in ["BPF_OUT_DIR", "SBF_OUT_DIR"] {
+        if let Ok(dir) = std::env::var(var) {
+            let path = PathBuf::from(dir).join(file_name);
+            assert!(
+                path.exists(),
+                "{} not found, run `anchor build` first",
+                path.display()
+            );
+            return;
+        }
+    }
+
+    let deploy_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../../target/deploy");
+    assert!(
+        deploy_dir.join(file_name).exists(),
+        "equilibrium_core.so not found, run `anchor build` first"
+    );
+    std::env::set_var("SBF_OUT_DIR", deploy_dir);
+}
+
+/// Redeploy the program under the upgradeable loader with `upgrade_authority`,
+/// returning its program data address
+///
+/// `ProgramTest` loads programs under the non-upgradeable loader, which leaves
+/// instructions gated on the upgrade authority nothing to check against.
+pub fn add_upgradeable_program(
+    program_test: &mut ProgramTest,
+    upgrade_authority: &Pubkey,
+) -> Pubkey {
+    use solana_sdk::account::WritableAccount;
+    use solana_sdk::bpf_loader_upgradeable::{self, UpgradeableLoaderState};
+
+    let dir = std::env::var("SBF_OUT_DIR")
+        .or_else(|_| std::env::var("BPF_OUT_DIR"))
+        .expect("require_program sets the output directory");
+    let elf = std::fs::read(PathBuf::from(dir).join("equilibrium_core.so")).unwrap();
+    let program_id = equilibrium_core::id();
+    let program_data =
+        Pubkey::find_program_address(&[program_id.as_ref()], &bpf_loader_upgradeable::ID).0;
+    let rent = solana_sdk::rent::Rent::default();
+
+    let program_state = UpgradeableLoaderState::Program {
+        programdata_address: program_data,
+    };
+    let mut program = AccountSharedData::new_data(
+        rent.minimum_balance(UpgradeableLoaderState::size_of_program()),
+        &program_state,
+        &bpf_loader_upgradeable::ID,
+    )
+    .unwrap();
+    program.set_executable(true);
+
+    let metadata_len = UpgradeableLoaderState::size_of_programdata_metadata();
+    let data_len = metadata_len + elf.len();
+    let mut data = AccountSharedData::new(
+        rent.minimum_balance(data_len),
+        data_len,
+        &bpf_loader_upgradeable::ID,
+    );
+    data.serialize_data(&UpgradeableLoaderState::ProgramData {
+        slot: 0,
+        upgrade_authority_address: Some(*upgrade_authority),
+    })
+    .unwrap();
+    data.data_as_mut_slice()[metadata_len..].copy_from_slice(&elf);
+
+    program_test.add_account(program_id, program.into());
+    program_test.add_account(program_data, data.into());
+    program_data
+}
+
+/// A Seed Pool deployment funded for a single user (the payer)
+pub struct SeedPoolEnv {
+    pub context: ProgramTestContext,
+    pub mints: [Pubkey; 3],
+    pub user_tokens: [Pubkey; 3],
+    pub amm_config: Pubkey,
+    pub pool: Pubkey,
+    pub pool_tokens: [Pubkey; 3],
+    pub lp_mint: Pubkey,
+    pub user_lp_token: Pubkey,
+    pub user_position: Pubkey,
+}
+
+impl SeedPoolEnv {
+    /// Start a validator, create the three stablecoin mints, and create the Seed Pool
+    pub async fn new(initial_amounts: [u64; 3]) -> Self {
+        Self::with_programs(initial_amounts, |_| {}).await
+    }
+
+    /// Like `new`, letting the caller register extra programs (e.g. a mock
+    /// multisig) before the validator starts
+    pub async fn with_programs(
+        initial_amounts: [u64; 3],
+        add_programs: impl FnOnce(&mut ProgramTest),
+    ) -> Self {
+        let mut program_test = ProgramTest::new("equilibrium_core", equilibrium_core::id(), None);
+        add_programs(&mut program_test);
+        let mut context = program_test.start_with_context().await;
+        let user = context.payer.pubkey();
+
+        let mut mints = [Pubkey::default(); 3];
+        let mut user_tokens = [Pubkey::default(); 3];
+        for i in 0..3 {
+            mints[i] = create_mint(&mut context).await;
+            user_tokens[i] = create_ata(&mut context, &mints[i], &user).await;
+            mint_to(
+                &mut context,
+                &mints[i],
+                &user_tokens[i],
+                INITIAL_MINT_AMOUNT,
+            )
+            .await;
+        }
+
+        let program_id = equilibrium_core::id();
+        let amm_config = config_address(CONFIG_INDEX);
+        let pool = seed_pool_address(&amm_config);
+        let (lp_mint, _) = Pubkey::find_program_address(&[b"lp-mint", pool.as_ref()], &program_id);
+        let pool_tokens = mints.map(|mint| {
+            Pubkey::find_program_address(
+                &[b"pool-token", pool.as_ref(), mint.as_ref()],
+                &program_id,
+            )
+            .0
+        });
+        let user_lp_token =
+            spl_associated_token_account::get_associated_token_address(&user, &lp_mint);
+        let (user_position, _) = Pubkey::find_program_address(
+            &[b"user-position", user.as_ref(), pool.as_ref()],
+            &program_id,
+        );
+
+        let mut env = Self {
+            context,
+            mints,
+            user_tokens,
+            amm_config,
+            pool,
+            pool_tokens,
+            lp_mint,
+            user_lp_token,
+            user_position,
+        };
+
+        let initialize = Instruction {
+            program_id,
+            accounts: equilibrium_core::accounts::Initialize {
+                authority: user,
+                amm_config,
+                system_program: system_program::ID,
+            }
+            .to_account_metas(None),
+            data: equilibrium_core::instruction::Initialize {
+                index: CONFIG_INDEX,
+                default_amplification: DEFAULT_AMPLIFICATION,
+                default_target_weights: DEFAULT_WEIGHTS,
+            }
+            .data(),
+        };
+        env.process(&[initialize]).await.unwrap();
+
+        let create_seed_pool = Instruction {
+            program_id,
+            accounts: equilibrium_core::accounts::CreateSeedPool {
+                payer: user,
+                amm_config,
+                pool,
+                token_mint_a: mints[0],
+                token_mint_b: mints[1],
+                token_mint_c: mints[2],
+                user_token_a: user_tokens[0],
+                user_token_b: user_tokens[1],
+                user_token_c: user_tokens[2],
+                pool_token_a: pool_tokens[0],
+                pool_token_b: pool_tokens[1],
+                pool_token_c: pool_tokens[2],
+                lp_mint,
+                user_lp_token,
+                token_program: spl_token::ID,
+                associated_token_program: spl_associated_token_account::ID,
+                system_program: system_program::ID,
+                rent: sysvar::rent::ID,
+                authority: user,
+            }
+            .to_account_metas(None),
+            data: equilibrium_core::instruction::CreateSeedPool {
+                amplification: DEFAULT_AMPLIFICATION,
+                target_weights: DEFAULT_WEIGHTS.to_vec(),
+                initial_amounts: initial_amounts.to_vec(),
+            }
+            .data(),
+        };
+        env.process(&[create_seed_pool]).await.unwrap();
+
+        env
+    }
+
+    pub fn user(&self) -> Pubkey {
+        self.context.payer.pubkey()
+    }
+
+    /// Send a transaction signed by the payer, returning the compute units consumed
+    pub async fn process(&mut self, instructions: &[Instruction]) -> Result<u64, BanksClientError> {
+        self.process_signed(instructions, &[]).await
+    }
+
+    /// Like `process`, with extra signers alongside the payer
+    pub async fn process_signed(
+        &mut self,
+        instructions: &[Instruction],
+        signers: &[&Keypair],
+    ) -> Result<u64, BanksClientError> {
+        Ok(self.execute(instructions, signers).await?.0)
+    }
+
+    /// Like `process`, returning the `E` events the transaction emitted
+    pub async fn process_events<E: anchor_lang::Event>(
+        &mut self,
+        instructions: &[Instruction],
+    ) -> Result<Vec<E>, BanksClientError> {
+        let (_, logs) = self.execute(instructions, &[]).await?;
+        Ok(logs
+            .iter()
+            .filter_map(|log| log.strip_prefix("Program data: "))
+            .filter_map(|data| base64::engine::general_purpose::STANDARD.decode(data).ok())
+            .filter(|data| data.starts_with(&E::DISCRIMINATOR))
+            .map(|data| E::try_from_slice(&data[E::DISCRIMINATOR.len()..]).unwrap())
+            .collect())
+    }
+
+    /// Send a transaction signed by the payer and `signers`, returning the compute
+    /// units consumed and the program logs
+    async fn execute(
+        &mut self,
+        instructions: &[Instruction],
+        signers: &[&Keypair],
+    ) -> Result<(u64, Vec<String>), BanksClientError> {
+        let mut all = vec![ComputeBudgetInstruction::set_compute_unit_limit(1_400_000)];
+        all.extend_from_slice(instructions);
+
+        let blockhash = self.context.get_new_latest_blockhash().await.unwrap();
+        let mut all_signers = vec![&self.context.payer];
+        all_signers.extend_from_slice(signers);
+        let transaction = Transaction::new_signed_with_payer(
+            &all,
+            Some(&self.context.payer.pubkey()),
+            &all_signers,
+            blockhash,
+        );
+        let outcome = self
+            .context
+            .banks_client
+            .process_transaction_with_metadata(transaction)
+            .await?;
+        outcome.result?;
+        Ok(outcome
+            .metadata
+            .map(|m| (m.compute_units_consumed, m.log_messages))
+            .unwrap_or_default())
+    }
+
+    pub fn deposit_ix(&self, amounts: [u64; 3], min_lp_amount: u64) -> Instruction {
+        Instruction {
+            program_id: equilibrium_core::id(),
+            accounts: equilibrium_core::accounts::Deposit {
+                user: self.user(),
+                pool: self.pool,
+                lp_mint: self.lp_mint,
+                user_lp_token: self.user_lp_token,
+                user_token_a: self.user_tokens[0],
+                user_token_b: self.user_tokens[1],
+                user_token_c: Some(self.user_tokens[2]),
+                token_mint_a: self.mints[0],
+                token_mint_b: self.mints[1],
+                token_mint_c: Some(self.mints[2]),
+                pool_token_a: self.pool_tokens[0],
+                pool_token_b: self.pool_tokens[1],
+                pool_token_c: Some(self.pool_tokens[2]),
+                user_position: self.user_position,
+                token_program: spl_token::ID,
+                system_program: system_program::ID,
+                rent: sysvar::rent::ID,
+                delegate: None,
+                pool_snapshot: None,
+                credential: None,
+                user_portfolio: None,
+            }
+            .to_account_metas(None),
+            data: equilibrium_core::instruction::Deposit {
+                amounts,
+                min_lp_amount,
+                concentration: 1000,
+                deadline: i64::MAX,
+            }
+            .data(),
+        }
+    }
+
+    pub fn deposit_exact_lp_ix(&self, lp_amount: u64, max_amounts: [u64; 3]) -> Instruction {
+        Instruction {
+            data: equilibrium_core::instruction::DepositExactLp {
+                lp_amount,
+                max_amounts,
+                concentration: 1000,
+                deadline: i64::MAX,
+            }
+            .data(),
+            ..self.deposit_ix([0; 3], 0)
+        }
+    }
+
+    pub fn deposit_with_max_imbalance_fee_ix(
+        &self,
+        amounts: [u64; 3],
+        max_imbalance_fee_bps: u64,
+    ) -> Instruction {
+        Instruction {
+            data: equilibrium_core::instruction::DepositWithMaxImbalanceFee {
+                amounts,
+                max_imbalance_fee_bps,
+                concentration: 1000,
+                deadline: i64::MAX,
+            }
+            .data(),
+            ..self.deposit_ix([0; 3], 0)
+        }
+    }
+
+    pub fn swap_ix(
+        &self,
+        token_in: usize,
+        token_out: usize,
+        amount_in: u64,
+        min_amount_out: u64,
+    ) -> Instruction {
+        Instruction {
+            program_id: equilibrium_core::id(),
+            accounts: equilibrium_core::accounts::Swap {
+                user: self.user(),
+                amm_config: self.amm_config,
+                pool: self.pool,
+                token_mint_in: self.mints[token_in],
+                token_mint_out: self.mints[token_out],
+                user_token_in: self.user_tokens[token_in],
+                user_token_out: self.user_tokens[token_out],
+                pool_token_in: self.pool_tokens[token_in],
+                pool_token_out: self.pool_tokens[token_out],
+                token_program: spl_token::ID,
+                recipient_token_out: None,
+                vote_escrow: None,
+                pool_snapshot: None,
+                credential: None,
+                integrator_fee_account: None,
+                referral_account: None,
+                referral_vault: None,
+            }
+            .to_account_metas(None),
+            data: equilibrium_core::instruction::Swap {
+                amount_in,
+                min_amount_out,
+                deadline: i64::MAX,
+                integrator_fee_bps: 0,
+                max_price_impact_bps: None,
+            }
+            .data(),
+        }
+    }
+
+    pub fn withdraw_ix(
+        &self,
+        lp_amount: u64,
+        min_amounts: [u64; 3],
+        mode: equilibrium_core::WithdrawMode,
+    ) -> Instruction {
+        Instruction {
+            program_id: equilibrium_core::id(),
+            accounts: equilibrium_core::accounts::Withdraw {
+                user: self.user(),
+                amm_config: self.amm_config,
+                pool: self.pool,
+                lp_mint: self.lp_mint,
+                user_lp_token: self.user_lp_token,
+                user_token_a: self.user_tokens[0],
+                user_token_b: self.user_tokens[1],
+                user_token_c: Some(self.user_tokens[2]),
+                token_mint_a: self.mints[0],
+                token_mint_b: self.mints[1],
+                token_mint_c: Some(self.mints[2]),
+                pool_token_a: self.pool_tokens[0],
+                pool_token_b: self.pool_tokens[1],
+                pool_token_c: Some(self.pool_tokens[2]),
+                user_position: self.user_position,
+                token_program: spl_token::ID,
+                recipient_token_a: None,
+                recipient_token_b: None,
+                recipient_token_c: None,
+                delegate: None,
+                vote_escrow: None,
+                fee_exemption: None,
+                pool_snapshot: None,
+                user_portfolio: None,
+            }
+            .to_account_metas(None),
+            data: equilibrium_core::instruction::Withdraw {
+                lp_amount,
+                min_amounts,
+                mode,
+                deadline: i64::MAX,
+            }
+            .data(),
+        }
+    }
+
+    pub fn withdraw_imbalanced_ix(&self, amounts: [u64; 3], max_lp_burn: u64) -> Instruction {
+        Instruction {
+            data: equilibrium_core::instruction::WithdrawImbalanced {
+                amounts,
+                max_lp_burn,
+                deadline: i64::MAX,
+            }
+            .data(),
+            ..self.withdraw_ix(0, [0; 3], equilibrium_core::WithdrawMode::Proportional)
+        }
+    }
+
+    pub fn withdraw_percent_ix(
+        &self,
+        bps: u64,
+        mode: equilibrium_core::WithdrawMode,
+    ) -> Instruction {
+        Instruction {
+            data: equilibrium_core::instruction::WithdrawPercent {
+                bps,
+                min_amounts: [0; 3],
+                mode,
+                deadline: i64::MAX,
+            }
+            .data(),
+            ..self.withdraw_ix(0, [0; 3], mode)
+        }
+    }
+
+    /// Move the validator clock forward by `seconds`
+    pub async fn advance_clock(&mut self, seconds: i64) {
+        let mut clock: Clock = self.context.banks_client.get_sysvar().await.unwrap();
+        clock.unix_timestamp += seconds;
+        self.context.set_sysvar(&clock);
+    }
+
+    pub async fn advance_slots(&mut self, slots: u64) {
+        let clock: Clock = self.context.banks_client.get_sysvar().await.unwrap();
+        self.context.warp_to_slot(clock.slot + slots).unwrap();
+    }
+
+    /// Simulate a transaction signed by the payer, returning the program's return data
+    pub async fn simulate(&mut self, instructions: &[Instruction]) -> Vec<u8> {
+        let blockhash = self.context.get_new_latest_blockhash().await.unwrap();
+        let transaction = Transaction::new_signed_with_payer(
+            instructions,
+            Some(&self.context.payer.pubkey()),
+            &[&self.context.payer],
+            blockhash,
+        );
+        let simulation = self
+            .context
+            .banks_client
+            .simulate_transaction(transaction)
+            .await
+            .unwrap();
+        simulation.result.unwrap().unwrap();
+        simulation
+            .simulation_details
+            .and_then(|details| details.return_data)
+            .map(|return_data| return_data.data)
+            .unwrap_or_default()
+    }
+
+    /// Build a read-only view instruction against the Seed Pool
+    pub fn view_ix(&self, data: impl InstructionData) -> Instruction {
+        Instruction {
+            program_id: equilibrium_core::id(),
+            accounts: equilibrium_core::accounts::ViewPool {
+                amm_config: self.amm_config,
+                pool: self.pool,
+                lp_mint: self.lp_mint,
+            }
+            .to_account_metas(None),
+            data: data.data(),
+        }
+    }
+
+    /// Build an `UpdatePool` setter signed by the config authority
+    pub fn update_pool_ix(&self, data: impl InstructionData) -> Instruction {
+        Instruction {
+            program_id: equilibrium_core::id(),
+            accounts: equilibrium_core::accounts::UpdatePool {
+                authority: self.user(),
+                amm_config: self.amm_config,
+                pool: self.pool,
+            }
+            .to_account_metas(None),
+            data: data.data(),
+        }
+    }
+
+    /// Build an `UpdateConfig` setter signed by the config authority
+    pub fn update_config_ix(&self, data: impl InstructionData) -> Instruction {
+        Instruction {
+            program_id: equilibrium_core::id(),
+            accounts: equilibrium_core::accounts::UpdateConfig {
+                authority: self.user(),
+                amm_config: self.amm_config,
+            }
+            .to_account_metas(None),
+            data: data.data(),
+        }
+    }
+
+    /// Fetch and deserialize the AMM config account
+    /// Deserialize any program account at `address`
+    pub async fn account_state<T: anchor_lang::AccountDeserialize>(
+        &mut self,
+        address: Pubkey,
+    ) -> T {
+        let account = self.account(address).await;
+        T::try_deserialize(&mut account.data.as_slice()).unwrap()
+    }
+
+    pub async fn config_state(&mut self) -> equilibrium_core::AmmConfig {
+        let account = self.account(self.amm_config).await;
+        anchor_lang::AccountDeserialize::try_deserialize(&mut account.data.as_slice()).unwrap()
+    }
+
+    /// Fetch and deserialize the Seed Pool account
+    pub async fn pool_state(&mut self) -> equilibrium_core::Pool {
+        let account = self.account(self.pool).await;
+        anchor_lang::AccountDeserialize::try_deserialize(&mut account.data.as_slice()).unwrap()
+    }
+
+    /// Fetch and deserialize the payer's position in the Seed Pool
+    pub async fn position_state(&mut self) -> equilibrium_core::UserPosition {
+        let account = self.account(self.user_position).await;
+        anchor_lang::AccountDeserialize::try_deserialize(&mut account.data.as_slice()).unwrap()
+    }
+
+    pub async fn token_balance(&mut self, token_account: Pubkey) -> u64 {
+        let account = self.account(token_account).await;
+        spl_token::state::Account::unpack(&account.data)
+            .unwrap()
+            .amount
+    }
+
+    pub async fn account(&mut self, address: Pubkey) -> Account {
+        self.context
+            .banks_client
+            .get_account(address)
+            .await
+            .unwrap()
+            .expect("account not found")
+    }
+
+    /// Zero token `index`'s reserve and vault balance, as a deprecated token's would be
+    pub async fn drain_token(&mut self, index: usize) {
+        let mut account = self.account(self.pool).await;
+        let mut pool = equilibrium_core::Pool::try_deserialize(&mut &account.data[..]).unwrap();
+        pool.reserves[index] = 0;
+        let mut data = Vec::new();
+        pool.try_serialize(&mut data).unwrap();
+        account.data[..data.len()].copy_from_slice(&data);
+        self.context
+            .set_account(&self.pool, &AccountSharedData::from(account));
+
+        self.set_token_balance(self.pool_tokens[index], 0).await;
+    }
+
+    /// Overwrite a token account's balance, e.g. to leave a pool vault short of its reserve
+    pub async fn set_token_balance(&mut self, token_account: Pubkey, amount: u64) {
+        let mut account = self.account(token_account).await;
+        let mut state = spl_token::state::Account::unpack(&account.data).unwrap();
+        state.amount = amount;
+        spl_token::state::Account::pack(state, &mut account.data).unwrap();
+        self.context
+            .set_account(&token_account, &AccountSharedData::from(account));
+    }
+}
+
+/// Assert a transaction failed with the given program error
+/// A USD*/partner Growth pool created next to the Seed Pool, with USD* played by the Seed LP
+pub struct GrowthPool {
+    pub pool: Pubkey,
+    pub lp_mint: Pubkey,
+    pub mints: [Pubkey; 2],
+    pub user_tokens: [Pubkey; 2],
+    pub pool_tokens: [Pubkey; 2],
+    pub user_lp_token: Pubkey,
+}
+
+fn pda(seeds: &[&[u8]]) -> Pubkey {
+    Pubkey::find_program_address(seeds, &equilibrium_core::id()).0
+}
+
+/// AMM config PDA of namespace `index`
+pub fn config_address(index: u16) -> Pubkey {
+    pda(&[b"amm-config", &index.to_le_bytes()])
+}
+
+/// Seed Pool PDA under `amm_config`
+pub fn seed_pool_address(amm_config: &Pubkey) -> Pubkey {
+    pda(&[b"pool", amm_config.as_ref(), b"seed"])
+}
+
+/// Create a Growth pool seeded with `reserve` of each side from `env`'s user
+pub async fn create_growth_pool(env: &mut SeedPoolEnv, reserve: u64) -> GrowthPool {
+    let user = env.user();
+    let partner_mint = create_mint(&mut env.context).await;
+    let user_partner_token = create_ata(&mut env.context, &partner_mint, &user).await;
+    mint_to(
+        &mut env.context,
+        &partner_mint,
+        &user_partner_token,
+        10 * reserve,
+    )
+    .await;
+
+    let (create, growth) = create_growth_pool_ix(
+        env,
+        [env.lp_mint, partner_mint],
+        [env.user_lp_token, user_partner_token],
+        reserve,
+        false,
+    );
+    env.process(&[create]).await.unwrap();
+    growth
+}
+
+/// `CreateGrowthPool` pairing `mints` ([USD*, partner]) with `reserve` of each from
+/// the user's `user_tokens`, and the accounts the pool will have. A `soulbound_lp`
+/// pool books the initial LP on the user's position instead of minting it.
+pub fn create_growth_pool_ix(
+    env: &SeedPoolEnv,
+    mints: [Pubkey; 2],
+    user_tokens: [Pubkey; 2],
+    reserve: u64,
+    soulbound_lp: bool,
+) -> (Instruction, GrowthPool) {
+    let user = env.user();
+    let [usd_star_mint, partner_mint] = mints;
+    let pool = pda(&[
+        b"pool",
+        env.amm_config.as_ref(),
+        b"growth",
+        partner_mint.as_ref(),
+    ]);
+    let lp_mint = pda(&[b"lp-mint", pool.as_ref()]);
+    let pool_tokens = [
+        pda(&[b"pool-token", pool.as_ref(), usd_star_mint.as_ref()]),
+        pda(&[b"pool-token", pool.as_ref(), partner_mint.as_ref()]),
+    ];
+    let user_lp_token = spl_associated_token_account::get_associated_token_address(&user, &lp_mint);
+    let creator_position =
+        soulbound_lp.then(|| pda(&[b"user-position", user.as_ref(), pool.as_ref()]));
+    let create = Instruction {
+        program_id: equilibrium_core::id(),
+        accounts: equilibrium_core::accounts::CreateGrowthPool {
+            payer: user,
+            amm_config: env.amm_config,
+            seed_pool: env.pool,
+            pool,
+            usdc_star_mint: usd_star_mint,
+            partner_token_mint: partner_mint,
+            partner_approval: None,
+            user_usdc_star: user_tokens[0],
+            user_partner_token: user_tokens[1],
+            pool_usdc_star: pool_tokens[0],
+            pool_partner_token: pool_tokens[1],
+            lp_mint,
+            user_lp_token,
+            token_program: spl_token::ID,
+            associated_token_program: spl_associated_token_account::ID,
+            system_program: system_program::ID,
+            rent: sysvar::rent::ID,
+            authority: user,
+            fee_recipient: None,
+            fee_recipient_usd_star: None,
+            creator_position,
+        }
+        .to_account_metas(None),
+        data: equilibrium_core::instruction::CreateGrowthPool {
+            amplification: 10,
+            initial_usdc_amount: reserve,
+            initial_partner_amount: reserve,
+            fee_tier: equilibrium_core::FeeTier::Stable,
+            soulbound_lp,
+            target_weights: [5000, 5000],
+        }
+        .data(),
+    };
+
+    let growth = GrowthPool {
+        pool,
+        lp_mint,
+        mints,
+        user_tokens,
+        pool_tokens,
+        user_lp_token,
+    };
+    (create, growth)
+}
+
+/// `UpdatePool` for a Growth pool, signed by the config authority
+pub fn growth_update_pool_ix(
+    env: &SeedPoolEnv,
+    growth: &GrowthPool,
+    data: impl InstructionData,
+) -> Instruction {
+    Instruction {
+        program_id: equilibrium_core::id(),
+        accounts: equilibrium_core::accounts::UpdatePool {
+            authority: env.user(),
+            amm_config: env.amm_config,
+            pool: growth.pool,
+        }
+        .to_account_metas(None),
+        data: data.data(),
+    }
+}
+
+/// Make the test user the keeper, publish a 1:1 partner price and band the pool at 1%
+pub async fn publish_price_and_band(env: &mut SeedPoolEnv, growth: &GrowthPool) {
+    let set_keeper = env.update_config_ix(equilibrium_core::instruction::SetRebalanceConfig {
+        keeper: env.user(),
+        rebalance_program: Pubkey::default(),
+        max_rebalance_bps: 0,
+    });
+    let publish_price = Instruction {
+        program_id: equilibrium_core::id(),
+        accounts: equilibrium_core::accounts::UpdatePartnerPrice {
+            keeper: env.user(),
+            amm_config: env.amm_config,
+            pool: growth.pool,
+        }
+        .to_account_metas(None),
+        data: equilibrium_core::instruction::UpdatePartnerPrice {
+            price: ORACLE_PRICE_PRECISION,
+        }
+        .data(),
+    };
+    let set_band = growth_update_pool_ix(
+        env,
+        growth,
+        equilibrium_core::instruction::SetOracleBand {
+            band_bps: 100,
+            surcharge_bps: 500,
+        },
+    );
+    env.process(&[set_keeper, publish_price, set_band])
+        .await
+        .unwrap();
+}
+
+pub fn assert_program_error(result: Result<u64, BanksClientError>, expected: ErrorCode) {
+    let expected = u32::from(expected);
+    match result {
+        Err(BanksClientError::TransactionError(TransactionError::InstructionError(
+            _,
+            InstructionError::Custom(code),
+        ))) => assert_eq!(code, expected, "unexpected program error"),
+        other => panic!("expected program error {expected}, got {other:?}"),
+    }
+}
+
+async fn send(
+    context: &mut ProgramTestContext,
+    instructions: &[Instruction],
+    signers: &[&Keypair],
+) {
+    let blockhash = context.get_new_latest_blockhash().await.unwrap();
+    let mut all_signers = vec![&context.payer];
+    all_signers.extend_from_slice(signers);
+    let transaction = Transaction::new_signed_with_payer(
+        instructions,
+        Some(&context.payer.pubkey()),
+        &all_signers,
+        blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+}
+
+pub async fn create_mint(context: &mut ProgramTestContext) -> Pubkey {
+    let mint = Keypair::new();
+    let payer = context.payer.pubkey();
+    let rent = context.banks_client.get_rent().await.unwrap();
+    let instructions = [
+        system_instruction::create_account(
+            &payer,
+            &mint.pubkey(),
+            rent.minimum_balance(spl_token::state::Mint::LEN),
+            spl_token::state::Mint::LEN as u64,
+            &spl_token::ID,
+        ),
+        spl_token::instruction::initialize_mint2(
+            &spl_token::ID,
+            &mint.pubkey(),
+            &payer,
+            None,
+            DECIMALS,
+        )
+        .unwrap(),
+    ];
+    send(context, &instructions, &[&mint]).await;
+    mint.pubkey()
+}
+
+pub async fn create_ata(context: &mut ProgramTestContext, mint: &Pubkey, owner: &Pubkey) -> Pubkey {
+    let instruction = spl_associated_token_account::instruction::create_associated_token_account(
+        &context.payer.pubkey(),
+        owner,
+        mint,
+        &spl_token::ID,
+    );
+    send(context, &[instruction], &[]).await;
+    spl_associated_token_account::get_associated_token_address(owner, mint)
+}
+
+pub async fn mint_to(
+    context: &mut ProgramTestContext,
+    mint: &Pubkey,
+    destination: &Pubkey,
+    amount: u64,
+) {
+    let payer = context.payer.pubkey();
+    let instruction =
+        spl_token::instruction::mint_to(&spl_token::ID, mint, destination, &payer, &[], amount)
+            .unwrap();
+    send(context, &[instruction], &[]).await;
+}
@@ -0,0 +1,74 @@
+//! Config layout versioning and in-place migration
+
+mod common;
+
+use anchor_lang::{AnchorSerialize, InstructionData, ToAccountMetas};
+use common::{assert_program_error, SeedPoolEnv, CONFIG_INDEX};
+use equilibrium_core::errors::ErrorCode;
+use equilibrium_core::{AmmConfig, CONFIG_RESERVED_BYTES, CONFIG_VERSION};
+use solana_sdk::{
+    account::AccountSharedData,
+    instruction::Instruction,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    system_program,
+};
+
+fn migrate_ix(env: &SeedPoolEnv, authority: Pubkey) -> Instruction {
+    Instruction {
+        program_id: equilibrium_core::id(),
+        accounts: equilibrium_core::accounts::MigrateConfig {
+            authority,
+            amm_config: env.amm_config,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: equilibrium_core::instruction::MigrateConfig {
+            index: CONFIG_INDEX,
+        }
+        .data(),
+    }
+}
+
+#[tokio::test]
+#[ignore = "needs the SBF program from `anchor build`"]
+async fn legacy_config_migrates_in_place_once() {
+    common::require_program();
+
+    let mut env = SeedPoolEnv::new([1_000_000_000; 3]).await;
+    let config = env.config_state().await;
+    assert_eq!(config.version, CONFIG_VERSION);
+    assert_program_error(
+        env.process(&[migrate_ix(&env, env.user())]).await,
+        ErrorCode::ConfigAlreadyMigrated,
+    );
+
+    // Rewind the account to the pre-versioning layout: no version, protocol fee
+    // share or headroom
+    let versioned_bytes = 1 + 8 + CONFIG_RESERVED_BYTES;
+    let mut account = env.account(env.amm_config).await;
+    let legacy_end = 8 + config.try_to_vec().unwrap().len() - versioned_bytes;
+    account.data[legacy_end..].fill(0);
+    account.data.truncate(AmmConfig::space() - versioned_bytes);
+    env.context
+        .set_account(&env.amm_config, &AccountSharedData::from(account));
+    assert_eq!(env.config_state().await.version, 0);
+
+    // Only the authority migrates
+    let stranger = Keypair::new();
+    assert_program_error(
+        env.process_signed(&[migrate_ix(&env, stranger.pubkey())], &[&stranger])
+            .await,
+        ErrorCode::Unauthorized,
+    );
+
+    env.process(&[migrate_ix(&env, env.user())]).await.unwrap();
+    let migrated = env.config_state().await;
+    assert_eq!(migrated.version, CONFIG_VERSION);
+    assert_eq!(migrated.authority, config.authority);
+    assert_eq!(migrated.guardians, config.guardians);
+    assert_eq!(
+        env.account(env.amm_config).await.data.len(),
+        AmmConfig::space()
+    );
+}
@@ -0,0 +1,173 @@
+//! Independent AMM configs side by side on one deployment
+
+mod common;
+
+use anchor_lang::{InstructionData, ToAccountMetas};
+use common::{
+    config_address, seed_pool_address, SeedPoolEnv, DEFAULT_AMPLIFICATION, DEFAULT_WEIGHTS,
+};
+use equilibrium_core::{AmmConfig, Pool, WithdrawMode};
+use solana_program_test::BanksClientError;
+use solana_sdk::{
+    instruction::{AccountMeta, Instruction, InstructionError},
+    native_token::LAMPORTS_PER_SOL,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    system_instruction, system_program, sysvar,
+    transaction::TransactionError,
+};
+
+const PARTNER_INDEX: u16 = 1;
+const RESERVE: u64 = 1_000_000_000;
+
+fn pda(seeds: &[&[u8]]) -> Pubkey {
+    Pubkey::find_program_address(seeds, &equilibrium_core::id()).0
+}
+
+fn assert_constraint(
+    result: Result<u64, BanksClientError>,
+    expected: anchor_lang::error::ErrorCode,
+) {
+    match result {
+        Err(BanksClientError::TransactionError(TransactionError::InstructionError(
+            _,
+            InstructionError::Custom(code),
+        ))) => assert_eq!(code, u32::from(expected), "unexpected constraint"),
+        other => panic!("expected {expected:?}, got {other:?}"),
+    }
+}
+
+/// Fund `partner` and make it the authority of a second config with its own Seed Pool
+async fn create_partner_config(env: &mut SeedPoolEnv, partner: &Keypair) -> (Pubkey, Pubkey) {
+    let user = env.user();
+    let amm_config = config_address(PARTNER_INDEX);
+    let fund = system_instruction::transfer(&user, &partner.pubkey(), LAMPORTS_PER_SOL);
+    let initialize = Instruction {
+        program_id: equilibrium_core::id(),
+        accounts: equilibrium_core::accounts::Initialize {
+            authority: partner.pubkey(),
+            amm_config,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: equilibrium_core::instruction::Initialize {
+            index: PARTNER_INDEX,
+            default_amplification: DEFAULT_AMPLIFICATION,
+            default_target_weights: DEFAULT_WEIGHTS,
+        }
+        .data(),
+    };
+    env.process_signed(&[fund, initialize], &[partner])
+        .await
+        .unwrap();
+
+    // Same stablecoins, different namespace: the pool and its LP mint are new accounts
+    let pool = seed_pool_address(&amm_config);
+    let lp_mint = pda(&[b"lp-mint", pool.as_ref()]);
+    let pool_tokens = env
+        .mints
+        .map(|mint| pda(&[b"pool-token", pool.as_ref(), mint.as_ref()]));
+    let create_seed_pool = Instruction {
+        program_id: equilibrium_core::id(),
+        accounts: equilibrium_core::accounts::CreateSeedPool {
+            payer: user,
+            amm_config,
+            pool,
+            token_mint_a: env.mints[0],
+            token_mint_b: env.mints[1],
+            token_mint_c: env.mints[2],
+            user_token_a: env.user_tokens[0],
+            user_token_b: env.user_tokens[1],
+            user_token_c: env.user_tokens[2],
+            pool_token_a: pool_tokens[0],
+            pool_token_b: pool_tokens[1],
+            pool_token_c: pool_tokens[2],
+            lp_mint,
+            user_lp_token: spl_associated_token_account::get_associated_token_address(
+                &user, &lp_mint,
+            ),
+            token_program: spl_token::ID,
+            associated_token_program: spl_associated_token_account::ID,
+            system_program: system_program::ID,
+            rent: sysvar::rent::ID,
+            authority: partner.pubkey(),
+        }
+        .to_account_metas(None),
+        data: equilibrium_core::instruction::CreateSeedPool {
+            amplification: DEFAULT_AMPLIFICATION,
+            target_weights: DEFAULT_WEIGHTS.to_vec(),
+            initial_amounts: vec![RESERVE; 3],
+        }
+        .data(),
+    };
+    env.process(&[create_seed_pool]).await.unwrap();
+
+    (amm_config, pool)
+}
+
+#[tokio::test]
+#[ignore = "needs the SBF program from `anchor build`"]
+async fn configs_keep_their_own_pools_and_grants() {
+    common::require_program();
+
+    let mut env = SeedPoolEnv::new([RESERVE; 3]).await;
+    let partner = Keypair::new();
+    let (partner_config, partner_pool) = create_partner_config(&mut env, &partner).await;
+
+    let config = env.account_state::<AmmConfig>(partner_config).await;
+    assert_eq!(
+        (config.index, config.authority),
+        (PARTNER_INDEX, partner.pubkey())
+    );
+    assert_eq!(env.config_state().await.index, common::CONFIG_INDEX);
+    assert_ne!(partner_pool, env.pool);
+    assert_eq!(
+        env.account_state::<Pool>(partner_pool).await.amm_config,
+        partner_config
+    );
+
+    // The partner's authority cannot reach pools of the other config
+    let set_exit_fee = Instruction {
+        program_id: equilibrium_core::id(),
+        accounts: equilibrium_core::accounts::UpdatePool {
+            authority: partner.pubkey(),
+            amm_config: partner_config,
+            pool: env.pool,
+        }
+        .to_account_metas(None),
+        data: equilibrium_core::instruction::SetExitFee { exit_fee_bps: 100 }.data(),
+    };
+    assert_constraint(
+        env.process_signed(&[set_exit_fee], &[&partner]).await,
+        anchor_lang::error::ErrorCode::ConstraintHasOne,
+    );
+
+    // ...nor exempt a wallet from the other config's exit fees
+    let user = env.user();
+    let fee_exemption = pda(&[b"fee-exemption", partner_config.as_ref(), user.as_ref()]);
+    let add = Instruction {
+        program_id: equilibrium_core::id(),
+        accounts: equilibrium_core::accounts::AddFeeExemption {
+            authority: partner.pubkey(),
+            amm_config: partner_config,
+            user,
+            fee_exemption,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: equilibrium_core::instruction::AddFeeExemption {}.data(),
+    };
+    let deposit = env.deposit_ix([100_000_000; 3], 0);
+    env.process_signed(&[add, deposit], &[&partner])
+        .await
+        .unwrap();
+
+    let mut withdraw = env.withdraw_ix(1_000_000, [0; 3], WithdrawMode::Proportional);
+    // The fee exemption sits ahead of the pool snapshot and the portfolio
+    let slot = withdraw.accounts.len() - 3;
+    withdraw.accounts[slot] = AccountMeta::new_readonly(fee_exemption, false);
+    assert_constraint(
+        env.process(&[withdraw]).await,
+        anchor_lang::error::ErrorCode::ConstraintSeeds,
+    );
+}
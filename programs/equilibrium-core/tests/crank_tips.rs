@@ -0,0 +1,66 @@
+//! Protocol-fee tips for permissionless crank callers
+
+mod common;
+
+use anchor_lang::{InstructionData, ToAccountMetas};
+use common::{create_ata, SeedPoolEnv};
+use equilibrium_core::instruction::{SetCrankTip, SetProtocolFeeShare};
+use solana_sdk::{instruction::Instruction, pubkey::Pubkey};
+
+const TIP: u64 = 1_000;
+
+fn recompute_ix(env: &SeedPoolEnv, tip_recipient: Pubkey) -> Instruction {
+    Instruction {
+        program_id: equilibrium_core::id(),
+        accounts: equilibrium_core::accounts::RecomputeInvariant {
+            pool: env.pool,
+            amm_config: Some(env.amm_config),
+            pool_token_tip: Some(env.pool_tokens[0]),
+            tip_recipient: Some(tip_recipient),
+            token_program: Some(spl_token::ID),
+        }
+        .to_account_metas(None),
+        data: equilibrium_core::instruction::RecomputeInvariant {}.data(),
+    }
+}
+
+#[tokio::test]
+#[ignore = "needs the SBF program from `anchor build`"]
+async fn recompute_pays_a_tip_out_of_protocol_fees() {
+    common::require_program();
+
+    let mut env = SeedPoolEnv::new([1_000_000_000, 1_000_000_000, 1_000_000_000]).await;
+    let keeper = Pubkey::new_unique();
+    let keeper_token = create_ata(&mut env.context, &env.mints[0], &keeper).await;
+    let set_tip = env.update_config_ix(SetCrankTip {
+        crank_tip_amount: TIP,
+        max_crank_tips_per_slot: 1,
+    });
+    let set_share = env.update_pool_ix(SetProtocolFeeShare {
+        protocol_fee_share_bps: 5_000,
+    });
+    env.process(&[set_tip, set_share]).await.unwrap();
+
+    // The swap accrues protocol fees in token 0 and leaves the cached D approximate
+    env.process(&[env.swap_ix(0, 1, 10_000_000, 0)])
+        .await
+        .unwrap();
+    let owed_before = env.pool_state().await.protocol_fees_owed[0];
+    assert!(owed_before > TIP);
+
+    env.process(&[recompute_ix(&env, keeper_token)])
+        .await
+        .unwrap();
+    assert_eq!(env.token_balance(keeper_token).await, TIP);
+    assert_eq!(
+        env.pool_state().await.protocol_fees_owed[0],
+        owed_before - TIP
+    );
+
+    // Recomputing an exact D again does no work and earns nothing
+    env.advance_clock(1).await;
+    env.process(&[recompute_ix(&env, keeper_token)])
+        .await
+        .unwrap();
+    assert_eq!(env.token_balance(keeper_token).await, TIP);
+}
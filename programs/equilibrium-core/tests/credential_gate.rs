@@ -0,0 +1,89 @@
+//! Permissioned pools limited to credential holders
+
+mod common;
+
+use common::{assert_program_error, create_ata, create_mint, mint_to, SeedPoolEnv};
+use equilibrium_core::errors::ErrorCode;
+use equilibrium_core::instruction::UpdatePoolParams;
+use equilibrium_core::PoolParamChange;
+use solana_sdk::{instruction::Instruction, pubkey::Pubkey};
+
+const RESERVE: u64 = 1_000_000_000;
+
+/// Accounts after the credential slot: `Deposit` ends with the user portfolio, `Swap`
+/// with the integrator fee account, the referral account and its vault
+const DEPOSIT_AFTER_CREDENTIAL: usize = 1;
+const SWAP_AFTER_CREDENTIAL: usize = 3;
+
+/// Pass `credential` in the optional account `after` slots from the end, which
+/// `None` left as the program id
+fn with_credential(mut instruction: Instruction, after: usize, credential: Pubkey) -> Instruction {
+    let index = instruction.accounts.len() - 1 - after;
+    instruction.accounts[index].pubkey = credential;
+    instruction
+}
+
+#[tokio::test]
+#[ignore = "needs the SBF program from `anchor build`"]
+async fn only_credential_holders_deposit_and_swap() {
+    common::require_program();
+
+    let mut env = SeedPoolEnv::new([RESERVE; 3]).await;
+    let user = env.user();
+    let credential_mint = create_mint(&mut env.context).await;
+    let credential = create_ata(&mut env.context, &credential_mint, &user).await;
+
+    // Gating needs a mint to check against
+    let update = env.update_pool_ix(UpdatePoolParams {
+        changes: vec![PoolParamChange::Credential {
+            requires_credential: true,
+            credential_mint: Pubkey::default(),
+        }],
+    });
+    assert_program_error(env.process(&[update]).await, ErrorCode::InvalidTokenMint);
+    let update = env.update_pool_ix(UpdatePoolParams {
+        changes: vec![PoolParamChange::Credential {
+            requires_credential: true,
+            credential_mint,
+        }],
+    });
+    env.process(&[update]).await.unwrap();
+    assert!(env.pool_state().await.requires_credential);
+
+    // Without a credential, or with an empty one, the pool turns the user away
+    let amounts = [RESERVE / 10; 3];
+    assert_program_error(
+        env.process(&[env.deposit_ix(amounts, 0)]).await,
+        ErrorCode::CredentialRequired,
+    );
+    assert_program_error(
+        env.process(&[with_credential(
+            env.swap_ix(0, 1, RESERVE / 100, 0),
+            SWAP_AFTER_CREDENTIAL,
+            credential,
+        )])
+        .await,
+        ErrorCode::CredentialRequired,
+    );
+
+    // Holding the credential opens both
+    mint_to(&mut env.context, &credential_mint, &credential, 1).await;
+    let lp_before = env.token_balance(env.user_lp_token).await;
+    env.process(&[with_credential(
+        env.deposit_ix(amounts, 0),
+        DEPOSIT_AFTER_CREDENTIAL,
+        credential,
+    )])
+    .await
+    .unwrap();
+    assert!(env.token_balance(env.user_lp_token).await > lp_before);
+    let out_before = env.token_balance(env.user_tokens[1]).await;
+    env.process(&[with_credential(
+        env.swap_ix(0, 1, RESERVE / 100, 0),
+        SWAP_AFTER_CREDENTIAL,
+        credential,
+    )])
+    .await
+    .unwrap();
+    assert!(env.token_balance(env.user_tokens[1]).await > out_before);
+}
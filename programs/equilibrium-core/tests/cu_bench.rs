@@ -0,0 +1,65 @@
+//! Compute-unit regression tests for the hot-path instructions
+//!
+//! Ceilings default to the values below and can be tightened or relaxed per
+//! environment with `EQUILIBRIUM_MAX_CU_SWAP`, `EQUILIBRIUM_MAX_CU_DEPOSIT`
+//! and `EQUILIBRIUM_MAX_CU_WITHDRAW`.
+//!
+//! The ceilings hold for the default build; `debug-logs` builds log pool stats
+//! on top and are not held to them.
+
+mod common;
+
+use common::SeedPoolEnv;
+use equilibrium_core::WithdrawMode;
+
+const DEFAULT_MAX_CU_SWAP: u64 = 200_000;
+const DEFAULT_MAX_CU_DEPOSIT: u64 = 200_000;
+const DEFAULT_MAX_CU_WITHDRAW: u64 = 200_000;
+
+fn ceiling(var: &str, default: u64) -> u64 {
+    std::env::var(var)
+        .ok()
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(default)
+}
+
+fn assert_within(instruction: &str, consumed: u64, max: u64) {
+    println!("{instruction}: {consumed} CU (ceiling {max})");
+    assert!(
+        consumed <= max,
+        "{instruction} consumed {consumed} CU, above the {max} CU ceiling"
+    );
+}
+
+#[tokio::test]
+#[ignore = "needs the SBF program from `anchor build`"]
+async fn hot_path_instructions_stay_under_cu_ceilings() {
+    common::require_program();
+
+    let mut env = SeedPoolEnv::new([1_000_000_000, 1_000_000_000, 1_000_000_000]).await;
+
+    let deposit = env.deposit_ix([100_000_000, 100_000_000, 100_000_000], 0);
+    let consumed = env.process(&[deposit]).await.unwrap();
+    assert_within(
+        "deposit",
+        consumed,
+        ceiling("EQUILIBRIUM_MAX_CU_DEPOSIT", DEFAULT_MAX_CU_DEPOSIT),
+    );
+
+    let swap = env.swap_ix(0, 1, 10_000_000, 0);
+    let consumed = env.process(&[swap]).await.unwrap();
+    assert_within(
+        "swap",
+        consumed,
+        ceiling("EQUILIBRIUM_MAX_CU_SWAP", DEFAULT_MAX_CU_SWAP),
+    );
+
+    let lp_amount = env.position_state().await.lp_amount / 2;
+    let withdraw = env.withdraw_ix(lp_amount, [0, 0, 0], WithdrawMode::Proportional);
+    let consumed = env.process(&[withdraw]).await.unwrap();
+    assert_within(
+        "withdraw",
+        consumed,
+        ceiling("EQUILIBRIUM_MAX_CU_WITHDRAW", DEFAULT_MAX_CU_WITHDRAW),
+    );
+}
@@ -0,0 +1,160 @@
+//! Scripted USDT depeg: an executable spec for the equilibrium mechanism
+//!
+//! A balanced Seed Pool takes a run of one-sided USDT dumps, then arbitrageurs buy
+//! the cheap USDT back. Throughout, the dynamic fee must escalate with imbalance,
+//! LP par value (reserves per LP token) must never fall, and withdrawals must keep
+//! working, paying out the over-weighted token first when asked to.
+
+mod common;
+
+use common::{SeedPoolEnv, DEFAULT_WEIGHTS};
+use equilibrium_core::state::math::total_weight_deviation;
+use equilibrium_core::{TokenStatus, WithdrawMode};
+use solana_sdk::program_pack::Pack;
+
+const USDC: usize = 0;
+const USDT: usize = 1;
+
+/// Seed Pool reserves at their 45/35/20 target weights
+const RESERVES: [u64; 3] = [4_500_000_000, 3_500_000_000, 2_000_000_000];
+
+/// Size of each panic sell and each arbitrage buy
+const CHUNK: u64 = 300_000_000;
+const PANIC_SELLS: usize = 6;
+const ARB_BUYS: usize = 4;
+
+/// Fixed-point scale of `par_value`
+const PAR_PRECISION: u128 = 1_000_000_000_000;
+
+/// Sum of reserves per LP token, valuing every stablecoin at $1
+async fn par_value(env: &mut SeedPoolEnv) -> u128 {
+    let reserves: u64 = env.pool_state().await.reserves.iter().sum();
+    reserves as u128 * PAR_PRECISION / lp_supply(env).await as u128
+}
+
+async fn lp_supply(env: &mut SeedPoolEnv) -> u64 {
+    let account = env.account(env.lp_mint).await;
+    spl_token::state::Mint::unpack(&account.data)
+        .unwrap()
+        .supply
+}
+
+/// Total deviation from target weights, in basis points
+async fn deviation_bps(env: &mut SeedPoolEnv) -> u64 {
+    let weights = env.pool_state().await.current_weights();
+    total_weight_deviation(&weights, &DEFAULT_WEIGHTS)
+}
+
+/// Swap `CHUNK` and return the fee rate LPs kept, in basis points of the input
+async fn swap_chunk(env: &mut SeedPoolEnv, token_in: usize, token_out: usize) -> u64 {
+    let fees_before = env.pool_state().await.lifetime_fees_per_token[token_in];
+    let swap = env.swap_ix(token_in, token_out, CHUNK, 0);
+    env.process(&[swap]).await.unwrap();
+    let fee = env.pool_state().await.lifetime_fees_per_token[token_in] - fees_before;
+    (fee * 10_000 / CHUNK as u128) as u64
+}
+
+/// Withdraw `lp_amount` and return what each token paid out
+async fn withdraw(env: &mut SeedPoolEnv, lp_amount: u64, mode: WithdrawMode) -> [u64; 3] {
+    let mut before = [0; 3];
+    for (i, balance) in before.iter_mut().enumerate() {
+        *balance = env.token_balance(env.user_tokens[i]).await;
+    }
+    let withdraw = env.withdraw_ix(lp_amount, [0; 3], mode);
+    env.process(&[withdraw]).await.unwrap();
+
+    let mut paid = [0; 3];
+    for (i, amount) in paid.iter_mut().enumerate() {
+        *amount = env.token_balance(env.user_tokens[i]).await - before[i];
+    }
+    paid
+}
+
+/// Withdraw `lp_amount` proportionally, checking it is paid its par value and
+/// leaves the remaining LPs no worse off
+async fn withdraw_at_par(env: &mut SeedPoolEnv, lp_amount: u64) -> [u64; 3] {
+    let par_before = par_value(env).await;
+    let paid = withdraw(env, lp_amount, WithdrawMode::Proportional).await;
+
+    let owed = (lp_amount as u128 * par_before / PAR_PRECISION) as u64;
+    let paid_total: u64 = paid.iter().sum();
+    assert!(paid_total <= owed && paid_total + 3 >= owed);
+    assert!(par_value(env).await >= par_before);
+    paid
+}
+
+#[tokio::test]
+#[ignore = "needs the SBF program from `anchor build`"]
+async fn usdt_depeg_escalates_fees_and_recovers_without_losing_lp_value() {
+    common::require_program();
+
+    let mut env = SeedPoolEnv::new(RESERVES).await;
+    let deposit = env.deposit_ix(RESERVES.map(|reserve| reserve / 10), 0);
+    env.process(&[deposit]).await.unwrap();
+    let position_lp = env.position_state().await.lp_amount;
+    let slice = position_lp / 10;
+
+    let par_at_start = par_value(&mut env).await;
+    assert_eq!(deviation_bps(&mut env).await, 0);
+    withdraw_at_par(&mut env, slice).await;
+
+    // Panic: holders dump USDT for USDC in one-sided chunks
+    let max_fee = env.pool_state().await.fee_params.max_fee;
+    let mut fee_rates = Vec::new();
+    let mut deviation = deviation_bps(&mut env).await;
+    let mut par = par_value(&mut env).await;
+    for _ in 0..PANIC_SELLS {
+        fee_rates.push(swap_chunk(&mut env, USDT, USDC).await);
+
+        // Every sell pushes the pool further out and pays LPs for it
+        let next_deviation = deviation_bps(&mut env).await;
+        assert!(next_deviation > deviation);
+        deviation = next_deviation;
+        let next_par = par_value(&mut env).await;
+        assert!(next_par > par);
+        par = next_par;
+    }
+
+    // The fee escalates with the imbalance, up to the pool's cap
+    assert!(fee_rates.windows(2).all(|pair| pair[1] >= pair[0]));
+    assert!(fee_rates.last() > fee_rates.first());
+    assert!(fee_rates.iter().all(|&rate| rate <= max_fee * 10 + 1));
+    let peak = env.pool_state().await;
+    assert_eq!(peak.token_status[USDT], TokenStatus::StressedOver);
+    assert_eq!(peak.token_status[USDC], TokenStatus::StressedUnder);
+
+    // At the peak, withdrawals still pay par, and balanced exits take the glut first
+    let proportional = withdraw_at_par(&mut env, slice).await;
+    assert!(proportional[USDT] > proportional[USDC]);
+    let balanced = withdraw(&mut env, slice, WithdrawMode::Balanced).await;
+    assert!(balanced[USDT] > proportional[USDT]);
+    assert!(balanced[USDC] < proportional[USDC]);
+    assert!(par_value(&mut env).await >= par);
+
+    // Recovery: arbitrageurs buy the cheap USDT back with USDC
+    let mut deviation = deviation_bps(&mut env).await;
+    let mut par = par_value(&mut env).await;
+    let mut arb_rates = Vec::new();
+    for _ in 0..ARB_BUYS {
+        arb_rates.push(swap_chunk(&mut env, USDC, USDT).await);
+
+        let next_deviation = deviation_bps(&mut env).await;
+        assert!(next_deviation < deviation);
+        deviation = next_deviation;
+        let next_par = par_value(&mut env).await;
+        assert!(next_par > par);
+        par = next_par;
+    }
+
+    // Fees ease as weights return to target, and no token is stressed any more
+    assert!(arb_rates.windows(2).all(|pair| pair[1] <= pair[0]));
+    assert!(arb_rates.last() < fee_rates.last());
+    assert_eq!(
+        env.pool_state().await.token_status,
+        [TokenStatus::Normal; 3]
+    );
+
+    // Through the whole episode, LPs only gained
+    withdraw_at_par(&mut env, slice).await;
+    assert!(par_value(&mut env).await > par_at_start);
+}
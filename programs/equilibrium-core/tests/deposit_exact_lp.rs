@@ -0,0 +1,51 @@
+//! Balanced deposits that mint an exact LP amount
+
+mod common;
+
+use common::{assert_program_error, SeedPoolEnv};
+use equilibrium_core::errors::ErrorCode;
+
+#[tokio::test]
+#[ignore = "needs the SBF program from `anchor build`"]
+async fn deposit_exact_lp_mints_requested_amount() {
+    common::require_program();
+
+    let mut env = SeedPoolEnv::new([1_000_000_000, 2_000_000_000, 3_000_000_000]).await;
+    let lp_supply = 6_000_000_000u64;
+    let lp_amount = 60_000_001;
+
+    // Inputs cover each reserve's share of the new LP, rounded up in the pool's favour
+    let expected: Vec<u64> = env
+        .pool_state()
+        .await
+        .reserves
+        .iter()
+        .take(3)
+        .map(|&reserve| (reserve as u128 * lp_amount as u128).div_ceil(lp_supply as u128) as u64)
+        .collect();
+
+    // A cap below any required input rejects the deposit
+    let mut tight = [u64::MAX; 3];
+    tight[2] = expected[2] - 1;
+    let deposit = env.deposit_exact_lp_ix(lp_amount, tight);
+    assert_program_error(env.process(&[deposit]).await, ErrorCode::SlippageExceeded);
+
+    let before = [
+        env.token_balance(env.user_tokens[0]).await,
+        env.token_balance(env.user_tokens[1]).await,
+        env.token_balance(env.user_tokens[2]).await,
+    ];
+    let lp_before = env.token_balance(env.user_lp_token).await;
+    let deposit = env.deposit_exact_lp_ix(lp_amount, [expected[0], expected[1], expected[2]]);
+    env.process(&[deposit]).await.unwrap();
+
+    assert_eq!(
+        env.token_balance(env.user_lp_token).await - lp_before,
+        lp_amount
+    );
+    for i in 0..3 {
+        let spent = before[i] - env.token_balance(env.user_tokens[i]).await;
+        assert_eq!(spent, expected[i], "token {i}");
+    }
+    assert_eq!(env.position_state().await.lp_amount, lp_amount);
+}
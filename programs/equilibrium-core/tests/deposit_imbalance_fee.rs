@@ -0,0 +1,39 @@
+//! Deposits guarded by a cap on the imbalance fee rather than a minimum LP out
+
+mod common;
+
+use common::{assert_program_error, SeedPoolEnv};
+use equilibrium_core::errors::ErrorCode;
+
+const RESERVE: u64 = 1_000_000_000;
+const AMOUNT: u64 = 100_000_000;
+
+#[tokio::test]
+#[ignore = "needs the SBF program from `anchor build`"]
+async fn imbalance_fee_cap_rejects_deposits_into_over_weighted_tokens() {
+    common::require_program();
+
+    // Equal reserves leave token 2 (20% target) over-weighted and token 0 (45%) under-weighted
+    let mut env = SeedPoolEnv::new([RESERVE; 3]).await;
+
+    // Adding more of the over-weighted token pays a fee, so a zero cap rejects it
+    let deposit = env.deposit_with_max_imbalance_fee_ix([0, 0, AMOUNT], 0);
+    assert_program_error(env.process(&[deposit]).await, ErrorCode::SlippageExceeded);
+
+    // The fee stays within the deposit curve's 0.5% ceiling, plus solver rounding
+    let lp_before = env.token_balance(env.user_lp_token).await;
+    let deposit = env.deposit_with_max_imbalance_fee_ix([0, 0, AMOUNT], 100);
+    env.process(&[deposit]).await.unwrap();
+    assert!(env.token_balance(env.user_lp_token).await > lp_before);
+
+    // Rebated deposits pass even with a zero cap
+    let deposit = env.deposit_with_max_imbalance_fee_ix([AMOUNT, 0, 0], 0);
+    env.process(&[deposit]).await.unwrap();
+
+    // A cap above 100% is malformed
+    let deposit = env.deposit_with_max_imbalance_fee_ix([AMOUNT, 0, 0], 10_001);
+    assert_program_error(
+        env.process(&[deposit]).await,
+        ErrorCode::InvalidInstructionData,
+    );
+}
@@ -0,0 +1,172 @@
+//! Exit fee exemptions and vote escrow fee discounts
+
+mod common;
+
+use anchor_lang::{InstructionData, ToAccountMetas};
+use common::{assert_program_error, create_ata, create_mint, mint_to, SeedPoolEnv};
+use equilibrium_core::errors::ErrorCode;
+use equilibrium_core::{WithdrawMode, MAX_VE_LOCK_DURATION};
+use solana_sdk::{
+    clock::Clock, instruction::AccountMeta, instruction::Instruction, pubkey::Pubkey,
+};
+
+const RESERVE: u64 = 1_000_000_000;
+
+fn vote_escrow(env: &SeedPoolEnv, owner: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[b"vote-escrow", env.amm_config.as_ref(), owner.as_ref()],
+        &equilibrium_core::id(),
+    )
+    .0
+}
+
+fn fee_exemption(env: &SeedPoolEnv, user: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[b"fee-exemption", env.amm_config.as_ref(), user.as_ref()],
+        &equilibrium_core::id(),
+    )
+    .0
+}
+
+/// Fill the trailing optional account slot `from_end` places before the last one
+///
+/// `Swap` ends with five optional accounts after its vote escrow, and `Withdraw`
+/// with two (pool snapshot, portfolio) after its fee exemption
+fn fill_optional(instruction: &mut Instruction, from_end: usize, address: Pubkey) {
+    let index = instruction.accounts.len() - 1 - from_end;
+    instruction.accounts[index] = AccountMeta::new_readonly(address, false);
+}
+
+/// Lock `amount` of a fresh governance token for the maximum duration
+async fn lock_max(env: &mut SeedPoolEnv, amount: u64) {
+    let user = env.user();
+    let ve_mint = create_mint(&mut env.context).await;
+    let owner_token = create_ata(&mut env.context, &ve_mint, &user).await;
+    mint_to(&mut env.context, &ve_mint, &owner_token, amount).await;
+
+    let now = env
+        .context
+        .banks_client
+        .get_sysvar::<Clock>()
+        .await
+        .unwrap()
+        .unix_timestamp;
+    let escrow = vote_escrow(env, &user);
+    let lock = Instruction {
+        program_id: equilibrium_core::id(),
+        accounts: equilibrium_core::accounts::LockVeTokens {
+            owner: user,
+            amm_config: env.amm_config,
+            ve_mint,
+            vote_escrow: escrow,
+            vault: Pubkey::find_program_address(
+                &[b"vote-escrow-vault", escrow.as_ref()],
+                &equilibrium_core::id(),
+            )
+            .0,
+            owner_token,
+            token_program: spl_token::ID,
+            system_program: solana_sdk::system_program::ID,
+            rent: solana_sdk::sysvar::rent::ID,
+        }
+        .to_account_metas(None),
+        data: equilibrium_core::instruction::LockVeTokens {
+            amount,
+            unlock_ts: now + MAX_VE_LOCK_DURATION,
+        }
+        .data(),
+    };
+    assert_program_error(
+        env.process(std::slice::from_ref(&lock)).await,
+        ErrorCode::VeLockDisabled,
+    );
+
+    let set_ve_mint = env.update_config_ix(equilibrium_core::instruction::SetVeMint { ve_mint });
+    env.process(&[set_ve_mint, lock]).await.unwrap();
+}
+
+#[tokio::test]
+#[ignore = "needs the SBF program from `anchor build`"]
+async fn locked_voting_power_discounts_the_swap_fee() {
+    common::require_program();
+
+    let mut plain = SeedPoolEnv::new([RESERVE; 3]).await;
+    let before = plain.token_balance(plain.user_tokens[1]).await;
+    plain
+        .process(&[plain.swap_ix(0, 1, 10_000_000, 0)])
+        .await
+        .unwrap();
+    let plain_out = plain.token_balance(plain.user_tokens[1]).await - before;
+
+    let mut locked = SeedPoolEnv::new([RESERVE; 3]).await;
+    lock_max(&mut locked, 100_000_000_000).await;
+    let mut swap = locked.swap_ix(0, 1, 10_000_000, 0);
+    fill_optional(&mut swap, 5, vote_escrow(&locked, &locked.user()));
+    let before = locked.token_balance(locked.user_tokens[1]).await;
+    locked.process(&[swap]).await.unwrap();
+    let locked_out = locked.token_balance(locked.user_tokens[1]).await - before;
+
+    // The top tier waives half of the 0.1% fee
+    assert!(locked_out > plain_out);
+    assert!(locked_out - plain_out >= 4_000);
+
+    // A lock in a governance token the config has since replaced earns nothing
+    let ve_mint = create_mint(&mut locked.context).await;
+    let set_ve_mint = locked.update_config_ix(equilibrium_core::instruction::SetVeMint { ve_mint });
+    locked.process(&[set_ve_mint]).await.unwrap();
+    let mut swap = locked.swap_ix(0, 1, 10_000_000, 0);
+    fill_optional(&mut swap, 5, vote_escrow(&locked, &locked.user()));
+    assert_program_error(locked.process(&[swap]).await, ErrorCode::VoteEscrowMismatch);
+}
+
+#[tokio::test]
+#[ignore = "needs the SBF program from `anchor build`"]
+async fn exempt_wallets_withdraw_without_the_exit_fee() {
+    common::require_program();
+
+    let mut env = SeedPoolEnv::new([RESERVE; 3]).await;
+    let user = env.user();
+    let add = Instruction {
+        program_id: equilibrium_core::id(),
+        accounts: equilibrium_core::accounts::AddFeeExemption {
+            authority: user,
+            amm_config: env.amm_config,
+            user,
+            fee_exemption: fee_exemption(&env, &user),
+            system_program: solana_sdk::system_program::ID,
+        }
+        .to_account_metas(None),
+        data: equilibrium_core::instruction::AddFeeExemption {}.data(),
+    };
+    let set_exit_fee =
+        env.update_pool_ix(equilibrium_core::instruction::SetExitFee { exit_fee_bps: 100 });
+    env.process(&[add, set_exit_fee]).await.unwrap();
+
+    // A tenth of the LP supply is worth a tenth of each reserve, with nothing held back
+    let mut withdraw = env.withdraw_ix(300_000_000, [0; 3], WithdrawMode::Proportional);
+    fill_optional(&mut withdraw, 2, fee_exemption(&env, &user));
+    let before = env.token_balance(env.user_tokens[0]).await;
+    env.process(&[withdraw]).await.unwrap();
+    assert_eq!(
+        env.token_balance(env.user_tokens[0]).await - before,
+        RESERVE / 10
+    );
+
+    let remove = Instruction {
+        program_id: equilibrium_core::id(),
+        accounts: equilibrium_core::accounts::RemoveFeeExemption {
+            authority: user,
+            amm_config: env.amm_config,
+            fee_exemption: fee_exemption(&env, &user),
+        }
+        .to_account_metas(None),
+        data: equilibrium_core::instruction::RemoveFeeExemption {}.data(),
+    };
+    env.process(&[remove]).await.unwrap();
+
+    let before = env.token_balance(env.user_tokens[0]).await;
+    env.process(&[env.withdraw_ix(300_000_000, [0; 3], WithdrawMode::Proportional)])
+        .await
+        .unwrap();
+    assert!(env.token_balance(env.user_tokens[0]).await - before < RESERVE / 10);
+}
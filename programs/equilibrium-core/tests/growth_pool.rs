@@ -0,0 +1,89 @@
+//! Growth pool creation against the Seed Pool's USD*
+
+mod common;
+
+use anchor_lang::InstructionData;
+use common::{
+    assert_program_error, create_ata, create_growth_pool_ix, create_mint, mint_to, SeedPoolEnv,
+};
+use equilibrium_core::errors::ErrorCode;
+use equilibrium_core::instruction::CreateGrowthPool;
+use equilibrium_core::{FeeTier, Pool};
+
+const RESERVE: u64 = 1_000_000_000;
+
+#[tokio::test]
+#[ignore = "needs the SBF program from `anchor build`"]
+async fn growth_pool_rejects_a_mint_posing_as_usd_star() {
+    common::require_program();
+
+    let mut env = SeedPoolEnv::new([RESERVE; 3]).await;
+    let user = env.user();
+    let mut mints = [Default::default(); 2];
+    let mut user_tokens = [Default::default(); 2];
+    for (mint, user_token) in mints.iter_mut().zip(user_tokens.iter_mut()) {
+        *mint = create_mint(&mut env.context).await;
+        *user_token = create_ata(&mut env.context, mint, &user).await;
+        mint_to(&mut env.context, mint, user_token, RESERVE).await;
+    }
+
+    // Any mint other than the Seed Pool's LP token is refused as USD*
+    let (create, _) = create_growth_pool_ix(&env, mints, user_tokens, RESERVE, false);
+    assert_program_error(env.process(&[create]).await, ErrorCode::InvalidUsdStarMint);
+
+    // The same partner token pairs fine with the real USD*
+    let (create, growth) = create_growth_pool_ix(
+        &env,
+        [env.lp_mint, mints[1]],
+        [env.user_lp_token, user_tokens[1]],
+        RESERVE,
+        false,
+    );
+    env.process(&[create]).await.unwrap();
+    assert_eq!(env.pool_state().await.lp_mint, growth.mints[0]);
+}
+
+#[tokio::test]
+#[ignore = "needs the SBF program from `anchor build`"]
+async fn growth_pool_target_weights_are_set_at_creation() {
+    common::require_program();
+
+    let mut env = SeedPoolEnv::new([RESERVE; 3]).await;
+    let user = env.user();
+    let partner_mint = create_mint(&mut env.context).await;
+    let user_partner = create_ata(&mut env.context, &partner_mint, &user).await;
+    mint_to(&mut env.context, &partner_mint, &user_partner, RESERVE).await;
+
+    let (mut create, growth) = create_growth_pool_ix(
+        &env,
+        [env.lp_mint, partner_mint],
+        [env.user_lp_token, user_partner],
+        RESERVE / 4,
+        false,
+    );
+    let with_weights = |target_weights| {
+        CreateGrowthPool {
+            amplification: 10,
+            initial_usdc_amount: RESERVE / 4,
+            initial_partner_amount: RESERVE / 4,
+            fee_tier: FeeTier::Stable,
+            soulbound_lp: false,
+            target_weights,
+        }
+        .data()
+    };
+
+    // Weights must cover the whole pool and stay within 20/80..80/20
+    for target_weights in [[5000, 4000], [9000, 1000], [1999, 8001]] {
+        create.data = with_weights(target_weights);
+        assert_program_error(
+            env.process(&[create.clone()]).await,
+            ErrorCode::InvalidWeights,
+        );
+    }
+
+    create.data = with_weights([8000, 2000]);
+    env.process(&[create]).await.unwrap();
+    let pool: Pool = env.account_state(growth.pool).await;
+    assert_eq!(pool.target_weights, vec![8000, 2000]);
+}
@@ -0,0 +1,111 @@
+//! Pause guardians: a security council that can halt pools but not run them
+
+mod common;
+
+use anchor_lang::{InstructionData, ToAccountMetas};
+use common::{assert_program_error, SeedPoolEnv};
+use equilibrium_core::errors::ErrorCode;
+use equilibrium_core::WithdrawMode;
+use solana_sdk::{
+    instruction::Instruction,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+};
+
+const RESERVE: u64 = 1_000_000_000;
+
+fn pause_ix(env: &SeedPoolEnv, guardian: &Pubkey) -> Instruction {
+    Instruction {
+        program_id: equilibrium_core::id(),
+        accounts: equilibrium_core::accounts::PausePool {
+            guardian: *guardian,
+            amm_config: env.amm_config,
+            pool: env.pool,
+        }
+        .to_account_metas(None),
+        data: equilibrium_core::instruction::PausePool {}.data(),
+    }
+}
+
+/// `UpdatePool` setter signed by `signer` instead of the config authority
+fn update_pool_as(env: &SeedPoolEnv, signer: &Pubkey, data: impl InstructionData) -> Instruction {
+    let mut instruction = env.update_pool_ix(data);
+    instruction.accounts[0].pubkey = *signer;
+    instruction
+}
+
+#[tokio::test]
+#[ignore = "needs the SBF program from `anchor build`"]
+async fn guardian_pauses_but_only_the_authority_unpauses() {
+    common::require_program();
+
+    let mut env = SeedPoolEnv::new([RESERVE; 3]).await;
+    let guardian = Keypair::new();
+
+    // Strangers cannot pause
+    assert_program_error(
+        env.process_signed(&[pause_ix(&env, &guardian.pubkey())], &[&guardian])
+            .await,
+        ErrorCode::Unauthorized,
+    );
+
+    let add = env.update_config_ix(equilibrium_core::instruction::AddGuardian {
+        guardian: guardian.pubkey(),
+    });
+    env.process(&[add]).await.unwrap();
+    assert_eq!(env.config_state().await.guardians, vec![guardian.pubkey()]);
+    let add_again = env.update_config_ix(equilibrium_core::instruction::AddGuardian {
+        guardian: guardian.pubkey(),
+    });
+    assert_program_error(env.process(&[add_again]).await, ErrorCode::InvalidGuardian);
+
+    env.process_signed(&[pause_ix(&env, &guardian.pubkey())], &[&guardian])
+        .await
+        .unwrap();
+    assert!(env.pool_state().await.paused);
+
+    // Swaps and deposits halt, withdrawals stay open
+    let swap = env.swap_ix(0, 1, 1_000_000, 0);
+    assert_program_error(env.process(&[swap]).await, ErrorCode::PoolPaused);
+    let deposit = env.deposit_ix([1_000_000; 3], 0);
+    assert_program_error(env.process(&[deposit]).await, ErrorCode::PoolPaused);
+    let withdraw = env.withdraw_ix(1_000_000, [0; 3], WithdrawMode::Proportional);
+    env.process(&[withdraw]).await.unwrap();
+
+    // The guardian holds no admin power: it can neither unpause nor change parameters
+    let unpause = update_pool_as(
+        &env,
+        &guardian.pubkey(),
+        equilibrium_core::instruction::SetPoolPaused { paused: false },
+    );
+    assert_program_error(
+        env.process_signed(&[unpause], &[&guardian]).await,
+        ErrorCode::Unauthorized,
+    );
+    let set_exit_fee = update_pool_as(
+        &env,
+        &guardian.pubkey(),
+        equilibrium_core::instruction::SetExitFee { exit_fee_bps: 100 },
+    );
+    assert_program_error(
+        env.process_signed(&[set_exit_fee], &[&guardian]).await,
+        ErrorCode::Unauthorized,
+    );
+
+    let unpause =
+        env.update_pool_ix(equilibrium_core::instruction::SetPoolPaused { paused: false });
+    env.process(&[unpause]).await.unwrap();
+    let swap = env.swap_ix(0, 1, 1_000_000, 0);
+    env.process(&[swap]).await.unwrap();
+
+    // A removed guardian loses the pause right
+    let remove = env.update_config_ix(equilibrium_core::instruction::RemoveGuardian {
+        guardian: guardian.pubkey(),
+    });
+    env.process(&[remove]).await.unwrap();
+    assert_program_error(
+        env.process_signed(&[pause_ix(&env, &guardian.pubkey())], &[&guardian])
+            .await,
+        ErrorCode::Unauthorized,
+    );
+}
@@ -0,0 +1,84 @@
+//! Front-end fees paid atomically out of a swap's input
+
+mod common;
+
+use anchor_lang::InstructionData;
+use common::{assert_program_error, create_ata, SeedPoolEnv};
+use equilibrium_core::errors::ErrorCode;
+use equilibrium_core::events::IntegratorFeePaid;
+use solana_sdk::{
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+};
+
+const RESERVE: u64 = 1_000_000_000;
+const AMOUNT: u64 = 1_000_000;
+
+/// A swap of `AMOUNT` of token 0 for token 1 paying `integrator_fee_bps` to `fee_account`
+fn swap_with_fee_ix(
+    env: &SeedPoolEnv,
+    integrator_fee_bps: u64,
+    fee_account: Option<Pubkey>,
+) -> Instruction {
+    let mut swap = env.swap_ix(0, 1, AMOUNT, 0);
+    if let Some(fee_account) = fee_account {
+        // Third to last, ahead of the referral account and its vault
+        let index = swap.accounts.len() - 3;
+        swap.accounts[index] = AccountMeta::new(fee_account, false);
+    }
+    swap.data = equilibrium_core::instruction::Swap {
+        amount_in: AMOUNT,
+        min_amount_out: 0,
+        deadline: i64::MAX,
+        integrator_fee_bps,
+        max_price_impact_bps: None,
+    }
+    .data();
+    swap
+}
+
+#[tokio::test]
+#[ignore = "needs the SBF program from `anchor build`"]
+async fn integrator_fee_is_paid_on_top_of_the_swap() {
+    common::require_program();
+
+    let mut env = SeedPoolEnv::new([RESERVE; 3]).await;
+    let integrator = Pubkey::new_unique();
+    let fee_account = create_ata(&mut env.context, &env.mints[0], &integrator).await;
+
+    // The rate is capped, and a fee needs somewhere to go
+    assert_program_error(
+        env.process(&[swap_with_fee_ix(&env, 101, Some(fee_account))])
+            .await,
+        ErrorCode::IntegratorFeeTooHigh,
+    );
+    assert_program_error(
+        env.process(&[swap_with_fee_ix(&env, 50, None)]).await,
+        ErrorCode::MissingIntegratorFeeAccount,
+    );
+
+    // The pool prices the full amount in, and the fee comes from the user on top
+    let user_before = env.token_balance(env.user_tokens[0]).await;
+    let reserve_before = env.pool_state().await.reserves[0];
+    let events = env
+        .process_events::<IntegratorFeePaid>(&[swap_with_fee_ix(&env, 50, Some(fee_account))])
+        .await
+        .unwrap();
+    let fee = AMOUNT * 50 / 10_000;
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].integrator_fee_account, fee_account);
+    assert_eq!(events[0].integrator_fee_bps, 50);
+    assert_eq!(events[0].fee_amount, fee);
+    assert_eq!(env.token_balance(fee_account).await, fee);
+    assert_eq!(
+        user_before - env.token_balance(env.user_tokens[0]).await,
+        AMOUNT + fee
+    );
+    assert_eq!(env.pool_state().await.reserves[0], reserve_before + AMOUNT);
+
+    // Passing the account with no fee moves nothing to it
+    env.process(&[swap_with_fee_ix(&env, 0, Some(fee_account))])
+        .await
+        .unwrap();
+    assert_eq!(env.token_balance(fee_account).await, fee);
+}
@@ -0,0 +1,56 @@
+//! Whale alerts for swaps and withdrawals draining a large share of a reserve
+
+mod common;
+
+use common::SeedPoolEnv;
+use equilibrium_core::events::{FlowDirection, LargeFlowEvent};
+use equilibrium_core::instruction::UpdatePoolParams;
+use equilibrium_core::{PoolParamChange, WithdrawMode};
+
+const RESERVE: u64 = 1_000_000_000;
+
+#[tokio::test]
+#[ignore = "needs the SBF program from `anchor build`"]
+async fn only_flows_above_the_threshold_raise_an_alert() {
+    common::require_program();
+
+    let mut env = SeedPoolEnv::new([RESERVE; 3]).await;
+    let update = env.update_pool_ix(UpdatePoolParams {
+        changes: vec![PoolParamChange::LargeFlowThreshold { threshold_bps: 500 }],
+    });
+    env.process(&[update]).await.unwrap();
+
+    // A 1% swap stays quiet
+    let small = env.swap_ix(0, 1, RESERVE / 100, 0);
+    let events = env
+        .process_events::<LargeFlowEvent>(&[small])
+        .await
+        .unwrap();
+    assert!(events.is_empty());
+
+    // A 10% swap reports the drained output token
+    let large = env.swap_ix(0, 1, RESERVE / 10, 0);
+    let events = env
+        .process_events::<LargeFlowEvent>(&[large])
+        .await
+        .unwrap();
+    assert_eq!(events.len(), 1);
+    let event = &events[0];
+    assert_eq!(event.direction, FlowDirection::Swap);
+    assert_eq!(event.user, env.user());
+    assert_eq!(event.token_mint, env.mints[1]);
+    assert!(event.reserve_share_bps >= 900);
+    assert!(event.weights_after[1] < event.weights_before[1]);
+    assert!(event.weights_after[0] > event.weights_before[0]);
+
+    // Withdrawing a fifth of the LP drains every reserve by about as much
+    let lp = env.token_balance(env.user_lp_token).await;
+    let withdraw = env.withdraw_ix(lp / 5, [0; 3], WithdrawMode::Proportional);
+    let events = env
+        .process_events::<LargeFlowEvent>(&[withdraw])
+        .await
+        .unwrap();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].direction, FlowDirection::Withdraw);
+    assert!(events[0].reserve_share_bps >= 1900);
+}
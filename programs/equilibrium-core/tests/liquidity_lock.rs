@@ -0,0 +1,238 @@
+//! Partner teams locking Growth pool LP on a cliff and vesting schedule
+
+mod common;
+
+use anchor_lang::{AnchorDeserialize, InstructionData, ToAccountMetas};
+use common::{assert_program_error, create_growth_pool, GrowthPool, SeedPoolEnv};
+use equilibrium_core::errors::ErrorCode;
+use equilibrium_core::{LockExpiryView, LockedPosition, UserPosition};
+use solana_sdk::{instruction::Instruction, pubkey::Pubkey, system_program, sysvar};
+
+const RESERVE: u64 = 1_000_000_000;
+const LOCKED: u64 = 120_000_000;
+const MONTH: i64 = 30 * 86_400;
+
+fn pda(seeds: &[&[u8]]) -> Pubkey {
+    Pubkey::find_program_address(seeds, &equilibrium_core::id()).0
+}
+
+fn position(owner: &Pubkey, pool: &Pubkey) -> Pubkey {
+    pda(&[b"user-position", owner.as_ref(), pool.as_ref()])
+}
+
+/// Deposit into the Growth pool, so the user's position holds LP to lock
+fn growth_deposit_ix(env: &SeedPoolEnv, growth: &GrowthPool, amount: u64) -> Instruction {
+    let user = env.user();
+    Instruction {
+        program_id: equilibrium_core::id(),
+        accounts: equilibrium_core::accounts::Deposit {
+            user,
+            pool: growth.pool,
+            lp_mint: growth.lp_mint,
+            user_lp_token: growth.user_lp_token,
+            user_token_a: growth.user_tokens[0],
+            user_token_b: growth.user_tokens[1],
+            user_token_c: None,
+            token_mint_a: growth.mints[0],
+            token_mint_b: growth.mints[1],
+            token_mint_c: None,
+            pool_token_a: growth.pool_tokens[0],
+            pool_token_b: growth.pool_tokens[1],
+            pool_token_c: None,
+            user_position: position(&user, &growth.pool),
+            token_program: spl_token::ID,
+            system_program: system_program::ID,
+            rent: sysvar::rent::ID,
+            delegate: None,
+            pool_snapshot: None,
+            credential: None,
+            user_portfolio: None,
+        }
+        .to_account_metas(None),
+        data: equilibrium_core::instruction::Deposit {
+            amounts: [amount, amount, 0],
+            min_lp_amount: 0,
+            concentration: 1000,
+            deadline: i64::MAX,
+        }
+        .data(),
+    }
+}
+
+struct Lock {
+    locked_position: Pubkey,
+    vault: Pubkey,
+}
+
+fn lock_ix(
+    env: &SeedPoolEnv,
+    growth: &GrowthPool,
+    lock_id: u64,
+    duration: i64,
+    cliff_duration: i64,
+) -> (Instruction, Lock) {
+    let user = env.user();
+    let locked_position = pda(&[
+        b"locked-position",
+        user.as_ref(),
+        growth.pool.as_ref(),
+        &lock_id.to_le_bytes(),
+    ]);
+    let vault = pda(&[b"locked-position-vault", locked_position.as_ref()]);
+    let ix = Instruction {
+        program_id: equilibrium_core::id(),
+        accounts: equilibrium_core::accounts::LockLiquidity {
+            owner: user,
+            pool: growth.pool,
+            lp_mint: growth.lp_mint,
+            locked_position,
+            vault,
+            owner_lp_token: growth.user_lp_token,
+            user_position: position(&user, &growth.pool),
+            token_program: spl_token::ID,
+            system_program: system_program::ID,
+            rent: sysvar::rent::ID,
+        }
+        .to_account_metas(None),
+        data: equilibrium_core::instruction::LockLiquidity {
+            lock_id,
+            lp_amount: LOCKED,
+            duration,
+            cliff_duration,
+        }
+        .data(),
+    };
+    (
+        ix,
+        Lock {
+            locked_position,
+            vault,
+        },
+    )
+}
+
+fn release_ix(env: &SeedPoolEnv, growth: &GrowthPool, lock: &Lock) -> Instruction {
+    Instruction {
+        program_id: equilibrium_core::id(),
+        accounts: equilibrium_core::accounts::ReleaseLiquidity {
+            owner: env.user(),
+            pool: growth.pool,
+            locked_position: lock.locked_position,
+            vault: lock.vault,
+            owner_lp_token: growth.user_lp_token,
+            user_position: position(&env.user(), &growth.pool),
+            token_program: spl_token::ID,
+        }
+        .to_account_metas(None),
+        data: equilibrium_core::instruction::ReleaseLiquidity {}.data(),
+    }
+}
+
+async fn lock_expiry(env: &mut SeedPoolEnv, lock: &Lock) -> LockExpiryView {
+    let view = Instruction {
+        program_id: equilibrium_core::id(),
+        accounts: equilibrium_core::accounts::ViewLockedPosition {
+            locked_position: lock.locked_position,
+        }
+        .to_account_metas(None),
+        data: equilibrium_core::instruction::GetLockExpiry {}.data(),
+    };
+    let data = env.simulate(&[view]).await;
+    LockExpiryView::try_from_slice(&data).unwrap()
+}
+
+#[tokio::test]
+#[ignore = "needs the SBF program from `anchor build`"]
+async fn locked_lp_vests_after_the_cliff_and_closes_when_released() {
+    common::require_program();
+
+    let mut env = SeedPoolEnv::new([RESERVE; 3]).await;
+    let growth = create_growth_pool(&mut env, RESERVE).await;
+    env.process(&[growth_deposit_ix(&env, &growth, RESERVE / 4)])
+        .await
+        .unwrap();
+    let user_position = position(&env.user(), &growth.pool);
+    let position_before = env.account_state::<UserPosition>(user_position).await;
+    assert!(position_before.lp_amount > LOCKED);
+    let balance_before = env.token_balance(growth.user_lp_token).await;
+
+    // Twelve months, nothing for the first three
+    let (lock, lock_accounts) = lock_ix(&env, &growth, 0, 12 * MONTH, 3 * MONTH);
+    env.process(&[lock]).await.unwrap();
+    assert_eq!(
+        env.token_balance(growth.user_lp_token).await,
+        balance_before - LOCKED
+    );
+    assert_eq!(env.token_balance(lock_accounts.vault).await, LOCKED);
+    // Locked LP leaves the position, so it no longer counts as the owner's
+    let position = env.account_state::<UserPosition>(user_position).await;
+    assert_eq!(position.lp_amount, position_before.lp_amount - LOCKED);
+
+    let locked = env
+        .account_state::<LockedPosition>(lock_accounts.locked_position)
+        .await;
+    let view = lock_expiry(&mut env, &lock_accounts).await;
+    assert_eq!((view.owner, view.pool), (env.user(), growth.pool));
+    assert_eq!((view.amount, view.vested, view.released), (LOCKED, 0, 0));
+    assert_eq!(view.end_ts, locked.start_ts + 12 * MONTH);
+    assert_eq!(view.seconds_remaining, 12 * MONTH);
+
+    // Nothing can leave before the cliff
+    env.advance_clock(3 * MONTH - 1).await;
+    assert_program_error(
+        env.process(&[release_ix(&env, &growth, &lock_accounts)])
+            .await,
+        ErrorCode::LockNotExpired,
+    );
+
+    // Halfway through, half the lock has vested
+    env.advance_clock(3 * MONTH + 1).await;
+    env.process(&[release_ix(&env, &growth, &lock_accounts)])
+        .await
+        .unwrap();
+    assert_eq!(env.token_balance(lock_accounts.vault).await, LOCKED / 2);
+    let position = env.account_state::<UserPosition>(user_position).await;
+    assert_eq!(position.lp_amount, position_before.lp_amount - LOCKED / 2);
+    let view = lock_expiry(&mut env, &lock_accounts).await;
+    assert_eq!((view.vested, view.released), (LOCKED / 2, LOCKED / 2));
+    assert_eq!(view.seconds_remaining, 6 * MONTH);
+
+    // At expiry the rest is paid out and the lock closes
+    env.advance_clock(6 * MONTH).await;
+    env.process(&[release_ix(&env, &growth, &lock_accounts)])
+        .await
+        .unwrap();
+    assert_eq!(
+        env.token_balance(growth.user_lp_token).await,
+        balance_before
+    );
+    let position = env.account_state::<UserPosition>(user_position).await;
+    assert_eq!(position.lp_amount, position_before.lp_amount);
+    for closed in [lock_accounts.locked_position, lock_accounts.vault] {
+        let account = env.context.banks_client.get_account(closed).await.unwrap();
+        assert!(account.is_none());
+    }
+}
+
+#[tokio::test]
+#[ignore = "needs the SBF program from `anchor build`"]
+async fn locks_only_accept_growth_pool_lp_on_a_valid_schedule() {
+    common::require_program();
+
+    let mut env = SeedPoolEnv::new([RESERVE; 3]).await;
+    let growth = create_growth_pool(&mut env, RESERVE).await;
+
+    // The cliff cannot outlast the lock
+    let (lock, _) = lock_ix(&env, &growth, 0, MONTH, MONTH + 1);
+    assert_program_error(env.process(&[lock]).await, ErrorCode::InvalidLockSchedule);
+
+    // Seed Pool LP (USD*) is not lockable
+    let seed_pool = GrowthPool {
+        pool: env.pool,
+        lp_mint: env.lp_mint,
+        user_lp_token: env.user_lp_token,
+        ..growth
+    };
+    let (lock, _) = lock_ix(&env, &seed_pool, 0, MONTH, 0);
+    assert_program_error(env.process(&[lock]).await, ErrorCode::InvalidPoolType);
+}
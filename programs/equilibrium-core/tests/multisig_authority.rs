@@ -0,0 +1,126 @@
+//! Handing the config authority to a PDA, as a Squads-style multisig vault
+
+mod common;
+
+use anchor_lang::solana_program::{
+    account_info::AccountInfo,
+    entrypoint::ProgramResult,
+    instruction::{AccountMeta, Instruction},
+    program::invoke_signed,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+use anchor_lang::{InstructionData, ToAccountMetas};
+use common::{assert_program_error, SeedPoolEnv};
+use equilibrium_core::errors::ErrorCode;
+use solana_program_test::processor;
+
+const VAULT_SEED: &[u8] = b"vault";
+
+fn multisig_id() -> Pubkey {
+    Pubkey::new_from_array([7; 32])
+}
+
+fn vault() -> Pubkey {
+    Pubkey::find_program_address(&[VAULT_SEED], &multisig_id()).0
+}
+
+/// Minimal stand-in for a multisig program: once "approved" it forwards the
+/// wrapped instruction to the target program with its vault PDA as signer.
+/// Accounts are `[target_program, ..target_accounts]`, data is passed through.
+fn process_multisig(program_id: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    let (target_program, target_accounts) = accounts
+        .split_first()
+        .ok_or(ProgramError::NotEnoughAccountKeys)?;
+    let (vault, bump) = Pubkey::find_program_address(&[VAULT_SEED], program_id);
+
+    let instruction = Instruction {
+        program_id: *target_program.key,
+        accounts: target_accounts
+            .iter()
+            .map(|account| AccountMeta {
+                pubkey: *account.key,
+                is_signer: account.is_signer || *account.key == vault,
+                is_writable: account.is_writable,
+            })
+            .collect(),
+        data: data.to_vec(),
+    };
+    invoke_signed(&instruction, target_accounts, &[&[VAULT_SEED, &[bump]]])
+}
+
+/// Wrap an admin instruction so the multisig vault signs it through CPI
+fn execute_as_config_authority(instruction: Instruction) -> Instruction {
+    let mut accounts = vec![AccountMeta::new_readonly(instruction.program_id, false)];
+    accounts.extend(instruction.accounts.into_iter().map(|mut meta| {
+        if meta.pubkey == vault() {
+            meta.is_signer = false;
+        }
+        meta
+    }));
+
+    Instruction {
+        program_id: multisig_id(),
+        accounts,
+        data: instruction.data,
+    }
+}
+
+async fn multisig_env() -> SeedPoolEnv {
+    SeedPoolEnv::with_programs(
+        [1_000_000_000, 1_000_000_000, 1_000_000_000],
+        |program_test| {
+            program_test.add_program("mock_multisig", multisig_id(), processor!(process_multisig));
+        },
+    )
+    .await
+}
+
+fn accept_authority_ix(env: &SeedPoolEnv, pending_authority: Pubkey) -> Instruction {
+    Instruction {
+        program_id: equilibrium_core::id(),
+        accounts: equilibrium_core::accounts::AcceptAuthority {
+            pending_authority,
+            amm_config: env.amm_config,
+        }
+        .to_account_metas(None),
+        data: equilibrium_core::instruction::AcceptAuthority {}.data(),
+    }
+}
+
+#[tokio::test]
+#[ignore = "needs the SBF program from `anchor build`"]
+async fn multisig_vault_can_take_over_and_exercise_config_authority() {
+    common::require_program();
+
+    let mut env = multisig_env().await;
+    let propose = env.update_config_ix(equilibrium_core::instruction::ProposeAuthority {
+        new_authority: vault(),
+    });
+    env.process(&[propose]).await.unwrap();
+    assert_eq!(env.config_state().await.pending_authority, vault());
+
+    // The current authority cannot complete the handoff on the vault's behalf
+    let accept = accept_authority_ix(&env, env.user());
+    assert_program_error(env.process(&[accept]).await, ErrorCode::Unauthorized);
+
+    let accept = execute_as_config_authority(accept_authority_ix(&env, vault()));
+    env.process(&[accept]).await.unwrap();
+    let config = env.config_state().await;
+    assert_eq!(config.authority, vault());
+    assert_eq!(config.pending_authority, Pubkey::default());
+
+    // The old keypair authority is locked out
+    let set_exit_fee =
+        env.update_pool_ix(equilibrium_core::instruction::SetExitFee { exit_fee_bps: 25 });
+    assert_program_error(env.process(&[set_exit_fee]).await, ErrorCode::Unauthorized);
+
+    // The vault drives per-pool admin through CPI
+    let mut set_exit_fee =
+        env.update_pool_ix(equilibrium_core::instruction::SetExitFee { exit_fee_bps: 25 });
+    set_exit_fee.accounts[0] = AccountMeta::new_readonly(vault(), true);
+    env.process(&[execute_as_config_authority(set_exit_fee)])
+        .await
+        .unwrap();
+    assert_eq!(env.pool_state().await.exit_fee_bps, 25);
+}
@@ -0,0 +1,154 @@
+//! Oracle-banded surcharges on Growth pools
+
+mod common;
+
+use anchor_lang::{InstructionData, ToAccountMetas};
+use common::{
+    assert_program_error, create_growth_pool, publish_price_and_band, GrowthPool, SeedPoolEnv,
+};
+use equilibrium_core::errors::ErrorCode;
+use solana_sdk::instruction::Instruction;
+
+const RESERVE: u64 = 1_000_000_000;
+
+fn growth_swap_ix(
+    env: &SeedPoolEnv,
+    growth: &GrowthPool,
+    token_in: usize,
+    amount_in: u64,
+) -> Instruction {
+    let token_out = 1 - token_in;
+    Instruction {
+        program_id: equilibrium_core::id(),
+        accounts: equilibrium_core::accounts::Swap {
+            user: env.user(),
+            amm_config: env.amm_config,
+            pool: growth.pool,
+            token_mint_in: growth.mints[token_in],
+            token_mint_out: growth.mints[token_out],
+            user_token_in: growth.user_tokens[token_in],
+            user_token_out: growth.user_tokens[token_out],
+            pool_token_in: growth.pool_tokens[token_in],
+            pool_token_out: growth.pool_tokens[token_out],
+            token_program: spl_token::ID,
+            recipient_token_out: None,
+            vote_escrow: None,
+            pool_snapshot: None,
+            credential: None,
+            integrator_fee_account: None,
+            referral_account: None,
+            referral_vault: None,
+        }
+        .to_account_metas(None),
+        data: equilibrium_core::instruction::Swap {
+            amount_in,
+            min_amount_out: 0,
+            deadline: i64::MAX,
+            integrator_fee_bps: 0,
+            max_price_impact_bps: None,
+        }
+        .data(),
+    }
+}
+
+async fn lifetime_fees(env: &mut SeedPoolEnv, growth: &GrowthPool, token: usize) -> u128 {
+    env.account_state::<equilibrium_core::Pool>(growth.pool)
+        .await
+        .lifetime_fees_per_token[token]
+}
+
+#[tokio::test]
+#[ignore = "needs the SBF program from `anchor build`"]
+async fn trades_pushing_away_from_the_oracle_pay_the_surcharge() {
+    common::require_program();
+
+    let mut env = SeedPoolEnv::new([RESERVE; 3]).await;
+    let growth = create_growth_pool(&mut env, RESERVE).await;
+
+    // Seed pools have no partner token to band
+    assert_program_error(
+        env.process(&[
+            env.update_pool_ix(equilibrium_core::instruction::SetOracleBand {
+                band_bps: 100,
+                surcharge_bps: 500,
+            }),
+        ])
+        .await,
+        ErrorCode::InvalidPoolType,
+    );
+
+    publish_price_and_band(&mut env, &growth).await;
+
+    // Buying 20% of the partner reserve lifts its price about 2% over the oracle
+    let amount_in = RESERVE / 5;
+    let fees_before = lifetime_fees(&mut env, &growth, 0).await;
+    env.process(&[growth_swap_ix(&env, &growth, 0, amount_in)])
+        .await
+        .unwrap();
+    let fee_paid = lifetime_fees(&mut env, &growth, 0).await - fees_before;
+    assert!(fee_paid >= amount_in as u128 * 500 / 10_000);
+
+    // Selling the partner back moves toward the oracle and pays the base fee only
+    let amount_in = RESERVE / 20;
+    let fees_before = lifetime_fees(&mut env, &growth, 1).await;
+    env.process(&[growth_swap_ix(&env, &growth, 1, amount_in)])
+        .await
+        .unwrap();
+    let fee_paid = lifetime_fees(&mut env, &growth, 1).await - fees_before;
+    assert!(fee_paid < amount_in as u128 * 100 / 10_000);
+}
+
+#[tokio::test]
+#[ignore = "needs the SBF program from `anchor build`"]
+async fn stale_oracle_prices_swaps_at_the_max_fee_or_rejects_them() {
+    common::require_program();
+
+    let mut env = SeedPoolEnv::new([RESERVE; 3]).await;
+    let growth = create_growth_pool(&mut env, RESERVE).await;
+    publish_price_and_band(&mut env, &growth).await;
+    let protect = env.update_config_ix(equilibrium_core::instruction::SetStalePoolProtection {
+        max_oracle_age_slots: 100,
+        reject_stale_swaps: false,
+    });
+    env.process(&[protect]).await.unwrap();
+
+    // A small trade near the peg pays the base fee while the price is fresh
+    let amount_in = RESERVE / 1_000;
+    let fees_before = lifetime_fees(&mut env, &growth, 0).await;
+    env.process(&[growth_swap_ix(&env, &growth, 0, amount_in)])
+        .await
+        .unwrap();
+    let fresh_fee = lifetime_fees(&mut env, &growth, 0).await - fees_before;
+
+    env.advance_slots(101).await;
+    let max_fee = env
+        .account_state::<equilibrium_core::Pool>(growth.pool)
+        .await
+        .fee_params
+        .max_fee;
+    let fees_before = lifetime_fees(&mut env, &growth, 0).await;
+    env.process(&[growth_swap_ix(&env, &growth, 0, amount_in)])
+        .await
+        .unwrap();
+    let stale_fee = lifetime_fees(&mut env, &growth, 0).await - fees_before;
+    assert!(stale_fee > fresh_fee);
+    assert_eq!(
+        stale_fee,
+        (amount_in as u128 * max_fee as u128).div_ceil(1000)
+    );
+
+    let reject = env.update_config_ix(equilibrium_core::instruction::SetStalePoolProtection {
+        max_oracle_age_slots: 100,
+        reject_stale_swaps: true,
+    });
+    env.process(&[reject]).await.unwrap();
+    assert_program_error(
+        env.process(&[growth_swap_ix(&env, &growth, 0, amount_in)])
+            .await,
+        ErrorCode::StalePool,
+    );
+
+    // Seed pools never price against an oracle
+    let swap = env.swap_ix(0, 1, amount_in, 0);
+    env.process(&[swap]).await.unwrap();
+}
@@ -0,0 +1,144 @@
+//! Reserves left behind when every LP token is burned outside `withdraw`
+
+mod common;
+
+use anchor_lang::{InstructionData, ToAccountMetas};
+use common::{assert_program_error, SeedPoolEnv};
+use equilibrium_core::errors::ErrorCode;
+use equilibrium_core::WithdrawMode;
+use solana_sdk::{instruction::Instruction, pubkey::Pubkey};
+
+const RESERVE: u64 = 1_000_000_000;
+
+fn treasury(env: &SeedPoolEnv) -> Pubkey {
+    Pubkey::find_program_address(
+        &[b"treasury", env.amm_config.as_ref()],
+        &equilibrium_core::id(),
+    )
+    .0
+}
+
+fn treasury_vault(env: &SeedPoolEnv, mint: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[b"treasury-vault", treasury(env).as_ref(), mint.as_ref()],
+        &equilibrium_core::id(),
+    )
+    .0
+}
+
+fn init_treasury_ixs(env: &SeedPoolEnv) -> Vec<Instruction> {
+    let mut ixs = vec![Instruction {
+        program_id: equilibrium_core::id(),
+        accounts: equilibrium_core::accounts::InitTreasury {
+            authority: env.user(),
+            amm_config: env.amm_config,
+            treasury: treasury(env),
+            system_program: solana_sdk::system_program::ID,
+        }
+        .to_account_metas(None),
+        data: equilibrium_core::instruction::InitTreasury {}.data(),
+    }];
+    for mint in env.mints {
+        ixs.push(Instruction {
+            program_id: equilibrium_core::id(),
+            accounts: equilibrium_core::accounts::InitTreasuryVault {
+                payer: env.user(),
+                treasury: treasury(env),
+                token_mint: mint,
+                treasury_vault: treasury_vault(env, &mint),
+                token_program: spl_token::ID,
+                system_program: solana_sdk::system_program::ID,
+                rent: solana_sdk::sysvar::rent::ID,
+            }
+            .to_account_metas(None),
+            data: equilibrium_core::instruction::InitTreasuryVault {}.data(),
+        });
+    }
+    ixs
+}
+
+fn recover_ix(env: &SeedPoolEnv, token: usize) -> Instruction {
+    Instruction {
+        program_id: equilibrium_core::id(),
+        accounts: equilibrium_core::accounts::RecoverOrphanedReserves {
+            authority: env.user(),
+            amm_config: env.amm_config,
+            pool: env.pool,
+            lp_mint: env.lp_mint,
+            token_mint: env.mints[token],
+            pool_token: env.pool_tokens[token],
+            treasury: treasury(env),
+            treasury_vault: treasury_vault(env, &env.mints[token]),
+            token_program: spl_token::ID,
+        }
+        .to_account_metas(None),
+        data: equilibrium_core::instruction::RecoverOrphanedReserves {}.data(),
+    }
+}
+
+#[tokio::test]
+#[ignore = "needs the SBF program from `anchor build`"]
+async fn orphaned_reserves_go_to_the_treasury_and_the_pool_reprices_from_empty() {
+    common::require_program();
+
+    let mut env = SeedPoolEnv::new([RESERVE; 3]).await;
+    env.process(&init_treasury_ixs(&env)).await.unwrap();
+
+    // Nothing is orphaned while LP is outstanding
+    assert_program_error(
+        env.process(&[recover_ix(&env, 0)]).await,
+        ErrorCode::LpSupplyOutstanding,
+    );
+
+    // Burn every LP token straight through the token program
+    let lp_balance = env.token_balance(env.user_lp_token).await;
+    let burn = spl_token::instruction::burn(
+        &spl_token::ID,
+        &env.user_lp_token,
+        &env.lp_mint,
+        &env.user(),
+        &[],
+        lp_balance,
+    )
+    .unwrap();
+    env.process(&[burn]).await.unwrap();
+
+    // The position still records the LP, but there is no supply to claim against
+    let position_lp = env.position_state().await.lp_amount;
+    assert_program_error(
+        env.process(&[env.withdraw_ix(position_lp, [0; 3], WithdrawMode::Proportional)])
+            .await,
+        ErrorCode::InsufficientLiquidity,
+    );
+
+    // Nor can a deposit collect them as the pool's first
+    assert_program_error(
+        env.process(&[env.deposit_ix([1; 3], 0)]).await,
+        ErrorCode::OrphanedReservesPending,
+    );
+
+    for token in 0..3 {
+        env.process(&[recover_ix(&env, token)]).await.unwrap();
+        assert_eq!(
+            env.token_balance(treasury_vault(&env, &env.mints[token]))
+                .await,
+            RESERVE
+        );
+    }
+    let pool = env.pool_state().await;
+    assert_eq!(pool.reserves, vec![0; 3]);
+    assert_eq!(pool.last_d, 0);
+    assert_program_error(
+        env.process(&[recover_ix(&env, 0)]).await,
+        ErrorCode::NothingToCollect,
+    );
+
+    // The next deposit is priced as the pool's first
+    let deposit = 100_000_000;
+    env.process(&[env.deposit_ix([deposit; 3], 0)])
+        .await
+        .unwrap();
+    let pool = env.pool_state().await;
+    assert_eq!(pool.reserves, vec![deposit; 3]);
+    assert_eq!(env.token_balance(env.user_lp_token).await, deposit * 3);
+}
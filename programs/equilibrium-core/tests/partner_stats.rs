@@ -0,0 +1,103 @@
+//! Crank-maintained statistics for partner dashboards
+
+mod common;
+
+use anchor_lang::{InstructionData, ToAccountMetas};
+use common::{assert_program_error, create_growth_pool, SeedPoolEnv};
+use equilibrium_core::errors::ErrorCode;
+use equilibrium_core::state::math::ORACLE_PRICE_PRECISION;
+use equilibrium_core::PartnerStats;
+use solana_sdk::{instruction::Instruction, pubkey::Pubkey, system_program};
+
+const RESERVE: u64 = 1_000_000_000;
+
+#[tokio::test]
+#[ignore = "needs the SBF program from `anchor build`"]
+async fn partner_stats_publish_tvl_and_depth_hourly_in_usd_star() {
+    common::require_program();
+
+    let mut env = SeedPoolEnv::new([RESERVE; 3]).await;
+    let growth = create_growth_pool(&mut env, RESERVE).await;
+    let partner_stats = Pubkey::find_program_address(
+        &[b"partner-stats", growth.pool.as_ref()],
+        &equilibrium_core::id(),
+    )
+    .0;
+
+    // Seed Pools have no partner side to report on
+    let init = |pool: Pubkey| Instruction {
+        program_id: equilibrium_core::id(),
+        accounts: equilibrium_core::accounts::InitPartnerStats {
+            payer: env.user(),
+            pool,
+            partner_stats: Pubkey::find_program_address(
+                &[b"partner-stats", pool.as_ref()],
+                &equilibrium_core::id(),
+            )
+            .0,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: equilibrium_core::instruction::InitPartnerStats {}.data(),
+    };
+    let (init_seed, init_growth) = (init(env.pool), init(growth.pool));
+    assert_program_error(env.process(&[init_seed]).await, ErrorCode::InvalidPoolType);
+    env.process(&[init_growth]).await.unwrap();
+
+    let stats = env.account_state::<PartnerStats>(partner_stats).await;
+    assert_eq!(stats.pool, growth.pool);
+    assert_eq!(stats.samples.len(), 1);
+    // Without an oracle price the partner token is valued at the pool's spot price
+    assert_eq!(stats.partner_price, ORACLE_PRICE_PRECISION);
+    assert_eq!(stats.tvl, 2 * RESERVE);
+    assert_eq!(
+        (stats.volume_24h, stats.fees_24h, stats.fee_apr_bps),
+        (0, 0, 0)
+    );
+    // A balanced pool is as deep from either side
+    assert!(stats.depth_usd_star_in > 0);
+    assert_eq!(stats.depth_usd_star_in, stats.depth_partner_in);
+
+    let refresh = Instruction {
+        program_id: equilibrium_core::id(),
+        accounts: equilibrium_core::accounts::RefreshPartnerStats {
+            pool: growth.pool,
+            partner_stats,
+        }
+        .to_account_metas(None),
+        data: equilibrium_core::instruction::RefreshPartnerStats {}.data(),
+    };
+    assert_program_error(
+        env.process(std::slice::from_ref(&refresh)).await,
+        ErrorCode::PoolStatsTooRecent,
+    );
+
+    // A fresh oracle price values the partner reserves at it instead
+    let set_keeper = env.update_config_ix(equilibrium_core::instruction::SetRebalanceConfig {
+        keeper: env.user(),
+        rebalance_program: Pubkey::default(),
+        max_rebalance_bps: 0,
+    });
+    let publish_price = Instruction {
+        program_id: equilibrium_core::id(),
+        accounts: equilibrium_core::accounts::UpdatePartnerPrice {
+            keeper: env.user(),
+            amm_config: env.amm_config,
+            pool: growth.pool,
+        }
+        .to_account_metas(None),
+        data: equilibrium_core::instruction::UpdatePartnerPrice {
+            price: 2 * ORACLE_PRICE_PRECISION,
+        }
+        .data(),
+    };
+    env.advance_clock(3600).await;
+    env.process(&[set_keeper, publish_price, refresh])
+        .await
+        .unwrap();
+    let refreshed = env.account_state::<PartnerStats>(partner_stats).await;
+    assert_eq!(refreshed.samples.len(), 2);
+    assert_eq!(refreshed.updated_at, stats.updated_at + 3600);
+    assert_eq!(refreshed.partner_price, 2 * ORACLE_PRICE_PRECISION);
+    assert_eq!(refreshed.tvl, 3 * RESERVE);
+}
@@ -0,0 +1,169 @@
+//! Routing one partner token into another through USD*
+
+mod common;
+
+use anchor_lang::{InstructionData, ToAccountMetas};
+use common::{
+    assert_program_error, create_ata, create_growth_pool, mint_to, publish_price_and_band,
+    GrowthPool, SeedPoolEnv,
+};
+use equilibrium_core::errors::ErrorCode;
+use solana_sdk::{
+    instruction::Instruction,
+    native_token::LAMPORTS_PER_SOL,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    system_instruction, system_program,
+};
+
+const RESERVE: u64 = 1_000_000_000;
+
+fn route_ix(
+    env: &SeedPoolEnv,
+    trader: &Pubkey,
+    pool_in: &GrowthPool,
+    pool_out: &GrowthPool,
+    amount_in: u64,
+) -> Instruction {
+    Instruction {
+        program_id: equilibrium_core::id(),
+        accounts: equilibrium_core::accounts::SwapPartnerToPartner {
+            user: *trader,
+            amm_config: env.amm_config,
+            pool_in: pool_in.pool,
+            pool_out: pool_out.pool,
+            partner_mint_in: pool_in.mints[1],
+            usd_star_mint: pool_in.mints[0],
+            partner_mint_out: pool_out.mints[1],
+            user_token_in: spl_associated_token_account::get_associated_token_address(
+                trader,
+                &pool_in.mints[1],
+            ),
+            user_token_out: spl_associated_token_account::get_associated_token_address(
+                trader,
+                &pool_out.mints[1],
+            ),
+            pool_in_partner_token: pool_in.pool_tokens[1],
+            pool_in_usd_star: pool_in.pool_tokens[0],
+            pool_out_usd_star: pool_out.pool_tokens[0],
+            pool_out_partner_token: pool_out.pool_tokens[1],
+            token_program: spl_token::ID,
+            associated_token_program: spl_associated_token_account::ID,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: equilibrium_core::instruction::SwapPartnerToPartner {
+            amount_in,
+            min_amount_out: 1,
+            deadline: i64::MAX,
+        }
+        .data(),
+    }
+}
+
+#[tokio::test]
+#[ignore = "needs the SBF program from `anchor build`"]
+async fn route_creates_the_output_account_idempotently() {
+    common::require_program();
+
+    let mut env = SeedPoolEnv::new([RESERVE; 3]).await;
+    let pool_in = create_growth_pool(&mut env, RESERVE / 4).await;
+    let pool_out = create_growth_pool(&mut env, RESERVE / 4).await;
+
+    // A fresh trader holding only the input partner token
+    let trader = Keypair::new();
+    let token_in = create_ata(&mut env.context, &pool_in.mints[1], &trader.pubkey()).await;
+    mint_to(&mut env.context, &pool_in.mints[1], &token_in, RESERVE).await;
+    let fund = system_instruction::transfer(&env.user(), &trader.pubkey(), LAMPORTS_PER_SOL);
+    env.process(&[fund]).await.unwrap();
+    let token_out = spl_associated_token_account::get_associated_token_address(
+        &trader.pubkey(),
+        &pool_out.mints[1],
+    );
+    assert!(env
+        .context
+        .banks_client
+        .get_account(token_out)
+        .await
+        .unwrap()
+        .is_none());
+
+    // The first route opens the output account, later ones reuse it
+    let route = route_ix(&env, &trader.pubkey(), &pool_in, &pool_out, RESERVE / 100);
+    env.process_signed(&[route], &[&trader]).await.unwrap();
+    let received = env.token_balance(token_out).await;
+    assert!(received > 0);
+
+    let route = route_ix(&env, &trader.pubkey(), &pool_in, &pool_out, RESERVE / 100);
+    env.process_signed(&[route], &[&trader]).await.unwrap();
+    assert!(env.token_balance(token_out).await > received);
+}
+
+#[tokio::test]
+#[ignore = "needs the SBF program from `anchor build`"]
+async fn route_through_a_rogue_usd_star_vault_is_rejected() {
+    common::require_program();
+
+    let mut env = SeedPoolEnv::new([RESERVE; 3]).await;
+    let pool_in = create_growth_pool(&mut env, RESERVE / 4).await;
+    let pool_out = create_growth_pool(&mut env, RESERVE / 4).await;
+
+    // Owned by the output pool and holding USD*, but not the vault it tracks
+    let usd_star = pool_out.mints[0];
+    let rogue = create_ata(&mut env.context, &usd_star, &pool_out.pool).await;
+    let mut route = route_ix(&env, &env.user(), &pool_in, &pool_out, RESERVE / 100);
+    let vault = route
+        .accounts
+        .iter_mut()
+        .find(|meta| meta.pubkey == pool_out.pool_tokens[0])
+        .unwrap();
+    vault.pubkey = rogue;
+    assert_program_error(env.process(&[route]).await, ErrorCode::VaultMismatch);
+}
+
+#[tokio::test]
+#[ignore = "needs the SBF program from `anchor build`"]
+async fn route_through_a_stale_pool_pays_the_floor_or_is_rejected() {
+    common::require_program();
+
+    let mut env = SeedPoolEnv::new([RESERVE; 3]).await;
+    let pool_in = create_growth_pool(&mut env, RESERVE / 4).await;
+    let pool_out = create_growth_pool(&mut env, RESERVE / 4).await;
+    publish_price_and_band(&mut env, &pool_in).await;
+    let user = env.user();
+
+    // With the oracle stale, the first hop is charged the pool's max fee
+    let protect = env.update_config_ix(equilibrium_core::instruction::SetStalePoolProtection {
+        max_oracle_age_slots: 100,
+        reject_stale_swaps: false,
+    });
+    env.process(&[protect]).await.unwrap();
+    env.advance_slots(101).await;
+    let pool = env
+        .account_state::<equilibrium_core::Pool>(pool_in.pool)
+        .await;
+    let amount_in = RESERVE / 1_000;
+    env.process(&[route_ix(&env, &user, &pool_in, &pool_out, amount_in)])
+        .await
+        .unwrap();
+    let fees = env
+        .account_state::<equilibrium_core::Pool>(pool_in.pool)
+        .await
+        .lifetime_fees_per_token[1]
+        - pool.lifetime_fees_per_token[1];
+    assert_eq!(
+        fees,
+        (amount_in as u128 * pool.fee_params.max_fee as u128).div_ceil(1000)
+    );
+
+    let reject = env.update_config_ix(equilibrium_core::instruction::SetStalePoolProtection {
+        max_oracle_age_slots: 100,
+        reject_stale_swaps: true,
+    });
+    env.process(&[reject]).await.unwrap();
+    assert_program_error(
+        env.process(&[route_ix(&env, &user, &pool_in, &pool_out, amount_in)])
+            .await,
+        ErrorCode::StalePool,
+    );
+}
@@ -0,0 +1,70 @@
+//! Batched pool parameter updates
+
+mod common;
+
+use common::{assert_program_error, SeedPoolEnv};
+use equilibrium_core::errors::ErrorCode;
+use equilibrium_core::instruction::UpdatePoolParams;
+use equilibrium_core::{FeeParams, PoolParamChange, TokenStatus};
+
+const RESERVE: u64 = 1_000_000_000;
+
+#[tokio::test]
+#[ignore = "needs the SBF program from `anchor build`"]
+async fn batch_applies_every_change_or_none() {
+    common::require_program();
+
+    let mut env = SeedPoolEnv::new([RESERVE; 3]).await;
+    let before = env.pool_state().await;
+    let fee_params = FeeParams {
+        max_fee: before.fee_params.max_fee + 1,
+        ..before.fee_params
+    };
+    let changes = vec![
+        PoolParamChange::ExitFee { exit_fee_bps: 25 },
+        PoolParamChange::FeeParams { fee_params },
+        PoolParamChange::MaxTradeBps { max_trade_bps: 500 },
+        PoolParamChange::TokenFrozen {
+            token_mint: env.mints[2],
+            frozen: true,
+        },
+        PoolParamChange::Paused { paused: true },
+    ];
+
+    // One invalid change sinks the whole batch
+    let mut invalid = changes.clone();
+    invalid.push(PoolParamChange::ProtocolFeeShare {
+        protocol_fee_share_bps: u64::MAX,
+    });
+    let update = env.update_pool_ix(UpdatePoolParams { changes: invalid });
+    assert_program_error(env.process(&[update]).await, ErrorCode::InvalidFee);
+    let unchanged = env.pool_state().await;
+    assert_eq!(unchanged.exit_fee_bps, before.exit_fee_bps);
+    assert!(!unchanged.paused);
+
+    // So does setting a parameter twice, or a parameter a Seed Pool lacks
+    let mut twice = changes.clone();
+    twice.push(PoolParamChange::ExitFee { exit_fee_bps: 50 });
+    let update = env.update_pool_ix(UpdatePoolParams { changes: twice });
+    assert_program_error(env.process(&[update]).await, ErrorCode::InvalidPoolParams);
+    let mut growth_only = changes.clone();
+    growth_only.push(PoolParamChange::OracleBand {
+        band_bps: 100,
+        surcharge_bps: 0,
+    });
+    let update = env.update_pool_ix(UpdatePoolParams {
+        changes: growth_only,
+    });
+    assert_program_error(env.process(&[update]).await, ErrorCode::InvalidPoolType);
+    let update = env.update_pool_ix(UpdatePoolParams { changes: vec![] });
+    assert_program_error(env.process(&[update]).await, ErrorCode::InvalidPoolParams);
+
+    let update = env.update_pool_ix(UpdatePoolParams { changes });
+    env.process(&[update]).await.unwrap();
+    let after = env.pool_state().await;
+    assert_eq!(after.exit_fee_bps, 25);
+    assert_eq!(after.fee_params, fee_params);
+    assert_eq!(after.max_trade_bps, 500);
+    assert_eq!(after.token_status[2], TokenStatus::Frozen);
+    assert!(after.paused);
+}
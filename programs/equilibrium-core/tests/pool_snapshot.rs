@@ -0,0 +1,133 @@
+//! Front-end pool snapshot kept current by the handlers that pass it
+
+mod common;
+
+use anchor_lang::{InstructionData, ToAccountMetas};
+use common::{SeedPoolEnv, DEFAULT_WEIGHTS};
+use equilibrium_core::state::math::{calculate_dynamic_fee_from_reserves, calculate_weights};
+use equilibrium_core::{PoolSnapshot, WithdrawMode};
+use solana_sdk::{instruction::Instruction, pubkey::Pubkey, system_program};
+
+const RESERVE: u64 = 1_000_000_000;
+
+fn snapshot_address(env: &SeedPoolEnv) -> Pubkey {
+    Pubkey::find_program_address(
+        &[b"pool-snapshot", env.pool.as_ref()],
+        &equilibrium_core::id(),
+    )
+    .0
+}
+
+fn refresh_ix(env: &SeedPoolEnv) -> Instruction {
+    Instruction {
+        program_id: equilibrium_core::id(),
+        accounts: equilibrium_core::accounts::RefreshPoolSnapshot {
+            pool: env.pool,
+            pool_snapshot: snapshot_address(env),
+        }
+        .to_account_metas(None),
+        data: equilibrium_core::instruction::RefreshPoolSnapshot {}.data(),
+    }
+}
+
+/// `Swap` passes the snapshot ahead of its credential, integrator fee and referral accounts
+const SWAP_SNAPSHOT_FROM_END: usize = 5;
+
+/// `Deposit` passes the snapshot ahead of its credential and portfolio
+const DEPOSIT_SNAPSHOT_FROM_END: usize = 3;
+
+/// `Withdraw` passes the snapshot ahead of its portfolio
+const WITHDRAW_SNAPSHOT_FROM_END: usize = 2;
+
+/// Pass the snapshot in the optional slot `from_end` places from the end of `instruction`
+fn with_snapshot(mut instruction: Instruction, snapshot: Pubkey, from_end: usize) -> Instruction {
+    let index = instruction.accounts.len() - from_end;
+    let slot = &mut instruction.accounts[index];
+    slot.pubkey = snapshot;
+    slot.is_writable = true;
+    instruction
+}
+
+/// Check the snapshot mirrors the pool's current state
+async fn assert_matches_pool(env: &mut SeedPoolEnv, snapshot: &PoolSnapshot) {
+    let pool = env.pool_state().await;
+    assert_eq!(snapshot.num_tokens, 3);
+    assert_eq!(snapshot.reserves[..3], pool.reserves[..]);
+    assert_eq!(snapshot.weights[..3], calculate_weights(&pool.reserves)[..]);
+    assert_eq!(
+        snapshot.fee,
+        calculate_dynamic_fee_from_reserves(
+            &pool.reserves,
+            &DEFAULT_WEIGHTS,
+            &pool.fee_params.into()
+        )
+    );
+    assert_eq!(snapshot.virtual_price, pool.virtual_price);
+}
+
+#[tokio::test]
+#[ignore = "needs the SBF program from `anchor build`"]
+async fn snapshot_tracks_handlers_and_catches_up_by_crank() {
+    common::require_program();
+
+    let mut env = SeedPoolEnv::new([RESERVE; 3]).await;
+    let pool_snapshot = snapshot_address(&env);
+    let init = Instruction {
+        program_id: equilibrium_core::id(),
+        accounts: equilibrium_core::accounts::InitPoolSnapshot {
+            payer: env.user(),
+            pool: env.pool,
+            pool_snapshot,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: equilibrium_core::instruction::InitPoolSnapshot {}.data(),
+    };
+    env.process(&[init]).await.unwrap();
+    let snapshot = env.account_state::<PoolSnapshot>(pool_snapshot).await;
+    assert_eq!(snapshot.pool, env.pool);
+    assert_eq!(snapshot.volume_24h, 0);
+    assert_matches_pool(&mut env, &snapshot).await;
+
+    // Each handler passed the snapshot leaves it matching the pool
+    let swap = with_snapshot(
+        env.swap_ix(0, 1, 50_000_000, 0),
+        pool_snapshot,
+        SWAP_SNAPSHOT_FROM_END,
+    );
+    let deposit = with_snapshot(
+        env.deposit_ix([10_000_000; 3], 0),
+        pool_snapshot,
+        DEPOSIT_SNAPSHOT_FROM_END,
+    );
+    env.process(&[swap, deposit]).await.unwrap();
+    let snapshot = env.account_state::<PoolSnapshot>(pool_snapshot).await;
+    assert_eq!(snapshot.volume_24h, 50_000_000);
+    assert_matches_pool(&mut env, &snapshot).await;
+
+    let withdraw = with_snapshot(
+        env.withdraw_ix(5_000_000, [0; 3], WithdrawMode::Proportional),
+        pool_snapshot,
+        WITHDRAW_SNAPSHOT_FROM_END,
+    );
+    env.process(&[withdraw]).await.unwrap();
+    let snapshot = env.account_state::<PoolSnapshot>(pool_snapshot).await;
+    assert_matches_pool(&mut env, &snapshot).await;
+
+    // A swap without the snapshot leaves it stale until the crank runs
+    let swap = env.swap_ix(1, 2, 20_000_000, 0);
+    env.process(&[swap]).await.unwrap();
+    let stale = env.account_state::<PoolSnapshot>(pool_snapshot).await;
+    assert_ne!(stale.reserves[..3], env.pool_state().await.reserves[..]);
+
+    env.process(&[refresh_ix(&env)]).await.unwrap();
+    let snapshot = env.account_state::<PoolSnapshot>(pool_snapshot).await;
+    assert_eq!(snapshot.volume_24h, 70_000_000);
+    assert_matches_pool(&mut env, &snapshot).await;
+
+    // A day later the volume has rolled out of the window
+    env.advance_clock(25 * 3600).await;
+    env.process(&[refresh_ix(&env)]).await.unwrap();
+    let snapshot = env.account_state::<PoolSnapshot>(pool_snapshot).await;
+    assert_eq!(snapshot.volume_24h, 0);
+}
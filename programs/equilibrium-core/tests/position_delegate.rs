@@ -0,0 +1,122 @@
+//! Delegated liquidity management on a user position
+
+mod common;
+
+use anchor_lang::{InstructionData, ToAccountMetas};
+use common::{assert_program_error, SeedPoolEnv};
+use equilibrium_core::errors::ErrorCode;
+use equilibrium_core::WithdrawMode;
+use solana_sdk::{
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+};
+
+fn set_delegate_ix(env: &SeedPoolEnv, delegate: Option<Pubkey>) -> Instruction {
+    Instruction {
+        program_id: equilibrium_core::id(),
+        accounts: equilibrium_core::accounts::SetPositionDelegate {
+            owner: env.user(),
+            user_position: env.user_position,
+        }
+        .to_account_metas(None),
+        data: equilibrium_core::instruction::SetPositionDelegate { delegate }.data(),
+    }
+}
+
+fn approve_ix(env: &SeedPoolEnv, token_account: Pubkey, delegate: Pubkey) -> Instruction {
+    spl_token::instruction::approve(
+        &spl_token::ID,
+        &token_account,
+        &delegate,
+        &env.user(),
+        &[],
+        u64::MAX,
+    )
+    .unwrap()
+}
+
+/// `Deposit` ends with the delegate, pool snapshot, credential and portfolio
+const DEPOSIT_DELEGATE_FROM_END: usize = 4;
+
+/// `Withdraw` ends with the delegate, vote escrow, fee exemption, pool snapshot
+/// and portfolio
+const WITHDRAW_DELEGATE_FROM_END: usize = 5;
+
+fn signed_by(mut instruction: Instruction, delegate: Pubkey, from_end: usize) -> Instruction {
+    let count = instruction.accounts.len();
+    instruction.accounts[count - from_end] = AccountMeta::new_readonly(delegate, true);
+    instruction
+}
+
+#[tokio::test]
+#[ignore = "needs the SBF program from `anchor build`"]
+async fn registered_delegate_can_deposit_and_withdraw_for_owner() {
+    common::require_program();
+
+    let mut env = SeedPoolEnv::new([1_000_000_000, 1_000_000_000, 1_000_000_000]).await;
+    let bot = Keypair::new();
+    let mut setup = vec![set_delegate_ix(&env, Some(bot.pubkey()))];
+    for token_account in env.user_tokens.into_iter().chain([env.user_lp_token]) {
+        setup.push(approve_ix(&env, token_account, bot.pubkey()));
+    }
+    env.process(&setup).await.unwrap();
+    assert_eq!(env.position_state().await.delegate, Some(bot.pubkey()));
+
+    let lp_before = env.position_state().await.lp_amount;
+    let deposit = signed_by(
+        env.deposit_ix([10_000_000, 10_000_000, 10_000_000], 0),
+        bot.pubkey(),
+        DEPOSIT_DELEGATE_FROM_END,
+    );
+    env.process_signed(&[deposit], &[&bot]).await.unwrap();
+    let lp_after = env.position_state().await.lp_amount;
+    assert!(lp_after > lp_before);
+
+    let balance_before = env.token_balance(env.user_tokens[0]).await;
+    let withdraw = signed_by(
+        env.withdraw_ix(lp_after - lp_before, [0, 0, 0], WithdrawMode::Proportional),
+        bot.pubkey(),
+        WITHDRAW_DELEGATE_FROM_END,
+    );
+    env.process_signed(&[withdraw], &[&bot]).await.unwrap();
+    assert!(env.token_balance(env.user_tokens[0]).await > balance_before);
+}
+
+#[tokio::test]
+#[ignore = "needs the SBF program from `anchor build`"]
+async fn delegate_cannot_act_unregistered_or_redirect_proceeds() {
+    common::require_program();
+
+    let mut env = SeedPoolEnv::new([1_000_000_000, 1_000_000_000, 1_000_000_000]).await;
+    let bot = Keypair::new();
+
+    let deposit = signed_by(
+        env.deposit_ix([10_000_000, 10_000_000, 10_000_000], 0),
+        bot.pubkey(),
+        DEPOSIT_DELEGATE_FROM_END,
+    );
+    assert_program_error(
+        env.process_signed(&[deposit], &[&bot]).await,
+        ErrorCode::Unauthorized,
+    );
+
+    let set_delegate = set_delegate_ix(&env, Some(bot.pubkey()));
+    let approve = approve_ix(&env, env.user_lp_token, bot.pubkey());
+    env.process(&[set_delegate, approve]).await.unwrap();
+
+    // Any recipient override is refused when a delegate signs
+    let mut withdraw = signed_by(
+        env.withdraw_ix(1_000_000, [0, 0, 0], WithdrawMode::Proportional),
+        bot.pubkey(),
+        WITHDRAW_DELEGATE_FROM_END,
+    );
+    // recipient_token_a sits three slots ahead of the delegate
+    let count = withdraw.accounts.len();
+    withdraw.accounts[count - WITHDRAW_DELEGATE_FROM_END - 3] =
+        AccountMeta::new(env.user_tokens[0], false);
+    assert_program_error(
+        env.process_signed(&[withdraw], &[&bot]).await,
+        ErrorCode::Unauthorized,
+    );
+}
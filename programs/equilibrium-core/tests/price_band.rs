@@ -0,0 +1,52 @@
+//! Hard peg guard on Seed Pool swaps
+
+mod common;
+
+use common::{assert_program_error, SeedPoolEnv};
+use equilibrium_core::errors::ErrorCode;
+use equilibrium_core::instruction::UpdatePoolParams;
+use equilibrium_core::PoolParamChange;
+
+const RESERVE: u64 = 1_000_000_000;
+
+#[tokio::test]
+#[ignore = "needs the SBF program from `anchor build`"]
+async fn swaps_cannot_push_a_pair_past_the_band() {
+    common::require_program();
+
+    let mut env = SeedPoolEnv::new([RESERVE; 3]).await;
+    let too_wide = env.update_pool_ix(UpdatePoolParams {
+        changes: vec![PoolParamChange::PriceBand { band_bps: 10_001 }],
+    });
+    assert_program_error(
+        env.process(&[too_wide]).await,
+        ErrorCode::InvalidInstructionData,
+    );
+    let band = env.update_pool_ix(UpdatePoolParams {
+        changes: vec![PoolParamChange::PriceBand { band_bps: 50 }],
+    });
+    env.process(&[band]).await.unwrap();
+    assert_eq!(env.pool_state().await.price_band_bps, 50);
+
+    // Small trades near the peg are untouched
+    env.process(&[env.swap_ix(0, 1, RESERVE / 100, 0)])
+        .await
+        .unwrap();
+
+    // Dumping token 0 stops once its price would leave the band
+    let mut dumped = 0;
+    let result = loop {
+        let result = env.process(&[env.swap_ix(0, 1, RESERVE / 5, 0)]).await;
+        if result.is_err() || dumped == 20 {
+            break result;
+        }
+        dumped += 1;
+    };
+    assert!(dumped > 0);
+    assert_program_error(result, ErrorCode::PriceOutOfBounds);
+
+    // Buying token 0 back towards the peg still goes through
+    env.process(&[env.swap_ix(1, 0, RESERVE / 5, 0)])
+        .await
+        .unwrap();
+}
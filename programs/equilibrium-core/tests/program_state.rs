@@ -0,0 +1,160 @@
+//! Program version tracking and feature flags
+
+mod common;
+
+use anchor_lang::{InstructionData, ToAccountMetas};
+use common::{add_upgradeable_program, assert_program_error, SeedPoolEnv};
+use equilibrium_core::errors::ErrorCode;
+use equilibrium_core::{FEATURE_LIMIT_ORDERS, PROGRAM_VERSION};
+use solana_sdk::{
+    account::Account,
+    instruction::Instruction,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+};
+
+fn program_state() -> Pubkey {
+    Pubkey::find_program_address(&[b"program-state"], &equilibrium_core::id()).0
+}
+
+fn init_program_state_ix(authority: Pubkey, program_data: Pubkey) -> Instruction {
+    Instruction {
+        program_id: equilibrium_core::id(),
+        accounts: equilibrium_core::accounts::InitProgramState {
+            authority,
+            program: equilibrium_core::id(),
+            program_data,
+            program_state: program_state(),
+            system_program: solana_sdk::system_program::ID,
+        }
+        .to_account_metas(None),
+        data: equilibrium_core::instruction::InitProgramState {}.data(),
+    }
+}
+
+fn update_program_state_ix(
+    authority: Pubkey,
+    program_data: Pubkey,
+    data: impl InstructionData,
+) -> Instruction {
+    Instruction {
+        program_id: equilibrium_core::id(),
+        accounts: equilibrium_core::accounts::UpdateProgramState {
+            authority,
+            program: equilibrium_core::id(),
+            program_data,
+            program_state: program_state(),
+        }
+        .to_account_metas(None),
+        data: data.data(),
+    }
+}
+
+fn place_limit_order_ix(env: &SeedPoolEnv) -> Instruction {
+    let order_id = 1u64;
+    let (limit_order, _) = Pubkey::find_program_address(
+        &[
+            b"limit-order",
+            env.user().as_ref(),
+            env.pool.as_ref(),
+            &order_id.to_le_bytes(),
+        ],
+        &equilibrium_core::id(),
+    );
+    let (escrow, _) = Pubkey::find_program_address(
+        &[b"limit-order-escrow", limit_order.as_ref()],
+        &equilibrium_core::id(),
+    );
+    Instruction {
+        program_id: equilibrium_core::id(),
+        accounts: equilibrium_core::accounts::PlaceLimitOrder {
+            owner: env.user(),
+            program_state: program_state(),
+            pool: env.pool,
+            token_mint_in: env.mints[0],
+            token_mint_out: env.mints[1],
+            owner_token_in: env.user_tokens[0],
+            limit_order,
+            escrow,
+            token_program: spl_token::ID,
+            system_program: solana_sdk::system_program::ID,
+            rent: solana_sdk::sysvar::rent::ID,
+        }
+        .to_account_metas(None),
+        data: equilibrium_core::instruction::PlaceLimitOrder {
+            order_id,
+            amount_in: 1_000_000,
+            min_amount_out: 1_000_000,
+        }
+        .data(),
+    }
+}
+
+#[tokio::test]
+#[ignore = "needs the SBF program from `anchor build`"]
+async fn limit_orders_stay_dark_until_their_feature_is_enabled() {
+    common::require_program();
+
+    let upgrade_authority = Keypair::new();
+    let mut program_data = Pubkey::default();
+    let mut env = SeedPoolEnv::with_programs([1_000_000_000; 3], |program_test| {
+        program_data = add_upgradeable_program(program_test, &upgrade_authority.pubkey());
+        program_test.add_account(
+            upgrade_authority.pubkey(),
+            Account::new(1_000_000_000, 0, &solana_sdk::system_program::ID),
+        );
+    })
+    .await;
+    let authority = upgrade_authority.pubkey();
+
+    // Config authority is not enough to take over program-wide state
+    assert_program_error(
+        env.process(&[init_program_state_ix(env.user(), program_data)])
+            .await,
+        ErrorCode::Unauthorized,
+    );
+    env.process_signed(
+        &[init_program_state_ix(authority, program_data)],
+        &[&upgrade_authority],
+    )
+    .await
+    .unwrap();
+
+    let state: equilibrium_core::ProgramState = env.account_state(program_state()).await;
+    assert_eq!(state.version, PROGRAM_VERSION);
+    assert_eq!(state.upgrade_history.len(), 1);
+
+    // The running binary is already recorded
+    let record = update_program_state_ix(
+        authority,
+        program_data,
+        equilibrium_core::instruction::RecordUpgrade {},
+    );
+    assert_program_error(
+        env.process_signed(&[record], &[&upgrade_authority]).await,
+        ErrorCode::VersionNotIncreased,
+    );
+
+    assert_program_error(
+        env.process(&[place_limit_order_ix(&env)]).await,
+        ErrorCode::FeatureDisabled,
+    );
+
+    let enable = |authority| {
+        update_program_state_ix(
+            authority,
+            program_data,
+            equilibrium_core::instruction::SetFeatureFlags {
+                feature_flags: FEATURE_LIMIT_ORDERS,
+            },
+        )
+    };
+    assert_program_error(
+        env.process(&[enable(env.user())]).await,
+        ErrorCode::Unauthorized,
+    );
+    env.process_signed(&[enable(authority)], &[&upgrade_authority])
+        .await
+        .unwrap();
+    env.process(&[place_limit_order_ix(&env)]).await.unwrap();
+}
@@ -0,0 +1,73 @@
+//! Config-wide protocol share of swap fees and per-pool overrides
+
+mod common;
+
+use common::{assert_program_error, SeedPoolEnv};
+use equilibrium_core::errors::ErrorCode;
+use equilibrium_core::events::ConfigUpdated;
+use equilibrium_core::instruction::{
+    SetConfigProtocolFeeShare, SetProtocolFeeShare, UpdatePoolParams,
+};
+use equilibrium_core::PoolParamChange;
+
+const RESERVE: u64 = 1_000_000_000;
+
+#[tokio::test]
+#[ignore = "needs the SBF program from `anchor build`"]
+async fn pools_split_fees_at_the_config_share_unless_overridden() {
+    common::require_program();
+
+    let mut env = SeedPoolEnv::new([RESERVE; 3]).await;
+    assert!(env.pool_state().await.inherits_protocol_fee_share);
+
+    // The switch starts off: LPs keep the whole fee
+    env.process(&[env.swap_ix(0, 1, 10_000_000, 0)])
+        .await
+        .unwrap();
+    assert_eq!(env.pool_state().await.protocol_fees_owed[0], 0);
+
+    let too_high = env.update_config_ix(SetConfigProtocolFeeShare {
+        protocol_fee_share_bps: 5_001,
+    });
+    assert_program_error(env.process(&[too_high]).await, ErrorCode::InvalidFee);
+
+    let set_share = env.update_config_ix(SetConfigProtocolFeeShare {
+        protocol_fee_share_bps: 2_000,
+    });
+    let events = env
+        .process_events::<ConfigUpdated>(&[set_share])
+        .await
+        .unwrap();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].amm_config, env.amm_config);
+    assert_eq!(events[0].old_protocol_fee_share_bps, 0);
+    assert_eq!(events[0].new_protocol_fee_share_bps, 2_000);
+    assert_eq!(env.config_state().await.protocol_fee_share_bps, 2_000);
+
+    env.process(&[env.swap_ix(0, 1, 10_000_000, 0)])
+        .await
+        .unwrap();
+    let owed = env.pool_state().await.protocol_fees_owed[0];
+    assert!(owed > 0);
+
+    // A per-pool share overrides the config's...
+    let override_share = env.update_pool_ix(SetProtocolFeeShare {
+        protocol_fee_share_bps: 0,
+    });
+    env.process(&[override_share]).await.unwrap();
+    assert!(!env.pool_state().await.inherits_protocol_fee_share);
+    env.process(&[env.swap_ix(0, 1, 10_000_000, 0)])
+        .await
+        .unwrap();
+    assert_eq!(env.pool_state().await.protocol_fees_owed[0], owed);
+
+    // ...until the pool is pointed back at it
+    let inherit = env.update_pool_ix(UpdatePoolParams {
+        changes: vec![PoolParamChange::InheritProtocolFeeShare],
+    });
+    env.process(&[inherit]).await.unwrap();
+    env.process(&[env.swap_ix(0, 1, 10_000_000, 0)])
+        .await
+        .unwrap();
+    assert!(env.pool_state().await.protocol_fees_owed[0] > owed);
+}
@@ -0,0 +1,60 @@
+//! Delivering swap output and withdrawal proceeds to a third-party wallet
+
+mod common;
+
+use common::{create_ata, SeedPoolEnv};
+use equilibrium_core::WithdrawMode;
+use solana_sdk::pubkey::Pubkey;
+
+#[tokio::test]
+#[ignore = "needs the SBF program from `anchor build`"]
+async fn swap_output_goes_to_recipient() {
+    common::require_program();
+
+    let mut env = SeedPoolEnv::new([1_000_000_000, 1_000_000_000, 1_000_000_000]).await;
+    let payee = Pubkey::new_unique();
+    let payee_token = create_ata(&mut env.context, &env.mints[1], &payee).await;
+    let user_balance_before = env.token_balance(env.user_tokens[1]).await;
+
+    // The recipient precedes the vote escrow and pool snapshot, `None` encodes as the program id
+    let mut swap = env.swap_ix(0, 1, 1_000_000, 0);
+    let count = swap.accounts.len();
+    let recipient_meta = &mut swap.accounts[count - 3];
+    recipient_meta.pubkey = payee_token;
+    recipient_meta.is_writable = true;
+    env.process(&[swap]).await.unwrap();
+
+    assert!(env.token_balance(payee_token).await > 0);
+    assert_eq!(
+        env.token_balance(env.user_tokens[1]).await,
+        user_balance_before
+    );
+}
+
+#[tokio::test]
+#[ignore = "needs the SBF program from `anchor build`"]
+async fn withdraw_proceeds_go_to_recipients() {
+    common::require_program();
+
+    let mut env = SeedPoolEnv::new([1_000_000_000, 1_000_000_000, 1_000_000_000]).await;
+    let payee = Pubkey::new_unique();
+    let mut payee_tokens = [Pubkey::default(); 3];
+    for (payee_token, mint) in payee_tokens.iter_mut().zip(env.mints) {
+        *payee_token = create_ata(&mut env.context, &mint, &payee).await;
+    }
+
+    // Recipient accounts precede the delegate, vote escrow, fee exemption and pool
+    // snapshot, `None` encodes as the program id
+    let mut withdraw = env.withdraw_ix(1_000_000, [0, 0, 0], WithdrawMode::Proportional);
+    let count = withdraw.accounts.len();
+    let recipients = &mut withdraw.accounts[count - 7..count - 4];
+    for (meta, payee_token) in recipients.iter_mut().zip(payee_tokens) {
+        meta.pubkey = payee_token;
+        meta.is_writable = true;
+    }
+    env.process(&[withdraw]).await.unwrap();
+
+    for payee_token in payee_tokens {
+        assert!(env.token_balance(payee_token).await > 0);
+    }
+}
@@ -0,0 +1,166 @@
+//! Referral fees accrued on the swap path and claimed in batches
+
+mod common;
+
+use anchor_lang::{InstructionData, ToAccountMetas};
+use common::{assert_program_error, create_ata, SeedPoolEnv};
+use equilibrium_core::errors::ErrorCode;
+use equilibrium_core::ReferralAccount;
+use solana_sdk::{
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+};
+
+const RESERVE: u64 = 1_000_000_000;
+const AMOUNT: u64 = 1_000_000;
+const FEE_BPS: u64 = 50;
+
+fn referral_account(env: &SeedPoolEnv, token: usize) -> Pubkey {
+    Pubkey::find_program_address(
+        &[
+            b"referral",
+            env.pool.as_ref(),
+            env.mints[token].as_ref(),
+            env.user().as_ref(),
+        ],
+        &equilibrium_core::id(),
+    )
+    .0
+}
+
+fn referral_vault(env: &SeedPoolEnv, token: usize) -> Pubkey {
+    Pubkey::find_program_address(
+        &[b"referral-vault", referral_account(env, token).as_ref()],
+        &equilibrium_core::id(),
+    )
+    .0
+}
+
+/// Fill the swap's trailing referral account and vault slots
+fn refer(swap: &mut Instruction, referral_account: Pubkey, referral_vault: Pubkey) {
+    let len = swap.accounts.len();
+    swap.accounts[len - 2] = AccountMeta::new(referral_account, false);
+    swap.accounts[len - 1] = AccountMeta::new(referral_vault, false);
+}
+
+/// A swap of `AMOUNT` of token 0 for token 1 accruing `FEE_BPS` to the user's referral account
+fn referred_swap_ix(env: &SeedPoolEnv) -> Instruction {
+    let mut swap = env.swap_ix(0, 1, AMOUNT, 0);
+    refer(&mut swap, referral_account(env, 0), referral_vault(env, 0));
+    swap.data = equilibrium_core::instruction::Swap {
+        amount_in: AMOUNT,
+        min_amount_out: 0,
+        deadline: i64::MAX,
+        integrator_fee_bps: FEE_BPS,
+        max_price_impact_bps: None,
+    }
+    .data();
+    swap
+}
+
+fn claim_ix(env: &SeedPoolEnv, destination: Pubkey) -> Instruction {
+    Instruction {
+        program_id: equilibrium_core::id(),
+        accounts: equilibrium_core::accounts::ClaimReferralFees {
+            referrer: env.user(),
+            pool: env.pool,
+            token_mint: env.mints[0],
+            referral_account: referral_account(env, 0),
+            vault: referral_vault(env, 0),
+            destination,
+            token_program: spl_token::ID,
+        }
+        .to_account_metas(None),
+        data: equilibrium_core::instruction::ClaimReferralFees {}.data(),
+    }
+}
+
+#[tokio::test]
+#[ignore = "needs the SBF program from `anchor build`"]
+async fn referral_fees_accrue_in_the_vault_until_claimed() {
+    common::require_program();
+
+    let mut env = SeedPoolEnv::new([RESERVE; 3]).await;
+    let register = Instruction {
+        program_id: equilibrium_core::id(),
+        accounts: equilibrium_core::accounts::RegisterReferral {
+            referrer: env.user(),
+            pool: env.pool,
+            token_mint: env.mints[0],
+            referral_account: referral_account(&env, 0),
+            vault: referral_vault(&env, 0),
+            token_program: spl_token::ID,
+            system_program: solana_sdk::system_program::ID,
+        }
+        .to_account_metas(None),
+        data: equilibrium_core::instruction::RegisterReferral {}.data(),
+    };
+    env.process(&[register]).await.unwrap();
+
+    // A referral account only takes fees in its own token, into its own vault
+    let mut wrong_token = env.swap_ix(1, 0, AMOUNT, 0);
+    refer(
+        &mut wrong_token,
+        referral_account(&env, 0),
+        referral_vault(&env, 0),
+    );
+    assert_program_error(
+        env.process(&[wrong_token]).await,
+        ErrorCode::InvalidTokenMint,
+    );
+    let mut wrong_vault = referred_swap_ix(&env);
+    refer(
+        &mut wrong_vault,
+        referral_account(&env, 0),
+        env.pool_tokens[0],
+    );
+    assert_program_error(env.process(&[wrong_vault]).await, ErrorCode::VaultMismatch);
+
+    // The fee goes to the referral vault and never touches the pool's
+    let user_before = env.token_balance(env.user_tokens[0]).await;
+    let vault_before = env.token_balance(env.pool_tokens[0]).await;
+    let reserve_before = env.pool_state().await.reserves[0];
+    env.process(&[referred_swap_ix(&env)]).await.unwrap();
+    env.advance_slots(1).await;
+    env.process(&[referred_swap_ix(&env)]).await.unwrap();
+
+    let fee = AMOUNT * FEE_BPS / 10_000;
+    let referral: ReferralAccount = env.account_state(referral_account(&env, 0)).await;
+    assert_eq!(referral.fees_owed, 2 * fee);
+    assert_eq!(referral.lifetime_fees, 2 * fee as u128);
+    assert_eq!(
+        user_before - env.token_balance(env.user_tokens[0]).await,
+        2 * (AMOUNT + fee)
+    );
+    assert_eq!(
+        env.pool_state().await.reserves[0],
+        reserve_before + 2 * AMOUNT
+    );
+    assert_eq!(
+        env.token_balance(env.pool_tokens[0]).await,
+        vault_before + 2 * AMOUNT
+    );
+    assert_eq!(env.token_balance(referral_vault(&env, 0)).await, 2 * fee);
+
+    // Both ways of paying the front end at once is ambiguous
+    let fee_account = create_ata(&mut env.context, &env.mints[0], &Pubkey::new_unique()).await;
+    let mut both = referred_swap_ix(&env);
+    let index = both.accounts.len() - 3;
+    both.accounts[index] = AccountMeta::new(fee_account, false);
+    assert_program_error(
+        env.process(&[both]).await,
+        ErrorCode::ConflictingIntegratorFeeAccounts,
+    );
+
+    // One claim pays out everything accrued
+    env.process(&[claim_ix(&env, fee_account)]).await.unwrap();
+    assert_eq!(env.token_balance(fee_account).await, 2 * fee);
+    let referral: ReferralAccount = env.account_state(referral_account(&env, 0)).await;
+    assert_eq!(referral.fees_owed, 0);
+    assert_eq!(env.token_balance(referral_vault(&env, 0)).await, 0);
+    env.advance_slots(1).await;
+    assert_program_error(
+        env.process(&[claim_ix(&env, fee_account)]).await,
+        ErrorCode::NoReferralFeesOwed,
+    );
+}
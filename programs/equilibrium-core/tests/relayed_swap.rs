@@ -0,0 +1,197 @@
+//! Gasless swaps: a trusted relayer submits orders users signed off-chain
+
+mod common;
+
+use anchor_lang::{AnchorSerialize, InstructionData, ToAccountMetas};
+use common::{assert_program_error, SeedPoolEnv};
+use equilibrium_core::errors::ErrorCode;
+use equilibrium_core::{RelayNonce, RelayedSwapOrder};
+use solana_sdk::{
+    ed25519_program,
+    instruction::Instruction,
+    native_token::LAMPORTS_PER_SOL,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    system_instruction, system_program, sysvar,
+};
+
+const RESERVE: u64 = 1_000_000_000;
+const AMOUNT_IN: u64 = 10_000_000;
+
+fn pda(seeds: &[&[u8]]) -> Pubkey {
+    Pubkey::find_program_address(seeds, &equilibrium_core::id()).0
+}
+
+fn order(env: &SeedPoolEnv, nonce: u64) -> RelayedSwapOrder {
+    RelayedSwapOrder {
+        pool: env.pool,
+        user: env.user(),
+        token_mint_in: env.mints[0],
+        token_mint_out: env.mints[1],
+        amount_in: AMOUNT_IN,
+        min_amount_out: AMOUNT_IN * 99 / 100,
+        deadline: i64::MAX,
+        nonce,
+    }
+}
+
+/// Ed25519 program instruction verifying `signer`'s signature over `message`,
+/// with the key, signature and message all inline
+fn ed25519_ix(signer: &Keypair, message: &[u8]) -> Instruction {
+    const PUBLIC_KEY_OFFSET: u16 = 16;
+    const SIGNATURE_OFFSET: u16 = PUBLIC_KEY_OFFSET + 32;
+    const MESSAGE_OFFSET: u16 = SIGNATURE_OFFSET + 64;
+
+    let mut data = vec![1, 0];
+    for field in [
+        SIGNATURE_OFFSET,
+        u16::MAX,
+        PUBLIC_KEY_OFFSET,
+        u16::MAX,
+        MESSAGE_OFFSET,
+        message.len() as u16,
+        u16::MAX,
+    ] {
+        data.extend_from_slice(&field.to_le_bytes());
+    }
+    data.extend_from_slice(signer.pubkey().as_ref());
+    data.extend_from_slice(signer.sign_message(message).as_ref());
+    data.extend_from_slice(message);
+    Instruction {
+        program_id: ed25519_program::ID,
+        accounts: vec![],
+        data,
+    }
+}
+
+fn relayed_swap_ix(env: &SeedPoolEnv, relayer: &Pubkey, order: RelayedSwapOrder) -> Instruction {
+    Instruction {
+        program_id: equilibrium_core::id(),
+        accounts: equilibrium_core::accounts::RelayedSwap {
+            relayer: *relayer,
+            user: order.user,
+            amm_config: env.amm_config,
+            pool: env.pool,
+            relay_authority: pda(&[b"relay-authority", env.pool.as_ref()]),
+            token_mint_in: env.mints[0],
+            token_mint_out: env.mints[1],
+            user_token_in: env.user_tokens[0],
+            user_token_out: env.user_tokens[1],
+            pool_token_in: env.pool_tokens[0],
+            pool_token_out: env.pool_tokens[1],
+            relay_nonce: relay_nonce_address(env),
+            instructions: sysvar::instructions::ID,
+            token_program: spl_token::ID,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: equilibrium_core::instruction::RelayedSwap { order }.data(),
+    }
+}
+
+fn relay_nonce_address(env: &SeedPoolEnv) -> Pubkey {
+    pda(&[b"relay-nonce", env.pool.as_ref(), env.user().as_ref()])
+}
+
+/// The order signed by the user, as the relayer submits it
+fn signed_order(env: &SeedPoolEnv, relayer: &Pubkey, order: RelayedSwapOrder) -> [Instruction; 2] {
+    [
+        ed25519_ix(&env.context.payer, &order.try_to_vec().unwrap()),
+        relayed_swap_ix(env, relayer, order),
+    ]
+}
+
+/// Trust a funded relayer on the Seed Pool and let it spend the user's USDC
+async fn setup() -> (SeedPoolEnv, Keypair) {
+    let mut env = SeedPoolEnv::new([RESERVE; 3]).await;
+    let relayer = Keypair::new();
+    let fund = system_instruction::transfer(&env.user(), &relayer.pubkey(), LAMPORTS_PER_SOL);
+    let set_relayer = env.update_pool_ix(equilibrium_core::instruction::SetRelayer {
+        relayer: relayer.pubkey(),
+    });
+    let approve = spl_token::instruction::approve(
+        &spl_token::ID,
+        &env.user_tokens[0],
+        &pda(&[b"relay-authority", env.pool.as_ref()]),
+        &env.user(),
+        &[],
+        u64::MAX,
+    )
+    .unwrap();
+    env.process(&[fund, set_relayer, approve]).await.unwrap();
+    (env, relayer)
+}
+
+#[tokio::test]
+#[ignore = "needs the SBF program from `anchor build`"]
+async fn relayer_executes_each_signed_order_once() {
+    common::require_program();
+
+    let (mut env, relayer) = setup().await;
+    let before = env.token_balance(env.user_tokens[1]).await;
+    env.process_signed(
+        &signed_order(&env, &relayer.pubkey(), order(&env, 0)),
+        &[&relayer],
+    )
+    .await
+    .unwrap();
+    assert!(env.token_balance(env.user_tokens[1]).await - before >= AMOUNT_IN * 99 / 100);
+    let relay_nonce = env
+        .account_state::<RelayNonce>(relay_nonce_address(&env))
+        .await;
+    assert_eq!(relay_nonce.next_nonce, 1);
+
+    // Replaying the same order fails, the next nonce goes through
+    assert_program_error(
+        env.process_signed(
+            &signed_order(&env, &relayer.pubkey(), order(&env, 0)),
+            &[&relayer],
+        )
+        .await,
+        ErrorCode::InvalidRelayedOrder,
+    );
+    env.process_signed(
+        &signed_order(&env, &relayer.pubkey(), order(&env, 1)),
+        &[&relayer],
+    )
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+#[ignore = "needs the SBF program from `anchor build`"]
+async fn relayed_orders_need_the_users_signature_and_the_pool_relayer() {
+    common::require_program();
+
+    let (mut env, relayer) = setup().await;
+
+    // No signature verification ahead of the swap
+    let unsigned = relayed_swap_ix(&env, &relayer.pubkey(), order(&env, 0));
+    assert_program_error(
+        env.process_signed(&[unsigned], &[&relayer]).await,
+        ErrorCode::InvalidOrderSignature,
+    );
+
+    // The relayer cannot raise the amount the user signed for
+    let [verify, _] = signed_order(&env, &relayer.pubkey(), order(&env, 0));
+    let inflated = RelayedSwapOrder {
+        amount_in: AMOUNT_IN * 10,
+        ..order(&env, 0)
+    };
+    let swap = relayed_swap_ix(&env, &relayer.pubkey(), inflated);
+    assert_program_error(
+        env.process_signed(&[verify, swap], &[&relayer]).await,
+        ErrorCode::InvalidOrderSignature,
+    );
+
+    // Nor can anyone but the pool's relayer submit the order
+    let stranger = Keypair::new();
+    assert_program_error(
+        env.process_signed(
+            &signed_order(&env, &stranger.pubkey(), order(&env, 0)),
+            &[&stranger],
+        )
+        .await,
+        ErrorCode::Unauthorized,
+    );
+}
@@ -0,0 +1,215 @@
+//! Growth pools whose LP lives only in positions
+
+mod common;
+
+use anchor_lang::{InstructionData, ToAccountMetas};
+use common::{
+    assert_program_error, create_ata, create_growth_pool_ix, create_mint, mint_to, GrowthPool,
+    SeedPoolEnv,
+};
+use equilibrium_core::errors::ErrorCode;
+use equilibrium_core::{Pool, UserPosition, WithdrawMode};
+use solana_sdk::{
+    instruction::Instruction,
+    program_pack::Pack,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    system_program, sysvar,
+};
+
+const RESERVE: u64 = 1_000_000_000;
+
+fn position(owner: &Pubkey, pool: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[b"user-position", owner.as_ref(), pool.as_ref()],
+        &equilibrium_core::id(),
+    )
+    .0
+}
+
+/// Create a soulbound Growth pool pairing USD* with a new partner token
+async fn create_soulbound_pool(env: &mut SeedPoolEnv) -> GrowthPool {
+    let user = env.user();
+    let partner_mint = create_mint(&mut env.context).await;
+    let user_partner_token = create_ata(&mut env.context, &partner_mint, &user).await;
+    mint_to(
+        &mut env.context,
+        &partner_mint,
+        &user_partner_token,
+        10 * RESERVE,
+    )
+    .await;
+
+    let (create, growth) = create_growth_pool_ix(
+        env,
+        [env.lp_mint, partner_mint],
+        [env.user_lp_token, user_partner_token],
+        RESERVE,
+        true,
+    );
+    env.process(&[create]).await.unwrap();
+    growth
+}
+
+fn deposit_ix(env: &SeedPoolEnv, growth: &GrowthPool, amount: u64) -> Instruction {
+    Instruction {
+        program_id: equilibrium_core::id(),
+        accounts: equilibrium_core::accounts::Deposit {
+            user: env.user(),
+            pool: growth.pool,
+            lp_mint: growth.lp_mint,
+            user_lp_token: growth.user_lp_token,
+            user_token_a: growth.user_tokens[0],
+            user_token_b: growth.user_tokens[1],
+            user_token_c: None,
+            token_mint_a: growth.mints[0],
+            token_mint_b: growth.mints[1],
+            token_mint_c: None,
+            pool_token_a: growth.pool_tokens[0],
+            pool_token_b: growth.pool_tokens[1],
+            pool_token_c: None,
+            user_position: position(&env.user(), &growth.pool),
+            token_program: spl_token::ID,
+            system_program: system_program::ID,
+            rent: sysvar::rent::ID,
+            delegate: None,
+            pool_snapshot: None,
+            credential: None,
+            user_portfolio: None,
+        }
+        .to_account_metas(None),
+        data: equilibrium_core::instruction::Deposit {
+            amounts: [amount, amount, 0],
+            min_lp_amount: 0,
+            concentration: 1000,
+            deadline: i64::MAX,
+        }
+        .data(),
+    }
+}
+
+fn withdraw_ix(env: &SeedPoolEnv, growth: &GrowthPool, lp_amount: u64) -> Instruction {
+    Instruction {
+        program_id: equilibrium_core::id(),
+        accounts: equilibrium_core::accounts::Withdraw {
+            user: env.user(),
+            amm_config: env.amm_config,
+            pool: growth.pool,
+            lp_mint: growth.lp_mint,
+            user_lp_token: growth.user_lp_token,
+            user_token_a: growth.user_tokens[0],
+            user_token_b: growth.user_tokens[1],
+            user_token_c: None,
+            token_mint_a: growth.mints[0],
+            token_mint_b: growth.mints[1],
+            token_mint_c: None,
+            pool_token_a: growth.pool_tokens[0],
+            pool_token_b: growth.pool_tokens[1],
+            pool_token_c: None,
+            user_position: position(&env.user(), &growth.pool),
+            token_program: spl_token::ID,
+            recipient_token_a: None,
+            recipient_token_b: None,
+            recipient_token_c: None,
+            delegate: None,
+            vote_escrow: None,
+            fee_exemption: None,
+            pool_snapshot: None,
+            user_portfolio: None,
+        }
+        .to_account_metas(None),
+        data: equilibrium_core::instruction::Withdraw {
+            lp_amount,
+            min_amounts: [0; 3],
+            mode: WithdrawMode::Proportional,
+            deadline: i64::MAX,
+        }
+        .data(),
+    }
+}
+
+async fn lp_mint_supply(env: &mut SeedPoolEnv, lp_mint: Pubkey) -> u64 {
+    let account = env.account(lp_mint).await;
+    spl_token::state::Mint::unpack(&account.data)
+        .unwrap()
+        .supply
+}
+
+#[tokio::test]
+#[ignore = "needs the SBF program from `anchor build`"]
+async fn soulbound_pool_keeps_lp_on_the_position_ledger() {
+    common::require_program();
+
+    let mut env = SeedPoolEnv::new([RESERVE; 3]).await;
+    let growth = create_soulbound_pool(&mut env).await;
+    let creator_position = position(&env.user(), &growth.pool);
+
+    // The creator's initial LP is booked on their position, and no LP token exists
+    let pool = env.account_state::<Pool>(growth.pool).await;
+    let initial = env.account_state::<UserPosition>(creator_position).await;
+    assert!(pool.soulbound_lp);
+    assert!(initial.lp_amount > 0 && initial.is_active);
+    assert_eq!(pool.internal_lp_supply, initial.lp_amount);
+    assert_eq!(lp_mint_supply(&mut env, growth.lp_mint).await, 0);
+
+    // Deposits credit the position and the internal supply only
+    env.process(&[deposit_ix(&env, &growth, RESERVE / 10)])
+        .await
+        .unwrap();
+    let deposited = env.account_state::<UserPosition>(creator_position).await;
+    let minted = deposited.lp_amount - initial.lp_amount;
+    assert!(minted > 0);
+    let pool = env.account_state::<Pool>(growth.pool).await;
+    assert_eq!(pool.internal_lp_supply, deposited.lp_amount);
+    assert_eq!(env.token_balance(growth.user_lp_token).await, 0);
+
+    // Withdrawals are priced and debited against the ledger
+    let partner_before = env.token_balance(growth.user_tokens[1]).await;
+    env.process(&[withdraw_ix(&env, &growth, minted)])
+        .await
+        .unwrap();
+    let withdrawn = env.account_state::<UserPosition>(creator_position).await;
+    assert_eq!(withdrawn.lp_amount, initial.lp_amount);
+    let pool = env.account_state::<Pool>(growth.pool).await;
+    assert_eq!(pool.internal_lp_supply, initial.lp_amount);
+    let paid = env.token_balance(growth.user_tokens[1]).await - partner_before;
+    assert!(paid > 0 && paid <= RESERVE / 10);
+
+    // More LP than the position holds cannot be withdrawn
+    assert_program_error(
+        env.process(&[withdraw_ix(&env, &growth, initial.lp_amount + 1)])
+            .await,
+        ErrorCode::InsufficientLiquidity,
+    );
+}
+
+#[tokio::test]
+#[ignore = "needs the SBF program from `anchor build`"]
+async fn soulbound_positions_cannot_be_transferred() {
+    common::require_program();
+
+    let mut env = SeedPoolEnv::new([RESERVE; 3]).await;
+    let growth = create_soulbound_pool(&mut env).await;
+
+    let recipient = Keypair::new().pubkey();
+    let recipient_lp_token = create_ata(&mut env.context, &growth.lp_mint, &recipient).await;
+    let transfer = Instruction {
+        program_id: equilibrium_core::id(),
+        accounts: equilibrium_core::accounts::TransferPosition {
+            owner: env.user(),
+            recipient,
+            pool: growth.pool,
+            lp_mint: growth.lp_mint,
+            owner_lp_token: growth.user_lp_token,
+            recipient_lp_token,
+            owner_position: position(&env.user(), &growth.pool),
+            recipient_position: position(&recipient, &growth.pool),
+            token_program: spl_token::ID,
+            system_program: system_program::ID,
+            credential: None,
+        }
+        .to_account_metas(None),
+        data: equilibrium_core::instruction::TransferPosition { amount: 1 }.data(),
+    };
+    assert_program_error(env.process(&[transfer]).await, ErrorCode::LpNotTransferable);
+}
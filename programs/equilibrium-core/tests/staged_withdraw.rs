@@ -0,0 +1,100 @@
+//! Two-step staged withdrawals for large exits
+
+mod common;
+
+use anchor_lang::{InstructionData, ToAccountMetas};
+use common::{assert_program_error, SeedPoolEnv};
+use equilibrium_core::errors::ErrorCode;
+use equilibrium_core::instruction::{SetExitFee, SetStagedWithdrawConfig};
+use equilibrium_core::WithdrawMode;
+use solana_sdk::{
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+};
+
+fn staged_withdrawal(env: &SeedPoolEnv) -> Pubkey {
+    Pubkey::find_program_address(
+        &[b"staged-withdraw", env.user_position.as_ref()],
+        &equilibrium_core::id(),
+    )
+    .0
+}
+
+fn request_ix(env: &SeedPoolEnv, lp_amount: u64) -> Instruction {
+    Instruction {
+        program_id: equilibrium_core::id(),
+        accounts: equilibrium_core::accounts::RequestWithdraw {
+            user: env.user(),
+            pool: env.pool,
+            lp_mint: env.lp_mint,
+            user_position: env.user_position,
+            staged_withdrawal: staged_withdrawal(env),
+            system_program: solana_sdk::system_program::ID,
+        }
+        .to_account_metas(None),
+        data: equilibrium_core::instruction::RequestWithdraw { lp_amount }.data(),
+    }
+}
+
+/// `ExecuteWithdraw` nests the `Withdraw` accounts ahead of the staged withdrawal
+fn execute_ix(env: &SeedPoolEnv) -> Instruction {
+    let mut accounts = env
+        .withdraw_ix(0, [0; 3], WithdrawMode::Proportional)
+        .accounts;
+    accounts.push(AccountMeta::new(staged_withdrawal(env), false));
+    Instruction {
+        program_id: equilibrium_core::id(),
+        accounts,
+        data: equilibrium_core::instruction::ExecuteWithdraw {
+            min_amounts: [0; 3],
+            deadline: i64::MAX,
+        }
+        .data(),
+    }
+}
+
+#[tokio::test]
+#[ignore = "needs the SBF program from `anchor build`"]
+async fn large_exit_executes_after_the_delay_without_exit_fee() {
+    common::require_program();
+
+    let mut env = SeedPoolEnv::new([1_000_000_000, 1_000_000_000, 1_000_000_000]).await;
+    let configure = env.update_pool_ix(SetStagedWithdrawConfig {
+        threshold_bps: 2_000,
+        delay: 3_600,
+    });
+    let set_exit_fee = env.update_pool_ix(SetExitFee { exit_fee_bps: 100 });
+    env.process(&[configure, set_exit_fee]).await.unwrap();
+
+    // 10% of the LP supply is below the staging threshold
+    let lp_supply = 3_000_000_000;
+    assert_program_error(
+        env.process(&[request_ix(&env, lp_supply / 10)]).await,
+        ErrorCode::StagedWithdrawNotAllowed,
+    );
+
+    let lp_amount = lp_supply / 2;
+    env.process(&[request_ix(&env, lp_amount)]).await.unwrap();
+    assert_program_error(
+        env.process(&[execute_ix(&env)]).await,
+        ErrorCode::StagedWithdrawNotReady,
+    );
+
+    env.advance_clock(3_600).await;
+    let balance_before = env.token_balance(env.user_tokens[0]).await;
+    env.process(&[execute_ix(&env)]).await.unwrap();
+
+    // A straight pro-rata half of the reserve, no exit fee withheld
+    assert_eq!(
+        env.token_balance(env.user_tokens[0]).await - balance_before,
+        500_000_000
+    );
+    assert_eq!(env.position_state().await.lp_amount, lp_supply - lp_amount);
+    assert!(env
+        .context
+        .banks_client
+        .get_account(staged_withdrawal(&env))
+        .await
+        .unwrap()
+        .is_none());
+}
@@ -0,0 +1,148 @@
+//! Selling a basket of Seed Pool tokens for one in a single invariant solve
+
+mod common;
+
+use anchor_lang::{InstructionData, ToAccountMetas};
+use common::{assert_program_error, SeedPoolEnv};
+use equilibrium_core::errors::ErrorCode;
+use equilibrium_core::instruction::SetTokenFrozen;
+use solana_sdk::{instruction::Instruction, system_program};
+
+const RESERVE: u64 = 1_000_000_000_000;
+const AMOUNT: u64 = 10_000_000_000;
+
+/// `SwapBasket` over `env`'s pool; a two-amount basket leaves out the third token's accounts
+fn swap_basket_ix(
+    env: &SeedPoolEnv,
+    amounts_in: Vec<u64>,
+    token_out_index: u8,
+    min_amount_out: u64,
+) -> Instruction {
+    let third = (amounts_in.len() != 2).then_some(2);
+    Instruction {
+        program_id: equilibrium_core::id(),
+        accounts: equilibrium_core::accounts::SwapBasket {
+            user: env.user(),
+            amm_config: env.amm_config,
+            pool: env.pool,
+            token_mint_a: env.mints[0],
+            token_mint_b: env.mints[1],
+            token_mint_c: third.map(|i| env.mints[i]),
+            user_token_a: env.user_tokens[0],
+            user_token_b: env.user_tokens[1],
+            user_token_c: third.map(|i| env.user_tokens[i]),
+            pool_token_a: env.pool_tokens[0],
+            pool_token_b: env.pool_tokens[1],
+            pool_token_c: third.map(|i| env.pool_tokens[i]),
+            token_program: spl_token::ID,
+            vote_escrow: None,
+            pool_snapshot: None,
+            credential: None,
+        }
+        .to_account_metas(None),
+        data: equilibrium_core::instruction::SwapBasket {
+            amounts_in,
+            token_out_index,
+            min_amount_out,
+            deadline: i64::MAX,
+        }
+        .data(),
+    }
+}
+
+#[tokio::test]
+#[ignore = "needs the SBF program from `anchor build`"]
+async fn basket_of_two_tokens_buys_the_third_in_one_swap() {
+    common::require_program();
+
+    let mut env = SeedPoolEnv::new([RESERVE; 3]).await;
+    let before = env.pool_state().await;
+    let balance_before = env.token_balance(env.user_tokens[2]).await;
+
+    // Asking for more than the inputs are worth reverts
+    let swap = swap_basket_ix(&env, vec![AMOUNT, AMOUNT, 0], 2, 2 * AMOUNT);
+    assert_program_error(env.process(&[swap]).await, ErrorCode::SlippageExceeded);
+
+    let swap = swap_basket_ix(&env, vec![AMOUNT, AMOUNT, 0], 2, 0);
+    env.process(&[swap]).await.unwrap();
+    let amount_out = env.token_balance(env.user_tokens[2]).await - balance_before;
+    assert!(amount_out < 2 * AMOUNT && amount_out > 2 * AMOUNT * 99 / 100);
+
+    // Both inputs land in the reserves, less the protocol's share of their fees
+    let after = env.pool_state().await;
+    for i in 0..2 {
+        assert_eq!(
+            after.reserves[i] + after.protocol_fees_owed[i],
+            before.reserves[i] + AMOUNT
+        );
+        assert!(after.lifetime_fees_per_token[i] > 0);
+    }
+    assert_eq!(after.reserves[2], before.reserves[2] - amount_out);
+    assert_eq!(after.swap_count, before.swap_count + 1);
+}
+
+#[tokio::test]
+#[ignore = "needs the SBF program from `anchor build`"]
+async fn basket_rejects_selling_the_token_it_buys() {
+    common::require_program();
+
+    let mut env = SeedPoolEnv::new([RESERVE; 3]).await;
+
+    let swap = swap_basket_ix(&env, vec![AMOUNT, 0, AMOUNT], 2, 0);
+    assert_program_error(env.process(&[swap]).await, ErrorCode::InvalidSwap);
+
+    // One amount per pool token
+    let swap = swap_basket_ix(&env, vec![AMOUNT, 0], 2, 0);
+    assert_program_error(
+        env.process(&[swap]).await,
+        ErrorCode::InvalidInstructionData,
+    );
+
+    // An empty basket is below any minimum swap
+    let swap = swap_basket_ix(&env, vec![0, 0, 0], 2, 0);
+    assert_program_error(env.process(&[swap]).await, ErrorCode::MinSwapAmount);
+}
+
+#[tokio::test]
+#[ignore = "needs the SBF program from `anchor build`"]
+async fn basket_trades_a_two_token_pool_without_third_accounts() {
+    common::require_program();
+
+    // Retire the third token so the Seed pool is down to a pair
+    let mut env = SeedPoolEnv::new([RESERVE; 3]).await;
+    env.drain_token(2).await;
+    let freeze = env.update_pool_ix(SetTokenFrozen {
+        token_mint: env.mints[2],
+        frozen: true,
+    });
+    let close = Instruction {
+        program_id: equilibrium_core::id(),
+        accounts: equilibrium_core::accounts::ClosePoolVault {
+            authority: env.user(),
+            amm_config: env.amm_config,
+            pool: env.pool,
+            lp_mint: env.lp_mint,
+            vault: env.pool_tokens[2],
+            token_program: spl_token::ID,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: equilibrium_core::instruction::ClosePoolVault { token_index: 2 }.data(),
+    };
+    env.process(&[freeze, close]).await.unwrap();
+    let before = env.pool_state().await;
+    assert_eq!(before.token_mints.len(), 2);
+
+    let balance_before = env.token_balance(env.user_tokens[1]).await;
+    let swap = swap_basket_ix(&env, vec![AMOUNT, 0], 1, 0);
+    env.process(&[swap]).await.unwrap();
+    let amount_out = env.token_balance(env.user_tokens[1]).await - balance_before;
+    assert!(amount_out < AMOUNT && amount_out > AMOUNT * 99 / 100);
+
+    let after = env.pool_state().await;
+    assert_eq!(
+        after.reserves[0] + after.protocol_fees_owed[0],
+        before.reserves[0] + AMOUNT
+    );
+    assert_eq!(after.reserves[1], before.reserves[1] - amount_out);
+}
@@ -0,0 +1,197 @@
+//! Post-swap callbacks into whitelisted hook programs
+
+mod common;
+
+use anchor_lang::solana_program::{
+    account_info::AccountInfo, entrypoint::ProgramResult, instruction::AccountMeta,
+    program_error::ProgramError, pubkey::Pubkey,
+};
+use anchor_lang::{AnchorDeserialize, InstructionData, ToAccountMetas};
+use common::{assert_program_error, SeedPoolEnv};
+use equilibrium_core::errors::ErrorCode;
+use equilibrium_core::instruction::{AddWhitelistedProgram, RemoveWhitelistedProgram};
+use equilibrium_core::{CpiTargetKind, SwapHookPayload, SWAP_HOOK_DISCRIMINATOR};
+use solana_program_test::processor;
+use solana_sdk::{account::Account, instruction::Instruction};
+
+fn hook_id() -> Pubkey {
+    Pubkey::new_from_array([9; 32])
+}
+
+fn record() -> Pubkey {
+    Pubkey::new_from_array([10; 32])
+}
+
+fn swap_hook_approval(env: &SeedPoolEnv) -> Pubkey {
+    Pubkey::find_program_address(
+        &[b"swap-hook", env.amm_config.as_ref(), hook_id().as_ref()],
+        &equilibrium_core::id(),
+    )
+    .0
+}
+
+/// Stand-in hook program that copies the callback data into its record account
+fn process_hook(_program_id: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    let record = accounts.first().ok_or(ProgramError::NotEnoughAccountKeys)?;
+    if data.get(..8) != Some(&SWAP_HOOK_DISCRIMINATOR[..]) {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+    record.try_borrow_mut_data()?[..data.len()].copy_from_slice(data);
+    Ok(())
+}
+
+async fn hook_env() -> SeedPoolEnv {
+    SeedPoolEnv::with_programs(
+        [1_000_000_000, 1_000_000_000, 1_000_000_000],
+        |program_test| {
+            program_test.add_program("mock_swap_hook", hook_id(), processor!(process_hook));
+            program_test.add_account(
+                record(),
+                Account {
+                    lamports: 1_000_000_000,
+                    data: vec![0; 256],
+                    owner: hook_id(),
+                    ..Account::default()
+                },
+            );
+        },
+    )
+    .await
+}
+
+fn approve_swap_hook_ix(env: &SeedPoolEnv) -> Instruction {
+    Instruction {
+        program_id: equilibrium_core::id(),
+        accounts: equilibrium_core::accounts::ApproveSwapHook {
+            authority: env.user(),
+            amm_config: env.amm_config,
+            hook_program: hook_id(),
+            swap_hook_approval: swap_hook_approval(env),
+            system_program: solana_sdk::system_program::ID,
+        }
+        .to_account_metas(None),
+        data: equilibrium_core::instruction::ApproveSwapHook {}.data(),
+    }
+}
+
+fn whitelist_hook_ix(env: &SeedPoolEnv) -> Instruction {
+    env.update_config_ix(AddWhitelistedProgram {
+        program_id: hook_id(),
+        kind: CpiTargetKind::SwapHook,
+    })
+}
+
+fn hooked_swap_ix(env: &SeedPoolEnv, approval: Pubkey, amount_in: u64) -> Instruction {
+    let mut swap = env.swap_ix(0, 1, amount_in, 0);
+    swap.accounts.extend([
+        AccountMeta::new_readonly(approval, false),
+        AccountMeta::new_readonly(hook_id(), false),
+        AccountMeta::new(record(), false),
+    ]);
+    swap
+}
+
+#[tokio::test]
+#[ignore = "needs the SBF program from `anchor build`"]
+async fn approved_hook_receives_the_swap_result() {
+    common::require_program();
+
+    let mut env = hook_env().await;
+    env.process(&[approve_swap_hook_ix(&env), whitelist_hook_ix(&env)])
+        .await
+        .unwrap();
+
+    let balance_before = env.token_balance(env.user_tokens[1]).await;
+    env.process(&[hooked_swap_ix(&env, swap_hook_approval(&env), 1_000_000)])
+        .await
+        .unwrap();
+    let amount_out = env.token_balance(env.user_tokens[1]).await - balance_before;
+
+    let data = env.account(record()).await.data;
+    let payload = SwapHookPayload::deserialize(&mut &data[8..]).unwrap();
+    assert_eq!(payload.pool, env.pool);
+    assert_eq!(payload.user, env.user());
+    assert_eq!(payload.recipient, env.user_tokens[1]);
+    assert_eq!(payload.token_mint_in, env.mints[0]);
+    assert_eq!(payload.token_mint_out, env.mints[1]);
+    assert_eq!(payload.amount_in, 1_000_000);
+    assert_eq!(payload.amount_out, amount_out);
+}
+
+#[tokio::test]
+#[ignore = "needs the SBF program from `anchor build`"]
+async fn unapproved_or_revoked_hook_is_rejected() {
+    common::require_program();
+
+    let mut env = hook_env().await;
+
+    // Any other program-owned account cannot stand in for the approval
+    let swap = hooked_swap_ix(&env, env.pool, 1_000_000);
+    assert_program_error(env.process(&[swap]).await, ErrorCode::InvalidSwapHook);
+
+    env.process(&[approve_swap_hook_ix(&env)]).await.unwrap();
+    let revoke = Instruction {
+        program_id: equilibrium_core::id(),
+        accounts: equilibrium_core::accounts::RevokeSwapHook {
+            authority: env.user(),
+            amm_config: env.amm_config,
+            swap_hook_approval: swap_hook_approval(&env),
+        }
+        .to_account_metas(None),
+        data: equilibrium_core::instruction::RevokeSwapHook {}.data(),
+    };
+    env.process(&[revoke]).await.unwrap();
+
+    let swap = hooked_swap_ix(&env, swap_hook_approval(&env), 1_000_000);
+    assert_program_error(env.process(&[swap]).await, ErrorCode::InvalidSwapHook);
+}
+
+#[tokio::test]
+#[ignore = "needs the SBF program from `anchor build`"]
+async fn hook_must_stay_on_the_config_whitelist() {
+    common::require_program();
+
+    let mut env = hook_env().await;
+    let approval = swap_hook_approval(&env);
+
+    // An approval alone does not make the hook callable
+    env.process(&[approve_swap_hook_ix(&env)]).await.unwrap();
+    assert_program_error(
+        env.process(&[hooked_swap_ix(&env, approval, 1_000_000)])
+            .await,
+        ErrorCode::ProgramNotWhitelisted,
+    );
+
+    // Whitelisted as a rebalance venue is not whitelisted as a hook
+    let venue = env.update_config_ix(AddWhitelistedProgram {
+        program_id: hook_id(),
+        kind: CpiTargetKind::RebalanceVenue,
+    });
+    env.process(&[venue]).await.unwrap();
+    assert_program_error(
+        env.process(&[hooked_swap_ix(&env, approval, 1_100_000)])
+            .await,
+        ErrorCode::ProgramNotWhitelisted,
+    );
+
+    env.process(&[whitelist_hook_ix(&env)]).await.unwrap();
+    env.process(&[hooked_swap_ix(&env, approval, 1_000_000)])
+        .await
+        .unwrap();
+    assert_program_error(
+        env.process(&[whitelist_hook_ix(&env)]).await,
+        ErrorCode::InvalidWhitelistedProgram,
+    );
+
+    // Delisting cuts the hook off even though its approval still exists
+    let delist = env.update_config_ix(RemoveWhitelistedProgram {
+        program_id: hook_id(),
+        kind: CpiTargetKind::SwapHook,
+    });
+    env.process(&[delist]).await.unwrap();
+    assert_program_error(
+        env.process(&[hooked_swap_ix(&env, approval, 1_200_000)])
+            .await,
+        ErrorCode::ProgramNotWhitelisted,
+    );
+}
@@ -0,0 +1,173 @@
+//! Input validation on the swap path
+
+mod common;
+
+use anchor_lang::InstructionData;
+use common::{assert_program_error, create_ata, SeedPoolEnv};
+use equilibrium_core::errors::ErrorCode;
+
+#[tokio::test]
+#[ignore = "needs the SBF program from `anchor build`"]
+async fn zero_amount_swap_is_rejected() {
+    common::require_program();
+
+    let mut env = SeedPoolEnv::new([1_000_000_000, 1_000_000_000, 1_000_000_000]).await;
+
+    let swap = env.swap_ix(0, 1, 0, 0);
+    assert_program_error(env.process(&[swap]).await, ErrorCode::MinSwapAmount);
+}
+
+#[tokio::test]
+#[ignore = "needs the SBF program from `anchor build`"]
+async fn swap_below_pool_minimum_is_rejected() {
+    common::require_program();
+
+    let mut env = SeedPoolEnv::new([1_000_000_000, 1_000_000_000, 1_000_000_000]).await;
+
+    let set_minimum = env.update_pool_ix(equilibrium_core::instruction::SetMinSwapAmount {
+        min_swap_amount: 1_000,
+    });
+    env.process(&[set_minimum]).await.unwrap();
+    assert_eq!(env.pool_state().await.min_swap_amount, 1_000);
+
+    let swap = env.swap_ix(0, 1, 999, 0);
+    assert_program_error(env.process(&[swap]).await, ErrorCode::MinSwapAmount);
+
+    let swap = env.swap_ix(0, 1, 1_000_000, 0);
+    env.process(&[swap]).await.unwrap();
+}
+
+#[tokio::test]
+#[ignore = "needs the SBF program from `anchor build`"]
+async fn swap_above_max_trade_size_is_rejected() {
+    common::require_program();
+
+    let mut env = SeedPoolEnv::new([1_000_000_000, 1_000_000_000, 1_000_000_000]).await;
+    assert_eq!(env.pool_state().await.max_trade_bps, 3_000);
+
+    // 40% of the output reserve
+    let swap = env.swap_ix(0, 1, 400_000_000, 0);
+    assert_program_error(env.process(&[swap]).await, ErrorCode::MaxTradeSizeExceeded);
+
+    let set_max_trade = env.update_pool_ix(equilibrium_core::instruction::SetMaxTradeBps {
+        max_trade_bps: 5_000,
+    });
+    env.process(&[set_max_trade]).await.unwrap();
+
+    let swap = env.swap_ix(0, 1, 400_000_000, 0);
+    env.process(&[swap]).await.unwrap();
+}
+
+#[tokio::test]
+#[ignore = "needs the SBF program from `anchor build`"]
+async fn swap_after_deadline_is_rejected() {
+    common::require_program();
+
+    let mut env = SeedPoolEnv::new([1_000_000_000, 1_000_000_000, 1_000_000_000]).await;
+    let now = env
+        .context
+        .banks_client
+        .get_sysvar::<solana_sdk::clock::Clock>()
+        .await
+        .unwrap()
+        .unix_timestamp;
+
+    let mut swap = env.swap_ix(0, 1, 1_000_000, 0);
+    swap.data = equilibrium_core::instruction::Swap {
+        amount_in: 1_000_000,
+        min_amount_out: 0,
+        deadline: now - 1,
+        integrator_fee_bps: 0,
+        max_price_impact_bps: None,
+    }
+    .data();
+    assert_program_error(env.process(&[swap]).await, ErrorCode::DeadlineExceeded);
+
+    let swap = env.swap_ix(0, 1, 1_000_000, 0);
+    env.process(&[swap]).await.unwrap();
+}
+
+#[tokio::test]
+#[ignore = "needs the SBF program from `anchor build`"]
+async fn indexed_swap_checks_indices_against_pool_mints() {
+    common::require_program();
+
+    let mut env = SeedPoolEnv::new([1_000_000_000, 1_000_000_000, 1_000_000_000]).await;
+    let indexed_swap = |env: &SeedPoolEnv, token_in_index: u8, token_out_index: u8| {
+        let mut swap = env.swap_ix(0, 1, 1_000_000, 0);
+        swap.data = equilibrium_core::instruction::SwapIndexed {
+            token_in_index,
+            token_out_index,
+            amount_in: 1_000_000,
+            min_amount_out: 0,
+            deadline: i64::MAX,
+            integrator_fee_bps: 0,
+            max_price_impact_bps: None,
+        }
+        .data();
+        swap
+    };
+
+    // Indices must name the mints the accounts carry, in the same order
+    for (token_in_index, token_out_index) in [(1, 0), (0, 2), (0, 3), (u8::MAX, 1)] {
+        assert_program_error(
+            env.process(&[indexed_swap(&env, token_in_index, token_out_index)])
+                .await,
+            ErrorCode::MintOrderMismatch,
+        );
+    }
+
+    let before = env.token_balance(env.user_tokens[1]).await;
+    env.process(&[indexed_swap(&env, 0, 1)]).await.unwrap();
+    let received = env.token_balance(env.user_tokens[1]).await - before;
+    assert!(received > 0);
+    let pool = env.pool_state().await;
+    assert_eq!(pool.reserves[1], 1_000_000_000 - received);
+}
+
+#[tokio::test]
+#[ignore = "needs the SBF program from `anchor build`"]
+async fn swap_into_a_non_vault_pool_account_is_rejected() {
+    common::require_program();
+
+    let mut env = SeedPoolEnv::new([1_000_000_000, 1_000_000_000, 1_000_000_000]).await;
+
+    // Owned by the pool and of the right mint, but not the vault the pool tracks
+    let pool = env.pool;
+    let stray = create_ata(&mut env.context, &env.mints[0], &pool).await;
+    let mut swap = env.swap_ix(0, 1, 1_000_000, 0);
+    let vault = swap
+        .accounts
+        .iter_mut()
+        .find(|meta| meta.pubkey == env.pool_tokens[0])
+        .unwrap();
+    vault.pubkey = stray;
+    assert_program_error(env.process(&[swap]).await, ErrorCode::VaultMismatch);
+}
+
+#[tokio::test]
+#[ignore = "needs the SBF program from `anchor build`"]
+async fn swap_past_the_price_impact_ceiling_is_rejected() {
+    common::require_program();
+
+    let mut env = SeedPoolEnv::new([1_000_000_000, 1_000_000_000, 1_000_000_000]).await;
+    let capped_swap = |env: &SeedPoolEnv, max_price_impact_bps: u64| {
+        let mut swap = env.swap_ix(0, 1, 250_000_000, 0);
+        swap.data = equilibrium_core::instruction::Swap {
+            amount_in: 250_000_000,
+            min_amount_out: 0,
+            deadline: i64::MAX,
+            integrator_fee_bps: 0,
+            max_price_impact_bps: Some(max_price_impact_bps),
+        }
+        .data();
+        swap
+    };
+
+    // A quarter of the output reserve moves the price, however flat the curve
+    assert_program_error(
+        env.process(&[capped_swap(&env, 0)]).await,
+        ErrorCode::PriceImpactExceeded,
+    );
+    env.process(&[capped_swap(&env, 100)]).await.unwrap();
+}
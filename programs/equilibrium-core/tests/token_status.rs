@@ -0,0 +1,61 @@
+//! Per-token depeg status and admin freezes
+
+mod common;
+
+use common::{assert_program_error, SeedPoolEnv};
+use equilibrium_core::errors::ErrorCode;
+use equilibrium_core::instruction::SetTokenFrozen;
+use equilibrium_core::TokenStatus;
+
+#[tokio::test]
+#[ignore = "needs the SBF program from `anchor build`"]
+async fn token_status_tracks_weight_deviation() {
+    common::require_program();
+
+    // Equal reserves sit 11.67 points under the 45% target and 13.34 over the 20% one
+    let mut env = SeedPoolEnv::new([1_000_000_000, 1_000_000_000, 1_000_000_000]).await;
+    assert_eq!(
+        env.pool_state().await.token_status,
+        [
+            TokenStatus::StressedUnder,
+            TokenStatus::Normal,
+            TokenStatus::StressedOver
+        ]
+    );
+
+    // Adding the scarce token and taking out the surplus one brings both back in range
+    let swap = env.swap_ix(0, 2, 200_000_000, 0);
+    env.process(&[swap]).await.unwrap();
+    assert_eq!(
+        env.pool_state().await.token_status,
+        [TokenStatus::Normal; 3]
+    );
+}
+
+#[tokio::test]
+#[ignore = "needs the SBF program from `anchor build`"]
+async fn frozen_token_cannot_be_swapped() {
+    common::require_program();
+
+    let mut env = SeedPoolEnv::new([1_000_000_000, 1_000_000_000, 1_000_000_000]).await;
+    let freeze = env.update_pool_ix(SetTokenFrozen {
+        token_mint: env.mints[1],
+        frozen: true,
+    });
+    env.process(&[freeze]).await.unwrap();
+    assert_eq!(env.pool_state().await.token_status[1], TokenStatus::Frozen);
+
+    let swap_in = env.swap_ix(1, 0, 1_000_000, 0);
+    assert_program_error(env.process(&[swap_in]).await, ErrorCode::TokenFrozen);
+    let swap_out = env.swap_ix(0, 1, 1_000_000, 0);
+    assert_program_error(env.process(&[swap_out]).await, ErrorCode::TokenFrozen);
+
+    let unfreeze = env.update_pool_ix(SetTokenFrozen {
+        token_mint: env.mints[1],
+        frozen: false,
+    });
+    env.process(&[unfreeze]).await.unwrap();
+    assert_eq!(env.pool_state().await.token_status[1], TokenStatus::Normal);
+    let swap_in = env.swap_ix(1, 0, 1_000_000, 0);
+    env.process(&[swap_in]).await.unwrap();
+}
@@ -0,0 +1,181 @@
+//! Moving part of a position to another wallet
+
+mod common;
+
+use anchor_lang::{InstructionData, ToAccountMetas};
+use common::{assert_program_error, create_ata, create_mint, mint_to, SeedPoolEnv};
+use equilibrium_core::errors::ErrorCode;
+use equilibrium_core::instruction::UpdatePoolParams;
+use equilibrium_core::{PoolParamChange, UserPosition, WithdrawMode};
+use solana_sdk::{
+    instruction::Instruction,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    system_program,
+};
+
+const RESERVE: u64 = 1_000_000_000;
+
+fn position(owner: &Pubkey, pool: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[b"user-position", owner.as_ref(), pool.as_ref()],
+        &equilibrium_core::id(),
+    )
+    .0
+}
+
+fn transfer_ix(
+    env: &SeedPoolEnv,
+    recipient: Pubkey,
+    recipient_lp_token: Pubkey,
+    amount: u64,
+) -> Instruction {
+    Instruction {
+        program_id: equilibrium_core::id(),
+        accounts: equilibrium_core::accounts::TransferPosition {
+            owner: env.user(),
+            recipient,
+            pool: env.pool,
+            lp_mint: env.lp_mint,
+            owner_lp_token: env.user_lp_token,
+            recipient_lp_token,
+            owner_position: env.user_position,
+            recipient_position: position(&recipient, &env.pool),
+            token_program: spl_token::ID,
+            system_program: system_program::ID,
+            credential: None,
+        }
+        .to_account_metas(None),
+        data: equilibrium_core::instruction::TransferPosition { amount }.data(),
+    }
+}
+
+#[tokio::test]
+#[ignore = "needs the SBF program from `anchor build`"]
+async fn splits_a_position_into_the_recipients() {
+    common::require_program();
+
+    let mut env = SeedPoolEnv::new([RESERVE; 3]).await;
+    let deposit = env.deposit_ix([100_000_000; 3], 0);
+    env.process(&[deposit]).await.unwrap();
+    let before = env.position_state().await;
+
+    let recipient = Keypair::new().pubkey();
+    let recipient_lp_token = create_ata(&mut env.context, &env.lp_mint, &recipient).await;
+    let amount = before.lp_amount / 4;
+    let transfer = transfer_ix(&env, recipient, recipient_lp_token, amount);
+    env.process(&[transfer]).await.unwrap();
+
+    let after = env.position_state().await;
+    assert_eq!(after.lp_amount, before.lp_amount - amount);
+    assert!(after.is_active);
+
+    let received = env
+        .account_state::<UserPosition>(position(&recipient, &env.pool))
+        .await;
+    assert_eq!(received.owner, recipient);
+    assert_eq!(received.lp_amount, amount);
+    assert!(received.is_active);
+    assert_eq!(
+        (received.min_price, received.max_price),
+        (before.min_price, before.max_price)
+    );
+    assert_eq!(received.delegate, None);
+    assert_eq!(env.token_balance(recipient_lp_token).await, amount);
+
+    // Sending the rest empties the position, which can then no longer withdraw
+    let transfer = transfer_ix(&env, recipient, recipient_lp_token, after.lp_amount);
+    env.process(&[transfer]).await.unwrap();
+    let emptied = env.position_state().await;
+    assert_eq!(emptied.lp_amount, 0);
+    assert!(!emptied.is_active);
+    assert_eq!(
+        env.account_state::<UserPosition>(position(&recipient, &env.pool))
+            .await
+            .lp_amount,
+        before.lp_amount
+    );
+    assert_program_error(
+        env.process(&[env.withdraw_ix(1, [0; 3], WithdrawMode::Proportional)])
+            .await,
+        ErrorCode::PositionNotActive,
+    );
+}
+
+#[tokio::test]
+#[ignore = "needs the SBF program from `anchor build`"]
+async fn rejects_overdrawn_empty_or_self_transfers() {
+    common::require_program();
+
+    let mut env = SeedPoolEnv::new([RESERVE; 3]).await;
+    let deposit = env.deposit_ix([100_000_000; 3], 0);
+    env.process(&[deposit]).await.unwrap();
+    let lp_amount = env.position_state().await.lp_amount;
+
+    let recipient = Keypair::new().pubkey();
+    let recipient_lp_token = create_ata(&mut env.context, &env.lp_mint, &recipient).await;
+    for amount in [0, lp_amount + 1] {
+        assert_program_error(
+            env.process(&[transfer_ix(&env, recipient, recipient_lp_token, amount)])
+                .await,
+            ErrorCode::InvalidPositionTransfer,
+        );
+    }
+
+    assert_program_error(
+        env.process(&[transfer_ix(&env, env.user(), env.user_lp_token, 1)])
+            .await,
+        ErrorCode::InvalidPositionTransfer,
+    );
+}
+
+#[tokio::test]
+#[ignore = "needs the SBF program from `anchor build`"]
+async fn permissioned_pools_only_transfer_to_credential_holders() {
+    common::require_program();
+
+    let mut env = SeedPoolEnv::new([RESERVE; 3]).await;
+    let deposit = env.deposit_ix([100_000_000; 3], 0);
+    env.process(&[deposit]).await.unwrap();
+
+    let credential_mint = create_mint(&mut env.context).await;
+    let update = env.update_pool_ix(UpdatePoolParams {
+        changes: vec![PoolParamChange::Credential {
+            requires_credential: true,
+            credential_mint,
+        }],
+    });
+    env.process(&[update]).await.unwrap();
+
+    let user = env.user();
+    let recipient = Keypair::new().pubkey();
+    let recipient_lp_token = create_ata(&mut env.context, &env.lp_mint, &recipient).await;
+    let user_credential = create_ata(&mut env.context, &credential_mint, &user).await;
+    let recipient_credential = create_ata(&mut env.context, &credential_mint, &recipient).await;
+    mint_to(&mut env.context, &credential_mint, &user_credential, 1).await;
+    // The recipient's credential goes in the trailing optional slot
+    let transfer_with = |env: &SeedPoolEnv, credential: Pubkey| {
+        let mut transfer = transfer_ix(env, recipient, recipient_lp_token, 1);
+        transfer.accounts.last_mut().unwrap().pubkey = credential;
+        transfer
+    };
+
+    // No credential, an empty one, or the sender's own do not admit the recipient
+    assert_program_error(
+        env.process(&[transfer_ix(&env, recipient, recipient_lp_token, 1)])
+            .await,
+        ErrorCode::CredentialRequired,
+    );
+    for credential in [recipient_credential, user_credential] {
+        assert_program_error(
+            env.process(&[transfer_with(&env, credential)]).await,
+            ErrorCode::CredentialRequired,
+        );
+    }
+
+    mint_to(&mut env.context, &credential_mint, &recipient_credential, 1).await;
+    let transfer = transfer_with(&env, recipient_credential);
+    env.process(&[transfer]).await.unwrap();
+    let position: UserPosition = env.account_state(position(&recipient, &env.pool)).await;
+    assert_eq!(position.lp_amount, 1);
+}
@@ -0,0 +1,134 @@
+//! Protocol fee accrual, collection into the treasury and sweeps
+
+mod common;
+
+use anchor_lang::{InstructionData, ToAccountMetas};
+use common::{assert_program_error, SeedPoolEnv};
+use equilibrium_core::errors::ErrorCode;
+use solana_sdk::{instruction::Instruction, pubkey::Pubkey};
+
+fn treasury(env: &SeedPoolEnv) -> Pubkey {
+    Pubkey::find_program_address(
+        &[b"treasury", env.amm_config.as_ref()],
+        &equilibrium_core::id(),
+    )
+    .0
+}
+
+fn treasury_vault(env: &SeedPoolEnv, mint: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[b"treasury-vault", treasury(env).as_ref(), mint.as_ref()],
+        &equilibrium_core::id(),
+    )
+    .0
+}
+
+fn collect_ix(env: &SeedPoolEnv, token: usize) -> Instruction {
+    Instruction {
+        program_id: equilibrium_core::id(),
+        accounts: equilibrium_core::accounts::CollectProtocolFees {
+            pool: env.pool,
+            token_mint: env.mints[token],
+            pool_token: env.pool_tokens[token],
+            treasury: treasury(env),
+            treasury_vault: treasury_vault(env, &env.mints[token]),
+            token_program: spl_token::ID,
+        }
+        .to_account_metas(None),
+        data: equilibrium_core::instruction::CollectProtocolFees {}.data(),
+    }
+}
+
+#[tokio::test]
+#[ignore = "needs the SBF program from `anchor build`"]
+async fn protocol_fees_accrue_outside_reserves_and_sweep_from_the_treasury() {
+    common::require_program();
+
+    let mut env = SeedPoolEnv::new([1_000_000_000, 1_000_000_000, 1_000_000_000]).await;
+    let init_treasury = Instruction {
+        program_id: equilibrium_core::id(),
+        accounts: equilibrium_core::accounts::InitTreasury {
+            authority: env.user(),
+            amm_config: env.amm_config,
+            treasury: treasury(&env),
+            system_program: solana_sdk::system_program::ID,
+        }
+        .to_account_metas(None),
+        data: equilibrium_core::instruction::InitTreasury {}.data(),
+    };
+    let init_vault = Instruction {
+        program_id: equilibrium_core::id(),
+        accounts: equilibrium_core::accounts::InitTreasuryVault {
+            payer: env.user(),
+            treasury: treasury(&env),
+            token_mint: env.mints[0],
+            treasury_vault: treasury_vault(&env, &env.mints[0]),
+            token_program: spl_token::ID,
+            system_program: solana_sdk::system_program::ID,
+            rent: solana_sdk::sysvar::rent::ID,
+        }
+        .to_account_metas(None),
+        data: equilibrium_core::instruction::InitTreasuryVault {}.data(),
+    };
+    let set_share = env.update_pool_ix(equilibrium_core::instruction::SetProtocolFeeShare {
+        protocol_fee_share_bps: 5_000,
+    });
+    env.process(&[init_treasury, init_vault, set_share])
+        .await
+        .unwrap();
+
+    assert_program_error(
+        env.process(&[collect_ix(&env, 0)]).await,
+        ErrorCode::NothingToCollect,
+    );
+
+    env.process(&[env.swap_ix(0, 1, 10_000_000, 0)])
+        .await
+        .unwrap();
+    let pool = env.pool_state().await;
+    let owed = pool.protocol_fees_owed[0];
+    assert!(owed > 0);
+    // The vault holds reserves plus the protocol's cut
+    assert_eq!(
+        env.token_balance(env.pool_tokens[0]).await,
+        pool.reserves[0] + owed
+    );
+
+    // Fees are only collected out of the vault the pool tracks
+    let pool_key = env.pool;
+    let rogue = common::create_ata(&mut env.context, &env.mints[0], &pool_key).await;
+    let mut collect = collect_ix(&env, 0);
+    let vault = collect
+        .accounts
+        .iter_mut()
+        .find(|meta| meta.pubkey == env.pool_tokens[0])
+        .unwrap();
+    vault.pubkey = rogue;
+    assert_program_error(env.process(&[collect]).await, ErrorCode::VaultMismatch);
+
+    env.process(&[collect_ix(&env, 0)]).await.unwrap();
+    assert_eq!(env.pool_state().await.protocol_fees_owed[0], 0);
+    assert_eq!(env.token_balance(treasury_vault(&env, &env.mints[0])).await, owed);
+
+    let balance_before = env.token_balance(env.user_tokens[0]).await;
+    let sweep = Instruction {
+        program_id: equilibrium_core::id(),
+        accounts: equilibrium_core::accounts::SweepTreasury {
+            authority: env.user(),
+            amm_config: env.amm_config,
+            treasury: treasury(&env),
+            token_mint: env.mints[0],
+            treasury_vault: treasury_vault(&env, &env.mints[0]),
+            destination: env.user_tokens[0],
+            token_program: spl_token::ID,
+        }
+        .to_account_metas(None),
+        data: equilibrium_core::instruction::SweepTreasury { amount: owed }.data(),
+    };
+    env.process(&[sweep]).await.unwrap();
+    assert_eq!(
+        env.token_balance(env.user_tokens[0]).await,
+        balance_before + owed
+    );
+    assert_eq!(env.token_balance(treasury_vault(&env, &env.mints[0])).await, 0);
+}
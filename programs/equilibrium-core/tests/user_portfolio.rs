@@ -0,0 +1,120 @@
+//! One account listing a wallet's positions across pools
+
+mod common;
+
+use anchor_lang::{InstructionData, ToAccountMetas};
+use common::SeedPoolEnv;
+use equilibrium_core::{PortfolioEntry, UserPortfolio, WithdrawMode};
+use solana_sdk::{
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+    system_program,
+};
+
+const RESERVE: u64 = 1_000_000_000;
+const AMOUNT: u64 = 1_000_000;
+
+fn portfolio_address(env: &SeedPoolEnv) -> Pubkey {
+    Pubkey::find_program_address(
+        &[b"user-portfolio", env.user().as_ref()],
+        &equilibrium_core::id(),
+    )
+    .0
+}
+
+/// `instruction` with its trailing optional `user_portfolio` account filled in
+fn with_portfolio(env: &SeedPoolEnv, mut instruction: Instruction) -> Instruction {
+    *instruction.accounts.last_mut().unwrap() = AccountMeta::new(portfolio_address(env), false);
+    instruction
+}
+
+#[tokio::test]
+#[ignore = "needs the SBF program from `anchor build`"]
+async fn portfolio_tracks_deposits_and_withdrawals() {
+    common::require_program();
+
+    let mut env = SeedPoolEnv::new([RESERVE; 3]).await;
+    let portfolio = portfolio_address(&env);
+
+    // The position opened with the pool is indexed at creation
+    let mut accounts = equilibrium_core::accounts::InitUserPortfolio {
+        owner: env.user(),
+        user_portfolio: portfolio,
+        system_program: system_program::ID,
+    }
+    .to_account_metas(None);
+    accounts.push(AccountMeta::new_readonly(env.user_position, false));
+    let init = Instruction {
+        program_id: equilibrium_core::id(),
+        accounts,
+        data: equilibrium_core::instruction::InitUserPortfolio {}.data(),
+    };
+    env.process(&[init]).await.unwrap();
+    let position = env.position_state().await;
+    let state: UserPortfolio = env.account_state(portfolio).await;
+    assert_eq!(state.owner, env.user());
+    assert_eq!(
+        state.positions,
+        vec![PortfolioEntry {
+            position: env.user_position,
+            pool: env.pool,
+            lp_amount: position.lp_amount,
+            deposited: [0; 3],
+            withdrawn: [0; 3],
+        }]
+    );
+
+    // Deposits passing the portfolio keep it current
+    let deposit = with_portfolio(&env, env.deposit_ix([AMOUNT; 3], 0));
+    env.process(&[deposit]).await.unwrap();
+    let position = env.position_state().await;
+    let state: UserPortfolio = env.account_state(portfolio).await;
+    assert_eq!(state.positions[0].lp_amount, position.lp_amount);
+    assert_eq!(state.positions[0].deposited, [AMOUNT; 3]);
+    assert_eq!(state.deposit_count, 1);
+
+    // A deposit made without it is picked up by a sync
+    env.process(&[env.deposit_ix([AMOUNT; 3], 0)])
+        .await
+        .unwrap();
+    let mut accounts = equilibrium_core::accounts::SyncUserPortfolio {
+        payer: env.user(),
+        user_portfolio: portfolio,
+    }
+    .to_account_metas(None);
+    accounts.push(AccountMeta::new_readonly(env.user_position, false));
+    let sync = Instruction {
+        program_id: equilibrium_core::id(),
+        accounts,
+        data: equilibrium_core::instruction::SyncUserPortfolio {}.data(),
+    };
+    env.process(&[sync]).await.unwrap();
+    let position = env.position_state().await;
+    let state: UserPortfolio = env.account_state(portfolio).await;
+    assert_eq!(state.positions[0].lp_amount, position.lp_amount);
+    assert_eq!(state.deposit_count, 1);
+
+    // Half out keeps the entry, with what was paid out
+    let before = env.token_balance(env.user_tokens[0]).await;
+    let withdraw = with_portfolio(
+        &env,
+        env.withdraw_percent_ix(5_000, WithdrawMode::Proportional),
+    );
+    env.process(&[withdraw]).await.unwrap();
+    let paid_out = env.token_balance(env.user_tokens[0]).await - before;
+    let position = env.position_state().await;
+    let state: UserPortfolio = env.account_state(portfolio).await;
+    assert_eq!(state.positions[0].lp_amount, position.lp_amount);
+    assert_eq!(state.positions[0].withdrawn[0], paid_out);
+    assert_eq!(state.withdraw_count, 1);
+
+    // Emptying the position drops it from the index
+    let withdraw = with_portfolio(
+        &env,
+        env.withdraw_percent_ix(10_000, WithdrawMode::Proportional),
+    );
+    env.process(&[withdraw]).await.unwrap();
+    let state: UserPortfolio = env.account_state(portfolio).await;
+    assert!(state.positions.is_empty());
+    assert_eq!(state.withdraw_count, 2);
+}
@@ -0,0 +1,81 @@
+//! Pool vaults are bound by address, not just by authority and mint
+
+mod common;
+
+use common::{assert_program_error, create_ata, mint_to, SeedPoolEnv};
+use equilibrium_core::errors::ErrorCode;
+use equilibrium_core::WithdrawMode;
+use solana_sdk::instruction::Instruction;
+
+const RESERVE: u64 = 1_000_000_000;
+const AMOUNT: u64 = 1_000_000;
+
+type Builder = fn(&SeedPoolEnv) -> Instruction;
+
+/// Every builder that moves tokens through the Seed Pool's vaults
+const BUILDERS: [(&str, Builder); 9] = [
+    ("swap 0 -> 1", |env| env.swap_ix(0, 1, AMOUNT, 0)),
+    ("swap 1 -> 2", |env| env.swap_ix(1, 2, AMOUNT, 0)),
+    ("swap 2 -> 0", |env| env.swap_ix(2, 0, AMOUNT, 0)),
+    ("deposit", |env| env.deposit_ix([AMOUNT; 3], 0)),
+    ("deposit one-sided", |env| env.deposit_ix([0, AMOUNT, 0], 0)),
+    ("deposit exact LP", |env| {
+        env.deposit_exact_lp_ix(AMOUNT, [u64::MAX; 3])
+    }),
+    ("withdraw", |env| {
+        env.withdraw_ix(AMOUNT, [0; 3], WithdrawMode::Proportional)
+    }),
+    ("withdraw imbalanced", |env| {
+        env.withdraw_imbalanced_ix([AMOUNT; 3], u64::MAX)
+    }),
+    ("withdraw percent", |env| {
+        env.withdraw_percent_ix(100, WithdrawMode::Proportional)
+    }),
+];
+
+#[tokio::test]
+#[ignore = "needs the SBF program from `anchor build`"]
+async fn rogue_vaults_are_rejected_in_every_vault_slot() {
+    common::require_program();
+
+    let mut env = SeedPoolEnv::new([RESERVE; 3]).await;
+    let pool_before = env.account(env.pool).await;
+
+    // Funded, owned by the pool and of the right mint: only the address gives them away
+    let pool = env.pool;
+    let mut rogue_vaults = Vec::new();
+    for i in 0..3 {
+        let mint = env.mints[i];
+        let rogue = create_ata(&mut env.context, &mint, &pool).await;
+        mint_to(&mut env.context, &mint, &rogue, RESERVE).await;
+        rogue_vaults.push(rogue);
+    }
+
+    // Substitute each rogue vault for the real one wherever a builder passes it
+    let mut substitutions = 0;
+    for (name, build) in BUILDERS {
+        for (i, &rogue) in rogue_vaults.iter().enumerate() {
+            let mut instruction = build(&env);
+            let Some(vault) = instruction
+                .accounts
+                .iter_mut()
+                .find(|meta| meta.pubkey == env.pool_tokens[i])
+            else {
+                continue;
+            };
+            vault.pubkey = rogue;
+            substitutions += 1;
+
+            let result = env.process(&[instruction]).await;
+            assert!(result.is_err(), "{}: rogue vault {} accepted", name, i);
+            assert_program_error(result, ErrorCode::VaultMismatch);
+        }
+    }
+    assert!(substitutions >= BUILDERS.len() * 2);
+
+    // Nothing moved, and the rogue vaults kept their balances
+    assert_eq!(env.account(env.pool).await.data, pool_before.data);
+    for rogue in rogue_vaults {
+        assert_eq!(env.token_balance(rogue).await, RESERVE);
+    }
+}
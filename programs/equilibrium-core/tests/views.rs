@@ -0,0 +1,187 @@
+//! Simulated read-only view instructions
+
+mod common;
+
+use anchor_lang::AnchorDeserialize;
+use anchor_lang::{InstructionData, ToAccountMetas};
+use common::{assert_program_error, SeedPoolEnv};
+use equilibrium_core::errors::ErrorCode;
+use equilibrium_core::instruction::{
+    GetAmountsOut, GetDepth, GetPoolApy, GetPoolState, GetVirtualPrice, QuoteFee,
+};
+use equilibrium_core::{DepthView, FeeQuoteView, PoolApyView, PoolStateView, POOL_STATS_INTERVAL};
+use solana_sdk::{instruction::Instruction, pubkey::Pubkey};
+
+#[tokio::test]
+#[ignore = "needs the SBF program from `anchor build`"]
+async fn views_return_pool_data_without_writing_state() {
+    common::require_program();
+
+    let mut env = SeedPoolEnv::new([1_000_000_000, 1_000_000_000, 1_000_000_000]).await;
+    let pool_before = env.account(env.pool).await;
+
+    let data = env.simulate(&[env.view_ix(GetPoolState {})]).await;
+    let state = PoolStateView::try_from_slice(&data).unwrap();
+    assert_eq!(state.reserves, vec![1_000_000_000; 3]);
+    assert_eq!(state.lp_supply, 3_000_000_000);
+    assert!(state.virtual_price > 0);
+
+    // Initial LP supply equals D for a balanced pool
+    let data = env.simulate(&[env.view_ix(GetVirtualPrice {})]).await;
+    let virtual_price = u128::try_from_slice(&data).unwrap();
+    assert!(virtual_price.abs_diff(1_000_000_000_000_000_000) < 1_000_000_000_000_000);
+
+    let data = env
+        .simulate(&[env.view_ix(GetAmountsOut {
+            token_mint_in: env.mints[0],
+            amount_in: 1_000_000,
+        })])
+        .await;
+    let amounts_out = Vec::<u64>::try_from_slice(&data).unwrap();
+    assert_eq!(amounts_out[0], 0);
+    assert!(amounts_out[1] > 0 && amounts_out[2] > 0);
+
+    assert_eq!(env.account(env.pool).await.data, pool_before.data);
+}
+
+#[tokio::test]
+#[ignore = "needs the SBF program from `anchor build`"]
+async fn fee_quote_tells_balancing_trades_from_unbalancing_ones() {
+    common::require_program();
+
+    // Equal reserves against 45/35/20 targets: token 0 is short, token 2 long
+    let mut env = SeedPoolEnv::new([1_000_000_000, 1_000_000_000, 1_000_000_000]).await;
+    let pool_before = env.account(env.pool).await;
+    let quote = |token_in_index, token_out_index| {
+        env.view_ix(QuoteFee {
+            token_in_index,
+            token_out_index,
+            amount_in: 50_000_000,
+        })
+    };
+    let (restoring, worsening) = (quote(0, 2), quote(2, 0));
+    let restoring = FeeQuoteView::try_from_slice(&env.simulate(&[restoring]).await).unwrap();
+    let worsening = FeeQuoteView::try_from_slice(&env.simulate(&[worsening]).await).unwrap();
+
+    assert!(restoring.improves_balance && !worsening.improves_balance);
+    assert!(restoring.weights_after[0] > restoring.weights_before[0]);
+    assert!(worsening.weights_after[0] < worsening.weights_before[0]);
+    assert!(restoring.fee_after_bps < worsening.fee_after_bps);
+    assert!(restoring.fee_bps > 0 && restoring.amount_out > 0);
+
+    assert_eq!(env.account(env.pool).await.data, pool_before.data);
+}
+
+#[tokio::test]
+#[ignore = "needs the SBF program from `anchor build`"]
+async fn depth_grows_with_the_price_impact_allowed() {
+    common::require_program();
+
+    let mut env = SeedPoolEnv::new([1_000_000_000, 1_000_000_000, 1_000_000_000]).await;
+    let depth = |env: &SeedPoolEnv, token_in_index, token_out_index| {
+        env.view_ix(GetDepth {
+            token_in_index,
+            token_out_index,
+        })
+    };
+    let (forward, backward) = (depth(&env, 0, 1), depth(&env, 1, 0));
+    let forward = DepthView::try_from_slice(&env.simulate(&[forward]).await).unwrap();
+    let backward = DepthView::try_from_slice(&env.simulate(&[backward]).await).unwrap();
+
+    assert!(forward.depth_10_bps > 0);
+    assert!(forward.depth_10_bps < forward.depth_50_bps);
+    assert!(forward.depth_50_bps < forward.depth_100_bps);
+    // A balanced pool is equally deep both ways
+    assert_eq!(forward.depth_100_bps, backward.depth_100_bps);
+
+    // Selling a token the pool already holds too much of moves the price sooner
+    let swap = env.swap_ix(0, 1, 200_000_000, 0);
+    env.process(&[swap]).await.unwrap();
+    let data = env.simulate(&[depth(&env, 0, 1)]).await;
+    let skewed = DepthView::try_from_slice(&data).unwrap();
+    assert!(skewed.depth_100_bps < forward.depth_100_bps);
+
+    let same_token = depth(&env, 1, 1);
+    assert_program_error(
+        env.process(&[same_token]).await,
+        ErrorCode::InvalidInstructionData,
+    );
+}
+
+#[tokio::test]
+#[ignore = "needs the SBF program from `anchor build`"]
+async fn routing_hint_points_from_under_to_over_weighted_token() {
+    common::require_program();
+
+    // Equal reserves against 45/35/20 targets
+    let mut env = SeedPoolEnv::new([1_000_000_000, 1_000_000_000, 1_000_000_000]).await;
+    let pool = env.pool_state().await;
+    assert_eq!((pool.best_token_in, pool.best_token_out), (0, 2));
+}
+
+#[tokio::test]
+#[ignore = "needs the SBF program from `anchor build`"]
+async fn pool_apy_reflects_fees_since_the_last_samples() {
+    common::require_program();
+
+    let mut env = SeedPoolEnv::new([1_000_000_000, 1_000_000_000, 1_000_000_000]).await;
+    let (pool_stats, _) =
+        Pubkey::find_program_address(&[b"pool-stats", env.pool.as_ref()], &equilibrium_core::id());
+    let init = Instruction {
+        program_id: equilibrium_core::id(),
+        accounts: equilibrium_core::accounts::InitPoolStats {
+            payer: env.user(),
+            pool: env.pool,
+            pool_stats,
+            system_program: solana_sdk::system_program::ID,
+        }
+        .to_account_metas(None),
+        data: equilibrium_core::instruction::InitPoolStats {}.data(),
+    };
+    let record = Instruction {
+        program_id: equilibrium_core::id(),
+        accounts: equilibrium_core::accounts::RecordPoolStats {
+            pool: env.pool,
+            pool_stats,
+            amm_config: None,
+            pool_token_tip: None,
+            tip_recipient: None,
+            token_program: None,
+        }
+        .to_account_metas(None),
+        data: equilibrium_core::instruction::RecordPoolStats {}.data(),
+    };
+    env.process(&[init]).await.unwrap();
+
+    // Samples are at least an hour apart
+    common::assert_program_error(
+        env.process(std::slice::from_ref(&record)).await,
+        equilibrium_core::errors::ErrorCode::PoolStatsTooRecent,
+    );
+
+    env.process(&[env.swap_ix(0, 1, 10_000_000, 0)])
+        .await
+        .unwrap();
+    env.advance_clock(POOL_STATS_INTERVAL).await;
+    env.process(&[record]).await.unwrap();
+
+    let view = Instruction {
+        program_id: equilibrium_core::id(),
+        accounts: equilibrium_core::accounts::ViewPoolStats {
+            pool: env.pool,
+            pool_stats,
+        }
+        .to_account_metas(None),
+        data: GetPoolApy {}.data(),
+    };
+    let data = env.simulate(&[view]).await;
+    let apy = PoolApyView::try_from_slice(&data).unwrap();
+    let pool = env.pool_state().await;
+    assert_eq!(apy.tvl, pool.reserves.iter().sum::<u64>());
+    assert_eq!(
+        apy.fees_24h,
+        pool.lifetime_fees_per_token.iter().sum::<u128>()
+    );
+    assert!(apy.fees_24h > 0 && apy.apy_24h_bps > 0);
+    assert_eq!(apy.fees_7d, apy.fees_24h);
+}
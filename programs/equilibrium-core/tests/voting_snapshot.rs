@@ -0,0 +1,213 @@
+//! Checkpointing LP voting weight at a governance snapshot slot
+
+mod common;
+
+use anchor_lang::{InstructionData, ToAccountMetas};
+use common::{assert_program_error, create_ata, create_mint, mint_to, SeedPoolEnv};
+use equilibrium_core::errors::ErrorCode;
+use equilibrium_core::{VotingSnapshot, MAX_VE_LOCK_DURATION};
+use solana_sdk::{
+    clock::Clock, instruction::Instruction, pubkey::Pubkey, signature::Keypair, signer::Signer,
+    system_program,
+};
+
+const RESERVE: u64 = 1_000_000_000;
+
+fn vote_escrow(env: &SeedPoolEnv) -> Pubkey {
+    Pubkey::find_program_address(
+        &[b"vote-escrow", env.amm_config.as_ref(), env.user().as_ref()],
+        &equilibrium_core::id(),
+    )
+    .0
+}
+
+fn checkpoint_ix(
+    env: &SeedPoolEnv,
+    snapshot_slot: u64,
+    vote_escrow: Option<Pubkey>,
+) -> (Instruction, Pubkey) {
+    let user = env.user();
+    let voting_snapshot = Pubkey::find_program_address(
+        &[
+            b"voting-snapshot",
+            user.as_ref(),
+            env.pool.as_ref(),
+            &snapshot_slot.to_le_bytes(),
+        ],
+        &equilibrium_core::id(),
+    )
+    .0;
+    let ix = Instruction {
+        program_id: equilibrium_core::id(),
+        accounts: equilibrium_core::accounts::CheckpointVotingPower {
+            owner: user,
+            pool: env.pool,
+            amm_config: env.amm_config,
+            user_position: env.user_position,
+            user_lp_token: Some(env.user_lp_token),
+            vote_escrow,
+            voting_snapshot,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: equilibrium_core::instruction::CheckpointVotingPower { snapshot_slot }.data(),
+    };
+    (ix, voting_snapshot)
+}
+
+async fn current_slot(env: &mut SeedPoolEnv) -> u64 {
+    let clock: Clock = env.context.banks_client.get_sysvar().await.unwrap();
+    clock.slot
+}
+
+/// Lock `amount` of the config's governance token for the maximum duration
+fn lock_ix(
+    env: &SeedPoolEnv,
+    ve_mint: Pubkey,
+    owner_token: Pubkey,
+    amount: u64,
+    now: i64,
+) -> Instruction {
+    let escrow = vote_escrow(env);
+    Instruction {
+        program_id: equilibrium_core::id(),
+        accounts: equilibrium_core::accounts::LockVeTokens {
+            owner: env.user(),
+            amm_config: env.amm_config,
+            ve_mint,
+            vote_escrow: escrow,
+            vault: Pubkey::find_program_address(
+                &[b"vote-escrow-vault", escrow.as_ref()],
+                &equilibrium_core::id(),
+            )
+            .0,
+            owner_token,
+            token_program: spl_token::ID,
+            system_program: system_program::ID,
+            rent: solana_sdk::sysvar::rent::ID,
+        }
+        .to_account_metas(None),
+        data: equilibrium_core::instruction::LockVeTokens {
+            amount,
+            unlock_ts: now + MAX_VE_LOCK_DURATION,
+        }
+        .data(),
+    }
+}
+
+#[tokio::test]
+#[ignore = "needs the SBF program from `anchor build`"]
+async fn checkpoint_records_lp_held_since_before_the_snapshot() {
+    common::require_program();
+
+    let mut env = SeedPoolEnv::new([RESERVE; 3]).await;
+    let deposit = env.deposit_ix([RESERVE / 10; 3], 0);
+    env.process(&[deposit]).await.unwrap();
+    let position = env.position_state().await;
+
+    // A snapshot in the future cannot be attested yet
+    env.advance_slots(10).await;
+    let snapshot_slot = current_slot(&mut env).await;
+    let (checkpoint, _) = checkpoint_ix(&env, snapshot_slot + 100, None);
+    assert_program_error(
+        env.process(&[checkpoint]).await,
+        ErrorCode::SnapshotSlotNotReached,
+    );
+
+    let (checkpoint, voting_snapshot) = checkpoint_ix(&env, snapshot_slot, None);
+    env.process(&[checkpoint]).await.unwrap();
+    let snapshot = env.account_state::<VotingSnapshot>(voting_snapshot).await;
+    assert_eq!(snapshot.snapshot_slot, snapshot_slot);
+    assert_eq!(snapshot.lp_amount, position.lp_amount);
+    assert_eq!(snapshot.lock_weight, 0);
+    assert_eq!(snapshot.voting_power(), position.lp_amount);
+
+    // LP added at the snapshot slot, as a flash loan would, disqualifies the position
+    env.advance_slots(10).await;
+    let snapshot_slot = current_slot(&mut env).await;
+    let deposit = env.deposit_ix([RESERVE / 10; 3], 0);
+    env.process(&[deposit]).await.unwrap();
+    let (checkpoint, _) = checkpoint_ix(&env, snapshot_slot, None);
+    assert_program_error(
+        env.process(&[checkpoint]).await,
+        ErrorCode::PositionChangedSinceSnapshot,
+    );
+}
+
+#[tokio::test]
+#[ignore = "needs the SBF program from `anchor build`"]
+async fn checkpoint_caps_lp_at_what_the_wallet_still_holds() {
+    common::require_program();
+
+    let mut env = SeedPoolEnv::new([RESERVE; 3]).await;
+    let deposit = env.deposit_ix([RESERVE / 10; 3], 0);
+    env.process(&[deposit]).await.unwrap();
+    let position = env.position_state().await;
+
+    // All but half the position's LP leaves the wallet by plain transfer, which
+    // leaves the position as it was
+    let recipient = Keypair::new().pubkey();
+    let recipient_lp = create_ata(&mut env.context, &env.lp_mint, &recipient).await;
+    let moved = env.token_balance(env.user_lp_token).await - position.lp_amount / 2;
+    let transfer = spl_token::instruction::transfer(
+        &spl_token::ID,
+        &env.user_lp_token,
+        &recipient_lp,
+        &env.user(),
+        &[],
+        moved,
+    )
+    .unwrap();
+    env.process(&[transfer]).await.unwrap();
+    assert_eq!(env.position_state().await.lp_amount, position.lp_amount);
+
+    env.advance_slots(10).await;
+    let snapshot_slot = current_slot(&mut env).await;
+    let (checkpoint, voting_snapshot) = checkpoint_ix(&env, snapshot_slot, None);
+    env.process(&[checkpoint]).await.unwrap();
+    let snapshot = env.account_state::<VotingSnapshot>(voting_snapshot).await;
+    assert_eq!(snapshot.lp_amount, position.lp_amount / 2);
+    assert_eq!(snapshot.voting_power(), position.lp_amount / 2);
+}
+
+#[tokio::test]
+#[ignore = "needs the SBF program from `anchor build`"]
+async fn checkpoint_ignores_a_lock_topped_up_at_the_snapshot() {
+    common::require_program();
+
+    let mut env = SeedPoolEnv::new([RESERVE; 3]).await;
+    let user = env.user();
+    let ve_mint = create_mint(&mut env.context).await;
+    let owner_token = create_ata(&mut env.context, &ve_mint, &user).await;
+    mint_to(&mut env.context, &ve_mint, &owner_token, 2 * RESERVE).await;
+    let now = env
+        .context
+        .banks_client
+        .get_sysvar::<Clock>()
+        .await
+        .unwrap()
+        .unix_timestamp;
+    let set_ve_mint = env.update_config_ix(equilibrium_core::instruction::SetVeMint { ve_mint });
+    let lock = lock_ix(&env, ve_mint, owner_token, RESERVE, now);
+    env.process(&[set_ve_mint, lock]).await.unwrap();
+
+    // Tokens locked at the snapshot slot were not locked at it
+    env.advance_slots(10).await;
+    let snapshot_slot = current_slot(&mut env).await;
+    let top_up = lock_ix(&env, ve_mint, owner_token, RESERVE, now);
+    env.process(&[top_up]).await.unwrap();
+    let (checkpoint, _) = checkpoint_ix(&env, snapshot_slot, Some(vote_escrow(&env)));
+    assert_program_error(
+        env.process(&[checkpoint]).await,
+        ErrorCode::VoteEscrowChangedSinceSnapshot,
+    );
+
+    // A later snapshot counts the whole lock
+    env.advance_slots(10).await;
+    let snapshot_slot = current_slot(&mut env).await;
+    let (checkpoint, voting_snapshot) = checkpoint_ix(&env, snapshot_slot, Some(vote_escrow(&env)));
+    env.process(&[checkpoint]).await.unwrap();
+    let snapshot = env.account_state::<VotingSnapshot>(voting_snapshot).await;
+    assert!(snapshot.lock_weight > RESERVE);
+    assert!(snapshot.lock_weight <= 2 * RESERVE);
+}
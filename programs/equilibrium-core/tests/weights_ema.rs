@@ -0,0 +1,97 @@
+//! Dynamic fee priced on an exponential moving average of the pool weights
+
+mod common;
+
+use anchor_lang::AnchorDeserialize;
+use common::{assert_program_error, SeedPoolEnv};
+use equilibrium_core::errors::ErrorCode;
+use equilibrium_core::events::SwapExecuted;
+use equilibrium_core::instruction::{GetPoolState, UpdatePoolParams};
+use equilibrium_core::state::math::{
+    calculate_dynamic_fee, calculate_fee_amount, calculate_weights, ema_retention_bps,
+};
+use equilibrium_core::{PoolParamChange, PoolStateView};
+
+const RESERVE: u64 = 1_000_000_000;
+
+#[tokio::test]
+#[ignore = "needs the SBF program from `anchor build`"]
+async fn large_swaps_only_move_the_fee_weights_as_the_slots_go_by() {
+    common::require_program();
+
+    let mut env = SeedPoolEnv::new([RESERVE; 3]).await;
+    let too_slow = env.update_pool_ix(UpdatePoolParams {
+        changes: vec![PoolParamChange::WeightsEma { ema_lambda: 10_001 }],
+    });
+    assert_program_error(
+        env.process(&[too_slow]).await,
+        ErrorCode::InvalidInstructionData,
+    );
+
+    // Turning the average on starts it from the live weights
+    let ema = env.update_pool_ix(UpdatePoolParams {
+        changes: vec![PoolParamChange::WeightsEma { ema_lambda: 1_000 }],
+    });
+    env.process(&[ema]).await.unwrap();
+    let pool = env.pool_state().await;
+    let weights_before = calculate_weights(&pool.reserves);
+    assert_eq!(pool.ema_lambda, 1_000);
+    assert_eq!(pool.weights_ema[..3], weights_before[..]);
+
+    // A large swap leaves the average alone until its weights have held for a slot
+    env.process(&[env.swap_ix(2, 0, RESERVE / 4, 0)])
+        .await
+        .unwrap();
+    let pool = env.pool_state().await;
+    let swap_slot = pool.ema_updated_slot;
+    let weights_after = calculate_weights(&pool.reserves);
+    assert_eq!(pool.weights_ema[..3], weights_before[..]);
+
+    // The next deposit folds them in, decayed by the slots they held
+    env.advance_slots(1).await;
+    env.process(&[env.deposit_ix([RESERVE / 1_000; 3], 0)])
+        .await
+        .unwrap();
+    let pool = env.pool_state().await;
+    let retention = ema_retention_bps(1_000, pool.ema_updated_slot - swap_slot);
+    assert!(retention <= 9_000);
+    for i in 0..3 {
+        assert_eq!(
+            pool.weights_ema[i],
+            (weights_before[i] * retention + weights_after[i] * (10_000 - retention)) / 10_000
+        );
+    }
+
+    // The next trader is quoted on the average, not the post-swap weights
+    let view = env.simulate(&[env.view_ix(GetPoolState {})]).await;
+    let view = PoolStateView::try_from_slice(&view).unwrap();
+    assert_eq!(
+        view.swap_fee,
+        calculate_dynamic_fee(
+            &pool.weights_ema[..3],
+            &pool.target_weights,
+            &pool.fee_params.into()
+        )
+    );
+    assert!(
+        view.swap_fee
+            < calculate_dynamic_fee(
+                &weights_after,
+                &pool.target_weights,
+                &pool.fee_params.into()
+            )
+    );
+
+    // Pushing the pool further off target pays at least the live fee
+    let live_fee = calculate_dynamic_fee(
+        &calculate_weights(&pool.reserves),
+        &pool.target_weights,
+        &pool.fee_params.into(),
+    );
+    assert!(live_fee > view.swap_fee);
+    let events = env
+        .process_events::<SwapExecuted>(&[env.swap_ix(2, 0, RESERVE / 100, 0)])
+        .await
+        .unwrap();
+    assert!(events[0].fee_amount >= calculate_fee_amount(RESERVE / 100, live_fee));
+}
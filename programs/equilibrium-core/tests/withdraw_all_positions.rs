@@ -0,0 +1,199 @@
+//! Unwinding positions across several pools in one instruction
+
+mod common;
+
+use anchor_lang::{InstructionData, ToAccountMetas};
+use common::{assert_program_error, create_growth_pool, GrowthPool, SeedPoolEnv};
+use equilibrium_core::errors::ErrorCode;
+use equilibrium_core::UserPosition;
+use solana_sdk::{
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+    system_program, sysvar,
+};
+
+const RESERVE: u64 = 1_000_000_000;
+
+fn position(owner: &Pubkey, pool: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[b"user-position", owner.as_ref(), pool.as_ref()],
+        &equilibrium_core::id(),
+    )
+    .0
+}
+
+fn growth_deposit_ix(env: &SeedPoolEnv, growth: &GrowthPool, amounts: [u64; 2]) -> Instruction {
+    let user = env.user();
+    Instruction {
+        program_id: equilibrium_core::id(),
+        accounts: equilibrium_core::accounts::Deposit {
+            user,
+            pool: growth.pool,
+            lp_mint: growth.lp_mint,
+            user_lp_token: growth.user_lp_token,
+            user_token_a: growth.user_tokens[0],
+            user_token_b: growth.user_tokens[1],
+            user_token_c: None,
+            token_mint_a: growth.mints[0],
+            token_mint_b: growth.mints[1],
+            token_mint_c: None,
+            pool_token_a: growth.pool_tokens[0],
+            pool_token_b: growth.pool_tokens[1],
+            pool_token_c: None,
+            user_position: position(&user, &growth.pool),
+            token_program: spl_token::ID,
+            system_program: system_program::ID,
+            rent: sysvar::rent::ID,
+            delegate: None,
+            pool_snapshot: None,
+            credential: None,
+            user_portfolio: None,
+        }
+        .to_account_metas(None),
+        data: equilibrium_core::instruction::Deposit {
+            amounts: [amounts[0], amounts[1], 0],
+            min_lp_amount: 0,
+            concentration: 1000,
+            deadline: i64::MAX,
+        }
+        .data(),
+    }
+}
+
+/// `[pool, lp_mint, user_lp_token, user_position, (pool_token, user_token)..]`
+fn position_set(
+    pool: Pubkey,
+    lp_mint: Pubkey,
+    user_lp_token: Pubkey,
+    user_position: Pubkey,
+    pool_tokens: &[Pubkey],
+    user_tokens: &[Pubkey],
+) -> Vec<AccountMeta> {
+    let mut accounts = vec![
+        AccountMeta::new(pool, false),
+        AccountMeta::new(lp_mint, false),
+        AccountMeta::new(user_lp_token, false),
+        AccountMeta::new(user_position, false),
+    ];
+    for (&pool_token, &user_token) in pool_tokens.iter().zip(user_tokens) {
+        accounts.push(AccountMeta::new(pool_token, false));
+        accounts.push(AccountMeta::new(user_token, false));
+    }
+    accounts
+}
+
+fn withdraw_all_ix(
+    env: &SeedPoolEnv,
+    sets: Vec<Vec<AccountMeta>>,
+    min_amounts: Vec<[u64; 3]>,
+) -> Instruction {
+    let mut accounts = equilibrium_core::accounts::WithdrawAllPositions {
+        user: env.user(),
+        token_program: spl_token::ID,
+        vote_escrow: None,
+        amm_config: None,
+        fee_exemption: None,
+        user_portfolio: None,
+    }
+    .to_account_metas(None);
+    accounts.extend(sets.into_iter().flatten());
+    Instruction {
+        program_id: equilibrium_core::id(),
+        accounts,
+        data: equilibrium_core::instruction::WithdrawAllPositions {
+            min_amounts,
+            deadline: i64::MAX,
+        }
+        .data(),
+    }
+}
+
+fn seed_set(env: &SeedPoolEnv) -> Vec<AccountMeta> {
+    position_set(
+        env.pool,
+        env.lp_mint,
+        env.user_lp_token,
+        env.user_position,
+        &env.pool_tokens,
+        &env.user_tokens,
+    )
+}
+
+fn growth_set(env: &SeedPoolEnv, growth: &GrowthPool) -> Vec<AccountMeta> {
+    position_set(
+        growth.pool,
+        growth.lp_mint,
+        growth.user_lp_token,
+        position(&env.user(), &growth.pool),
+        &growth.pool_tokens,
+        &growth.user_tokens,
+    )
+}
+
+#[tokio::test]
+#[ignore = "needs the SBF program from `anchor build`"]
+async fn withdraws_every_listed_position_in_full() {
+    common::require_program();
+
+    let mut env = SeedPoolEnv::new([RESERVE; 3]).await;
+    let growth = create_growth_pool(&mut env, RESERVE).await;
+    let deposit = env.deposit_ix([100_000_000; 3], 0);
+    env.process(&[deposit]).await.unwrap();
+    let deposit = growth_deposit_ix(&env, &growth, [50_000_000, 50_000_000]);
+    env.process(&[deposit]).await.unwrap();
+
+    let growth_position = position(&env.user(), &growth.pool);
+    let growth_lp = env
+        .account_state::<UserPosition>(growth_position)
+        .await
+        .lp_amount;
+    let growth_lp_before = env.token_balance(growth.user_lp_token).await;
+    let partner_before = env.token_balance(growth.user_tokens[1]).await;
+    let usdt_before = env.token_balance(env.user_tokens[1]).await;
+
+    let withdraw = withdraw_all_ix(
+        &env,
+        vec![seed_set(&env), growth_set(&env, &growth)],
+        vec![[0; 3], [0; 3]],
+    );
+    env.process(&[withdraw]).await.unwrap();
+
+    let seed_position = env.position_state().await;
+    assert_eq!(seed_position.lp_amount, 0);
+    assert!(!seed_position.is_active);
+    let growth_position = env.account_state::<UserPosition>(growth_position).await;
+    assert_eq!(growth_position.lp_amount, 0);
+    assert!(!growth_position.is_active);
+
+    assert_eq!(
+        growth_lp_before - env.token_balance(growth.user_lp_token).await,
+        growth_lp
+    );
+    assert!(env.token_balance(growth.user_tokens[1]).await > partner_before);
+    assert!(env.token_balance(env.user_tokens[1]).await > usdt_before);
+}
+
+#[tokio::test]
+#[ignore = "needs the SBF program from `anchor build`"]
+async fn rejects_unlisted_or_repeated_sets() {
+    common::require_program();
+
+    let mut env = SeedPoolEnv::new([RESERVE; 3]).await;
+    let deposit = env.deposit_ix([100_000_000; 3], 0);
+    env.process(&[deposit]).await.unwrap();
+
+    // Trailing accounts without a matching `min_amounts` entry are refused
+    let withdraw = withdraw_all_ix(&env, vec![seed_set(&env), seed_set(&env)], vec![[0; 3]]);
+    assert_program_error(
+        env.process(&[withdraw]).await,
+        ErrorCode::InvalidInputLength,
+    );
+
+    // The second pass sees the position already emptied
+    let withdraw = withdraw_all_ix(
+        &env,
+        vec![seed_set(&env), seed_set(&env)],
+        vec![[0; 3], [0; 3]],
+    );
+    assert_program_error(env.process(&[withdraw]).await, ErrorCode::PositionNotActive);
+}
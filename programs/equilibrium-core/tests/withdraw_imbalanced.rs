@@ -0,0 +1,86 @@
+//! Withdrawals of exact per-token amounts
+
+mod common;
+
+use anchor_lang::{InstructionData, ToAccountMetas};
+use common::{assert_program_error, SeedPoolEnv};
+use equilibrium_core::errors::ErrorCode;
+use equilibrium_core::PoolSnapshot;
+use solana_sdk::{
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+    system_program,
+};
+
+#[tokio::test]
+#[ignore = "needs the SBF program from `anchor build`"]
+async fn withdraw_imbalanced_pays_exact_amounts() {
+    common::require_program();
+
+    let mut env = SeedPoolEnv::new([1_000_000_000, 1_000_000_000, 1_000_000_000]).await;
+    let deposit = env.deposit_ix([100_000_000, 100_000_000, 100_000_000], 0);
+    env.process(&[deposit]).await.unwrap();
+    let lp_before = env.position_state().await.lp_amount;
+
+    // Pulling the under-weighted token pays the imbalance fee on top of its face value
+    let amounts = [50_000_000, 0, 0];
+    let withdraw = env.withdraw_imbalanced_ix(amounts, 50_000_000);
+    assert_program_error(env.process(&[withdraw]).await, ErrorCode::SlippageExceeded);
+
+    let pool_snapshot = Pubkey::find_program_address(
+        &[b"pool-snapshot", env.pool.as_ref()],
+        &equilibrium_core::id(),
+    )
+    .0;
+    let init = Instruction {
+        program_id: equilibrium_core::id(),
+        accounts: equilibrium_core::accounts::InitPoolSnapshot {
+            payer: env.user(),
+            pool: env.pool,
+            pool_snapshot,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: equilibrium_core::instruction::InitPoolSnapshot {}.data(),
+    };
+    env.process(&[init]).await.unwrap();
+
+    let balance_before = env.token_balance(env.user_tokens[0]).await;
+    let mut withdraw = env.withdraw_imbalanced_ix(amounts, 60_000_000);
+    // The snapshot sits just ahead of the portfolio
+    let slot = withdraw.accounts.len() - 2;
+    withdraw.accounts[slot] = AccountMeta::new(pool_snapshot, false);
+    env.process(&[withdraw]).await.unwrap();
+
+    assert_eq!(
+        env.token_balance(env.user_tokens[0]).await - balance_before,
+        50_000_000
+    );
+    let burned = lp_before - env.position_state().await.lp_amount;
+    assert!(
+        burned > 50_000_000 && burned <= 60_000_000,
+        "burned {burned}"
+    );
+
+    let pool = env.pool_state().await;
+    assert_eq!(pool.reserves[0], 1_050_000_000);
+    let snapshot = env.account_state::<PoolSnapshot>(pool_snapshot).await;
+    assert_eq!(snapshot.reserves[..3], pool.reserves[..]);
+    assert_eq!(snapshot.virtual_price, pool.virtual_price);
+}
+
+#[tokio::test]
+#[ignore = "needs the SBF program from `anchor build`"]
+async fn withdraw_imbalanced_rejects_more_than_the_position() {
+    common::require_program();
+
+    let mut env = SeedPoolEnv::new([1_000_000_000, 1_000_000_000, 1_000_000_000]).await;
+    let deposit = env.deposit_ix([10_000_000, 10_000_000, 10_000_000], 0);
+    env.process(&[deposit]).await.unwrap();
+
+    let withdraw = env.withdraw_imbalanced_ix([50_000_000, 0, 0], u64::MAX);
+    assert_program_error(
+        env.process(&[withdraw]).await,
+        ErrorCode::InsufficientLiquidity,
+    );
+}
@@ -0,0 +1,74 @@
+//! Proportional versus balanced withdrawals, and withdrawing a share of a position
+
+mod common;
+
+use common::{assert_program_error, SeedPoolEnv};
+use equilibrium_core::errors::ErrorCode;
+use equilibrium_core::WithdrawMode;
+
+#[tokio::test]
+#[ignore = "needs the SBF program from `anchor build`"]
+async fn balanced_withdraw_pays_out_over_weighted_tokens_first() {
+    common::require_program();
+
+    // Equal reserves leave the 20% target token over-weighted and the 45% one under-weighted
+    let mut env = SeedPoolEnv::new([1_000_000_000, 1_000_000_000, 1_000_000_000]).await;
+    let deposit = env.deposit_ix([100_000_000, 100_000_000, 100_000_000], 0);
+    env.process(&[deposit]).await.unwrap();
+
+    let before = [
+        env.token_balance(env.user_tokens[0]).await,
+        env.token_balance(env.user_tokens[2]).await,
+    ];
+    let lp_amount = env.position_state().await.lp_amount;
+    let withdraw = env.withdraw_ix(lp_amount, [0, 0, 0], WithdrawMode::Balanced);
+    env.process(&[withdraw]).await.unwrap();
+    let received = [
+        env.token_balance(env.user_tokens[0]).await - before[0],
+        env.token_balance(env.user_tokens[2]).await - before[1],
+    ];
+
+    assert!(
+        received[1] > received[0],
+        "expected more of the over-weighted token, got {received:?}"
+    );
+
+    let pool = env.pool_state().await;
+    assert!(pool.reserves[2] < pool.reserves[0]);
+}
+
+#[tokio::test]
+#[ignore = "needs the SBF program from `anchor build`"]
+async fn percent_withdraw_sizes_from_the_position_at_execution() {
+    common::require_program();
+
+    let mut env = SeedPoolEnv::new([1_000_000_000, 1_000_000_000, 1_000_000_000]).await;
+    let deposit = env.deposit_ix([100_000_000, 100_000_000, 100_000_000], 0);
+    env.process(&[deposit]).await.unwrap();
+
+    // The position grows between quoting and executing; the percentage tracks it
+    let withdraw_half = env.withdraw_percent_ix(5_000, WithdrawMode::Proportional);
+    let top_up = env.deposit_ix([10_000_000, 10_000_000, 10_000_000], 0);
+    env.process(&[top_up]).await.unwrap();
+    let lp_before = env.position_state().await.lp_amount;
+    env.process(&[withdraw_half]).await.unwrap();
+    assert_eq!(
+        env.position_state().await.lp_amount,
+        lp_before - lp_before / 2
+    );
+
+    // 100% always exits in full, leaving no dust behind
+    let withdraw_all = env.withdraw_percent_ix(10_000, WithdrawMode::Balanced);
+    env.process(&[withdraw_all]).await.unwrap();
+    let position = env.position_state().await;
+    assert_eq!(position.lp_amount, 0);
+    assert!(!position.is_active);
+
+    for bps in [0, 10_001] {
+        let withdraw = env.withdraw_percent_ix(bps, WithdrawMode::Proportional);
+        assert_program_error(
+            env.process(&[withdraw]).await,
+            ErrorCode::InvalidInstructionData,
+        );
+    }
+}
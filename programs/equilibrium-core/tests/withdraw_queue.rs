@@ -0,0 +1,170 @@
+//! Withdrawals queued while a pool vault holds less than its recorded reserve
+
+mod common;
+
+use anchor_lang::{InstructionData, ToAccountMetas};
+use common::{assert_program_error, SeedPoolEnv};
+use equilibrium_core::errors::ErrorCode;
+use equilibrium_core::events::WithdrawQueued;
+use equilibrium_core::{WithdrawMode, WithdrawQueue, WithdrawRequest};
+use solana_sdk::{instruction::Instruction, pubkey::Pubkey, system_program};
+
+const RESERVE: u64 = 1_000_000_000;
+const SHORTFALL: u64 = 100_000_000;
+
+fn pda(seeds: &[&[u8]]) -> Pubkey {
+    Pubkey::find_program_address(seeds, &equilibrium_core::id()).0
+}
+
+fn queue_address(env: &SeedPoolEnv) -> Pubkey {
+    pda(&[b"withdraw-queue", env.pool.as_ref()])
+}
+
+fn request_address(env: &SeedPoolEnv, seq: u64) -> Pubkey {
+    pda(&[b"withdraw-request", env.pool.as_ref(), &seq.to_le_bytes()])
+}
+
+fn queue_ix(env: &SeedPoolEnv, seq: u64, lp_amount: u64) -> Instruction {
+    Instruction {
+        program_id: equilibrium_core::id(),
+        accounts: equilibrium_core::accounts::QueueWithdraw {
+            user: env.user(),
+            pool: env.pool,
+            lp_mint: env.lp_mint,
+            user_lp_token: env.user_lp_token,
+            user_position: env.user_position,
+            pool_token_a: env.pool_tokens[0],
+            pool_token_b: env.pool_tokens[1],
+            pool_token_c: Some(env.pool_tokens[2]),
+            withdraw_queue: queue_address(env),
+            withdraw_request: request_address(env, seq),
+            token_program: spl_token::ID,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: equilibrium_core::instruction::QueueWithdraw {
+            lp_amount,
+            min_amounts: [0; 3],
+            deadline: i64::MAX,
+        }
+        .data(),
+    }
+}
+
+fn fulfill_ix(env: &SeedPoolEnv, seq: u64) -> Instruction {
+    Instruction {
+        program_id: equilibrium_core::id(),
+        accounts: equilibrium_core::accounts::FulfillWithdrawRequest {
+            keeper: env.user(),
+            pool: env.pool,
+            withdraw_queue: queue_address(env),
+            withdraw_request: request_address(env, seq),
+            owner: env.user(),
+            pool_token_a: env.pool_tokens[0],
+            pool_token_b: env.pool_tokens[1],
+            pool_token_c: Some(env.pool_tokens[2]),
+            owner_token_a: env.user_tokens[0],
+            owner_token_b: env.user_tokens[1],
+            owner_token_c: Some(env.user_tokens[2]),
+            token_program: spl_token::ID,
+        }
+        .to_account_metas(None),
+        data: equilibrium_core::instruction::FulfillWithdrawRequest {}.data(),
+    }
+}
+
+#[tokio::test]
+#[ignore = "needs the SBF program from `anchor build`"]
+async fn short_vault_queues_withdrawals_and_pays_them_in_order() {
+    common::require_program();
+
+    let mut env = SeedPoolEnv::new([RESERVE; 3]).await;
+    let lp_amount = env.position_state().await.lp_amount / 10;
+
+    // A healthy pool is withdrawn from directly
+    assert_program_error(
+        env.process(&[queue_ix(&env, 0, lp_amount)]).await,
+        ErrorCode::VaultsBacked,
+    );
+
+    // With part of token 0 out of its vault, a plain withdrawal names the problem
+    let vault = env.pool_tokens[0];
+    let vault_balance = env.token_balance(vault).await;
+    env.set_token_balance(vault, vault_balance - SHORTFALL)
+        .await;
+    assert_program_error(
+        env.process(&[env.withdraw_ix(lp_amount, [0; 3], WithdrawMode::Proportional)])
+            .await,
+        ErrorCode::VaultShortfall,
+    );
+
+    // Queued withdrawals are priced and booked now
+    let reserves_before = env.pool_state().await.reserves;
+    let events = env
+        .process_events::<WithdrawQueued>(&[queue_ix(&env, 0, lp_amount)])
+        .await
+        .unwrap();
+    assert_eq!(events[0].seq, 0);
+    assert_eq!(events[0].lp_amount, lp_amount);
+    let reserves_after = env.pool_state().await.reserves;
+    for i in 0..3 {
+        assert_eq!(reserves_after[i], reserves_before[i] - events[0].amounts[i]);
+    }
+    env.process(&[queue_ix(&env, 1, lp_amount)]).await.unwrap();
+    let queue: WithdrawQueue = env.account_state(queue_address(&env)).await;
+    assert_eq!((queue.head, queue.tail), (0, 2));
+
+    // Requests are paid in order, and only once the vault holds them again
+    assert_program_error(
+        env.process(&[fulfill_ix(&env, 1)]).await,
+        ErrorCode::NotNextInQueue,
+    );
+    assert_program_error(
+        env.process(&[fulfill_ix(&env, 0)]).await,
+        ErrorCode::VaultShortfall,
+    );
+
+    // The queued tokens are owed even while the reserves alone are covered, so a
+    // plain withdrawal cannot spend them
+    let mut queued = 0;
+    for seq in 0..2 {
+        let request: WithdrawRequest = env.account_state(request_address(&env, seq)).await;
+        queued += request.amounts[0];
+    }
+    assert_eq!(env.pool_state().await.queued_owed[0], queued);
+    env.set_token_balance(vault, vault_balance - 1).await;
+    assert_program_error(
+        env.process(&[env.withdraw_ix(lp_amount, [0; 3], WithdrawMode::Proportional)])
+            .await,
+        ErrorCode::VaultShortfall,
+    );
+
+    // Once the vault holds them, withdrawals go on beside the queue
+    env.set_token_balance(vault, vault_balance).await;
+    env.process(&[env.withdraw_ix(lp_amount, [0; 3], WithdrawMode::Proportional)])
+        .await
+        .unwrap();
+    let request: WithdrawRequest = env.account_state(request_address(&env, 0)).await;
+    let before = env.token_balance(env.user_tokens[0]).await;
+    env.process(&[fulfill_ix(&env, 0)]).await.unwrap();
+    assert_eq!(
+        env.token_balance(env.user_tokens[0]).await - before,
+        request.amounts[0]
+    );
+    env.process(&[fulfill_ix(&env, 1)]).await.unwrap();
+    let queue: WithdrawQueue = env.account_state(queue_address(&env)).await;
+    assert_eq!((queue.head, queue.tail), (2, 2));
+    assert_eq!(env.pool_state().await.queued_owed, [0; 3]);
+
+    // Paid requests are closed, and the backed pool withdraws directly again
+    assert!(env
+        .context
+        .banks_client
+        .get_account(request_address(&env, 0))
+        .await
+        .unwrap()
+        .is_none());
+    env.process(&[env.withdraw_ix(lp_amount, [0; 3], WithdrawMode::Proportional)])
+        .await
+        .unwrap();
+}
@@ -71,6 +71,10 @@ describe("equilibrium-core", () => {
   const INITIAL_MINT_AMOUNT = 10_000_000_000; // 10,000 tokens with 6 decimals
   const DEFAULT_AMPLIFICATION = 200;
   const DEFAULT_WEIGHTS = [4500, 3500, 2000]; // 45% USDC, 35% USDT, 20% PYUSD
+  const CONFIG_INDEX = 0;
+
+  // Transactions must land within a minute of being built
+  const deadline = () => new anchor.BN(Math.floor(Date.now() / 1000) + 60);
 
   // Initialize test tokens and accounts
   before(async () => {
@@ -199,7 +203,10 @@ describe("equilibrium-core", () => {
     // Find AMM config PDA
     const [ammConfigPda, ammConfigPdaBump] =
       await PublicKey.findProgramAddressSync(
-        [Buffer.from("amm-config")],
+        [
+          Buffer.from("amm-config"),
+          new anchor.BN(CONFIG_INDEX).toArrayLike(Buffer, "le", 2),
+        ],
         program.programId
       );
     ammConfig = ammConfigPda;
@@ -209,7 +216,7 @@ describe("equilibrium-core", () => {
     // Find Seed Pool PDA
     const [seedPoolPda, seedPoolPdaBump] =
       await PublicKey.findProgramAddressSync(
-        [Buffer.from("pool"), Buffer.from("seed")],
+        [Buffer.from("pool"), ammConfig.toBuffer(), Buffer.from("seed")],
         program.programId
       );
     seedPool = seedPoolPda;
@@ -265,6 +272,7 @@ describe("equilibrium-core", () => {
       await PublicKey.findProgramAddressSync(
         [
           Buffer.from("pool"),
+          ammConfig.toBuffer(),
           Buffer.from("growth"),
           partnerTokenMint.toBuffer(),
         ],
@@ -325,6 +333,7 @@ describe("equilibrium-core", () => {
 
     await program.methods
       .initialize(
+        CONFIG_INDEX,
         new anchor.BN(DEFAULT_AMPLIFICATION),
         DEFAULT_WEIGHTS.map((w) => new anchor.BN(w))
       )
@@ -447,7 +456,8 @@ describe("equilibrium-core", () => {
       .deposit(
         additionalAmounts.map((a) => new anchor.BN(a)),
         new anchor.BN(minLpAmount),
-        new anchor.BN(concentration)
+        new anchor.BN(concentration),
+        deadline()
       )
       .accounts({
         user: wallet.publicKey,
@@ -514,7 +524,10 @@ describe("equilibrium-core", () => {
       .createGrowthPool(
         new anchor.BN(DEFAULT_AMPLIFICATION),
         new anchor.BN(initialUsdcStarAmount),
-        new anchor.BN(initialPartnerAmount)
+        new anchor.BN(initialPartnerAmount),
+        { stable: {} },
+        false,
+        [new anchor.BN(5000), new anchor.BN(5000)]
       )
       .accounts({
         payer: wallet.publicKey,
@@ -578,7 +591,13 @@ describe("equilibrium-core", () => {
     const minAmountOut = 190_000; // Expect at least 0.19 USDT (accounting for fees)
 
     await program.methods
-      .swap(new anchor.BN(amountIn), new anchor.BN(minAmountOut))
+      .swap(
+        new anchor.BN(amountIn),
+        new anchor.BN(minAmountOut),
+        deadline(),
+        new anchor.BN(0),
+        null
+      )
       .accounts({
         user: wallet.publicKey,
         pool: seedPool,
@@ -605,6 +624,15 @@ describe("equilibrium-core", () => {
     ); // USDC increased
     expect(seedPoolAccount.reserves[1].toNumber()).to.be.lessThan(1_500_000); // USDT decreased
 
+    // Verify swap statistics were recorded
+    expect(seedPoolAccount.swapCount.toNumber()).to.equal(1);
+    expect(seedPoolAccount.lifetimeVolumePerToken[0].toNumber()).to.equal(
+      amountIn
+    );
+    expect(seedPoolAccount.lifetimeFeesPerToken[0].toNumber()).to.be.greaterThan(
+      0
+    );
+
     console.log("Swap from USDC to USDT completed successfully");
   });
 
@@ -622,7 +650,13 @@ describe("equilibrium-core", () => {
 
     // Step 1: Swap Partner Token for USD* in Growth Pool
     await program.methods
-      .swap(new anchor.BN(partnerAmountIn), new anchor.BN(minUsdcStarAmountOut))
+      .swap(
+        new anchor.BN(partnerAmountIn),
+        new anchor.BN(minUsdcStarAmountOut),
+        deadline(),
+        new anchor.BN(0),
+        null
+      )
       .accounts({
         user: wallet.publicKey,
         pool: growthPool,
@@ -660,10 +694,13 @@ describe("equilibrium-core", () => {
     await program.methods
       .withdraw(
         new anchor.BN(usdcStarToWithdraw),
-        minAmountsOut.map((a) => new anchor.BN(a))
+        minAmountsOut.map((a) => new anchor.BN(a)),
+        { proportional: {} },
+        deadline()
       )
       .accounts({
         user: wallet.publicKey,
+        ammConfig: ammConfig,
         pool: seedPool,
         lpMint: seedPoolLpMint,
         userLpToken: userSeedPoolLpAccount,
@@ -729,10 +766,13 @@ describe("equilibrium-core", () => {
     await program.methods
       .withdraw(
         new anchor.BN(lpAmountToWithdraw),
-        minAmountsOut.map((a) => new anchor.BN(a))
+        minAmountsOut.map((a) => new anchor.BN(a)),
+        { proportional: {} },
+        deadline()
       )
       .accounts({
         user: wallet.publicKey,
+        ammConfig: ammConfig,
         pool: seedPool,
         lpMint: seedPoolLpMint,
         userLpToken: userSeedPoolLpAccount,
@@ -789,7 +829,7 @@ describe("equilibrium-core", () => {
     console.log("Withdrawing liquidity from Growth Pool...");
 
     // First, we need to create a position by depositing some liquidity
-    const depositAmounts = [50_000, 50_000]; // 0.05 USD* and 0.05 Partner tokens
+    const depositAmounts = [50_000, 50_000, 0]; // 0.05 USD* and 0.05 Partner tokens, unused third slot
     const minLpAmount = 50_000; // Expect at least 0.05 LP tokens
     const concentration = 1000; // Concentration factor (1000 = 1.0)
 
@@ -797,7 +837,8 @@ describe("equilibrium-core", () => {
       .deposit(
         depositAmounts.map((a) => new anchor.BN(a)),
         new anchor.BN(minLpAmount),
-        new anchor.BN(concentration)
+        new anchor.BN(concentration),
+        deadline()
       )
       .accounts({
         user: wallet.publicKey,
@@ -842,15 +883,18 @@ describe("equilibrium-core", () => {
     const lpAmountToWithdraw = userPositionBefore.lpAmount.toNumber() / 2; // Withdraw half
 
     // Minimum amounts to receive
-    const minAmountsOut = [20_000, 20_000]; // Minimum 0.02 of each token
+    const minAmountsOut = [20_000, 20_000, 0]; // Minimum 0.02 of each token, unused third slot
 
     await program.methods
       .withdraw(
         new anchor.BN(lpAmountToWithdraw),
-        minAmountsOut.map((a) => new anchor.BN(a))
+        minAmountsOut.map((a) => new anchor.BN(a)),
+        { proportional: {} },
+        deadline()
       )
       .accounts({
         user: wallet.publicKey,
+        ammConfig: ammConfig,
         pool: growthPool,
         lpMint: growthPoolLpMint,
         userLpToken: userGrowthPoolLpAccount,
@@ -0,0 +1,413 @@
+//! Bootstrap a local validator with Equilibrium-Core state for integration work
+//!
+//! Against a validator that already runs the program, this creates three stablecoin
+//! mints, initializes the AMM config, creates the Seed Pool and a Growth Pool, funds
+//! test wallets and writes a JSON manifest of every address involved:
+//!
+//! ```text
+//! solana-test-validator --reset \
+//!     --bpf-program 1uu1R8otFuC235hhTstPWVUwsuZ1z5cLoKYd1biVv8Y target/deploy/equilibrium_core.so
+//! cargo run -p fixture-gen -- --wallets 3 --out fixtures/localnet.json
+//! ```
+//!
+//! The payer keypair becomes the config authority and keeper. The Growth Pool uses
+//! the Seed Pool LP token as USD*, as the integration suites do.
+
+use anchor_lang::{InstructionData, ToAccountMetas};
+use serde_json::{json, Value};
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::{
+    commitment_config::CommitmentConfig,
+    compute_budget::ComputeBudgetInstruction,
+    instruction::Instruction,
+    native_token::LAMPORTS_PER_SOL,
+    program_pack::Pack,
+    pubkey::Pubkey,
+    signature::{read_keypair_file, Keypair, Signer},
+    system_instruction, system_program, sysvar,
+    transaction::Transaction,
+};
+use std::error::Error;
+use std::path::PathBuf;
+
+type Result<T> = std::result::Result<T, Box<dyn Error>>;
+
+const DECIMALS: u8 = 6;
+const ONE: u64 = 10u64.pow(DECIMALS as u32);
+
+/// Stablecoins of the Seed Pool, in pool order
+const SEED_TOKENS: [&str; 3] = ["FAKE_USDC", "FAKE_USDT", "FAKE_PYUSD"];
+const PARTNER_TOKEN: &str = "FAKE_PARTNER";
+
+const SEED_AMPLIFICATION: u64 = 200;
+const SEED_WEIGHTS: [u64; 3] = [4500, 3500, 2000];
+const SEED_RESERVE: u64 = 1_000_000 * ONE;
+const GROWTH_AMPLIFICATION: u64 = 100;
+const GROWTH_RESERVE: u64 = 100_000 * ONE;
+
+/// Minted to the payer on top of the pool reserves, so it can keep trading
+const PAYER_BUFFER: u64 = 1_000_000 * ONE;
+const WALLET_TOKENS: u64 = 100_000 * ONE;
+const WALLET_USD_STAR: u64 = 10_000 * ONE;
+const WALLET_LAMPORTS: u64 = 10 * LAMPORTS_PER_SOL;
+
+const USAGE: &str =
+    "usage: fixture-gen [--url URL] [--keypair PATH] [--config-index N] [--wallets N] [--out PATH]";
+
+struct Args {
+    url: String,
+    keypair: PathBuf,
+    /// Config namespace to bootstrap, so several fixtures can share one validator
+    config_index: u16,
+    wallets: usize,
+    out: PathBuf,
+}
+
+impl Args {
+    fn parse() -> Result<Self> {
+        let home = std::env::var("HOME").unwrap_or_default();
+        let mut args = Self {
+            url: "http://127.0.0.1:8899".to_string(),
+            keypair: PathBuf::from(home).join(".config/solana/id.json"),
+            config_index: 0,
+            wallets: 3,
+            out: PathBuf::from("fixtures/localnet.json"),
+        };
+
+        let mut argv = std::env::args().skip(1);
+        while let Some(flag) = argv.next() {
+            if flag == "--help" || flag == "-h" {
+                println!("{USAGE}");
+                std::process::exit(0);
+            }
+            let value = argv
+                .next()
+                .ok_or_else(|| format!("{flag} needs a value\n{USAGE}"))?;
+            match flag.as_str() {
+                "--url" => args.url = value,
+                "--keypair" => args.keypair = PathBuf::from(value),
+                "--config-index" => args.config_index = value.parse()?,
+                "--wallets" => args.wallets = value.parse()?,
+                "--out" => args.out = PathBuf::from(value),
+                _ => return Err(format!("unknown flag {flag}\n{USAGE}").into()),
+            }
+        }
+        Ok(args)
+    }
+}
+
+/// RPC client plus the keypair paying for and signing every setup transaction
+struct Fixture {
+    client: RpcClient,
+    payer: Keypair,
+}
+
+impl Fixture {
+    fn send(&self, instructions: &[Instruction], signers: &[&Keypair]) -> Result<()> {
+        let mut all = vec![ComputeBudgetInstruction::set_compute_unit_limit(1_400_000)];
+        all.extend_from_slice(instructions);
+
+        let mut all_signers = vec![&self.payer];
+        all_signers.extend_from_slice(signers);
+        let transaction = Transaction::new_signed_with_payer(
+            &all,
+            Some(&self.payer.pubkey()),
+            &all_signers,
+            self.client.get_latest_blockhash()?,
+        );
+        self.client.send_and_confirm_transaction(&transaction)?;
+        Ok(())
+    }
+
+    fn create_mint(&self) -> Result<Pubkey> {
+        let mint = Keypair::new();
+        let payer = self.payer.pubkey();
+        let rent = self
+            .client
+            .get_minimum_balance_for_rent_exemption(spl_token::state::Mint::LEN)?;
+        self.send(
+            &[
+                system_instruction::create_account(
+                    &payer,
+                    &mint.pubkey(),
+                    rent,
+                    spl_token::state::Mint::LEN as u64,
+                    &spl_token::ID,
+                ),
+                spl_token::instruction::initialize_mint2(
+                    &spl_token::ID,
+                    &mint.pubkey(),
+                    &payer,
+                    None,
+                    DECIMALS,
+                )?,
+            ],
+            &[&mint],
+        )?;
+        Ok(mint.pubkey())
+    }
+
+    fn create_ata(&self, mint: &Pubkey, owner: &Pubkey) -> Result<Pubkey> {
+        let instruction =
+            spl_associated_token_account::instruction::create_associated_token_account_idempotent(
+                &self.payer.pubkey(),
+                owner,
+                mint,
+                &spl_token::ID,
+            );
+        self.send(&[instruction], &[])?;
+        Ok(spl_associated_token_account::get_associated_token_address(
+            owner, mint,
+        ))
+    }
+
+    fn mint_to(&self, mint: &Pubkey, destination: &Pubkey, amount: u64) -> Result<()> {
+        let instruction = spl_token::instruction::mint_to(
+            &spl_token::ID,
+            mint,
+            destination,
+            &self.payer.pubkey(),
+            &[],
+            amount,
+        )?;
+        self.send(&[instruction], &[])
+    }
+}
+
+fn pda(seeds: &[&[u8]]) -> Pubkey {
+    Pubkey::find_program_address(seeds, &equilibrium_core::id()).0
+}
+
+fn main() -> Result<()> {
+    let args = Args::parse()?;
+    let payer = read_keypair_file(&args.keypair)
+        .map_err(|err| format!("reading {}: {err}", args.keypair.display()))?;
+    let fixture = Fixture {
+        client: RpcClient::new_with_commitment(args.url.clone(), CommitmentConfig::confirmed()),
+        payer,
+    };
+    let program_id = equilibrium_core::id();
+    let authority = fixture.payer.pubkey();
+
+    let program = fixture.client.get_account(&program_id).ok();
+    if !program.is_some_and(|account| account.executable) {
+        return Err(format!("program {program_id} is not deployed at {}", args.url).into());
+    }
+    let amm_config = pda(&[b"amm-config", &args.config_index.to_le_bytes()]);
+    if fixture.client.get_account(&amm_config).is_ok() {
+        return Err(format!(
+            "AMM config {} already exists; pick another --config-index or --reset the validator",
+            args.config_index
+        )
+        .into());
+    }
+
+    // Stablecoins, held by the payer
+    let mut mints = [Pubkey::default(); 3];
+    let mut payer_tokens = [Pubkey::default(); 3];
+    for i in 0..3 {
+        mints[i] = fixture.create_mint()?;
+        payer_tokens[i] = fixture.create_ata(&mints[i], &authority)?;
+        fixture.mint_to(&mints[i], &payer_tokens[i], SEED_RESERVE + PAYER_BUFFER)?;
+    }
+    println!("created mints {mints:?}");
+
+    let initialize = Instruction {
+        program_id,
+        accounts: equilibrium_core::accounts::Initialize {
+            authority,
+            amm_config,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: equilibrium_core::instruction::Initialize {
+            index: args.config_index,
+            default_amplification: SEED_AMPLIFICATION,
+            default_target_weights: SEED_WEIGHTS,
+        }
+        .data(),
+    };
+    fixture.send(&[initialize], &[])?;
+    println!("initialized config {amm_config}");
+
+    // Seed Pool
+    let seed_pool = pda(&[b"pool", amm_config.as_ref(), b"seed"]);
+    let seed_lp_mint = pda(&[b"lp-mint", seed_pool.as_ref()]);
+    let seed_pool_tokens =
+        mints.map(|mint| pda(&[b"pool-token", seed_pool.as_ref(), mint.as_ref()]));
+    let payer_seed_lp =
+        spl_associated_token_account::get_associated_token_address(&authority, &seed_lp_mint);
+    let create_seed_pool = Instruction {
+        program_id,
+        accounts: equilibrium_core::accounts::CreateSeedPool {
+            payer: authority,
+            amm_config,
+            pool: seed_pool,
+            token_mint_a: mints[0],
+            token_mint_b: mints[1],
+            token_mint_c: mints[2],
+            user_token_a: payer_tokens[0],
+            user_token_b: payer_tokens[1],
+            user_token_c: payer_tokens[2],
+            pool_token_a: seed_pool_tokens[0],
+            pool_token_b: seed_pool_tokens[1],
+            pool_token_c: seed_pool_tokens[2],
+            lp_mint: seed_lp_mint,
+            user_lp_token: payer_seed_lp,
+            token_program: spl_token::ID,
+            associated_token_program: spl_associated_token_account::ID,
+            system_program: system_program::ID,
+            rent: sysvar::rent::ID,
+            authority,
+        }
+        .to_account_metas(None),
+        data: equilibrium_core::instruction::CreateSeedPool {
+            amplification: SEED_AMPLIFICATION,
+            target_weights: SEED_WEIGHTS.to_vec(),
+            initial_amounts: vec![SEED_RESERVE; 3],
+        }
+        .data(),
+    };
+    fixture.send(&[create_seed_pool], &[])?;
+    println!("created Seed Pool {seed_pool}");
+
+    // Growth Pool pairing USD* with a partner token
+    let usd_star_mint = seed_lp_mint;
+    let partner_mint = fixture.create_mint()?;
+    let payer_partner = fixture.create_ata(&partner_mint, &authority)?;
+    fixture.mint_to(&partner_mint, &payer_partner, GROWTH_RESERVE + PAYER_BUFFER)?;
+    let growth_pool = pda(&[
+        b"pool",
+        amm_config.as_ref(),
+        b"growth",
+        partner_mint.as_ref(),
+    ]);
+    let growth_lp_mint = pda(&[b"lp-mint", growth_pool.as_ref()]);
+    let growth_pool_tokens = [usd_star_mint, partner_mint]
+        .map(|mint| pda(&[b"pool-token", growth_pool.as_ref(), mint.as_ref()]));
+    let create_growth_pool = Instruction {
+        program_id,
+        accounts: equilibrium_core::accounts::CreateGrowthPool {
+            payer: authority,
+            amm_config,
+            seed_pool,
+            pool: growth_pool,
+            usdc_star_mint: usd_star_mint,
+            partner_token_mint: partner_mint,
+            partner_approval: None,
+            user_usdc_star: payer_seed_lp,
+            user_partner_token: payer_partner,
+            pool_usdc_star: growth_pool_tokens[0],
+            pool_partner_token: growth_pool_tokens[1],
+            lp_mint: growth_lp_mint,
+            user_lp_token: spl_associated_token_account::get_associated_token_address(
+                &authority,
+                &growth_lp_mint,
+            ),
+            token_program: spl_token::ID,
+            associated_token_program: spl_associated_token_account::ID,
+            system_program: system_program::ID,
+            rent: sysvar::rent::ID,
+            authority,
+            fee_recipient: None,
+            fee_recipient_usd_star: None,
+            creator_position: None,
+        }
+        .to_account_metas(None),
+        data: equilibrium_core::instruction::CreateGrowthPool {
+            amplification: GROWTH_AMPLIFICATION,
+            initial_usdc_amount: GROWTH_RESERVE,
+            initial_partner_amount: GROWTH_RESERVE,
+            fee_tier: equilibrium_core::FeeTier::Stable,
+            soulbound_lp: false,
+            target_weights: [5000, 5000],
+        }
+        .data(),
+    };
+    fixture.send(&[create_growth_pool], &[])?;
+    println!("created Growth Pool {growth_pool}");
+
+    // Test wallets holding SOL, every stablecoin, the partner token and some USD*
+    let mut wallets = Vec::with_capacity(args.wallets);
+    for _ in 0..args.wallets {
+        let wallet = Keypair::new();
+        let owner = wallet.pubkey();
+        fixture.send(
+            &[system_instruction::transfer(
+                &authority,
+                &owner,
+                WALLET_LAMPORTS,
+            )],
+            &[],
+        )?;
+
+        let mut token_accounts = serde_json::Map::new();
+        for (name, mint) in SEED_TOKENS
+            .iter()
+            .zip(mints)
+            .chain([(&PARTNER_TOKEN, partner_mint)])
+        {
+            let token_account = fixture.create_ata(&mint, &owner)?;
+            fixture.mint_to(&mint, &token_account, WALLET_TOKENS)?;
+            token_accounts.insert(name.to_string(), json!(token_account.to_string()));
+        }
+        let usd_star = fixture.create_ata(&usd_star_mint, &owner)?;
+        let transfer = spl_token::instruction::transfer(
+            &spl_token::ID,
+            &payer_seed_lp,
+            &usd_star,
+            &authority,
+            &[],
+            WALLET_USD_STAR,
+        )?;
+        fixture.send(&[transfer], &[])?;
+        token_accounts.insert("USD_STAR".to_string(), json!(usd_star.to_string()));
+
+        println!("funded wallet {owner}");
+        wallets.push(json!({
+            "pubkey": owner.to_string(),
+            "keypair": wallet.to_bytes().to_vec(),
+            "token_accounts": token_accounts,
+        }));
+    }
+
+    let addresses = |keys: &[Pubkey]| -> Value { keys.iter().map(|key| key.to_string()).collect() };
+    let manifest = json!({
+        "rpc_url": args.url,
+        "program_id": program_id.to_string(),
+        "authority": authority.to_string(),
+        "config_index": args.config_index,
+        "amm_config": amm_config.to_string(),
+        "mints": SEED_TOKENS
+            .iter()
+            .zip(mints)
+            .map(|(name, mint)| (name.to_string(), json!(mint.to_string())))
+            .chain([(PARTNER_TOKEN.to_string(), json!(partner_mint.to_string()))])
+            .collect::<serde_json::Map<_, _>>(),
+        "decimals": DECIMALS,
+        "seed_pool": {
+            "pool": seed_pool.to_string(),
+            "lp_mint": seed_lp_mint.to_string(),
+            "token_mints": addresses(&mints),
+            "pool_tokens": addresses(&seed_pool_tokens),
+            "amplification": SEED_AMPLIFICATION,
+            "target_weights": SEED_WEIGHTS,
+        },
+        "growth_pool": {
+            "pool": growth_pool.to_string(),
+            "lp_mint": growth_lp_mint.to_string(),
+            "token_mints": addresses(&[usd_star_mint, partner_mint]),
+            "pool_tokens": addresses(&growth_pool_tokens),
+            "amplification": GROWTH_AMPLIFICATION,
+        },
+        "wallets": wallets,
+    });
+
+    if let Some(parent) = args.out.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(&args.out, serde_json::to_string_pretty(&manifest)?)?;
+    println!("wrote {}", args.out.display());
+
+    Ok(())
+}